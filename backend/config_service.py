@@ -10,8 +10,8 @@ from typing import Dict, Any
 
 logger = logging.getLogger(__name__)
 
-# Configuration file path
-CONFIG_DIR = os.path.join(os.path.expanduser("~"), ".serina")
+# Configuration file path; SERINA_CONFIG_DIR points a development copy elsewhere
+CONFIG_DIR = os.environ.get("SERINA_CONFIG_DIR") or os.path.join(os.path.expanduser("~"), ".serina")
 CONFIG_FILE = os.path.join(CONFIG_DIR, "config.json")
 
 # Default configuration
@@ -25,20 +25,57 @@ DEFAULT_CONFIG = {
         "check_interval_minutes": 15,
         "max_emails_per_check": 20
     },
+    "tracking": {
+        "read_receipts": False,  # Recipients see a read-receipt prompt
+        "delivery_receipts": False
+    },
     "notifications": {
         "quiet_hours_start": "22:00",
         "quiet_hours_end": "08:00",
         "show_desktop_notifications": True,
-        "notification_position": "bottom-right"  # "top-right", "bottom-right", "bottom-left", "top-left"
+        "notification_position": "bottom-right",  # "top-right", "bottom-right", "bottom-left", "top-left"
+        "persistent_list": False,  # Keep notifications in a list until dismissed
+        "hide_while_presenting": True,  # No popups or previews during screen shares
+        "focus_minutes": 25,  # Focus session started from the tray
+        "profiles": [],  # Named overrides, e.g. {"name": "Weekend", "show_desktop_notifications": false}
+        "active_profile": ""  # Empty uses the settings above
     },
     "ui": {
-        "dark_mode": True,
+        "theme": {
+            "mode": "system",  # "system", "light" or "dark"
+            "preset": "serina",  # serina, ocean, forest, sunset, grape, mono
+            "accent_color": ""  # "#rrggbb" overriding the preset accent
+        },
         "window_width": 1200,
-        "window_height": 800
+        "window_height": 800,
+        "high_contrast": False,
+        "menu_bar_only": False,  # macOS only
+        "locale": "",  # empty follows the OS language
+        "window_effect": "none",  # "vibrancy" (macOS), "acrylic" or "mica" (Windows 11)
+        "compact_mode": False,  # main window as a small always-on-top unread ticker
+        "mini_inbox_position": None,  # {"x", "y"} in physical pixels, remembered after dragging
+        "gamification": False,  # inbox-zero streaks and a daily triage goal
+        "daily_triage_goal": 20
     },
     "reminders": {
         "default_snooze_minutes": 60,
-        "snooze_options": [15, 30, 60, 120, 240]  # minutes
+        "snooze_options": [15, 30, 60, 120, 240],  # minutes
+        "escalate_after_active_minutes": 30,
+        "idle_threshold_minutes": 5,
+        "max_reminders": 3,
+        "deadline_lead_minutes": 60
+    },
+    "scheduling": {
+        "working_hours_start": "09:00",
+        "working_hours_end": "17:00",
+        "default_duration_minutes": 30,
+        "slots_to_offer": 3,
+        "days_ahead": 5,  # working days searched for free time
+        "buffer_minutes": 15
+    },
+    "telemetry": {
+        "enabled": False,  # Opt-in anonymous feature and error counts
+        "endpoint": ""
     }
 }
 
@@ -55,6 +92,7 @@ def load_config() -> Dict[str, Any]:
             with open(CONFIG_FILE, 'r') as f:
                 config = json.load(f)
             
+            _migrate_legacy(config)
             # Merge with defaults to ensure all keys exist
             merged_config = _merge_configs(DEFAULT_CONFIG, config)
             logger.info("Configuration loaded successfully")
@@ -114,6 +152,15 @@ def set_config_value(key_path: str, value) -> bool:
     
     return save_config(config)
 
+def _migrate_legacy(config: Dict[str, Any]):
+    """Turn the old ui.dark_mode switch into a theme mode (mirrors theme::migrate_legacy)."""
+    ui = config.get('ui')
+    if not isinstance(ui, dict) or 'dark_mode' not in ui:
+        return
+    dark_mode = ui.pop('dark_mode')
+    if 'theme' not in ui:
+        ui['theme'] = {"mode": "dark" if dark_mode else "light", "preset": "serina"}
+
 def _merge_configs(default: Dict, user: Dict) -> Dict:
     """Recursively merge user config with default config."""
     merged = default.copy()
@@ -179,8 +226,8 @@ def get_ui_config():
     return get_config_value('ui', {})
 
 def is_dark_mode():
-    """Check if dark mode is enabled."""
-    return get_config_value('ui.dark_mode', True)
+    """Check if dark mode is enabled; following the OS counts as dark here."""
+    return get_config_value('ui.theme.mode', 'system') != 'light'
 
 def get_check_interval():
     """Get email check interval in minutes."""
@@ -5,12 +5,52 @@ Simplified for MVP - Windows only, requires Outlook desktop app
 
 import win32com.client
 import pythoncom
-from datetime import datetime
+from datetime import datetime, timedelta
 from typing import List, Dict, Optional
+import base64
 import logging
+import os
+import re
+import uuid
+
+from config_service import CONFIG_DIR
 
 logger = logging.getLogger(__name__)
 
+# MAPI property holding the Internet Message-ID header
+PR_INTERNET_MESSAGE_ID = "http://schemas.microsoft.com/mapi/proptag/0x1035001F"
+PR_TRANSPORT_MESSAGE_HEADERS = "http://schemas.microsoft.com/mapi/proptag/0x007D001F"
+PR_ATTACH_CONTENT_ID = "http://schemas.microsoft.com/mapi/proptag/0x3712001F"
+PR_ATTACH_MIME_TAG = "http://schemas.microsoft.com/mapi/proptag/0x370E001F"
+# What was last done to a message (reply, forward...) and when, from any client
+PR_LAST_VERB_EXECUTED = "http://schemas.microsoft.com/mapi/proptag/0x10810003"
+PR_LAST_VERB_EXECUTION_TIME = "http://schemas.microsoft.com/mapi/proptag/0x10820040"
+REPLY_VERBS = (102, 103)  # Reply, Reply All
+# A recipient's SMTP address, even when Exchange shows an X.500 one
+PR_SMTP_ADDRESS = "http://schemas.microsoft.com/mapi/proptag/0x39FE001F"
+OL_TO, OL_CC = 1, 2  # Recipient.Type values
+
+# User property stamped on tracked replies so the sent copy can be found again
+TRACKING_PROPERTY = "SerinaTrackingId"
+
+# User property naming the teammate who claimed a message in a shared mailbox
+CLAIM_PROPERTY = "SerinaClaimedBy"
+OL_TEXT = 1  # olText user property type
+
+# Contact photos are saved here so notifications can show them by file path
+AVATAR_DIR = os.path.join(CONFIG_DIR, "avatars")
+
+# Attachments are saved here just long enough to stream them to the app
+ATTACHMENT_DIR = os.path.join(os.path.expanduser("~"), ".serina", "attachments")
+
+# Report message classes and the tracking status they imply
+REPORT_STATUSES = {
+    "REPORT.IPM.Note.DR": "delivered",
+    "REPORT.IPM.Note.NDR": "failed",
+    "REPORT.IPM.Note.IPNRN": "read",
+    "REPORT.IPM.Note.IPNNRN": "deleted_unread",
+}
+
 class OutlookEmailService:
     def __init__(self):
         self.outlook = None
@@ -28,10 +68,31 @@ class OutlookEmailService:
             logger.error(f"Failed to initialize Outlook COM: {e}")
             raise Exception("Could not connect to Outlook. Make sure Outlook is installed and you're logged in.")
     
-    def get_new_emails(self, limit: int = 20) -> List[Dict]:
-        """Get new/unread emails from Inbox."""
+    def get_accounts(self) -> List[Dict]:
+        """Accounts configured in the Outlook profile."""
+        return [
+            {'address': account.SmtpAddress, 'name': account.DisplayName}
+            for account in self.namespace.Accounts
+        ]
+
+    def _inbox(self, account: str = ""):
+        """Inbox of the given account address, a shared mailbox the user has access to,
+        or the profile's default Inbox."""
+        if not account:
+            return self.namespace.GetDefaultFolder(6)  # 6 = Inbox
+        for candidate in self.namespace.Accounts:
+            if (candidate.SmtpAddress or "").lower() == account.lower():
+                return candidate.DeliveryStore.GetDefaultFolder(6)
+        recipient = self.namespace.CreateRecipient(account)
+        if recipient.Resolve():
+            return self.namespace.GetSharedDefaultFolder(recipient, 6)
+        raise ValueError(f"No Outlook account or shared mailbox {account}")
+
+    def get_new_emails(self, limit: int = 20, account: str = "") -> List[Dict]:
+        """Get new/unread emails from an account's Inbox."""
+        # Lookup errors propagate so the app can back off this account
+        inbox = self._inbox(account)
         try:
-            inbox = self.namespace.GetDefaultFolder(6)  # 6 = Inbox
             messages = inbox.Items
             messages.Sort("[ReceivedTime]", True)  # Sort by newest first
             
@@ -46,12 +107,16 @@ class OutlookEmailService:
                 if hasattr(message, 'UnRead') and message.UnRead:
                     email_data = {
                         'id': message.EntryID,
+                        'message_id': self._message_id(message),
                         'subject': getattr(message, 'Subject', 'No Subject'),
                         'sender': getattr(message, 'SenderName', 'Unknown Sender'),
                         'sender_email': getattr(message, 'SenderEmailAddress', ''),
                         'body': getattr(message, 'Body', ''),
                         'received_time': getattr(message, 'ReceivedTime', datetime.now()),
                         'is_unread': message.UnRead,
+                        'account': account,
+                        'conversation_id': getattr(message, 'ConversationID', ''),
+                        'claimed_by': self._claimed_by(message),
                         'importance': getattr(message, 'Importance', 1)  # 0=Low, 1=Normal, 2=High
                     }
                     emails.append(email_data)
@@ -70,25 +135,115 @@ class OutlookEmailService:
             message = self.namespace.GetItemFromID(email_id)
             return {
                 'id': message.EntryID,
+                'message_id': self._message_id(message),
                 'subject': getattr(message, 'Subject', 'No Subject'),
                 'sender': getattr(message, 'SenderName', 'Unknown Sender'),
                 'sender_email': getattr(message, 'SenderEmailAddress', ''),
                 'body': getattr(message, 'Body', ''),
+                # Carries any schema.org markup for shipment/order/flight extraction
+                'html_body': self._inline_images(message, getattr(message, 'HTMLBody', '')),
                 'received_time': getattr(message, 'ReceivedTime', datetime.now()),
-                'is_unread': message.UnRead
+                'is_unread': message.UnRead,
+                'conversation_id': getattr(message, 'ConversationID', ''),
+                'claimed_by': self._claimed_by(message),
+                'is_newsletter': self._is_newsletter(message),
+                'to': self._recipient_addresses(message, OL_TO),
+                'cc': self._recipient_addresses(message, OL_CC)
             }
         except Exception as e:
             logger.error(f"Failed to get email {email_id}: {e}")
             return None
     
-    def send_reply(self, email_id: str, reply_text: str) -> bool:
-        """Send reply to an email."""
+    def _recipient_addresses(self, message, kind: int) -> List[str]:
+        """SMTP addresses of a message's To or CC recipients."""
+        addresses = []
+        for recipient in message.Recipients:
+            if recipient.Type != kind:
+                continue
+            try:
+                address = recipient.PropertyAccessor.GetProperty(PR_SMTP_ADDRESS)
+            except Exception:
+                address = recipient.Address
+            if address:
+                addresses.append(address)
+        return addresses
+
+    def get_attachments(self, email_id: str) -> List[Dict]:
+        """Names and sizes of an email's attachments; index is Outlook's 1-based position."""
+        try:
+            message = self.namespace.GetItemFromID(email_id)
+            return [
+                {'index': i, 'name': attachment.FileName, 'size': attachment.Size}
+                for i, attachment in enumerate(message.Attachments, start=1)
+            ]
+        except Exception as e:
+            logger.error(f"Failed to list attachments for {email_id}: {e}")
+            return []
+
+    def save_attachment(self, email_id: str, index: int) -> Optional[Dict]:
+        """Save one attachment to a temporary file; the caller removes it once sent."""
+        try:
+            attachment = self.namespace.GetItemFromID(email_id).Attachments.Item(index)
+            os.makedirs(ATTACHMENT_DIR, exist_ok=True)
+            path = os.path.join(ATTACHMENT_DIR, uuid.uuid4().hex)
+            attachment.SaveAsFile(path)
+            return {'path': path, 'name': attachment.FileName}
+        except Exception as e:
+            logger.error(f"Failed to save attachment {index} of {email_id}: {e}")
+            return None
+
+    def _inline_images(self, message, html: str) -> str:
+        """Embed cid: images as data: URIs so the cached HTML renders offline."""
+        if not html or 'cid:' not in html:
+            return html
+        for attachment in message.Attachments:
+            try:
+                accessor = attachment.PropertyAccessor
+                content_id = (accessor.GetProperty(PR_ATTACH_CONTENT_ID) or '').strip('<>')
+                if not content_id or f"cid:{content_id}" not in html:
+                    continue
+                mime = accessor.GetProperty(PR_ATTACH_MIME_TAG) or 'image/png'
+                os.makedirs(ATTACHMENT_DIR, exist_ok=True)
+                path = os.path.join(ATTACHMENT_DIR, uuid.uuid4().hex)
+                attachment.SaveAsFile(path)
+                try:
+                    with open(path, 'rb') as f:
+                        data = base64.b64encode(f.read()).decode('ascii')
+                finally:
+                    os.remove(path)
+                html = html.replace(f"cid:{content_id}", f"data:{mime};base64,{data}")
+            except Exception as e:
+                logger.warning(f"Skipping inline image: {e}")
+        return html
+
+    def _message_id(self, message) -> str:
+        """Internet Message-ID of a message, or '' when unavailable."""
+        try:
+            return message.PropertyAccessor.GetProperty(PR_INTERNET_MESSAGE_ID) or ''
+        except Exception:
+            return ''
+
+    def send_reply(self, email_id: str, reply_text: str,
+                   attachments: Optional[List[str]] = None,
+                   request_read_receipt: bool = False,
+                   request_delivery_receipt: bool = False,
+                   reply_all: bool = False) -> Optional[str]:
+        """Send reply to an email. Returns a tracking id, or None on failure."""
         try:
             original_message = self.namespace.GetItemFromID(email_id)
-            reply = original_message.Reply()
+            reply = original_message.ReplyAll() if reply_all else original_message.Reply()
             
             # Preserve original body and add new reply
             reply.Body = reply_text + "\n\n" + reply.Body
+            for path in attachments or []:
+                reply.Attachments.Add(path)
+
+            # Opt-in receipts; the recipient's client decides whether to honour them
+            reply.ReadReceiptRequested = request_read_receipt
+            reply.OriginatorDeliveryReportRequested = request_delivery_receipt
+            message_id = uuid.uuid4().hex
+            reply.UserProperties.Add(TRACKING_PROPERTY, OL_TEXT).Value = message_id
+
             reply.Send()
             
             # Mark original as read
@@ -96,10 +251,401 @@ class OutlookEmailService:
             original_message.Save()
             
             logger.info(f"Reply sent for email {email_id}")
-            return True
+            return message_id
             
         except Exception as e:
             logger.error(f"Failed to send reply: {e}")
+            return None
+
+    def send_email(self, to: str, subject: str, body: str,
+                   attachments: Optional[List[str]] = None) -> bool:
+        """Send a new email; `to` may list several addresses separated by semicolons."""
+        try:
+            mail = self.outlook.CreateItem(0)  # 0 = olMailItem
+            mail.To = to
+            mail.Subject = subject
+            mail.Body = body
+            for path in attachments or []:
+                mail.Attachments.Add(path)
+            mail.Send()
+            logger.info("New email sent")
+            return True
+        except Exception as e:
+            logger.error(f"Failed to send email: {e}")
+            return False
+
+    def get_aging_emails(self, min_age_days: int) -> List[Dict]:
+        """Get Inbox messages received at least `min_age_days` ago."""
+        try:
+            inbox = self.namespace.GetDefaultFolder(6)
+            cutoff = datetime.now() - timedelta(days=min_age_days)
+            messages = inbox.Items.Restrict(
+                f"[ReceivedTime] < '{cutoff.strftime('%m/%d/%Y %I:%M %p')}'"
+            )
+
+            emails = []
+            for message in messages:
+                received = getattr(message, 'ReceivedTime', None)
+                age_days = (datetime.now(received.tzinfo) - received).days if received else 0
+                emails.append({
+                    'id': message.EntryID,
+                    'subject': getattr(message, 'Subject', 'No Subject'),
+                    'sender': getattr(message, 'SenderName', 'Unknown Sender'),
+                    'sender_email': getattr(message, 'SenderEmailAddress', ''),
+                    'age_days': max(age_days, 0),
+                    'is_unread': bool(getattr(message, 'UnRead', False)),
+                    'is_newsletter': self._is_newsletter(message),
+                })
+            return emails
+        except Exception as e:
+            logger.error(f"Failed to get aging emails: {e}")
+            return []
+
+    def archive_email(self, email_id: str) -> bool:
+        """Move a message to the mailbox's Archive folder, creating it if needed."""
+        try:
+            message = self.namespace.GetItemFromID(email_id)
+            root = self.namespace.GetDefaultFolder(6).Parent
+            try:
+                archive = root.Folders["Archive"]
+            except Exception:
+                archive = root.Folders.Add("Archive")
+            message.Move(archive)
+            logger.info(f"Email {email_id} archived")
+            return True
+        except Exception as e:
+            logger.error(f"Failed to archive email: {e}")
+            return False
+
+    def delete_email(self, email_id: str) -> bool:
+        """Move a message to Deleted Items."""
+        try:
+            message = self.namespace.GetItemFromID(email_id)
+            message.Delete()
+            logger.info(f"Email {email_id} deleted")
+            return True
+        except Exception as e:
+            logger.error(f"Failed to delete email: {e}")
+            return False
+
+    def add_category(self, email_id: str, category: str) -> bool:
+        """Add an Outlook category to a message, keeping the ones it already has."""
+        try:
+            message = self.namespace.GetItemFromID(email_id)
+            categories = [c.strip() for c in (message.Categories or '').split(',') if c.strip()]
+            if category not in categories:
+                categories.append(category)
+                message.Categories = ', '.join(categories)
+                message.Save()
+            logger.info(f"Email {email_id} labelled {category}")
+            return True
+        except Exception as e:
+            logger.error(f"Failed to label email: {e}")
+            return False
+
+    def _claimed_by(self, message) -> str:
+        """Teammate holding a shared-mailbox message, or an empty string."""
+        try:
+            prop = message.UserProperties.Find(CLAIM_PROPERTY)
+            return prop.Value if prop is not None else ''
+        except Exception:
+            return ''
+
+    def set_claim(self, email_id: str, name: str, claimed_by: str, force: bool = False) -> Dict:
+        """Set or clear the claim on a message. Another teammate's claim stays unless forced;
+        the result says who holds it now and whether this call changed it."""
+        message = self.namespace.GetItemFromID(email_id)
+        current = self._claimed_by(message)
+        if current and current != name and not force:
+            return {'claimed_by': current, 'changed': False}
+        if current != claimed_by:
+            prop = message.UserProperties.Find(CLAIM_PROPERTY)
+            if prop is None:
+                prop = message.UserProperties.Add(CLAIM_PROPERTY, OL_TEXT)
+            prop.Value = claimed_by
+            message.Save()
+            logger.info(f"Email {email_id} claimed by {claimed_by or 'nobody'}")
+        return {'claimed_by': claimed_by, 'changed': current != claimed_by}
+
+    def _is_newsletter(self, message) -> bool:
+        """Mailing-list mail carries a List-Unsubscribe header."""
+        try:
+            headers = message.PropertyAccessor.GetProperty(PR_TRANSPORT_MESSAGE_HEADERS) or ''
+            return 'list-unsubscribe:' in headers.lower()
+        except Exception:
+            return False
+
+    def get_junk_emails(self, limit: int = 50) -> List[Dict]:
+        """Get recent Junk folder messages, flagging senders that are known contacts."""
+        try:
+            junk = self.namespace.GetDefaultFolder(23)  # 23 = Junk Email
+            messages = junk.Items
+            messages.Sort("[ReceivedTime]", True)
+            contacts = self._contact_addresses()
+
+            emails = []
+            for message in messages:
+                if len(emails) >= limit:
+                    break
+                sender_email = getattr(message, 'SenderEmailAddress', '')
+                emails.append({
+                    'id': message.EntryID,
+                    'subject': getattr(message, 'Subject', 'No Subject'),
+                    'sender': getattr(message, 'SenderName', 'Unknown Sender'),
+                    'sender_email': sender_email,
+                    'received_time': str(getattr(message, 'ReceivedTime', '')),
+                    'known_contact': sender_email.lower() in contacts,
+                })
+            return emails
+        except Exception as e:
+            logger.error(f"Failed to get junk emails: {e}")
+            return []
+
+    def move_to_inbox(self, email_id: str) -> bool:
+        """Move a misfiled message back to the Inbox."""
+        try:
+            message = self.namespace.GetItemFromID(email_id)
+            message.Move(self.namespace.GetDefaultFolder(6))
+            logger.info(f"Email {email_id} moved to Inbox")
+            return True
+        except Exception as e:
+            logger.error(f"Failed to move email to Inbox: {e}")
+            return False
+
+    def _contact_addresses(self) -> set:
+        """Lower-cased addresses from the Outlook Contacts folder."""
+        addresses = set()
+        contacts = self.namespace.GetDefaultFolder(10)  # 10 = Contacts
+        for contact in contacts.Items:
+            for field in ('Email1Address', 'Email2Address', 'Email3Address'):
+                address = getattr(contact, field, '') or ''
+                if address:
+                    addresses.add(address.lower())
+        return addresses
+
+    def known_contacts(self, addresses: List[str]) -> Optional[List[str]]:
+        """The given addresses that are in the Outlook Contacts folder, lower-cased."""
+        try:
+            contacts = self._contact_addresses()
+            return [address.lower() for address in addresses if address.lower() in contacts]
+        except Exception as e:
+            logger.error(f"Failed to look up contacts: {e}")
+            return None
+
+    def get_contact_photo(self, address: str) -> Optional[str]:
+        """Save the Outlook contact picture for an address and return its path."""
+        try:
+            address = address.lower()
+            contacts = self.namespace.GetDefaultFolder(10)  # 10 = Contacts
+            for contact in contacts.Items:
+                emails = [(getattr(contact, field, '') or '').lower()
+                          for field in ('Email1Address', 'Email2Address', 'Email3Address')]
+                if address not in emails or not getattr(contact, 'HasPicture', False):
+                    continue
+                for attachment in contact.Attachments:
+                    if attachment.FileName == "ContactPicture.jpg":
+                        os.makedirs(AVATAR_DIR, exist_ok=True)
+                        name = re.sub(r'[^a-z0-9._-]', '_', address)
+                        path = os.path.join(AVATAR_DIR, f"{name}.jpg")
+                        attachment.SaveAsFile(path)
+                        return path
+            return None
+        except Exception as e:
+            logger.error(f"Failed to get contact photo for {address}: {e}")
+            return None
+
+    def get_sent_emails(self, limit: int = 20) -> List[Dict]:
+        """Get the most recent messages from Sent Items."""
+        try:
+            sent = self.namespace.GetDefaultFolder(5)  # 5 = Sent Items
+            messages = sent.Items
+            messages.Sort("[SentOn]", True)
+            return self._outgoing_list(messages, limit)
+        except Exception as e:
+            logger.error(f"Failed to get sent emails: {e}")
+            return []
+
+    def get_replies(self, email_id: str) -> List[Dict]:
+        """Replies to a message's conversation sent after it arrived: the ones in its
+        mailbox's Sent Items, plus the reply Outlook records on the message itself, which
+        also covers teammates whose sent copies stay in their own mailboxes."""
+        message = self.namespace.GetItemFromID(email_id)
+        me = (self.namespace.CurrentUser.Name or '').lower()
+        replies = []
+        try:
+            accessor = message.PropertyAccessor
+            if accessor.GetProperty(PR_LAST_VERB_EXECUTED) in REPLY_VERBS:
+                replies.append({
+                    'sender': '',
+                    'sent_time': str(accessor.GetProperty(PR_LAST_VERB_EXECUTION_TIME)),
+                    'from_me': False,
+                })
+        except Exception:
+            pass  # Never replied to
+        conversation_id = getattr(message, 'ConversationID', '')
+        if conversation_id:
+            sent = message.Parent.Store.GetDefaultFolder(5)  # 5 = Sent Items
+            after = f"[SentOn] > '{message.ReceivedTime:%m/%d/%Y %I:%M %p}'"
+            for item in sent.Items.Restrict(after):
+                if getattr(item, 'ConversationID', '') != conversation_id:
+                    continue
+                sender = getattr(item, 'SenderName', '') or ''
+                replies.append({
+                    'sender': sender,
+                    'sent_time': str(item.SentOn),
+                    'from_me': sender.lower() == me,
+                })
+        return replies
+
+    def get_outbox(self) -> List[Dict]:
+        """Get messages still waiting in the Outbox."""
+        try:
+            outbox = self.namespace.GetDefaultFolder(4)  # 4 = Outbox
+            return self._outgoing_list(outbox.Items, outbox.Items.Count)
+        except Exception as e:
+            logger.error(f"Failed to get outbox: {e}")
+            return []
+
+    def retry_outbox_item(self, email_id: str) -> bool:
+        """Send an Outbox message again."""
+        try:
+            message = self.namespace.GetItemFromID(email_id)
+            message.Send()
+            logger.info(f"Outbox item {email_id} resent")
+            return True
+        except Exception as e:
+            logger.error(f"Failed to resend outbox item: {e}")
+            return False
+
+    def delete_outbox_item(self, email_id: str) -> bool:
+        """Delete a message from the Outbox."""
+        try:
+            message = self.namespace.GetItemFromID(email_id)
+            message.Delete()
+            logger.info(f"Outbox item {email_id} deleted")
+            return True
+        except Exception as e:
+            logger.error(f"Failed to delete outbox item: {e}")
+            return False
+
+    def defer_outbox_item(self, email_id: str, until: datetime) -> bool:
+        """Move the deferred delivery time of an Outbox message."""
+        try:
+            message = self.namespace.GetItemFromID(email_id)
+            # Outlook takes local wall-clock time
+            if until.tzinfo is not None:
+                until = until.astimezone().replace(tzinfo=None)
+            message.DeferredDeliveryTime = until
+            message.Send()
+            logger.info(f"Outbox item {email_id} deferred until {until}")
+            return True
+        except Exception as e:
+            logger.error(f"Failed to defer outbox item: {e}")
+            return False
+
+    def flush_outbox(self) -> bool:
+        """Push everything waiting in the Outbox to the server now."""
+        try:
+            self.namespace.SendAndReceive(False)
+            logger.info("Outbox flushed")
+            return True
+        except Exception as e:
+            logger.error(f"Failed to flush outbox: {e}")
+            return False
+
+    def _outgoing_list(self, messages, limit: int) -> List[Dict]:
+        emails = []
+        for message in messages:
+            if len(emails) >= limit:
+                break
+            emails.append({
+                'id': message.EntryID,
+                'subject': getattr(message, 'Subject', 'No Subject'),
+                'recipients': getattr(message, 'To', ''),
+                'body': getattr(message, 'Body', ''),
+                'sent_on': str(getattr(message, 'SentOn', '')),
+                'submitted': bool(getattr(message, 'Submitted', False)),
+                'deferred_until': self._deferred_until(message),
+            })
+        return emails
+
+    def _deferred_until(self, message) -> Optional[str]:
+        """Deferred delivery time, or None when the message goes out on the next send."""
+        deferred = getattr(message, 'DeferredDeliveryTime', None)
+        # Outlook reports "no deferral" as 4501-01-01
+        if deferred is None or deferred.year >= 4501:
+            return None
+        return str(deferred)
+
+    def get_tracked_sent(self, limit: int = 50) -> List[Dict]:
+        """Get tracking status for recent replies sent through SERINA."""
+        try:
+            sent = self.namespace.GetDefaultFolder(5)  # 5 = Sent Items
+            messages = sent.Items
+            messages.Sort("[SentOn]", True)
+
+            results = []
+            for message in messages:
+                if len(results) >= limit:
+                    break
+                prop = message.UserProperties.Find(TRACKING_PROPERTY)
+                if prop is not None:
+                    results.append(self._sent_status(message, prop.Value))
+            return results
+
+        except Exception as e:
+            logger.error(f"Failed to get tracked sent items: {e}")
+            return []
+
+    def get_sent_status(self, message_id: str) -> Optional[Dict]:
+        """Get delivery/read status for one tracked reply."""
+        for status in self.get_tracked_sent(limit=500):
+            if status['message_id'] == message_id:
+                return status
+        return None
+
+    def _sent_status(self, message, message_id: str) -> Dict:
+        """Match delivery and read reports in the inbox against a sent message."""
+        status = {
+            'message_id': message_id,
+            'subject': getattr(message, 'Subject', ''),
+            'recipients': getattr(message, 'To', ''),
+            'sent_on': str(getattr(message, 'SentOn', '')),
+            'read_receipt_requested': bool(getattr(message, 'ReadReceiptRequested', False)),
+            'delivery_receipt_requested': bool(getattr(message, 'OriginatorDeliveryReportRequested', False)),
+            'status': 'sent',
+            'delivered_at': None,
+            'read_at': None,
+        }
+
+        inbox = self.namespace.GetDefaultFolder(6)
+        topic = getattr(message, 'ConversationTopic', '')
+        reports = inbox.Items.Restrict("[MessageClass] >= 'REPORT' AND [MessageClass] < 'REPORU'")
+        for report in reports:
+            report_status = REPORT_STATUSES.get(getattr(report, 'MessageClass', ''))
+            if not report_status or getattr(report, 'ConversationTopic', '') != topic:
+                continue
+            received = str(getattr(report, 'CreationTime', ''))
+            if report_status == 'delivered':
+                status['delivered_at'] = received
+            elif report_status == 'read':
+                status['read_at'] = received
+            # Read outranks delivered; failures always win
+            if status['status'] != 'failed' and (report_status != 'delivered' or status['status'] == 'sent'):
+                status['status'] = report_status
+        return status
+    
+    def save_reply_draft(self, email_id: str, reply_text: str) -> bool:
+        """Save a reply to an email in Drafts for the user to review and send."""
+        try:
+            original_message = self.namespace.GetItemFromID(email_id)
+            reply = original_message.Reply()
+            reply.Body = reply_text + "\n\n" + reply.Body
+            reply.Save()
+            logger.info(f"Reply draft saved for email {email_id}")
+            return True
+        except Exception as e:
+            logger.error(f"Failed to save reply draft: {e}")
             return False
     
     def mark_as_read(self, email_id: str) -> bool:
@@ -151,6 +697,47 @@ class OutlookEmailService:
             logger.error(f"Failed to snooze email: {e}")
             return False
     
+    def get_busy_times(self, start: datetime, end: datetime) -> List[Dict]:
+        """Busy calendar entries overlapping start..end, recurring meetings included."""
+        calendar = self.namespace.GetDefaultFolder(9)  # 9 = Calendar
+        items = calendar.Items
+        items.IncludeRecurrences = True
+        items.Sort("[Start]")
+        restriction = (f"[Start] < '{end:%m/%d/%Y %I:%M %p}' AND "
+                       f"[End] > '{start:%m/%d/%Y %I:%M %p}'")
+        blocks = []
+        for item in items.Restrict(restriction):
+            if getattr(item, 'BusyStatus', 2) == 0:  # 0 = Free
+                continue
+            blocks.append({'start': str(item.Start), 'end': str(item.End)})
+        return blocks
+
+    def create_meeting(self, subject: str, start: datetime, end: datetime, attendee: str) -> bool:
+        """Send a meeting request to one attendee."""
+        try:
+            meeting = self.outlook.CreateItem(1)  # 1 = Appointment
+            meeting.MeetingStatus = 1  # 1 = Meeting
+            meeting.Subject = subject
+            meeting.Start = start
+            meeting.End = end
+            meeting.Recipients.Add(attendee)
+            meeting.Recipients.ResolveAll()
+            meeting.Send()
+            logger.info(f"Meeting request sent to {attendee} for {start}")
+            return True
+        except Exception as e:
+            logger.error(f"Failed to create meeting: {e}")
+            return False
+
+    def check_connection(self) -> Dict:
+        """Verify the Outlook session can reach the mailbox."""
+        try:
+            self.namespace.GetDefaultFolder(6).Items.Count
+            return {'connected': True, 'account': self.namespace.CurrentUser.Name}
+        except Exception as e:
+            logger.error(f"Email connection check failed: {e}")
+            return {'connected': False, 'error': str(e)}
+
     def get_unread_count(self) -> int:
         """Get count of unread emails."""
         try:
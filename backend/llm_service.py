@@ -33,10 +33,11 @@ class LLMService:
         
         logger.info(f"Initialized LLM service with {self.provider}")
     
-    def generate_email_summary(self, email_content: str) -> str:
+    def generate_email_summary(self, email_content: str, language: Optional[str] = None) -> str:
         """Generate a concise summary of an email."""
+        language_line = f"Write the summary in {language}.\n" if language else ""
         prompt = f"""
-Summarize this email in 2-3 sentences. Focus on:
+{language_line}Summarize this email in 2-3 sentences. Focus on:
 - Main purpose/request
 - Any action needed
 - Important details
@@ -3,19 +3,25 @@ SERINA Backend - Simplified FastAPI Server
 MVP Version with Outlook COM integration
 """
 
-from fastapi import FastAPI, HTTPException
+from fastapi import FastAPI, HTTPException, Query
 from fastapi.middleware.cors import CORSMiddleware
+from fastapi.middleware.gzip import GZipMiddleware
+from fastapi.responses import FileResponse
+from starlette.background import BackgroundTask
 from pydantic import BaseModel
 from typing import List, Optional
+from datetime import datetime
 import logging
+import os
 import uvicorn
 
 from email_service import email_service
 from llm_service import create_llm_service
 from config_service import load_config, save_config
 
-# Configure logging
-logging.basicConfig(level=logging.INFO)
+# Configure logging; SERINA_LOG_LEVEL=debug for development
+LOG_LEVEL = os.environ.get("SERINA_LOG_LEVEL", "info").lower()
+logging.basicConfig(level=LOG_LEVEL.upper())
 logger = logging.getLogger(__name__)
 
 app = FastAPI(title="SERINA Email Assistant", version="1.0.0")
@@ -29,29 +35,68 @@ app.add_middleware(
     allow_headers=["*"],
 )
 
+# Email lists get large; the Tauri client decompresses as it streams
+app.add_middleware(GZipMiddleware, minimum_size=1024)
+
 # Global LLM service instance
 llm_service = None
 
 # Pydantic models for API requests/responses
 class EmailResponse(BaseModel):
     id: str
+    message_id: str = ""
     subject: str
     sender: str
     sender_email: str
     body: str
     received_time: str
     is_unread: bool
+    account: str = ""
+    conversation_id: str = ""
 
 class ReplyRequest(BaseModel):
     reply_text: str
+    # Files staged by the app, attached as-is
+    attachments: List[str] = []
+    request_read_receipt: bool = False
+    request_delivery_receipt: bool = False
+    # Answer everyone on the original, not just the sender
+    reply_all: bool = False
+
+class DraftReplyRequest(BaseModel):
+    reply_text: str
+
+class SendRequest(BaseModel):
+    to: str
+    subject: str = ""
+    body: str = ""
+    # Files shared with the app, attached as-is
+    attachments: List[str] = []
+
+class DeferRequest(BaseModel):
+    until: datetime
 
 class TaskRequest(BaseModel):
     title: str
     description: str
 
+class CategoryRequest(BaseModel):
+    category: str
+
+class ClaimRequest(BaseModel):
+    name: str
+    force: bool = False
+
 class LLMRequest(BaseModel):
     email_content: str
     instruction: Optional[str] = ""
+    language: Optional[str] = None
+
+class MeetingRequest(BaseModel):
+    subject: str
+    start: datetime
+    end: datetime
+    attendee: str
 
 class ConfigRequest(BaseModel):
     config: dict
@@ -84,16 +129,44 @@ async def health_check():
     return {"status": "healthy", "service": "SERINA Backend"}
 
 # Email endpoints
+@app.get("/diagnostics/email")
+async def email_diagnostics(account_id: str = "default"):
+    """Check the connection to the Outlook mailbox."""
+    return email_service.check_connection()
+
+@app.get("/accounts")
+async def get_accounts():
+    """List the accounts in the Outlook profile."""
+    try:
+        return email_service.get_accounts()
+    except Exception as e:
+        logger.error(f"Error listing accounts: {e}")
+        raise HTTPException(status_code=500, detail="Failed to list accounts")
+
 @app.get("/emails", response_model=List[EmailResponse])
-async def get_emails(limit: int = 20):
-    """Get new emails from Outlook."""
+async def get_emails(limit: int = 20, account: str = ""):
+    """Get new emails from Outlook, optionally from one account."""
     try:
-        emails = email_service.get_new_emails(limit=limit)
+        emails = email_service.get_new_emails(limit=limit, account=account)
         return emails
+    except ValueError as e:
+        raise HTTPException(status_code=404, detail=str(e))
     except Exception as e:
         logger.error(f"Error fetching emails: {e}")
         raise HTTPException(status_code=500, detail="Failed to fetch emails")
 
+# Fixed paths go before /emails/{email_id}, which would otherwise match them
+@app.get("/emails/aging")
+async def get_aging_emails(min_age_days: int = 30):
+    """Get Inbox emails older than the given number of days."""
+    return email_service.get_aging_emails(min_age_days)
+
+@app.get("/emails/unread-count")
+async def get_unread_count():
+    """Get count of unread emails."""
+    count = email_service.get_unread_count()
+    return {"count": count}
+
 @app.get("/emails/{email_id}")
 async def get_email(email_id: str):
     """Get specific email by ID."""
@@ -102,13 +175,67 @@ async def get_email(email_id: str):
         raise HTTPException(status_code=404, detail="Email not found")
     return email
 
+@app.get("/emails/{email_id}/replies")
+async def get_replies(email_id: str):
+    """Replies to an email's conversation sent since it arrived, by anyone."""
+    try:
+        return email_service.get_replies(email_id)
+    except Exception as e:
+        logger.error(f"Error getting replies: {e}")
+        raise HTTPException(status_code=500, detail="Failed to get replies")
+
+@app.get("/emails/{email_id}/attachments")
+async def get_attachments(email_id: str):
+    """List an email's attachments."""
+    return email_service.get_attachments(email_id)
+
+@app.get("/emails/{email_id}/attachments/{index}")
+async def get_attachment(email_id: str, index: int):
+    """Stream one attachment as raw bytes; the temporary copy is removed afterwards."""
+    saved = email_service.save_attachment(email_id, index)
+    if not saved:
+        raise HTTPException(status_code=404, detail="Attachment not found")
+    return FileResponse(
+        saved["path"],
+        filename=saved["name"],
+        background=BackgroundTask(os.remove, saved["path"]),
+    )
+
 @app.post("/emails/{email_id}/reply")
 async def send_reply(email_id: str, request: ReplyRequest):
     """Send reply to an email."""
-    success = email_service.send_reply(email_id, request.reply_text)
-    if not success:
+    message_id = email_service.send_reply(
+        email_id,
+        request.reply_text,
+        attachments=request.attachments,
+        request_read_receipt=request.request_read_receipt,
+        request_delivery_receipt=request.request_delivery_receipt,
+        reply_all=request.reply_all
+    )
+    if not message_id:
         raise HTTPException(status_code=500, detail="Failed to send reply")
-    return {"message": "Reply sent successfully"}
+    return {"message": "Reply sent successfully", "message_id": message_id}
+
+@app.post("/emails/{email_id}/draft-reply")
+async def save_reply_draft(email_id: str, request: DraftReplyRequest):
+    """Save a reply to an email as a draft without sending it."""
+    success = email_service.save_reply_draft(email_id, request.reply_text)
+    if not success:
+        raise HTTPException(status_code=500, detail="Failed to save reply draft")
+    return {"message": "Reply draft saved"}
+
+@app.post("/emails/send")
+async def send_email(request: SendRequest):
+    """Send a new email."""
+    success = email_service.send_email(
+        request.to,
+        request.subject,
+        request.body,
+        attachments=request.attachments
+    )
+    if not success:
+        raise HTTPException(status_code=500, detail="Failed to send email")
+    return {"message": "Email sent successfully"}
 
 @app.post("/emails/{email_id}/mark-read")
 async def mark_email_read(email_id: str):
@@ -138,11 +265,150 @@ async def snooze_email(email_id: str, minutes: int = 60):
         raise HTTPException(status_code=500, detail="Failed to snooze email")
     return {"message": f"Email snoozed for {minutes} minutes"}
 
-@app.get("/emails/unread-count")
-async def get_unread_count():
-    """Get count of unread emails."""
-    count = email_service.get_unread_count()
-    return {"count": count}
+@app.post("/emails/{email_id}/archive")
+async def archive_email(email_id: str):
+    """Move an email to the Archive folder."""
+    success = email_service.archive_email(email_id)
+    if not success:
+        raise HTTPException(status_code=500, detail="Failed to archive email")
+    return {"message": "Email archived"}
+
+@app.post("/emails/{email_id}/categorize")
+async def categorize_email(email_id: str, request: CategoryRequest):
+    """Add an Outlook category to an email."""
+    success = email_service.add_category(email_id, request.category)
+    if not success:
+        raise HTTPException(status_code=500, detail="Failed to categorize email")
+    return {"message": "Email categorized"}
+
+@app.post("/emails/{email_id}/claim")
+async def claim_email(email_id: str, request: ClaimRequest):
+    """Claim an email in a shared mailbox; someone else's claim is kept unless forced."""
+    try:
+        return email_service.set_claim(email_id, request.name, request.name, request.force)
+    except Exception as e:
+        logger.error(f"Error claiming email: {e}")
+        raise HTTPException(status_code=500, detail="Failed to claim email")
+
+@app.post("/emails/{email_id}/release")
+async def release_email(email_id: str, request: ClaimRequest):
+    """Release a claim; only its holder can, unless forced."""
+    try:
+        return email_service.set_claim(email_id, request.name, "", request.force)
+    except Exception as e:
+        logger.error(f"Error releasing email: {e}")
+        raise HTTPException(status_code=500, detail="Failed to release email")
+
+@app.delete("/emails/{email_id}")
+async def delete_email(email_id: str):
+    """Move an email to Deleted Items."""
+    success = email_service.delete_email(email_id)
+    if not success:
+        raise HTTPException(status_code=500, detail="Failed to delete email")
+    return {"message": "Email deleted"}
+
+@app.post("/emails/{email_id}/not-spam")
+async def not_spam(email_id: str):
+    """Move a misfiled email from Junk back to the Inbox."""
+    success = email_service.move_to_inbox(email_id)
+    if not success:
+        raise HTTPException(status_code=500, detail="Failed to move email to Inbox")
+    return {"message": "Email moved to Inbox"}
+
+@app.get("/calendar/busy")
+async def get_busy_times(start: datetime, end: datetime):
+    """Get busy calendar time between two local times."""
+    try:
+        return email_service.get_busy_times(start, end)
+    except Exception as e:
+        logger.error(f"Error reading calendar: {e}")
+        raise HTTPException(status_code=500, detail="Failed to read calendar")
+
+@app.post("/calendar/meetings")
+async def create_meeting(request: MeetingRequest):
+    """Send a meeting request."""
+    success = email_service.create_meeting(
+        request.subject, request.start, request.end, request.attendee
+    )
+    if not success:
+        raise HTTPException(status_code=500, detail="Failed to create meeting")
+    return {"message": "Meeting request sent"}
+
+@app.get("/junk")
+async def get_junk_emails(limit: int = 50):
+    """Get recent emails from the Junk folder."""
+    return email_service.get_junk_emails(limit=limit)
+
+@app.get("/contacts/photo")
+async def get_contact_photo(email: str):
+    """Get the saved Outlook contact picture path for a sender."""
+    path = email_service.get_contact_photo(email)
+    if not path:
+        raise HTTPException(status_code=404, detail="No contact photo")
+    return {"path": path}
+
+@app.get("/contacts/known")
+async def get_known_contacts(email: List[str] = Query(default=[])):
+    """Which of the given addresses are in the Outlook contacts."""
+    known = email_service.known_contacts(email)
+    if known is None:
+        raise HTTPException(status_code=500, detail="Failed to read contacts")
+    return {"known": known}
+
+@app.get("/sent")
+async def get_sent_emails(limit: int = 20):
+    """Get recently sent emails."""
+    return email_service.get_sent_emails(limit=limit)
+
+@app.get("/outbox")
+async def get_outbox():
+    """Get emails waiting in the Outbox."""
+    return email_service.get_outbox()
+
+@app.post("/outbox/flush")
+async def flush_outbox():
+    """Send everything waiting in the Outbox, e.g. before SERINA quits."""
+    success = email_service.flush_outbox()
+    if not success:
+        raise HTTPException(status_code=500, detail="Failed to flush outbox")
+    return {"message": "Outbox flushed"}
+
+@app.post("/outbox/{email_id}/retry")
+async def retry_outbox_item(email_id: str):
+    """Resend a stuck Outbox email."""
+    success = email_service.retry_outbox_item(email_id)
+    if not success:
+        raise HTTPException(status_code=500, detail="Failed to resend email")
+    return {"message": "Email resent"}
+
+@app.post("/outbox/{email_id}/defer")
+async def defer_outbox_item(email_id: str, request: DeferRequest):
+    """Change when a scheduled Outbox email is sent."""
+    success = email_service.defer_outbox_item(email_id, request.until)
+    if not success:
+        raise HTTPException(status_code=500, detail="Failed to reschedule email")
+    return {"message": "Email rescheduled"}
+
+@app.delete("/outbox/{email_id}")
+async def delete_outbox_item(email_id: str):
+    """Delete an email from the Outbox."""
+    success = email_service.delete_outbox_item(email_id)
+    if not success:
+        raise HTTPException(status_code=500, detail="Failed to delete email")
+    return {"message": "Email deleted"}
+
+@app.get("/sent/tracked")
+async def get_tracked_sent(limit: int = 50):
+    """Get delivery/read status for recent tracked replies."""
+    return email_service.get_tracked_sent(limit=limit)
+
+@app.get("/sent/{message_id}/status")
+async def get_sent_status(message_id: str):
+    """Get delivery/read status for one tracked reply."""
+    status = email_service.get_sent_status(message_id)
+    if not status:
+        raise HTTPException(status_code=404, detail="Sent message not found")
+    return status
 
 # LLM endpoints
 @app.post("/llm/summarize")
@@ -151,7 +417,7 @@ async def summarize_email(request: LLMRequest):
     if not llm_service:
         raise HTTPException(status_code=503, detail="LLM service not configured")
     
-    summary = llm_service.generate_email_summary(request.email_content)
+    summary = llm_service.generate_email_summary(request.email_content, request.language)
     return {"summary": summary}
 
 @app.post("/llm/generate-task")
@@ -228,5 +494,5 @@ if __name__ == "__main__":
         host="127.0.0.1",
         port=8000,
         reload=True,
-        log_level="info"
+        log_level=LOG_LEVEL
     )
\ No newline at end of file
@@ -0,0 +1,132 @@
+// SERINA Storage Benchmarks
+// Cache writes and reads plus snippet generation over a 50k-message synthetic mailbox
+
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
+use serina::cache::EmailCache;
+use serina::parsing;
+use serina::utils::EmailData;
+
+const CORPUS_SIZE: usize = 50_000;
+
+const WORDS: &[&str] = &[
+    "budget", "review", "meeting", "forecast", "invoice", "attached", "please", "thanks",
+    "quarter", "deadline", "project", "update", "customer", "contract", "schedule", "team",
+];
+
+/// A deterministic mailbox: mixed HTML and plain bodies, quoted history, duplicate deliveries
+fn corpus(size: usize) -> Vec<EmailData> {
+    (0..size)
+        .map(|i| {
+            let words: Vec<&str> = (0..60)
+                .map(|w| WORDS[(i * 7 + w * 3) % WORDS.len()])
+                .collect();
+            let text = words.join(" ");
+            let body = if i % 3 == 0 {
+                format!(
+                    "<html><body><div>Hi,</div><p>{}</p><blockquote>{}</blockquote></body></html>",
+                    text,
+                    text.to_uppercase()
+                )
+            } else {
+                format!(
+                    "Hello,\n\n{}\n\nThanks,\nSam\n\nOn Monday, Jane wrote:\n> {}",
+                    text, text
+                )
+            };
+            EmailData {
+                id: format!("msg-{}", i),
+                // Every tenth message is a second delivery of the one before it
+                message_id: format!("<{}@example.com>", i - usize::from(i % 10 == 9)),
+                subject: format!(
+                    "{} {} #{}",
+                    WORDS[i % WORDS.len()],
+                    WORDS[(i / 3) % WORDS.len()],
+                    i
+                ),
+                sender: format!("Sender {}", i % 500),
+                sender_email: format!("sender{}@example.com", i % 500),
+                body,
+                received_time: format!("2024-05-{:02}T{:02}:{:02}:00", 1 + i % 28, i % 24, i % 60),
+                is_unread: i % 4 == 0,
+                ..Default::default()
+            }
+        })
+        .collect()
+}
+
+fn filled_cache(emails: &[EmailData]) -> EmailCache {
+    let cache = EmailCache::in_memory().unwrap();
+    cache.store(emails).unwrap();
+    cache
+}
+
+fn bench_store(c: &mut Criterion) {
+    let emails = corpus(CORPUS_SIZE);
+    let mut group = c.benchmark_group("cache/store");
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(CORPUS_SIZE as u64));
+    group.bench_function("insert 50k", |b| {
+        b.iter_batched(
+            || EmailCache::in_memory().unwrap(),
+            |cache| cache.store(&emails).unwrap(),
+            BatchSize::PerIteration,
+        )
+    });
+
+    // A sync re-delivers the latest page of messages the cache already holds
+    let cache = filled_cache(&emails);
+    let page = &emails[CORPUS_SIZE - 50..];
+    group.sample_size(50);
+    group.throughput(Throughput::Elements(page.len() as u64));
+    group.bench_function("refresh 50 of 50k", |b| {
+        b.iter(|| cache.store(page).unwrap())
+    });
+    group.finish();
+}
+
+fn bench_query(c: &mut Criterion) {
+    let emails = corpus(CORPUS_SIZE);
+    let cache = filled_cache(&emails);
+    let mut group = c.benchmark_group("cache/query");
+    for limit in [20, 200, 2000] {
+        group.bench_with_input(BenchmarkId::new("recent", limit), &limit, |b, &limit| {
+            b.iter(|| cache.recent(limit).unwrap())
+        });
+    }
+    let cutoff = chrono::Utc::now() - chrono::Duration::days(30);
+    group.bench_function("count_older_than", |b| {
+        b.iter(|| cache.count_older_than(cutoff).unwrap())
+    });
+    let page = emails[CORPUS_SIZE - 100..].to_vec();
+    group.bench_function("collapse 100 of 50k", |b| {
+        b.iter_batched(
+            || page.clone(),
+            |page| cache.collapse(page).unwrap(),
+            BatchSize::SmallInput,
+        )
+    });
+    group.finish();
+}
+
+fn bench_snippets(c: &mut Criterion) {
+    let emails = corpus(CORPUS_SIZE);
+    let mut group = c.benchmark_group("parsing");
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(CORPUS_SIZE as u64));
+    group.bench_function("snippets 50k", |b| {
+        b.iter_batched(
+            || emails.clone(),
+            |mut emails| parsing::add_snippets(&mut emails),
+            BatchSize::LargeInput,
+        )
+    });
+    group.finish();
+
+    let html = &emails[0].body;
+    c.bench_function("parsing/html_to_text", |b| {
+        b.iter(|| parsing::html_to_text(html))
+    });
+}
+
+criterion_group!(benches, bench_store, bench_query, bench_snippets);
+criterion_main!(benches);
@@ -0,0 +1,133 @@
+## Tray menu
+
+tray-show = Show
+tray-settings = Settings
+tray-quit = Quit
+tray-profile = Notification profile
+tray-profile-default = Default
+tray-focus-start = Start focus session
+tray-focus-end =
+    { $minutes ->
+        [one] End focus session (1 minute left)
+       *[other] End focus session ({ $minutes } minutes left)
+    }
+tray-pinned = Pinned ({ $count })
+tray-pin-open = Open
+tray-pin-done = Done
+tray-upcoming = Upcoming
+tray-activity = Activity
+tray-lock = Lock SERINA
+
+## Window titles
+
+window-main-title = SERINA Email Assistant
+window-settings-title = SERINA Settings
+window-reminder-title = SERINA reminder: { $summary }
+window-mini-inbox-title = SERINA mini inbox
+window-print-title = SERINA print
+window-compose-title = SERINA new message
+window-upcoming-title = SERINA upcoming
+window-activity-title = SERINA activity
+
+## Email counts
+
+email-count =
+    { $count ->
+        [0] No new emails
+        [one] { $count } new email
+       *[other] { $count } new emails
+    }
+
+## Notifications
+
+notification-update-title = Update available
+notification-update-body = SERINA { $version } is ready to install
+notification-misfiled-title = Possibly misfiled email
+notification-misfiled-body = "{ $subject }" from { $sender } landed in Junk { $when }
+notification-reply-failed-title = Reply not sent
+notification-reply-retrying-body = { $error }. SERINA will try again shortly.
+notification-reply-given-up-body = { $error }. Retry, edit or discard the reply.
+notification-code-title = Code { $code }
+notification-code-body = Verification code from { $sender }
+notification-payment-title =
+    { $days ->
+        [0] Bill due today
+        [1] Bill due tomorrow
+       *[other] Bill due in { $days } days
+    }
+notification-payment-overdue-title = Bill overdue
+notification-payment-body = { $payee }: { $amount }
+notification-snooze-title = Snoozed email is back
+notification-snooze-body = "{ $subject }" from { $sender }
+notification-deadline-title = Deadline coming up
+notification-deadline-body = "{ $subject }" from { $sender } is due { $when }
+notification-rule-delete-title = { $rule } wants to delete an email
+notification-rule-delete-body = "{ $subject }" from { $sender }
+notification-hidden-title = New email
+notification-hidden-body = Preview hidden while you are presenting
+notification-locked-title = SERINA
+lock-biometric-reason = Unlock SERINA
+notification-focus-title = Focus session over
+notification-focus-body =
+    { $summary }, { $alerts ->
+        [0] no other alerts
+        [one] 1 other alert
+       *[other] { $alerts } other alerts
+    }
+
+## Notification actions
+
+action-open = Open
+action-mark-read = Mark as read
+action-snooze = Snooze
+action-not-spam = Not spam
+action-reply = Send reply
+action-pin = Pin
+action-retry-send = Retry now
+action-edit-reply = Edit
+action-discard-reply = Discard
+action-copy-code = Copy code
+action-mark-paid = Mark paid
+action-delete = Delete
+action-reply-placeholder = Type a reply
+
+## Dates
+
+# strftime pattern for absolute dates
+date-time-format = %m/%d/%Y %-I:%M %p
+time-just-now = just now
+time-minutes-ago =
+    { $count ->
+        [one] 1 minute ago
+       *[other] { $count } minutes ago
+    }
+time-hours-ago =
+    { $count ->
+        [one] 1 hour ago
+       *[other] { $count } hours ago
+    }
+time-yesterday = yesterday
+time-days-ago = { $count } days ago
+
+## Links
+
+link-confirm-title = Open this link?
+link-confirm-body = This link goes to { $host } and looks risky:
+link-risk-unresolved = It's a shortened link whose destination couldn't be checked
+link-risk-ip-address = It uses a bare IP address instead of a site name
+link-risk-punycode = The site name uses lookalike international characters
+link-risk-insecure = The connection isn't encrypted
+link-risk-unusual-port = It uses an unusual port
+link-risk-mismatched-text = Its text shows a different site than it opens
+
+## Confirmations
+
+confirm-title = This can't be undone
+confirm-delete-emails = Permanently delete these { $count } emails?
+confirm-purge-data = Erase this data stored on this computer?
+confirm-more = …and { $count } more
+
+## Errors
+
+error-no-update = No update available
+error-updates-off = Updates are turned off in this build
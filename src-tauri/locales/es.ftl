@@ -0,0 +1,133 @@
+## Tray menu
+
+tray-show = Mostrar
+tray-settings = Configuración
+tray-quit = Salir
+tray-profile = Perfil de notificaciones
+tray-profile-default = Predeterminado
+tray-focus-start = Iniciar sesión de concentración
+tray-focus-end =
+    { $minutes ->
+        [one] Terminar sesión de concentración (queda 1 minuto)
+       *[other] Terminar sesión de concentración (quedan { $minutes } minutos)
+    }
+tray-pinned = Fijados ({ $count })
+tray-pin-open = Abrir
+tray-pin-done = Hecho
+tray-upcoming = Próximos
+tray-activity = Actividad
+tray-lock = Bloquear SERINA
+
+## Window titles
+
+window-main-title = SERINA Asistente de correo
+window-settings-title = Configuración de SERINA
+window-reminder-title = Recordatorio de SERINA: { $summary }
+window-mini-inbox-title = Mini bandeja de SERINA
+window-print-title = Impresión de SERINA
+window-compose-title = Nuevo mensaje de SERINA
+window-upcoming-title = Próximos en SERINA
+window-activity-title = Actividad de SERINA
+
+## Email counts
+
+email-count =
+    { $count ->
+        [0] No hay correos nuevos
+        [one] { $count } correo nuevo
+       *[other] { $count } correos nuevos
+    }
+
+## Notifications
+
+notification-update-title = Actualización disponible
+notification-update-body = SERINA { $version } está lista para instalar
+notification-misfiled-title = Posible correo mal clasificado
+notification-misfiled-body = "{ $subject }" de { $sender } llegó a Correo no deseado { $when }
+notification-reply-failed-title = No se envió la respuesta
+notification-reply-retrying-body = { $error }. SERINA lo intentará de nuevo en breve.
+notification-reply-given-up-body = { $error }. Reintenta, edita o descarta la respuesta.
+notification-code-title = Código { $code }
+notification-code-body = Código de verificación de { $sender }
+notification-payment-title =
+    { $days ->
+        [0] Factura vence hoy
+        [1] Factura vence mañana
+       *[other] Factura vence en { $days } días
+    }
+notification-payment-overdue-title = Factura vencida
+notification-payment-body = { $payee }: { $amount }
+notification-snooze-title = Un correo pospuesto ha vuelto
+notification-snooze-body = "{ $subject }" de { $sender }
+notification-deadline-title = Se acerca una fecha límite
+notification-deadline-body = "{ $subject }" de { $sender } vence el { $when }
+notification-rule-delete-title = { $rule } quiere eliminar un correo
+notification-rule-delete-body = "{ $subject }" de { $sender }
+notification-hidden-title = Correo nuevo
+notification-hidden-body = Vista previa oculta mientras presentas
+notification-locked-title = SERINA
+lock-biometric-reason = Desbloquear SERINA
+notification-focus-title = Sesión de concentración terminada
+notification-focus-body =
+    { $summary }, { $alerts ->
+        [0] ningún otro aviso
+        [one] 1 aviso más
+       *[other] { $alerts } avisos más
+    }
+
+## Notification actions
+
+action-open = Abrir
+action-mark-read = Marcar como leído
+action-snooze = Posponer
+action-not-spam = No es spam
+action-reply = Enviar respuesta
+action-pin = Fijar
+action-retry-send = Reintentar ahora
+action-edit-reply = Editar
+action-discard-reply = Descartar
+action-copy-code = Copiar código
+action-mark-paid = Marcar como pagada
+action-delete = Eliminar
+action-reply-placeholder = Escribe una respuesta
+
+## Dates
+
+# strftime pattern for absolute dates
+date-time-format = %d/%m/%Y %H:%M
+time-just-now = justo ahora
+time-minutes-ago =
+    { $count ->
+        [one] hace 1 minuto
+       *[other] hace { $count } minutos
+    }
+time-hours-ago =
+    { $count ->
+        [one] hace 1 hora
+       *[other] hace { $count } horas
+    }
+time-yesterday = ayer
+time-days-ago = hace { $count } días
+
+## Links
+
+link-confirm-title = ¿Abrir este enlace?
+link-confirm-body = Este enlace lleva a { $host } y parece arriesgado:
+link-risk-unresolved = Es un enlace acortado cuyo destino no se pudo comprobar
+link-risk-ip-address = Usa una dirección IP en lugar del nombre de un sitio
+link-risk-punycode = El nombre del sitio usa caracteres internacionales que imitan a otros
+link-risk-insecure = La conexión no está cifrada
+link-risk-unusual-port = Usa un puerto poco habitual
+link-risk-mismatched-text = Su texto muestra un sitio distinto del que abre
+
+## Confirmations
+
+confirm-title = Esto no se puede deshacer
+confirm-delete-emails = ¿Eliminar definitivamente estos { $count } correos?
+confirm-purge-data = ¿Borrar estos datos guardados en este equipo?
+confirm-more = …y { $count } más
+
+## Errors
+
+error-no-update = No hay actualizaciones disponibles
+error-updates-off = Las actualizaciones están desactivadas en esta compilación
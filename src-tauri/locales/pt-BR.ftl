@@ -0,0 +1,133 @@
+## Tray menu
+
+tray-show = Mostrar
+tray-settings = Configurações
+tray-quit = Sair
+tray-profile = Perfil de notificações
+tray-profile-default = Padrão
+tray-focus-start = Iniciar sessão de foco
+tray-focus-end =
+    { $minutes ->
+        [one] Encerrar sessão de foco (falta 1 minuto)
+       *[other] Encerrar sessão de foco (faltam { $minutes } minutos)
+    }
+tray-pinned = Fixados ({ $count })
+tray-pin-open = Abrir
+tray-pin-done = Concluído
+tray-upcoming = Próximos
+tray-activity = Atividade
+tray-lock = Bloquear o SERINA
+
+## Window titles
+
+window-main-title = SERINA Assistente de e-mail
+window-settings-title = Configurações do SERINA
+window-reminder-title = Lembrete do SERINA: { $summary }
+window-mini-inbox-title = Mini caixa de entrada do SERINA
+window-print-title = Impressão do SERINA
+window-compose-title = Nova mensagem do SERINA
+window-upcoming-title = Próximos no SERINA
+window-activity-title = Atividade do SERINA
+
+## Email counts
+
+email-count =
+    { $count ->
+        [0] Nenhum e-mail novo
+        [one] { $count } e-mail novo
+       *[other] { $count } e-mails novos
+    }
+
+## Notifications
+
+notification-update-title = Atualização disponível
+notification-update-body = SERINA { $version } está pronto para instalar
+notification-misfiled-title = E-mail possivelmente mal classificado
+notification-misfiled-body = "{ $subject }" de { $sender } foi parar no Lixo Eletrônico { $when }
+notification-reply-failed-title = Resposta não enviada
+notification-reply-retrying-body = { $error }. O SERINA tentará novamente em breve.
+notification-reply-given-up-body = { $error }. Tente novamente, edite ou descarte a resposta.
+notification-code-title = Código { $code }
+notification-code-body = Código de verificação de { $sender }
+notification-payment-title =
+    { $days ->
+        [0] Conta vence hoje
+        [1] Conta vence amanhã
+       *[other] Conta vence em { $days } dias
+    }
+notification-payment-overdue-title = Conta vencida
+notification-payment-body = { $payee }: { $amount }
+notification-snooze-title = Um e-mail adiado voltou
+notification-snooze-body = "{ $subject }" de { $sender }
+notification-deadline-title = Prazo se aproximando
+notification-deadline-body = "{ $subject }" de { $sender } vence em { $when }
+notification-rule-delete-title = { $rule } quer excluir um e-mail
+notification-rule-delete-body = "{ $subject }" de { $sender }
+notification-hidden-title = Novo e-mail
+notification-hidden-body = Prévia oculta enquanto você apresenta
+notification-locked-title = SERINA
+lock-biometric-reason = Desbloquear o SERINA
+notification-focus-title = Sessão de foco encerrada
+notification-focus-body =
+    { $summary }, { $alerts ->
+        [0] nenhum outro alerta
+        [one] 1 outro alerta
+       *[other] { $alerts } outros alertas
+    }
+
+## Notification actions
+
+action-open = Abrir
+action-mark-read = Marcar como lido
+action-snooze = Adiar
+action-not-spam = Não é spam
+action-reply = Enviar resposta
+action-pin = Fixar
+action-retry-send = Tentar agora
+action-edit-reply = Editar
+action-discard-reply = Descartar
+action-copy-code = Copiar código
+action-mark-paid = Marcar como paga
+action-delete = Excluir
+action-reply-placeholder = Digite uma resposta
+
+## Dates
+
+# strftime pattern for absolute dates
+date-time-format = %d/%m/%Y %H:%M
+time-just-now = agora mesmo
+time-minutes-ago =
+    { $count ->
+        [one] há 1 minuto
+       *[other] há { $count } minutos
+    }
+time-hours-ago =
+    { $count ->
+        [one] há 1 hora
+       *[other] há { $count } horas
+    }
+time-yesterday = ontem
+time-days-ago = há { $count } dias
+
+## Links
+
+link-confirm-title = Abrir este link?
+link-confirm-body = Este link leva a { $host } e parece arriscado:
+link-risk-unresolved = É um link encurtado cujo destino não pôde ser verificado
+link-risk-ip-address = Usa um endereço IP em vez do nome de um site
+link-risk-punycode = O nome do site usa caracteres internacionais que imitam outros
+link-risk-insecure = A conexão não é criptografada
+link-risk-unusual-port = Usa uma porta incomum
+link-risk-mismatched-text = O texto mostra um site diferente do que abre
+
+## Confirmations
+
+confirm-title = Isso não pode ser desfeito
+confirm-delete-emails = Excluir permanentemente estes { $count } e-mails?
+confirm-purge-data = Apagar estes dados salvos neste computador?
+confirm-more = …e mais { $count }
+
+## Errors
+
+error-no-update = Nenhuma atualização disponível
+error-updates-off = As atualizações estão desativadas nesta versão
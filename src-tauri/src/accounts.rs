@@ -0,0 +1,117 @@
+// SERINA Account Sync
+// Per-account backoff and progress reporting for parallel mailbox sync
+
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// The Outlook profile's default inbox, used when no accounts are configured
+pub const DEFAULT_ACCOUNT: &str = "";
+
+/// Longest wait between retries of a failing account
+const MAX_BACKOFF_MINUTES: i64 = 60;
+
+/// Accounts to sync this round
+pub fn configured_accounts(accounts: &[String]) -> Vec<String> {
+    let accounts: Vec<String> = accounts
+        .iter()
+        .map(|account| account.trim().to_lowercase())
+        .filter(|account| !account.is_empty())
+        .collect();
+    if accounts.is_empty() {
+        vec![DEFAULT_ACCOUNT.to_string()]
+    } else {
+        accounts
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Failures {
+    count: u32,
+    retry_at: DateTime<Utc>,
+}
+
+/// Tracks failing accounts so one broken mailbox backs off without holding up the rest
+#[derive(Default)]
+pub struct AccountBackoff {
+    failures: Mutex<HashMap<String, Failures>>,
+}
+
+impl AccountBackoff {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_due(&self, account: &str, now: DateTime<Utc>) -> bool {
+        self.failures
+            .lock()
+            .unwrap()
+            .get(account)
+            .is_none_or(|failures| failures.retry_at <= now)
+    }
+
+    pub fn record_success(&self, account: &str) {
+        self.failures.lock().unwrap().remove(account);
+    }
+
+    /// Returns when the account will next be tried: 1, 2, 4... minutes, capped at an hour
+    pub fn record_failure(&self, account: &str, now: DateTime<Utc>) -> DateTime<Utc> {
+        let mut failures = self.failures.lock().unwrap();
+        let count = failures.get(account).map_or(0, |f| f.count) + 1;
+        let minutes = 1i64
+            .checked_shl(count.saturating_sub(1))
+            .unwrap_or(MAX_BACKOFF_MINUTES)
+            .min(MAX_BACKOFF_MINUTES);
+        let retry_at = now + Duration::minutes(minutes);
+        failures.insert(account.to_string(), Failures { count, retry_at });
+        retry_at
+    }
+}
+
+/// Emitted as `sync-progress` each time an account finishes
+#[derive(Debug, Clone, Serialize)]
+pub struct SyncProgress {
+    pub account: String,
+    pub completed: usize,
+    pub total: usize,
+    pub fetched: usize,
+    pub error: Option<String>,
+    /// When a failed account will be retried
+    pub retry_at: Option<DateTime<Utc>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_backoff() {
+        let backoff = AccountBackoff::new();
+        let now = Utc.with_ymd_and_hms(2024, 5, 1, 9, 0, 0).unwrap();
+        assert!(backoff.is_due("a@example.com", now));
+
+        let retry = backoff.record_failure("a@example.com", now);
+        assert_eq!(retry, now + Duration::minutes(1));
+        assert!(!backoff.is_due("a@example.com", now));
+        assert!(backoff.is_due("b@example.com", now));
+        backoff.record_failure("a@example.com", now);
+        assert_eq!(
+            backoff.record_failure("a@example.com", now),
+            now + Duration::minutes(4)
+        );
+        for _ in 0..40 {
+            backoff.record_failure("a@example.com", now);
+        }
+        assert!(backoff.is_due("a@example.com", now + Duration::minutes(60)));
+
+        backoff.record_success("a@example.com");
+        assert!(backoff.is_due("a@example.com", now));
+        assert_eq!(configured_accounts(&[]), vec![DEFAULT_ACCOUNT]);
+        assert_eq!(
+            configured_accounts(&[" Work@Example.com ".to_string(), String::new()]),
+            vec!["work@example.com"]
+        );
+    }
+}
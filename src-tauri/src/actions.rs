@@ -0,0 +1,157 @@
+// SERINA Action Items
+// To-dos pulled out of recent emails, merged across messages that ask for the same thing
+
+use crate::dates::parse_received_time;
+use crate::utils::EmailData;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Most cached emails looked at in one scan
+pub const MAX_SCANNED: usize = 100;
+/// Emails sent to the LLM together in one prompt
+pub const EMAILS_PER_PROMPT: usize = 8;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ActionScope {
+    /// Every unread email
+    #[default]
+    Unread,
+    /// Received in the last 24 hours
+    Today,
+    /// Received in the last 7 days
+    Week,
+}
+
+impl ActionScope {
+    pub fn includes(self, email: &EmailData, now: DateTime<Utc>) -> bool {
+        let within = |window: Duration| {
+            parse_received_time(&email.received_time).is_some_and(|time| now - time <= window)
+        };
+        match self {
+            ActionScope::Unread => email.is_unread,
+            ActionScope::Today => within(Duration::hours(24)),
+            ActionScope::Week => within(Duration::days(7)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ActionItem {
+    pub title: String,
+    /// "YYYY-MM-DD"
+    pub due: Option<String>,
+    /// Emails that asked for it, in the order they were scanned
+    pub email_ids: Vec<String>,
+}
+
+/// One entry of the JSON list returned by the action item prompt
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct ExtractedAction {
+    email_id: String,
+    action: String,
+    due: String,
+}
+
+/// Parse the action item prompt's response for `batch`; items citing emails that weren't
+/// in the batch are dropped
+pub fn parse_action_items(text: &str, batch: &[EmailData]) -> Result<Vec<ActionItem>, String> {
+    let extracted: Vec<ExtractedAction> = serde_json::from_str(crate::llm::strip_code_fence(text))
+        .map_err(|e| format!("Malformed action item response: {}", e))?;
+    Ok(extracted
+        .into_iter()
+        .filter(|item| !item.action.trim().is_empty())
+        .filter(|item| batch.iter().any(|email| email.id == item.email_id))
+        .map(|item| ActionItem {
+            title: item.action.trim().to_string(),
+            due: NaiveDate::parse_from_str(item.due.trim(), "%Y-%m-%d")
+                .ok()
+                .map(|date| date.to_string()),
+            email_ids: vec![item.email_id],
+        })
+        .collect())
+}
+
+/// Case and punctuation don't make two action items different
+fn dedupe_key(title: &str) -> String {
+    title
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Merge items asking for the same thing, keeping the earliest due date, and put dated
+/// items first, soonest first
+pub fn dedupe(items: Vec<ActionItem>) -> Vec<ActionItem> {
+    let mut merged: Vec<(String, ActionItem)> = Vec::new();
+    for item in items {
+        let key = dedupe_key(&item.title);
+        match merged.iter_mut().find(|(other, _)| *other == key) {
+            Some((_, existing)) => {
+                for id in item.email_ids {
+                    if !existing.email_ids.contains(&id) {
+                        existing.email_ids.push(id);
+                    }
+                }
+                existing.due = match (existing.due.take(), item.due) {
+                    (Some(a), Some(b)) => Some(a.min(b)),
+                    (a, b) => a.or(b),
+                };
+            }
+            None => merged.push((key, item)),
+        }
+    }
+    let mut items: Vec<ActionItem> = merged.into_iter().map(|(_, item)| item).collect();
+    items.sort_by(|a, b| match (&a.due, &b.due) {
+        (Some(a), Some(b)) => a.cmp(b),
+        (Some(_), None) => std::cmp::Ordering::Less,
+        (None, Some(_)) => std::cmp::Ordering::Greater,
+        (None, None) => std::cmp::Ordering::Equal,
+    });
+    items
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn email(id: &str) -> EmailData {
+        EmailData {
+            received_time: "2024-05-10T09:00:00Z".to_string(),
+            ..EmailData::sample(id)
+        }
+    }
+
+    #[test]
+    fn test_parse_and_dedupe() {
+        let batch = [email("a"), email("b")];
+        let response = r#"```json
+            [{"email_id": "a", "action": "Send the Q3 report", "due": "2024-05-17"},
+             {"email_id": "b", "action": "send the Q3 report!", "due": "2024-05-14"},
+             {"email_id": "b", "action": "Book travel", "due": "next week"},
+             {"email_id": "zzz", "action": "Made up", "due": ""}]
+            ```"#;
+        let items = dedupe(parse_action_items(response, &batch).unwrap());
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].title, "Send the Q3 report");
+        assert_eq!(items[0].due.as_deref(), Some("2024-05-14"));
+        assert_eq!(items[0].email_ids, ["a", "b"]);
+        assert_eq!(items[1].due, None);
+        assert!(parse_action_items("none", &batch).is_err());
+    }
+
+    #[test]
+    fn test_scope() {
+        let now = DateTime::parse_from_rfc3339("2024-05-12T09:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let mut read = email("a");
+        read.is_unread = false;
+        assert!(!ActionScope::Unread.includes(&read, now));
+        assert!(!ActionScope::Today.includes(&read, now));
+        assert!(ActionScope::Week.includes(&read, now));
+    }
+}
@@ -0,0 +1,216 @@
+// SERINA Activity Feed
+// Append-only stream of what happened to mail, powering the timeline and showing what automation did
+
+use crate::purge;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs::OpenOptions;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Events returned per page when the caller doesn't say
+pub const PAGE_SIZE: usize = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ActivityKind {
+    Received,
+    Summarized,
+    Replied,
+    /// A rule or script acted on an email
+    RuleFired,
+    /// What automation would have done to an email in observe-only mode
+    Observed,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ActivityEvent {
+    /// Position in the stream, one higher than the event before; pages are cut by it
+    pub seq: u64,
+    pub timestamp: DateTime<Utc>,
+    pub kind: ActivityKind,
+    #[serde(default)]
+    pub email_id: Option<String>,
+    /// What the timeline shows, usually the subject
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub details: serde_json::Value,
+}
+
+/// One page of the feed, newest first
+#[derive(Debug, Clone, Serialize)]
+pub struct ActivityPage {
+    pub events: Vec<ActivityEvent>,
+    /// Pass back as the cursor for the next, older page; `None` once the start is reached
+    pub next_cursor: Option<u64>,
+}
+
+struct Stream {
+    /// Sequence number of the next event
+    next_seq: u64,
+    /// Emails already recorded as received
+    received: HashSet<String>,
+}
+
+/// JSON-lines event stream; events are only ever appended
+pub struct ActivityLog {
+    path: PathBuf,
+    stream: Mutex<Stream>,
+}
+
+impl ActivityLog {
+    pub fn new(path: PathBuf) -> Self {
+        let events = read_events(&path);
+        let stream = Stream {
+            next_seq: events.last().map_or(1, |event| event.seq + 1),
+            received: events
+                .into_iter()
+                .filter(|event| event.kind == ActivityKind::Received)
+                .filter_map(|event| event.email_id)
+                .collect(),
+        };
+        Self {
+            path,
+            stream: Mutex::new(stream),
+        }
+    }
+
+    /// Add an event and return it. An email is recorded as received only once, however
+    /// often it reappears in the inbox after restarts; repeats return `None`.
+    pub fn append(
+        &self,
+        kind: ActivityKind,
+        email_id: Option<&str>,
+        title: &str,
+        details: serde_json::Value,
+    ) -> Result<Option<ActivityEvent>, String> {
+        let mut stream = self.stream.lock().unwrap();
+        if let (ActivityKind::Received, Some(id)) = (kind, email_id) {
+            if stream.received.contains(id) {
+                return Ok(None);
+            }
+        }
+        let event = ActivityEvent {
+            seq: stream.next_seq,
+            timestamp: Utc::now(),
+            kind,
+            email_id: email_id.map(str::to_string),
+            title: title.to_string(),
+            details,
+        };
+        let line = serde_json::to_string(&event).map_err(|e| e.to_string())?;
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| e.to_string())?;
+        writeln!(file, "{}", line).map_err(|e| e.to_string())?;
+        stream.next_seq += 1;
+        if let (ActivityKind::Received, Some(id)) = (kind, email_id) {
+            stream.received.insert(id.to_string());
+        }
+        Ok(Some(event))
+    }
+
+    /// Up to `limit` events older than `cursor`, or the newest ones without a cursor
+    pub fn page(&self, cursor: Option<u64>, limit: usize) -> ActivityPage {
+        let _guard = self.stream.lock().unwrap();
+        let mut events: Vec<ActivityEvent> = read_events(&self.path)
+            .into_iter()
+            .filter(|event| cursor.is_none_or(|cursor| event.seq < cursor))
+            .collect();
+        let older = events.len() > limit;
+        events.drain(..events.len().saturating_sub(limit));
+        events.reverse();
+        let next_cursor = events.last().filter(|_| older).map(|event| event.seq);
+        ActivityPage {
+            events,
+            next_cursor,
+        }
+    }
+
+    /// Drop events older than `cutoff`, for data retention; returns how many were dropped.
+    /// Later events keep their sequence numbers.
+    pub fn prune_before(&self, cutoff: DateTime<Utc>) -> Result<usize, String> {
+        let _guard = self.stream.lock().unwrap();
+        purge::prune_log(&self.path, cutoff)
+    }
+
+    /// Securely delete the whole feed; returns how many events it held
+    pub fn clear(&self) -> Result<usize, String> {
+        let mut stream = self.stream.lock().unwrap();
+        let count = read_events(&self.path).len();
+        purge::secure_delete(&self.path)?;
+        stream.received.clear();
+        Ok(count)
+    }
+}
+
+/// Every readable event, oldest first; unreadable lines are skipped
+fn read_events(path: &Path) -> Vec<ActivityEvent> {
+    let Ok(file) = std::fs::File::open(path) else {
+        return Vec::new();
+    };
+    BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_str(&line).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paging() {
+        let path = std::env::temp_dir().join(format!("serina-activity-{}.log", std::process::id()));
+        let log = ActivityLog::new(path.clone());
+        assert!(log.page(None, 10).events.is_empty());
+        for n in 1..=5 {
+            let event = log
+                .append(
+                    ActivityKind::Received,
+                    Some(&n.to_string()),
+                    "Hello",
+                    serde_json::Value::Null,
+                )
+                .unwrap();
+            assert_eq!(event.unwrap().seq, n);
+        }
+
+        let first = log.page(None, 2);
+        let seqs = |page: &ActivityPage| page.events.iter().map(|e| e.seq).collect::<Vec<_>>();
+        assert_eq!(seqs(&first), vec![5, 4]);
+        assert_eq!(first.next_cursor, Some(4));
+        let last = log.page(Some(2), 2);
+        assert_eq!(seqs(&last), vec![1]);
+        assert_eq!(last.next_cursor, None);
+
+        // Numbering carries on after a restart, and emails seen before stay recorded
+        let reopened = ActivityLog::new(path.clone());
+        let again = reopened.append(
+            ActivityKind::Received,
+            Some("3"),
+            "Hello",
+            serde_json::Value::Null,
+        );
+        assert_eq!(again, Ok(None));
+        let event = reopened
+            .append(
+                ActivityKind::Replied,
+                Some("3"),
+                "Re: Hello",
+                serde_json::Value::Null,
+            )
+            .unwrap();
+        assert_eq!(event.unwrap().seq, 6);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,192 @@
+// SERINA Inbox Analytics
+// Personal stats from the email cache and the audit log: volume, busiest senders and how
+// quickly replies go out
+
+use crate::audit::{AuditAction, AuditEntry};
+use crate::dates::parse_received_time;
+use crate::utils::EmailData;
+use chrono::{DateTime, Datelike, Duration, TimeZone, Timelike, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// Most cached emails read for one report
+pub const MAX_ANALYZED: usize = 5000;
+const BUSIEST_SENDERS: usize = 10;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnalyticsPeriod {
+    Day,
+    #[default]
+    Week,
+    Month,
+}
+
+impl AnalyticsPeriod {
+    pub fn duration(self) -> Duration {
+        match self {
+            AnalyticsPeriod::Day => Duration::days(1),
+            AnalyticsPeriod::Week => Duration::days(7),
+            AnalyticsPeriod::Month => Duration::days(30),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SenderCount {
+    pub sender: String,
+    pub sender_email: String,
+    pub count: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct InboxAnalytics {
+    pub period: AnalyticsPeriod,
+    pub received: usize,
+    pub unread: usize,
+    pub replied: usize,
+    pub unreplied: usize,
+    /// From arrival to the first reply sent through SERINA
+    pub average_response_minutes: Option<f64>,
+    pub busiest_senders: Vec<SenderCount>,
+    /// Emails received in each hour of the day, local time
+    pub by_hour: [usize; 24],
+    /// Emails received on each day of the week, Monday first
+    pub by_weekday: [usize; 7],
+}
+
+/// Stats for emails received in the `period` before `now`. `replies` are the audit log's
+/// sent replies; hours and weekdays are counted in `tz`.
+pub fn compute<Tz: TimeZone>(
+    emails: &[EmailData],
+    replies: &[AuditEntry],
+    period: AnalyticsPeriod,
+    now: DateTime<Utc>,
+    tz: &Tz,
+) -> InboxAnalytics {
+    let since = now - period.duration();
+    let mut first_reply: HashMap<&str, DateTime<Utc>> = HashMap::new();
+    for entry in replies
+        .iter()
+        .filter(|entry| entry.action == AuditAction::ReplySent)
+    {
+        if let Some(id) = &entry.email_id {
+            let time = first_reply.entry(id.as_str()).or_insert(entry.timestamp);
+            *time = (*time).min(entry.timestamp);
+        }
+    }
+
+    let mut stats = InboxAnalytics {
+        period,
+        received: 0,
+        unread: 0,
+        replied: 0,
+        unreplied: 0,
+        average_response_minutes: None,
+        busiest_senders: Vec::new(),
+        by_hour: [0; 24],
+        by_weekday: [0; 7],
+    };
+    let mut seen_messages = HashSet::new();
+    let mut senders: HashMap<String, SenderCount> = HashMap::new();
+    let mut response_minutes = Vec::new();
+
+    for email in emails {
+        let Some(received) = parse_received_time(&email.received_time) else {
+            continue;
+        };
+        if received < since || received > now {
+            continue;
+        }
+        // The same message delivered twice counts once
+        if !email.message_id.is_empty() && !seen_messages.insert(email.message_id.as_str()) {
+            continue;
+        }
+
+        stats.received += 1;
+        if email.is_unread {
+            stats.unread += 1;
+        }
+        let local = received.with_timezone(tz);
+        stats.by_hour[local.hour() as usize] += 1;
+        stats.by_weekday[local.weekday().num_days_from_monday() as usize] += 1;
+
+        let sender = senders
+            .entry(email.sender_email.to_lowercase())
+            .or_insert_with(|| SenderCount {
+                sender: email.sender.clone(),
+                sender_email: email.sender_email.clone(),
+                count: 0,
+            });
+        sender.count += 1;
+
+        match first_reply.get(email.id.as_str()) {
+            Some(reply) => {
+                stats.replied += 1;
+                if *reply >= received {
+                    response_minutes.push((*reply - received).num_seconds() as f64 / 60.0);
+                }
+            }
+            None => stats.unreplied += 1,
+        }
+    }
+
+    if !response_minutes.is_empty() {
+        stats.average_response_minutes =
+            Some(response_minutes.iter().sum::<f64>() / response_minutes.len() as f64);
+    }
+    let mut busiest: Vec<SenderCount> = senders.into_values().collect();
+    busiest.sort_by(|a, b| {
+        b.count
+            .cmp(&a.count)
+            .then_with(|| a.sender_email.cmp(&b.sender_email))
+    });
+    busiest.truncate(BUSIEST_SENDERS);
+    stats.busiest_senders = busiest;
+    stats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::Origin;
+
+    fn email(id: &str, sender: &str, received: &str) -> EmailData {
+        EmailData {
+            message_id: format!("<{}@example.com>", id),
+            sender: sender.to_string(),
+            sender_email: format!("{}@example.com", sender),
+            received_time: received.to_string(),
+            is_unread: false,
+            ..EmailData::sample(id)
+        }
+    }
+
+    #[test]
+    fn test_compute() {
+        let now = Utc.with_ymd_and_hms(2024, 5, 10, 12, 0, 0).unwrap();
+        let mut duplicate = email("b", "jane", "2024-05-09T09:00:00Z");
+        duplicate.id = "b2".to_string();
+        let emails = [
+            email("a", "jane", "2024-05-10T09:00:00Z"),
+            email("b", "jane", "2024-05-09T09:00:00Z"),
+            duplicate,
+            email("c", "sam", "2024-05-06T15:30:00Z"),
+            email("old", "sam", "2024-04-01T09:00:00Z"),
+        ];
+        let mut reply = AuditEntry::new(AuditAction::ReplySent, Origin::User, Some("a".into()));
+        reply.timestamp = Utc.with_ymd_and_hms(2024, 5, 10, 10, 30, 0).unwrap();
+        let mut later = reply.clone();
+        later.timestamp = Utc.with_ymd_and_hms(2024, 5, 10, 11, 0, 0).unwrap();
+
+        let stats = compute(&emails, &[later, reply], AnalyticsPeriod::Week, now, &Utc);
+        assert_eq!(stats.received, 3);
+        assert_eq!((stats.replied, stats.unreplied), (1, 2));
+        assert_eq!(stats.average_response_minutes, Some(90.0));
+        assert_eq!(stats.busiest_senders[0].sender, "jane");
+        assert_eq!(stats.busiest_senders[0].count, 2);
+        assert_eq!(stats.by_hour[9], 2);
+        // Friday, Thursday and Monday
+        assert_eq!(stats.by_weekday, [1, 0, 0, 1, 1, 0, 0]);
+    }
+}
@@ -0,0 +1,291 @@
+// SERINA App Lock
+// Optional PIN (or OS biometric) gate in front of every window, on launch and after idling
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Hash rounds, so a copied lock file can't be brute-forced quickly
+const PIN_ROUNDS: u32 = if cfg!(test) { 10 } else { 100_000 };
+/// Wrong PINs allowed before each further attempt has to wait
+const FREE_ATTEMPTS: u32 = 5;
+const RETRY_DELAY: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppLockConfig {
+    pub enabled: bool,
+    pub lock_on_launch: bool,
+    /// Lock after this many minutes without keyboard or mouse input (0 = never)
+    pub idle_minutes: u32,
+    /// Offer Windows Hello, Touch ID or a fingerprint reader before asking for the PIN
+    pub biometric: bool,
+}
+
+impl Default for AppLockConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            lock_on_launch: true,
+            idle_minutes: 10,
+            biometric: true,
+        }
+    }
+}
+
+impl AppLockConfig {
+    /// Whether `idle` time away from the keyboard should lock the app
+    pub fn idle_expired(&self, idle: Option<Duration>) -> bool {
+        self.enabled
+            && self.idle_minutes > 0
+            && idle.is_some_and(|idle| idle >= Duration::from_secs(self.idle_minutes as u64 * 60))
+    }
+}
+
+/// What the lock screen and settings need to know
+#[derive(Debug, Clone, Serialize)]
+pub struct LockStatus {
+    pub locked: bool,
+    pub pin_set: bool,
+    pub biometric_available: bool,
+}
+
+/// 4 to 12 digits
+pub fn validate_pin(pin: &str) -> Result<(), String> {
+    if !(4..=12).contains(&pin.len()) || !pin.chars().all(|c| c.is_ascii_digit()) {
+        return Err("The PIN must be 4 to 12 digits".to_string());
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PinHash {
+    salt: String,
+    hash: String,
+}
+
+impl PinHash {
+    fn new(pin: &str) -> Self {
+        let mut salt = [0u8; 16];
+        crate::encryption::random_bytes(&mut salt);
+        let salt = to_hex(&salt);
+        Self {
+            hash: hash_pin(&salt, pin),
+            salt,
+        }
+    }
+
+    fn matches(&self, pin: &str) -> bool {
+        let hash = hash_pin(&self.salt, pin);
+        // Compare every byte so timing doesn't reveal how much matched
+        hash.len() == self.hash.len()
+            && hash
+                .bytes()
+                .zip(self.hash.bytes())
+                .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+                == 0
+    }
+}
+
+fn hash_pin(salt: &str, pin: &str) -> String {
+    let mut digest = Sha256::new()
+        .chain_update(salt)
+        .chain_update(pin)
+        .finalize();
+    for _ in 1..PIN_ROUNDS {
+        digest = Sha256::new()
+            .chain_update(digest)
+            .chain_update(pin)
+            .finalize();
+    }
+    to_hex(&digest)
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[derive(Default)]
+struct LockState {
+    locked: bool,
+    failures: u32,
+    retry_after: Option<Instant>,
+}
+
+/// The PIN (persisted) and whether the app is currently locked (in memory)
+pub struct AppLock {
+    path: Option<PathBuf>,
+    pin: Mutex<Option<PinHash>>,
+    state: Mutex<LockState>,
+}
+
+impl AppLock {
+    pub fn load(path: PathBuf) -> Self {
+        let pin = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok());
+        Self {
+            path: Some(path),
+            pin: Mutex::new(pin),
+            state: Mutex::new(LockState::default()),
+        }
+    }
+
+    pub fn in_memory() -> Self {
+        Self {
+            path: None,
+            pin: Mutex::new(None),
+            state: Mutex::new(LockState::default()),
+        }
+    }
+
+    pub fn pin_set(&self) -> bool {
+        self.pin.lock().unwrap().is_some()
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.state.lock().unwrap().locked
+    }
+
+    /// Set, change or (with `None`) remove the PIN. Changing or removing one needs the current PIN.
+    pub fn set_pin(&self, pin: Option<&str>, current: Option<&str>) -> Result<(), String> {
+        if let Some(pin) = pin {
+            validate_pin(pin)?;
+        }
+        let mut stored = self.pin.lock().unwrap();
+        if let Some(existing) = stored.as_ref() {
+            if !current.is_some_and(|current| existing.matches(current)) {
+                return Err("The current PIN is wrong".to_string());
+            }
+        }
+        let updated = pin.map(PinHash::new);
+        if let Some(path) = &self.path {
+            match &updated {
+                Some(hash) => {
+                    if let Some(parent) = path.parent() {
+                        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+                    }
+                    let contents = serde_json::to_string(hash).map_err(|e| e.to_string())?;
+                    std::fs::write(path, contents).map_err(|e| e.to_string())?;
+                }
+                None => {
+                    if let Err(e) = std::fs::remove_file(path) {
+                        if e.kind() != std::io::ErrorKind::NotFound {
+                            return Err(e.to_string());
+                        }
+                    }
+                }
+            }
+        }
+        *stored = updated;
+        if stored.is_none() {
+            self.state.lock().unwrap().locked = false;
+        }
+        Ok(())
+    }
+
+    /// Lock now; without a PIN there is no way back in, so nothing happens. Returns whether the
+    /// app went from unlocked to locked.
+    pub fn lock(&self) -> bool {
+        if !self.pin_set() {
+            return false;
+        }
+        let mut state = self.state.lock().unwrap();
+        let newly = !state.locked;
+        state.locked = true;
+        newly
+    }
+
+    /// Unlock after the OS confirmed the user, e.g. with a fingerprint
+    pub fn unlock(&self) {
+        let mut state = self.state.lock().unwrap();
+        *state = LockState::default();
+    }
+
+    pub fn unlock_with_pin(&self, pin: &str, now: Instant) -> Result<(), String> {
+        let mut state = self.state.lock().unwrap();
+        if let Some(retry_after) = state.retry_after {
+            if now < retry_after {
+                let wait = (retry_after - now).as_secs().max(1);
+                return Err(format!("Too many wrong PINs. Try again in {} s", wait));
+            }
+        }
+        let matches = self
+            .pin
+            .lock()
+            .unwrap()
+            .as_ref()
+            .is_some_and(|hash| hash.matches(pin));
+        if matches {
+            *state = LockState::default();
+            return Ok(());
+        }
+        state.failures += 1;
+        if state.failures >= FREE_ATTEMPTS {
+            state.retry_after = Some(now + RETRY_DELAY);
+        }
+        Err("Wrong PIN".to_string())
+    }
+
+    pub fn status(&self, biometric_available: bool) -> LockStatus {
+        LockStatus {
+            locked: self.is_locked(),
+            pin_set: self.pin_set(),
+            biometric_available,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pin_unlock_and_throttle() {
+        let lock = AppLock::in_memory();
+        assert!(!lock.lock());
+        assert!(validate_pin("12a4").is_err());
+
+        lock.set_pin(Some("2468"), None).unwrap();
+        assert!(lock.set_pin(Some("1357"), Some("0000")).is_err());
+        assert!(lock.lock());
+        assert!(!lock.lock());
+
+        let now = Instant::now();
+        assert_eq!(lock.unlock_with_pin("2468", now), Ok(()));
+        assert!(!lock.is_locked());
+
+        lock.lock();
+        for _ in 0..FREE_ATTEMPTS {
+            assert_eq!(
+                lock.unlock_with_pin("0000", now),
+                Err("Wrong PIN".to_string())
+            );
+        }
+        // Even the right PIN waits out the delay
+        assert!(lock.unlock_with_pin("2468", now).is_err());
+        assert!(lock.unlock_with_pin("2468", now + RETRY_DELAY).is_ok());
+
+        lock.set_pin(None, Some("2468")).unwrap();
+        assert!(!lock.pin_set());
+    }
+
+    #[test]
+    fn test_idle_expired() {
+        let config = AppLockConfig {
+            enabled: true,
+            idle_minutes: 5,
+            ..Default::default()
+        };
+        assert!(config.idle_expired(Some(Duration::from_secs(300))));
+        assert!(!config.idle_expired(Some(Duration::from_secs(299))));
+        assert!(!config.idle_expired(None));
+        let never = AppLockConfig {
+            idle_minutes: 0,
+            ..config
+        };
+        assert!(!never.idle_expired(Some(Duration::from_secs(3600))));
+    }
+}
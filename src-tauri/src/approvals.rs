@@ -0,0 +1,203 @@
+// SERINA Send Approvals
+// Replies written by automation wait here until the user approves or rejects them, unless the
+// recipient is trusted; unanswered ones expire rather than going out late
+
+use crate::utils::EmailData;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ApprovalConfig {
+    /// Recipients (addresses or `@domain`) automated replies go to without approval
+    pub trusted_recipients: Vec<String>,
+    /// A reply nobody approves within this many hours is dropped
+    pub expire_hours: u32,
+}
+
+impl Default for ApprovalConfig {
+    fn default() -> Self {
+        Self {
+            trusted_recipients: Vec::new(),
+            expire_hours: 24,
+        }
+    }
+}
+
+impl ApprovalConfig {
+    /// Whether an automated reply to `recipient` has to wait for the user
+    pub fn needs_approval(&self, recipient: &str) -> bool {
+        !crate::spam::is_vip(recipient, &self.trusted_recipients)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PendingApproval {
+    pub id: u64,
+    /// The email being replied to
+    pub email_id: String,
+    pub recipient: String,
+    pub subject: String,
+    pub reply_text: String,
+    /// What wrote the reply, e.g. "auto_reply"
+    pub source: String,
+    pub queued_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Automated replies waiting for the user, saved on every change so they survive a restart
+pub struct ApprovalQueue {
+    path: Option<PathBuf>,
+    pending: Mutex<Vec<PendingApproval>>,
+}
+
+impl ApprovalQueue {
+    pub fn load(path: PathBuf) -> Self {
+        let pending = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+        Self {
+            path: Some(path),
+            pending: Mutex::new(pending),
+        }
+    }
+
+    pub fn in_memory() -> Self {
+        Self {
+            path: None,
+            pending: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Oldest first
+    pub fn list(&self) -> Vec<PendingApproval> {
+        self.pending.lock().unwrap().clone()
+    }
+
+    /// Hold a reply to `email` for approval. Returns `None` when one is already waiting for it.
+    pub fn add(
+        &self,
+        email: &EmailData,
+        reply_text: &str,
+        source: &str,
+        config: &ApprovalConfig,
+        now: DateTime<Utc>,
+    ) -> Result<Option<PendingApproval>, String> {
+        let mut pending = self.pending.lock().unwrap();
+        if pending.iter().any(|reply| reply.email_id == email.id) {
+            return Ok(None);
+        }
+        let id = pending.iter().map(|reply| reply.id).max().unwrap_or(0) + 1;
+        let reply = PendingApproval {
+            id,
+            email_id: email.id.clone(),
+            recipient: email.sender_email.clone(),
+            subject: email.subject.clone(),
+            reply_text: reply_text.to_string(),
+            source: source.to_string(),
+            queued_at: now,
+            expires_at: now + Duration::hours(config.expire_hours.max(1) as i64),
+        };
+        pending.push(reply.clone());
+        self.persist(&pending)?;
+        Ok(Some(reply))
+    }
+
+    /// Take a reply off the queue to send it. Expired replies are dropped instead.
+    pub fn take(&self, id: u64, now: DateTime<Utc>) -> Result<PendingApproval, String> {
+        let reply = self
+            .remove(id)?
+            .ok_or_else(|| "This reply is no longer waiting for approval".to_string())?;
+        if reply.expires_at <= now {
+            return Err("This reply expired before it was approved".to_string());
+        }
+        Ok(reply)
+    }
+
+    pub fn remove(&self, id: u64) -> Result<Option<PendingApproval>, String> {
+        let mut pending = self.pending.lock().unwrap();
+        let Some(index) = pending.iter().position(|reply| reply.id == id) else {
+            return Ok(None);
+        };
+        let reply = pending.remove(index);
+        self.persist(&pending)?;
+        Ok(Some(reply))
+    }
+
+    /// Drop replies nobody approved in time and return them
+    pub fn expire(&self, now: DateTime<Utc>) -> Result<Vec<PendingApproval>, String> {
+        let mut pending = self.pending.lock().unwrap();
+        let (expired, waiting): (Vec<_>, Vec<_>) =
+            pending.drain(..).partition(|reply| reply.expires_at <= now);
+        *pending = waiting;
+        if !expired.is_empty() {
+            self.persist(&pending)?;
+        }
+        Ok(expired)
+    }
+
+    fn persist(&self, pending: &[PendingApproval]) -> Result<(), String> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let contents = serde_json::to_string_pretty(pending).map_err(|e| e.to_string())?;
+        std::fs::write(path, contents).map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_approval_queue() {
+        let path =
+            std::env::temp_dir().join(format!("serina-approvals-{}.json", std::process::id()));
+        let queue = ApprovalQueue::load(path.clone());
+        let config = ApprovalConfig::default();
+        let now = Utc.with_ymd_and_hms(2026, 10, 5, 9, 0, 0).unwrap();
+        let add = |id: &str| {
+            let email = EmailData {
+                subject: "Hi".to_string(),
+                sender: "Ana".to_string(),
+                sender_email: "ana@example.com".to_string(),
+                received_time: "2026-10-05T08:00:00Z".to_string(),
+                ..EmailData::sample(id)
+            };
+            queue
+                .add(&email, "I'm away", "auto_reply", &config, now)
+                .unwrap()
+        };
+        let first = add("1").unwrap();
+        assert!(add("1").is_none());
+        let second = add("2").unwrap();
+
+        // Waiting replies survive a restart
+        assert_eq!(ApprovalQueue::load(path.clone()).list().len(), 2);
+
+        assert_eq!(queue.take(first.id, now).unwrap().email_id, "1");
+        assert!(queue.take(first.id, now).is_err());
+        let later = now + Duration::hours(24);
+        assert_eq!(queue.expire(later).unwrap(), vec![second]);
+        assert!(queue.list().is_empty());
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_trusted_recipients_skip_approval() {
+        let config = ApprovalConfig {
+            trusted_recipients: vec!["@example.com".to_string()],
+            ..Default::default()
+        };
+        assert!(!config.needs_approval("Ana@Example.com"));
+        assert!(config.needs_approval("ana@example.org"));
+        assert!(ApprovalConfig::default().needs_approval("ana@example.com"));
+    }
+}
@@ -0,0 +1,90 @@
+// SERINA Attachments
+// Attachments are streamed to disk and handed to the webview by path, never as base64 over IPC
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Downloads older than this are removed during maintenance
+pub const MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AttachmentInfo {
+    /// Outlook's 1-based position of the attachment on the message
+    pub index: u32,
+    pub name: String,
+    pub size: u64,
+}
+
+/// Where downloads go; `tauri.conf.json` scopes the asset protocol to this directory
+pub fn attachment_dir() -> PathBuf {
+    std::env::temp_dir().join("serina-attachments")
+}
+
+/// A file name that stays inside its directory: separators, control characters and leading dots
+/// are replaced
+pub fn safe_file_name(name: &str) -> String {
+    let cleaned: String = name
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+    let cleaned = cleaned.trim().trim_start_matches('.');
+    if cleaned.is_empty() {
+        "attachment".to_string()
+    } else {
+        cleaned.to_string()
+    }
+}
+
+/// Where one attachment is saved. Outlook entry ids are long, so each email's folder is named
+/// after a short hash of its id.
+pub fn attachment_path(dir: &Path, email_id: &str, index: u32, name: &str) -> PathBuf {
+    let digest = Sha256::digest(email_id.as_bytes());
+    let folder: String = digest[..8].iter().map(|b| format!("{:02x}", b)).collect();
+    dir.join(format!("{}-{}", folder, index))
+        .join(safe_file_name(name))
+}
+
+/// Remove download folders last touched more than `max_age` ago; returns how many went
+pub fn purge_older_than(dir: &Path, max_age: Duration) -> usize {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .filter(|entry| {
+            entry
+                .metadata()
+                .and_then(|meta| meta.modified())
+                .is_ok_and(|modified| modified.elapsed().unwrap_or_default() > max_age)
+        })
+        .filter(|entry| std::fs::remove_dir_all(entry.path()).is_ok())
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_attachment_path() {
+        assert_eq!(safe_file_name("Q3 forecast.xlsx"), "Q3 forecast.xlsx");
+        assert_eq!(safe_file_name("../../etc/passwd"), "_.._etc_passwd");
+        assert_eq!(safe_file_name("..\\boot.ini"), "_boot.ini");
+        assert_eq!(safe_file_name("  "), "attachment");
+
+        let dir = Path::new("/tmp/serina-attachments");
+        let path = attachment_path(dir, "00000000ABCDEF", 2, "../report.pdf");
+        assert_eq!(path.parent().unwrap().parent().unwrap(), dir);
+        assert_eq!(path.file_name().unwrap(), "_report.pdf");
+        assert_ne!(
+            attachment_path(dir, "00000000ABCDEF", 1, "report.pdf"),
+            attachment_path(dir, "00000000ABCDF0", 1, "report.pdf")
+        );
+    }
+}
@@ -0,0 +1,221 @@
+// SERINA Audit Log
+// Append-only record of every mutating action taken on emails
+
+use crate::purge;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::OpenOptions;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditAction {
+    ReplySent,
+    MarkedRead,
+    TaskCreated,
+    Snoozed,
+    RuleApplied,
+    MarkedNotSpam,
+    Archived,
+    Deleted,
+    MeetingScheduled,
+    /// A new message, not a reply
+    EmailSent,
+    /// Taken or handed back in a shared mailbox
+    Claimed,
+    Released,
+}
+
+/// Who triggered an action
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Origin {
+    User,
+    Rule,
+    Llm,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub timestamp: DateTime<Utc>,
+    pub action: AuditAction,
+    pub origin: Origin,
+    pub email_id: Option<String>,
+    #[serde(default)]
+    pub details: serde_json::Value,
+}
+
+impl AuditEntry {
+    pub fn new(action: AuditAction, origin: Origin, email_id: Option<String>) -> Self {
+        Self {
+            timestamp: Utc::now(),
+            action,
+            origin,
+            email_id,
+            details: serde_json::Value::Null,
+        }
+    }
+
+    pub fn with_details(mut self, details: serde_json::Value) -> Self {
+        self.details = details;
+        self
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AuditFilter {
+    pub action: Option<AuditAction>,
+    pub origin: Option<Origin>,
+    pub email_id: Option<String>,
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+    /// Return at most this many of the newest entries
+    pub limit: Option<usize>,
+}
+
+impl AuditFilter {
+    pub fn matches(&self, entry: &AuditEntry) -> bool {
+        self.action.is_none_or(|a| a == entry.action)
+            && self.origin.is_none_or(|o| o == entry.origin)
+            && self
+                .email_id
+                .as_ref()
+                .is_none_or(|id| entry.email_id.as_ref() == Some(id))
+            && self.since.is_none_or(|t| entry.timestamp >= t)
+            && self.until.is_none_or(|t| entry.timestamp <= t)
+    }
+}
+
+/// JSON-lines audit log; entries are only ever appended
+pub struct AuditLog {
+    path: PathBuf,
+    write_lock: Mutex<()>,
+}
+
+impl AuditLog {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            write_lock: Mutex::new(()),
+        }
+    }
+
+    pub fn append(&self, entry: &AuditEntry) -> Result<(), String> {
+        let line = serde_json::to_string(entry).map_err(|e| e.to_string())?;
+        let _guard = self.write_lock.lock().unwrap();
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| e.to_string())?;
+        writeln!(file, "{}", line).map_err(|e| e.to_string())
+    }
+
+    /// Entries matching `filter`, oldest first; unreadable lines are skipped
+    pub fn query(&self, filter: &AuditFilter) -> Result<Vec<AuditEntry>, String> {
+        let file = match std::fs::File::open(&self.path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.to_string()),
+        };
+
+        let mut entries: Vec<AuditEntry> = BufReader::new(file)
+            .lines()
+            .map_while(Result::ok)
+            .filter_map(|line| serde_json::from_str(&line).ok())
+            .filter(|entry| filter.matches(entry))
+            .collect();
+
+        if let Some(limit) = filter.limit {
+            let skip = entries.len().saturating_sub(limit);
+            entries.drain(..skip);
+        }
+        Ok(entries)
+    }
+
+    /// Drop entries older than `cutoff`, for data retention; returns how many were dropped
+    pub fn prune_before(&self, cutoff: DateTime<Utc>) -> Result<usize, String> {
+        let _guard = self.write_lock.lock().unwrap();
+        purge::prune_log(&self.path, cutoff)
+    }
+
+    /// Securely delete the whole log; returns how many entries it held
+    pub fn clear(&self) -> Result<usize, String> {
+        let count = self.query(&AuditFilter::default())?.len();
+        let _guard = self.write_lock.lock().unwrap();
+        purge::secure_delete(&self.path)?;
+        Ok(count)
+    }
+
+    /// Write matching entries to `dest` as a JSON array; returns how many were exported
+    pub fn export(&self, filter: &AuditFilter, dest: &Path) -> Result<usize, String> {
+        let entries = self.query(filter)?;
+        let contents = serde_json::to_string_pretty(&entries).map_err(|e| e.to_string())?;
+        std::fs::write(dest, contents).map_err(|e| e.to_string())?;
+        Ok(entries.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_append_and_query() {
+        let dir = std::env::temp_dir().join(format!("serina-audit-{}", std::process::id()));
+        let log = AuditLog::new(dir.join("audit.log"));
+
+        assert!(log.query(&AuditFilter::default()).unwrap().is_empty());
+
+        log.append(&AuditEntry::new(
+            AuditAction::ReplySent,
+            Origin::User,
+            Some("a".to_string()),
+        ))
+        .unwrap();
+        log.append(&AuditEntry::new(
+            AuditAction::MarkedRead,
+            Origin::Rule,
+            Some("b".to_string()),
+        ))
+        .unwrap();
+        log.append(&AuditEntry::new(
+            AuditAction::MarkedRead,
+            Origin::User,
+            Some("a".to_string()),
+        ))
+        .unwrap();
+
+        let all = log.query(&AuditFilter::default()).unwrap();
+        assert_eq!(all.len(), 3);
+
+        let by_rule = AuditFilter {
+            origin: Some(Origin::Rule),
+            ..Default::default()
+        };
+        assert_eq!(
+            log.query(&by_rule).unwrap()[0].email_id.as_deref(),
+            Some("b")
+        );
+
+        let latest = AuditFilter {
+            email_id: Some("a".to_string()),
+            limit: Some(1),
+            ..Default::default()
+        };
+        let latest = log.query(&latest).unwrap();
+        assert_eq!(latest.len(), 1);
+        assert_eq!(latest[0].action, AuditAction::MarkedRead);
+
+        let exported = dir.join("export.json");
+        assert_eq!(log.export(&AuditFilter::default(), &exported).unwrap(), 3);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,226 @@
+// SERINA Auto-Reply
+// Out-of-office responder rules, evaluated by the background scheduler
+
+use crate::dates;
+use crate::utils::EmailData;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AutoReplyConfig {
+    pub enabled: bool,
+    /// First day the responder is active (inclusive)
+    pub start_date: Option<NaiveDate>,
+    /// Last day the responder is active (inclusive)
+    pub end_date: Option<NaiveDate>,
+    pub body: String,
+    /// Id of a saved template to send instead of `body`
+    pub template: Option<String>,
+    /// Reply to the same sender at most once in this many days
+    pub resend_after_days: u32,
+    /// Sender addresses containing any of these are never answered
+    pub skip_senders: Vec<String>,
+    /// When non-empty, only senders from these domains are answered
+    pub only_domains: Vec<String>,
+}
+
+impl Default for AutoReplyConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            start_date: None,
+            end_date: None,
+            body: String::new(),
+            template: None,
+            resend_after_days: 4,
+            skip_senders: [
+                "noreply",
+                "no-reply",
+                "donotreply",
+                "mailer-daemon",
+                "postmaster",
+            ]
+            .iter()
+            .map(|s| s.to_string())
+            .collect(),
+            only_domains: Vec::new(),
+        }
+    }
+}
+
+impl AutoReplyConfig {
+    /// Enabled, has a body or template, and `today` falls inside the configured date range
+    pub fn is_active(&self, today: NaiveDate) -> bool {
+        self.enabled
+            && (!self.body.trim().is_empty() || self.template.is_some())
+            && self.covers(today)
+    }
+
+    /// Whether `day` falls inside the configured date range
+    pub fn covers(&self, day: NaiveDate) -> bool {
+        self.start_date.is_none_or(|start| day >= start)
+            && self.end_date.is_none_or(|end| day <= end)
+    }
+
+    /// Whether a sender matches the responder's criteria
+    pub fn accepts_sender(&self, sender_email: &str) -> bool {
+        let sender = sender_email.trim().to_lowercase();
+        if !sender.contains('@') {
+            return false;
+        }
+        if self
+            .skip_senders
+            .iter()
+            .any(|skip| sender.contains(&skip.to_lowercase()))
+        {
+            return false;
+        }
+        if self.only_domains.is_empty() {
+            return true;
+        }
+        let domain = sender.rsplit('@').next().unwrap_or_default();
+        self.only_domains
+            .iter()
+            .any(|d| d.trim_start_matches('@').eq_ignore_ascii_case(domain))
+    }
+}
+
+/// Tracks when each sender last got an auto-reply
+pub struct AutoResponder {
+    path: Option<PathBuf>,
+    last_sent: Mutex<HashMap<String, DateTime<Utc>>>,
+}
+
+impl AutoResponder {
+    pub fn load(path: PathBuf) -> Self {
+        let last_sent = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+        Self {
+            path: Some(path),
+            last_sent: Mutex::new(last_sent),
+        }
+    }
+
+    pub fn in_memory() -> Self {
+        Self {
+            path: None,
+            last_sent: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Decide whether `email` should get an auto-reply now. Mail received outside the date
+    /// range, or with no readable received time, never does.
+    pub fn should_reply(
+        &self,
+        config: &AutoReplyConfig,
+        email: &EmailData,
+        now: DateTime<Utc>,
+    ) -> bool {
+        if !config.is_active(now.date_naive()) || !config.accepts_sender(&email.sender_email) {
+            return false;
+        }
+        let received = dates::parse_received_time(&email.received_time);
+        if !received.is_some_and(|received| config.covers(received.date_naive())) {
+            return false;
+        }
+        let last_sent = self.last_sent.lock().unwrap();
+        match last_sent.get(&email.sender_email.trim().to_lowercase()) {
+            Some(sent) => now - *sent >= Duration::days(config.resend_after_days as i64),
+            None => true,
+        }
+    }
+
+    pub fn record(&self, sender_email: &str, now: DateTime<Utc>) {
+        let mut last_sent = self.last_sent.lock().unwrap();
+        last_sent.insert(sender_email.trim().to_lowercase(), now);
+        if let Some(path) = &self.path {
+            if let Ok(contents) = serde_json::to_string_pretty(&*last_sent) {
+                let _ = std::fs::write(path, contents);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> AutoReplyConfig {
+        AutoReplyConfig {
+            enabled: true,
+            start_date: NaiveDate::from_ymd_opt(2026, 10, 1),
+            end_date: NaiveDate::from_ymd_opt(2026, 10, 10),
+            body: "I'm away until the 11th.".to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn at(day: u32) -> DateTime<Utc> {
+        NaiveDate::from_ymd_opt(2026, 10, day)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap()
+            .and_utc()
+    }
+
+    #[test]
+    fn test_active_range_and_senders() {
+        let config = config();
+        assert!(config.is_active(at(1).date_naive()));
+        assert!(!config.is_active(at(11).date_naive()));
+        assert!(config.accepts_sender("alice@example.com"));
+        assert!(!config.accepts_sender("no-reply@shop.example"));
+        assert!(!config.accepts_sender("not an address"));
+
+        let scoped = AutoReplyConfig {
+            only_domains: vec!["@partner.com".to_string()],
+            ..config
+        };
+        assert!(scoped.accepts_sender("bob@Partner.com"));
+        assert!(!scoped.accepts_sender("alice@example.com"));
+    }
+
+    fn email(sender_email: &str, received: DateTime<Utc>) -> EmailData {
+        EmailData {
+            subject: "Hi".to_string(),
+            sender_email: sender_email.to_string(),
+            received_time: received.to_rfc3339(),
+            ..EmailData::sample("1")
+        }
+    }
+
+    #[test]
+    fn test_once_per_sender() {
+        let config = config();
+        let responder = AutoResponder::in_memory();
+
+        assert!(responder.should_reply(&config, &email("alice@example.com", at(2)), at(2)));
+        responder.record("alice@example.com", at(2));
+        assert!(!responder.should_reply(&config, &email("Alice@example.com", at(3)), at(3)));
+        assert!(responder.should_reply(&config, &email("alice@example.com", at(6)), at(6)));
+    }
+
+    #[test]
+    fn test_older_mail_gets_no_reply() {
+        let config = config();
+        let responder = AutoResponder::in_memory();
+        let before_absence = NaiveDate::from_ymd_opt(2026, 9, 14)
+            .unwrap()
+            .and_hms_opt(9, 0, 0)
+            .unwrap()
+            .and_utc();
+
+        // Still unread in the inbox once the absence starts, but sent before it
+        assert!(!responder.should_reply(&config, &email("bob@example.com", before_absence), at(2)));
+        let mut unreadable = email("bob@example.com", at(2));
+        unreadable.received_time.clear();
+        assert!(!responder.should_reply(&config, &unreadable, at(2)));
+        assert!(responder.should_reply(&config, &email("bob@example.com", at(2)), at(2)));
+    }
+}
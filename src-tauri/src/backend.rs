@@ -0,0 +1,277 @@
+// SERINA Backend Client
+// Requests to the Python FastAPI backend, shared by every command that needs Outlook
+
+use crate::http::PinnedClient;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::io::{BufReader, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tokio::sync::watch;
+
+/// Consecutive failures before requests stop going to the backend
+pub const FAILURE_THRESHOLD: u32 = 3;
+/// How often a tripped breaker checks whether the backend is back
+pub const PROBE_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Bodies without a known size, or larger than this, are spooled to disk while they download
+pub const SPOOL_THRESHOLD: u64 = 1024 * 1024;
+
+/// Send `body` as JSON to `endpoint` on the backend and return the response text
+pub async fn request(
+    client: &PinnedClient,
+    base_url: &str,
+    method: &str,
+    endpoint: &str,
+    body: Option<String>,
+) -> Result<String, String> {
+    send(client, base_url, method, endpoint, body)
+        .await?
+        .text()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Like `request`, but parses the JSON response. Large bodies stream to a temporary file and
+/// are parsed from there, so a big email list is never held as text and as values at once.
+pub async fn request_json<T: DeserializeOwned>(
+    client: &PinnedClient,
+    base_url: &str,
+    method: &str,
+    endpoint: &str,
+    body: Option<String>,
+) -> Result<T, String> {
+    let mut response = send(client, base_url, method, endpoint, body).await?;
+    if response
+        .content_length()
+        .is_some_and(|length| length <= SPOOL_THRESHOLD)
+    {
+        return response.json().await.map_err(|e| e.to_string());
+    }
+    let spool = Spool::new();
+    let mut file = std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(&spool.0)
+        .map_err(|e| e.to_string())?;
+    write_chunks(&mut response, &mut file).await?;
+    file.seek(SeekFrom::Start(0)).map_err(|e| e.to_string())?;
+    serde_json::from_reader(BufReader::new(file)).map_err(|e| e.to_string())
+}
+
+/// Stream a response body straight into `dest`, e.g. an attachment, and return its size
+pub async fn download(
+    client: &PinnedClient,
+    base_url: &str,
+    endpoint: &str,
+    dest: &Path,
+) -> Result<u64, String> {
+    let mut response = send(client, base_url, "GET", endpoint, None).await?;
+    let mut file = std::fs::File::create(dest).map_err(|e| e.to_string())?;
+    let written = write_chunks(&mut response, &mut file).await;
+    if written.is_err() {
+        let _ = std::fs::remove_file(dest);
+    }
+    written
+}
+
+async fn send(
+    client: &PinnedClient,
+    base_url: &str,
+    method: &str,
+    endpoint: &str,
+    body: Option<String>,
+) -> Result<reqwest::Response, String> {
+    let url = format!("{}{}", base_url, endpoint);
+
+    let mut request = match method.to_uppercase().as_str() {
+        "GET" => client.get(&url),
+        "POST" => client.post(&url),
+        "PUT" => client.put(&url),
+        "DELETE" => client.delete(&url),
+        _ => return Err("Unsupported HTTP method".to_string()),
+    };
+
+    if let Some(body_data) = body {
+        request = request
+            .header("Content-Type", "application/json")
+            .body(body_data);
+    }
+
+    let response = client.send(request).await?;
+
+    if !response.status().is_success() {
+        return Err(format!("HTTP {}: {}", response.status(), response.status()));
+    }
+    Ok(response)
+}
+
+/// Copy a body as it arrives, already decompressed, without holding more than one chunk
+async fn write_chunks(
+    response: &mut reqwest::Response,
+    file: &mut std::fs::File,
+) -> Result<u64, String> {
+    let mut written = 0;
+    while let Some(chunk) = response.chunk().await.map_err(|e| e.to_string())? {
+        file.write_all(&chunk).map_err(|e| e.to_string())?;
+        written += chunk.len() as u64;
+    }
+    file.flush().map_err(|e| e.to_string())?;
+    Ok(written)
+}
+
+/// A temporary file removed once the response has been parsed
+struct Spool(PathBuf);
+
+impl Spool {
+    fn new() -> Self {
+        static NEXT: AtomicU64 = AtomicU64::new(0);
+        let name = format!(
+            "serina-{}-{}.json",
+            std::process::id(),
+            NEXT.fetch_add(1, Ordering::Relaxed)
+        );
+        Self(std::env::temp_dir().join(name))
+    }
+}
+
+impl Drop for Spool {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+    /// Requests go through
+    #[default]
+    Closed,
+    /// The backend looks down; requests fail immediately until a probe succeeds
+    Open,
+}
+
+/// Sent as `backend-health` whenever the breaker opens or closes
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BackendHealth {
+    pub state: CircuitState,
+    pub consecutive_failures: u32,
+    pub last_error: Option<String>,
+}
+
+/// Stops every command waiting on a dead backend: after `FAILURE_THRESHOLD` failures in a row
+/// requests fail fast, and a background probe closes the breaker once `/health` answers again
+pub struct CircuitBreaker {
+    health: watch::Sender<BackendHealth>,
+}
+
+impl Default for CircuitBreaker {
+    fn default() -> Self {
+        Self {
+            health: watch::channel(BackendHealth::default()).0,
+        }
+    }
+}
+
+impl CircuitBreaker {
+    pub fn health(&self) -> BackendHealth {
+        self.health.borrow().clone()
+    }
+
+    /// Changes only when the breaker opens or closes
+    pub fn subscribe(&self) -> watch::Receiver<BackendHealth> {
+        self.health.subscribe()
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.health.borrow().state == CircuitState::Open
+    }
+
+    /// Refuse a request while the breaker is open
+    pub fn check(&self) -> Result<(), String> {
+        let health = self.health.borrow();
+        if health.state == CircuitState::Closed {
+            return Ok(());
+        }
+        Err(format!(
+            "Backend unavailable after {} failed requests ({}); retrying in the background",
+            health.consecutive_failures,
+            health.last_error.as_deref().unwrap_or("no response")
+        ))
+    }
+
+    /// Note how a request went. Client errors like a 404 mean the backend is up.
+    pub fn record<T>(&self, result: &Result<T, String>) {
+        match result {
+            Err(error) if !error.starts_with("HTTP 4") => self.record_failure(error),
+            _ => self.record_success(),
+        }
+    }
+
+    pub fn record_success(&self) {
+        self.health.send_if_modified(|health| {
+            let was_open = health.state == CircuitState::Open;
+            *health = BackendHealth::default();
+            was_open
+        });
+    }
+
+    pub fn record_failure(&self, error: &str) {
+        self.health.send_if_modified(|health| {
+            health.consecutive_failures += 1;
+            health.last_error = Some(error.to_string());
+            let opens = health.state == CircuitState::Closed
+                && health.consecutive_failures >= FAILURE_THRESHOLD;
+            if opens {
+                health.state = CircuitState::Open;
+            }
+            opens
+        });
+    }
+
+    /// Check `/health` while open; a good answer closes the breaker
+    pub async fn probe(&self, client: &PinnedClient, base_url: &str) {
+        if self.is_open() {
+            self.record(&request(client, base_url, "GET", "/health", None).await);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_circuit_breaker() {
+        let breaker = CircuitBreaker::default();
+        let mut changes = breaker.subscribe();
+        let refused: Result<String, String> =
+            Err("error sending request: connection refused".to_string());
+
+        breaker.record(&refused);
+        breaker.record::<String>(&Err("HTTP 404 Not Found: 404 Not Found".to_string()));
+        breaker.record(&refused);
+        breaker.record(&refused);
+        assert!(breaker.check().is_ok());
+        assert!(!changes.has_changed().unwrap());
+
+        breaker.record(&refused);
+        assert!(changes.has_changed().unwrap());
+        assert_eq!(changes.borrow_and_update().state, CircuitState::Open);
+        let error = breaker.check().unwrap_err();
+        assert!(error.contains("after 3 failed requests"), "{}", error);
+        assert!(error.contains("connection refused"), "{}", error);
+
+        // Further failures while open don't re-announce
+        breaker.record(&refused);
+        assert!(!changes.has_changed().unwrap());
+
+        breaker.record(&Ok("{}".to_string()));
+        assert!(changes.has_changed().unwrap());
+        assert_eq!(breaker.health(), BackendHealth::default());
+        assert!(breaker.check().is_ok());
+    }
+}
@@ -0,0 +1,266 @@
+// SERINA LLM Budget
+// Per-minute rate limiting and monthly spend tracking for LLM requests
+
+use crate::utils::LLMLimits;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::hash::{Hash, Hasher};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+const RATE_WINDOW: Duration = Duration::from_secs(60);
+
+/// Most summaries kept for serving while over budget
+const SUMMARY_CACHE_CAPACITY: usize = 500;
+
+/// Spend for a single calendar month, persisted between runs
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UsageRecord {
+    pub month: String,
+    pub tokens: u64,
+    pub spent_usd: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BudgetError {
+    /// Too many requests this minute; retry after the given wait
+    RateLimited(Duration),
+    /// The monthly cap has been reached
+    BudgetExceeded { spent_usd: f64, cap_usd: f64 },
+}
+
+impl std::fmt::Display for BudgetError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BudgetError::RateLimited(wait) => write!(
+                f,
+                "LLM rate limit reached, try again in {}s",
+                wait.as_secs().max(1)
+            ),
+            BudgetError::BudgetExceeded { spent_usd, cap_usd } => write!(
+                f,
+                "Monthly LLM budget reached (${:.2} of ${:.2})",
+                spent_usd, cap_usd
+            ),
+        }
+    }
+}
+
+struct BudgetState {
+    recent: VecDeque<Instant>,
+    usage: UsageRecord,
+}
+
+/// Shared limiter and spend tracker for all LLM calls
+pub struct Budget {
+    path: Option<PathBuf>,
+    state: Mutex<BudgetState>,
+}
+
+pub fn current_month() -> String {
+    chrono::Local::now().format("%Y-%m").to_string()
+}
+
+impl Budget {
+    /// Load the persisted usage record, starting fresh if it's missing or unreadable
+    pub fn load(path: PathBuf) -> Self {
+        let usage = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+        Self {
+            path: Some(path),
+            state: Mutex::new(BudgetState {
+                recent: VecDeque::new(),
+                usage,
+            }),
+        }
+    }
+
+    /// In-memory budget that is never persisted
+    pub fn in_memory() -> Self {
+        Self {
+            path: None,
+            state: Mutex::new(BudgetState {
+                recent: VecDeque::new(),
+                usage: UsageRecord::default(),
+            }),
+        }
+    }
+
+    /// Reserve a request slot, or explain why the request can't go out
+    pub fn check(&self, limits: &LLMLimits) -> Result<(), BudgetError> {
+        self.check_at(limits, Instant::now(), &current_month())
+    }
+
+    pub fn check_at(
+        &self,
+        limits: &LLMLimits,
+        now: Instant,
+        month: &str,
+    ) -> Result<(), BudgetError> {
+        let mut state = self.state.lock().unwrap();
+        roll_month(&mut state.usage, month);
+
+        if limits.monthly_budget_usd > 0.0 && state.usage.spent_usd >= limits.monthly_budget_usd {
+            return Err(BudgetError::BudgetExceeded {
+                spent_usd: state.usage.spent_usd,
+                cap_usd: limits.monthly_budget_usd,
+            });
+        }
+
+        if limits.requests_per_minute > 0 {
+            while let Some(oldest) = state.recent.front() {
+                if now.duration_since(*oldest) >= RATE_WINDOW {
+                    state.recent.pop_front();
+                } else {
+                    break;
+                }
+            }
+            if state.recent.len() >= limits.requests_per_minute as usize {
+                let oldest = state.recent[0];
+                return Err(BudgetError::RateLimited(
+                    RATE_WINDOW.saturating_sub(now.duration_since(oldest)),
+                ));
+            }
+            state.recent.push_back(now);
+        }
+
+        Ok(())
+    }
+
+    /// Record spend for a finished request; returns true when this crossed the monthly cap
+    pub fn record(&self, limits: &LLMLimits, tokens: u64) -> bool {
+        self.record_for_month(limits, tokens, &current_month())
+    }
+
+    pub fn record_for_month(&self, limits: &LLMLimits, tokens: u64, month: &str) -> bool {
+        let mut state = self.state.lock().unwrap();
+        roll_month(&mut state.usage, month);
+
+        let cap = limits.monthly_budget_usd;
+        let was_under = cap <= 0.0 || state.usage.spent_usd < cap;
+        state.usage.tokens += tokens;
+        state.usage.spent_usd += tokens as f64 / 1000.0 * limits.cost_per_1k_tokens;
+        let crossed = cap > 0.0 && was_under && state.usage.spent_usd >= cap;
+
+        if let Some(path) = &self.path {
+            if let Ok(contents) = serde_json::to_string_pretty(&state.usage) {
+                let _ = std::fs::write(path, contents);
+            }
+        }
+        crossed
+    }
+
+    /// Whether background LLM work (auto-classification and the like) should be skipped
+    pub fn is_exhausted(&self, limits: &LLMLimits) -> bool {
+        let state = self.state.lock().unwrap();
+        limits.monthly_budget_usd > 0.0
+            && state.usage.month == current_month()
+            && state.usage.spent_usd >= limits.monthly_budget_usd
+    }
+
+    pub fn usage(&self) -> UsageRecord {
+        self.state.lock().unwrap().usage.clone()
+    }
+}
+
+fn roll_month(usage: &mut UsageRecord, month: &str) {
+    if usage.month != month {
+        *usage = UsageRecord {
+            month: month.to_string(),
+            ..Default::default()
+        };
+    }
+}
+
+/// Recently generated summaries, served when the budget blocks new LLM calls
+#[derive(Default)]
+pub struct SummaryCache {
+    entries: Mutex<HashMap<u64, String>>,
+}
+
+impl SummaryCache {
+    fn key(content: &str, language: Option<&str>) -> u64 {
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        content.hash(&mut hasher);
+        language.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    pub fn get(&self, content: &str, language: Option<&str>) -> Option<String> {
+        let entries = self.entries.lock().unwrap();
+        entries.get(&Self::key(content, language)).cloned()
+    }
+
+    pub fn insert(&self, content: &str, language: Option<&str>, summary: String) {
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() >= SUMMARY_CACHE_CAPACITY {
+            entries.clear();
+        }
+        entries.insert(Self::key(content, language), summary);
+    }
+
+    /// Forget every summary; returns how many there were
+    pub fn clear(&self) -> usize {
+        let mut entries = self.entries.lock().unwrap();
+        let count = entries.len();
+        entries.clear();
+        count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limits(rpm: u32, cap: f64) -> LLMLimits {
+        LLMLimits {
+            requests_per_minute: rpm,
+            monthly_budget_usd: cap,
+            cost_per_1k_tokens: 1.0,
+        }
+    }
+
+    #[test]
+    fn test_rate_limit_window() {
+        let budget = Budget::in_memory();
+        let limits = limits(2, 0.0);
+        let start = Instant::now();
+
+        assert!(budget.check_at(&limits, start, "2026-10").is_ok());
+        assert!(budget.check_at(&limits, start, "2026-10").is_ok());
+        assert!(matches!(
+            budget.check_at(&limits, start + Duration::from_secs(10), "2026-10"),
+            Err(BudgetError::RateLimited(wait)) if wait == Duration::from_secs(50)
+        ));
+        assert!(budget
+            .check_at(&limits, start + Duration::from_secs(61), "2026-10")
+            .is_ok());
+    }
+
+    #[test]
+    fn test_monthly_cap() {
+        let budget = Budget::in_memory();
+        let limits = limits(0, 1.0);
+
+        assert!(!budget.record_for_month(&limits, 600, "2026-10"));
+        assert!(budget.record_for_month(&limits, 600, "2026-10"));
+        assert!(matches!(
+            budget.check_at(&limits, Instant::now(), "2026-10"),
+            Err(BudgetError::BudgetExceeded { .. })
+        ));
+        // Spend resets when the month rolls over
+        assert!(budget.check_at(&limits, Instant::now(), "2026-11").is_ok());
+        assert_eq!(budget.usage().tokens, 0);
+    }
+
+    #[test]
+    fn test_summary_cache() {
+        let cache = SummaryCache::default();
+        cache.insert("body", None, "summary".to_string());
+        assert_eq!(cache.get("body", None).as_deref(), Some("summary"));
+        assert!(cache.get("body", Some("French")).is_none());
+    }
+}
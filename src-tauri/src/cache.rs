@@ -0,0 +1,672 @@
+// SERINA Email Cache
+// Local SQLite copy of fetched emails, used to spot duplicate deliveries
+// and rebuilt from the provider whenever it turns out to be corrupt
+
+use crate::encryption::Key;
+use crate::utils::EmailData;
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS emails (
+        id TEXT PRIMARY KEY,
+        message_id TEXT NOT NULL DEFAULT '',
+        subject TEXT NOT NULL DEFAULT '',
+        sender TEXT NOT NULL DEFAULT '',
+        sender_email TEXT NOT NULL DEFAULT '',
+        body TEXT NOT NULL DEFAULT '',
+        received_time TEXT NOT NULL DEFAULT '',
+        is_unread INTEGER NOT NULL DEFAULT 0,
+        cached_at TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS idx_emails_message_id ON emails(message_id);
+    CREATE TABLE IF NOT EXISTS email_html (
+        id TEXT PRIMARY KEY,
+        html TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS data_key (
+        id INTEGER PRIMARY KEY CHECK (id = 1),
+        wrapped TEXT NOT NULL
+    );
+";
+
+/// Result of `PRAGMA integrity_check`
+#[derive(Debug, Clone, Serialize)]
+pub struct IntegrityReport {
+    pub ok: bool,
+    pub errors: Vec<String>,
+}
+
+/// Database file size before and after a compaction
+#[derive(Debug, Clone, Serialize)]
+pub struct CompactReport {
+    pub bytes_before: u64,
+    pub bytes_after: u64,
+}
+
+/// An email in `get_emails` output, with the number of duplicate copies folded into it
+#[derive(Debug, Serialize)]
+pub struct CollapsedEmail {
+    #[serde(flatten)]
+    pub email: EmailData,
+    pub duplicates: u32,
+}
+
+pub struct EmailCache {
+    path: Option<PathBuf>,
+    conn: Mutex<Connection>,
+    /// Seals subjects, senders and bodies when the cache is encrypted. Ids, Message-IDs and
+    /// timestamps stay readable so duplicates can still be found and old entries purged.
+    data_key: Option<Key>,
+}
+
+impl EmailCache {
+    pub fn open(path: &Path) -> Result<Self, String> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let conn = Connection::open(path).map_err(|e| e.to_string())?;
+        conn.pragma_update(None, "journal_mode", "WAL")
+            .map_err(|e| e.to_string())?;
+        let cache = Self::init(Some(path.to_path_buf()), conn)?;
+        let report = cache.verify()?;
+        if !report.ok {
+            return Err(format!("Cache is corrupt: {}", report.errors.join("; ")));
+        }
+        Ok(cache)
+    }
+
+    /// Open the cache, moving a corrupt file aside and starting empty instead of failing.
+    /// Returns whether a rebuild happened; the cache refills from the provider as mail is fetched.
+    pub fn open_or_rebuild(path: &Path) -> Result<(Self, bool), String> {
+        match Self::open(path) {
+            Ok(cache) => Ok((cache, false)),
+            Err(e) => {
+                crate::log_warn!("Rebuilding email cache: {}", e);
+                let stamp = Utc::now().format("%Y%m%d%H%M%S");
+                let backup = path.with_extension(format!("db.corrupt-{}", stamp));
+                std::fs::rename(path, &backup).map_err(|e| e.to_string())?;
+                for suffix in ["-wal", "-shm"] {
+                    let mut sidecar = path.as_os_str().to_owned();
+                    sidecar.push(suffix);
+                    let _ = std::fs::remove_file(PathBuf::from(sidecar));
+                }
+                Ok((Self::open(path)?, true))
+            }
+        }
+    }
+
+    pub fn in_memory() -> Result<Self, String> {
+        Self::init(
+            None,
+            Connection::open_in_memory().map_err(|e| e.to_string())?,
+        )
+    }
+
+    fn init(path: Option<PathBuf>, conn: Connection) -> Result<Self, String> {
+        // Zero deleted content instead of leaving it in free pages
+        conn.pragma_update(None, "secure_delete", true)
+            .map_err(|e| e.to_string())?;
+        conn.execute_batch(SCHEMA).map_err(|e| e.to_string())?;
+        Ok(Self {
+            path,
+            conn: Mutex::new(conn),
+            data_key: None,
+        })
+    }
+
+    /// Encrypt the cache under a data key wrapped by `master`, sealing any plaintext entries.
+    /// Without a master key the cache is kept in plaintext. Entries that can't be decrypted,
+    /// because the keychain lost the key or encryption was switched off, are dropped and
+    /// refilled from the provider.
+    pub fn with_master_key(mut self, master: Option<&Key>) -> Result<Self, String> {
+        let wrapped: Option<String> = {
+            let conn = self.conn.lock().unwrap();
+            conn.query_row("SELECT wrapped FROM data_key WHERE id = 1", [], |row| {
+                row.get(0)
+            })
+            .map(Some)
+            .or_else(|e| match e {
+                rusqlite::Error::QueryReturnedNoRows => Ok(None),
+                e => Err(e.to_string()),
+            })?
+        };
+        let Some(master) = master else {
+            if wrapped.is_some() {
+                self.clear()?;
+                self.conn
+                    .lock()
+                    .unwrap()
+                    .execute("DELETE FROM data_key", [])
+                    .map_err(|e| e.to_string())?;
+            }
+            return Ok(self);
+        };
+
+        if let Some(wrapped) = wrapped {
+            match master.unwrap_data_key(&wrapped) {
+                Ok(key) => {
+                    self.data_key = Some(key);
+                    return Ok(self);
+                }
+                Err(_) => {
+                    self.clear()?;
+                }
+            }
+        }
+        let (key, wrapped) = master.new_data_key();
+        {
+            let mut conn = self.conn.lock().unwrap();
+            let tx = conn.transaction().map_err(|e| e.to_string())?;
+            tx.execute(
+                "INSERT INTO data_key (id, wrapped) VALUES (1, ?1)
+                 ON CONFLICT(id) DO UPDATE SET wrapped = excluded.wrapped",
+                [&wrapped],
+            )
+            .map_err(|e| e.to_string())?;
+            seal_existing(&tx, &key)?;
+            tx.commit().map_err(|e| e.to_string())?;
+        }
+        self.data_key = Some(key);
+        // Push the plaintext out of the WAL and free pages
+        self.compact()?;
+        Ok(self)
+    }
+
+    pub fn is_encrypted(&self) -> bool {
+        self.data_key.is_some()
+    }
+
+    fn seal(&self, text: &str) -> String {
+        match &self.data_key {
+            Some(key) => key.seal_text(text),
+            None => text.to_string(),
+        }
+    }
+
+    fn open_text(&self, stored: String) -> Result<String, String> {
+        match &self.data_key {
+            Some(key) => key.open_text(&stored),
+            None => Ok(stored),
+        }
+    }
+
+    fn open_email(&self, mut email: EmailData) -> Result<EmailData, String> {
+        email.subject = self.open_text(email.subject)?;
+        email.sender = self.open_text(email.sender)?;
+        email.sender_email = self.open_text(email.sender_email)?;
+        email.body = self.open_text(email.body)?;
+        Ok(email)
+    }
+
+    /// Run SQLite's full integrity check
+    pub fn verify(&self) -> Result<IntegrityReport, String> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn
+            .prepare("PRAGMA integrity_check")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], |row| row.get::<_, String>(0))
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+        let ok = rows.len() == 1 && rows[0] == "ok";
+        Ok(IntegrityReport {
+            ok,
+            errors: if ok { Vec::new() } else { rows },
+        })
+    }
+
+    /// Fold the WAL back into the database file
+    pub fn checkpoint(&self) -> Result<(), String> {
+        let conn = self.conn.lock().unwrap();
+        conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))
+            .map_err(|e| e.to_string())
+    }
+
+    /// Checkpoint and VACUUM, reclaiming space left by purged entries
+    pub fn compact(&self) -> Result<CompactReport, String> {
+        let bytes_before = self.file_size();
+        self.checkpoint()?;
+        self.conn
+            .lock()
+            .unwrap()
+            .execute_batch("VACUUM")
+            .map_err(|e| e.to_string())?;
+        self.checkpoint()?;
+        Ok(CompactReport {
+            bytes_before,
+            bytes_after: self.file_size(),
+        })
+    }
+
+    /// Scheduled upkeep: always checkpoint, vacuum once a quarter of the pages are free
+    pub fn maintain(&self) -> Result<(), String> {
+        let (free, total) = {
+            let conn = self.conn.lock().unwrap();
+            let pragma = |name: &str| {
+                conn.query_row(&format!("PRAGMA {}", name), [], |row| row.get::<_, i64>(0))
+                    .map_err(|e| e.to_string())
+            };
+            (pragma("freelist_count")?, pragma("page_count")?)
+        };
+        if total > 0 && free * 4 >= total {
+            self.compact().map(|_| ())
+        } else {
+            self.checkpoint()
+        }
+    }
+
+    fn file_size(&self) -> u64 {
+        self.path
+            .as_ref()
+            .and_then(|p| std::fs::metadata(p).ok())
+            .map(|m| m.len())
+            .unwrap_or(0)
+    }
+
+    /// Insert or refresh emails; the first sighting of each id keeps its place in line
+    pub fn store(&self, emails: &[EmailData]) -> Result<(), String> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction().map_err(|e| e.to_string())?;
+        let now = Utc::now().to_rfc3339();
+        for email in emails {
+            tx.execute(
+                "INSERT INTO emails
+                    (id, message_id, subject, sender, sender_email, body, received_time, is_unread, cached_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+                 ON CONFLICT(id) DO UPDATE SET
+                    message_id = excluded.message_id,
+                    subject = excluded.subject,
+                    sender = excluded.sender,
+                    sender_email = excluded.sender_email,
+                    body = excluded.body,
+                    received_time = excluded.received_time,
+                    is_unread = excluded.is_unread",
+                params![
+                    email.id,
+                    email.message_id,
+                    self.seal(&email.subject),
+                    self.seal(&email.sender),
+                    self.seal(&email.sender_email),
+                    self.seal(&email.body),
+                    email.received_time,
+                    email.is_unread,
+                    now,
+                ],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+        tx.commit().map_err(|e| e.to_string())
+    }
+
+    /// Ids of every cached copy of a Message-ID, in the order they were delivered
+    fn deliveries(&self, message_id: &str) -> Result<Vec<String>, String> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn
+            .prepare_cached("SELECT id FROM emails WHERE message_id = ?1 ORDER BY rowid")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([message_id], |row| row.get(0))
+            .map_err(|e| e.to_string())?;
+        rows.collect::<Result<_, _>>().map_err(|e| e.to_string())
+    }
+
+    /// Cache `emails` and keep one copy of each Message-ID among them: the earliest delivery
+    /// present. Copies outside `emails`, e.g. on another page, don't hide any in it.
+    pub fn collapse(&self, emails: Vec<EmailData>) -> Result<Vec<CollapsedEmail>, String> {
+        self.store(&emails)?;
+
+        let mut copies: HashMap<String, Vec<String>> = HashMap::new();
+        for email in emails.iter().filter(|email| !email.message_id.is_empty()) {
+            copies
+                .entry(email.message_id.clone())
+                .or_default()
+                .push(email.id.clone());
+        }
+        let mut kept_ids: HashMap<String, String> = HashMap::new();
+        for (message_id, ids) in &copies {
+            let kept = self
+                .deliveries(message_id)?
+                .into_iter()
+                .find(|id| ids.contains(id))
+                .unwrap_or_else(|| ids[0].clone());
+            kept_ids.insert(message_id.clone(), kept);
+        }
+
+        Ok(emails
+            .into_iter()
+            .filter(|email| email.message_id.is_empty() || kept_ids[&email.message_id] == email.id)
+            .map(|email| {
+                let duplicates = copies
+                    .get(&email.message_id)
+                    .map_or(0, |ids| ids.len() as u32 - 1);
+                CollapsedEmail { email, duplicates }
+            })
+            .collect())
+    }
+
+    /// The `limit` most recently cached emails, newest first
+    pub fn recent(&self, limit: usize) -> Result<Vec<EmailData>, String> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn
+            .prepare_cached(
+                "SELECT id, message_id, subject, sender, sender_email, body, received_time, is_unread
+                 FROM emails ORDER BY rowid DESC LIMIT ?1",
+            )
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([limit as i64], email_from_row)
+            .map_err(|e| e.to_string())?;
+        rows.map(|row| self.open_email(row.map_err(|e| e.to_string())?))
+            .collect()
+    }
+
+    /// One cached email
+    pub fn get(&self, id: &str) -> Result<Option<EmailData>, String> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn
+            .prepare_cached(
+                "SELECT id, message_id, subject, sender, sender_email, body, received_time, is_unread
+                 FROM emails WHERE id = ?1",
+            )
+            .map_err(|e| e.to_string())?;
+        let mut rows = stmt
+            .query_map([id], email_from_row)
+            .map_err(|e| e.to_string())?;
+        rows.next()
+            .transpose()
+            .map_err(|e| e.to_string())?
+            .map(|email| self.open_email(email))
+            .transpose()
+    }
+
+    /// Keep the HTML body of an opened email so it can be shown again offline
+    pub fn store_html(&self, id: &str, html: &str) -> Result<(), String> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO email_html (id, html) VALUES (?1, ?2)
+             ON CONFLICT(id) DO UPDATE SET html = excluded.html",
+            params![id, self.seal(html)],
+        )
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+    }
+
+    pub fn html(&self, id: &str) -> Result<Option<String>, String> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn
+            .prepare_cached("SELECT html FROM email_html WHERE id = ?1")
+            .map_err(|e| e.to_string())?;
+        let mut rows = stmt
+            .query_map([id], |row| row.get(0))
+            .map_err(|e| e.to_string())?;
+        rows.next()
+            .transpose()
+            .map_err(|e| e.to_string())?
+            .map(|html| self.open_text(html))
+            .transpose()
+    }
+
+    /// Number of entries first cached before `cutoff`
+    pub fn count_older_than(&self, cutoff: DateTime<Utc>) -> Result<usize, String> {
+        let conn = self.conn.lock().unwrap();
+        conn.query_row(
+            "SELECT COUNT(*) FROM emails WHERE cached_at < ?1",
+            [cutoff.to_rfc3339()],
+            |row| row.get::<_, i64>(0),
+        )
+        .map(|count| count as usize)
+        .map_err(|e| e.to_string())
+    }
+
+    /// Remove entries first cached before `cutoff`
+    pub fn purge_older_than(&self, cutoff: DateTime<Utc>) -> Result<usize, String> {
+        let conn = self.conn.lock().unwrap();
+        let purged = conn
+            .execute(
+                "DELETE FROM emails WHERE cached_at < ?1",
+                [cutoff.to_rfc3339()],
+            )
+            .map_err(|e| e.to_string())?;
+        conn.execute(
+            "DELETE FROM email_html WHERE id NOT IN (SELECT id FROM emails)",
+            [],
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(purged)
+    }
+
+    /// Delete every cached email and its HTML, then compact so nothing is left in the WAL
+    /// or free pages; returns how many emails were removed
+    pub fn clear(&self) -> Result<usize, String> {
+        let removed = {
+            let conn = self.conn.lock().unwrap();
+            let removed = conn
+                .execute("DELETE FROM emails", [])
+                .map_err(|e| e.to_string())?;
+            conn.execute("DELETE FROM email_html", [])
+                .map_err(|e| e.to_string())?;
+            removed
+        };
+        self.compact()?;
+        Ok(removed)
+    }
+
+    /// Cached emails with an earlier copy of the same message, hidden when listed alongside it
+    pub fn hidden_duplicates(&self) -> Result<usize, String> {
+        let conn = self.conn.lock().unwrap();
+        conn.query_row(
+            "SELECT COUNT(*) FROM emails e
+             WHERE e.message_id != ''
+               AND EXISTS (SELECT 1 FROM emails o
+                           WHERE o.message_id = e.message_id AND o.rowid < e.rowid)",
+            [],
+            |row| row.get::<_, i64>(0),
+        )
+        .map(|count| count as usize)
+        .map_err(|e| e.to_string())
+    }
+}
+
+/// Encrypt every cached subject, sender, body and HTML body in place
+fn seal_existing(tx: &rusqlite::Transaction, key: &Key) -> Result<(), String> {
+    let rows: Vec<(String, [String; 4])> = {
+        let mut stmt = tx
+            .prepare("SELECT id, subject, sender, sender_email, body FROM emails")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get(0)?,
+                    [row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?],
+                ))
+            })
+            .map_err(|e| e.to_string())?;
+        rows.collect::<Result<_, _>>().map_err(|e| e.to_string())?
+    };
+    for (id, fields) in rows {
+        let [subject, sender, sender_email, body] = fields.map(|field| key.seal_text(&field));
+        tx.execute(
+            "UPDATE emails SET subject = ?2, sender = ?3, sender_email = ?4, body = ?5
+             WHERE id = ?1",
+            params![id, subject, sender, sender_email, body],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+
+    let html: Vec<(String, String)> = {
+        let mut stmt = tx
+            .prepare("SELECT id, html FROM email_html")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(|e| e.to_string())?;
+        rows.collect::<Result<_, _>>().map_err(|e| e.to_string())?
+    };
+    for (id, html) in html {
+        tx.execute(
+            "UPDATE email_html SET html = ?2 WHERE id = ?1",
+            params![id, key.seal_text(&html)],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+fn email_from_row(row: &rusqlite::Row) -> rusqlite::Result<EmailData> {
+    Ok(EmailData {
+        id: row.get(0)?,
+        message_id: row.get(1)?,
+        subject: row.get(2)?,
+        sender: row.get(3)?,
+        sender_email: row.get(4)?,
+        body: row.get(5)?,
+        received_time: row.get(6)?,
+        is_unread: row.get(7)?,
+        account: String::new(),
+        snippet: String::new(),
+        deadline: None,
+        conversation_id: String::new(),
+        muted: false,
+        claimed_by: String::new(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn email(id: &str, message_id: &str) -> EmailData {
+        EmailData {
+            message_id: message_id.to_string(),
+            subject: "Weekly digest".to_string(),
+            sender: "List".to_string(),
+            sender_email: "list@example.com".to_string(),
+            ..EmailData::sample(id)
+        }
+    }
+
+    #[test]
+    fn test_collapse_duplicates() {
+        let cache = EmailCache::in_memory().unwrap();
+
+        let first = cache
+            .collapse(vec![
+                email("a", "<m1@example.com>"),
+                email("b", "<m1@example.com>"),
+                email("c", ""),
+                email("d", "<m2@example.com>"),
+            ])
+            .unwrap();
+        let ids: Vec<(&str, u32)> = first
+            .iter()
+            .map(|c| (c.email.id.as_str(), c.duplicates))
+            .collect();
+        assert_eq!(ids, [("a", 1), ("c", 0), ("d", 0)]);
+
+        // Without the original in the list, the earliest copy that is stands in for it
+        let later = cache
+            .collapse(vec![
+                email("e", "<m1@example.com>"),
+                email("b", "<m1@example.com>"),
+            ])
+            .unwrap();
+        let ids: Vec<(&str, u32)> = later
+            .iter()
+            .map(|c| (c.email.id.as_str(), c.duplicates))
+            .collect();
+        assert_eq!(ids, [("b", 1)]);
+        let alone = cache
+            .collapse(vec![email("e", "<m1@example.com>")])
+            .unwrap();
+        assert_eq!(alone.len(), 1);
+        assert_eq!(alone[0].email.id, "e");
+        assert_eq!(cache.hidden_duplicates().unwrap(), 2);
+
+        let recent: Vec<String> = cache.recent(2).unwrap().into_iter().map(|e| e.id).collect();
+        assert_eq!(recent, ["e", "d"]);
+    }
+
+    #[test]
+    fn test_rebuilds_corrupt_file() {
+        let dir = std::env::temp_dir().join(format!("serina-cache-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("cache.db");
+        std::fs::write(&path, b"definitely not a sqlite database").unwrap();
+
+        let (cache, rebuilt) = EmailCache::open_or_rebuild(&path).unwrap();
+        assert!(rebuilt);
+        cache.store(&[email("a", "")]).unwrap();
+        assert!(cache.verify().unwrap().ok);
+        cache.maintain().unwrap();
+        assert!(cache.compact().unwrap().bytes_after > 0);
+
+        drop(cache);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_purge_older_than() {
+        let cache = EmailCache::in_memory().unwrap();
+        cache.store(&[email("a", ""), email("b", "")]).unwrap();
+
+        cache.store_html("a", "<p>Digest</p>").unwrap();
+        assert_eq!(cache.get("a").unwrap().unwrap().subject, "Weekly digest");
+        assert_eq!(cache.html("a").unwrap().as_deref(), Some("<p>Digest</p>"));
+
+        let past = Utc::now() - chrono::Duration::days(1);
+        assert_eq!(cache.count_older_than(past).unwrap(), 0);
+
+        let future = Utc::now() + chrono::Duration::days(1);
+        assert_eq!(cache.count_older_than(future).unwrap(), 2);
+        assert_eq!(cache.purge_older_than(future).unwrap(), 2);
+        assert_eq!(cache.count_older_than(future).unwrap(), 0);
+        assert!(cache.get("a").unwrap().is_none());
+        assert!(cache.html("a").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_encrypts_existing_entries() {
+        let dir = std::env::temp_dir().join(format!("serina-cache-enc-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("cache.db");
+        let master = Key::generate();
+
+        let (cache, _) = EmailCache::open_or_rebuild(&path).unwrap();
+        cache.store(&[email("a", "<m1@example.com>")]).unwrap();
+        cache.store_html("a", "<p>Digest</p>").unwrap();
+        let cache = cache.with_master_key(Some(&master)).unwrap();
+        assert!(cache.is_encrypted());
+        cache.store(&[email("b", "")]).unwrap();
+        drop(cache);
+
+        let raw = std::fs::read(&path).unwrap();
+        assert!(!raw.windows(13).any(|w| w == b"Weekly digest"));
+        assert!(!raw.windows(6).any(|w| w == b"Digest"));
+
+        let (cache, _) = EmailCache::open_or_rebuild(&path).unwrap();
+        let cache = cache.with_master_key(Some(&master)).unwrap();
+        assert_eq!(cache.get("a").unwrap().unwrap().subject, "Weekly digest");
+        assert_eq!(cache.html("a").unwrap().as_deref(), Some("<p>Digest</p>"));
+        assert_eq!(cache.recent(5).unwrap().len(), 2);
+        drop(cache);
+
+        // A lost keychain key leaves an empty cache rather than unreadable entries
+        let (cache, _) = EmailCache::open_or_rebuild(&path).unwrap();
+        let cache = cache.with_master_key(Some(&Key::generate())).unwrap();
+        assert!(cache.recent(5).unwrap().is_empty());
+        drop(cache);
+        let (cache, _) = EmailCache::open_or_rebuild(&path).unwrap();
+        let cache = cache.with_master_key(None).unwrap();
+        assert!(!cache.is_encrypted());
+        assert!(cache.recent(5).unwrap().is_empty());
+
+        drop(cache);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
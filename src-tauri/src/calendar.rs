@@ -0,0 +1,303 @@
+// SERINA Meeting Scheduling
+// Free slots from the Outlook calendar and the meeting times offered in replies, so an
+// answer picking one can be turned into an invite
+
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, Timelike, Utc, Weekday};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// Candidate start times are on this grid
+const SLOT_STEP_MINUTES: i64 = 30;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SchedulingConfig {
+    /// "HH:MM", local time
+    pub working_hours_start: String,
+    pub working_hours_end: String,
+    pub default_duration_minutes: u32,
+    pub slots_to_offer: usize,
+    /// Working days searched for free time, starting today
+    pub days_ahead: u32,
+    /// Free time kept either side of existing meetings
+    pub buffer_minutes: u32,
+}
+
+impl Default for SchedulingConfig {
+    fn default() -> Self {
+        Self {
+            working_hours_start: "09:00".to_string(),
+            working_hours_end: "17:00".to_string(),
+            default_duration_minutes: 30,
+            slots_to_offer: 3,
+            days_ahead: 5,
+            buffer_minutes: 15,
+        }
+    }
+}
+
+/// A busy calendar entry as reported by the backend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BusyBlock {
+    pub start: String,
+    pub end: String,
+}
+
+/// Start and end in local time
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimeSlot {
+    pub start: NaiveDateTime,
+    pub end: NaiveDateTime,
+}
+
+impl TimeSlot {
+    fn overlaps(&self, start: NaiveDateTime, end: NaiveDateTime) -> bool {
+        self.start < end && start < self.end
+    }
+}
+
+fn parse_hours(value: &str, fallback: (u32, u32)) -> NaiveTime {
+    NaiveTime::parse_from_str(value.trim(), "%H:%M")
+        .unwrap_or_else(|_| NaiveTime::from_hms_opt(fallback.0, fallback.1, 0).unwrap())
+}
+
+/// The first `now` or later that lies on the slot grid
+fn round_up(now: NaiveDateTime) -> NaiveDateTime {
+    let minutes = now.hour() as i64 * 60 + now.minute() as i64;
+    let rounded = (minutes + SLOT_STEP_MINUTES - 1) / SLOT_STEP_MINUTES * SLOT_STEP_MINUTES;
+    now.date().and_hms_opt(0, 0, 0).unwrap() + Duration::minutes(rounded)
+}
+
+/// Free slots of `duration` in working hours, avoiding `busy` (plus the buffer) and any
+/// slot already offered. Slots are spread over different days before doubling up on one.
+pub fn free_slots(
+    config: &SchedulingConfig,
+    busy: &[(NaiveDateTime, NaiveDateTime)],
+    exclude: &[TimeSlot],
+    now: NaiveDateTime,
+    duration: Duration,
+) -> Vec<TimeSlot> {
+    let day_start = parse_hours(&config.working_hours_start, (9, 0));
+    let day_end = parse_hours(&config.working_hours_end, (17, 0));
+    let buffer = Duration::minutes(config.buffer_minutes as i64);
+
+    let mut days: Vec<Vec<TimeSlot>> = Vec::new();
+    let mut date: NaiveDate = now.date();
+    while days.len() < config.days_ahead as usize {
+        if !matches!(date.weekday(), Weekday::Sat | Weekday::Sun) {
+            let mut start = round_up(date.and_time(day_start).max(now));
+            let mut free = Vec::new();
+            while start + duration <= date.and_time(day_end) {
+                let slot = TimeSlot {
+                    start,
+                    end: start + duration,
+                };
+                let taken = busy
+                    .iter()
+                    .any(|(from, to)| slot.overlaps(*from - buffer, *to + buffer))
+                    || exclude
+                        .iter()
+                        .any(|other| slot.overlaps(other.start, other.end));
+                if !taken {
+                    free.push(slot);
+                }
+                start += Duration::minutes(SLOT_STEP_MINUTES);
+            }
+            days.push(free);
+        }
+        date += Duration::days(1);
+    }
+
+    let mut slots = Vec::new();
+    for round in 0.. {
+        let mut any = false;
+        for day in &days {
+            if let Some(slot) = day.get(round) {
+                any = true;
+                if slots.len() < config.slots_to_offer {
+                    slots.push(*slot);
+                }
+            }
+        }
+        if !any || slots.len() >= config.slots_to_offer {
+            break;
+        }
+    }
+    slots.sort_by_key(|slot| slot.start);
+    slots
+}
+
+/// "re: fw: Sync" and "Sync" are the same conversation
+pub fn normalize_subject(subject: &str) -> String {
+    let mut subject = subject.trim().to_lowercase();
+    loop {
+        let stripped = ["re:", "fw:", "fwd:", "aw:"]
+            .iter()
+            .find_map(|prefix| subject.strip_prefix(prefix))
+            .map(|rest| rest.trim_start().to_string());
+        match stripped {
+            Some(rest) => subject = rest,
+            None => return subject,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MeetingProposal {
+    /// The email the times were offered in reply to
+    pub email_id: String,
+    pub sender_email: String,
+    pub subject: String,
+    pub duration_minutes: u32,
+    pub slots: Vec<TimeSlot>,
+    pub proposed_at: chrono::DateTime<Utc>,
+}
+
+/// Times offered to people, waiting for their answer
+pub struct ProposalStore {
+    path: Option<PathBuf>,
+    proposals: Mutex<Vec<MeetingProposal>>,
+}
+
+impl ProposalStore {
+    pub fn load(path: PathBuf) -> Self {
+        let proposals = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+        Self {
+            path: Some(path),
+            proposals: Mutex::new(proposals),
+        }
+    }
+
+    pub fn in_memory() -> Self {
+        Self {
+            path: None,
+            proposals: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Record offered times; a new offer in the same conversation replaces the last one
+    pub fn upsert(&self, proposal: MeetingProposal) -> Result<(), String> {
+        let mut proposals = self.proposals.lock().unwrap();
+        let subject = normalize_subject(&proposal.subject);
+        proposals.retain(|other| {
+            !(other
+                .sender_email
+                .eq_ignore_ascii_case(&proposal.sender_email)
+                && normalize_subject(&other.subject) == subject)
+        });
+        proposals.push(proposal);
+        self.persist(&proposals)
+    }
+
+    /// The open proposal an incoming email answers, matched by sender and conversation
+    pub fn find_reply(&self, sender_email: &str, subject: &str) -> Option<MeetingProposal> {
+        let subject = normalize_subject(subject);
+        self.proposals
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|proposal| {
+                proposal.sender_email.eq_ignore_ascii_case(sender_email)
+                    && normalize_subject(&proposal.subject) == subject
+            })
+            .cloned()
+    }
+
+    pub fn remove(&self, email_id: &str) -> Result<(), String> {
+        let mut proposals = self.proposals.lock().unwrap();
+        proposals.retain(|proposal| proposal.email_id != email_id);
+        self.persist(&proposals)
+    }
+
+    fn persist(&self, proposals: &[MeetingProposal]) -> Result<(), String> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let contents = serde_json::to_string_pretty(proposals).map_err(|e| e.to_string())?;
+        std::fs::write(path, contents).map_err(|e| e.to_string())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MeetingStatus {
+    /// Times offered for the first time
+    Proposed,
+    /// They picked a time and an invite went out
+    Confirmed,
+    /// None of the times worked, so new ones are offered
+    Countered,
+}
+
+/// Which offered slot the slot-choice prompt picked; "0" or anything unexpected is none
+pub fn parse_slot_choice(text: &str, offered: usize) -> Option<usize> {
+    let choice: usize = text
+        .trim()
+        .trim_end_matches('.')
+        .parse()
+        .ok()
+        .filter(|choice| (1..=offered).contains(choice))?;
+    Some(choice - 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        // May 2024: the 10th is a Friday
+        NaiveDate::from_ymd_opt(2024, 5, day)
+            .unwrap()
+            .and_hms_opt(hour, minute, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_free_slots() {
+        let config = SchedulingConfig {
+            days_ahead: 2,
+            ..SchedulingConfig::default()
+        };
+        let busy = [(at(10, 9, 0), at(10, 11, 0))];
+        let slots = free_slots(&config, &busy, &[], at(10, 8, 10), Duration::minutes(30));
+        let starts: Vec<NaiveDateTime> = slots.iter().map(|slot| slot.start).collect();
+        // Friday after the meeting and its buffer, then Monday, skipping the weekend
+        assert_eq!(starts, [at(10, 11, 30), at(10, 12, 0), at(13, 9, 0)]);
+
+        let later = free_slots(&config, &busy, &slots, at(10, 8, 10), Duration::minutes(30));
+        assert!(later.iter().all(|slot| !slots.contains(slot)));
+    }
+
+    #[test]
+    fn test_proposals() {
+        let store = ProposalStore::in_memory();
+        store
+            .upsert(MeetingProposal {
+                email_id: "a".to_string(),
+                sender_email: "jane@example.com".to_string(),
+                subject: "Quarterly sync".to_string(),
+                duration_minutes: 30,
+                slots: Vec::new(),
+                proposed_at: Utc::now(),
+            })
+            .unwrap();
+        assert!(store
+            .find_reply("Jane@example.com", "RE: Fwd: quarterly sync")
+            .is_some());
+        assert!(store
+            .find_reply("sam@example.com", "Quarterly sync")
+            .is_none());
+        store.remove("a").unwrap();
+        assert!(store
+            .find_reply("jane@example.com", "Quarterly sync")
+            .is_none());
+
+        assert_eq!(parse_slot_choice("2", 3), Some(1));
+        assert_eq!(parse_slot_choice("0", 3), None);
+        assert_eq!(parse_slot_choice("the first one", 3), None);
+    }
+}
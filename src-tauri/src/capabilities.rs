@@ -0,0 +1,182 @@
+// SERINA Capabilities
+// Which commands each window may invoke; popups only get short-lived tokens for their own email
+
+use crate::windows::AppWindow;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// How long a popup's token stays valid
+pub const TOKEN_TTL: Duration = Duration::from_secs(15 * 60);
+
+/// Argument carrying the token in an invoke payload
+const TOKEN_ARG: &str = "capabilityToken";
+
+/// What the reminder popup may call without a token: its own chrome and counts, nothing that
+/// reads mail or reaches the backend
+const REMINDER_COMMANDS: &[&str] = &[
+    "get_theme",
+    "get_ui_config",
+    "get_lock_status",
+    "unlock_app",
+    "close_window",
+    "get_unread_count",
+    "open_main_window",
+];
+
+/// Commands a window may call without a token; `None` means the window is trusted with all
+fn open_commands(window: AppWindow) -> Option<&'static [&'static str]> {
+    match window {
+        AppWindow::Reminder => Some(REMINDER_COMMANDS),
+        AppWindow::Main
+        | AppWindow::Settings
+        | AppWindow::MiniInbox
+        | AppWindow::Print
+        | AppWindow::Compose
+        | AppWindow::Upcoming
+        | AppWindow::Activity => None,
+    }
+}
+
+struct Grant {
+    window: &'static str,
+    commands: &'static [&'static str],
+    /// The only email the granted commands may touch
+    email_id: Option<String>,
+    expires: Instant,
+}
+
+/// Tokens handed to restricted windows, one live token per window
+#[derive(Default)]
+pub struct CapabilityStore {
+    grants: Mutex<HashMap<String, Grant>>,
+}
+
+impl CapabilityStore {
+    /// A token letting `window` call `commands`, limited to `email_id` when given. Replaces any
+    /// token the window held before.
+    pub fn issue(
+        &self,
+        window: AppWindow,
+        commands: &'static [&'static str],
+        email_id: Option<&str>,
+        now: Instant,
+    ) -> String {
+        let mut bytes = [0u8; 16];
+        crate::encryption::random_bytes(&mut bytes);
+        let token: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+        let mut grants = self.grants.lock().unwrap();
+        grants.retain(|_, grant| grant.window != window.label() && grant.expires > now);
+        grants.insert(
+            token.clone(),
+            Grant {
+                window: window.label(),
+                commands,
+                email_id: email_id.map(str::to_string),
+                expires: now + TOKEN_TTL,
+            },
+        );
+        token
+    }
+
+    /// Drop a closed window's token
+    pub fn revoke(&self, window: &str) {
+        self.grants
+            .lock()
+            .unwrap()
+            .retain(|_, grant| grant.window != window);
+    }
+
+    /// Whether the window labelled `window` may invoke `command` with these arguments
+    pub fn authorize(
+        &self,
+        window: &str,
+        command: &str,
+        args: &Value,
+        now: Instant,
+    ) -> Result<(), String> {
+        let denied = || format!("\"{}\" isn't available in this window", command);
+        let Some(kind) = AppWindow::from_label(window) else {
+            return Err(denied());
+        };
+        let Some(open) = open_commands(kind) else {
+            return Ok(());
+        };
+        if open.contains(&command) {
+            return Ok(());
+        }
+        let token = args
+            .get(TOKEN_ARG)
+            .and_then(Value::as_str)
+            .ok_or_else(denied)?;
+        let grants = self.grants.lock().unwrap();
+        let grant = grants
+            .get(token)
+            .filter(|grant| grant.window == window)
+            .ok_or_else(denied)?;
+        if grant.expires <= now {
+            return Err("This window's permission has expired; reopen it".to_string());
+        }
+        if !grant.commands.contains(&command) {
+            return Err(denied());
+        }
+        if let Some(email_id) = &grant.email_id {
+            if args.get("emailId").and_then(Value::as_str) != Some(email_id.as_str()) {
+                return Err("This window can only act on its own email".to_string());
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_authorize() {
+        let store = CapabilityStore::default();
+        let now = Instant::now();
+        assert!(store
+            .authorize("main", "backend_request", &json!({}), now)
+            .is_ok());
+        assert!(store
+            .authorize("reminder", "get_unread_count", &json!({}), now)
+            .is_ok());
+        assert!(store
+            .authorize("reminder", "backend_request", &json!({}), now)
+            .is_err());
+        assert!(store
+            .authorize("elsewhere", "get_theme", &json!({}), now)
+            .is_err());
+
+        let token = store.issue(AppWindow::Reminder, &["mark_email_read"], Some("42"), now);
+        let args = |email_id: &str| json!({ "emailId": email_id, "capabilityToken": token });
+        assert!(store
+            .authorize("reminder", "mark_email_read", &args("42"), now)
+            .is_ok());
+        assert_eq!(
+            store.authorize("reminder", "mark_email_read", &args("43"), now),
+            Err("This window can only act on its own email".to_string())
+        );
+        assert!(store
+            .authorize("reminder", "delete_email", &args("42"), now)
+            .is_err());
+        assert!(store
+            .authorize("reminder", "mark_email_read", &args("42"), now + TOKEN_TTL)
+            .is_err());
+
+        // A new popup's token replaces the old one
+        let newer = store.issue(AppWindow::Reminder, &["mark_email_read"], Some("7"), now);
+        assert!(store
+            .authorize("reminder", "mark_email_read", &args("42"), now)
+            .is_err());
+        store.revoke("reminder");
+        let revoked = json!({ "emailId": "7", "capabilityToken": newer });
+        assert!(store
+            .authorize("reminder", "mark_email_read", &revoked, now)
+            .is_err());
+    }
+}
@@ -0,0 +1,149 @@
+// SERINA Clipboard
+// Emails and summaries formatted for pasting into docs and chat tools, as Markdown and HTML
+
+use crate::mailview;
+use crate::parsing;
+use crate::utils::EmailData;
+use std::sync::Mutex;
+
+/// Both flavours of one copy: editors that take rich text paste the HTML, everything else the
+/// Markdown
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClipboardContent {
+    pub text: String,
+    pub html: String,
+}
+
+/// Outlook's timestamps trimmed to the minute; anything unparseable is shown as sent
+fn display_date(received_time: &str) -> String {
+    chrono::DateTime::parse_from_rfc3339(received_time)
+        .map(|date| date.naive_local())
+        .or_else(|_| chrono::NaiveDateTime::parse_from_str(received_time, "%Y-%m-%dT%H:%M:%S%.f"))
+        .map(|date| date.format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_else(|_| received_time.to_string())
+}
+
+/// The email with its headers. `html` is the cached HTML body, used for the rich flavour.
+pub fn email(email: &EmailData, html: Option<&str>) -> ClipboardContent {
+    let from = mailview::from_line(email);
+    let date = display_date(&email.received_time);
+    let text = format!(
+        "## {}\n\n**From:** {}  \n**Date:** {}\n\n{}\n",
+        email.subject,
+        from,
+        date,
+        parsing::plain_text(&email.body).trim()
+    );
+    let html = format!(
+        "<h2>{}</h2><p><b>From:</b> {}<br><b>Date:</b> {}</p>{}",
+        mailview::escape(&email.subject),
+        mailview::escape(&from),
+        mailview::escape(&date),
+        mailview::body_html(email, html)
+    );
+    ClipboardContent { text, html }
+}
+
+/// A summary credited to the email it came from
+pub fn summary(email: &EmailData, summary: &str) -> ClipboardContent {
+    let summary = summary.trim();
+    let text = format!(
+        "**Summary of \"{}\"** ({}, {})\n\n{}\n",
+        email.subject,
+        email.sender,
+        display_date(&email.received_time),
+        summary
+    );
+    let paragraphs: String = summary
+        .split("\n\n")
+        .map(|paragraph| {
+            format!(
+                "<p>{}</p>",
+                mailview::escape(paragraph.trim()).replace('\n', "<br>")
+            )
+        })
+        .collect();
+    let html = format!(
+        "<p><b>Summary of \u{201c}{}\u{201d}</b> ({}, {})</p>{}",
+        mailview::escape(&email.subject),
+        mailview::escape(&email.sender),
+        mailview::escape(&display_date(&email.received_time)),
+        paragraphs
+    );
+    ClipboardContent { text, html }
+}
+
+/// A bare piece of text, such as a one-time code
+pub fn text(text: &str) -> ClipboardContent {
+    ClipboardContent {
+        text: text.to_string(),
+        html: mailview::escape(text),
+    }
+}
+
+/// The system clipboard, opened on first use and kept open: on X11 copied content is served by
+/// the process that owns the clipboard, so it would vanish if the handle were dropped
+#[derive(Default)]
+pub struct Clipboard {
+    handle: Mutex<Option<arboard::Clipboard>>,
+}
+
+impl Clipboard {
+    /// Put both flavours on the clipboard
+    pub fn write(&self, content: &ClipboardContent) -> Result<(), String> {
+        let mut handle = self.handle.lock().unwrap();
+        if handle.is_none() {
+            *handle = Some(arboard::Clipboard::new().map_err(|e| e.to_string())?);
+        }
+        let clipboard = handle.as_mut().expect("clipboard was just opened");
+        clipboard
+            .set_html(&content.html, Some(&content.text))
+            .map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> EmailData {
+        serde_json::from_value(serde_json::json!({
+            "id": "abc",
+            "subject": "Budget <draft>",
+            "sender": "Dana",
+            "sender_email": "dana@example.com",
+            "body": "<p>Numbers attached.</p><script>alert(1)</script>",
+            "received_time": "2024-05-14T09:12:33",
+            "is_unread": true
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_email() {
+        let copied = email(&sample(), None);
+        assert!(copied.text.starts_with("## Budget <draft>\n"));
+        assert!(copied
+            .text
+            .contains("**From:** Dana <dana@example.com>  \n"));
+        assert!(copied.text.contains("**Date:** 2024-05-14 09:12"));
+        assert!(copied.text.contains("Numbers attached."));
+        assert!(!copied.text.contains("<p>"));
+        assert!(copied.html.contains("<h2>Budget &lt;draft&gt;</h2>"));
+        assert!(copied.html.contains("<p>Numbers attached.</p>"));
+        assert!(!copied.html.contains("<script>"));
+    }
+
+    #[test]
+    fn test_summary() {
+        let copied = summary(&sample(), "Dana sent the numbers.\n\nReply by <Friday>.");
+        assert!(copied
+            .text
+            .starts_with("**Summary of \"Budget <draft>\"** (Dana, 2024-05-14 09:12)"));
+        assert!(copied.text.ends_with("Reply by <Friday>.\n"));
+        assert!(copied
+            .html
+            .ends_with("<p>Dana sent the numbers.</p><p>Reply by &lt;Friday&gt;.</p>"));
+        assert_eq!(display_date("not a date"), "not a date");
+    }
+}
@@ -0,0 +1,86 @@
+// SERINA Clock
+// The current time, swappable so quiet hours, snoozes and scheduling can be tested
+
+use chrono::{DateTime, FixedOffset, Local, NaiveDateTime, NaiveTime, Utc};
+use std::sync::{Arc, Mutex};
+
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+
+    /// Date and time in the user's timezone, for calendar days and working hours
+    fn local_now(&self) -> NaiveDateTime;
+
+    /// Time of day in the user's timezone, which quiet hours are written in
+    fn local_time(&self) -> NaiveTime {
+        self.local_now().time()
+    }
+}
+
+/// The clock as managed state
+pub type SharedClock = Arc<dyn Clock>;
+
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+
+    fn local_now(&self) -> NaiveDateTime {
+        Local::now().naive_local()
+    }
+}
+
+pub fn system() -> SharedClock {
+    Arc::new(SystemClock)
+}
+
+/// A clock that only moves when told to, in a fixed timezone
+pub struct MockClock {
+    now: Mutex<DateTime<FixedOffset>>,
+}
+
+impl MockClock {
+    /// Start at an RFC 3339 time, e.g. "2024-05-14T22:30:00-03:00"
+    pub fn at(time: &str) -> Self {
+        Self {
+            now: Mutex::new(DateTime::parse_from_rfc3339(time).expect("valid RFC 3339 time")),
+        }
+    }
+
+    pub fn set(&self, time: &str) {
+        *self.now.lock().unwrap() =
+            DateTime::parse_from_rfc3339(time).expect("valid RFC 3339 time");
+    }
+
+    pub fn advance(&self, by: chrono::Duration) {
+        let mut now = self.now.lock().unwrap();
+        *now += by;
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> DateTime<Utc> {
+        self.now.lock().unwrap().with_timezone(&Utc)
+    }
+
+    fn local_now(&self) -> NaiveDateTime {
+        self.now.lock().unwrap().naive_local()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mock_clock() {
+        let clock = MockClock::at("2024-05-14T23:30:00-03:00");
+        assert_eq!(clock.now().to_rfc3339(), "2024-05-15T02:30:00+00:00");
+        assert_eq!(clock.local_time().to_string(), "23:30:00");
+
+        clock.advance(chrono::Duration::minutes(45));
+        assert_eq!(clock.local_time().to_string(), "00:15:00");
+        assert_eq!(clock.local_now().date().to_string(), "2024-05-15");
+    }
+}
@@ -0,0 +1,420 @@
+// SERINA Compose Checks
+// Pre-send checks for outgoing replies
+
+use crate::dates;
+use crate::recipients::RecipientCheck;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WarningKind {
+    EmptyBody,
+    Placeholder,
+    RepeatedWord,
+    MissingAttachment,
+    /// Someone else answered the thread first, e.g. a teammate in a shared mailbox
+    AlreadyReplied,
+    /// A generated reply runs past the configured length
+    TooLong,
+    /// A generated reply uses a phrase from the banned list
+    BannedPhrase,
+    /// A generated reply talks about an attachment nobody added
+    InventedAttachment,
+    /// A generated reply links somewhere the original email didn't
+    InventedLink,
+    /// A generated reply isn't in the original email's language
+    LanguageMismatch,
+    /// A recipient's domain looks like a misspelling of a common one
+    DomainTypo,
+    /// A recipient isn't in the contacts
+    NewRecipient,
+    /// Reply all to a long recipient list
+    ReplyAll,
+    /// Reply all reaches people outside the organization on a thread that mixes both
+    ExternalRecipients,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SendWarning {
+    pub kind: WarningKind,
+    pub message: String,
+}
+
+/// Which checks to run on a reply and which warnings the user has already seen and accepted
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SendOptions {
+    /// Also look for an empty body, leftover placeholders and repeated words
+    pub check: bool,
+    /// Files attached in the compose window, on top of the staged ones
+    pub attachment_count: u32,
+    pub allow_missing_attachment: bool,
+    pub allow_collision: bool,
+    pub acknowledge_guardrails: bool,
+    /// Answer everyone on the original, not just the sender
+    pub reply_all: bool,
+    pub allow_reply_all: bool,
+    pub allow_external: bool,
+}
+
+impl SendWarning {
+    pub fn new(kind: WarningKind, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            message: message.into(),
+        }
+    }
+}
+
+/// Phrases that announce an attachment, across the languages we see most often
+const ATTACHMENT_PHRASES: &[&str] = &[
+    // English
+    "attached",
+    "attachment",
+    "enclosed",
+    // Spanish
+    "adjunto",
+    "adjunta",
+    "archivo adjunto",
+    // Portuguese
+    "anexo",
+    "anexado",
+    "anexada",
+    // French
+    "ci-joint",
+    "ci-jointe",
+    "pièce jointe",
+    "en pièce jointe",
+    // German
+    "anhang",
+    "angehängt",
+    "beigefügt",
+    // Italian
+    "allegato",
+    "allegata",
+    "in allegato",
+    // Dutch
+    "bijlage",
+    "bijgevoegd",
+];
+
+/// Placeholder markers that usually mean a draft wasn't finished
+const PLACEHOLDERS: &[&str] = &["[name]", "[your name]", "{{", "xxx", "tbd", "lorem ipsum"];
+
+/// Run the local pre-send checks over a reply body
+pub fn pre_send_checks(text: &str) -> Vec<SendWarning> {
+    let mut warnings = Vec::new();
+
+    if text.trim().is_empty() {
+        warnings.push(SendWarning::new(WarningKind::EmptyBody, "Reply is empty"));
+        return warnings;
+    }
+
+    let lower = text.to_lowercase();
+    if let Some(marker) = PLACEHOLDERS.iter().find(|p| lower.contains(*p)) {
+        warnings.push(SendWarning::new(
+            WarningKind::Placeholder,
+            format!("Reply still contains placeholder text \"{}\"", marker),
+        ));
+    }
+
+    if let Some(word) = find_repeated_word(&lower) {
+        warnings.push(SendWarning::new(
+            WarningKind::RepeatedWord,
+            format!("Repeated word \"{}\"", word),
+        ));
+    }
+
+    warnings
+}
+
+/// Check whether the body mentions an attachment in any supported language
+pub fn mentions_attachment(text: &str) -> Option<&'static str> {
+    let lower = text.to_lowercase();
+    ATTACHMENT_PHRASES
+        .iter()
+        .copied()
+        .find(|phrase| contains_phrase(&lower, phrase))
+}
+
+/// Warn when the body announces an attachment but nothing is attached
+pub fn missing_attachment_check(text: &str, attachment_count: u32) -> Option<SendWarning> {
+    if attachment_count > 0 {
+        return None;
+    }
+    mentions_attachment(text).map(|phrase| {
+        SendWarning::new(
+            WarningKind::MissingAttachment,
+            format!(
+                "Reply mentions an attachment (\"{}\") but none is included",
+                phrase
+            ),
+        )
+    })
+}
+
+/// A reply to an email's conversation found in its mailbox
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThreadReply {
+    /// Empty when only the fact of a reply is known, not who sent it
+    #[serde(default)]
+    pub sender: String,
+    pub sent_time: String,
+    #[serde(default)]
+    pub from_me: bool,
+}
+
+/// Replies this soon after one of ours are taken to be ours, reaching Sent Items late
+const OWN_REPLY_GRACE_MINUTES: i64 = 5;
+
+/// Warn when someone else replied after the email arrived and after our own last reply, so
+/// two people working the same mailbox don't both answer
+pub fn collision_check(
+    replies: &[ThreadReply],
+    received: DateTime<Utc>,
+    last_own_reply: Option<DateTime<Utc>>,
+) -> Option<SendWarning> {
+    let since = last_own_reply
+        .map(|time| time + Duration::minutes(OWN_REPLY_GRACE_MINUTES))
+        .map_or(received, |time| time.max(received));
+    let (latest, _) = replies
+        .iter()
+        .filter(|reply| !reply.from_me)
+        .filter_map(|reply| Some((reply, dates::parse_received_time(&reply.sent_time)?)))
+        .filter(|(_, sent)| *sent > since)
+        .max_by_key(|(_, sent)| *sent)?;
+    let message = match latest.sender.trim() {
+        "" => "Someone already replied to this conversation".to_string(),
+        sender => format!("{} already replied to this conversation", sender),
+    };
+    Some(SendWarning::new(WarningKind::AlreadyReplied, message))
+}
+
+/// Warnings for recipients that are well formed but may not be who was meant
+pub fn recipient_warnings(checks: &[RecipientCheck]) -> Vec<SendWarning> {
+    let mut warnings = Vec::new();
+    for check in checks {
+        if let Some(suggestion) = &check.suggestion {
+            warnings.push(SendWarning::new(
+                WarningKind::DomainTypo,
+                format!("Did you mean {} instead of {}?", suggestion, check.address),
+            ));
+        }
+        if check.new_recipient {
+            warnings.push(SendWarning::new(
+                WarningKind::NewRecipient,
+                format!("{} isn't in your contacts", check.address),
+            ));
+        }
+    }
+    warnings
+}
+
+/// Replying all to more people than this needs confirming
+pub const REPLY_ALL_THRESHOLD: usize = 10;
+/// External addresses named in the warning before the rest are counted
+const EXTERNAL_LISTED: usize = 3;
+
+/// Who an email went to, from the full email
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ThreadRecipients {
+    pub sender_email: String,
+    pub to: Vec<String>,
+    pub cc: Vec<String>,
+}
+
+impl ThreadRecipients {
+    /// Everyone a reply all reaches: the sender, To and CC, once each, less `own` addresses
+    pub fn reply_all(&self, own: &[String]) -> Vec<String> {
+        let mut everyone: Vec<String> = Vec::new();
+        let addresses = std::iter::once(&self.sender_email)
+            .chain(&self.to)
+            .chain(&self.cc);
+        for address in addresses {
+            let address = address.trim().to_lowercase();
+            if !address.is_empty()
+                && !everyone.contains(&address)
+                && !own.iter().any(|own| own.eq_ignore_ascii_case(&address))
+            {
+                everyone.push(address);
+            }
+        }
+        everyone
+    }
+}
+
+/// Whether `address` is at one of `domains` or a subdomain of one
+fn is_internal(address: &str, domains: &[String]) -> bool {
+    let domain = address.rsplit('@').next().unwrap_or_default();
+    domains.iter().any(|internal| {
+        let internal = internal.trim().trim_start_matches('@').to_lowercase();
+        !internal.is_empty() && (domain == internal || domain.ends_with(&format!(".{}", internal)))
+    })
+}
+
+/// Warn before a reply all reaches many people, or reaches outsiders on a thread that also
+/// has colleagues on it. Without `internal_domains` nobody counts as external.
+pub fn reply_all_checks(
+    recipients: &ThreadRecipients,
+    own: &[String],
+    internal_domains: &[String],
+) -> Vec<SendWarning> {
+    let everyone = recipients.reply_all(own);
+    let mut warnings = Vec::new();
+    if everyone.len() > REPLY_ALL_THRESHOLD {
+        warnings.push(SendWarning::new(
+            WarningKind::ReplyAll,
+            format!("Reply all goes to {} people", everyone.len()),
+        ));
+    }
+    let external: Vec<&String> = everyone
+        .iter()
+        .filter(|address| !is_internal(address, internal_domains))
+        .collect();
+    if !internal_domains.is_empty() && !external.is_empty() && external.len() < everyone.len() {
+        let listed: Vec<&str> = external
+            .iter()
+            .take(EXTERNAL_LISTED)
+            .map(|address| address.as_str())
+            .collect();
+        let more = match external.len().saturating_sub(EXTERNAL_LISTED) {
+            0 => String::new(),
+            more => format!(" and {} more", more),
+        };
+        warnings.push(SendWarning::new(
+            WarningKind::ExternalRecipients,
+            format!(
+                "Reply all includes people outside your organization: {}{}",
+                listed.join(", "),
+                more
+            ),
+        ));
+    }
+    warnings
+}
+
+/// Error string returned to the frontend when a send is held back by checks
+pub fn blocked_error(warnings: &[SendWarning]) -> String {
+    serde_json::json!({
+        "error": "send_blocked",
+        "warnings": warnings
+    })
+    .to_string()
+}
+
+/// Substring match that only accepts hits on word boundaries
+pub(crate) fn contains_phrase(haystack: &str, phrase: &str) -> bool {
+    haystack.match_indices(phrase).any(|(start, _)| {
+        let end = start + phrase.len();
+        let before = haystack[..start].chars().next_back();
+        let after = haystack[end..].chars().next();
+        !before.is_some_and(char::is_alphanumeric) && !after.is_some_and(char::is_alphanumeric)
+    })
+}
+
+fn find_repeated_word(lower: &str) -> Option<String> {
+    let words: Vec<&str> = lower
+        .split_whitespace()
+        .map(|w| w.trim_matches(|c: char| !c.is_alphanumeric()))
+        .filter(|w| !w.is_empty())
+        .collect();
+
+    words
+        .windows(2)
+        .find(|pair| pair[0] == pair[1] && pair[0].chars().any(|c| c.is_alphabetic()))
+        .map(|pair| pair[0].to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(text: &str) -> Vec<WarningKind> {
+        pre_send_checks(text).into_iter().map(|w| w.kind).collect()
+    }
+
+    #[test]
+    fn test_pre_send_checks() {
+        assert!(pre_send_checks("Thanks, see you on Monday.").is_empty());
+        assert_eq!(kinds("   "), vec![WarningKind::EmptyBody]);
+        assert_eq!(kinds("Hi [Name], thanks!"), vec![WarningKind::Placeholder]);
+        assert_eq!(
+            kinds("I will send the the file"),
+            vec![WarningKind::RepeatedWord]
+        );
+    }
+
+    #[test]
+    fn test_missing_attachment_check() {
+        assert!(missing_attachment_check("Please find the report attached.", 0).is_some());
+        assert!(missing_attachment_check("Please find the report attached.", 1).is_none());
+        assert!(missing_attachment_check("Segue em anexo o contrato.", 0).is_some());
+        assert!(missing_attachment_check("Veuillez trouver ci-joint la facture", 0).is_some());
+        assert!(missing_attachment_check("Die Datei ist im Anhang.", 0).is_some());
+        assert!(missing_attachment_check("Thanks, talk soon.", 0).is_none());
+        // Word boundaries avoid false positives
+        assert!(missing_attachment_check("I'm unattached to the idea", 0).is_none());
+    }
+
+    #[test]
+    fn test_collision_check() {
+        let reply = |sender: &str, sent_time: &str, from_me| ThreadReply {
+            sender: sender.to_string(),
+            sent_time: sent_time.to_string(),
+            from_me,
+        };
+        let received = dates::parse_received_time("2024-05-14T09:00:00Z").unwrap();
+        let replies = vec![
+            reply("Me", "2024-05-14 09:30:00+00:00", true),
+            reply("Ben", "2024-05-14 09:10:00+00:00", false),
+            reply("", "2024-05-14 08:00:00+00:00", false),
+        ];
+        assert_eq!(
+            collision_check(&replies, received, None).unwrap().message,
+            "Ben already replied to this conversation"
+        );
+        let own = dates::parse_received_time("2024-05-14T09:08:00Z");
+        assert!(collision_check(&replies, received, own).is_none());
+        assert!(collision_check(&replies[2..], received, None).is_none());
+    }
+
+    #[test]
+    fn test_reply_all_checks() {
+        let own = vec!["me@acme.example".to_string()];
+        let internal = vec!["acme.example".to_string()];
+        let recipients = |to: &[&str]| ThreadRecipients {
+            sender_email: "Boss@acme.example".to_string(),
+            to: to.iter().map(|a| a.to_string()).collect(),
+            cc: vec!["me@acme.example".to_string()],
+        };
+        let kinds = |recipients: &ThreadRecipients, internal: &[String]| {
+            reply_all_checks(recipients, &own, internal)
+                .into_iter()
+                .map(|w| w.kind)
+                .collect::<Vec<_>>()
+        };
+
+        let colleagues = recipients(&["ana@acme.example", "ben@eu.acme.example"]);
+        assert_eq!(colleagues.reply_all(&own).len(), 3);
+        assert!(kinds(&colleagues, &internal).is_empty());
+
+        let mixed = recipients(&["ana@acme.example", "client@partner.example"]);
+        let warnings = reply_all_checks(&mixed, &own, &internal);
+        assert_eq!(warnings[0].kind, WarningKind::ExternalRecipients);
+        assert_eq!(
+            warnings[0].message,
+            "Reply all includes people outside your organization: client@partner.example"
+        );
+        // Nothing is external when the organization's domains aren't known
+        assert!(kinds(&mixed, &[]).is_empty());
+
+        let crowd: Vec<String> = (0..12).map(|n| format!("p{}@acme.example", n)).collect();
+        let crowd: Vec<&str> = crowd.iter().map(String::as_str).collect();
+        assert_eq!(
+            kinds(&recipients(&crowd), &internal),
+            vec![WarningKind::ReplyAll]
+        );
+    }
+}
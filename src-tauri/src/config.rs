@@ -0,0 +1,321 @@
+// SERINA Configuration
+// Reads the config file shared with the Python backend (~/.serina/config.json)
+
+use crate::logging::LogLevel;
+use crate::utils::AppConfig;
+use crate::{purge, settings};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::SystemTime;
+use tokio::sync::watch;
+
+static CONFIG_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// Directory holding SERINA's config and local data
+pub fn config_dir() -> PathBuf {
+    CONFIG_DIR
+        .get_or_init(|| {
+            dirs::home_dir()
+                .unwrap_or_else(|| PathBuf::from("."))
+                .join(".serina")
+        })
+        .clone()
+}
+
+/// Keep config and local data somewhere else; only takes effect before the first `config_dir`
+pub fn use_config_dir(dir: PathBuf) -> Result<(), String> {
+    CONFIG_DIR
+        .set(dir)
+        .map_err(|_| "Config directory is already in use".to_string())
+}
+
+pub fn config_path() -> PathBuf {
+    config_dir().join("config.json")
+}
+
+/// Settings taken from the environment or command line instead of the config file, e.g. to
+/// run against a staging backend. Layered as defaults < file < environment < flags.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Overrides {
+    pub backend_url: Option<String>,
+    pub config_dir: Option<PathBuf>,
+    pub log_level: Option<LogLevel>,
+    /// Serve canned fixtures instead of calling the backend and LLM providers
+    pub mock: bool,
+}
+
+impl Overrides {
+    /// `SERINA_BACKEND_URL`, `SERINA_CONFIG_DIR`, `SERINA_LOG_LEVEL` and `SERINA_MOCK`, then
+    /// `--backend-url`, `--config-dir`, `--log-level` and `--mock`
+    pub fn resolve(env: impl Fn(&str) -> Option<String>, args: &[String]) -> Result<Self, String> {
+        let mut overrides = Self {
+            backend_url: env("SERINA_BACKEND_URL").filter(|url| !url.is_empty()),
+            config_dir: env("SERINA_CONFIG_DIR")
+                .filter(|dir| !dir.is_empty())
+                .map(PathBuf::from),
+            log_level: env("SERINA_LOG_LEVEL")
+                .filter(|level| !level.is_empty())
+                .map(|level| LogLevel::parse(&level))
+                .transpose()?,
+            mock: env("SERINA_MOCK").is_some_and(|mock| !matches!(mock.as_str(), "" | "0")),
+        };
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let (flag, inline) = match arg.split_once('=') {
+                Some((flag, value)) => (flag, Some(value.to_string())),
+                None => (arg.as_str(), None),
+            };
+            if flag == "--mock" {
+                overrides.mock = true;
+                continue;
+            }
+            if !matches!(flag, "--backend-url" | "--config-dir" | "--log-level") {
+                // Other arguments belong to the OS or the webview
+                continue;
+            }
+            let value = inline
+                .or_else(|| args.next().cloned())
+                .ok_or_else(|| format!("{} needs a value", flag))?;
+            match flag {
+                "--backend-url" => overrides.backend_url = Some(value),
+                "--log-level" => overrides.log_level = Some(LogLevel::parse(&value)?),
+                _ => overrides.config_dir = Some(PathBuf::from(value)),
+            }
+        }
+        if let Some(url) = &overrides.backend_url {
+            url::Url::parse(url).map_err(|e| format!("Invalid backend URL {}: {}", url, e))?;
+        }
+        Ok(overrides)
+    }
+
+    /// Overrides for this process
+    pub fn from_process() -> Result<Self, String> {
+        let args: Vec<String> = std::env::args().skip(1).collect();
+        Self::resolve(|name| std::env::var(name).ok(), &args)
+    }
+
+    pub fn apply(&self, config: &mut AppConfig) {
+        if let Some(url) = &self.backend_url {
+            config.network.backend_url = url.clone();
+        }
+    }
+}
+
+/// Modification time and length, to notice when the file changed on disk
+type FileStamp = Option<(Option<SystemTime>, u64)>;
+
+fn file_stamp(path: &Path) -> FileStamp {
+    let metadata = std::fs::metadata(path).ok()?;
+    Some((metadata.modified().ok(), metadata.len()))
+}
+
+/// The config as managed state. The Python backend and the settings window write the file
+/// too, so it's parsed again whenever the file changes on disk, and subscribers are told.
+pub struct ConfigStore {
+    path: PathBuf,
+    cached: Mutex<Option<(FileStamp, AppConfig)>>,
+    revision: watch::Sender<u64>,
+    overrides: Overrides,
+}
+
+impl Default for ConfigStore {
+    fn default() -> Self {
+        Self::at(config_path())
+    }
+}
+
+impl ConfigStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn at(path: PathBuf) -> Self {
+        Self {
+            path,
+            cached: Mutex::new(None),
+            revision: watch::channel(0).0,
+            overrides: Overrides::default(),
+        }
+    }
+
+    /// Layer environment and command-line settings over whatever the file says
+    pub fn with_overrides(mut self, overrides: Overrides) -> Self {
+        self.overrides = overrides;
+        self
+    }
+
+    pub fn overrides(&self) -> &Overrides {
+        &self.overrides
+    }
+
+    /// Receives the new revision number each time the config changes
+    pub fn subscribe(&self) -> watch::Receiver<u64> {
+        self.revision.subscribe()
+    }
+
+    fn changed(&self) {
+        self.revision.send_modify(|revision| *revision += 1);
+    }
+
+    /// Drop the parsed config after someone else saved it, e.g. the backend's `/config`
+    pub fn reload(&self) {
+        *self.cached.lock().unwrap() = None;
+        self.changed();
+    }
+
+    /// The current config, falling back to defaults for anything missing
+    pub fn get(&self) -> Result<AppConfig, String> {
+        let stamp = file_stamp(&self.path);
+        let mut cached = self.cached.lock().unwrap();
+        if let Some((seen, config)) = cached.as_ref() {
+            if *seen == stamp {
+                return Ok(config.clone());
+            }
+        }
+        let seen_before = cached.is_some();
+        let mut config = match stamp {
+            Some(_) => {
+                let contents = std::fs::read_to_string(&self.path).map_err(|e| e.to_string())?;
+                parse_config(&contents)?
+            }
+            None => AppConfig::default(),
+        };
+        self.overrides.apply(&mut config);
+        *cached = Some((stamp, config.clone()));
+        drop(cached);
+        if seen_before {
+            self.changed();
+        }
+        Ok(config)
+    }
+
+    /// The config file as raw JSON, keeping keys only the Python backend knows about
+    pub fn raw(&self) -> Result<serde_json::Value, String> {
+        if !self.path.exists() {
+            return Ok(serde_json::json!({}));
+        }
+        let contents = std::fs::read_to_string(&self.path).map_err(|e| e.to_string())?;
+        serde_json::from_str(&contents).map_err(|e| format!("Invalid config file: {}", e))
+    }
+
+    pub fn save_raw(&self, config: &serde_json::Value) -> Result<(), String> {
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+        let contents = serde_json::to_string_pretty(config).map_err(|e| e.to_string())?;
+        std::fs::write(&self.path, contents).map_err(|e| e.to_string())?;
+        self.reload();
+        Ok(())
+    }
+
+    /// Remove API keys and other credentials from the file, overwriting the old copy before
+    /// the new one is written; returns how many were removed
+    pub fn purge_secrets(&self) -> Result<usize, String> {
+        let mut raw = self.raw()?;
+        let removed = settings::strip_secrets(&mut raw);
+        if removed > 0 {
+            purge::secure_delete(&self.path)?;
+            self.save_raw(&raw)?;
+        }
+        Ok(removed)
+    }
+
+    /// Edit the raw JSON in place and save it
+    pub fn update(&self, edit: impl FnOnce(&mut serde_json::Value)) -> Result<(), String> {
+        let mut raw = self.raw()?;
+        edit(&mut raw);
+        self.save_raw(&raw)
+    }
+}
+
+/// Parse config JSON, upgrading old settings and applying the active notification profile
+pub fn parse_config(contents: &str) -> Result<AppConfig, String> {
+    let mut raw: serde_json::Value =
+        serde_json::from_str(contents).map_err(|e| format!("Invalid config file: {}", e))?;
+    crate::theme::migrate_legacy(&mut raw);
+    let mut config: AppConfig =
+        serde_json::from_value(raw).map_err(|e| format!("Invalid config file: {}", e))?;
+    crate::profiles::apply_active(&mut config.notifications);
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_partial_config() {
+        let config =
+            parse_config(r#"{"llm": {"provider": "openrouter"}, "reminders": {}}"#).unwrap();
+        assert_eq!(config.llm.provider, "openrouter");
+        assert_eq!(config.llm.model, "gpt-3.5-turbo");
+        assert_eq!(config.email.check_interval_minutes, 15);
+        assert!(parse_config("not json").is_err());
+    }
+
+    #[test]
+    fn test_config_store() {
+        let dir = std::env::temp_dir().join(format!("serina-config-{}", std::process::id()));
+        let store = ConfigStore::at(dir.join("config.json"));
+        let changes = store.subscribe();
+        assert_eq!(store.get().unwrap().llm.provider, "openai");
+        assert!(!changes.has_changed().unwrap());
+
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("config.json"), r#"{"backend_only": 1}"#).unwrap();
+        store
+            .update(|raw| raw["llm"] = serde_json::json!({ "provider": "openrouter" }))
+            .unwrap();
+        assert!(changes.has_changed().unwrap());
+        assert_eq!(store.get().unwrap().llm.provider, "openrouter");
+        assert_eq!(store.raw().unwrap()["backend_only"], 1);
+
+        // Written behind the store's back, as the Python backend does
+        std::fs::write(
+            dir.join("config.json"),
+            r#"{"llm": {"provider": "ollama"}}"#,
+        )
+        .unwrap();
+        assert_eq!(store.get().unwrap().llm.provider, "ollama");
+        assert_eq!(*changes.borrow(), 2);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_overrides() {
+        let env = |name: &str| {
+            (name == "SERINA_BACKEND_URL").then(|| "http://staging.local:8000".to_string())
+        };
+        let args = |list: &[&str]| list.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+
+        let overrides = Overrides::resolve(env, &args(&["--config-dir", "/tmp/serina"])).unwrap();
+        assert_eq!(
+            overrides.backend_url.as_deref(),
+            Some("http://staging.local:8000")
+        );
+        assert_eq!(overrides.config_dir, Some(PathBuf::from("/tmp/serina")));
+        assert!(!overrides.mock);
+
+        // Flags beat the environment
+        let overrides = Overrides::resolve(
+            env,
+            &args(&["-psn_0_1", "--mock", "--backend-url=http://[::1]:9000"]),
+        )
+        .unwrap();
+        let mut config = AppConfig::default();
+        overrides.apply(&mut config);
+        assert_eq!(config.network.backend_url(), "http://[::1]:9000");
+        assert!(overrides.mock);
+        assert_eq!(overrides.log_level, None);
+
+        let env = |name: &str| (name == "SERINA_LOG_LEVEL").then(|| "warning".to_string());
+        let overrides = Overrides::resolve(env, &args(&[])).unwrap();
+        assert_eq!(overrides.log_level, Some(LogLevel::Warn));
+        let overrides = Overrides::resolve(env, &args(&["--log-level", "debug"])).unwrap();
+        assert_eq!(overrides.log_level, Some(LogLevel::Debug));
+
+        assert!(Overrides::resolve(|_| None, &args(&["--backend-url"])).is_err());
+        assert!(Overrides::resolve(|_| None, &args(&["--log-level=loud"])).is_err());
+        assert!(Overrides::resolve(|_| None, &args(&["--backend-url=nope"])).is_err());
+    }
+}
@@ -0,0 +1,193 @@
+// SERINA Destructive Action Confirmation
+// Bulk deletes and data purges only run with a one-time token the UI asks for once the user has
+// confirmed, bound to exactly what gets destroyed, so a UI bug can't wipe mail on its own
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Deleting more emails than this at once needs a confirmation token
+pub const BULK_DELETE_THRESHOLD: usize = 10;
+/// How long the user has between confirming and the action running
+const TOKEN_TTL: Duration = Duration::from_secs(120);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DestructiveAction {
+    /// Targets are email ids
+    DeleteEmails,
+    /// Targets are `PurgeScope` names
+    PurgeData,
+}
+
+impl DestructiveAction {
+    /// Whether destroying `count` targets needs the user's confirmation
+    pub fn needs_confirmation(self, count: usize) -> bool {
+        match self {
+            DestructiveAction::DeleteEmails => count > BULK_DELETE_THRESHOLD,
+            DestructiveAction::PurgeData => count > 0,
+        }
+    }
+
+    /// Fluent id of the question the confirmation dialog asks
+    pub fn message_id(self) -> &'static str {
+        match self {
+            DestructiveAction::DeleteEmails => "confirm-delete-emails",
+            DestructiveAction::PurgeData => "confirm-purge-data",
+        }
+    }
+}
+
+/// Handed to the UI after the user confirms; passed back with the destructive command
+#[derive(Debug, Clone, Serialize)]
+pub struct Confirmation {
+    pub token: String,
+    pub action: DestructiveAction,
+    pub count: usize,
+    pub expires_in_secs: u64,
+}
+
+struct Pending {
+    action: DestructiveAction,
+    targets: BTreeSet<String>,
+    expires: Instant,
+}
+
+/// Tokens issued and not yet used
+#[derive(Default)]
+pub struct Confirmations {
+    pending: Mutex<HashMap<String, Pending>>,
+}
+
+impl Confirmations {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A token allowing `action` on `targets`, once
+    pub fn issue(
+        &self,
+        action: DestructiveAction,
+        targets: impl IntoIterator<Item = String>,
+        now: Instant,
+    ) -> Confirmation {
+        let targets: BTreeSet<String> = targets.into_iter().collect();
+        let mut bytes = [0u8; 16];
+        crate::encryption::random_bytes(&mut bytes);
+        let token: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+        let count = targets.len();
+        let mut pending = self.pending.lock().unwrap();
+        pending.retain(|_, entry| entry.expires > now);
+        pending.insert(
+            token.clone(),
+            Pending {
+                action,
+                targets,
+                expires: now + TOKEN_TTL,
+            },
+        );
+        Confirmation {
+            token,
+            action,
+            count,
+            expires_in_secs: TOKEN_TTL.as_secs(),
+        }
+    }
+
+    /// Check that `action` may go ahead on `targets`. Small deletes need no token; otherwise the
+    /// token must be unexpired, for this action, and cover every target. A token works once,
+    /// even if the check fails.
+    pub fn require(
+        &self,
+        token: Option<&str>,
+        action: DestructiveAction,
+        targets: &[String],
+        now: Instant,
+    ) -> Result<(), String> {
+        if !action.needs_confirmation(targets.len()) {
+            return Ok(());
+        }
+        let needed = || match action {
+            DestructiveAction::DeleteEmails => {
+                format!("Deleting {} emails needs confirmation", targets.len())
+            }
+            DestructiveAction::PurgeData => "Deleting local data needs confirmation".to_string(),
+        };
+        let token = token.ok_or_else(needed)?;
+        let pending = self
+            .pending
+            .lock()
+            .unwrap()
+            .remove(token)
+            .ok_or_else(needed)?;
+        if pending.expires <= now {
+            return Err("The confirmation expired; confirm again".to_string());
+        }
+        if pending.action != action || !targets.iter().all(|t| pending.targets.contains(t)) {
+            return Err("The confirmation doesn't cover everything being deleted".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ids(count: usize) -> Vec<String> {
+        (0..count).map(|n| n.to_string()).collect()
+    }
+
+    #[test]
+    fn test_bulk_delete_needs_matching_token() {
+        let confirmations = Confirmations::new();
+        let now = Instant::now();
+        let action = DestructiveAction::DeleteEmails;
+        assert!(confirmations
+            .require(None, action, &ids(BULK_DELETE_THRESHOLD), now)
+            .is_ok());
+        let many = ids(40);
+        assert_eq!(
+            confirmations.require(None, action, &many, now),
+            Err("Deleting 40 emails needs confirmation".to_string())
+        );
+
+        let confirmed = confirmations.issue(action, many.clone(), now);
+        assert_eq!(confirmed.count, 40);
+        // Fewer than confirmed is fine; the token is then spent
+        let token = Some(confirmed.token.as_str());
+        assert!(confirmations
+            .require(token, action, &many[..20], now)
+            .is_ok());
+        assert!(confirmations.require(token, action, &many, now).is_err());
+
+        let confirmed = confirmations.issue(action, many.clone(), now);
+        let more = ids(41);
+        assert!(confirmations
+            .require(Some(&confirmed.token), action, &more, now)
+            .is_err());
+
+        let confirmed = confirmations.issue(action, many.clone(), now);
+        assert!(confirmations
+            .require(Some(&confirmed.token), action, &many, now + TOKEN_TTL)
+            .is_err());
+    }
+
+    #[test]
+    fn test_purge_always_needs_token() {
+        let confirmations = Confirmations::new();
+        let now = Instant::now();
+        let purge = DestructiveAction::PurgeData;
+        let scopes = vec!["logs".to_string()];
+        assert!(confirmations.require(None, purge, &scopes, now).is_err());
+        let delete = confirmations.issue(DestructiveAction::DeleteEmails, scopes.clone(), now);
+        assert!(confirmations
+            .require(Some(&delete.token), purge, &scopes, now)
+            .is_err());
+        let confirmed = confirmations.issue(purge, scopes.clone(), now);
+        assert!(confirmations
+            .require(Some(&confirmed.token), purge, &scopes, now)
+            .is_ok());
+    }
+}
@@ -0,0 +1,118 @@
+// SERINA Date Formatting
+// Turns backend timestamps into localized relative and absolute display strings
+
+use crate::i18n::Localizer;
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone, Utc};
+use serde::Serialize;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FormattedTime {
+    /// e.g. "2 hours ago"
+    pub relative: String,
+    /// Date and time in the locale's usual layout, in local time
+    pub absolute: String,
+}
+
+/// Parse `received_time` as sent by the backend: RFC 3339, Python's `str(datetime)`,
+/// or a naive timestamp taken to be local time
+pub fn parse_received_time(value: &str) -> Option<DateTime<Utc>> {
+    let value = value.trim();
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Some(time.with_timezone(&Utc));
+    }
+    if let Ok(time) = DateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S%.f%:z") {
+        return Some(time.with_timezone(&Utc));
+    }
+    let naive = NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S%.f").ok()?;
+    Local
+        .from_local_datetime(&naive)
+        .earliest()
+        .map(|time| time.with_timezone(&Utc))
+}
+
+/// "just now", "5 minutes ago", "yesterday"; older than a week falls back to the absolute form
+pub fn relative(localizer: &Localizer, time: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    let elapsed = now.signed_duration_since(time);
+    let count = |n: i64| n.to_string();
+    match elapsed.num_minutes() {
+        m if m < 1 => localizer.text("time-just-now"),
+        m if m < 60 => localizer.format("time-minutes-ago", &[("count", &count(m))]),
+        _ => match elapsed.num_hours() {
+            h if h < 24 => localizer.format("time-hours-ago", &[("count", &count(h))]),
+            h if h < 48 => localizer.text("time-yesterday"),
+            _ if elapsed.num_days() < 7 => {
+                localizer.format("time-days-ago", &[("count", &count(elapsed.num_days()))])
+            }
+            _ => absolute(localizer, time),
+        },
+    }
+}
+
+pub fn absolute(localizer: &Localizer, time: DateTime<Utc>) -> String {
+    absolute_in(localizer, &time.with_timezone(&Local))
+}
+
+fn absolute_in<Tz: TimeZone>(localizer: &Localizer, time: &DateTime<Tz>) -> String
+where
+    Tz::Offset: std::fmt::Display,
+{
+    use std::fmt::Write;
+
+    let pattern = localizer.text("date-time-format");
+    let mut formatted = String::new();
+    // A bad pattern in a translation makes chrono error rather than format; don't panic on it
+    match write!(formatted, "{}", time.format(&pattern)) {
+        Ok(()) => formatted,
+        Err(_) => time.to_rfc3339(),
+    }
+}
+
+/// Both forms for a backend timestamp; unparseable values are passed through as-is
+pub fn format_received_time(localizer: &Localizer, value: &str) -> FormattedTime {
+    match parse_received_time(value) {
+        Some(time) => FormattedTime {
+            relative: relative(localizer, time, Utc::now()),
+            absolute: absolute(localizer, time),
+        },
+        None => FormattedTime {
+            relative: value.to_string(),
+            absolute: value.to_string(),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_parse_received_time() {
+        let expected = Utc.with_ymd_and_hms(2024, 5, 1, 10, 23, 45).unwrap();
+        assert_eq!(parse_received_time("2024-05-01T10:23:45Z"), Some(expected));
+        assert_eq!(
+            parse_received_time("2024-05-01 12:23:45.120000+02:00"),
+            Some(expected + Duration::milliseconds(120))
+        );
+        assert!(parse_received_time("2024-05-01 10:23:45").is_some());
+        assert!(parse_received_time("yesterday-ish").is_none());
+    }
+
+    #[test]
+    fn test_relative_and_absolute() {
+        let localizer = Localizer::new("en-US");
+        let now = Utc.with_ymd_and_hms(2024, 5, 10, 12, 0, 0).unwrap();
+        let ago = |minutes| relative(&localizer, now - Duration::minutes(minutes), now);
+        assert_eq!(ago(0), "just now");
+        assert_eq!(ago(1), "1 minute ago");
+        assert_eq!(ago(150), "2 hours ago");
+        assert_eq!(ago(30 * 60), "yesterday");
+        assert_eq!(ago(3 * 24 * 60), "3 days ago");
+
+        let time = Utc.with_ymd_and_hms(2024, 5, 1, 15, 4, 0).unwrap();
+        assert_eq!(absolute_in(&localizer, &time), "05/01/2024 3:04 PM");
+        localizer.set_locale("pt-BR");
+        assert_eq!(absolute_in(&localizer, &time), "01/05/2024 15:04");
+        assert_eq!(ago(150), "há 2 horas");
+    }
+}
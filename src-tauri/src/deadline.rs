@@ -0,0 +1,256 @@
+// SERINA Deadline Detection
+// Finds explicit deadlines such as "by Friday EOD" or "due May 20th at 3pm" in email text
+
+use crate::dates::parse_received_time;
+use crate::utils::EmailData;
+use chrono::{Datelike, Duration, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Weekday};
+
+/// Words after which a date is taken as a deadline
+const TRIGGERS: &[&str] = &["by", "due", "before", "deadline", "than"];
+
+/// Words allowed between a trigger and the date: "due on the", "deadline is this"
+const FILLERS: &[&str] = &["on", "the", "this", "is", "of", "at"];
+
+const MONTHS: &[&str] = &[
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+];
+
+/// When a date is given without a time, work is due at the end of the working day
+fn end_of_day() -> NaiveTime {
+    NaiveTime::from_hms_opt(17, 0, 0).unwrap()
+}
+
+fn weekday(word: &str) -> Option<Weekday> {
+    match word.get(..3)? {
+        "mon" => Some(Weekday::Mon),
+        "tue" => Some(Weekday::Tue),
+        "wed" => Some(Weekday::Wed),
+        "thu" => Some(Weekday::Thu),
+        "fri" => Some(Weekday::Fri),
+        "sat" => Some(Weekday::Sat),
+        "sun" => Some(Weekday::Sun),
+        _ => None,
+    }
+    .filter(|_| word.ends_with("day") || word.len() <= 4)
+}
+
+fn month(word: &str) -> Option<u32> {
+    let prefix = word.get(..3)?;
+    MONTHS
+        .iter()
+        .position(|month| *month == prefix)
+        .map(|i| i as u32 + 1)
+}
+
+/// "3", "3rd", "21st"
+fn day_of_month(word: &str) -> Option<u32> {
+    let digits = word.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+    digits.parse().ok().filter(|day| (1..=31).contains(day))
+}
+
+/// The next `target` on or after `from`
+fn upcoming(from: NaiveDate, target: Weekday) -> NaiveDate {
+    let ahead = (7 + target.num_days_from_monday() - from.weekday().num_days_from_monday()) % 7;
+    from + Duration::days(ahead as i64)
+}
+
+/// A month and day on or after `from`, rolling over into next year
+fn next_date(from: NaiveDate, month: u32, day: u32) -> Option<NaiveDate> {
+    let this_year = NaiveDate::from_ymd_opt(from.year(), month, day)?;
+    if this_year >= from {
+        Some(this_year)
+    } else {
+        NaiveDate::from_ymd_opt(from.year() + 1, month, day)
+    }
+}
+
+fn last_day_of_month(date: NaiveDate) -> NaiveDate {
+    let (year, month) = if date.month() == 12 {
+        (date.year() + 1, 1)
+    } else {
+        (date.year(), date.month() + 1)
+    };
+    NaiveDate::from_ymd_opt(year, month, 1).unwrap() - Duration::days(1)
+}
+
+/// "end of day" and friends, returning how many words they use
+fn end_of(words: &[&str]) -> Option<(&'static str, usize)> {
+    match words {
+        ["eod" | "cob", ..] => Some(("day", 1)),
+        ["eow", ..] => Some(("week", 1)),
+        ["eom", ..] => Some(("month", 1)),
+        ["close", "of", "business", ..] => Some(("day", 3)),
+        ["end", "of", "the", period, ..] => Some((period_name(period)?, 4)),
+        ["end", "of", period, ..] => Some((period_name(period)?, 3)),
+        _ => None,
+    }
+}
+
+fn period_name(word: &str) -> Option<&'static str> {
+    match word {
+        "day" | "today" => Some("day"),
+        "week" => Some("week"),
+        "month" => Some("month"),
+        _ => None,
+    }
+}
+
+/// A date starting at `words[0]`, with the number of words it used and any time it implies
+fn parse_date(words: &[&str], today: NaiveDate) -> Option<(NaiveDate, usize, Option<NaiveTime>)> {
+    if let Some((period, used)) = end_of(words) {
+        let date = match period {
+            "day" => today,
+            "week" => upcoming(today, Weekday::Fri),
+            _ => last_day_of_month(today),
+        };
+        return Some((date, used, Some(end_of_day())));
+    }
+    let first = *words.first()?;
+    match first {
+        "today" | "tonight" => return Some((today, 1, None)),
+        "tomorrow" => return Some((today + Duration::days(1), 1, None)),
+        "next" => {
+            let target = weekday(words.get(1)?)?;
+            let date = upcoming(today + Duration::days(1), target);
+            return Some((date, 2, None));
+        }
+        _ => {}
+    }
+    if let Some(target) = weekday(first) {
+        return Some((upcoming(today, target), 1, None));
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(first, "%Y-%m-%d") {
+        return Some((date, 1, None));
+    }
+    // "May 20th" or "20 May"
+    let (month, day) = match (month(first), words.get(1).copied().and_then(day_of_month)) {
+        (Some(month), Some(day)) => (month, day),
+        _ => (month(words.get(1)?)?, day_of_month(first)?),
+    };
+    Some((next_date(today, month, day)?, 2, None))
+}
+
+/// "5pm", "5:30 pm", "17:00", "noon", "eod"
+fn parse_time(words: &[&str]) -> Option<NaiveTime> {
+    let words = match words {
+        ["at", rest @ ..] => rest,
+        _ => words,
+    };
+    let first = *words.first()?;
+    if first == "noon" || first == "midday" {
+        return NaiveTime::from_hms_opt(12, 0, 0);
+    }
+    if end_of(words).is_some_and(|(period, _)| period == "day") {
+        return Some(end_of_day());
+    }
+    let (clock, suffix) = match first.find(|c: char| c.is_ascii_alphabetic()) {
+        Some(i) => (&first[..i], &first[i..]),
+        None => (first, words.get(1).copied().unwrap_or_default()),
+    };
+    let (hour, minute) = match clock.split_once(':') {
+        Some((hour, minute)) => (hour.parse::<u32>().ok()?, minute.parse::<u32>().ok()?),
+        None => (clock.parse::<u32>().ok()?, 0),
+    };
+    let hour = match suffix.trim_end_matches('.') {
+        "pm" | "p.m" if hour < 12 => hour + 12,
+        "am" | "a.m" if hour == 12 => 0,
+        "pm" | "p.m" | "am" | "a.m" => hour,
+        // A bare number is a day or a quantity, not a time
+        _ if clock.contains(':') => hour,
+        _ => return None,
+    };
+    NaiveTime::from_hms_opt(hour, minute, 0)
+}
+
+fn words(text: &str) -> Vec<String> {
+    text.split_whitespace()
+        .map(|word| {
+            word.trim_matches(|c: char| !c.is_alphanumeric() && c != ':')
+                .trim_end_matches(':')
+                .to_lowercase()
+        })
+        .filter(|word| !word.is_empty())
+        .collect()
+}
+
+/// The first explicit deadline in `text`, read relative to `reference` (when the email was
+/// received); dates already past at that point are ignored
+pub fn detect(text: &str, reference: NaiveDateTime) -> Option<NaiveDateTime> {
+    let words = words(text);
+    let words: Vec<&str> = words.iter().map(String::as_str).collect();
+    let today = reference.date();
+
+    for (i, word) in words.iter().enumerate() {
+        if !TRIGGERS.contains(word) || (*word == "than" && (i == 0 || words[i - 1] != "later")) {
+            continue;
+        }
+        let mut start = i + 1;
+        while words.get(start).is_some_and(|w| FILLERS.contains(w)) {
+            start += 1;
+        }
+        let Some((date, used, time)) = parse_date(&words[start..], today) else {
+            continue;
+        };
+        let time = parse_time(&words[start + used..])
+            .or(time)
+            .unwrap_or_else(end_of_day);
+        let deadline = date.and_time(time);
+        if deadline >= reference {
+            return Some(deadline);
+        }
+    }
+    None
+}
+
+/// Fill in `deadline` for emails freshly read from the backend, as RFC 3339
+pub fn add_deadlines(emails: &mut [EmailData]) {
+    for email in emails {
+        let received = parse_received_time(&email.received_time)
+            .map_or_else(Local::now, |time| time.with_timezone(&Local));
+        email.deadline = detect(
+            &crate::parsing::new_content(&email.body),
+            received.naive_local(),
+        )
+        .and_then(|deadline| Local.from_local_datetime(&deadline).earliest())
+        .map(|deadline| deadline.to_rfc3339());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(text: &str) -> Option<String> {
+        // A Wednesday morning
+        let reference = NaiveDate::from_ymd_opt(2024, 5, 8)
+            .unwrap()
+            .and_hms_opt(10, 0, 0)
+            .unwrap();
+        detect(text, reference).map(|d| d.format("%Y-%m-%d %H:%M").to_string())
+    }
+
+    #[test]
+    fn test_detect() {
+        let cases = [
+            ("Please send it by Friday EOD.", Some("2024-05-10 17:00")),
+            (
+                "The report is due May 20th at 3pm",
+                Some("2024-05-20 15:00"),
+            ),
+            ("Can you reply by tomorrow noon?", Some("2024-05-09 12:00")),
+            ("Deadline: 2024-06-01", Some("2024-06-01 17:00")),
+            (
+                "Get it to me by the end of the week",
+                Some("2024-05-10 17:00"),
+            ),
+            ("no later than 3 June, 9:30 am", Some("2024-06-03 09:30")),
+            ("Due on January 5", Some("2025-01-05 17:00")),
+            ("by next Wednesday", Some("2024-05-15 17:00")),
+            ("By the way, bye for now", None),
+            ("It was due 2024-05-01", None),
+        ];
+        for (text, expected) in cases {
+            assert_eq!(at(text).as_deref(), expected, "{}", text);
+        }
+    }
+}
@@ -0,0 +1,182 @@
+// SERINA Diagnostics
+// Connection tests for the backend, email account and LLM providers
+
+use crate::http::PinnedClient;
+use crate::utils::ProviderConfig;
+use serde::Serialize;
+use std::time::{Duration, Instant};
+
+const PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuthResult {
+    Ok,
+    Failed,
+    NotRequired,
+    Unknown,
+}
+
+/// Outcome of one connection test, shown in the settings window
+#[derive(Debug, Clone, Serialize)]
+pub struct Diagnostics {
+    pub target: String,
+    pub ok: bool,
+    pub latency_ms: u64,
+    pub auth: AuthResult,
+    /// Specific failure reason when `ok` is false
+    pub reason: Option<String>,
+}
+
+impl Diagnostics {
+    pub fn failed(target: &str, auth: AuthResult, reason: impl Into<String>) -> Self {
+        Self {
+            target: target.to_string(),
+            ok: false,
+            latency_ms: 0,
+            auth,
+            reason: Some(reason.into()),
+        }
+    }
+}
+
+/// Explain a non-success HTTP status in settings-window terms
+pub fn describe_status(status: reqwest::StatusCode) -> (AuthResult, String) {
+    match status.as_u16() {
+        401 | 403 => (AuthResult::Failed, "API key was rejected".to_string()),
+        404 => (
+            AuthResult::Unknown,
+            "Endpoint not found; check the base URL".to_string(),
+        ),
+        429 => (AuthResult::Ok, "Rate limited or out of credits".to_string()),
+        code if code >= 500 => (
+            AuthResult::Unknown,
+            format!("Service error (HTTP {})", code),
+        ),
+        _ => (AuthResult::Unknown, format!("Unexpected HTTP {}", status)),
+    }
+}
+
+/// Explain a transport failure (timeout, refused connection, DNS, TLS)
+pub fn describe_error(error: &reqwest::Error) -> String {
+    if error.is_timeout() {
+        "Timed out waiting for a response".to_string()
+    } else if error.is_connect() {
+        format!("Could not connect: {}", error)
+    } else {
+        error.to_string()
+    }
+}
+
+/// GET `url` and report latency plus a classified failure reason.
+/// On success the response body is returned alongside for callers that inspect it.
+pub async fn probe(
+    client: &PinnedClient,
+    target: &str,
+    url: &str,
+    api_key: Option<&str>,
+) -> (Diagnostics, Option<String>) {
+    let mut request = client.get(url).timeout(PROBE_TIMEOUT);
+    if let Some(key) = api_key.filter(|k| !k.is_empty()) {
+        request = request.bearer_auth(key);
+    }
+    let auth_on_success = if api_key.is_some() {
+        AuthResult::Ok
+    } else {
+        AuthResult::NotRequired
+    };
+
+    let started = Instant::now();
+    let result = request.send().await.map_err(|e| describe_error(&e));
+    let latency_ms = started.elapsed().as_millis() as u64;
+
+    match result {
+        Ok(response) if response.status().is_success() => {
+            let body = response.text().await.ok();
+            (
+                Diagnostics {
+                    target: target.to_string(),
+                    ok: true,
+                    latency_ms,
+                    auth: auth_on_success,
+                    reason: None,
+                },
+                body,
+            )
+        }
+        Ok(response) => {
+            let (auth, reason) = describe_status(response.status());
+            (
+                Diagnostics {
+                    latency_ms,
+                    ..Diagnostics::failed(target, auth, reason)
+                },
+                None,
+            )
+        }
+        Err(reason) => (
+            Diagnostics {
+                latency_ms,
+                ..Diagnostics::failed(target, AuthResult::Unknown, reason)
+            },
+            None,
+        ),
+    }
+}
+
+/// Check an LLM provider by listing its models, which needs a valid key but costs nothing
+pub async fn test_llm(
+    client: &PinnedClient,
+    provider: &ProviderConfig,
+    privacy_mode: bool,
+) -> Diagnostics {
+    let target = provider.provider.as_str();
+    let base_url = match crate::llm::base_url(provider) {
+        Ok(url) => url,
+        Err(e) => return Diagnostics::failed(target, AuthResult::Unknown, e),
+    };
+    if let Err(e) = crate::privacy::ensure_allowed(&base_url, privacy_mode) {
+        return Diagnostics::failed(target, AuthResult::Unknown, e);
+    }
+    if !provider.is_usable() {
+        return Diagnostics::failed(target, AuthResult::Failed, "No API key configured");
+    }
+
+    let api_key = (!provider.is_local()).then_some(provider.api_key.as_str());
+    let url = format!("{}/models", base_url);
+    probe(client, target, &url, api_key).await.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_describe_status() {
+        let (auth, reason) = describe_status(reqwest::StatusCode::UNAUTHORIZED);
+        assert_eq!(auth, AuthResult::Failed);
+        assert!(reason.contains("rejected"));
+        assert_eq!(
+            describe_status(reqwest::StatusCode::BAD_GATEWAY).0,
+            AuthResult::Unknown
+        );
+    }
+
+    #[tokio::test]
+    async fn test_llm_without_key() {
+        let client = PinnedClient::new(reqwest::Client::new());
+        let provider = ProviderConfig {
+            provider: "openai".to_string(),
+            api_key: String::new(),
+            model: String::new(),
+            base_url: None,
+        };
+        let result = test_llm(&client, &provider, false).await;
+        assert!(!result.ok);
+        assert_eq!(result.auth, AuthResult::Failed);
+
+        // Privacy mode blocks cloud providers before any request is made
+        let result = test_llm(&client, &provider, true).await;
+        assert!(result.reason.unwrap().contains("Privacy mode"));
+    }
+}
@@ -0,0 +1,169 @@
+// SERINA Cache Encryption
+// Envelope encryption for local mail copies: each database has a random data key, stored
+// wrapped by a master key that lives in the OS keychain rather than on disk
+
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Nonce};
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+
+const KEYCHAIN_SERVICE: &str = "serina";
+const KEYCHAIN_ACCOUNT: &str = "cache-master-key";
+/// Marks a sealed value, so a wrong key fails loudly instead of returning garbage
+const SEALED_PREFIX: &str = "enc1:";
+const NONCE_LEN: usize = 12;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SecurityConfig {
+    /// Encrypt cached subjects, senders and bodies with a key kept in the OS keychain
+    pub encrypt_cache: bool,
+}
+
+impl Default for SecurityConfig {
+    fn default() -> Self {
+        Self {
+            encrypt_cache: true,
+        }
+    }
+}
+
+/// A 256-bit AES-GCM key
+#[derive(Clone)]
+pub struct Key(Aes256Gcm);
+
+impl Key {
+    pub fn generate() -> Self {
+        Self::from_bytes(&Aes256Gcm::generate_key(OsRng))
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Self {
+        Self(Aes256Gcm::new_from_slice(bytes).expect("AES-256 keys are 32 bytes"))
+    }
+
+    fn decode(encoded: &str) -> Result<Self, String> {
+        let bytes = base64::engine::general_purpose::STANDARD
+            .decode(encoded.trim())
+            .map_err(|e| e.to_string())?;
+        if bytes.len() != 32 {
+            return Err("Stored key has the wrong length".to_string());
+        }
+        Ok(Self::from_bytes(&bytes))
+    }
+
+    /// Encrypt under a fresh nonce; the result is text, to sit in the same columns as plaintext
+    pub fn seal(&self, plaintext: &[u8]) -> String {
+        let nonce = Aes256Gcm::generate_nonce(OsRng);
+        let ciphertext = self
+            .0
+            .encrypt(&nonce, plaintext)
+            .expect("AES-GCM encryption does not fail for in-memory buffers");
+        let mut sealed = nonce.to_vec();
+        sealed.extend(ciphertext);
+        format!(
+            "{}{}",
+            SEALED_PREFIX,
+            base64::engine::general_purpose::STANDARD.encode(sealed)
+        )
+    }
+
+    pub fn open(&self, sealed: &str) -> Result<Vec<u8>, String> {
+        let encoded = sealed
+            .strip_prefix(SEALED_PREFIX)
+            .ok_or("Value is not encrypted")?;
+        let bytes = base64::engine::general_purpose::STANDARD
+            .decode(encoded)
+            .map_err(|e| e.to_string())?;
+        if bytes.len() < NONCE_LEN {
+            return Err("Encrypted value is truncated".to_string());
+        }
+        let (nonce, ciphertext) = bytes.split_at(NONCE_LEN);
+        self.0
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| "Encrypted value can't be read with this key".to_string())
+    }
+
+    pub fn seal_text(&self, text: &str) -> String {
+        self.seal(text.as_bytes())
+    }
+
+    pub fn open_text(&self, sealed: &str) -> Result<String, String> {
+        String::from_utf8(self.open(sealed)?).map_err(|e| e.to_string())
+    }
+
+    /// A new data key along with its copy wrapped by this (master) key, for storing
+    pub fn new_data_key(&self) -> (Key, String) {
+        let bytes = Aes256Gcm::generate_key(OsRng);
+        (Self::from_bytes(&bytes), self.seal(&bytes))
+    }
+
+    /// Recover a data key stored by `new_data_key`
+    pub fn unwrap_data_key(&self, wrapped: &str) -> Result<Key, String> {
+        let bytes = self.open(wrapped)?;
+        if bytes.len() != 32 {
+            return Err("Stored key has the wrong length".to_string());
+        }
+        Ok(Self::from_bytes(&bytes))
+    }
+}
+
+/// Fill `buf` from the OS random number generator
+pub fn random_bytes(buf: &mut [u8]) {
+    OsRng.fill_bytes(buf);
+}
+
+fn keychain_entry() -> Result<keyring::Entry, String> {
+    keyring::Entry::new(KEYCHAIN_SERVICE, KEYCHAIN_ACCOUNT).map_err(|e| e.to_string())
+}
+
+/// The master key from the OS keychain, created on first use
+pub fn master_key() -> Result<Key, String> {
+    let entry = keychain_entry()?;
+    match entry.get_password() {
+        Ok(encoded) => Key::decode(&encoded),
+        Err(keyring::Error::NoEntry) => {
+            let bytes = Aes256Gcm::generate_key(OsRng);
+            entry
+                .set_password(&base64::engine::general_purpose::STANDARD.encode(bytes))
+                .map_err(|e| format!("Couldn't save the cache key to the keychain: {}", e))?;
+            Ok(Key::from_bytes(&bytes))
+        }
+        Err(e) => Err(format!(
+            "Couldn't read the cache key from the keychain: {}",
+            e
+        )),
+    }
+}
+
+/// Remove the master key from the keychain, leaving anything sealed under it unreadable.
+/// Returns whether there was one.
+pub fn delete_master_key() -> Result<bool, String> {
+    match keychain_entry()?.delete_password() {
+        Ok(()) => Ok(true),
+        Err(keyring::Error::NoEntry) => Ok(false),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_envelope_round_trip() {
+        let master = Key::generate();
+        let (data, wrapped) = master.new_data_key();
+        let sealed = data.seal_text("Quarterly numbers");
+        assert!(sealed.starts_with(SEALED_PREFIX));
+        assert!(!sealed.contains("Quarterly"));
+        assert_ne!(sealed, data.seal_text("Quarterly numbers"));
+
+        let unwrapped = master.unwrap_data_key(&wrapped).unwrap();
+        assert_eq!(unwrapped.open_text(&sealed).unwrap(), "Quarterly numbers");
+
+        assert!(Key::generate().unwrap_data_key(&wrapped).is_err());
+        assert!(unwrapped.open_text("Quarterly numbers").is_err());
+        assert!(unwrapped.open_text("enc1:AAAA").is_err());
+    }
+}
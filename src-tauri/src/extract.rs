@@ -0,0 +1,263 @@
+// SERINA Structured Extraction
+// Shipping, receipt and flight details from transactional emails, for rendering as cards
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Words that suggest an email is worth asking the LLM about when it has no markup
+const TRANSACTIONAL_KEYWORDS: &[&str] = &[
+    "tracking",
+    "shipped",
+    "shipment",
+    "delivery",
+    "order",
+    "receipt",
+    "invoice",
+    "flight",
+    "boarding",
+    "itinerary",
+];
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Shipment {
+    pub carrier: String,
+    pub tracking_number: String,
+    pub tracking_url: String,
+    pub status: String,
+    pub expected_arrival: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Order {
+    pub merchant: String,
+    pub order_number: String,
+    /// Amount as written, e.g. "42.50"
+    pub total: String,
+    pub currency: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Flight {
+    pub airline: String,
+    /// Airline code and number, e.g. "UA 123"
+    pub flight_number: String,
+    pub departure_airport: String,
+    pub arrival_airport: String,
+    pub departure_time: String,
+    pub arrival_time: String,
+    pub confirmation: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TransactionalData {
+    Shipment(Shipment),
+    Order(Order),
+    Flight(Flight),
+}
+
+/// A field as text: strings and numbers as-is, things and places by name or code
+fn text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.trim().to_string(),
+        Value::Number(n) => n.to_string(),
+        Value::Object(map) => ["name", "iataCode", "@id"]
+            .iter()
+            .find_map(|key| map.get(*key).map(text).filter(|s| !s.is_empty()))
+            .unwrap_or_default(),
+        _ => String::new(),
+    }
+}
+
+fn field(item: &Value, keys: &[&str]) -> String {
+    keys.iter()
+        .map(|key| text(&item[*key]))
+        .find(|s| !s.is_empty())
+        .unwrap_or_default()
+}
+
+/// "http://schema.org/OrderInTransit" reads better as "In transit"
+fn status(value: &Value) -> String {
+    let status = text(value);
+    let name = status.rsplit('/').next().unwrap_or_default();
+    let name = name.strip_prefix("Order").unwrap_or(name);
+    let mut words = String::new();
+    for (i, c) in name.chars().enumerate() {
+        if i > 0 && c.is_uppercase() {
+            words.push(' ');
+            words.extend(c.to_lowercase());
+        } else {
+            words.push(c);
+        }
+    }
+    words
+}
+
+fn from_item(item: &Value) -> Option<TransactionalData> {
+    let kind = text(&item["@type"]);
+    match kind.rsplit('/').next().unwrap_or_default() {
+        "ParcelDelivery" => Some(TransactionalData::Shipment(Shipment {
+            carrier: field(item, &["carrier", "provider"]),
+            tracking_number: field(item, &["trackingNumber"]),
+            tracking_url: field(item, &["trackingUrl"]),
+            status: status(&item["deliveryStatus"]),
+            expected_arrival: field(item, &["expectedArrivalUntil", "expectedArrivalFrom"]),
+        })),
+        "Order" | "Invoice" => {
+            let offer = &item["acceptedOffer"];
+            let offer = offer.as_array().and_then(|o| o.first()).unwrap_or(offer);
+            Some(TransactionalData::Order(Order {
+                merchant: field(item, &["seller", "merchant", "provider"]),
+                order_number: field(item, &["orderNumber", "confirmationNumber"]),
+                total: [
+                    field(item, &["price", "totalPrice"]),
+                    field(offer, &["price"]),
+                ]
+                .into_iter()
+                .find(|s| !s.is_empty())
+                .unwrap_or_default(),
+                currency: [
+                    field(item, &["priceCurrency"]),
+                    field(offer, &["priceCurrency"]),
+                ]
+                .into_iter()
+                .find(|s| !s.is_empty())
+                .unwrap_or_default(),
+            }))
+        }
+        "FlightReservation" => {
+            let flight = &item["reservationFor"];
+            let airline = &flight["airline"];
+            let number = field(flight, &["flightNumber"]);
+            let code = field(airline, &["iataCode"]);
+            Some(TransactionalData::Flight(Flight {
+                airline: field(flight, &["airline"]),
+                flight_number: if code.is_empty() || number.starts_with(&code) {
+                    number
+                } else {
+                    format!("{} {}", code, number)
+                },
+                departure_airport: field(&flight["departureAirport"], &["iataCode", "name"]),
+                arrival_airport: field(&flight["arrivalAirport"], &["iataCode", "name"]),
+                departure_time: field(flight, &["departureTime"]),
+                arrival_time: field(flight, &["arrivalTime"]),
+                confirmation: field(item, &["reservationNumber"]),
+            }))
+        }
+        _ => None,
+    }
+}
+
+/// Every item in a JSON-LD document, looking inside lists and `@graph`
+fn collect_items<'a>(value: &'a Value, items: &mut Vec<&'a Value>) {
+    match value {
+        Value::Array(list) => list.iter().for_each(|v| collect_items(v, items)),
+        Value::Object(map) => match map.get("@graph") {
+            Some(graph) => collect_items(graph, items),
+            None => items.push(value),
+        },
+        _ => {}
+    }
+}
+
+/// Read schema.org JSON-LD blocks from an HTML body, as airlines and shops embed for
+/// mail clients. Malformed blocks are skipped.
+pub fn from_schema_org(html: &str) -> Vec<TransactionalData> {
+    let lower = html.to_ascii_lowercase();
+    let mut found = Vec::new();
+    let mut offset = 0;
+    while let Some(marker) = lower[offset..].find("application/ld+json") {
+        let after = offset + marker;
+        let Some(open) = lower[after..].find('>').map(|i| after + i + 1) else {
+            break;
+        };
+        let close = lower[open..]
+            .find("</script")
+            .map_or(html.len(), |i| open + i);
+        offset = close;
+        let Ok(document) = serde_json::from_str::<Value>(&html[open..close]) else {
+            continue;
+        };
+        let mut items = Vec::new();
+        collect_items(&document, &mut items);
+        found.extend(items.into_iter().filter_map(from_item));
+    }
+    found
+}
+
+/// Whether an email without markup is worth an LLM extraction
+pub fn looks_transactional(subject: &str, text: &str) -> bool {
+    let haystack = format!("{} {}", subject, text).to_lowercase();
+    TRANSACTIONAL_KEYWORDS
+        .iter()
+        .any(|keyword| haystack.contains(keyword))
+}
+
+/// Parse the JSON list returned by the extraction prompt; unknown kinds are dropped
+pub fn parse_extraction(text: &str) -> Result<Vec<TransactionalData>, String> {
+    let items: Vec<Value> = serde_json::from_str(crate::llm::strip_code_fence(text))
+        .map_err(|e| format!("Malformed extraction response: {}", e))?;
+    Ok(items
+        .into_iter()
+        .filter_map(|item| serde_json::from_value(item).ok())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_schema_org() {
+        let html = r#"<html><head>
+            <script type="application/ld+json">{"@context": "http://schema.org", "@graph": [
+              {"@type": "ParcelDelivery", "carrier": {"@type": "Organization", "name": "UPS"},
+               "trackingNumber": "1Z999AA10123456784",
+               "deliveryStatus": "http://schema.org/OrderInTransit"},
+              {"@type": "Order", "seller": {"name": "Acme"}, "orderNumber": "A-1001",
+               "acceptedOffer": [{"price": 42.5, "priceCurrency": "USD"}]}]}</script>
+            <script type="application/ld+json">{"@type": "FlightReservation",
+              "reservationNumber": "XYZ123",
+              "reservationFor": {"@type": "Flight", "flightNumber": "123",
+                "airline": {"name": "United", "iataCode": "UA"},
+                "departureAirport": {"iataCode": "SFO"}, "arrivalAirport": {"iataCode": "JFK"},
+                "departureTime": "2024-05-01T08:00:00-07:00"}}</script>
+            <script type="application/ld+json">{not json</script></head></html>"#;
+        let found = from_schema_org(html);
+        assert_eq!(found.len(), 3);
+        let TransactionalData::Shipment(shipment) = &found[0] else {
+            panic!("expected a shipment");
+        };
+        assert_eq!(shipment.carrier, "UPS");
+        assert_eq!(shipment.status, "In transit");
+        let TransactionalData::Order(order) = &found[1] else {
+            panic!("expected an order");
+        };
+        assert_eq!(
+            (order.total.as_str(), order.currency.as_str()),
+            ("42.5", "USD")
+        );
+        let TransactionalData::Flight(flight) = &found[2] else {
+            panic!("expected a flight");
+        };
+        assert_eq!(flight.flight_number, "UA 123");
+        assert_eq!(flight.departure_airport, "SFO");
+        assert_eq!(flight.confirmation, "XYZ123");
+    }
+
+    #[test]
+    fn test_parse_extraction() {
+        let response = "```json\n[{\"type\": \"shipment\", \"carrier\": \"DHL\", \
+            \"tracking_number\": \"JD0001\"}, {\"type\": \"coupon\"}]\n```";
+        let found = parse_extraction(response).unwrap();
+        assert_eq!(found.len(), 1);
+        assert!(
+            matches!(&found[0], TransactionalData::Shipment(s) if s.tracking_number == "JD0001")
+        );
+        assert!(parse_extraction("no data").is_err());
+        assert!(looks_transactional("Your order has shipped", ""));
+    }
+}
@@ -0,0 +1,432 @@
+// SERINA Feeds
+// Atom feeds of selected mail, served on loopback for RSS readers
+
+use crate::mailview;
+use crate::utils::EmailData;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Write};
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Loopback port feeds are served on unless configured otherwise
+pub const FEED_PORT: u16 = 47614;
+/// Entries kept per feed; older ones drop off as new mail arrives
+const MAX_ENTRIES: usize = 30;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FeedConfig {
+    /// Serve feeds at `http://127.0.0.1:<port>/feeds/<name>.atom`
+    pub enabled: bool,
+    pub port: u16,
+    pub feeds: Vec<FeedRule>,
+}
+
+impl Default for FeedConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: FEED_PORT,
+            feeds: vec![FeedRule {
+                name: "newsletters".to_string(),
+                title: "Newsletters".to_string(),
+                newsletters: true,
+                senders: Vec::new(),
+                subject_keywords: Vec::new(),
+            }],
+        }
+    }
+}
+
+impl FeedConfig {
+    pub fn feed(&self, name: &str) -> Option<&FeedRule> {
+        self.feeds.iter().find(|feed| feed.name == name)
+    }
+}
+
+/// Which mail goes into a feed. An email belongs if any of the rules matches it.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FeedRule {
+    /// Used in the URL, e.g. "newsletters"
+    pub name: String,
+    pub title: String,
+    /// Mail with a List-Unsubscribe header
+    pub newsletters: bool,
+    /// Addresses or @domains
+    pub senders: Vec<String>,
+    pub subject_keywords: Vec<String>,
+}
+
+impl FeedRule {
+    /// Whether the sender or subject alone put the email in the feed
+    pub fn matches_headers(&self, email: &EmailData) -> bool {
+        let sender = email.sender_email.trim().to_lowercase();
+        let subject = email.subject.to_lowercase();
+        let listed = !sender.is_empty()
+            && self.senders.iter().any(|entry| {
+                let entry = entry.trim().to_lowercase();
+                if entry.starts_with('@') {
+                    sender.ends_with(&entry)
+                } else {
+                    sender == entry
+                }
+            });
+        listed
+            || self.subject_keywords.iter().any(|keyword| {
+                let keyword = keyword.trim().to_lowercase();
+                !keyword.is_empty() && subject.contains(&keyword)
+            })
+    }
+
+    pub fn matches(&self, email: &EmailData, is_newsletter: bool) -> bool {
+        (self.newsletters && is_newsletter) || self.matches_headers(email)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeedEntry {
+    /// Name of the feed it was published to
+    pub feed: String,
+    pub email_id: String,
+    pub title: String,
+    pub author: String,
+    pub author_email: String,
+    pub published: DateTime<Utc>,
+    /// Sanitized HTML of the body
+    pub content: String,
+}
+
+impl FeedEntry {
+    pub fn new(
+        feed: &str,
+        email: &EmailData,
+        html: Option<&str>,
+        published: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            feed: feed.to_string(),
+            email_id: email.id.clone(),
+            title: email.subject.clone(),
+            author: email.sender.clone(),
+            author_email: email.sender_email.clone(),
+            published,
+            content: mailview::body_html(email, html),
+        }
+    }
+}
+
+/// Published entries, newest first and saved on every change, so feeds survive a restart
+/// and keep messages that were archived since
+pub struct FeedStore {
+    path: Option<PathBuf>,
+    entries: Mutex<Vec<FeedEntry>>,
+    serving: AtomicBool,
+}
+
+impl FeedStore {
+    pub fn load(path: PathBuf) -> Self {
+        let entries = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+        Self {
+            path: Some(path),
+            entries: Mutex::new(entries),
+            serving: AtomicBool::new(false),
+        }
+    }
+
+    pub fn in_memory() -> Self {
+        Self {
+            path: None,
+            entries: Mutex::new(Vec::new()),
+            serving: AtomicBool::new(false),
+        }
+    }
+
+    /// Add an entry unless the email is already in its feed. Returns whether it was added.
+    pub fn publish(&self, entry: FeedEntry) -> Result<bool, String> {
+        let mut entries = self.entries.lock().unwrap();
+        if entries
+            .iter()
+            .any(|existing| existing.feed == entry.feed && existing.email_id == entry.email_id)
+        {
+            return Ok(false);
+        }
+        let position = entries
+            .iter()
+            .position(|existing| existing.published < entry.published)
+            .unwrap_or(entries.len());
+        let feed = entry.feed.clone();
+        entries.insert(position, entry);
+        let mut kept = 0;
+        entries.retain(|existing| {
+            if existing.feed != feed {
+                return true;
+            }
+            kept += 1;
+            kept <= MAX_ENTRIES
+        });
+        self.persist(&entries)?;
+        Ok(true)
+    }
+
+    /// A feed's entries, newest first
+    pub fn entries(&self, feed: &str) -> Vec<FeedEntry> {
+        self.entries
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|entry| entry.feed == feed)
+            .cloned()
+            .collect()
+    }
+
+    /// True for the first caller only, which then runs the server for the life of the app
+    pub fn start_serving(&self) -> bool {
+        !self.serving.swap(true, Ordering::SeqCst)
+    }
+
+    fn persist(&self, entries: &[FeedEntry]) -> Result<(), String> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let contents = serde_json::to_string_pretty(entries).map_err(|e| e.to_string())?;
+        std::fs::write(path, contents).map_err(|e| e.to_string())
+    }
+}
+
+/// The Atom document for a feed. `updated` is used when the feed has no entries yet.
+pub fn atom(feed: &FeedRule, entries: &[FeedEntry], updated: DateTime<Utc>) -> String {
+    let updated = entries.first().map_or(updated, |entry| entry.published);
+    let mut document = format!(
+        "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n\
+         <feed xmlns=\"http://www.w3.org/2005/Atom\">\n\
+         <id>urn:serina:feed:{}</id>\n<title>{}</title>\n<updated>{}</updated>\n\
+         <generator>SERINA</generator>\n",
+        mailview::escape(&feed.name),
+        mailview::escape(&feed.title),
+        updated.to_rfc3339(),
+    );
+    for entry in entries {
+        document.push_str(&format!(
+            "<entry>\n<id>urn:serina:email:{}</id>\n<title>{}</title>\n<updated>{}</updated>\n\
+             <author><name>{}</name><email>{}</email></author>\n\
+             <content type=\"html\">{}</content>\n</entry>\n",
+            mailview::escape(&entry.email_id),
+            mailview::escape(&entry.title),
+            entry.published.to_rfc3339(),
+            mailview::escape(&entry.author),
+            mailview::escape(&entry.author_email),
+            mailview::escape(&entry.content),
+        ));
+    }
+    document.push_str("</feed>\n");
+    document
+}
+
+/// Serve feeds on loopback only
+pub fn listen(port: u16) -> std::io::Result<TcpListener> {
+    TcpListener::bind((Ipv4Addr::LOCALHOST, port))
+}
+
+/// What a reader asked for
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeedRequest {
+    pub method: String,
+    pub path: String,
+    pub host: String,
+}
+
+impl FeedRequest {
+    /// The feed in a `/feeds/<name>.atom` path
+    pub fn feed_name(&self) -> Option<&str> {
+        let path = self.path.split('?').next().unwrap_or_default();
+        path.strip_prefix("/feeds/")?
+            .strip_suffix(".atom")
+            .filter(|name| !name.is_empty() && !name.contains('/'))
+    }
+
+    /// Whether the request was addressed to this machine. A web page can make the browser
+    /// reach loopback under its own host name; those requests are turned away.
+    pub fn is_local(&self) -> bool {
+        let host = self
+            .host
+            .rsplit_once(':')
+            .map_or(self.host.as_str(), |(host, port)| {
+                if port.chars().all(|c| c.is_ascii_digit()) {
+                    host
+                } else {
+                    &self.host
+                }
+            });
+        matches!(host, "127.0.0.1" | "localhost" | "[::1]")
+    }
+}
+
+/// Read the request line and Host header of one request
+pub fn read_request(stream: &TcpStream) -> Result<FeedRequest, String> {
+    stream
+        .set_read_timeout(Some(Duration::from_secs(2)))
+        .map_err(|e| e.to_string())?;
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    reader.read_line(&mut line).map_err(|e| e.to_string())?;
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(path)) = (parts.next(), parts.next()) else {
+        return Err("Invalid feed request".to_string());
+    };
+    let mut request = FeedRequest {
+        method: method.to_string(),
+        path: path.to_string(),
+        host: String::new(),
+    };
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header).map_err(|e| e.to_string())? == 0 {
+            break;
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("host") {
+                request.host = value.trim().to_lowercase();
+            }
+        }
+    }
+    Ok(request)
+}
+
+/// Write a complete response and close the connection
+pub fn respond(
+    mut stream: TcpStream,
+    status: &str,
+    content_type: &str,
+    body: &str,
+) -> Result<(), String> {
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-store\r\n\
+         Connection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    );
+    stream
+        .write_all(response.as_bytes())
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+    use std::io::Read;
+
+    fn email(id: &str, subject: &str, sender_email: &str) -> EmailData {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "subject": subject,
+            "sender": "Morning Brief",
+            "sender_email": sender_email,
+            "body": "<p>Markets & more</p><script>alert(1)</script>",
+            "received_time": "",
+            "is_unread": true
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_rules() {
+        let rule = FeedRule {
+            name: "reading".to_string(),
+            title: "Reading".to_string(),
+            newsletters: true,
+            senders: vec!["@substack.com".to_string()],
+            subject_keywords: vec!["Weekly".to_string()],
+        };
+        assert!(rule.matches(&email("a", "Hi", "brief@substack.com"), false));
+        assert!(rule.matches(&email("a", "This weekly roundup", "x@example.com"), false));
+        assert!(rule.matches(&email("a", "Hi", "x@example.com"), true));
+        assert!(!rule.matches(&email("a", "Hi", "x@example.com"), false));
+    }
+
+    #[test]
+    fn test_feed_store_and_atom() {
+        let path = std::env::temp_dir().join(format!("serina-feeds-{}.json", std::process::id()));
+        let store = FeedStore::load(path.clone());
+        let now = Utc.with_ymd_and_hms(2024, 5, 14, 9, 0, 0).unwrap();
+        for i in 0..MAX_ENTRIES + 2 {
+            let published = now + Duration::minutes(i as i64);
+            let entry = FeedEntry::new(
+                "newsletters",
+                &email(&i.to_string(), "Brief", ""),
+                None,
+                published,
+            );
+            assert!(store.publish(entry).unwrap());
+        }
+        let again = FeedEntry::new("newsletters", &email("5", "Brief", ""), None, now);
+        assert!(!store.publish(again).unwrap());
+        let entries = store.entries("newsletters");
+        assert_eq!(entries.len(), MAX_ENTRIES);
+        assert_eq!(entries[0].email_id, (MAX_ENTRIES + 1).to_string());
+        assert_eq!(
+            FeedStore::load(path.clone()).entries("newsletters"),
+            entries
+        );
+        assert!(store.entries("other").is_empty());
+        std::fs::remove_file(&path).unwrap();
+
+        let config = FeedConfig::default();
+        let document = atom(config.feed("newsletters").unwrap(), &entries[..1], now);
+        assert!(document.contains("<title>Newsletters</title>"));
+        assert!(document.contains(&format!(
+            "<updated>{}</updated>",
+            entries[0].published.to_rfc3339()
+        )));
+        assert!(document.contains("&lt;p&gt;Markets &amp;amp; more&lt;/p&gt;"));
+        assert!(!document.contains("script"));
+
+        assert!(store.start_serving());
+        assert!(!store.start_serving());
+    }
+
+    #[test]
+    fn test_serve() {
+        let listener = listen(0).unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let mut client = TcpStream::connect((Ipv4Addr::LOCALHOST, port)).unwrap();
+        client
+            .write_all(b"GET /feeds/newsletters.atom?x=1 HTTP/1.1\r\nHost: 127.0.0.1:47614\r\nAccept: */*\r\n\r\n")
+            .unwrap();
+        let (stream, _) = listener.accept().unwrap();
+        let request = read_request(&stream).unwrap();
+        assert_eq!(request.feed_name(), Some("newsletters"));
+        assert!(request.is_local());
+        respond(stream, "200 OK", "application/atom+xml", "<feed/>").unwrap();
+        let mut response = String::new();
+        client.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.ends_with("\r\n\r\n<feed/>"));
+
+        let remote = FeedRequest {
+            method: "GET".to_string(),
+            path: "/feeds/newsletters.atom".to_string(),
+            host: "attacker.example:47614".to_string(),
+        };
+        assert!(!remote.is_local());
+        let other = FeedRequest {
+            path: "/feeds/../config.atom".to_string(),
+            ..remote
+        };
+        assert_eq!(other.feed_name(), None);
+    }
+}
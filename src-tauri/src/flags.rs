@@ -0,0 +1,162 @@
+// SERINA Feature Flags
+// Runtime toggles so experimental subsystems can ship dark
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// Known flags and their built-in defaults
+pub const DEFAULT_FLAGS: &[(&str, bool)] = &[
+    ("auto_reply", true),
+    ("spam_monitor", true),
+    ("semantic_search", false),
+];
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FlagsConfig {
+    /// JSON object of flag name => bool fetched during maintenance; empty disables
+    pub remote_url: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FlagSource {
+    Default,
+    Remote,
+    User,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FlagState {
+    pub enabled: bool,
+    pub source: FlagSource,
+}
+
+/// User overrides win over remote values, which win over built-in defaults
+pub struct FeatureFlags {
+    path: Option<PathBuf>,
+    overrides: Mutex<HashMap<String, bool>>,
+    remote: Mutex<HashMap<String, bool>>,
+}
+
+impl FeatureFlags {
+    pub fn load(path: PathBuf) -> Self {
+        let overrides = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+        Self {
+            path: Some(path),
+            overrides: Mutex::new(overrides),
+            remote: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn in_memory() -> Self {
+        Self {
+            path: None,
+            overrides: Mutex::new(HashMap::new()),
+            remote: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn state(&self, name: &str) -> FlagState {
+        if let Some(&enabled) = self.overrides.lock().unwrap().get(name) {
+            return FlagState {
+                enabled,
+                source: FlagSource::User,
+            };
+        }
+        if let Some(&enabled) = self.remote.lock().unwrap().get(name) {
+            return FlagState {
+                enabled,
+                source: FlagSource::Remote,
+            };
+        }
+        let enabled = DEFAULT_FLAGS
+            .iter()
+            .find(|(flag, _)| *flag == name)
+            .is_some_and(|(_, enabled)| *enabled);
+        FlagState {
+            enabled,
+            source: FlagSource::Default,
+        }
+    }
+
+    /// Unknown flags are off
+    pub fn is_enabled(&self, name: &str) -> bool {
+        self.state(name).enabled
+    }
+
+    /// Every known or overridden flag with its effective value
+    pub fn all(&self) -> BTreeMap<String, FlagState> {
+        let mut names: Vec<String> = DEFAULT_FLAGS.iter().map(|(n, _)| n.to_string()).collect();
+        names.extend(self.remote.lock().unwrap().keys().cloned());
+        names.extend(self.overrides.lock().unwrap().keys().cloned());
+        names
+            .into_iter()
+            .map(|name| {
+                let state = self.state(&name);
+                (name, state)
+            })
+            .collect()
+    }
+
+    /// Set a per-user override, or clear it with `None`
+    pub fn set_override(&self, name: &str, enabled: Option<bool>) -> Result<(), String> {
+        let mut overrides = self.overrides.lock().unwrap();
+        match enabled {
+            Some(enabled) => overrides.insert(name.to_string(), enabled),
+            None => overrides.remove(name),
+        };
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let contents = serde_json::to_string_pretty(&*overrides).map_err(|e| e.to_string())?;
+        std::fs::write(path, contents).map_err(|e| e.to_string())
+    }
+
+    pub fn set_remote(&self, flags: HashMap<String, bool>) {
+        *self.remote.lock().unwrap() = flags;
+    }
+}
+
+/// Parse a remote flag document, ignoring non-boolean entries
+pub fn parse_remote(body: &str) -> Result<HashMap<String, bool>, String> {
+    let value: serde_json::Value =
+        serde_json::from_str(body).map_err(|e| format!("Invalid feature flag document: {}", e))?;
+    let object = value
+        .as_object()
+        .ok_or_else(|| "Feature flag document must be a JSON object".to_string())?;
+    Ok(object
+        .iter()
+        .filter_map(|(name, value)| value.as_bool().map(|enabled| (name.clone(), enabled)))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flag_precedence() {
+        let flags = FeatureFlags::in_memory();
+        assert!(flags.is_enabled("auto_reply"));
+        assert!(!flags.is_enabled("semantic_search"));
+        assert!(!flags.is_enabled("no_such_flag"));
+
+        flags.set_remote(parse_remote(r#"{"semantic_search": true, "bad": "yes"}"#).unwrap());
+        assert_eq!(flags.state("semantic_search").source, FlagSource::Remote);
+        assert!(flags.is_enabled("semantic_search"));
+        assert!(!flags.all().contains_key("bad"));
+
+        flags.set_override("semantic_search", Some(false)).unwrap();
+        assert!(!flags.is_enabled("semantic_search"));
+        flags.set_override("semantic_search", None).unwrap();
+        assert!(flags.is_enabled("semantic_search"));
+
+        assert!(parse_remote("[]").is_err());
+    }
+}
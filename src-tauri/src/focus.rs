@@ -0,0 +1,177 @@
+// SERINA Focus Sessions
+// A timed do-not-disturb: notifications and new mail are held back and delivered as one
+// catch-up digest when the session ends
+
+use crate::utils::EmailData;
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use std::sync::Mutex;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FocusSession {
+    pub started_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+}
+
+impl FocusSession {
+    /// Whole minutes left, rounded up so the last minute still shows as 1
+    pub fn remaining_minutes(&self, now: DateTime<Utc>) -> i64 {
+        let seconds = (self.ends_at - now).num_seconds().max(0);
+        (seconds + 59) / 60
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct HeldNotification {
+    pub title: String,
+    pub body: String,
+}
+
+/// Everything that was held back during a session
+#[derive(Debug, Clone, Serialize)]
+pub struct FocusDigest {
+    pub started_at: DateTime<Utc>,
+    pub ended_at: DateTime<Utc>,
+    /// Emails that arrived during the session, oldest first
+    pub emails: Vec<EmailData>,
+    pub notifications: Vec<HeldNotification>,
+}
+
+struct Active {
+    session: FocusSession,
+    emails: Vec<EmailData>,
+    notifications: Vec<HeldNotification>,
+}
+
+impl Active {
+    fn into_digest(self, now: DateTime<Utc>) -> FocusDigest {
+        FocusDigest {
+            started_at: self.session.started_at,
+            ended_at: now.min(self.session.ends_at),
+            emails: self.emails,
+            notifications: self.notifications,
+        }
+    }
+}
+
+/// The running focus session, if any. Sessions are not persisted; a restart ends one.
+#[derive(Default)]
+pub struct FocusState {
+    active: Mutex<Option<Active>>,
+}
+
+impl FocusState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start a session, or restart the timer of the running one keeping what it holds
+    pub fn start(&self, minutes: u32, now: DateTime<Utc>) -> Result<FocusSession, String> {
+        if minutes == 0 {
+            return Err("A focus session needs at least one minute".to_string());
+        }
+        let session = FocusSession {
+            started_at: now,
+            ends_at: now + Duration::minutes(minutes as i64),
+        };
+        let mut active = self.active.lock().unwrap();
+        match active.as_mut() {
+            Some(running) => running.session.ends_at = session.ends_at,
+            None => {
+                *active = Some(Active {
+                    session: session.clone(),
+                    emails: Vec::new(),
+                    notifications: Vec::new(),
+                })
+            }
+        }
+        Ok(active.as_ref().unwrap().session.clone())
+    }
+
+    /// The running session; one past its end still counts until `take_finished` collects it
+    pub fn current(&self) -> Option<FocusSession> {
+        self.active
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(|active| active.session.clone())
+    }
+
+    /// Hold a notification back; false when no session is running and it should be shown
+    pub fn hold_notification(&self, title: &str, body: &str) -> bool {
+        let mut active = self.active.lock().unwrap();
+        let Some(active) = active.as_mut() else {
+            return false;
+        };
+        let held = HeldNotification {
+            title: title.to_string(),
+            body: body.to_string(),
+        };
+        // Repeats of the same alert (reminders, retried snoozes) only count once
+        if !active.notifications.contains(&held) {
+            active.notifications.push(held);
+        }
+        true
+    }
+
+    /// Queue emails that arrived during the session for the digest; muted conversations are
+    /// left out
+    pub fn hold_emails(&self, emails: &[EmailData]) {
+        let mut active = self.active.lock().unwrap();
+        let Some(active) = active.as_mut() else {
+            return;
+        };
+        for email in emails.iter().filter(|email| !email.muted) {
+            if !active.emails.iter().any(|held| held.id == email.id) {
+                active.emails.push(email.clone());
+            }
+        }
+    }
+
+    /// End the session now
+    pub fn end(&self, now: DateTime<Utc>) -> Option<FocusDigest> {
+        let active = self.active.lock().unwrap().take()?;
+        Some(active.into_digest(now))
+    }
+
+    /// End the session if its time is up
+    pub fn take_finished(&self, now: DateTime<Utc>) -> Option<FocusDigest> {
+        let mut active = self.active.lock().unwrap();
+        if active.as_ref()?.session.ends_at > now {
+            return None;
+        }
+        Some(active.take()?.into_digest(now))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_session() {
+        let focus = FocusState::new();
+        let now = Utc.with_ymd_and_hms(2024, 5, 10, 9, 0, 0).unwrap();
+        assert!(!focus.hold_notification("Update available", "1.2.0"));
+        assert!(focus.start(0, now).is_err());
+
+        let session = focus.start(25, now).unwrap();
+        assert_eq!(session.remaining_minutes(now + Duration::seconds(30)), 25);
+        assert!(focus.hold_notification("Snoozed email is back", "Lunch"));
+        assert!(focus.hold_notification("Snoozed email is back", "Lunch"));
+        focus.hold_emails(&[EmailData::sample("a"), EmailData::sample("b")]);
+        focus.hold_emails(&[EmailData::sample("a")]);
+        focus.hold_emails(&[EmailData {
+            muted: true,
+            ..EmailData::sample("c")
+        }]);
+
+        assert!(focus.take_finished(now + Duration::minutes(10)).is_none());
+        let digest = focus.take_finished(now + Duration::minutes(26)).unwrap();
+        assert_eq!(digest.ended_at, session.ends_at);
+        assert_eq!(digest.emails.len(), 2);
+        assert_eq!(digest.notifications.len(), 1);
+        assert!(focus.current().is_none());
+    }
+}
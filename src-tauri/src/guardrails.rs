@@ -0,0 +1,371 @@
+// SERINA Reply Guardrails
+// Checks on LLM-written replies before they can be sent: length, banned phrases, attachments
+// and links the model made up, and a reply in a different language than the email
+
+use crate::compose::{self, SendWarning, WarningKind};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GuardrailConfig {
+    /// Longest generated reply, in characters
+    pub max_chars: usize,
+    /// Phrases a generated reply must not contain, matched as whole words ignoring case
+    pub banned_phrases: Vec<String>,
+}
+
+impl Default for GuardrailConfig {
+    fn default() -> Self {
+        Self {
+            max_chars: 3000,
+            banned_phrases: vec![
+                "as an ai".to_string(),
+                "as a language model".to_string(),
+                "i cannot assist".to_string(),
+            ],
+        }
+    }
+}
+
+/// Words common in one language and rare in the others, for telling them apart
+const STOPWORDS: &[(&str, &[&str])] = &[
+    (
+        "en",
+        &[
+            "the", "and", "you", "is", "to", "for", "with", "this", "that", "have", "will",
+            "thanks", "please", "are", "we",
+        ],
+    ),
+    (
+        "es",
+        &[
+            "el", "los", "las", "y", "es", "con", "gracias", "usted", "pero", "muy", "hola",
+            "saludos", "del", "una", "por",
+        ],
+    ),
+    (
+        "pt",
+        &[
+            "o",
+            "os",
+            "é",
+            "não",
+            "com",
+            "você",
+            "obrigado",
+            "obrigada",
+            "muito",
+            "mas",
+            "olá",
+            "atenciosamente",
+            "do",
+            "da",
+            "em",
+        ],
+    ),
+    (
+        "fr",
+        &[
+            "le",
+            "les",
+            "et",
+            "est",
+            "vous",
+            "pour",
+            "avec",
+            "merci",
+            "je",
+            "nous",
+            "une",
+            "des",
+            "pas",
+            "bonjour",
+            "cordialement",
+        ],
+    ),
+    (
+        "de",
+        &[
+            "der", "die", "das", "und", "ist", "sie", "für", "mit", "danke", "ich", "wir", "nicht",
+            "ein", "eine", "bitte",
+        ],
+    ),
+    (
+        "it",
+        &[
+            "il", "gli", "è", "per", "grazie", "sono", "non", "della", "cordiali", "saluti",
+            "ciao", "questo", "anche",
+        ],
+    ),
+    (
+        "nl",
+        &[
+            "de", "het", "een", "en", "van", "met", "voor", "niet", "bedankt", "groeten", "wij",
+            "ik", "u",
+        ],
+    ),
+];
+
+/// Stopword hits needed before a language is named
+const MIN_LANGUAGE_HITS: usize = 3;
+/// Only the start of a text is looked at to guess its language
+const LANGUAGE_SAMPLE_CHARS: usize = 2000;
+
+/// The language `text` is most likely written in (ISO 639-1), when it's clear
+pub fn detect_language(text: &str) -> Option<&'static str> {
+    let sample: String = text.chars().take(LANGUAGE_SAMPLE_CHARS).collect();
+    let words: Vec<String> = sample
+        .split(|c: char| !c.is_alphabetic())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+    let mut scores: Vec<(&'static str, usize)> = STOPWORDS
+        .iter()
+        .map(|(code, stopwords)| {
+            let hits = words
+                .iter()
+                .filter(|word| stopwords.contains(&word.as_str()))
+                .count();
+            (*code, hits)
+        })
+        .collect();
+    scores.sort_by_key(|(_, hits)| std::cmp::Reverse(*hits));
+    let (code, best) = scores[0];
+    (best >= MIN_LANGUAGE_HITS && best >= scores[1].1 * 2).then_some(code)
+}
+
+/// Web addresses written out in `text`, as they appear
+fn find_links(text: &str) -> Vec<&str> {
+    text.split_whitespace()
+        .map(|word| word.trim_matches(|c: char| "<>()[]{}\"'.,;:!?".contains(c)))
+        .filter(|word| {
+            let lower = word.to_ascii_lowercase();
+            lower.starts_with("http://")
+                || lower.starts_with("https://")
+                || lower.starts_with("www.")
+        })
+        .collect()
+}
+
+/// What a generated reply is checked against
+pub struct ReplySource<'a> {
+    /// The email being answered
+    pub original: &'a str,
+    /// Whether the reply should be in the email's language; off when the user asked for another
+    pub same_language: bool,
+    /// Files going out with the reply
+    pub attachment_count: u32,
+}
+
+/// Check a generated reply; an empty list means it may go out without a second look
+pub fn check_reply(
+    reply: &str,
+    source: &ReplySource,
+    config: &GuardrailConfig,
+) -> Vec<SendWarning> {
+    let mut warnings = Vec::new();
+    let length = reply.chars().count();
+    if config.max_chars > 0 && length > config.max_chars {
+        warnings.push(SendWarning::new(
+            WarningKind::TooLong,
+            format!(
+                "Reply is {} characters, over the limit of {}",
+                length, config.max_chars
+            ),
+        ));
+    }
+
+    let lower = reply.to_lowercase();
+    for phrase in &config.banned_phrases {
+        let phrase = phrase.trim().to_lowercase();
+        if !phrase.is_empty() && compose::contains_phrase(&lower, &phrase) {
+            warnings.push(SendWarning::new(
+                WarningKind::BannedPhrase,
+                format!("Reply contains the banned phrase \"{}\"", phrase),
+            ));
+        }
+    }
+
+    if source.attachment_count == 0 {
+        if let Some(phrase) = compose::mentions_attachment(reply) {
+            warnings.push(SendWarning::new(
+                WarningKind::InventedAttachment,
+                format!(
+                    "Reply mentions an attachment (\"{}\") the draft couldn't include",
+                    phrase
+                ),
+            ));
+        }
+    }
+
+    let original = source.original.to_lowercase();
+    for link in find_links(reply) {
+        let normalized = link.trim_end_matches('/').to_lowercase();
+        if !original.contains(&normalized) {
+            warnings.push(SendWarning::new(
+                WarningKind::InventedLink,
+                format!("Reply links to {}, which isn't in the original email", link),
+            ));
+        }
+    }
+
+    if source.same_language {
+        if let (Some(expected), Some(actual)) =
+            (detect_language(source.original), detect_language(reply))
+        {
+            if expected != actual {
+                warnings.push(SendWarning::new(
+                    WarningKind::LanguageMismatch,
+                    format!(
+                        "Reply looks to be in \"{}\" but the email is in \"{}\"",
+                        actual, expected
+                    ),
+                ));
+            }
+        }
+    }
+    warnings
+}
+
+struct Generated {
+    original: String,
+    same_language: bool,
+}
+
+/// The email each generated draft answers, so the send path can check it again
+#[derive(Default)]
+pub struct GeneratedReplies {
+    drafts: Mutex<HashMap<String, Generated>>,
+}
+
+impl GeneratedReplies {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn remember(&self, email_id: &str, original: &str, same_language: bool) {
+        self.drafts.lock().unwrap().insert(
+            email_id.to_string(),
+            Generated {
+                original: original.to_string(),
+                same_language,
+            },
+        );
+    }
+
+    /// Check the reply about to be sent for `email_id`; nothing to check if no draft was
+    /// generated for it
+    pub fn check(
+        &self,
+        email_id: &str,
+        reply: &str,
+        attachment_count: u32,
+        config: &GuardrailConfig,
+    ) -> Vec<SendWarning> {
+        let drafts = self.drafts.lock().unwrap();
+        let Some(draft) = drafts.get(email_id) else {
+            return Vec::new();
+        };
+        let source = ReplySource {
+            original: &draft.original,
+            same_language: draft.same_language,
+            attachment_count,
+        };
+        check_reply(reply, &source, config)
+    }
+
+    /// Drop the draft once its reply has gone out
+    pub fn forget(&self, email_id: &str) {
+        self.drafts.lock().unwrap().remove(email_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(reply: &str, original: &str) -> Vec<WarningKind> {
+        let source = ReplySource {
+            original,
+            same_language: true,
+            attachment_count: 0,
+        };
+        check_reply(reply, &source, &GuardrailConfig::default())
+            .into_iter()
+            .map(|w| w.kind)
+            .collect()
+    }
+
+    #[test]
+    fn test_detect_language() {
+        assert_eq!(
+            detect_language("Thanks for the update, we will have it ready for you."),
+            Some("en")
+        );
+        assert_eq!(
+            detect_language("Hola, gracias por el mensaje. Los datos están con usted."),
+            Some("es")
+        );
+        assert_eq!(
+            detect_language("Olá, muito obrigado. Você não precisa fazer nada com os dados."),
+            Some("pt")
+        );
+        assert_eq!(
+            detect_language("Bonjour, merci pour votre message. Nous avons les documents."),
+            Some("fr")
+        );
+        assert_eq!(detect_language("OK"), None);
+    }
+
+    #[test]
+    fn test_check_reply() {
+        let original = "Hi, can you send the slides from https://example.com/deck? Thanks and have a good week.";
+        assert!(kinds(
+            "Sure, the slides are at https://example.com/deck. Thanks for the reminder, I will have them to you today.",
+            original
+        )
+        .is_empty());
+        assert_eq!(
+            kinds(
+                "Sure, get them from https://files.example.net/deck and have a look. Thanks!",
+                original
+            ),
+            vec![WarningKind::InventedLink]
+        );
+        assert_eq!(
+            kinds(
+                "Please find the slides attached. Thanks, I will follow up with the notes this week.",
+                original
+            ),
+            vec![WarningKind::InventedAttachment]
+        );
+        assert_eq!(
+            kinds(
+                "Hola, gracias por el mensaje. Le envío las diapositivas con mucho gusto.",
+                original
+            ),
+            vec![WarningKind::LanguageMismatch]
+        );
+        assert_eq!(
+            kinds("As an AI, I can't open files.", original),
+            vec![WarningKind::BannedPhrase]
+        );
+        let long = "a ".repeat(2000);
+        assert_eq!(kinds(&long, original), vec![WarningKind::TooLong]);
+    }
+
+    #[test]
+    fn test_generated_replies_are_rechecked() {
+        let generated = GeneratedReplies::new();
+        let config = GuardrailConfig::default();
+        let reply = "The report is attached.";
+        assert!(generated.check("1", reply, 0, &config).is_empty());
+        generated.remember("1", "Could you send the report?", true);
+        assert_eq!(generated.check("1", reply, 0, &config).len(), 1);
+        assert!(generated.check("1", reply, 1, &config).is_empty());
+        generated.forget("1");
+        assert!(generated.check("1", reply, 0, &config).is_empty());
+    }
+}
@@ -0,0 +1,618 @@
+// SERINA HTTP Client
+// One outbound client for backend, LLM and provider calls, with proxy, custom CA and pinning
+
+use crate::ratelimit::{self, RateLimited, RateLimiter};
+use base64::Engine;
+use rustls::client::{ServerCertVerified, ServerCertVerifier, WebPkiVerifier};
+use rustls::{Certificate, ClientConfig, RootCertStore, ServerName};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+use tokio::sync::broadcast;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProxyMode {
+    /// Proxy environment variables, then the OS settings
+    #[default]
+    System,
+    None,
+    Manual,
+    /// Proxy auto-config script at `pac_url`
+    Pac,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NetworkConfig {
+    /// Where the Python backend listens; empty for the local default
+    pub backend_url: String,
+    pub proxy_mode: ProxyMode,
+    /// e.g. "http://proxy.corp.example:8080", used in manual mode
+    pub proxy_url: String,
+    pub proxy_username: String,
+    pub proxy_password: String,
+    pub pac_url: String,
+    /// Hosts or ".domain" suffixes that skip the proxy; this machine always does
+    pub no_proxy: Vec<String>,
+    /// PEM files with extra trusted roots, e.g. a TLS-inspection CA
+    pub ca_certificates: Vec<String>,
+    /// Public keys expected from sensitive hosts such as the LLM API
+    pub pins: Vec<CertificatePin>,
+}
+
+/// The backend started by `python backend/main.py`
+pub const DEFAULT_BACKEND_URL: &str = "http://127.0.0.1:8000";
+
+impl NetworkConfig {
+    pub fn backend_url(&self) -> &str {
+        match self.backend_url.trim_end_matches('/') {
+            "" => DEFAULT_BACKEND_URL,
+            url => url,
+        }
+    }
+}
+
+/// Accepted server keys for one host; list the next key ahead of a rotation so it keeps working
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CertificatePin {
+    pub host: String,
+    /// "sha256/<base64>" hashes of the leaf certificate's SubjectPublicKeyInfo
+    pub sha256: Vec<String>,
+}
+
+/// Proxy from the usual environment variables
+pub fn env_proxy() -> Option<String> {
+    [
+        "HTTPS_PROXY",
+        "https_proxy",
+        "HTTP_PROXY",
+        "http_proxy",
+        "ALL_PROXY",
+        "all_proxy",
+    ]
+    .iter()
+    .filter_map(|name| std::env::var(name).ok())
+    .find(|value| !value.trim().is_empty())
+}
+
+fn env_no_proxy() -> Vec<String> {
+    std::env::var("NO_PROXY")
+        .or_else(|_| std::env::var("no_proxy"))
+        .map(|value| value.split(',').map(|s| s.trim().to_string()).collect())
+        .unwrap_or_default()
+}
+
+/// The first proxy named in a PAC script. Scripts aren't evaluated, so this covers the common
+/// single-proxy case; anything that only returns DIRECT means no proxy.
+pub fn pac_proxy(script: &str) -> Option<String> {
+    let words: Vec<&str> = script
+        .split(|c: char| c.is_whitespace() || matches!(c, '"' | '\'' | ';'))
+        .filter(|word| !word.is_empty())
+        .collect();
+    words.windows(2).find_map(|pair| match pair {
+        ["PROXY", host] => Some(format!("http://{}", host)),
+        ["HTTPS", host] => Some(format!("https://{}", host)),
+        _ => None,
+    })
+}
+
+/// Whether `host` goes direct: loopback always, plus `no_proxy` entries ("*" matches everything)
+pub fn bypasses_proxy(host: &str, no_proxy: &[String]) -> bool {
+    let host = host
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .to_lowercase();
+    if host == "localhost"
+        || host
+            .parse::<std::net::IpAddr>()
+            .is_ok_and(|ip| ip.is_loopback())
+    {
+        return true;
+    }
+    no_proxy.iter().any(|entry| {
+        let entry = entry.trim().to_lowercase();
+        let domain = entry.trim_start_matches('.');
+        entry == "*"
+            || (!domain.is_empty() && (host == domain || host.ends_with(&format!(".{}", domain))))
+    })
+}
+
+/// The proxy to use under `config`, if any
+pub async fn resolve_proxy(config: &NetworkConfig) -> Result<Option<String>, String> {
+    match config.proxy_mode {
+        ProxyMode::None => Ok(None),
+        ProxyMode::System => Ok(env_proxy()),
+        ProxyMode::Manual => {
+            let url = config.proxy_url.trim();
+            if url.is_empty() {
+                return Err("Manual proxy mode needs a proxy URL".to_string());
+            }
+            Ok(Some(url.to_string()))
+        }
+        ProxyMode::Pac => {
+            // The PAC file itself is fetched directly, as browsers do
+            let script = reqwest::Client::builder()
+                .no_proxy()
+                .build()
+                .map_err(|e| e.to_string())?
+                .get(config.pac_url.trim())
+                .send()
+                .await
+                .and_then(|response| response.error_for_status())
+                .map_err(|e| format!("Couldn't fetch proxy script: {}", e))?
+                .text()
+                .await
+                .map_err(|e| e.to_string())?;
+            Ok(pac_proxy(&script))
+        }
+    }
+}
+
+pub async fn build_client(config: &NetworkConfig) -> Result<reqwest::Client, String> {
+    client_builder(config)
+        .await?
+        .build()
+        .map_err(|e| e.to_string())
+}
+
+/// Proxy, CA and pin settings for `config`, for callers that need to adjust the client further
+async fn client_builder(config: &NetworkConfig) -> Result<reqwest::ClientBuilder, String> {
+    // Exposes the peer certificate on responses, for reading a server's current pin
+    let mut builder = reqwest::Client::builder().tls_info(true);
+    if config.pins.iter().any(|pin| !pin.sha256.is_empty()) {
+        builder = builder.use_preconfigured_tls(pinned_tls(config)?);
+    } else {
+        for path in &config.ca_certificates {
+            let certificates = reqwest::Certificate::from_pem_bundle(&read_ca(path)?)
+                .map_err(|e| format!("Invalid CA certificate {}: {}", path, e))?;
+            for certificate in certificates {
+                builder = builder.add_root_certificate(certificate);
+            }
+        }
+    }
+
+    match resolve_proxy(config).await? {
+        Some(proxy_url) => {
+            reqwest::Url::parse(&proxy_url).map_err(|e| format!("Invalid proxy URL: {}", e))?;
+            let mut no_proxy = config.no_proxy.clone();
+            if config.proxy_mode == ProxyMode::System {
+                no_proxy.extend(env_no_proxy());
+            }
+            let mut proxy = reqwest::Proxy::custom(move |url| {
+                let host = url.host_str().unwrap_or_default();
+                (!bypasses_proxy(host, &no_proxy)).then(|| proxy_url.clone())
+            });
+            if !config.proxy_username.is_empty() {
+                proxy = proxy.basic_auth(&config.proxy_username, &config.proxy_password);
+            }
+            builder = builder.proxy(proxy);
+        }
+        // In system mode with no proxy variables, reqwest still picks up the OS setting
+        None if config.proxy_mode == ProxyMode::System => {}
+        None => builder = builder.no_proxy(),
+    }
+    Ok(builder)
+}
+
+fn read_ca(path: &str) -> Result<Vec<u8>, String> {
+    std::fs::read(path).map_err(|e| format!("Couldn't read CA certificate {}: {}", path, e))
+}
+
+/// TLS settings that check pins during the handshake: the OS roots plus `ca_certificates` are
+/// trusted as usual, and a pinned host whose key doesn't match fails before any request is sent
+fn pinned_tls(config: &NetworkConfig) -> Result<ClientConfig, String> {
+    let mut roots = RootCertStore::empty();
+    let native = rustls_native_certs::load_native_certs()
+        .map_err(|e| format!("Couldn't load the system certificates: {}", e))?;
+    roots.add_parsable_certificates(&native.into_iter().map(|c| c.0).collect::<Vec<_>>());
+    for path in &config.ca_certificates {
+        let certificates = rustls_pemfile::certs(&mut read_ca(path)?.as_slice())
+            .map_err(|e| format!("Invalid CA certificate {}: {}", path, e))?;
+        for certificate in certificates {
+            roots
+                .add(&Certificate(certificate))
+                .map_err(|e| format!("Invalid CA certificate {}: {}", path, e))?;
+        }
+    }
+    let verifier = PinVerifier {
+        inner: WebPkiVerifier::new(roots, None),
+        pins: config.pins.clone(),
+    };
+    Ok(ClientConfig::builder()
+        .with_safe_defaults()
+        .with_custom_certificate_verifier(Arc::new(verifier))
+        .with_no_client_auth())
+}
+
+/// The pins for the host being connected to, then the usual chain and hostname checks
+struct PinVerifier {
+    inner: WebPkiVerifier,
+    pins: Vec<CertificatePin>,
+}
+
+impl ServerCertVerifier for PinVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &Certificate,
+        intermediates: &[Certificate],
+        server_name: &ServerName,
+        scts: &mut dyn Iterator<Item = &[u8]>,
+        ocsp_response: &[u8],
+        now: SystemTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        let host = match server_name {
+            ServerName::DnsName(name) => name.as_ref().to_string(),
+            ServerName::IpAddress(ip) => ip.to_string(),
+            _ => String::new(),
+        };
+        check_pin(&self.pins, &host, Some(&end_entity.0)).map_err(rustls::Error::General)?;
+        self.inner.verify_server_cert(
+            end_entity,
+            intermediates,
+            server_name,
+            scts,
+            ocsp_response,
+            now,
+        )
+    }
+}
+
+/// One DER element split off the front of a buffer
+struct DerElement<'a> {
+    tag: u8,
+    whole: &'a [u8],
+    content: &'a [u8],
+    rest: &'a [u8],
+}
+
+fn der_element(input: &[u8]) -> Option<DerElement<'_>> {
+    let (&tag, after_tag) = input.split_first()?;
+    let (&first, after_len) = after_tag.split_first()?;
+    let (len, header) = if first < 0x80 {
+        (first as usize, 2)
+    } else {
+        let count = (first & 0x7f) as usize;
+        if count == 0 || count > 4 || after_len.len() < count {
+            return None;
+        }
+        let len = after_len[..count]
+            .iter()
+            .fold(0usize, |len, byte| (len << 8) | *byte as usize);
+        (len, 2 + count)
+    };
+    let end = header.checked_add(len).filter(|end| *end <= input.len())?;
+    Some(DerElement {
+        tag,
+        whole: &input[..end],
+        content: &input[header..end],
+        rest: &input[end..],
+    })
+}
+
+/// The SubjectPublicKeyInfo of a DER certificate
+pub fn public_key_info(certificate: &[u8]) -> Option<&[u8]> {
+    let cert = der_element(certificate)?.content;
+    let mut tbs = der_element(cert)?.content;
+    // Optional [0] version, then serial, signature algorithm, issuer, validity and subject
+    let version = der_element(tbs)?;
+    if version.tag == 0xa0 {
+        tbs = version.rest;
+    }
+    for _ in 0..5 {
+        tbs = der_element(tbs)?.rest;
+    }
+    Some(der_element(tbs)?.whole)
+}
+
+/// "sha256/<base64>" pin for a DER certificate's public key
+pub fn certificate_pin(certificate: &[u8]) -> Option<String> {
+    let digest = Sha256::digest(public_key_info(certificate)?);
+    Some(format!(
+        "sha256/{}",
+        base64::engine::general_purpose::STANDARD.encode(digest)
+    ))
+}
+
+fn pins_for<'a>(pins: &'a [CertificatePin], host: &str) -> Option<&'a CertificatePin> {
+    pins.iter()
+        .find(|pin| pin.host.trim().eq_ignore_ascii_case(host) && !pin.sha256.is_empty())
+}
+
+/// Check a server certificate against the pins for `host`; unpinned hosts always pass
+pub fn check_pin(
+    pins: &[CertificatePin],
+    host: &str,
+    certificate: Option<&[u8]>,
+) -> Result<(), String> {
+    let Some(expected) = pins_for(pins, host) else {
+        return Ok(());
+    };
+    let actual = certificate
+        .and_then(certificate_pin)
+        .ok_or_else(|| format!("{} is pinned but presented no readable certificate", host))?;
+    let matches = expected.sha256.iter().any(|pin| {
+        pin.trim().trim_start_matches("sha256/") == actual.trim_start_matches("sha256/")
+    });
+    if matches {
+        Ok(())
+    } else {
+        Err(format!(
+            "Certificate pin mismatch for {}: the server key is {}. If {} rotated its \
+             certificate, confirm the new key and update the pin under network settings.",
+            host, actual, host
+        ))
+    }
+}
+
+fn peer_certificate(response: &reqwest::Response) -> Option<&[u8]> {
+    response
+        .extensions()
+        .get::<reqwest::tls::TlsInfo>()?
+        .peer_certificate()
+}
+
+/// The outbound client; pins are enforced in its TLS handshake. Clones share the rate-limit
+/// queue.
+#[derive(Clone)]
+pub struct PinnedClient {
+    client: reqwest::Client,
+    limiter: Arc<RateLimiter>,
+}
+
+impl PinnedClient {
+    pub async fn build(config: &NetworkConfig) -> Result<Self, String> {
+        Ok(Self::new(build_client(config).await?))
+    }
+
+    /// A client that returns redirects instead of following them, for checking each hop
+    pub async fn build_without_redirects(config: &NetworkConfig) -> Result<Self, String> {
+        let client = client_builder(config)
+            .await?
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .map_err(|e| e.to_string())?;
+        Ok(Self::new(client))
+    }
+
+    pub fn new(client: reqwest::Client) -> Self {
+        Self {
+            client,
+            limiter: Arc::default(),
+        }
+    }
+
+    /// Share a rate-limit queue that outlives this client, e.g. across network setting changes
+    pub fn with_limiter(self, limiter: Arc<RateLimiter>) -> Self {
+        Self { limiter, ..self }
+    }
+
+    /// Send a request, waiting out rate limits. Requests to a host that asked us to slow down
+    /// queue until its window reopens, and a 429 is retried after the server's Retry-After (or
+    /// a short backoff) up to `ratelimit::MAX_RETRIES` times.
+    pub async fn send(
+        &self,
+        request: reqwest::RequestBuilder,
+    ) -> Result<reqwest::Response, String> {
+        let mut request = request.build().map_err(|e| e.to_string())?;
+        let host = request.url().host_str().unwrap_or_default().to_lowercase();
+        let mut attempt = 0;
+        loop {
+            self.limiter.wait_turn(&host).await;
+            let retry = request.try_clone();
+            let response = self
+                .client
+                .execute(request)
+                .await
+                .map_err(|e| e.to_string())?;
+            let wait = ratelimit::retry_after(response.headers(), chrono::Utc::now());
+            if response.status() != reqwest::StatusCode::TOO_MANY_REQUESTS {
+                if let Some(wait) = wait.filter(|_| ratelimit::exhausted(response.headers())) {
+                    self.limiter.block(&host, wait);
+                }
+                return Ok(response);
+            }
+
+            attempt += 1;
+            let wait = wait.unwrap_or_else(|| ratelimit::backoff(attempt));
+            match retry {
+                Some(next) if attempt <= ratelimit::MAX_RETRIES && wait <= ratelimit::MAX_WAIT => {
+                    self.limiter.block(&host, wait);
+                    self.limiter.notify(RateLimited {
+                        host: host.clone(),
+                        wait_secs: wait.as_secs_f64().ceil() as u64,
+                        attempt,
+                    });
+                    request = next;
+                }
+                _ => {
+                    self.limiter.block(&host, wait);
+                    return Err(format!(
+                        "HTTP 429 Too Many Requests: {} is rate limited, try again in {}s",
+                        host,
+                        wait.as_secs_f64().ceil() as u64
+                    ));
+                }
+            }
+        }
+    }
+}
+
+impl std::ops::Deref for PinnedClient {
+    type Target = reqwest::Client;
+
+    fn deref(&self) -> &reqwest::Client {
+        &self.client
+    }
+}
+
+/// The client for all outbound requests, rebuilt when the network settings change
+#[derive(Default)]
+pub struct HttpClient {
+    current: Mutex<Option<(NetworkConfig, PinnedClient)>>,
+    without_redirects: Mutex<Option<(NetworkConfig, PinnedClient)>>,
+    limiter: Arc<RateLimiter>,
+}
+
+impl HttpClient {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub async fn get(&self, config: &NetworkConfig) -> Result<PinnedClient, String> {
+        if let Some((built_for, client)) = self.current.lock().unwrap().as_ref() {
+            if built_for == config {
+                return Ok(client.clone());
+            }
+        }
+        let client = PinnedClient::build(config)
+            .await?
+            .with_limiter(self.limiter.clone());
+        *self.current.lock().unwrap() = Some((config.clone(), client.clone()));
+        Ok(client)
+    }
+
+    /// Like `get`, but redirects come back as responses instead of being followed
+    pub async fn get_without_redirects(
+        &self,
+        config: &NetworkConfig,
+    ) -> Result<PinnedClient, String> {
+        if let Some((built_for, client)) = self.without_redirects.lock().unwrap().as_ref() {
+            if built_for == config {
+                return Ok(client.clone());
+            }
+        }
+        let client = PinnedClient::build_without_redirects(config)
+            .await?
+            .with_limiter(self.limiter.clone());
+        *self.without_redirects.lock().unwrap() = Some((config.clone(), client.clone()));
+        Ok(client)
+    }
+
+    /// Waits imposed by rate limits, from whichever client is current
+    pub fn subscribe_rate_limits(&self) -> broadcast::Receiver<RateLimited> {
+        self.limiter.subscribe()
+    }
+}
+
+/// The current pin of a server, ignoring configured pins; used to re-pin after a rotation
+pub async fn fetch_pin(config: &NetworkConfig, url: &str) -> Result<String, String> {
+    let config = NetworkConfig {
+        pins: Vec::new(),
+        ..config.clone()
+    };
+    let response = build_client(&config)
+        .await?
+        .head(url)
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    peer_certificate(&response)
+        .and_then(certificate_pin)
+        .ok_or_else(|| "The server presented no certificate (is the URL https?)".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pac_proxy() {
+        let script = r#"function FindProxyForURL(url, host) {
+            if (isPlainHostName(host)) return "DIRECT";
+            return "PROXY proxy.corp.example:8080; DIRECT";
+        }"#;
+        assert_eq!(
+            pac_proxy(script).as_deref(),
+            Some("http://proxy.corp.example:8080")
+        );
+        assert_eq!(
+            pac_proxy("function FindProxyForURL() { return 'DIRECT'; }"),
+            None
+        );
+    }
+
+    const TEST_CERTIFICATE: &str =
+        "MIIBezCCASGgAwIBAgIUNEjtaMhEgnhewoSkDcxVodEQG3cwCgYIKoZIzj0EAwIw\
+        EzERMA8GA1UEAwwIcGluLnRlc3QwHhcNMjYxMDE2MDkzNzMyWhcNMzYxMDEzMDkz\
+        NzMyWjATMREwDwYDVQQDDAhwaW4udGVzdDBZMBMGByqGSM49AgEGCCqGSM49AwEH\
+        A0IABKSxVXy1N/pVkCAMBuFQQVRIqVYXNFr6u0zjxnox/mcjZplYx0SB1uY/YUq5\
+        ssQCDyHWsQ2o7SA7wwzrFIlrNvajUzBRMB0GA1UdDgQWBBTTNTHefpAi4/jm0qQg\
+        nzDEWRbiSjAfBgNVHSMEGDAWgBTTNTHefpAi4/jm0qQgnzDEWRbiSjAPBgNVHRMB\
+        Af8EBTADAQH/MAoGCCqGSM49BAMCA0gAMEUCIFNHiEDaR3I5oUZJO9Ij94AWGNJ/\
+        48CtjWytfIDLCC4AAiEAl6KQ2ZXlk4u8DRC2OYdf/k4meJUpr3unTyEzMddPbBY=";
+
+    #[test]
+    fn test_certificate_pins() {
+        let der = base64::engine::general_purpose::STANDARD
+            .decode(TEST_CERTIFICATE.replace(' ', ""))
+            .unwrap();
+        // Same value as `openssl x509 -pubkey | openssl pkey -pubin -outform der | sha256 | base64`
+        let pin = "sha256/QtLRJEONoj41rTssdweX/FeumOJ8ce2b0lqsxp7St3o=";
+        assert_eq!(certificate_pin(&der).as_deref(), Some(pin));
+
+        let pins = vec![CertificatePin {
+            host: "api.openai.com".to_string(),
+            sha256: vec!["sha256/old".to_string(), pin.to_string()],
+        }];
+        assert!(check_pin(&pins, "api.openai.com", Some(&der)).is_ok());
+        assert!(check_pin(&pins, "example.com", None).is_ok());
+        let err = check_pin(&pins[..], "api.openai.com", Some(&der[..der.len() / 2])).unwrap_err();
+        assert!(err.contains("no readable certificate"));
+
+        let rotated = vec![CertificatePin {
+            sha256: vec!["sha256/old".to_string()],
+            ..pins[0].clone()
+        }];
+        let err = check_pin(&rotated, "api.openai.com", Some(&der)).unwrap_err();
+        assert!(err.contains(pin));
+    }
+
+    #[test]
+    fn test_pins_checked_in_handshake() {
+        let der = base64::engine::general_purpose::STANDARD
+            .decode(TEST_CERTIFICATE.replace(' ', ""))
+            .unwrap();
+        let verify = |pin: &str, host: &str| {
+            let verifier = PinVerifier {
+                inner: WebPkiVerifier::new(RootCertStore::empty(), None),
+                pins: vec![CertificatePin {
+                    host: "pin.test".to_string(),
+                    sha256: vec![pin.to_string()],
+                }],
+            };
+            verifier.verify_server_cert(
+                &Certificate(der.clone()),
+                &[],
+                &ServerName::try_from(host).unwrap(),
+                &mut std::iter::empty(),
+                &[],
+                SystemTime::now(),
+            )
+        };
+        // A wrong key is refused before the connection can carry a request
+        let err = verify("sha256/old", "pin.test").unwrap_err().to_string();
+        assert!(err.contains("Certificate pin mismatch"));
+        // A matching key still needs a trusted chain; this certificate is self-signed
+        let pin = certificate_pin(&der).unwrap();
+        let err = verify(&pin, "pin.test").unwrap_err().to_string();
+        assert!(!err.contains("pin mismatch"));
+        let err = verify("sha256/old", "other.test").unwrap_err().to_string();
+        assert!(!err.contains("pin mismatch"));
+    }
+
+    #[test]
+    fn test_bypasses_proxy() {
+        let no_proxy = vec![".corp.example".to_string(), "intranet".to_string()];
+        assert!(bypasses_proxy("127.0.0.1", &[]));
+        assert!(bypasses_proxy("[::1]", &[]));
+        assert!(bypasses_proxy("localhost", &[]));
+        assert!(bypasses_proxy("mail.corp.example", &no_proxy));
+        assert!(bypasses_proxy("intranet", &no_proxy));
+        assert!(!bypasses_proxy("api.openai.com", &no_proxy));
+        assert!(!bypasses_proxy("notcorp.example", &no_proxy));
+        assert!(bypasses_proxy("api.openai.com", &["*".to_string()]));
+    }
+}
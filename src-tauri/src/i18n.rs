@@ -0,0 +1,198 @@
+// SERINA Localization
+// Fluent message bundles for strings that originate in Rust (tray, notifications, errors)
+
+use fluent_bundle::concurrent::FluentBundle;
+use fluent_bundle::{FluentArgs, FluentResource, FluentValue};
+use serde::Serialize;
+use std::sync::RwLock;
+use unic_langid::LanguageIdentifier;
+
+pub const DEFAULT_LOCALE: &str = "en-US";
+
+/// Bundled translations, keyed by locale
+const RESOURCES: &[(&str, &str)] = &[
+    ("en-US", include_str!("../locales/en-US.ftl")),
+    ("es", include_str!("../locales/es.ftl")),
+    ("pt-BR", include_str!("../locales/pt-BR.ftl")),
+];
+
+type Bundle = FluentBundle<FluentResource>;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LocaleInfo {
+    pub locale: String,
+    pub supported: Vec<String>,
+}
+
+pub fn supported_locales() -> Vec<String> {
+    RESOURCES
+        .iter()
+        .map(|(locale, _)| locale.to_string())
+        .collect()
+}
+
+/// Pick the closest bundled locale: exact tag first, then same language, then English
+pub fn negotiate(requested: &str) -> &'static str {
+    let requested = requested.replace('_', "-");
+    let Ok(requested) = requested.parse::<LanguageIdentifier>() else {
+        return DEFAULT_LOCALE;
+    };
+    let available = || {
+        RESOURCES.iter().filter_map(|(locale, _)| {
+            locale
+                .parse::<LanguageIdentifier>()
+                .ok()
+                .map(|id| (*locale, id))
+        })
+    };
+    available()
+        .find(|(_, id)| *id == requested)
+        .or_else(|| available().find(|(_, id)| id.language == requested.language))
+        .map_or(DEFAULT_LOCALE, |(locale, _)| locale)
+}
+
+/// The OS user interface locale, or English when it can't be read
+pub fn detect_locale() -> String {
+    sys_locale::get_locale().unwrap_or_else(|| DEFAULT_LOCALE.to_string())
+}
+
+fn build_bundle(locale: &'static str) -> Bundle {
+    let source = RESOURCES
+        .iter()
+        .find(|(name, _)| *name == locale)
+        .map_or("", |(_, source)| source);
+    let id: LanguageIdentifier = locale.parse().unwrap_or_default();
+    let mut bundle = FluentBundle::new_concurrent(vec![id]);
+    // Unicode isolation marks show up as junk in tray menus and toasts
+    bundle.set_use_isolating(false);
+    let resource = FluentResource::try_new(source.to_string()).unwrap_or_else(|(res, _)| res);
+    let _ = bundle.add_resource(resource);
+    bundle
+}
+
+/// Formats messages in the active locale, falling back to English for missing ones
+pub struct Localizer {
+    locale: RwLock<&'static str>,
+    bundle: RwLock<Bundle>,
+    fallback: Bundle,
+}
+
+impl Localizer {
+    /// An empty locale follows the OS setting
+    pub fn new(locale: &str) -> Self {
+        let locale = resolve(locale);
+        Self {
+            locale: RwLock::new(locale),
+            bundle: RwLock::new(build_bundle(locale)),
+            fallback: build_bundle(DEFAULT_LOCALE),
+        }
+    }
+
+    pub fn locale(&self) -> &'static str {
+        *self.locale.read().unwrap()
+    }
+
+    pub fn info(&self) -> LocaleInfo {
+        LocaleInfo {
+            locale: self.locale().to_string(),
+            supported: supported_locales(),
+        }
+    }
+
+    /// Switch languages, returning the bundled locale actually used
+    pub fn set_locale(&self, locale: &str) -> &'static str {
+        let locale = resolve(locale);
+        *self.bundle.write().unwrap() = build_bundle(locale);
+        *self.locale.write().unwrap() = locale;
+        locale
+    }
+
+    pub fn text(&self, id: &str) -> String {
+        self.format(id, &[])
+    }
+
+    /// Format a message; numeric-looking arguments take part in plural selection
+    pub fn format(&self, id: &str, args: &[(&str, &str)]) -> String {
+        let mut fluent_args = FluentArgs::new();
+        for (name, value) in args {
+            fluent_args.set(*name, FluentValue::try_number(value));
+        }
+        let bundle = self.bundle.read().unwrap();
+        format_in(&bundle, id, &fluent_args)
+            .or_else(|| format_in(&self.fallback, id, &fluent_args))
+            .unwrap_or_else(|| id.to_string())
+    }
+
+    pub fn format_email_count(&self, count: u32) -> String {
+        self.format("email-count", &[("count", &count.to_string())])
+    }
+}
+
+fn resolve(locale: &str) -> &'static str {
+    if locale.trim().is_empty() {
+        negotiate(&detect_locale())
+    } else {
+        negotiate(locale)
+    }
+}
+
+fn format_in(bundle: &Bundle, id: &str, args: &FluentArgs) -> Option<String> {
+    let pattern = bundle.get_message(id)?.value()?;
+    let mut errors = Vec::new();
+    Some(
+        bundle
+            .format_pattern(pattern, Some(args), &mut errors)
+            .into_owned(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_negotiate() {
+        assert_eq!(negotiate("pt_BR"), "pt-BR");
+        assert_eq!(negotiate("es-MX"), "es");
+        assert_eq!(negotiate("fr-FR"), DEFAULT_LOCALE);
+        assert_eq!(negotiate("not a locale"), DEFAULT_LOCALE);
+    }
+
+    #[test]
+    fn test_plural_formatting() {
+        let localizer = Localizer::new("en-US");
+        assert_eq!(localizer.format_email_count(0), "No new emails");
+        assert_eq!(localizer.format_email_count(1), "1 new email");
+        assert_eq!(localizer.format_email_count(5), "5 new emails");
+
+        assert_eq!(localizer.set_locale("pt-BR"), "pt-BR");
+        assert_eq!(localizer.format_email_count(3), "3 e-mails novos");
+        assert_eq!(
+            localizer.format("notification-update-body", &[("version", "1.2.0")]),
+            "SERINA 1.2.0 está pronto para instalar"
+        );
+        assert_eq!(localizer.text("no-such-message"), "no-such-message");
+    }
+
+    #[test]
+    fn test_every_locale_has_every_message() {
+        let english_ids: Vec<&str> = RESOURCES[0]
+            .1
+            .lines()
+            .filter(|line| !line.starts_with([' ', '#']))
+            .filter_map(|line| line.split_once(" =").map(|(id, _)| id))
+            .collect();
+        assert!(english_ids.contains(&"email-count"));
+        for (locale, source) in RESOURCES {
+            assert!(
+                FluentResource::try_new(source.to_string()).is_ok(),
+                "{}",
+                locale
+            );
+            let bundle = build_bundle(locale);
+            for id in &english_ids {
+                assert!(bundle.has_message(id), "{} is missing {}", locale, id);
+            }
+        }
+    }
+}
@@ -0,0 +1,164 @@
+// SERINA Inbox Snapshot
+// Diffs each sync against the last one so the UI can be patched instead of refetched
+
+use crate::utils::EmailData;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Payload of `email-removed`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RemovedEmail {
+    pub id: String,
+    pub account: String,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct InboxDiff {
+    pub added: Vec<EmailData>,
+    pub updated: Vec<EmailData>,
+    pub removed: Vec<RemovedEmail>,
+}
+
+impl InboxDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.updated.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// The emails the UI was last told about, by id
+#[derive(Default)]
+pub struct InboxSnapshot {
+    emails: Mutex<HashMap<String, EmailData>>,
+}
+
+impl InboxSnapshot {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Emails in the inbox as of the last sync
+    pub fn email_count(&self) -> usize {
+        self.emails.lock().unwrap().len()
+    }
+
+    /// Unread emails as of the last sync, not counting muted conversations
+    pub fn unread_count(&self) -> u32 {
+        let emails = self.emails.lock().unwrap();
+        emails
+            .values()
+            .filter(|email| email.is_unread && !email.muted)
+            .count() as u32
+    }
+
+    /// Mark a conversation muted or not without waiting for the next sync; returns the emails
+    /// that changed
+    pub fn set_muted(&self, conversation_id: &str, muted: bool) -> Vec<EmailData> {
+        let mut emails = self.emails.lock().unwrap();
+        let mut changed: Vec<EmailData> = emails
+            .values_mut()
+            .filter(|email| email.conversation_id == conversation_id && email.muted != muted)
+            .map(|email| {
+                email.muted = muted;
+                email.clone()
+            })
+            .collect();
+        changed.sort_by(|a, b| a.id.cmp(&b.id));
+        changed
+    }
+
+    pub fn get(&self, email_id: &str) -> Option<EmailData> {
+        self.emails.lock().unwrap().get(email_id).cloned()
+    }
+
+    /// Record who claimed an email without waiting for the next sync; returns it if it changed
+    pub fn set_claim(&self, email_id: &str, claimed_by: &str) -> Option<EmailData> {
+        let mut emails = self.emails.lock().unwrap();
+        let email = emails.get_mut(email_id)?;
+        if email.claimed_by == claimed_by {
+            return None;
+        }
+        email.claimed_by = claimed_by.to_string();
+        Some(email.clone())
+    }
+
+    /// Replace the snapshot for `synced_accounts` with `emails`. Emails from accounts that
+    /// weren't synced this round (backing off, say) are left alone rather than reported removed.
+    pub fn apply(&self, synced_accounts: &[String], emails: &[EmailData]) -> InboxDiff {
+        let mut snapshot = self.emails.lock().unwrap();
+        let mut diff = InboxDiff::default();
+        for email in emails {
+            match snapshot.get(&email.id) {
+                None => diff.added.push(email.clone()),
+                Some(previous) if previous != email => diff.updated.push(email.clone()),
+                Some(_) => {}
+            }
+        }
+
+        let current: std::collections::HashSet<&str> =
+            emails.iter().map(|email| email.id.as_str()).collect();
+        snapshot.retain(|id, email| {
+            let keep = current.contains(id.as_str()) || !synced_accounts.contains(&email.account);
+            if !keep {
+                diff.removed.push(RemovedEmail {
+                    id: id.clone(),
+                    account: email.account.clone(),
+                });
+            }
+            keep
+        });
+        for email in emails {
+            snapshot.insert(email.id.clone(), email.clone());
+        }
+        diff.removed.sort_by(|a, b| a.id.cmp(&b.id));
+        diff
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn email(id: &str, account: &str, subject: &str) -> EmailData {
+        EmailData {
+            subject: subject.to_string(),
+            sender: "Jane".to_string(),
+            sender_email: "jane@example.com".to_string(),
+            account: account.to_string(),
+            ..EmailData::sample(id)
+        }
+    }
+
+    #[test]
+    fn test_apply_diff() {
+        let snapshot = InboxSnapshot::new();
+        let both = ["work".to_string(), "home".to_string()];
+        let first = snapshot.apply(&both, &[email("1", "work", "A"), email("2", "home", "B")]);
+        assert_eq!(first.added.len(), 2);
+
+        // "home" is backing off: its email stays; "work" changed one and lost nothing else
+        let work_only = ["work".to_string()];
+        let diff = snapshot.apply(&work_only, &[email("1", "work", "A (edited)")]);
+        assert_eq!(diff.updated.len(), 1);
+        assert!(diff.added.is_empty() && diff.removed.is_empty());
+
+        let diff = snapshot.apply(&both, &[email("3", "work", "C")]);
+        assert_eq!(diff.added[0].id, "3");
+        let removed: Vec<&str> = diff.removed.iter().map(|r| r.id.as_str()).collect();
+        assert_eq!(removed, ["1", "2"]);
+        assert!(snapshot.apply(&both, &[email("3", "work", "C")]).is_empty());
+        assert_eq!(snapshot.unread_count(), 1);
+    }
+
+    #[test]
+    fn test_set_muted() {
+        let snapshot = InboxSnapshot::new();
+        let mut storm = email("1", "", "RE: Offsite");
+        storm.conversation_id = "storm".to_string();
+        snapshot.apply(&[String::new()], &[storm, email("2", "", "Lunch")]);
+        assert_eq!(snapshot.unread_count(), 2);
+        assert_eq!(snapshot.set_muted("storm", true)[0].id, "1");
+        assert!(snapshot.set_muted("storm", true).is_empty());
+        assert_eq!(snapshot.unread_count(), 1);
+    }
+}
@@ -0,0 +1,257 @@
+// SERINA Intake
+// Files and text shared from other apps, turned into pre-filled compose drafts
+
+use crate::outbox::{self, RejectedFile};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Loopback port the running instance listens on for shares launched as a second process
+pub const HANDOFF_PORT: u16 = 47613;
+/// Command-line flags the OS share entries launch SERINA with
+pub const SHARE_FLAG: &str = "--share";
+pub const SHARE_TEXT_FLAG: &str = "--share-text";
+/// Subjects made from shared text are cut to this many characters
+const SUBJECT_LEN: usize = 60;
+
+/// Whatever another app handed over
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ShareRequest {
+    pub text: String,
+    pub files: Vec<PathBuf>,
+}
+
+impl ShareRequest {
+    pub fn is_empty(&self) -> bool {
+        self.text.trim().is_empty() && self.files.is_empty()
+    }
+}
+
+/// A share request from `--share <path>...` and `--share-text <text>` arguments; `None` for an
+/// ordinary launch. `--share` takes every path up to the next flag, as Linux passes them all at
+/// once.
+pub fn from_args(args: &[String]) -> Option<ShareRequest> {
+    let mut request = ShareRequest::default();
+    let mut args = args.iter().peekable();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            SHARE_FLAG => {
+                while let Some(path) = args.next_if(|next| !next.starts_with("--")) {
+                    request.files.push(PathBuf::from(path));
+                }
+            }
+            SHARE_TEXT_FLAG => {
+                if let Some(text) = args.next() {
+                    if !request.text.is_empty() {
+                        request.text.push('\n');
+                    }
+                    request.text.push_str(text);
+                }
+            }
+            _ => {}
+        }
+    }
+    (!request.is_empty()).then_some(request)
+}
+
+/// The share this process was launched with, if any
+pub fn from_process() -> Option<ShareRequest> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    from_args(&args)
+}
+
+/// A new message waiting in the compose window
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ComposeDraft {
+    pub id: u64,
+    pub subject: String,
+    pub body: String,
+    pub attachments: Vec<PathBuf>,
+    /// Shared files that can't be attached
+    pub rejected: Vec<RejectedFile>,
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+/// Screenshots tools name files "Screenshot 2024-05-14 091200.png" or "Screen Shot …"
+fn is_screenshot(path: &Path) -> bool {
+    let name = file_name(path).to_lowercase().replace(' ', "");
+    name.starts_with("screenshot") || name.starts_with("screencapture")
+}
+
+/// Pre-fill a draft: the subject comes from the shared files or the first line of text, files
+/// that fail the outbox checks are listed rather than attached
+pub fn draft(request: &ShareRequest) -> ComposeDraft {
+    let mut attachments = Vec::new();
+    let mut rejected = Vec::new();
+    let mut total = 0;
+    for path in &request.files {
+        match outbox::validate(path, total) {
+            Ok(size) => {
+                total += size;
+                attachments.push(path.clone());
+            }
+            Err(reason) => rejected.push(RejectedFile {
+                name: file_name(path),
+                reason,
+            }),
+        }
+    }
+
+    let first_line = request
+        .text
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty());
+    let subject = match attachments.as_slice() {
+        [] => first_line
+            .map(|line| line.chars().take(SUBJECT_LEN).collect())
+            .unwrap_or_default(),
+        files if files.iter().all(|path| is_screenshot(path)) => "Screenshot".to_string(),
+        [file] => file_name(file),
+        files => format!("{} files", files.len()),
+    };
+    ComposeDraft {
+        id: 0,
+        subject,
+        body: request.text.trim().to_string(),
+        attachments,
+        rejected,
+    }
+}
+
+/// Drafts opened from shares, until they're sent or discarded
+#[derive(Default)]
+pub struct IntakeQueue {
+    drafts: Mutex<(u64, HashMap<u64, ComposeDraft>)>,
+}
+
+impl IntakeQueue {
+    /// Keep a draft and return its id
+    pub fn add(&self, mut draft: ComposeDraft) -> u64 {
+        let mut drafts = self.drafts.lock().unwrap();
+        drafts.0 += 1;
+        draft.id = drafts.0;
+        drafts.1.insert(draft.id, draft);
+        drafts.0
+    }
+
+    pub fn get(&self, id: u64) -> Option<ComposeDraft> {
+        self.drafts.lock().unwrap().1.get(&id).cloned()
+    }
+
+    pub fn remove(&self, id: u64) -> Option<ComposeDraft> {
+        self.drafts.lock().unwrap().1.remove(&id)
+    }
+}
+
+/// Become the instance that receives shares. Fails when another instance already listens.
+pub fn listen(port: u16) -> std::io::Result<TcpListener> {
+    TcpListener::bind((Ipv4Addr::LOCALHOST, port))
+}
+
+/// Pass a share to the instance that is already running
+pub fn hand_off(port: u16, request: &ShareRequest) -> std::io::Result<()> {
+    let mut stream =
+        TcpStream::connect_timeout(&(Ipv4Addr::LOCALHOST, port).into(), Duration::from_secs(2))?;
+    let line = serde_json::to_string(request)?;
+    stream.write_all(line.as_bytes())?;
+    stream.write_all(b"\n")
+}
+
+/// Read one handed-off share
+pub fn receive(stream: TcpStream) -> Result<ShareRequest, String> {
+    stream
+        .set_read_timeout(Some(Duration::from_secs(2)))
+        .map_err(|e| e.to_string())?;
+    let mut line = String::new();
+    BufReader::new(stream)
+        .read_line(&mut line)
+        .map_err(|e| e.to_string())?;
+    serde_json::from_str(&line).map_err(|e| format!("Invalid share request: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn test_from_args() {
+        assert_eq!(from_args(&args(&["--minimized"])), None);
+        assert_eq!(
+            from_args(&args(&["--share", "/tmp/a.png", "/tmp/b.png", "--mock"]))
+                .unwrap()
+                .files
+                .len(),
+            2
+        );
+        assert_eq!(
+            from_args(&args(&[
+                "--share",
+                "C:\\Users\\dana\\report.pdf",
+                "--share-text",
+                "See page 3"
+            ])),
+            Some(ShareRequest {
+                text: "See page 3".to_string(),
+                files: vec![PathBuf::from("C:\\Users\\dana\\report.pdf")],
+            })
+        );
+    }
+
+    #[test]
+    fn test_draft() {
+        let text = ShareRequest {
+            text: "\n  Notes from the offsite  \nAction items follow".to_string(),
+            files: Vec::new(),
+        };
+        assert_eq!(draft(&text).subject, "Notes from the offsite");
+
+        let dir = std::env::temp_dir().join(format!("serina-intake-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let screenshot = dir.join("Screenshot 2024-05-14 091200.png");
+        std::fs::write(&screenshot, b"png").unwrap();
+        let installer = dir.join("setup.exe");
+        std::fs::write(&installer, b"MZ").unwrap();
+        let shared = draft(&ShareRequest {
+            text: String::new(),
+            files: vec![screenshot.clone(), installer],
+        });
+        assert_eq!(shared.subject, "Screenshot");
+        assert_eq!(shared.attachments, vec![screenshot]);
+        assert_eq!(shared.rejected[0].name, "setup.exe");
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let queue = IntakeQueue::default();
+        let id = queue.add(shared.clone());
+        assert_eq!(queue.get(id).unwrap().subject, "Screenshot");
+        assert!(queue.remove(id).is_some());
+        assert!(queue.get(id).is_none());
+    }
+
+    #[test]
+    fn test_hand_off() {
+        let listener = listen(0).unwrap();
+        let port = listener.local_addr().unwrap().port();
+        assert!(listen(port).is_err());
+        let request = ShareRequest {
+            text: "Shared text".to_string(),
+            files: vec![PathBuf::from("/tmp/a.png")],
+        };
+        hand_off(port, &request).unwrap();
+        let (stream, _) = listener.accept().unwrap();
+        assert_eq!(receive(stream).unwrap(), request);
+    }
+}
@@ -1,69 +1,435 @@
 // SERINA Tauri Library
 // Additional utilities and shared code
 
+pub mod accounts;
+pub mod actions;
+pub mod activity;
+pub mod analytics;
+pub mod applock;
+pub mod approvals;
+pub mod attachments;
+pub mod audit;
+pub mod autoreply;
+pub mod backend;
+pub mod budget;
+pub mod cache;
+pub mod calendar;
+pub mod capabilities;
+pub mod clipboard;
+pub mod clock;
+pub mod compose;
+pub mod config;
+pub mod confirm;
+pub mod dates;
+pub mod deadline;
+pub mod diagnostics;
+pub mod encryption;
+pub mod extract;
+pub mod feeds;
+pub mod flags;
+pub mod focus;
+pub mod guardrails;
+pub mod http;
+pub mod i18n;
+pub mod inbox;
+pub mod intake;
+pub mod links;
+pub mod llm;
+pub mod logging;
+pub mod mailboxes;
+pub mod mailview;
+pub mod mock;
+pub mod mute;
+pub mod network;
+pub mod notification;
+pub mod otp;
+pub mod outbox;
+pub mod parsing;
+pub mod payments;
+pub mod pins;
+pub mod platform;
+pub mod power;
+pub mod privacy;
+pub mod profiles;
+pub mod purge;
+pub mod ratelimit;
+pub mod recipients;
+pub mod reader;
+pub mod redact;
+pub mod reminder;
+pub mod retention;
+pub mod retry;
+pub mod rules;
+pub mod scripts;
+pub mod settings;
+pub mod shutdown;
+pub mod snooze;
+pub mod spam;
+pub mod streaks;
+pub mod teamsync;
+pub mod telemetry;
+pub mod templates;
+pub mod theme;
+pub mod tokens;
+pub mod triage;
+pub mod upcoming;
+pub mod update;
+pub mod webview;
+pub mod window;
+pub mod windows;
+
 pub mod utils {
     use serde::{Deserialize, Serialize};
+    use std::collections::HashMap;
 
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
     pub struct EmailData {
         pub id: String,
+        /// Internet Message-ID header, shared by duplicate deliveries
+        #[serde(default)]
+        pub message_id: String,
         pub subject: String,
         pub sender: String,
         pub sender_email: String,
         pub body: String,
         pub received_time: String,
         pub is_unread: bool,
+        /// Address of the account it arrived in; empty for the default inbox
+        #[serde(default)]
+        pub account: String,
+        /// Short plain-text preview of `body`, filled in by `parsing::add_snippets`
+        #[serde(default)]
+        pub snippet: String,
+        /// Deadline found in the body, RFC 3339; filled in by `deadline::add_deadlines`
+        #[serde(default)]
+        pub deadline: Option<String>,
+        /// Outlook's ConversationID, shared by every message in a thread
+        #[serde(default)]
+        pub conversation_id: String,
+        /// Part of a muted conversation; filled in by `MuteList::apply`
+        #[serde(default)]
+        pub muted: bool,
+        /// Teammate handling it, for mail in a shared mailbox; empty when nobody has claimed it
+        #[serde(default)]
+        pub claimed_by: String,
     }
 
-    #[derive(Debug, Serialize, Deserialize)]
+    #[cfg(test)]
+    impl EmailData {
+        /// An unread email with only an id, for tests to fill in with `..EmailData::sample(id)`
+        pub fn sample(id: &str) -> Self {
+            Self {
+                id: id.to_string(),
+                is_unread: true,
+                ..Default::default()
+            }
+        }
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
     pub struct NotificationData {
         pub title: String,
         pub body: String,
         pub count: u32,
     }
 
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+    #[serde(default)]
     pub struct AppConfig {
+        /// Block every outbound call except the local backend and local LLMs
+        pub privacy_mode: bool,
+        /// Rules, scripts, auto-replies, newsletter digests and scheduled retention only log
+        /// what they would do to the activity feed, for trying out new automations safely
+        pub observe_only: bool,
         pub llm: LLMConfig,
         pub email: EmailConfig,
         pub notifications: NotificationConfig,
         pub ui: UIConfig,
+        pub auto_reply: crate::autoreply::AutoReplyConfig,
+        /// Which automated replies go out without the user approving them first
+        pub approvals: crate::approvals::ApprovalConfig,
+        pub tracking: TrackingConfig,
+        pub spam: crate::spam::SpamConfig,
+        pub retention: crate::retention::RetentionConfig,
+        pub sync: crate::settings::SyncConfig,
+        pub updates: crate::update::UpdateConfig,
+        pub flags: crate::flags::FlagsConfig,
+        pub triage: crate::triage::TriageConfig,
+        /// Proxy and extra root certificates for outbound HTTP
+        pub network: crate::http::NetworkConfig,
+        pub reminders: crate::reminder::ReminderConfig,
+        pub scheduling: crate::calendar::SchedulingConfig,
+        /// Opt-in anonymous usage counts
+        pub telemetry: crate::telemetry::TelemetryConfig,
+        pub newsletters: crate::reader::NewsletterConfig,
+        pub verification_codes: crate::otp::OtpConfig,
+        pub payments: crate::payments::PaymentConfig,
+        /// Atom feeds of selected mail for RSS readers
+        pub feeds: crate::feeds::FeedConfig,
+        /// Rules and templates shared by a team
+        pub team_sync: crate::teamsync::TeamSyncConfig,
+        /// At-rest protection of local data
+        pub security: crate::encryption::SecurityConfig,
+        /// PIN or biometric gate before windows show mail
+        pub app_lock: crate::applock::AppLockConfig,
+        /// Checks generated replies must pass before they're sent without a second look
+        pub guardrails: crate::guardrails::GuardrailConfig,
     }
 
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    #[serde(default)]
     pub struct LLMConfig {
         pub provider: String,
         pub api_key: String,
         pub model: String,
+        /// Providers tried in order when the primary one fails
+        pub fallbacks: Vec<ProviderConfig>,
+        /// Per-feature model overrides for the primary provider (e.g. "reply" => "gpt-4o")
+        pub models: HashMap<String, String>,
+        pub limits: LLMLimits,
+        pub redaction: RedactionConfig,
+    }
+
+    impl Default for LLMConfig {
+        fn default() -> Self {
+            Self {
+                provider: "openai".to_string(),
+                api_key: String::new(),
+                model: "gpt-3.5-turbo".to_string(),
+                fallbacks: Vec::new(),
+                models: HashMap::new(),
+                limits: LLMLimits::default(),
+                redaction: RedactionConfig::default(),
+            }
+        }
+    }
+
+    impl LLMConfig {
+        /// Every configured provider, usable or not
+        pub fn providers(&self) -> Vec<ProviderConfig> {
+            let primary = ProviderConfig {
+                provider: self.provider.clone(),
+                api_key: self.api_key.clone(),
+                model: self.model.clone(),
+                base_url: None,
+            };
+            std::iter::once(primary)
+                .chain(self.fallbacks.iter().cloned())
+                .collect()
+        }
+
+        /// Primary provider followed by the configured fallbacks
+        pub fn provider_chain(&self) -> Vec<ProviderConfig> {
+            self.providers().into_iter().filter(|p| p.is_usable()).collect()
+        }
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    #[serde(default)]
+    pub struct LLMLimits {
+        /// Maximum LLM requests per minute (0 = unlimited)
+        pub requests_per_minute: u32,
+        /// Monthly spend cap in USD (0 = no cap)
+        pub monthly_budget_usd: f64,
+        /// Estimated price used to turn token counts into spend
+        pub cost_per_1k_tokens: f64,
+    }
+
+    impl Default for LLMLimits {
+        fn default() -> Self {
+            Self {
+                requests_per_minute: 0,
+                monthly_budget_usd: 0.0,
+                cost_per_1k_tokens: 0.002,
+            }
+        }
+    }
+
+    /// What to mask in email content before it reaches a cloud LLM
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    #[serde(default)]
+    pub struct RedactionConfig {
+        pub enabled: bool,
+        pub emails: bool,
+        pub phones: bool,
+        pub account_numbers: bool,
+        /// Names to mask, matched case-insensitively as whole words
+        pub names: Vec<String>,
+        /// Extra regular expressions to mask
+        pub patterns: Vec<String>,
+    }
+
+    impl Default for RedactionConfig {
+        fn default() -> Self {
+            Self {
+                enabled: false,
+                emails: true,
+                phones: true,
+                account_numbers: true,
+                names: Vec::new(),
+                patterns: Vec::new(),
+            }
+        }
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct ProviderConfig {
+        pub provider: String,
+        #[serde(default)]
+        pub api_key: String,
+        #[serde(default)]
+        pub model: String,
+        /// Overrides the provider's default API base URL
+        #[serde(default)]
+        pub base_url: Option<String>,
+    }
+
+    impl ProviderConfig {
+        /// Local providers don't need an API key
+        pub fn is_local(&self) -> bool {
+            self.provider == "ollama"
+        }
+
+        pub fn is_usable(&self) -> bool {
+            self.is_local() || !self.api_key.is_empty()
+        }
     }
 
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    #[serde(default)]
     pub struct EmailConfig {
         pub check_interval_minutes: u32,
         pub max_emails_per_check: u32,
+        /// Name used for {{my_name}} in templates
+        pub display_name: String,
+        /// Hide duplicate deliveries of the same message in `get_emails`
+        pub collapse_duplicates: bool,
+        /// Slower polling and deferred background work on battery saver or metered networks
+        pub power: crate::power::PowerPolicy,
+        /// Outlook account addresses to sync; empty syncs the profile's default inbox
+        pub accounts: Vec<String>,
+        /// How many accounts sync at once
+        pub sync_workers: usize,
+        /// Team mailboxes synced alongside `accounts`, where mail can be claimed
+        pub shared_mailboxes: Vec<crate::mailboxes::SharedMailbox>,
+        /// Warn before sending to addresses that aren't in the Outlook contacts
+        pub verify_recipients: bool,
+        /// The organization's domains, for spotting outsiders on a reply all; empty uses the
+        /// domains of `accounts`
+        pub internal_domains: Vec<String>,
+    }
+
+    impl Default for EmailConfig {
+        fn default() -> Self {
+            Self {
+                check_interval_minutes: 15,
+                max_emails_per_check: 20,
+                display_name: String::new(),
+                collapse_duplicates: true,
+                power: crate::power::PowerPolicy::default(),
+                accounts: Vec::new(),
+                sync_workers: 3,
+                shared_mailboxes: Vec::new(),
+                verify_recipients: true,
+                internal_domains: Vec::new(),
+            }
+        }
+    }
+
+    /// Opt-in delivery and read receipts on sent replies
+    #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+    #[serde(default)]
+    pub struct TrackingConfig {
+        /// Ask recipients for a read receipt; their mail client shows them the request
+        pub read_receipts: bool,
+        pub delivery_receipts: bool,
     }
 
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    #[serde(default)]
     pub struct NotificationConfig {
         pub quiet_hours_start: String,
         pub quiet_hours_end: String,
         pub show_desktop_notifications: bool,
         pub notification_position: String,
+        /// Addresses (or `@domain`) whose mail should never go unnoticed
+        pub vip_senders: Vec<String>,
+        /// Keep every notification in a list until dismissed, instead of only transient toasts
+        pub persistent_list: bool,
+        /// Hide popups and notification content while presenting or sharing the screen
+        pub hide_while_presenting: bool,
+        /// Length of a focus session started from the tray
+        pub focus_minutes: u32,
+        /// Named overrides of the settings above
+        pub profiles: Vec<crate::profiles::NotificationProfile>,
+        /// Name of the profile in effect; empty uses the settings above as they are
+        pub active_profile: String,
     }
 
-    #[derive(Debug, Serialize, Deserialize)]
+    impl Default for NotificationConfig {
+        fn default() -> Self {
+            Self {
+                quiet_hours_start: "22:00".to_string(),
+                quiet_hours_end: "08:00".to_string(),
+                show_desktop_notifications: true,
+                notification_position: "bottom-right".to_string(),
+                vip_senders: Vec::new(),
+                persistent_list: false,
+                hide_while_presenting: true,
+                focus_minutes: 25,
+                profiles: Vec::new(),
+                active_profile: String::new(),
+            }
+        }
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    #[serde(default)]
     pub struct UIConfig {
-        pub dark_mode: bool,
+        pub theme: crate::theme::ThemeConfig,
         pub window_width: f64,
         pub window_height: f64,
+        pub high_contrast: bool,
+        /// macOS: live in the menu bar with no dock icon (applies on restart)
+        pub menu_bar_only: bool,
+        /// Language for tray, notification and error text; empty follows the OS
+        pub locale: String,
+        /// Translucent backdrop behind the main window, where the OS has one
+        pub window_effect: crate::window::WindowEffect,
+        /// Show the main window as a small always-on-top unread ticker
+        pub compact_mode: bool,
+        /// Where the mini inbox was last dragged to; `None` opens it bottom-right
+        pub mini_inbox_position: Option<crate::window::WindowPosition>,
+        /// Opt-in inbox-zero streaks and daily triage goal
+        pub gamification: bool,
+        /// Emails to deal with in a day before the goal counts as met
+        pub daily_triage_goal: u32,
+    }
+
+    impl Default for UIConfig {
+        fn default() -> Self {
+            Self {
+                theme: crate::theme::ThemeConfig::default(),
+                window_width: 1200.0,
+                window_height: 800.0,
+                high_contrast: false,
+                menu_bar_only: false,
+                locale: String::new(),
+                window_effect: crate::window::WindowEffect::None,
+                compact_mode: false,
+                mini_inbox_position: None,
+                gamification: false,
+                daily_triage_goal: 20,
+            }
+        }
     }
 
     /// Check if the current time is within quiet hours
-    pub fn is_quiet_hours(start: &str, end: &str) -> bool {
-        use chrono::{Local, NaiveTime};
+    pub fn is_quiet_hours(clock: &dyn crate::clock::Clock, start: &str, end: &str) -> bool {
+        use chrono::NaiveTime;
+
+        let now = clock.local_time();
 
-        let now = Local::now().time();
-        
         if let (Ok(start_time), Ok(end_time)) = (
             NaiveTime::parse_from_str(start, "%H:%M"),
             NaiveTime::parse_from_str(end, "%H:%M"),
@@ -80,13 +446,9 @@ pub mod utils {
         }
     }
 
-    /// Format email count for display
+    /// Format email count for display in English; see `i18n::Localizer` for the user's language
     pub fn format_email_count(count: u32) -> String {
-        match count {
-            0 => "No new emails".to_string(),
-            1 => "1 new email".to_string(),
-            n => format!("{} new emails", n),
-        }
+        crate::i18n::Localizer::new(crate::i18n::DEFAULT_LOCALE).format_email_count(count)
     }
 
     /// Validate API key format (basic check)
@@ -115,10 +477,15 @@ mod tests {
     use super::utils::*;
 
     #[test]
-    fn test_quiet_hours_same_day() {
-        // This would need proper mocking for real tests
-        // Just basic structure for now
-        assert!(!is_quiet_hours("25:00", "26:00")); // Invalid times
+    fn test_quiet_hours() {
+        let clock = crate::clock::MockClock::at("2024-05-14T23:30:00-03:00");
+        assert!(is_quiet_hours(&clock, "22:00", "08:00"));
+        assert!(!is_quiet_hours(&clock, "09:00", "17:00"));
+
+        clock.set("2024-05-15T12:00:00-03:00");
+        assert!(!is_quiet_hours(&clock, "22:00", "08:00"));
+        assert!(is_quiet_hours(&clock, "09:00", "17:00"));
+        assert!(!is_quiet_hours(&clock, "25:00", "26:00")); // Invalid times
     }
 
     #[test]
@@ -128,6 +495,29 @@ mod tests {
         assert_eq!(format_email_count(5), "5 new emails");
     }
 
+    #[test]
+    fn test_provider_chain() {
+        let config: LLMConfig = serde_json::from_str(
+            r#"{
+                "provider": "openai",
+                "api_key": "sk-primary",
+                "model": "gpt-4o-mini",
+                "fallbacks": [
+                    {"provider": "openrouter", "api_key": ""},
+                    {"provider": "ollama", "model": "llama3"}
+                ]
+            }"#,
+        )
+        .unwrap();
+        let chain: Vec<String> = config
+            .provider_chain()
+            .into_iter()
+            .map(|p| p.provider)
+            .collect();
+        // The openrouter entry has no key and is skipped
+        assert_eq!(chain, vec!["openai", "ollama"]);
+    }
+
     #[test]
     fn test_api_key_validation() {
         assert!(is_valid_api_key("sk-1234567890", "openai"));
@@ -0,0 +1,220 @@
+// SERINA Link Safety
+// Checks a link before it leaves for the browser: expands shorteners and flags destinations
+// that look like phishing, so the user confirms them first
+
+use crate::{privacy, webview};
+use serde::Serialize;
+use std::time::Duration;
+use url::Url;
+
+/// Redirects followed when expanding a shortened link
+const MAX_HOPS: usize = 5;
+const RESOLVE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Hosts whose links only redirect somewhere else
+const SHORTENERS: &[&str] = &[
+    "bit.ly",
+    "buff.ly",
+    "cutt.ly",
+    "goo.gl",
+    "is.gd",
+    "lnkd.in",
+    "ow.ly",
+    "rb.gy",
+    "rebrand.ly",
+    "shorturl.at",
+    "t.co",
+    "t.ly",
+    "tiny.cc",
+    "tinyurl.com",
+];
+
+/// Endings that make link text a file name, not a site
+const FILE_EXTENSIONS: &[&str] = &[
+    "csv", "doc", "docx", "gif", "htm", "html", "jpeg", "jpg", "pdf", "png", "ppt", "pptx", "txt",
+    "xls", "xlsx",
+];
+
+/// Why a link deserves a second look
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkRisk {
+    /// A shortened link whose destination couldn't be looked up
+    Unresolved,
+    /// A bare IP address instead of a name
+    IpAddress,
+    /// An internationalized name, which can imitate a familiar one (xn--pple-43d.com)
+    Punycode,
+    /// Plain http
+    Insecure,
+    /// A port other than the scheme's usual one
+    UnusualPort,
+    /// The link's text names a different site than it goes to
+    MismatchedText,
+}
+
+impl LinkRisk {
+    /// Fluent message explaining the risk in the confirmation prompt
+    pub fn message_id(self) -> &'static str {
+        match self {
+            LinkRisk::Unresolved => "link-risk-unresolved",
+            LinkRisk::IpAddress => "link-risk-ip-address",
+            LinkRisk::Punycode => "link-risk-punycode",
+            LinkRisk::Insecure => "link-risk-insecure",
+            LinkRisk::UnusualPort => "link-risk-unusual-port",
+            LinkRisk::MismatchedText => "link-risk-mismatched-text",
+        }
+    }
+}
+
+pub fn is_shortener(url: &Url) -> bool {
+    url.host_str()
+        .map(|host| host.trim_end_matches('.').to_ascii_lowercase())
+        .is_some_and(|host| SHORTENERS.contains(&host.as_str()))
+}
+
+/// What's suspicious about `url`, given the text it was shown as (if any)
+pub fn assess(url: &Url, text: Option<&str>) -> Vec<LinkRisk> {
+    if url.scheme() == "mailto" {
+        return Vec::new();
+    }
+    let mut risks = Vec::new();
+    if is_shortener(url) {
+        risks.push(LinkRisk::Unresolved);
+    }
+    match url.host() {
+        Some(url::Host::Ipv4(_) | url::Host::Ipv6(_)) => risks.push(LinkRisk::IpAddress),
+        Some(url::Host::Domain(domain))
+            if domain.split('.').any(|label| label.starts_with("xn--")) =>
+        {
+            risks.push(LinkRisk::Punycode)
+        }
+        _ => {}
+    }
+    if url.scheme() == "http" {
+        risks.push(LinkRisk::Insecure);
+    }
+    if url.port().is_some() {
+        risks.push(LinkRisk::UnusualPort);
+    }
+    let shown = text.and_then(shown_host);
+    if let (Some(shown), Some(host)) = (shown, url.host_str()) {
+        if !same_site(&shown, &host.to_ascii_lowercase()) {
+            risks.push(LinkRisk::MismatchedText);
+        }
+    }
+    risks
+}
+
+/// The host a link's text spells out, when the text looks like an address ("www.bank.com/login")
+fn shown_host(text: &str) -> Option<String> {
+    let text = text.trim();
+    if text.is_empty() || text.contains(char::is_whitespace) {
+        return None;
+    }
+    let with_scheme = if text.contains("://") {
+        text.to_string()
+    } else {
+        format!("https://{}", text)
+    };
+    let url = Url::parse(&with_scheme).ok()?;
+    let host = url.host_str()?.trim_end_matches('.').to_ascii_lowercase();
+    // "Click here" has no dot, and "statement.pdf" is a file name rather than a site
+    let tld = host.rsplit('.').next()?;
+    (host.contains('.')
+        && tld.len() >= 2
+        && tld.chars().all(|c| c.is_ascii_alphabetic())
+        && !FILE_EXTENSIONS.contains(&tld))
+    .then_some(host)
+}
+
+/// Whether one host is the other or a subdomain of it (www.bank.com and bank.com)
+fn same_site(a: &str, b: &str) -> bool {
+    let a = a.trim_start_matches("www.");
+    let b = b.trim_end_matches('.').trim_start_matches("www.");
+    a == b || a.ends_with(&format!(".{}", b)) || b.ends_with(&format!(".{}", a))
+}
+
+/// Follow a shortened link's redirects to where it really goes, without opening it. `client`
+/// must not follow redirects itself: each hop is checked like any external link, so a redirect
+/// can't reach this machine, and privacy mode stops it before any request.
+pub async fn resolve(
+    client: &reqwest::Client,
+    url: Url,
+    privacy_mode: bool,
+) -> Result<Url, String> {
+    let mut current = url;
+    for _ in 0..MAX_HOPS {
+        if !is_shortener(&current) {
+            return Ok(current);
+        }
+        privacy::ensure_allowed(current.as_str(), privacy_mode)?;
+        let response = client
+            .head(current.clone())
+            .timeout(RESOLVE_TIMEOUT)
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+        if !response.status().is_redirection() {
+            return Ok(current);
+        }
+        let location = response
+            .headers()
+            .get(reqwest::header::LOCATION)
+            .and_then(|value| value.to_str().ok())
+            .ok_or("Redirect without a destination")?;
+        let next = current.join(location).map_err(|e| e.to_string())?;
+        current = webview::external_url(next.as_str())?;
+    }
+    Err("Too many redirects".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn risks(url: &str, text: Option<&str>) -> Vec<LinkRisk> {
+        assess(&Url::parse(url).unwrap(), text)
+    }
+
+    #[test]
+    fn test_assess() {
+        assert_eq!(risks("https://www.example.com/account", None), vec![]);
+        assert_eq!(
+            risks("https://bit.ly/3xYz", Some("Track your parcel")),
+            vec![LinkRisk::Unresolved]
+        );
+        assert_eq!(
+            risks("http://203.0.113.9:8080/login", None),
+            vec![
+                LinkRisk::IpAddress,
+                LinkRisk::Insecure,
+                LinkRisk::UnusualPort
+            ]
+        );
+        assert_eq!(
+            risks("https://xn--pple-43d.com/", None),
+            vec![LinkRisk::Punycode]
+        );
+        assert_eq!(risks("mailto:billing@example.com", Some("x.com")), vec![]);
+    }
+
+    #[test]
+    fn test_mismatched_text() {
+        let url = "https://login.bank-secure.example/";
+        assert_eq!(
+            risks(url, Some("www.mybank.com")),
+            vec![LinkRisk::MismatchedText]
+        );
+        assert_eq!(risks(url, Some("Sign in")), vec![]);
+        assert_eq!(risks(url, Some("statement.pdf")), vec![]);
+        assert_eq!(
+            risks("https://www.mybank.com/login", Some("https://mybank.com")),
+            vec![]
+        );
+        assert_eq!(
+            risks("https://mybank.com/login", Some("secure.mybank.com/login")),
+            vec![]
+        );
+    }
+}
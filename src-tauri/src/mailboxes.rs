@@ -0,0 +1,127 @@
+// SERINA Shared Mailboxes
+// Team mailboxes (support@, sales@) synced as accounts of their own, with claims on their mail
+
+use crate::accounts;
+use serde::{Deserialize, Serialize};
+
+/// A shared or delegated mailbox the Outlook profile can open
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SharedMailbox {
+    pub address: String,
+    /// Shown instead of the address, e.g. "Support"
+    pub name: String,
+}
+
+impl SharedMailbox {
+    pub fn label(&self) -> &str {
+        if self.name.trim().is_empty() {
+            &self.address
+        } else {
+            &self.name
+        }
+    }
+}
+
+/// Personal accounts followed by shared mailboxes, each address once. A shared mailbox
+/// doesn't replace the default inbox when no personal accounts are listed.
+pub fn sync_accounts(accounts: &[String], shared: &[SharedMailbox]) -> Vec<String> {
+    let mut all = accounts::configured_accounts(accounts);
+    for mailbox in shared {
+        let address = mailbox.address.trim().to_lowercase();
+        if !address.is_empty() && !all.contains(&address) {
+            all.push(address);
+        }
+    }
+    all
+}
+
+pub fn is_shared(shared: &[SharedMailbox], account: &str) -> bool {
+    shared
+        .iter()
+        .any(|mailbox| mailbox.address.trim().eq_ignore_ascii_case(account))
+}
+
+/// The name claims are made under, so teammates can tell who has an email
+pub fn claimant(display_name: &str) -> Result<String, String> {
+    let name = display_name.trim();
+    if name.is_empty() {
+        return Err("Set your name in Settings so teammates can see who claimed it".to_string());
+    }
+    Ok(name.to_string())
+}
+
+/// The backend's answer to a claim or release: who holds the email now, and whether the
+/// request changed that
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClaimState {
+    pub claimed_by: String,
+    pub changed: bool,
+}
+
+impl ClaimState {
+    /// An error unless `name` got what it asked for: holding the claim after claiming, or
+    /// nobody holding it after releasing
+    pub fn check(&self, name: &str, claiming: bool) -> Result<(), String> {
+        let held_by_other = !self.claimed_by.is_empty() && self.claimed_by != name;
+        if held_by_other {
+            let verb = if claiming {
+                "already claimed"
+            } else {
+                "claimed"
+            };
+            return Err(format!("This email is {} by {}", verb, self.claimed_by));
+        }
+        if claiming == self.claimed_by.is_empty() {
+            return Err("The mailbox didn't record the change".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sync_accounts() {
+        let shared = vec![
+            SharedMailbox {
+                address: " Support@Example.com".to_string(),
+                name: "Support".to_string(),
+            },
+            SharedMailbox {
+                address: "me@example.com".to_string(),
+                name: String::new(),
+            },
+        ];
+        assert_eq!(
+            sync_accounts(&[], &shared),
+            vec!["", "support@example.com", "me@example.com"]
+        );
+        assert_eq!(
+            sync_accounts(&["me@example.com".to_string()], &shared),
+            vec!["me@example.com", "support@example.com"]
+        );
+        assert!(is_shared(&shared, "support@example.com"));
+        assert!(!is_shared(&shared, ""));
+        assert_eq!(shared[1].label(), "me@example.com");
+    }
+
+    #[test]
+    fn test_claim_check() {
+        let state = |claimed_by: &str| ClaimState {
+            claimed_by: claimed_by.to_string(),
+            changed: true,
+        };
+        assert!(state("Ana").check("Ana", true).is_ok());
+        assert_eq!(
+            state("Ben").check("Ana", true).unwrap_err(),
+            "This email is already claimed by Ben"
+        );
+        assert!(state("").check("Ana", false).is_ok());
+        assert!(state("Ben").check("Ana", false).is_err());
+        assert!(state("").check("Ana", true).is_err());
+        assert!(claimant("  ").is_err());
+    }
+}
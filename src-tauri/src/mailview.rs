@@ -0,0 +1,262 @@
+// SERINA Mail View
+// Sanitized email documents for the `serina-mail://` protocol, rendered from the local cache
+
+use crate::attachments::AttachmentInfo;
+use crate::parsing;
+use crate::utils::EmailData;
+use serde::Serialize;
+use std::borrow::Cow;
+use std::collections::HashSet;
+
+pub const SCHEME: &str = "serina-mail";
+
+/// Links in a mail document point here (`/-/open?email=<id>&url=<link>`) so a click reaches the
+/// link checks instead of navigating the frame; `-` is never an email id on its own
+pub const LINK_PATH: &str = "/-/open";
+
+/// Nothing loads but inline styles and images embedded in the message itself: no scripts, no
+/// remote images (tracking pixels), no frames or forms
+pub const CSP: &str = "default-src 'none'; img-src data:; style-src 'unsafe-inline'; \
+                       font-src data:; form-action 'none'; frame-ancestors 'self'";
+
+/// Style properties that only affect layout and colour
+const STYLE_PROPERTIES: &[&str] = &[
+    "color",
+    "background-color",
+    "font-family",
+    "font-size",
+    "font-style",
+    "font-weight",
+    "line-height",
+    "text-align",
+    "text-decoration",
+    "vertical-align",
+    "width",
+    "max-width",
+    "height",
+    "margin",
+    "margin-top",
+    "margin-bottom",
+    "margin-left",
+    "margin-right",
+    "padding",
+    "padding-top",
+    "padding-bottom",
+    "padding-left",
+    "padding-right",
+    "border",
+    "border-collapse",
+    "border-color",
+    "border-style",
+    "border-width",
+    "white-space",
+];
+
+/// The email id in a protocol URL. Linux and macOS use `serina-mail://localhost/<id>`, Windows
+/// `https://serina-mail.localhost/<id>`; only the path matters.
+pub fn email_id(uri: &str) -> Option<String> {
+    let url = url::Url::parse(uri).ok()?;
+    let id = url.path().trim_matches('/');
+    let id = percent_decode(id)?;
+    (!id.is_empty() && !id.contains('/')).then_some(id)
+}
+
+/// The email and original link behind a click on `LINK_PATH`
+pub fn link_target(uri: &str) -> Option<(String, String)> {
+    let url = url::Url::parse(uri).ok()?;
+    if url.path() != LINK_PATH {
+        return None;
+    }
+    let mut email_id = None;
+    let mut link = None;
+    for (key, value) in url.query_pairs() {
+        match key.as_ref() {
+            "email" => email_id = Some(value.into_owned()),
+            "url" => link = Some(value.into_owned()),
+            _ => {}
+        }
+    }
+    Some((email_id.filter(|id| !id.is_empty())?, link?))
+}
+
+fn link_href(email_id: &str, link: &str) -> String {
+    let query = url::form_urlencoded::Serializer::new(String::new())
+        .append_pair("email", email_id)
+        .append_pair("url", link)
+        .finish();
+    format!("{}?{}", LINK_PATH, query)
+}
+
+fn percent_decode(value: &str) -> Option<String> {
+    let bytes: Vec<u8> = url::form_urlencoded::parse(format!("id={}", value).as_bytes())
+        .next()
+        .map(|(_, id)| id.into_owned().into_bytes())?;
+    String::from_utf8(bytes).ok()
+}
+
+/// Escape text for an HTML element or attribute
+pub fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Strip an HTML body down to formatting: scripts, event handlers, forms and remote content go
+pub fn sanitize_html(html: &str) -> String {
+    clean(html, None)
+}
+
+/// `sanitize_html`, optionally pointing links at `LINK_PATH` for the given email
+fn clean(html: &str, links_from: Option<&str>) -> String {
+    let links_from = links_from.map(str::to_string);
+    ammonia::Builder::default()
+        .add_generic_attributes(["style", "align", "valign", "bgcolor", "width", "height"])
+        .filter_style_properties(STYLE_PROPERTIES.iter().copied().collect::<HashSet<_>>())
+        .add_url_schemes(["data"])
+        .attribute_filter(move |element, attribute, value| {
+            // Embedded images stay; a data: link could be a whole page
+            if value.trim_start().to_ascii_lowercase().starts_with("data:")
+                && !(element == "img" && attribute == "src")
+            {
+                return None;
+            }
+            if let (Some(email_id), "a", "href") = (&links_from, element, attribute) {
+                return Some(Cow::Owned(link_href(email_id, value)));
+            }
+            Some(Cow::Borrowed(value))
+        })
+        .clean(html)
+        .to_string()
+}
+
+/// Sanitized markup for an email's body: its cached HTML if there is one, otherwise the text body
+pub fn body_html(email: &EmailData, html: Option<&str>) -> String {
+    body(email, html, false)
+}
+
+fn body(email: &EmailData, html: Option<&str>, route_links: bool) -> String {
+    let links_from = route_links.then_some(email.id.as_str());
+    match html.filter(|html| !html.trim().is_empty()) {
+        Some(html) => clean(html, links_from),
+        None if parsing::looks_like_html(&email.body) => clean(&email.body, links_from),
+        None => format!(
+            "<div style=\"white-space: pre-wrap\">{}</div>",
+            escape(&email.body)
+        ),
+    }
+}
+
+/// The full document for an email, with its links routed through `LINK_PATH`
+pub fn render(email: &EmailData, html: Option<&str>) -> String {
+    let content = body(email, html, true);
+    format!(
+        "<!DOCTYPE html><html><head><meta charset=\"utf-8\">\
+         <meta http-equiv=\"Content-Security-Policy\" content=\"{}\">\
+         <title>{}</title>\
+         <style>body {{ font-family: system-ui, sans-serif; font-size: 14px; margin: 0; \
+         overflow-wrap: anywhere; }} img {{ max-width: 100%; height: auto; }}</style>\
+         </head><body>{}</body></html>",
+        CSP,
+        escape(&email.subject),
+        content
+    )
+}
+
+/// What the print window lays out: headers, the attachment list and the sanitized body
+#[derive(Debug, Clone, Serialize)]
+pub struct PrintableEmail {
+    pub subject: String,
+    pub from: String,
+    pub received_time: String,
+    pub attachments: Vec<AttachmentInfo>,
+    pub body_html: String,
+}
+
+/// "Name <address>", or just whichever one the email has
+pub fn from_line(email: &EmailData) -> String {
+    if email.sender_email.is_empty() || email.sender == email.sender_email {
+        email.sender.clone()
+    } else {
+        format!("{} <{}>", email.sender, email.sender_email)
+    }
+}
+
+pub fn printable(
+    email: &EmailData,
+    html: Option<&str>,
+    attachments: Vec<AttachmentInfo>,
+) -> PrintableEmail {
+    PrintableEmail {
+        subject: email.subject.clone(),
+        from: from_line(email),
+        received_time: email.received_time.clone(),
+        attachments,
+        body_html: body_html(email, html),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sanitize_html() {
+        let html = r#"<div style="color: red; background: url(https://t.example/px)" onclick="steal()">
+            <script>alert(1)</script><p>Hi</p>
+            <img src="https://t.example/open.gif"><img src="data:image/png;base64,iVBORw0KGgo=">
+            <a href="data:text/html,<script>alert(1)</script>">x</a>
+            <form action="https://evil.example"><input name="password"></form></div>"#;
+        let clean = sanitize_html(html);
+        assert!(clean.contains("<p>Hi</p>"));
+        assert!(clean.contains(r#"style="color:red""#), "{}", clean);
+        assert!(clean.contains("data:image/png"));
+        for gone in ["script", "onclick", "url(", "data:text", "<form", "<input"] {
+            assert!(!clean.contains(gone), "{} in {}", gone, clean);
+        }
+    }
+
+    #[test]
+    fn test_render() {
+        let email: EmailData = serde_json::from_value(serde_json::json!({
+            "id": "abc",
+            "subject": "Budget <draft>",
+            "sender": "Dana",
+            "sender_email": "dana@example.com",
+            "body": "Numbers: 1 < 2\n<script>alert(1)</script>",
+            "received_time": "2024-05-14T09:12:00",
+            "is_unread": true
+        }))
+        .unwrap();
+        let document = render(&email, None);
+        assert!(document.contains("Budget &lt;draft&gt;"));
+        assert!(document.contains("1 &lt; 2"));
+        assert!(!document.contains("<script>"));
+        assert!(document.contains(CSP));
+        assert!(render(&email, Some("<b>Rich</b>")).contains("<b>Rich</b>"));
+        let linked = render(
+            &email,
+            Some("<a href=\"https://example.com/?a=1&b=2\">Go</a>"),
+        );
+        assert!(linked.contains("href=\"/-/open?email=abc&amp;url=https%3A%2F%2Fexample.com"));
+        assert_eq!(
+            link_target("https://serina-mail.localhost/-/open?email=abc&url=https%3A%2F%2Fexample.com%2F%3Fa%3D1%26b%3D2"),
+            Some(("abc".to_string(), "https://example.com/?a=1&b=2".to_string()))
+        );
+        assert_eq!(link_target("serina-mail://localhost/abc"), None);
+
+        let printable = printable(&email, Some("<p onclick=\"x()\">Hi</p>"), Vec::new());
+        assert_eq!(printable.from, "Dana <dana@example.com>");
+        assert_eq!(printable.body_html, "<p>Hi</p>");
+
+        assert_eq!(
+            email_id("serina-mail://localhost/abc").as_deref(),
+            Some("abc")
+        );
+        assert_eq!(
+            email_id("https://serina-mail.localhost/00AB%2BCD").as_deref(),
+            Some("00AB+CD")
+        );
+        assert_eq!(email_id("serina-mail://localhost/"), None);
+    }
+}
@@ -7,12 +7,116 @@
 )]
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use serina::accounts::{AccountBackoff, SyncProgress};
+use serina::actions::{self, ActionItem, ActionScope};
+use serina::activity::{self, ActivityKind, ActivityLog, ActivityPage};
+use serina::analytics::{self, AnalyticsPeriod, InboxAnalytics};
+use serina::applock::{AppLock, LockStatus};
+use serina::approvals::{ApprovalQueue, PendingApproval};
+use serina::attachments::{self, AttachmentInfo};
+use serina::audit::{AuditAction, AuditEntry, AuditFilter, AuditLog, Origin};
+use serina::autoreply::AutoResponder;
+use serina::backend::{BackendHealth, CircuitBreaker};
+use serina::budget::{Budget, BudgetError, SummaryCache, UsageRecord};
+use serina::cache::{CompactReport, EmailCache, IntegrityReport};
+use serina::calendar::{self, BusyBlock, MeetingProposal, MeetingStatus, ProposalStore, TimeSlot};
+use serina::capabilities::CapabilityStore;
+use serina::clipboard::{self, Clipboard};
+use serina::clock::{self, SharedClock};
+use serina::compose::{SendOptions, SendWarning, ThreadRecipients, ThreadReply, WarningKind};
+use serina::config::{ConfigStore, Overrides};
+use serina::confirm::{Confirmation, Confirmations, DestructiveAction};
+use serina::dates::{self, FormattedTime};
+use serina::diagnostics::{self, AuthResult, Diagnostics};
+use serina::encryption::{self, SecurityConfig};
+use serina::extract::{self, TransactionalData};
+use serina::feeds::{self, FeedConfig, FeedEntry, FeedStore};
+use serina::flags::{self, FeatureFlags, FlagState};
+use serina::focus::{FocusDigest, FocusSession, FocusState};
+use serina::guardrails::{self, GeneratedReplies, ReplySource};
+use serina::http::{CertificatePin, HttpClient, PinnedClient};
+use serina::i18n::{LocaleInfo, Localizer};
+use serina::inbox::InboxSnapshot;
+use serina::intake::{self, ComposeDraft, IntakeQueue, ShareRequest};
+use serina::links::{self, LinkRisk};
+use serina::llm::{self, validate_language, ReplyOptions};
+use serina::mailboxes::{self, ClaimState, SharedMailbox};
+use serina::mailview::{self, PrintableEmail};
+use serina::mock::MockBackend;
+use serina::mute::{MuteList, MutedThread};
+use serina::network::{ConnectivityMonitor, SyncTrigger, CONNECTIVITY_POLL};
+use serina::notification::{
+    self, NotificationEntry, NotificationList, NotificationPermission, PermissionState,
+};
+use serina::otp::{self, CodeExpiry};
+use serina::outbox::{OutboxStore, StagedAttachment};
+use serina::parsing::BodySegment;
+use serina::payments::{self, Invoice, Payment, PaymentSource, PaymentStore};
+use serina::pins::{PinStore, PinnedEmail};
+use serina::platform::{self, ActionResponse, EmailAction, EmailNotification};
+use serina::power::{PowerState, SyncPlan, WakeDetector};
+use serina::profiles::{self, AutoSwitch};
+use serina::purge::{self, PurgeResult, PurgeScope};
+use serina::reader::{self, ReaderView};
+use serina::recipients::{self, RecipientCheck};
+use serina::redact::{self, Redacted, Redaction};
+use serina::reminder::ReminderEscalation;
+use serina::retention::{self, AgingEmail, RetentionAction, RetentionConfig, RetentionPreview};
+use serina::retry::{FailedReply, RetryQueue};
+use serina::rules::{self, Rule, RuleAction, RuleIssue, RuleStore, Simulation, Trigger};
+use serina::scripts::{self, Script, ScriptRun, ScriptStore};
+use serina::settings::{self, SettingsBundle, SettingsWatcher};
+use serina::shutdown::{self, Shutdown};
+use serina::snooze::{SnoozeQueue, SnoozedEmail};
+use serina::spam::{self, JunkEmail, MisfiledTracker};
+use serina::streaks::{Milestone, StreakState, StreakTracker};
+use serina::teamsync::{self, TeamSnapshot, TeamSource, TeamStore, TeamSyncStatus};
+use serina::telemetry::{self, Telemetry, TelemetryBatch};
+use serina::templates::{self, Template, TemplateStore};
+use serina::theme::{self, ResolvedTheme, SystemTheme, ThemeMode, ThemePreset};
+use serina::triage::{TriageAction, TriageSelection, TriageState};
+use serina::upcoming::{self, OutgoingEmail, ScheduledItem, ScheduledKind};
+use serina::update::{self, UpdateInfo};
+use serina::utils::{is_quiet_hours, AppConfig, EmailData, NotificationData, UIConfig};
+use serina::webview::{self, Navigation};
+use serina::window::{
+    self, CompactMode, Delivery, PositionMemory, WindowEffect, WindowGeometry, WindowPosition,
+    WindowPresence, COMPACT_SIZE,
+};
+use serina::windows::AppWindow;
+use serina::{backend, compose, config, deadline, http, logging, parsing, privacy, tokens};
+use serina::{log_debug, log_error, log_info, log_warn};
+use std::collections::{BTreeMap, HashSet};
 use tauri::{
-    CustomMenuItem, Manager, SystemTray, SystemTrayEvent, SystemTrayMenu, SystemTrayMenuItem,
-    Window, WindowBuilder, WindowUrl,
+    AppHandle, CustomMenuItem, GlobalShortcutManager, Manager, State, SystemTray, SystemTrayEvent,
+    SystemTrayMenu, SystemTrayMenuItem, SystemTraySubmenu, Window, WindowBuilder, WindowUrl,
 };
 
+/// Tray menu item ids and the messages that label them
+const TRAY_ITEMS: [(&str, &str); 3] = [
+    ("show", "tray-show"),
+    ("settings", "tray-settings"),
+    ("quit", "tray-quit"),
+];
+
+/// Tray item that starts a focus session, or shows its countdown and ends it
+const TRAY_FOCUS: &str = "focus";
+
+/// Tray item that opens the list of scheduled items
+const TRAY_UPCOMING: &str = "upcoming";
+const TRAY_ACTIVITY: &str = "activity";
+const TRAY_LOCK: &str = "lock";
+
+/// Tray ids of notification profile items are this plus the profile name; the bare
+/// prefix goes back to the base settings
+const TRAY_PROFILE_PREFIX: &str = "profile:";
+
+/// Tray ids of pinned email items are these plus the email id
+const TRAY_PIN_OPEN_PREFIX: &str = "pin-open:";
+const TRAY_PIN_DONE_PREFIX: &str = "pin-done:";
+
+const MAINTENANCE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(24 * 60 * 60);
+
 #[derive(Debug, Serialize, Deserialize)]
 struct BackendRequest {
     endpoint: String,
@@ -29,54 +133,225 @@ struct EmailRequest {
     minutes: Option<u32>,
 }
 
+// Window registry helpers
+
+fn app_window(app: &AppHandle, kind: AppWindow) -> Result<Window, String> {
+    app.get_window(kind.label())
+        .ok_or_else(|| format!("Window '{}' is not open", kind.label()))
+}
+
+/// Bring the main window to the front
+fn show_main_window(app: &AppHandle) {
+    if let Ok(window) = app_window(app, AppWindow::Main) {
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
+
+/// Create a window from its registry options, hidden so the caller can place it first.
+/// `title` is for windows whose title depends on content.
+fn build_window(
+    app: &AppHandle,
+    kind: AppWindow,
+    url: String,
+    title: Option<String>,
+) -> Result<Window, String> {
+    let options = kind.options();
+    let title = title
+        .or_else(|| options.title_id.map(|id| app.state::<Localizer>().text(id)))
+        .unwrap_or_default();
+    let (width, height) = options.size;
+    let (handle, label) = (app.clone(), kind.label());
+    let mut builder = WindowBuilder::new(app, kind.label(), WindowUrl::App(url.into()))
+        .title(title)
+        .inner_size(width, height)
+        .resizable(options.resizable)
+        .decorations(options.decorations)
+        .always_on_top(options.always_on_top)
+        .skip_taskbar(options.skip_taskbar)
+        .transparent(options.transparent)
+        .visible(false)
+        .on_navigation(move |url| match webview::navigation(&url) {
+            Navigation::Allow => true,
+            Navigation::OpenExternal(url) => {
+                let app = handle.clone();
+                tauri::async_runtime::spawn(async move {
+                    let parent = app.get_window(label);
+                    if let Err(e) = open_link(&app, parent, url, None).await {
+                        log_warn!("Failed to open link: {}", e);
+                    }
+                });
+                false
+            }
+            Navigation::Block => {
+                log_info!("Blocked navigation to {}", url);
+                false
+            }
+        });
+    if let Some((min_width, min_height)) = options.min_size {
+        builder = builder.min_inner_size(min_width, min_height);
+    }
+    if options.center {
+        builder = builder.center();
+    }
+    builder.build().map_err(|e| e.to_string())
+}
+
+/// Hand a checked link to the system browser or mail client
+fn open_in_browser(app: &AppHandle, url: &url::Url) -> Result<(), String> {
+    tauri::api::shell::open(&app.shell_scope(), url.as_str(), None).map_err(|e| e.to_string())
+}
+
+/// Open an external link once it passes the link checks: shortened links are expanded first,
+/// and a destination that looks like phishing needs the user's go-ahead. `text` is what the
+/// link was shown as. Returns whether the link was opened.
+async fn open_link(
+    app: &AppHandle,
+    parent: Option<Window>,
+    url: url::Url,
+    text: Option<String>,
+) -> Result<bool, String> {
+    let config = app.state::<ConfigStore>().get()?;
+    // Expanding a link is a request to its host, which privacy mode doesn't allow
+    let url = if links::is_shortener(&url) && !config.privacy_mode {
+        match expand_link(app, &config, url.clone()).await {
+            Ok(resolved) => resolved,
+            Err(e) => {
+                log_warn!("Couldn't expand {}: {}", url, e);
+                url
+            }
+        }
+    } else {
+        url
+    };
+    let risks = links::assess(&url, text.as_deref());
+    if !risks.is_empty() && !confirm_link(app, parent, &url, &risks).await? {
+        return Ok(false);
+    }
+    open_in_browser(app, &url)?;
+    Ok(true)
+}
+
+/// Where a shortened link leads, asking each hop with the shared client's network settings
+async fn expand_link(
+    app: &AppHandle,
+    config: &AppConfig,
+    url: url::Url,
+) -> Result<url::Url, String> {
+    let client = app
+        .state::<HttpClient>()
+        .get_without_redirects(&config.network)
+        .await?;
+    links::resolve(&client, url, config.privacy_mode).await
+}
+
+/// Ask before opening a risky link, listing what looks wrong with it
+async fn confirm_link(
+    app: &AppHandle,
+    parent: Option<Window>,
+    url: &url::Url,
+    risks: &[LinkRisk],
+) -> Result<bool, String> {
+    let localizer = app.state::<Localizer>();
+    let title = localizer.text("link-confirm-title");
+    let host = url.host_str().unwrap_or(url.as_str());
+    let mut message = localizer.format("link-confirm-body", &[("host", host)]);
+    for risk in risks {
+        message.push_str("\n• ");
+        message.push_str(&localizer.text(risk.message_id()));
+    }
+    message.push_str("\n\n");
+    message.push_str(url.as_str());
+    // The dialog blocks until answered, so it waits off the async runtime
+    tauri::async_runtime::spawn_blocking(move || {
+        tauri::api::dialog::blocking::confirm(parent.as_ref(), title, message)
+    })
+    .await
+    .map_err(|e| e.to_string())
+}
+
 // Tauri Commands (exposed to frontend)
 
 #[tauri::command]
-async fn show_settings_window(window: Window) -> Result<(), String> {
-    match window.get_window("settings") {
-        Some(settings_window) => {
-            settings_window.show().map_err(|e| e.to_string())?;
+async fn show_settings_window(app: AppHandle) -> Result<(), String> {
+    let settings_window = match app_window(&app, AppWindow::Settings) {
+        Ok(settings_window) => {
             settings_window.center().map_err(|e| e.to_string())?;
-            settings_window.set_focus().map_err(|e| e.to_string())?;
-        }
-        None => {
-            WindowBuilder::new(
-                &window.app_handle(),
-                "settings",
-                WindowUrl::App("/settings".into()),
-            )
-            .title("SERINA Settings")
-            .inner_size(600.0, 500.0)
-            .resizable(false)
-            .center()
-            .build()
-            .map_err(|e| e.to_string())?;
+            settings_window
         }
-    }
-    Ok(())
+        Err(_) => build_window(
+            &app,
+            AppWindow::Settings,
+            AppWindow::Settings.url(&[]),
+            None,
+        )?,
+    };
+    settings_window.show().map_err(|e| e.to_string())?;
+    settings_window.set_focus().map_err(|e| e.to_string())
 }
 
 #[tauri::command]
 async fn show_reminder_popup(window: Window, email_count: u32) -> Result<(), String> {
+    open_reminder_popup(&window.app_handle(), email_count, None, None)
+}
+
+/// Commands a reminder popup for one email may run on it
+const REMINDER_GRANTS: &[&str] = &["mark_email_read", "snooze_email"];
+
+/// Show the reminder popup. With `email_id`, the popup gets a short-lived token to mark read
+/// or snooze that one email. A `notice` title and body are shown in place of the new mail
+/// count, for messages that aren't about new mail.
+fn open_reminder_popup(
+    app: &AppHandle,
+    email_count: u32,
+    email_id: Option<&str>,
+    notice: Option<(&str, &str)>,
+) -> Result<(), String> {
+    if app.state::<FocusState>().current().is_some() {
+        return Ok(());
+    }
     // Close existing reminder if open
-    if let Some(reminder_window) = window.get_window("reminder") {
+    if let Ok(reminder_window) = app_window(app, AppWindow::Reminder) {
         reminder_window.close().map_err(|e| e.to_string())?;
     }
 
+    let persistent = app
+        .state::<ConfigStore>()
+        .get()
+        .map(|c| c.notifications.persistent_list)
+        .unwrap_or_default();
+    // Screen readers announce the window title, so it carries the actual news
+    let localizer = app.state::<Localizer>();
+    let summary = match notice {
+        Some((title, _)) => title.to_string(),
+        None => localizer.format_email_count(email_count),
+    };
+    let title = localizer.format("window-reminder-title", &[("summary", &summary)]);
+
     // Create new reminder popup
-    let reminder_window = WindowBuilder::new(
-        &window.app_handle(),
-        "reminder",
-        WindowUrl::App(format!("/reminder?count={}", email_count).into()),
-    )
-    .title("SERINA Reminder")
-    .inner_size(320.0, 120.0)
-    .resizable(false)
-    .decorations(false)
-    .always_on_top(true)
-    .skip_taskbar(true)
-    .build()
-    .map_err(|e| e.to_string())?;
+    // The popup may not list mail itself, so it gets its counts up front
+    let pending = app.state::<InboxSnapshot>().email_count();
+    let mut query = vec![
+        ("count", email_count.to_string()),
+        ("pending", pending.to_string()),
+        ("persistent", (persistent as u8).to_string()),
+    ];
+    if let Some((title, body)) = notice {
+        query.push(("title", title.to_string()));
+        query.push(("body", body.to_string()));
+    }
+    if let Some(email_id) = email_id {
+        let token = app.state::<CapabilityStore>().issue(
+            AppWindow::Reminder,
+            REMINDER_GRANTS,
+            Some(email_id),
+            std::time::Instant::now(),
+        );
+        query.push(("email", email_id.to_string()));
+        query.push(("token", token));
+    }
+    let url = AppWindow::Reminder.url(&query);
+    let reminder_window = build_window(app, AppWindow::Reminder, url, Some(title))?;
 
     // Position at bottom-right of screen
     if let Ok(monitor) = reminder_window.current_monitor() {
@@ -90,10 +365,14 @@ async fn show_reminder_popup(window: Window, email_count: u32) -> Result<(), Str
                 .map_err(|e| e.to_string())?;
         }
     }
+    reminder_window.show().map_err(|e| e.to_string())?;
 
-    // Auto-close after 10 seconds
+    // Auto-close after 10 seconds, unless the user wants popups to wait for them
+    if persistent {
+        return Ok(());
+    }
     let reminder_handle = reminder_window.clone();
-    tokio::spawn(async move {
+    tauri::async_runtime::spawn(async move {
         tokio::time::sleep(tokio::time::Duration::from_secs(10)).await;
         let _ = reminder_handle.close();
     });
@@ -101,270 +380,5873 @@ async fn show_reminder_popup(window: Window, email_count: u32) -> Result<(), Str
     Ok(())
 }
 
+/// Open (or raise) the always-on-top mini inbox where it was last left
 #[tauri::command]
-async fn show_system_notification(title: String, body: String) -> Result<(), String> {
-    tauri::api::notification::Notification::new("com.serina.emailassistant")
-        .title(&title)
-        .body(&body)
-        .show()
-        .map_err(|e| e.to_string())?;
+async fn show_mini_inbox(app: AppHandle) -> Result<(), String> {
+    if let Ok(mini_inbox) = app_window(&app, AppWindow::MiniInbox) {
+        mini_inbox.show().map_err(|e| e.to_string())?;
+        return mini_inbox.set_focus().map_err(|e| e.to_string());
+    }
+
+    let (width, height) = AppWindow::MiniInbox.options().size;
+    let mini_inbox = build_window(
+        &app,
+        AppWindow::MiniInbox,
+        AppWindow::MiniInbox.url(&[]),
+        None,
+    )?;
+
+    if let Ok(Some(monitor)) = mini_inbox.current_monitor() {
+        let saved = app
+            .state::<ConfigStore>()
+            .get()
+            .ok()
+            .and_then(|c| c.ui.mini_inbox_position);
+        let position = match saved {
+            Some(saved) => {
+                let bounds = WindowGeometry {
+                    x: monitor.position().x,
+                    y: monitor.position().y,
+                    width: monitor.size().width,
+                    height: monitor.size().height,
+                };
+                let size = mini_inbox.outer_size().map_err(|e| e.to_string())?;
+                let position = window::clamp_to_monitor(saved, (size.width, size.height), bounds);
+                tauri::Position::Physical(tauri::PhysicalPosition {
+                    x: position.x,
+                    y: position.y,
+                })
+            }
+            // Bottom-right, like the reminder popup
+            None => {
+                let scale_factor = monitor.scale_factor();
+                let x = (monitor.size().width as f64 / scale_factor) - width - 20.0;
+                let y = (monitor.size().height as f64 / scale_factor) - height - 60.0;
+                tauri::Position::Logical(tauri::LogicalPosition { x, y })
+            }
+        };
+        mini_inbox
+            .set_position(position)
+            .map_err(|e| e.to_string())?;
+    }
+    mini_inbox.show().map_err(|e| e.to_string())
+}
+
+/// Save where the mini inbox was dragged to, once it closes
+fn remember_mini_inbox_position(app: &AppHandle) -> Result<(), String> {
+    let Some(position) = app.state::<PositionMemory>().take() else {
+        return Ok(());
+    };
+    let mut raw = app.state::<ConfigStore>().raw()?;
+    raw["ui"]["mini_inbox_position"] = serde_json::to_value(position).map_err(|e| e.to_string())?;
+    app.state::<ConfigStore>().save_raw(&raw)
+}
+
+/// Inline email buttons in the app's own windows, handled like notification buttons
+#[tauri::command]
+async fn run_email_action(
+    app: AppHandle,
+    email_id: String,
+    action: EmailAction,
+) -> Result<(), String> {
+    if action == EmailAction::Reply {
+        return Err("Replies need the compose view".to_string());
+    }
+    handle_email_action(
+        &app,
+        ActionResponse {
+            action,
+            email_id,
+            reply_text: None,
+        },
+    )
+    .await;
     Ok(())
 }
 
 #[tauri::command]
-async fn backend_request(
-    endpoint: String,
-    method: String,
-    body: Option<String>,
-) -> Result<String, String> {
-    let client = reqwest::Client::new();
-    let url = format!("http://127.0.0.1:8000{}", endpoint);
+async fn show_system_notification(
+    app: AppHandle,
+    title: String,
+    body: String,
+) -> Result<(), String> {
+    notify(&app, &title, &body)
+}
 
-    let mut request = match method.to_uppercase().as_str() {
-        "GET" => client.get(&url),
-        "POST" => client.post(&url),
-        "PUT" => client.put(&url),
-        "DELETE" => client.delete(&url),
-        _ => return Err("Unsupported HTTP method".to_string()),
+/// Show a desktop notification, falling back to the in-app reminder popup when the OS denies them
+fn notify(app: &AppHandle, title: &str, body: &str) -> Result<(), String> {
+    record_notification(app, title, body)?;
+    if app.state::<FocusState>().hold_notification(title, body) || deliver_in_app(app, title, body)
+    {
+        return Ok(());
+    }
+    // On a call or projector, say that something arrived but not what
+    let presenting = is_presenting(app);
+    let (title, body) = if app.state::<AppLock>().is_locked() {
+        // Locked: only how much is waiting
+        let localizer = app.state::<Localizer>();
+        let unread = app.state::<InboxSnapshot>().unread_count();
+        (
+            localizer.text("notification-locked-title"),
+            localizer.format_email_count(unread),
+        )
+    } else if presenting {
+        let localizer = app.state::<Localizer>();
+        (
+            localizer.text("notification-hidden-title"),
+            localizer.text("notification-hidden-body"),
+        )
+    } else {
+        (title.to_string(), body.to_string())
     };
+    let permission = app.state::<PermissionState>();
+    if permission.current().allows_notifications() {
+        let shown = tauri::api::notification::Notification::new(notification::APP_ID)
+            .title(&title)
+            .body(&body)
+            .show();
+        permission.record_delivery(shown.is_ok());
+        if shown.is_ok() {
+            return Ok(());
+        }
+    }
+    if presenting {
+        return Ok(());
+    }
+    open_reminder_popup(app, 0, None, Some((&title, &body)))
+}
+
+/// Whether popups and notification content should be held back for a presentation
+fn is_presenting(app: &AppHandle) -> bool {
+    let hide = app
+        .state::<ConfigStore>()
+        .get()
+        .map(|c| c.notifications.hide_while_presenting)
+        .unwrap_or(true);
+    hide && platform::presentation_state().is_presenting()
+}
+
+#[tauri::command]
+async fn is_tray_available() -> bool {
+    platform::tray_available()
+}
+
+/// Keep a copy in the persistent notification list when the user asked for one
+fn record_notification(app: &AppHandle, title: &str, body: &str) -> Result<(), String> {
+    let persistent = app
+        .state::<ConfigStore>()
+        .get()
+        .map(|c| c.notifications.persistent_list)
+        .unwrap_or_default();
+    if persistent {
+        let entry = app.state::<NotificationList>().push(title, body)?;
+        let _ = app.emit_all("notification-added", &entry);
+    }
+    Ok(())
+}
 
-    if let Some(body_data) = body {
-        request = request
-            .header("Content-Type", "application/json")
-            .body(body_data);
+/// Notify about a specific email, with action buttons where the platform has them
+async fn notify_email(
+    app: &AppHandle,
+    email_id: &str,
+    sender_email: &str,
+    title: &str,
+    body: &str,
+    actions: &[EmailAction],
+) -> Result<(), String> {
+    // Rich notifications show the sender's picture and content; skip them while presenting or locked
+    if !platform::supports_notification_actions()
+        || is_presenting(app)
+        || app.state::<AppLock>().is_locked()
+    {
+        return notify(app, title, body);
     }
+    record_notification(app, title, body)?;
+    if app.state::<FocusState>().hold_notification(title, body) || deliver_in_app(app, title, body)
+    {
+        return Ok(());
+    }
+    let config = app.state::<ConfigStore>().get().unwrap_or_default();
+    let avatar = if sync_plan(&config).fetch_attachments {
+        sender_avatar(app, sender_email).await
+    } else {
+        None
+    };
+    let notification = {
+        let localizer = app.state::<Localizer>();
+        EmailNotification {
+            email_id: email_id.to_string(),
+            title: title.to_string(),
+            body: body.to_string(),
+            avatar,
+            actions: actions
+                .iter()
+                .map(|action| (*action, localizer.text(action.message_id())))
+                .collect(),
+            reply_placeholder: Some(localizer.text("action-reply-placeholder")),
+            urgent: spam::is_vip(sender_email, &config.notifications.vip_senders),
+        }
+    };
+    let app = app.clone();
+    // The OS call blocks until the user answers, so it gets its own thread
+    tauri::async_runtime::spawn_blocking(move || {
+        match platform::notify_with_actions(&notification) {
+            Ok(Some(response)) => {
+                tauri::async_runtime::block_on(handle_email_action(&app, response))
+            }
+            Ok(None) => {}
+            Err(e) => {
+                log_warn!("Actionable notification failed: {}", e);
+                let _ = open_reminder_popup(&app, 1, Some(&notification.email_id), None);
+            }
+        }
+    });
+    Ok(())
+}
 
-    let response = request.send().await.map_err(|e| e.to_string())?;
+/// Path of the sender's Outlook contact picture, if they have one
+async fn sender_avatar(app: &AppHandle, sender_email: &str) -> Option<std::path::PathBuf> {
+    let endpoint = format!(
+        "/contacts/photo?{}",
+        url::form_urlencoded::Serializer::new(String::new())
+            .append_pair("email", sender_email)
+            .finish()
+    );
+    let response = backend(app, endpoint, "GET".to_string(), None).await.ok()?;
+    let value: serde_json::Value = serde_json::from_str(&response).ok()?;
+    value["path"].as_str().map(std::path::PathBuf::from)
+}
 
-    if !response.status().is_success() {
-        return Err(format!("HTTP {}: {}", response.status(), response.status()));
+/// Carry out a button pressed (or reply typed) on an email notification
+async fn handle_email_action(app: &AppHandle, response: ActionResponse) {
+    let audit = app.state::<AuditLog>();
+    let email_id = response.email_id;
+    let result = match response.action {
+        EmailAction::Open => {
+            show_main_window(app);
+            let _ = app.emit_all("open-email", &email_id);
+            Ok(String::new())
+        }
+        EmailAction::MarkRead => mark_email_read(app.clone(), audit, email_id).await,
+        EmailAction::Snooze => {
+            let minutes = app
+                .state::<ConfigStore>()
+                .get()
+                .map(|c| c.triage.snooze_minutes)
+                .unwrap_or(60);
+            snooze_email(
+                app.clone(),
+                audit,
+                app.state::<SnoozeQueue>(),
+                email_id,
+                Some(minutes),
+            )
+            .await
+        }
+        EmailAction::NotSpam => not_spam(app.clone(), audit, email_id).await,
+        EmailAction::Pin => pin_email(app.clone(), email_id)
+            .await
+            .map(|_| String::new()),
+        EmailAction::RetrySend => retry_failed_reply(app.clone(), email_id).await,
+        EmailAction::EditReply => edit_failed_reply(app.clone(), email_id)
+            .await
+            .map(|_| String::new()),
+        EmailAction::DiscardReply => discard_failed_reply(app.clone(), email_id)
+            .await
+            .map(|_| String::new()),
+        EmailAction::CopyCode => copy_verification_code(app.clone(), email_id)
+            .await
+            .map(|_| String::new()),
+        EmailAction::MarkPaid => mark_payment_paid(app.clone(), email_id)
+            .await
+            .map(|_| String::new()),
+        EmailAction::Delete => delete_held_email(app, email_id)
+            .await
+            .map(|_| String::new()),
+        EmailAction::Reply => match response.reply_text {
+            Some(text) => {
+                let sent = send_reply(app.clone(), audit, email_id.clone(), text, None).await;
+                // A reply that was saved for retry has already said so
+                let saved = app.state::<RetryQueue>().get(&email_id).is_some();
+                if let (Err(e), false) = (&sent, saved) {
+                    let title = app
+                        .state::<Localizer>()
+                        .text("notification-reply-failed-title");
+                    let _ = notify(app, &title, e);
+                }
+                sent
+            }
+            // An empty reply box means the user wants to write it in the app
+            None => {
+                let _ = app.emit_all("open-email", &email_id);
+                Ok(String::new())
+            }
+        },
+    };
+    if let Err(e) = result {
+        log_warn!("Notification action {:?} failed: {}", response.action, e);
     }
+}
 
-    response.text().await.map_err(|e| e.to_string())
+/// Show the unread count on the dock icon
+async fn update_badge(app: &AppHandle) -> Result<(), String> {
+    let response = get_unread_count(app.clone()).await?;
+    let count = serde_json::from_str::<serde_json::Value>(&response)
+        .ok()
+        .and_then(|v| v["count"].as_u64())
+        .unwrap_or(0) as u32;
+    let _ = app.emit_all("unread-count-changed", count);
+    if app
+        .state::<ConfigStore>()
+        .get()
+        .is_ok_and(|c| c.ui.gamification)
+    {
+        let today = app.state::<SharedClock>().local_now().date();
+        let milestones = app.state::<StreakTracker>().record_unread(count, today)?;
+        emit_milestones(app, milestones);
+    }
+    app.run_on_main_thread(move || platform::set_badge_count(count))
+        .map_err(|e| e.to_string())
 }
 
-// Email-specific commands for easier frontend usage
+/// Count an email the user dealt with toward the daily triage goal
+fn record_triaged(app: &AppHandle) {
+    let Ok(config) = app.state::<ConfigStore>().get() else {
+        return;
+    };
+    if !config.ui.gamification {
+        return;
+    }
+    let today = app.state::<SharedClock>().local_now().date();
+    match app
+        .state::<StreakTracker>()
+        .record_triaged(today, config.ui.daily_triage_goal)
+    {
+        Ok(milestones) => emit_milestones(app, milestones),
+        Err(e) => log_warn!("Failed to record triaged email: {}", e),
+    }
+}
+
+fn emit_milestones(app: &AppHandle, milestones: Vec<Milestone>) {
+    for milestone in milestones {
+        let _ = app.emit_all("milestone-reached", &milestone);
+    }
+}
 
+/// Inbox-zero streak and today's triage count, for the gamification widget
 #[tauri::command]
-async fn get_emails(limit: Option<u32>) -> Result<String, String> {
-    let limit = limit.unwrap_or(20);
-    backend_request(format!("/emails?limit={}", limit), "GET".to_string(), None).await
+async fn get_streaks(
+    tracker: State<'_, StreakTracker>,
+    clock: State<'_, SharedClock>,
+) -> Result<StreakState, String> {
+    Ok(tracker.state(clock.local_now().date()))
 }
 
 #[tauri::command]
-async fn get_email(email_id: String) -> Result<String, String> {
-    backend_request(format!("/emails/{}", email_id), "GET".to_string(), None).await
+async fn get_notification_permission(
+    permission: State<'_, PermissionState>,
+) -> Result<NotificationPermission, String> {
+    Ok(permission.current())
 }
 
+/// Send the user to the OS notification settings when notifications are blocked
 #[tauri::command]
-async fn send_reply(email_id: String, reply_text: String) -> Result<String, String> {
-    let body = serde_json::json!({
-        "reply_text": reply_text
-    });
-    backend_request(
-        format!("/emails/{}/reply", email_id),
-        "POST".to_string(),
-        Some(body.to_string()),
-    )
-    .await
+async fn request_notification_permission(
+    permission: State<'_, PermissionState>,
+) -> Result<NotificationPermission, String> {
+    // A fresh request should re-check the OS rather than trust an old failure
+    permission.record_delivery(true);
+    let current = permission.current();
+    if current == NotificationPermission::Denied {
+        notification::open_permission_settings()?;
+    }
+    Ok(current)
 }
 
 #[tauri::command]
-async fn mark_email_read(email_id: String) -> Result<String, String> {
-    backend_request(
-        format!("/emails/{}/mark-read", email_id),
-        "POST".to_string(),
-        None,
-    )
-    .await
+async fn get_notification_list(
+    list: State<'_, NotificationList>,
+) -> Result<Vec<NotificationEntry>, String> {
+    Ok(list.list())
 }
 
 #[tauri::command]
-async fn create_task_from_email(
-    email_id: String,
-    title: String,
-    description: String,
-) -> Result<String, String> {
-    let body = serde_json::json!({
-        "title": title,
-        "description": description
-    });
-    backend_request(
-        format!("/emails/{}/create-task", email_id),
-        "POST".to_string(),
-        Some(body.to_string()),
-    )
-    .await
+async fn dismiss_notification(list: State<'_, NotificationList>, id: u64) -> Result<(), String> {
+    list.dismiss(id)
 }
 
 #[tauri::command]
-async fn snooze_email(email_id: String, minutes: Option<u32>) -> Result<String, String> {
-    let minutes = minutes.unwrap_or(60);
-    backend_request(
-        format!("/emails/{}/snooze?minutes={}", email_id, minutes),
-        "POST".to_string(),
-        None,
-    )
-    .await
+async fn clear_notifications(list: State<'_, NotificationList>) -> Result<(), String> {
+    list.clear()
+}
+
+/// The backend's address, which the config file, environment or flags can move
+fn backend_url(app: &AppHandle) -> String {
+    let network = app.state::<ConfigStore>().get().unwrap_or_default().network;
+    network.backend_url().to_string()
 }
 
+/// The shared outbound client for the current network settings
+async fn http_client(app: &AppHandle) -> Result<PinnedClient, String> {
+    let network = app.state::<ConfigStore>().get().unwrap_or_default().network;
+    app.state::<HttpClient>().get(&network).await
+}
+
+/// Read-only passthrough for the UI. Anything that changes or deletes mail has its own
+/// command, which is where confirmations and pre-send checks happen.
 #[tauri::command]
-async fn get_unread_count() -> Result<String, String> {
-    backend_request("/emails/unread-count".to_string(), "GET".to_string(), None).await
+async fn backend_request(
+    app: AppHandle,
+    endpoint: String,
+    method: String,
+    body: Option<String>,
+) -> Result<String, String> {
+    if !method.eq_ignore_ascii_case("GET") {
+        return Err(format!("{} {} can't be sent directly", method, endpoint));
+    }
+    backend(&app, endpoint, method, body).await
 }
 
-// LLM commands
+async fn backend(
+    app: &AppHandle,
+    endpoint: String,
+    method: String,
+    body: Option<String>,
+) -> Result<String, String> {
+    if let Some(mock) = app.try_state::<MockBackend>() {
+        return mock.respond(&method, &endpoint, body.as_deref());
+    }
+    log_debug!("{} {}", method, endpoint);
+    let breaker = app.state::<CircuitBreaker>();
+    breaker.check()?;
+    let client = http_client(app).await?;
+    let result = backend::request(&client, &backend_url(app), &method, &endpoint, body).await;
+    breaker.record(&result);
+    if let Err(e) = &result {
+        track_error(app, e);
+    }
+    result
+}
+
+/// Like `backend` for GETs, parsing the JSON as it streams in instead of buffering the body
+async fn backend_json<T: serde::de::DeserializeOwned>(
+    app: &AppHandle,
+    endpoint: String,
+) -> Result<T, String> {
+    if let Some(mock) = app.try_state::<MockBackend>() {
+        let response = mock.respond("GET", &endpoint, None)?;
+        return serde_json::from_str(&response).map_err(|e| e.to_string());
+    }
+    let breaker = app.state::<CircuitBreaker>();
+    breaker.check()?;
+    let client = http_client(app).await?;
+    let result = backend::request_json(&client, &backend_url(app), "GET", &endpoint, None).await;
+    breaker.record(&result);
+    if let Err(e) = &result {
+        track_error(app, e);
+    }
+    result
+}
+
+// Email-specific commands for easier frontend usage
 
 #[tauri::command]
-async fn summarize_email(email_content: String) -> Result<String, String> {
-    let body = serde_json::json!({
-        "email_content": email_content
-    });
-    backend_request(
-        "/llm/summarize".to_string(),
-        "POST".to_string(),
-        Some(body.to_string()),
-    )
-    .await
+async fn get_emails(
+    app: AppHandle,
+    cache: State<'_, EmailCache>,
+    limit: Option<u32>,
+) -> Result<String, String> {
+    let limit = limit.unwrap_or(20);
+    let emails = fetch_email_list(&app, format!("/emails?limit={}", limit)).await?;
+    if !app
+        .state::<ConfigStore>()
+        .get()
+        .map(|c| c.email.collapse_duplicates)
+        .unwrap_or(true)
+    {
+        return serde_json::to_string(&emails).map_err(|e| e.to_string());
+    }
+
+    let collapsed = cache.collapse(emails)?;
+    serde_json::to_string(&collapsed).map_err(|e| e.to_string())
+}
+
+/// Read an email list from the backend, adding previews and deadlines
+async fn fetch_email_list(app: &AppHandle, endpoint: String) -> Result<Vec<EmailData>, String> {
+    let mut emails: Vec<EmailData> = backend_json(app, endpoint).await?;
+    parsing::add_snippets(&mut emails);
+    deadline::add_deadlines(&mut emails);
+    app.state::<MuteList>()
+        .apply(&mut emails, app.state::<SharedClock>().now());
+    Ok(emails)
 }
 
+/// Volume, busiest senders and response times for the stats view
 #[tauri::command]
-async fn generate_task_from_email(email_content: String) -> Result<String, String> {
-    let body = serde_json::json!({
-        "email_content": email_content
-    });
-    backend_request(
-        "/llm/generate-task".to_string(),
-        "POST".to_string(),
-        Some(body.to_string()),
-    )
-    .await
+async fn get_inbox_analytics(
+    cache: State<'_, EmailCache>,
+    audit: State<'_, AuditLog>,
+    clock: State<'_, SharedClock>,
+    period: Option<AnalyticsPeriod>,
+) -> Result<InboxAnalytics, String> {
+    let period = period.unwrap_or_default();
+    let now = clock.now();
+    let emails = cache.recent(analytics::MAX_ANALYZED)?;
+    let replies = audit.query(&AuditFilter {
+        action: Some(AuditAction::ReplySent),
+        since: Some(now - period.duration()),
+        ..Default::default()
+    })?;
+    Ok(analytics::compute(
+        &emails,
+        &replies,
+        period,
+        now,
+        &chrono::Local,
+    ))
 }
 
+/// Reclaim space in the local cache database
 #[tauri::command]
-async fn generate_reply(email_content: String, instruction: Option<String>) -> Result<String, String> {
-    let body = serde_json::json!({
-        "email_content": email_content,
-        "instruction": instruction.unwrap_or_default()
-    });
-    backend_request(
-        "/llm/generate-reply".to_string(),
-        "POST".to_string(),
-        Some(body.to_string()),
-    )
-    .await
+async fn compact_database(cache: State<'_, EmailCache>) -> Result<CompactReport, String> {
+    cache.compact()
 }
 
-// Configuration commands
+#[tauri::command]
+async fn verify_cache_integrity(cache: State<'_, EmailCache>) -> Result<IntegrityReport, String> {
+    cache.verify()
+}
 
+/// Number of cached emails hidden as duplicate deliveries
 #[tauri::command]
-async fn get_config() -> Result<String, String> {
-    backend_request("/config".to_string(), "GET".to_string(), None).await
+async fn get_hidden_duplicate_count(cache: State<'_, EmailCache>) -> Result<usize, String> {
+    cache.hidden_duplicates()
 }
 
 #[tauri::command]
-async fn save_config(config: serde_json::Value) -> Result<String, String> {
-    let body = serde_json::json!({
-        "config": config
-    });
-    backend_request(
-        "/config".to_string(),
-        "POST".to_string(),
-        Some(body.to_string()),
+async fn get_email(app: AppHandle, email_id: String) -> Result<String, String> {
+    let response = backend(
+        &app,
+        format!("/emails/{}", email_id),
+        "GET".to_string(),
+        None,
     )
-    .await
+    .await?;
+    cache_opened_email(&app, &response);
+    Ok(response)
 }
 
-#[tauri::command]
-async fn health_check() -> Result<String, String> {
-    backend_request("/health".to_string(), "GET".to_string(), None).await
+/// Keep an opened email, HTML body included, so `serina-mail://` can show it again offline
+fn cache_opened_email(app: &AppHandle, response: &str) {
+    let Ok(value) = serde_json::from_str::<serde_json::Value>(response) else {
+        return;
+    };
+    let Ok(email) = serde_json::from_value::<EmailData>(value.clone()) else {
+        return;
+    };
+    let cache = app.state::<EmailCache>();
+    let mut stored = cache.store(std::slice::from_ref(&email));
+    if let (Ok(()), Some(html)) = (&stored, value["html_body"].as_str()) {
+        stored = cache.store_html(&email.id, html);
+    }
+    if let Err(e) = stored {
+        log_warn!("Failed to cache email {}: {}", email.id, e);
+    }
 }
 
-// Window control commands
+/// Cached emails as sanitized documents under a strict CSP, for `serina-mail://<email id>`.
+/// Only the cache is read: no backend call, and nothing else on disk is reachable. Links in the
+/// documents come back as `mailview::LINK_PATH` and are opened after the link checks.
+fn serve_mail(
+    app: &AppHandle,
+    request: &tauri::http::Request,
+) -> Result<tauri::http::Response, Box<dyn std::error::Error>> {
+    // A clicked link: check and open it, and show the email again
+    let link = mailview::link_target(request.uri());
+    if let Some((_, link)) = &link {
+        match webview::external_url(link) {
+            Ok(url) => {
+                let app = app.clone();
+                tauri::async_runtime::spawn(async move {
+                    let parent = app.get_window(AppWindow::Main.label());
+                    if let Err(e) = open_link(&app, parent, url, None).await {
+                        log_warn!("Failed to open link: {}", e);
+                    }
+                });
+            }
+            Err(e) => log_warn!("Ignored link in email: {}", e),
+        }
+    }
+    let document = app.try_state::<EmailCache>().and_then(|cache| {
+        let id = match link {
+            Some((id, _)) => id,
+            None => mailview::email_id(request.uri())?,
+        };
+        let email = cache.get(&id).ok()??;
+        let html = cache.html(&id).ok().flatten();
+        Some(mailview::render(&email, html.as_deref()))
+    });
+    let response = tauri::http::ResponseBuilder::new()
+        .header("Content-Security-Policy", mailview::CSP)
+        .header("X-Content-Type-Options", "nosniff");
+    match document {
+        Some(document) => response
+            .mimetype("text/html")
+            .status(200)
+            .body(document.into_bytes()),
+        None => response.status(404).body(Vec::new()),
+    }
+}
 
-#[tauri::command]
-async fn minimize_window(window: Window) -> Result<(), String> {
-    window.minimize().map_err(|e| e.to_string())
+/// An email and its HTML body, fetched fresh when the backend is reachable and otherwise from
+/// the cache, so it can be printed or copied offline
+async fn load_email(
+    app: &AppHandle,
+    email_id: &str,
+) -> Result<(EmailData, Option<String>), String> {
+    if let Err(e) = get_email(app.clone(), email_id.to_string()).await {
+        log_info!("Using cached copy of {}: {}", email_id, e);
+    }
+    let cache = app.state::<EmailCache>();
+    let email = cache
+        .get(email_id)?
+        .ok_or_else(|| "Email is not available offline".to_string())?;
+    let html = cache.html(email_id)?;
+    Ok((email, html))
 }
 
+/// Lay the email out in the hidden print window, which asks for the OS print dialog once it
+/// has rendered. PDFs come from the dialog's PDF printer ("Microsoft Print to PDF", macOS
+/// "Save as PDF", or "Print to File" on Linux); the webview can't write one on its own.
 #[tauri::command]
-async fn maximize_window(window: Window) -> Result<(), String> {
-    if window.is_maximized().map_err(|e| e.to_string())? {
-        window.unmaximize().map_err(|e| e.to_string())
-    } else {
-        window.maximize().map_err(|e| e.to_string())
+async fn print_email(app: AppHandle, email_id: String) -> Result<(), String> {
+    if let Ok(previous) = app_window(&app, AppWindow::Print) {
+        let _ = previous.close();
     }
+    build_window(
+        &app,
+        AppWindow::Print,
+        AppWindow::Print.url(&[("email", email_id)]),
+        None,
+    )?;
+    Ok(())
 }
 
+/// Headers, attachment list and sanitized body for the print window
 #[tauri::command]
-async fn close_window(window: Window) -> Result<(), String> {
-    window.close().map_err(|e| e.to_string())
+async fn get_printable_email(app: AppHandle, email_id: String) -> Result<PrintableEmail, String> {
+    let (email, html) = load_email(&app, &email_id).await?;
+    let attachments = list_attachments(app.clone(), email_id)
+        .await
+        .unwrap_or_default();
+    Ok(mailview::printable(&email, html.as_deref(), attachments))
 }
 
-fn main() {
-    // Create system tray
-    let quit = CustomMenuItem::new("quit".to_string(), "Quit");
-    let show = CustomMenuItem::new("show".to_string(), "Show");
-    let settings = CustomMenuItem::new("settings".to_string(), "Settings");
-    let tray_menu = SystemTrayMenu::new()
-        .add_item(show)
-        .add_native_item(SystemTrayMenuItem::Separator)
-        .add_item(settings)
-        .add_native_item(SystemTrayMenuItem::Separator)
-        .add_item(quit);
+/// Called by the print window once its layout is on screen
+#[tauri::command]
+async fn print_window(window: Window) -> Result<(), String> {
+    window.print().map_err(|e| e.to_string())
+}
 
-    let system_tray = SystemTray::new().with_menu(tray_menu);
+/// Copy an email with its headers, as Markdown plus an HTML flavour for rich-text editors
+#[tauri::command]
+async fn copy_email_as_markdown(app: AppHandle, email_id: String) -> Result<(), String> {
+    let (email, html) = load_email(&app, &email_id).await?;
+    app.state::<Clipboard>()
+        .write(&clipboard::email(&email, html.as_deref()))?;
+    track(&app, "copy_email");
+    Ok(())
+}
 
-    tauri::Builder::default()
-        .system_tray(system_tray)
-        .on_system_tray_event(|app, event| match event {
+/// Copy an email's summary, reusing the one already shown when there is one
+#[tauri::command]
+async fn copy_summary(app: AppHandle, email_id: String) -> Result<(), String> {
+    let (email, _) = load_email(&app, &email_id).await?;
+    let cached = app
+        .state::<SummaryCache>()
+        .get(&parsing::new_content(&email.body), None);
+    let summary = match cached {
+        Some(summary) => summary,
+        None => {
+            let response =
+                summarize_email(app.clone(), email.body.clone(), None, Some(email_id)).await?;
+            summary_text(&response)?
+        }
+    };
+    app.state::<Clipboard>()
+        .write(&clipboard::summary(&email, &summary))?;
+    track(&app, "copy_summary");
+    Ok(())
+}
+
+/// Copy the one-time code in an email; codes aren't kept anywhere, so it is found again
+#[tauri::command]
+async fn copy_verification_code(app: AppHandle, email_id: String) -> Result<String, String> {
+    let (email, _) = load_email(&app, &email_id).await?;
+    let code = otp::find_code(&email)
+        .ok_or_else(|| "No verification code found in this email".to_string())?;
+    app.state::<Clipboard>().write(&clipboard::text(&code))?;
+    track(&app, "copy_code");
+    Ok(code)
+}
+
+/// Unpaid bills found in mail, soonest due first
+#[tauri::command]
+async fn get_upcoming_payments(payments: State<'_, PaymentStore>) -> Result<Vec<Payment>, String> {
+    payments.upcoming()
+}
+
+#[tauri::command]
+async fn mark_payment_paid(app: AppHandle, email_id: String) -> Result<Vec<Payment>, String> {
+    let store = app.state::<PaymentStore>();
+    if !store.mark_paid(&email_id)? {
+        return Err("This bill isn't being tracked".to_string());
+    }
+    payments_changed(&app)
+}
+
+/// Stop tracking an email that isn't a bill after all
+#[tauri::command]
+async fn dismiss_payment(app: AppHandle, email_id: String) -> Result<Vec<Payment>, String> {
+    app.state::<PaymentStore>().remove(&email_id)?;
+    payments_changed(&app)
+}
+
+fn payments_changed(app: &AppHandle) -> Result<Vec<Payment>, String> {
+    let payments = app.state::<PaymentStore>().upcoming()?;
+    let _ = app.emit_all("payments-changed", &payments);
+    Ok(payments)
+}
+
+/// The summary out of a `summarize_email` response
+fn summary_text(response: &str) -> Result<String, String> {
+    serde_json::from_str::<serde_json::Value>(response)
+        .ok()
+        .and_then(|value| value["summary"].as_str().map(str::to_string))
+        .ok_or_else(|| "Invalid summary response".to_string())
+}
+
+/// The email as an article for reader mode: the main content of its HTML as Markdown
+#[tauri::command]
+async fn get_reader_view(app: AppHandle, email_id: String) -> Result<ReaderView, String> {
+    let (email, html) = load_email(&app, &email_id).await?;
+    track(&app, "reader_mode");
+    Ok(reader::reader_view(&email, html.as_deref()))
+}
+
+/// Names and sizes of an email's attachments
+#[tauri::command]
+async fn list_attachments(app: AppHandle, email_id: String) -> Result<Vec<AttachmentInfo>, String> {
+    backend_json(&app, format!("/emails/{}/attachments", email_id)).await
+}
+
+/// Stream one attachment to disk and return its path, which the webview loads through the
+/// asset protocol (`convertFileSrc`) or hands to the default app
+#[tauri::command]
+async fn download_attachment(
+    app: AppHandle,
+    email_id: String,
+    attachment: AttachmentInfo,
+) -> Result<String, String> {
+    if app.try_state::<MockBackend>().is_some() {
+        return Err("Attachments aren't available in mock mode".to_string());
+    }
+    let path = attachments::attachment_path(
+        &attachments::attachment_dir(),
+        &email_id,
+        attachment.index,
+        &attachment.name,
+    );
+    let downloaded = std::fs::metadata(&path).is_ok_and(|meta| meta.len() == attachment.size);
+    if !downloaded {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+        let breaker = app.state::<CircuitBreaker>();
+        breaker.check()?;
+        let client = http_client(&app).await?;
+        let endpoint = format!("/emails/{}/attachments/{}", email_id, attachment.index);
+        let result = backend::download(&client, &backend_url(&app), &endpoint, &path).await;
+        breaker.record(&result);
+        result?;
+    }
+    Ok(path.to_string_lossy().into_owned())
+}
+
+// Shares from other apps
+
+/// Open a compose window pre-filled from a share; an open compose window switches to it
+fn open_shared_compose(app: &AppHandle, request: &ShareRequest) -> Result<(), String> {
+    let draft_id = app.state::<IntakeQueue>().add(intake::draft(request));
+    track(app, "share_intake");
+    let window = match app_window(app, AppWindow::Compose) {
+        Ok(window) => {
+            window
+                .emit("compose-draft", draft_id)
+                .map_err(|e| e.to_string())?;
+            window
+        }
+        Err(_) => build_window(
+            app,
+            AppWindow::Compose,
+            AppWindow::Compose.url(&[("draft", draft_id.to_string())]),
+            None,
+        )?,
+    };
+    window.show().map_err(|e| e.to_string())?;
+    window.set_focus().map_err(|e| e.to_string())
+}
+
+/// Accept shares handed over by processes the OS launched with `--share`
+fn start_intake(app: AppHandle, listener: std::net::TcpListener) {
+    tauri::async_runtime::spawn_blocking(move || {
+        for stream in listener.incoming().flatten() {
+            let opened =
+                intake::receive(stream).and_then(|request| open_shared_compose(&app, &request));
+            if let Err(e) = opened {
+                log_warn!("Failed to open shared item: {}", e);
+            }
+        }
+    });
+}
+
+/// Services menu shares arrive on the main thread; they take the same route as the others
+fn forward_share(request: ShareRequest) {
+    if let Err(e) = intake::hand_off(intake::HANDOFF_PORT, &request) {
+        log_warn!("Failed to forward share: {}", e);
+    }
+}
+
+#[tauri::command]
+fn get_compose_draft(
+    intake: State<'_, IntakeQueue>,
+    draft_id: u64,
+) -> Result<ComposeDraft, String> {
+    intake
+        .get(draft_id)
+        .ok_or_else(|| "This draft is no longer available".to_string())
+}
+
+#[tauri::command]
+fn discard_compose_draft(intake: State<'_, IntakeQueue>, draft_id: u64) {
+    intake.remove(draft_id);
+}
+
+/// Check each address on a To line: syntax, likely domain typos and, when enabled, whether
+/// it's in the Outlook contacts. A failed contact lookup flags nobody as new.
+#[tauri::command]
+async fn check_recipients(app: AppHandle, to: String) -> Result<Vec<RecipientCheck>, String> {
+    let addresses = recipients::split_recipients(&to);
+    let verify = app.state::<ConfigStore>().get()?.email.verify_recipients;
+    let known = if verify && !addresses.is_empty() {
+        // The serializer isn't Send, so it's done before the request is awaited
+        let query = {
+            let mut query = url::form_urlencoded::Serializer::new(String::new());
+            for address in &addresses {
+                query.append_pair("email", address);
+            }
+            query.finish()
+        };
+        match backend_json::<serde_json::Value>(&app, format!("/contacts/known?{}", query)).await {
+            Ok(response) => Some(
+                response["known"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|address| address.as_str().map(str::to_lowercase))
+                    .collect::<HashSet<String>>(),
+            ),
+            Err(e) => {
+                log_warn!("Couldn't check recipients against contacts: {}", e);
+                None
+            }
+        }
+    } else {
+        None
+    };
+    Ok(recipients::check_recipients(&to, known.as_ref()))
+}
+
+/// Send a new message from a shared draft. Attachments come from the draft, never from the
+/// webview.
+#[tauri::command]
+async fn send_email(
+    app: AppHandle,
+    audit: State<'_, AuditLog>,
+    shutdown: State<'_, Shutdown>,
+    draft_id: u64,
+    to: String,
+    subject: String,
+    body: String,
+    allow_recipient_warnings: Option<bool>,
+) -> Result<String, String> {
+    let draft = app
+        .state::<IntakeQueue>()
+        .get(draft_id)
+        .ok_or_else(|| "This draft is no longer available".to_string())?;
+    let checks = check_recipients(app.clone(), to.clone()).await?;
+    if checks.is_empty() {
+        return Err("Add at least one recipient".to_string());
+    }
+    if let Some(error) = checks.iter().find_map(|check| check.error.clone()) {
+        return Err(error);
+    }
+    if !allow_recipient_warnings.unwrap_or(false) {
+        let warnings = compose::recipient_warnings(&checks);
+        if !warnings.is_empty() {
+            return Err(compose::blocked_error(&warnings));
+        }
+    }
+    let request = serde_json::json!({
+        "to": to,
+        "subject": subject,
+        "body": body,
+        "attachments": draft.attachments
+    });
+    let Some(_task) = shutdown.task() else {
+        return Err("SERINA is quitting; the email was not sent".to_string());
+    };
+    let response = backend(
+        &app,
+        "/emails/send".to_string(),
+        "POST".to_string(),
+        Some(request.to_string()),
+    )
+    .await?;
+    app.state::<IntakeQueue>().remove(draft_id);
+    track(&app, "send_email");
+    record_audit(
+        &audit,
+        AuditEntry::new(AuditAction::EmailSent, Origin::User, None).with_details(
+            serde_json::json!({ "length": body.len(), "attachments": draft.attachments.len() }),
+        ),
+    );
+    Ok(response)
+}
+
+/// Stage files dropped onto the main window for the reply being composed. Each copy reports
+/// `attachment-progress`; files that fail validation are reported as `attachment-rejected`.
+fn stage_dropped_files(app: AppHandle, paths: Vec<std::path::PathBuf>) {
+    tauri::async_runtime::spawn_blocking(move || {
+        let outbox = app.state::<OutboxStore>();
+        for path in paths {
+            let staged = outbox.stage(&path, |progress| {
+                let _ = app.emit_all("attachment-progress", progress);
+            });
+            if let Err(rejected) = staged {
+                let _ = app.emit_all("attachment-rejected", rejected);
+            }
+        }
+        let _ = app.emit_all("staged-attachments", outbox.staged());
+    });
+}
+
+/// Attachments waiting to go out with the next reply
+#[tauri::command]
+fn get_staged_attachments(outbox: State<'_, OutboxStore>) -> Vec<StagedAttachment> {
+    outbox.staged()
+}
+
+#[tauri::command]
+fn remove_staged_attachment(
+    outbox: State<'_, OutboxStore>,
+    id: u64,
+) -> Result<Vec<StagedAttachment>, String> {
+    outbox.remove(id)?;
+    Ok(outbox.staged())
+}
+
+#[tauri::command]
+async fn send_reply(
+    app: AppHandle,
+    audit: State<'_, AuditLog>,
+    email_id: String,
+    reply_text: String,
+    options: Option<SendOptions>,
+) -> Result<String, String> {
+    let options = options.unwrap_or_default();
+    let reply_all = options.reply_all;
+    track(&app, "send_reply");
+    let outbox = app.state::<OutboxStore>();
+    let staged = outbox.staged();
+    let attachment_count = options.attachment_count + staged.len() as u32;
+    let mut warnings = Vec::new();
+    if options.check {
+        warnings.extend(compose::pre_send_checks(&reply_text));
+    }
+    if !options.acknowledge_guardrails {
+        let config = app.state::<ConfigStore>().get()?;
+        warnings.extend(app.state::<GeneratedReplies>().check(
+            &email_id,
+            &reply_text,
+            attachment_count,
+            &config.guardrails,
+        ));
+    }
+    // An invented attachment already says what the missing-attachment check would
+    let invented_attachment = warnings
+        .iter()
+        .any(|warning| warning.kind == WarningKind::InventedAttachment);
+    if !options.allow_missing_attachment && !invented_attachment {
+        warnings.extend(compose::missing_attachment_check(
+            &reply_text,
+            attachment_count,
+        ));
+    }
+    if !options.allow_collision {
+        warnings.extend(reply_collision(&app, &email_id).await);
+    }
+    if reply_all {
+        warnings.extend(
+            reply_all_warnings(&app, &email_id)
+                .await
+                .into_iter()
+                .filter(|warning| match warning.kind {
+                    WarningKind::ReplyAll => !options.allow_reply_all,
+                    WarningKind::ExternalRecipients => !options.allow_external,
+                    _ => true,
+                }),
+        );
+    }
+    if !warnings.is_empty() {
+        return Err(compose::blocked_error(&warnings));
+    }
+
+    let attachments: Vec<_> = staged.iter().map(|file| file.path.clone()).collect();
+    let sent = deliver_reply(&app, &email_id, &reply_text, reply_all, &attachments).await;
+    let response = match sent {
+        Ok(response) => response,
+        // Keep the reply rather than dropping it with the error
+        Err(e) => {
+            let reply = app.state::<RetryQueue>().add(
+                &email_id,
+                &reply_text,
+                reply_all,
+                &staged,
+                &e,
+                app.state::<SharedClock>().now(),
+            )?;
+            outbox.clear();
+            let _ = app.emit_all("staged-attachments", outbox.staged());
+            reply_failed(&app, &reply).await;
+            return Err(match reply.retry_at {
+                Some(_) => format!("{}; the reply was saved and will be sent again shortly", e),
+                None => format!("{}; the reply was saved so it can be edited or retried", e),
+            });
+        }
+    };
+    outbox.clear();
+    let _ = app.emit_all("staged-attachments", outbox.staged());
+    app.state::<GeneratedReplies>().forget(&email_id);
+    record_email_activity(
+        &app,
+        ActivityKind::Replied,
+        &email_id,
+        serde_json::json!({ "attachments": staged.len() }),
+    );
+    record_audit(
+        &audit,
+        AuditEntry::new(AuditAction::ReplySent, Origin::User, Some(email_id)).with_details(
+            serde_json::json!({
+                "length": reply_text.len(),
+                "attachments": staged.len(),
+                "reply_all": reply_all
+            }),
+        ),
+    );
+    Ok(response)
+}
+
+/// Look for replies a teammate sent since the email arrived, or since our own last reply.
+/// Only shared mailboxes are checked, and a failed lookup doesn't hold the reply back.
+async fn reply_collision(app: &AppHandle, email_id: &str) -> Option<SendWarning> {
+    let email = app.state::<InboxSnapshot>().get(email_id)?;
+    let shared = app
+        .state::<ConfigStore>()
+        .get()
+        .ok()?
+        .email
+        .shared_mailboxes;
+    if !mailboxes::is_shared(&shared, &email.account) {
+        return None;
+    }
+    let received = dates::parse_received_time(&email.received_time)?;
+    let replies: Vec<ThreadReply> =
+        match backend_json(app, format!("/emails/{}/replies", email_id)).await {
+            Ok(replies) => replies,
+            Err(e) => {
+                log_warn!("Couldn't check for other replies: {}", e);
+                return None;
+            }
+        };
+    let last_own_reply = app
+        .state::<AuditLog>()
+        .query(&AuditFilter {
+            action: Some(AuditAction::ReplySent),
+            email_id: Some(email_id.to_string()),
+            limit: Some(1),
+            ..Default::default()
+        })
+        .ok()
+        .and_then(|entries| entries.last().map(|entry| entry.timestamp));
+    compose::collision_check(&replies, received, last_own_reply)
+}
+
+/// Check who a reply all would reach. The thread's recipients come from the full email; the
+/// organization is `internal_domains`, or else the domains of the synced accounts. When the
+/// recipients can't be checked, the reply all is held back until the user allows it.
+async fn reply_all_warnings(app: &AppHandle, email_id: &str) -> Vec<SendWarning> {
+    let unchecked = |e: String| {
+        log_warn!("Couldn't check reply all recipients: {}", e);
+        vec![SendWarning::new(
+            WarningKind::ReplyAll,
+            "Couldn't check who reply all goes to",
+        )]
+    };
+    let recipients: ThreadRecipients =
+        match backend_json(app, format!("/emails/{}", email_id)).await {
+            Ok(recipients) => recipients,
+            Err(e) => return unchecked(e),
+        };
+    let config = match app.state::<ConfigStore>().get() {
+        Ok(config) => config,
+        Err(e) => return unchecked(e),
+    };
+    let email = config.email;
+    let mut own = email.accounts;
+    if let Some(account) = app
+        .state::<InboxSnapshot>()
+        .get(email_id)
+        .map(|email| email.account)
+        .filter(|account| !account.is_empty())
+    {
+        own.push(account);
+    }
+    let internal = if email.internal_domains.is_empty() {
+        own.iter()
+            .filter_map(|address| address.rsplit_once('@'))
+            .map(|(_, domain)| domain.to_lowercase())
+            .collect()
+    } else {
+        email.internal_domains
+    };
+    compose::reply_all_checks(&recipients, &own, &internal)
+}
+
+/// Post a reply to the backend, to everyone on the original when `reply_all`; `attachments`
+/// are attached as-is
+async fn deliver_reply(
+    app: &AppHandle,
+    email_id: &str,
+    reply_text: &str,
+    reply_all: bool,
+    attachments: &[std::path::PathBuf],
+) -> Result<String, String> {
+    let tracking = app
+        .state::<ConfigStore>()
+        .get()
+        .map(|c| c.tracking)
+        .unwrap_or_default();
+    let body = serde_json::json!({
+        "reply_text": reply_text,
+        "attachments": attachments,
+        "request_read_receipt": tracking.read_receipts,
+        "request_delivery_receipt": tracking.delivery_receipts,
+        "reply_all": reply_all
+    });
+    let shutdown = app.state::<Shutdown>();
+    let Some(_task) = shutdown.task() else {
+        return Err("SERINA is quitting; the reply was not sent".to_string());
+    };
+    backend(
+        app,
+        format!("/emails/{}/reply", email_id),
+        "POST".to_string(),
+        Some(body.to_string()),
+    )
+    .await
+}
+
+// Failed replies
+
+/// Tell the windows, and the user with retry, edit and discard buttons. Called when a reply
+/// first fails and again when automatic retries give up.
+async fn reply_failed(app: &AppHandle, reply: &FailedReply) {
+    let _ = app.emit_all("failed-replies", app.state::<RetryQueue>().list());
+    let (title, body) = {
+        let localizer = app.state::<Localizer>();
+        let body_id = if reply.retry_at.is_some() {
+            "notification-reply-retrying-body"
+        } else {
+            "notification-reply-given-up-body"
+        };
+        (
+            localizer.text("notification-reply-failed-title"),
+            localizer.format(body_id, &[("error", &reply.last_error)]),
+        )
+    };
+    let actions = [
+        EmailAction::RetrySend,
+        EmailAction::EditReply,
+        EmailAction::DiscardReply,
+    ];
+    if let Err(e) = notify_email(app, &reply.email_id, "", &title, &body, &actions).await {
+        log_warn!("Failed to report unsent reply: {}", e);
+    }
+}
+
+/// Try a failed reply again, dropping it once sent
+async fn resend_reply(app: &AppHandle, reply: FailedReply) -> Result<String, String> {
+    let retries = app.state::<RetryQueue>();
+    let result = deliver_reply(
+        app,
+        &reply.email_id,
+        &reply.reply_text,
+        reply.reply_all,
+        &reply.attachments,
+    )
+    .await;
+    match &result {
+        Ok(_) => {
+            retries.remove(&reply.email_id)?;
+            record_audit(
+                &app.state::<AuditLog>(),
+                AuditEntry::new(
+                    AuditAction::ReplySent,
+                    Origin::User,
+                    Some(reply.email_id.clone()),
+                )
+                .with_details(serde_json::json!({
+                    "length": reply.reply_text.len(),
+                    "attachments": reply.attachments.len(),
+                    "attempts": reply.attempts + 1
+                })),
+            );
+            let _ = app.emit_all("reply-resent", &reply.email_id);
+        }
+        Err(e) => {
+            let updated =
+                retries.record_failure(&reply.email_id, e, app.state::<SharedClock>().now())?;
+            if let Some(updated) = updated.filter(|updated| updated.retry_at.is_none()) {
+                reply_failed(app, &updated).await;
+            }
+        }
+    }
+    let _ = app.emit_all("failed-replies", retries.list());
+    result
+}
+
+/// Send replies whose next automatic attempt has come
+async fn retry_due_replies(app: &AppHandle) {
+    let due = app
+        .state::<RetryQueue>()
+        .due(app.state::<SharedClock>().now());
+    for reply in due {
+        if let Err(e) = resend_reply(app, reply).await {
+            log_warn!("Retrying a failed reply failed again: {}", e);
+        }
+    }
+}
+
+#[tauri::command]
+async fn get_failed_replies(retries: State<'_, RetryQueue>) -> Result<Vec<FailedReply>, String> {
+    Ok(retries.list())
+}
+
+/// Send a failed reply now instead of waiting for the next attempt
+#[tauri::command]
+async fn retry_failed_reply(app: AppHandle, email_id: String) -> Result<String, String> {
+    let reply = app
+        .state::<RetryQueue>()
+        .get(&email_id)
+        .ok_or_else(|| "This reply is no longer waiting to be sent".to_string())?;
+    resend_reply(&app, reply).await
+}
+
+/// Put a failed reply back in the compose view, with its attachments staged again
+#[tauri::command]
+async fn edit_failed_reply(app: AppHandle, email_id: String) -> Result<FailedReply, String> {
+    let retries = app.state::<RetryQueue>();
+    let reply = retries
+        .get(&email_id)
+        .ok_or_else(|| "This reply is no longer waiting to be sent".to_string())?;
+    let outbox = app.state::<OutboxStore>();
+    for path in &reply.attachments {
+        if let Err(rejected) = outbox.stage(path, |_| {}) {
+            let _ = app.emit_all("attachment-rejected", &rejected);
+        }
+    }
+    retries.remove(&email_id)?;
+    let _ = app.emit_all("staged-attachments", outbox.staged());
+    let _ = app.emit_all("failed-replies", retries.list());
+    show_main_window(&app);
+    let _ = app.emit_all("open-email", &email_id);
+    let _ = app.emit_all("edit-reply", &reply);
+    Ok(reply)
+}
+
+#[tauri::command]
+async fn discard_failed_reply(app: AppHandle, email_id: String) -> Result<(), String> {
+    let retries = app.state::<RetryQueue>();
+    retries
+        .remove(&email_id)?
+        .ok_or_else(|| "This reply is no longer waiting to be sent".to_string())?;
+    let _ = app.emit_all("failed-replies", retries.list());
+    Ok(())
+}
+
+// Send approvals
+
+#[tauri::command]
+fn list_pending_approvals(app: AppHandle) -> Result<Vec<PendingApproval>, String> {
+    expire_approvals(&app)?;
+    Ok(app.state::<ApprovalQueue>().list())
+}
+
+/// Send an automated reply the user approved, with their edits when given. A failed send is
+/// kept for retry like any other reply.
+#[tauri::command]
+async fn approve(app: AppHandle, id: u64, reply_text: Option<String>) -> Result<String, String> {
+    let approvals = app.state::<ApprovalQueue>();
+    let now = app.state::<SharedClock>().now();
+    let taken = approvals.take(id, now);
+    let _ = app.emit_all("pending-approvals", approvals.list());
+    let pending = taken?;
+    let text = reply_text.unwrap_or(pending.reply_text);
+    match deliver_reply(&app, &pending.email_id, &text, false, &[]).await {
+        Ok(response) => {
+            record_email_activity(
+                &app,
+                ActivityKind::Replied,
+                &pending.email_id,
+                serde_json::json!({ "rule": pending.source, "approved": true }),
+            );
+            record_audit(
+                &app.state::<AuditLog>(),
+                AuditEntry::new(AuditAction::ReplySent, Origin::User, Some(pending.email_id))
+                    .with_details(serde_json::json!({ "rule": pending.source, "approved": true })),
+            );
+            Ok(response)
+        }
+        Err(e) => {
+            let reply =
+                app.state::<RetryQueue>()
+                    .add(&pending.email_id, &text, false, &[], &e, now)?;
+            reply_failed(&app, &reply).await;
+            Err(e)
+        }
+    }
+}
+
+/// Drop an automated reply without sending it
+#[tauri::command]
+fn reject(app: AppHandle, id: u64) -> Result<(), String> {
+    let approvals = app.state::<ApprovalQueue>();
+    approvals
+        .remove(id)?
+        .ok_or_else(|| "This reply is no longer waiting for approval".to_string())?;
+    let _ = app.emit_all("pending-approvals", approvals.list());
+    Ok(())
+}
+
+/// Drop automated replies nobody approved in time
+fn expire_approvals(app: &AppHandle) -> Result<(), String> {
+    let approvals = app.state::<ApprovalQueue>();
+    let expired = approvals.expire(app.state::<SharedClock>().now())?;
+    if !expired.is_empty() {
+        let _ = app.emit_all("pending-approvals", approvals.list());
+    }
+    Ok(())
+}
+
+#[tauri::command]
+async fn mark_email_read(
+    app: AppHandle,
+    audit: State<'_, AuditLog>,
+    email_id: String,
+) -> Result<String, String> {
+    let response = backend(
+        &app,
+        format!("/emails/{}/mark-read", email_id),
+        "POST".to_string(),
+        None,
+    )
+    .await?;
+    record_audit(
+        &audit,
+        AuditEntry::new(AuditAction::MarkedRead, Origin::User, Some(email_id)),
+    );
+    record_triaged(&app);
+    if let Err(e) = update_badge(&app).await {
+        log_warn!("Failed to update badge: {}", e);
+    }
+    Ok(response)
+}
+
+#[tauri::command]
+async fn create_task_from_email(
+    app: AppHandle,
+    audit: State<'_, AuditLog>,
+    email_id: String,
+    title: String,
+    description: String,
+) -> Result<String, String> {
+    let body = serde_json::json!({
+        "title": title,
+        "description": description
+    });
+    let response = backend(
+        &app,
+        format!("/emails/{}/create-task", email_id),
+        "POST".to_string(),
+        Some(body.to_string()),
+    )
+    .await?;
+    record_audit(
+        &audit,
+        AuditEntry::new(AuditAction::TaskCreated, Origin::User, Some(email_id))
+            .with_details(serde_json::json!({ "title": title })),
+    );
+    Ok(response)
+}
+
+/// Busy calendar time between `from` and `to`, in local time
+async fn busy_times(
+    app: &AppHandle,
+    from: chrono::NaiveDateTime,
+    to: chrono::NaiveDateTime,
+) -> Result<Vec<(chrono::NaiveDateTime, chrono::NaiveDateTime)>, String> {
+    let endpoint = format!(
+        "/calendar/busy?{}",
+        url::form_urlencoded::Serializer::new(String::new())
+            .append_pair("start", &from.format("%Y-%m-%dT%H:%M:%S").to_string())
+            .append_pair("end", &to.format("%Y-%m-%dT%H:%M:%S").to_string())
+            .finish()
+    );
+    let response = backend(app, endpoint, "GET".to_string(), None).await?;
+    let blocks: Vec<BusyBlock> = serde_json::from_str(&response).map_err(|e| e.to_string())?;
+    let local = |value: &str| {
+        dates::parse_received_time(value)
+            .map(|time| time.with_timezone(&chrono::Local).naive_local())
+    };
+    Ok(blocks
+        .iter()
+        .filter_map(|block| Some((local(&block.start)?, local(&block.end)?)))
+        .collect())
+}
+
+/// Free slots to offer, leaving out ones offered before
+async fn meeting_slots(
+    app: &AppHandle,
+    duration_minutes: u32,
+    exclude: &[TimeSlot],
+) -> Result<Vec<TimeSlot>, String> {
+    let scheduling = app
+        .state::<ConfigStore>()
+        .get()
+        .unwrap_or_default()
+        .scheduling;
+    let now = app.state::<SharedClock>().local_now();
+    // Weekends don't count as search days, so look twice as far
+    let until = now + chrono::Duration::days(scheduling.days_ahead as i64 * 2 + 1);
+    let busy = busy_times(app, now, until).await?;
+    let slots = calendar::free_slots(
+        &scheduling,
+        &busy,
+        exclude,
+        now,
+        chrono::Duration::minutes(duration_minutes as i64),
+    );
+    if slots.is_empty() {
+        return Err(format!(
+            "No free time in the next {} working days",
+            scheduling.days_ahead
+        ));
+    }
+    Ok(slots)
+}
+
+fn describe_slot(slot: &TimeSlot) -> String {
+    format!(
+        "{}-{}",
+        slot.start.format("%A, %B %-d at %H:%M"),
+        slot.end.format("%H:%M")
+    )
+}
+
+async fn fetch_email(app: &AppHandle, email_id: &str) -> Result<EmailData, String> {
+    let response = backend(
+        app,
+        format!("/emails/{}", email_id),
+        "GET".to_string(),
+        None,
+    )
+    .await?;
+    serde_json::from_str(&response).map_err(|e| e.to_string())
+}
+
+/// Find free times on the calendar and draft a reply offering them
+#[tauri::command]
+async fn propose_meeting_times(
+    app: AppHandle,
+    proposals: State<'_, ProposalStore>,
+    email_id: String,
+    duration_minutes: Option<u32>,
+) -> Result<String, String> {
+    track(&app, "propose_meeting");
+    let email = fetch_email(&app, &email_id).await?;
+    let duration = duration_minutes.unwrap_or_else(|| {
+        app.state::<ConfigStore>()
+            .get()
+            .unwrap_or_default()
+            .scheduling
+            .default_duration_minutes
+    });
+    let slots = meeting_slots(&app, duration, &[]).await?;
+    let times: Vec<String> = slots.iter().map(describe_slot).collect();
+    let instruction = format!(
+        "Propose a {}-minute meeting at one of these times and ask which works best: {}",
+        duration,
+        times.join("; ")
+    );
+    let content = parsing::new_content(&email.body);
+    let output = run_llm(&app, &llm::reply_prompt(&content, &instruction)).await?;
+    proposals.upsert(MeetingProposal {
+        email_id: email.id,
+        sender_email: email.sender_email,
+        subject: email.subject,
+        duration_minutes: duration,
+        slots: slots.clone(),
+        proposed_at: app.state::<SharedClock>().now(),
+    })?;
+    Ok(serde_json::json!({
+        "status": MeetingStatus::Proposed,
+        "slots": slots,
+        "reply": output.text,
+        "redactions": output.redactions
+    })
+    .to_string())
+}
+
+/// Handle an answer to offered times: send an invite for the time they picked, or draft a
+/// reply offering new times when none worked
+#[tauri::command]
+async fn respond_to_meeting_reply(
+    app: AppHandle,
+    audit: State<'_, AuditLog>,
+    proposals: State<'_, ProposalStore>,
+    email_id: String,
+) -> Result<String, String> {
+    let email = fetch_email(&app, &email_id).await?;
+    let proposal = proposals
+        .find_reply(&email.sender_email, &email.subject)
+        .filter(|proposal| proposal.email_id != email.id)
+        .ok_or_else(|| "This email doesn't answer a meeting proposal".to_string())?;
+    let content = parsing::new_content(&email.body);
+    let times: Vec<String> = proposal.slots.iter().map(describe_slot).collect();
+    let choice = run_llm(&app, &llm::slot_choice_prompt(&content, &times)).await?;
+
+    let (status, slots, instruction) =
+        match calendar::parse_slot_choice(&choice.text, proposal.slots.len()) {
+            Some(index) => {
+                let slot = proposal.slots[index];
+                let body = serde_json::json!({
+                    "subject": proposal.subject,
+                    "start": slot.start.format("%Y-%m-%dT%H:%M:%S").to_string(),
+                    "end": slot.end.format("%Y-%m-%dT%H:%M:%S").to_string(),
+                    "attendee": proposal.sender_email
+                });
+                backend(
+                    &app,
+                    "/calendar/meetings".to_string(),
+                    "POST".to_string(),
+                    Some(body.to_string()),
+                )
+                .await?;
+                proposals.remove(&proposal.email_id)?;
+                record_audit(
+                    &audit,
+                    AuditEntry::new(AuditAction::MeetingScheduled, Origin::User, Some(email.id))
+                        .with_details(serde_json::json!({ "start": slot.start })),
+                );
+                let instruction = format!(
+                    "Confirm the meeting on {} and say a calendar invite has been sent",
+                    describe_slot(&slot)
+                );
+                (MeetingStatus::Confirmed, vec![slot], instruction)
+            }
+            None => {
+                let slots = meeting_slots(&app, proposal.duration_minutes, &proposal.slots).await?;
+                let times: Vec<String> = slots.iter().map(describe_slot).collect();
+                proposals.upsert(MeetingProposal {
+                    email_id: email.id,
+                    slots: slots.clone(),
+                    proposed_at: app.state::<SharedClock>().now(),
+                    ..proposal
+                })?;
+                let instruction = format!(
+                    "None of the earlier times worked. Offer these times instead and ask which \
+                     works best: {}",
+                    times.join("; ")
+                );
+                (MeetingStatus::Countered, slots, instruction)
+            }
+        };
+
+    let output = run_llm(&app, &llm::reply_prompt(&content, &instruction)).await?;
+    Ok(serde_json::json!({
+        "status": status,
+        "slots": slots,
+        "reply": output.text,
+        "redactions": output.redactions
+    })
+    .to_string())
+}
+
+/// Schedule a reminder ahead of the deadline found in an email
+#[tauri::command]
+async fn create_reminder_from_email(
+    app: AppHandle,
+    audit: State<'_, AuditLog>,
+    snoozes: State<'_, SnoozeQueue>,
+    email_id: String,
+) -> Result<SnoozedEmail, String> {
+    let response = backend(
+        &app,
+        format!("/emails/{}", email_id),
+        "GET".to_string(),
+        None,
+    )
+    .await?;
+    let mut email: EmailData = serde_json::from_str(&response).map_err(|e| e.to_string())?;
+    deadline::add_deadlines(std::slice::from_mut(&mut email));
+    let deadline = email
+        .deadline
+        .as_deref()
+        .and_then(|d| chrono::DateTime::parse_from_rfc3339(d).ok())
+        .map(|d| d.with_timezone(&chrono::Utc))
+        .ok_or_else(|| "No deadline found in this email".to_string())?;
+
+    let now = app.state::<SharedClock>().now();
+    if deadline <= now {
+        return Err("The deadline in this email has already passed".to_string());
+    }
+    let lead = app
+        .state::<ConfigStore>()
+        .get()
+        .unwrap_or_default()
+        .reminders
+        .deadline_lead_minutes;
+    let until = (deadline - chrono::Duration::minutes(lead as i64)).max(now);
+    let entry = snoozes.schedule(SnoozedEmail {
+        email_id: email_id.clone(),
+        until,
+        deadline: Some(deadline),
+    })?;
+    record_audit(
+        &audit,
+        AuditEntry::new(AuditAction::Snoozed, Origin::User, Some(email_id))
+            .with_details(serde_json::json!({ "deadline": deadline.to_rfc3339() })),
+    );
+    Ok(entry)
+}
+
+#[tauri::command]
+async fn snooze_email(
+    app: AppHandle,
+    audit: State<'_, AuditLog>,
+    snoozes: State<'_, SnoozeQueue>,
+    email_id: String,
+    minutes: Option<u32>,
+) -> Result<String, String> {
+    track(&app, "snooze");
+    let minutes = minutes.unwrap_or(60);
+    let response = backend(
+        &app,
+        format!("/emails/{}/snooze?minutes={}", email_id, minutes),
+        "POST".to_string(),
+        None,
+    )
+    .await?;
+    snoozes.add(&email_id, minutes, app.state::<SharedClock>().now())?;
+    record_audit(
+        &audit,
+        AuditEntry::new(AuditAction::Snoozed, Origin::User, Some(email_id))
+            .with_details(serde_json::json!({ "minutes": minutes })),
+    );
+    Ok(response)
+}
+
+// Muted conversations
+
+/// Patch the windows' lists and tell them the mutes changed
+fn mutes_changed(app: &AppHandle, changed: &[EmailData]) {
+    for email in changed {
+        let _ = app.emit_all("email-updated", email);
+    }
+    let now = app.state::<SharedClock>().now();
+    let _ = app.emit_all("muted-threads-changed", app.state::<MuteList>().list(now));
+}
+
+/// Stop notifications, reminders and priority for a whole conversation, for `minutes` or
+/// until unmuted
+#[tauri::command]
+async fn mute_thread(
+    app: AppHandle,
+    thread_id: String,
+    minutes: Option<u32>,
+) -> Result<MutedThread, String> {
+    track(&app, "mute_thread");
+    let changed = app.state::<InboxSnapshot>().set_muted(&thread_id, true);
+    let subject = changed
+        .first()
+        .map(|email| email.subject.clone())
+        .unwrap_or_default();
+    let muted = app.state::<MuteList>().mute(
+        &thread_id,
+        &subject,
+        minutes,
+        app.state::<SharedClock>().now(),
+    )?;
+    mutes_changed(&app, &changed);
+    Ok(muted)
+}
+
+#[tauri::command]
+async fn unmute_thread(app: AppHandle, thread_id: String) -> Result<(), String> {
+    if !app.state::<MuteList>().unmute(&thread_id)? {
+        return Err("This conversation isn't muted".to_string());
+    }
+    let changed = app.state::<InboxSnapshot>().set_muted(&thread_id, false);
+    mutes_changed(&app, &changed);
+    Ok(())
+}
+
+#[tauri::command]
+async fn get_muted_threads(app: AppHandle) -> Result<Vec<MutedThread>, String> {
+    Ok(app
+        .state::<MuteList>()
+        .list(app.state::<SharedClock>().now()))
+}
+
+// Shared mailboxes
+
+#[tauri::command]
+async fn get_shared_mailboxes(app: AppHandle) -> Result<Vec<SharedMailbox>, String> {
+    Ok(app.state::<ConfigStore>().get()?.email.shared_mailboxes)
+}
+
+/// Take an email in a shared mailbox so teammates see it's being handled. An email someone
+/// else holds is only taken over with `force`.
+#[tauri::command]
+async fn claim_email(
+    app: AppHandle,
+    email_id: String,
+    force: Option<bool>,
+) -> Result<ClaimState, String> {
+    track(&app, "claim_email");
+    let name = mailboxes::claimant(&app.state::<ConfigStore>().get()?.email.display_name)?;
+    change_claim(&app, &email_id, &name, true, force.unwrap_or(false)).await
+}
+
+/// Hand back a claim so the email is free for anyone again
+#[tauri::command]
+async fn release_email(
+    app: AppHandle,
+    email_id: String,
+    force: Option<bool>,
+) -> Result<ClaimState, String> {
+    let name = mailboxes::claimant(&app.state::<ConfigStore>().get()?.email.display_name)?;
+    change_claim(&app, &email_id, &name, false, force.unwrap_or(false)).await
+}
+
+/// Ask the mailbox to record the claim, which refuses when someone else got there first
+async fn change_claim(
+    app: &AppHandle,
+    email_id: &str,
+    name: &str,
+    claiming: bool,
+    force: bool,
+) -> Result<ClaimState, String> {
+    let verb = if claiming { "claim" } else { "release" };
+    let body = serde_json::json!({ "name": name, "force": force });
+    let response = backend(
+        app,
+        format!("/emails/{}/{}", email_id, verb),
+        "POST".to_string(),
+        Some(body.to_string()),
+    )
+    .await?;
+    let state: ClaimState = serde_json::from_str(&response).map_err(|e| e.to_string())?;
+    if let Some(email) = app
+        .state::<InboxSnapshot>()
+        .set_claim(email_id, &state.claimed_by)
+    {
+        let _ = app.emit_all("email-updated", &email);
+    }
+    state.check(name, claiming)?;
+    if state.changed {
+        let action = if claiming {
+            AuditAction::Claimed
+        } else {
+            AuditAction::Released
+        };
+        record_audit(
+            &app.state::<AuditLog>(),
+            AuditEntry::new(action, Origin::User, Some(email_id.to_string()))
+                .with_details(serde_json::json!({ "name": name })),
+        );
+    }
+    Ok(state)
+}
+
+// Pinned emails
+
+/// Tell the windows and rebuild the tray after a pin changes
+fn pins_changed(app: &AppHandle) {
+    let _ = app.emit_all("pins-changed", app.state::<PinStore>().list());
+    refresh_tray_menu(app);
+}
+
+/// Keep an email in the tray's Pinned menu until it's dismissed; lighter than a task
+#[tauri::command]
+async fn pin_email(app: AppHandle, email_id: String) -> Result<Vec<PinnedEmail>, String> {
+    track(&app, "pin");
+    let (email, _) = load_email(&app, &email_id).await?;
+    let pins = app.state::<PinStore>();
+    pins.pin(PinnedEmail {
+        email_id,
+        subject: email.subject,
+        sender: email.sender,
+        pinned_at: app.state::<SharedClock>().now(),
+    })?;
+    pins_changed(&app);
+    Ok(pins.list())
+}
+
+#[tauri::command]
+fn unpin_email(app: AppHandle, email_id: String) -> Result<Vec<PinnedEmail>, String> {
+    let pins = app.state::<PinStore>();
+    if pins.unpin(&email_id)? {
+        pins_changed(&app);
+    }
+    Ok(pins.list())
+}
+
+#[tauri::command]
+fn get_pinned_emails(pins: State<'_, PinStore>) -> Vec<PinnedEmail> {
+    pins.list()
+}
+
+// Keyboard triage commands
+
+/// Load the inbox into the triage list, keeping the current selection where possible
+#[tauri::command]
+async fn triage_start(app: AppHandle, limit: Option<u32>) -> Result<TriageSelection, String> {
+    track(&app, "triage");
+    let limit = limit.unwrap_or(50);
+    let emails = fetch_email_list(&app, format!("/emails?limit={}", limit)).await?;
+    let selection = app
+        .state::<TriageState>()
+        .set_emails(emails.into_iter().map(|e| e.id).collect());
+    let _ = app.emit_all("triage-selection", &selection);
+    Ok(selection)
+}
+
+#[tauri::command]
+async fn get_triage_selection(state: State<'_, TriageState>) -> Result<TriageSelection, String> {
+    Ok(state.selection())
+}
+
+#[tauri::command]
+async fn triage_select(app: AppHandle, email_id: String) -> Result<TriageSelection, String> {
+    let selection = app.state::<TriageState>().select(&email_id)?;
+    let _ = app.emit_all("triage-selection", &selection);
+    Ok(selection)
+}
+
+/// Run a triage action on the selected email; the same entry point serves in-app and global shortcuts
+#[tauri::command]
+async fn triage(app: AppHandle, action: TriageAction) -> Result<TriageSelection, String> {
+    run_triage_action(&app, action).await
+}
+
+async fn run_triage_action(
+    app: &AppHandle,
+    action: TriageAction,
+) -> Result<TriageSelection, String> {
+    let state = app.state::<TriageState>();
+    let current = state.selection().email_id;
+    let selection = match (action, current) {
+        (TriageAction::Next, _) => state.step(true),
+        (TriageAction::Previous, _) => state.step(false),
+        (_, None) => return Err("No email selected".to_string()),
+        (TriageAction::Archive, Some(email_id)) => {
+            backend(
+                app,
+                format!("/emails/{}/archive", email_id),
+                "POST".to_string(),
+                None,
+            )
+            .await?;
+            record_audit(
+                &app.state::<AuditLog>(),
+                AuditEntry::new(AuditAction::Archived, Origin::User, Some(email_id.clone())),
+            );
+            record_triaged(app);
+            state.remove(&email_id)
+        }
+        (TriageAction::Snooze, Some(email_id)) => {
+            let minutes = app
+                .state::<ConfigStore>()
+                .get()
+                .map(|c| c.triage.snooze_minutes)
+                .unwrap_or(60);
+            snooze_email(
+                app.clone(),
+                app.state::<AuditLog>(),
+                app.state::<SnoozeQueue>(),
+                email_id.clone(),
+                Some(minutes),
+            )
+            .await?;
+            record_triaged(app);
+            state.remove(&email_id)
+        }
+        (TriageAction::Reply, Some(_)) => {
+            // Composing needs the main window, even when triggered by a global shortcut
+            show_main_window(app);
+            let selection = state.selection();
+            let _ = app.emit_all("triage-reply", &selection);
+            selection
+        }
+    };
+    let _ = app.emit_all("triage-selection", &selection);
+    Ok(selection)
+}
+
+/// Bind the configured system-wide triage shortcuts
+fn register_triage_shortcuts(app: &AppHandle) {
+    let shortcuts = app
+        .state::<ConfigStore>()
+        .get()
+        .map(|c| c.triage.global_shortcuts)
+        .unwrap_or_default();
+    let mut manager = app.global_shortcut_manager();
+    for (action, accelerator) in shortcuts {
+        let handle = app.clone();
+        let result = manager.register(&accelerator, move || {
+            let app = handle.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = run_triage_action(&app, action).await {
+                    log_warn!("Triage shortcut failed: {}", e);
+                }
+            });
+        });
+        if let Err(e) = result {
+            log_warn!("Could not register shortcut {}: {}", accelerator, e);
+        }
+    }
+}
+
+#[tauri::command]
+async fn get_sent_emails(app: AppHandle, limit: Option<u32>) -> Result<String, String> {
+    let limit = limit.unwrap_or(20);
+    backend(
+        &app,
+        format!("/sent?limit={}", limit),
+        "GET".to_string(),
+        None,
+    )
+    .await
+}
+
+#[tauri::command]
+async fn get_outbox(app: AppHandle) -> Result<String, String> {
+    backend(&app, "/outbox".to_string(), "GET".to_string(), None).await
+}
+
+#[tauri::command]
+async fn retry_outbox_item(app: AppHandle, email_id: String) -> Result<String, String> {
+    backend(
+        &app,
+        format!("/outbox/{}/retry", email_id),
+        "POST".to_string(),
+        None,
+    )
+    .await
+}
+
+#[tauri::command]
+async fn delete_outbox_item(app: AppHandle, email_id: String) -> Result<String, String> {
+    backend(
+        &app,
+        format!("/outbox/{}", email_id),
+        "DELETE".to_string(),
+        None,
+    )
+    .await
+}
+
+/// Delivery/read status of a reply, by the `message_id` returned from `send_reply`
+#[tauri::command]
+async fn get_sent_status(app: AppHandle, message_id: String) -> Result<String, String> {
+    backend(
+        &app,
+        format!("/sent/{}/status", message_id),
+        "GET".to_string(),
+        None,
+    )
+    .await
+}
+
+/// Tracking status of recent replies, for the sent-items tracking view
+#[tauri::command]
+async fn get_tracked_sent(app: AppHandle, limit: Option<u32>) -> Result<String, String> {
+    let limit = limit.unwrap_or(50);
+    backend(
+        &app,
+        format!("/sent/tracked?limit={}", limit),
+        "GET".to_string(),
+        None,
+    )
+    .await
+}
+
+/// Move a misfiled email from the junk folder back to the inbox
+#[tauri::command]
+async fn not_spam(
+    app: AppHandle,
+    audit: State<'_, AuditLog>,
+    email_id: String,
+) -> Result<String, String> {
+    let response = backend(
+        &app,
+        format!("/emails/{}/not-spam", email_id),
+        "POST".to_string(),
+        None,
+    )
+    .await?;
+    record_audit(
+        &audit,
+        AuditEntry::new(AuditAction::MarkedNotSpam, Origin::User, Some(email_id)),
+    );
+    Ok(response)
+}
+
+#[tauri::command]
+async fn get_unread_count(app: AppHandle) -> Result<String, String> {
+    backend(
+        &app,
+        "/emails/unread-count".to_string(),
+        "GET".to_string(),
+        None,
+    )
+    .await
+}
+
+// Telemetry commands
+
+fn telemetry_enabled(app: &AppHandle) -> bool {
+    app.state::<ConfigStore>()
+        .get()
+        .is_ok_and(|config| config.telemetry.enabled)
+}
+
+/// Count a feature use, when the user opted in
+fn track(app: &AppHandle, feature: &'static str) {
+    if telemetry_enabled(app) {
+        app.state::<Telemetry>().record_feature(feature);
+    }
+}
+
+/// Count an error by category, when the user opted in
+fn track_error(app: &AppHandle, error: &str) {
+    if telemetry_enabled(app) {
+        app.state::<Telemetry>().record_error(error);
+    }
+}
+
+/// Exactly what the next telemetry upload would send
+#[tauri::command]
+async fn get_telemetry_preview(telemetry: State<'_, Telemetry>) -> Result<TelemetryBatch, String> {
+    Ok(telemetry.preview())
+}
+
+/// Opt in or out; opting out also drops anything still queued
+#[tauri::command]
+async fn set_telemetry_enabled(app: AppHandle, enabled: bool) -> Result<bool, String> {
+    let mut raw = app.state::<ConfigStore>().raw()?;
+    raw["telemetry"]["enabled"] = serde_json::Value::Bool(enabled);
+    app.state::<ConfigStore>().save_raw(&raw)?;
+    if !enabled {
+        app.state::<Telemetry>().clear();
+    }
+    Ok(enabled)
+}
+
+/// Post queued counts during maintenance, keeping them for next time if that fails
+async fn send_telemetry(app: &AppHandle, config: &AppConfig) -> Result<(), String> {
+    let endpoint = config.telemetry.endpoint.trim();
+    if !config.telemetry.enabled || endpoint.is_empty() {
+        return Ok(());
+    }
+    privacy::ensure_allowed(endpoint, config.privacy_mode)?;
+    let Some(batch) = app.state::<Telemetry>().take() else {
+        return Ok(());
+    };
+    let result = telemetry::upload(&http_client(app).await?, endpoint, &batch).await;
+    if result.is_err() {
+        app.state::<Telemetry>().restore(batch);
+    }
+    result
+}
+
+// Audit log commands
+
+/// Record a completed action; a failed audit write never fails the action itself
+fn record_audit(audit: &AuditLog, entry: AuditEntry) {
+    if let Err(e) = audit.append(&entry) {
+        log_error!("Failed to write audit log: {}", e);
+    }
+}
+
+/// Add to the activity feed and push the event to open timelines
+fn record_activity(
+    app: &AppHandle,
+    kind: ActivityKind,
+    email_id: Option<&str>,
+    title: &str,
+    details: serde_json::Value,
+) {
+    match app
+        .state::<ActivityLog>()
+        .append(kind, email_id, title, details)
+    {
+        Ok(Some(event)) => {
+            let _ = app.emit_all("activity", &event);
+        }
+        Ok(None) => {}
+        Err(e) => log_warn!("Failed to write activity feed: {}", e),
+    }
+}
+
+/// `record_activity` titled with the email's subject, when it's still in the inbox
+fn record_email_activity(
+    app: &AppHandle,
+    kind: ActivityKind,
+    email_id: &str,
+    details: serde_json::Value,
+) {
+    let subject = app
+        .state::<InboxSnapshot>()
+        .get(email_id)
+        .map(|email| email.subject)
+        .unwrap_or_default();
+    record_activity(app, kind, Some(email_id), &subject, details);
+}
+
+/// The timeline, newest first. Pass the previous page's `next_cursor` to load older events.
+#[tauri::command]
+async fn get_activity_feed(
+    activity: State<'_, ActivityLog>,
+    cursor: Option<u64>,
+    limit: Option<usize>,
+) -> Result<ActivityPage, String> {
+    Ok(activity.page(cursor, limit.unwrap_or(activity::PAGE_SIZE)))
+}
+
+/// Open (or raise) the activity timeline
+#[tauri::command]
+async fn show_activity_window(app: AppHandle) -> Result<(), String> {
+    let window = match app_window(&app, AppWindow::Activity) {
+        Ok(window) => window,
+        Err(_) => build_window(
+            &app,
+            AppWindow::Activity,
+            AppWindow::Activity.url(&[]),
+            None,
+        )?,
+    };
+    window.show().map_err(|e| e.to_string())?;
+    window.set_focus().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn get_audit_log(
+    audit: State<'_, AuditLog>,
+    filters: Option<AuditFilter>,
+) -> Result<Vec<AuditEntry>, String> {
+    audit.query(&filters.unwrap_or_default())
+}
+
+#[tauri::command]
+async fn export_audit_log(
+    audit: State<'_, AuditLog>,
+    path: String,
+    filters: Option<AuditFilter>,
+) -> Result<usize, String> {
+    audit.export(&filters.unwrap_or_default(), std::path::Path::new(&path))
+}
+
+// Template commands
+
+#[tauri::command]
+async fn list_templates(templates: State<'_, TemplateStore>) -> Result<Vec<Template>, String> {
+    Ok(templates.list())
+}
+
+#[tauri::command]
+async fn save_template(
+    templates: State<'_, TemplateStore>,
+    template: Template,
+) -> Result<Template, String> {
+    templates.save(template)
+}
+
+#[tauri::command]
+async fn delete_template(
+    templates: State<'_, TemplateStore>,
+    template_id: String,
+) -> Result<bool, String> {
+    templates.delete(&template_id)
+}
+
+/// Render a template for the compose flow, filling sender variables from `email_id` when given
+#[tauri::command]
+async fn render_template(
+    app: AppHandle,
+    templates: State<'_, TemplateStore>,
+    template_id: String,
+    email_id: Option<String>,
+) -> Result<String, String> {
+    let template = templates
+        .get(&template_id)
+        .ok_or_else(|| format!("Template not found: {}", template_id))?;
+    let config = app.state::<ConfigStore>().get()?;
+
+    let vars = match email_id {
+        Some(id) => {
+            let response =
+                backend(&app, format!("/emails/{}", id), "GET".to_string(), None).await?;
+            let email: EmailData = serde_json::from_str(&response).map_err(|e| e.to_string())?;
+            templates::vars_for_email(&email, &config.email.display_name)
+        }
+        None => {
+            let mut vars = templates::TemplateVars::new();
+            vars.insert("my_name".to_string(), config.email.display_name.clone());
+            vars
+        }
+    };
+    Ok(templates::render(&template.body, &vars))
+}
+
+/// Save a reply rendered from a template as an Outlook draft, for the user to review and send
+#[tauri::command]
+async fn draft_template_reply(
+    app: AppHandle,
+    templates: State<'_, TemplateStore>,
+    template_id: String,
+    email_id: String,
+) -> Result<String, String> {
+    let reply_text =
+        render_template(app.clone(), templates, template_id, Some(email_id.clone())).await?;
+    let body = serde_json::json!({ "reply_text": reply_text });
+    backend(
+        &app,
+        format!("/emails/{}/draft-reply", email_id),
+        "POST".to_string(),
+        Some(body.to_string()),
+    )
+    .await
+}
+
+// LLM commands
+
+/// LLM output plus a report of what was masked before the request left the machine
+struct LlmOutput {
+    text: String,
+    redactions: Vec<Redaction>,
+}
+
+/// Run a prompt through the configured provider chain and report which provider served it
+async fn run_llm(app: &AppHandle, prompt: &llm::Prompt) -> Result<LlmOutput, String> {
+    track(app, prompt.feature.key());
+    let config = app.state::<ConfigStore>().get()?;
+    let limits = &config.llm.limits;
+    let budget = app.state::<Budget>();
+
+    loop {
+        match budget.check(limits) {
+            Ok(()) => break,
+            Err(BudgetError::RateLimited(wait)) => tokio::time::sleep(wait).await,
+            Err(err) => {
+                let _ = app.emit_all("budget-exceeded", budget.usage());
+                return Err(err.to_string());
+            }
+        }
+    }
+
+    // Only cloud providers get redacted content; a local model sees the original
+    let chain = llm::chain_for(&config, prompt.feature)?;
+    let redacted = if config.llm.redaction.enabled && chain.iter().any(|p| !p.is_local()) {
+        redact::redact(&prompt.user, &config.llm.redaction)?
+    } else {
+        Redacted {
+            text: prompt.user.clone(),
+            redactions: Vec::new(),
+        }
+    };
+    let prompt = llm::Prompt {
+        user: redacted.text,
+        ..prompt.clone()
+    };
+
+    let completion = match app.try_state::<MockBackend>() {
+        Some(mock) => mock.complete(&prompt),
+        None => llm::complete(&http_client(app).await?, &chain, &prompt)
+            .await
+            .inspect_err(|e| track_error(app, e))?,
+    };
+    let _ = app.emit_all("llm-provider-used", &completion.route);
+
+    if budget.record(limits, completion.tokens) {
+        let _ = app.emit_all("budget-exceeded", budget.usage());
+    }
+    Ok(LlmOutput {
+        text: redact::restore(&completion.text, &redacted.redactions),
+        redactions: redacted.redactions,
+    })
+}
+
+#[tauri::command]
+async fn summarize_email(
+    app: AppHandle,
+    email_content: String,
+    language: Option<String>,
+    email_id: Option<String>,
+) -> Result<String, String> {
+    if let Some(language) = &language {
+        validate_language(language)?;
+    }
+    let language = language.as_deref();
+    let config = app.state::<ConfigStore>().get()?;
+    // Quoted history and signatures would crowd out what this message actually says
+    let email_content = parsing::new_content(&email_content);
+
+    // Over budget: serve a previously generated summary instead of failing
+    let cache = app.state::<SummaryCache>();
+    if app.state::<Budget>().is_exhausted(&config.llm.limits) {
+        if let Some(summary) = cache.get(&email_content, language) {
+            return Ok(serde_json::json!({ "summary": summary, "cached": true }).to_string());
+        }
+    }
+
+    // Emails longer than the context window are summarized in chunks, then merged
+    let chain = llm::chain_for(&config, llm::Feature::Summarize)?;
+    let model = chain.first().map(llm::resolve_model).unwrap_or_default();
+    let chunks = tokens::chunk_text(&email_content, &model, tokens::input_budget(&chain));
+
+    let output = if chunks.len() <= 1 {
+        run_llm(&app, &llm::summary_prompt(&email_content, language)).await?
+    } else {
+        let mut partials = Vec::with_capacity(chunks.len());
+        let mut redactions = Vec::new();
+        for chunk in &chunks {
+            let partial = run_llm(&app, &llm::summary_prompt(chunk, language)).await?;
+            redactions.extend(partial.redactions);
+            partials.push(partial.text);
+        }
+        let mut merged = run_llm(&app, &llm::merge_summaries_prompt(&partials, language)).await?;
+        merged.redactions.extend(redactions);
+        merged
+    };
+    cache.insert(&email_content, language, output.text.clone());
+    if let Some(email_id) = &email_id {
+        record_email_activity(
+            &app,
+            ActivityKind::Summarized,
+            email_id,
+            serde_json::json!({}),
+        );
+    }
+    Ok(serde_json::json!({
+        "summary": output.text,
+        "redactions": output.redactions
+    })
+    .to_string())
+}
+
+/// Deduplicated action items from recent emails, for task creation and the morning briefing
+#[tauri::command]
+async fn extract_action_items(
+    app: AppHandle,
+    scope: Option<ActionScope>,
+) -> Result<Vec<ActionItem>, String> {
+    let scope = scope.unwrap_or_default();
+    let clock = app.state::<SharedClock>();
+    let now = clock.now();
+    let emails: Vec<EmailData> = app
+        .state::<EmailCache>()
+        .recent(actions::MAX_SCANNED)?
+        .into_iter()
+        .filter(|email| scope.includes(email, now))
+        .collect();
+    let today = clock.local_now().format("%Y-%m-%d (%A)").to_string();
+
+    let mut items = Vec::new();
+    for batch in emails.chunks(actions::EMAILS_PER_PROMPT) {
+        let output = run_llm(&app, &llm::action_items_prompt(batch, &today)).await?;
+        items.extend(actions::parse_action_items(&output.text, batch)?);
+    }
+    Ok(actions::dedupe(items))
+}
+
+/// Shipment, order and flight details for an email: the sender's schema.org markup when
+/// there is any, otherwise the LLM for emails that look transactional
+#[tauri::command]
+async fn extract_email_data(
+    app: AppHandle,
+    email_id: String,
+) -> Result<Vec<TransactionalData>, String> {
+    let response = backend(
+        &app,
+        format!("/emails/{}", email_id),
+        "GET".to_string(),
+        None,
+    )
+    .await?;
+    let value: serde_json::Value = serde_json::from_str(&response).map_err(|e| e.to_string())?;
+    let found = extract::from_schema_org(value["html_body"].as_str().unwrap_or_default());
+    if !found.is_empty() {
+        return Ok(found);
+    }
+
+    let email: EmailData = serde_json::from_value(value).map_err(|e| e.to_string())?;
+    let content = parsing::new_content(&email.body);
+    if !extract::looks_transactional(&email.subject, &content) {
+        return Ok(Vec::new());
+    }
+    let output = run_llm(&app, &llm::extraction_prompt(&content, &email.subject)).await?;
+    extract::parse_extraction(&output.text)
+}
+
+/// The body split into new content, quoted history and signature, for collapsing quotes
+#[tauri::command]
+fn get_email_segments(body: String) -> Vec<BodySegment> {
+    parsing::split_body(&body)
+}
+
+#[tauri::command]
+async fn generate_task_from_email(app: AppHandle, email_content: String) -> Result<String, String> {
+    let prompt = llm::task_prompt(
+        &parsing::new_content(&email_content),
+        &llm::guess_subject(&email_content),
+    );
+    let output = run_llm(&app, &prompt).await?;
+    let task = llm::parse_task(&output.text);
+    Ok(serde_json::json!({
+        "title": task.title,
+        "description": task.description,
+        "redactions": output.redactions
+    })
+    .to_string())
+}
+
+#[tauri::command]
+async fn generate_reply(
+    app: AppHandle,
+    email_content: String,
+    instruction: Option<String>,
+    options: Option<ReplyOptions>,
+    email_id: Option<String>,
+) -> Result<String, String> {
+    let options = options.unwrap_or_default();
+    options.validate()?;
+    let instruction = options.to_instruction(&instruction.unwrap_or_default());
+    let email_content = parsing::new_content(&email_content);
+    let output = run_llm(&app, &llm::reply_prompt(&email_content, &instruction)).await?;
+    // A language the user picked wins over the email's
+    let same_language = options
+        .language
+        .as_deref()
+        .is_none_or(|language| language.trim().eq_ignore_ascii_case(llm::AUTO_LANGUAGE));
+    let source = ReplySource {
+        original: &email_content,
+        same_language,
+        attachment_count: 0,
+    };
+    let config = app.state::<ConfigStore>().get()?;
+    let warnings = guardrails::check_reply(&output.text, &source, &config.guardrails);
+    // The send path checks the reply again, as the user left it
+    if let Some(email_id) = &email_id {
+        app.state::<GeneratedReplies>()
+            .remember(email_id, &email_content, same_language);
+    }
+    Ok(serde_json::json!({
+        "reply": output.text,
+        "warnings": warnings,
+        "redactions": output.redactions
+    })
+    .to_string())
+}
+
+#[tauri::command]
+async fn translate_text(
+    app: AppHandle,
+    text: String,
+    target_lang: String,
+) -> Result<String, String> {
+    if text.trim().is_empty() {
+        return Err("Nothing to translate".to_string());
+    }
+    validate_language(&target_lang)?;
+    let output = run_llm(&app, &llm::translate_prompt(&text, &target_lang)).await?;
+    Ok(serde_json::json!({
+        "translation": output.text,
+        "target_language": target_lang.trim(),
+        "redactions": output.redactions
+    })
+    .to_string())
+}
+
+#[tauri::command]
+async fn proofread_text(app: AppHandle, text: String) -> Result<String, String> {
+    if text.trim().is_empty() {
+        return Err("Nothing to proofread".to_string());
+    }
+    let output = run_llm(&app, &llm::proofread_prompt(&text)).await?;
+    let corrections = llm::parse_corrections(&output.text)?;
+    Ok(serde_json::json!({
+        "corrections": corrections,
+        "redactions": output.redactions
+    })
+    .to_string())
+}
+
+#[tauri::command]
+async fn count_tokens(
+    app: AppHandle,
+    text: String,
+    model: Option<String>,
+) -> Result<usize, String> {
+    let model = match model {
+        Some(model) => model,
+        None => {
+            let config = app.state::<ConfigStore>().get()?;
+            config
+                .llm
+                .provider_chain()
+                .first()
+                .map(llm::resolve_model)
+                .unwrap_or_else(|| config.llm.model.clone())
+        }
+    };
+    Ok(tokens::count_tokens(&text, &model))
+}
+
+#[tauri::command]
+async fn get_llm_usage(budget: State<'_, Budget>) -> Result<UsageRecord, String> {
+    Ok(budget.usage())
+}
+
+// Configuration commands
+
+#[tauri::command]
+async fn get_config(app: AppHandle) -> Result<String, String> {
+    backend(&app, "/config".to_string(), "GET".to_string(), None).await
+}
+
+#[tauri::command]
+async fn save_config(app: AppHandle, config: serde_json::Value) -> Result<String, String> {
+    let body = serde_json::json!({
+        "config": config
+    });
+    let response = backend(
+        &app,
+        "/config".to_string(),
+        "POST".to_string(),
+        Some(body.to_string()),
+    )
+    .await?;
+    app.state::<ConfigStore>().reload();
+    Ok(response)
+}
+
+/// Write config and templates, minus credentials, to a portable JSON file
+#[tauri::command]
+async fn export_settings(
+    app: AppHandle,
+    templates: State<'_, TemplateStore>,
+    path: String,
+) -> Result<(), String> {
+    SettingsBundle::new(app.state::<ConfigStore>().raw()?, templates.local())
+        .write(std::path::Path::new(&path))
+}
+
+#[tauri::command]
+async fn import_settings(app: AppHandle, path: String) -> Result<(), String> {
+    import_bundle(&app, std::path::Path::new(&path))
+}
+
+/// Apply a settings bundle, keeping this machine's credentials
+fn import_bundle(app: &AppHandle, path: &std::path::Path) -> Result<(), String> {
+    let bundle = SettingsBundle::read(path)?;
+    let mut imported = bundle.config;
+    settings::restore_secrets(&mut imported, &app.state::<ConfigStore>().raw()?);
+    config::parse_config(&imported.to_string())?;
+
+    app.state::<ConfigStore>().save_raw(&imported)?;
+    app.state::<TemplateStore>().replace_all(bundle.templates)?;
+    let _ = app.emit_all("settings-imported", path.display().to_string());
+    Ok(())
+}
+
+#[tauri::command]
+async fn health_check(app: AppHandle) -> Result<String, String> {
+    backend(&app, "/health".to_string(), "GET".to_string(), None).await
+}
+
+// Automation rules
+
+/// JSON Schema of triggers, conditions and actions, for the rule editor
+#[tauri::command]
+fn get_rule_schema() -> serde_json::Value {
+    rules::schema()
+}
+
+#[tauri::command]
+async fn list_rules(rules: State<'_, RuleStore>) -> Result<Vec<Rule>, String> {
+    Ok(rules.list())
+}
+
+#[tauri::command]
+async fn save_rule(rules: State<'_, RuleStore>, rule: Rule) -> Result<Rule, String> {
+    rules.save(rule)
+}
+
+#[tauri::command]
+async fn delete_rule(rules: State<'_, RuleStore>, rule_id: String) -> Result<bool, String> {
+    rules.delete(&rule_id)
+}
+
+/// Check a rule while it's being edited; an empty list means it can be saved
+#[tauri::command]
+fn validate_rule(rule: Rule) -> Vec<RuleIssue> {
+    rules::validate(&rule)
+}
+
+/// Dry run: which conditions an email meets and what the rule would do to it
+#[tauri::command]
+async fn simulate_rule(app: AppHandle, rule: Rule, email_id: String) -> Result<Simulation, String> {
+    let (email, _) = load_email(&app, &email_id).await?;
+    Ok(rules::simulate(&rule, &email))
+}
+
+/// Run a saved rule over the cached inbox now. Returns how many emails it acted on.
+#[tauri::command]
+async fn run_rule(
+    app: AppHandle,
+    rule_id: String,
+    confirmation: Option<String>,
+) -> Result<usize, String> {
+    let rule = app
+        .state::<RuleStore>()
+        .get(&rule_id)
+        .ok_or_else(|| "This rule no longer exists".to_string())?;
+    let emails = app.state::<EmailCache>().recent(rules::MAX_SCANNED)?;
+    if rule.actions.contains(&RuleAction::Delete) {
+        let doomed: Vec<String> = emails
+            .iter()
+            .filter(|email| rule.applies_to(email))
+            .map(|email| email.id.clone())
+            .collect();
+        app.state::<Confirmations>().require(
+            confirmation.as_deref(),
+            DestructiveAction::DeleteEmails,
+            &doomed,
+            std::time::Instant::now(),
+        )?;
+    }
+    let mut applied = 0;
+    for email in emails.iter().filter(|email| rule.applies_to(email)) {
+        apply_rule(&app, &rule, email, true).await?;
+        applied += 1;
+    }
+    Ok(applied)
+}
+
+// Scripts
+
+#[tauri::command]
+async fn list_scripts(scripts: State<'_, ScriptStore>) -> Result<Vec<Script>, String> {
+    Ok(scripts.list())
+}
+
+/// Save a script; one that doesn't compile is rejected with the syntax error
+#[tauri::command]
+async fn save_script(scripts: State<'_, ScriptStore>, script: Script) -> Result<Script, String> {
+    scripts.save(script)
+}
+
+#[tauri::command]
+async fn delete_script(scripts: State<'_, ScriptStore>, script_id: String) -> Result<bool, String> {
+    scripts.delete(&script_id)
+}
+
+/// Dry run: what a script would do to an email, with its log output
+#[tauri::command]
+async fn test_script(
+    app: AppHandle,
+    source: String,
+    email_id: String,
+) -> Result<ScriptRun, String> {
+    let (email, _) = load_email(&app, &email_id).await?;
+    tauri::async_runtime::spawn_blocking(move || scripts::run(&source, &email))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+// Team sync commands
+
+#[tauri::command]
+async fn get_team_sync_status(app: AppHandle) -> Result<TeamSyncStatus, String> {
+    let enabled = app.state::<ConfigStore>().get()?.team_sync.is_enabled();
+    Ok(app.state::<TeamStore>().status(enabled))
+}
+
+/// Pull the team's rules and templates without waiting for the next scheduled pull
+#[tauri::command]
+async fn sync_team_now(app: AppHandle) -> Result<TeamSyncStatus, String> {
+    let config = app.state::<ConfigStore>().get()?;
+    if !config.team_sync.is_enabled() {
+        return Err("Enter the address of your team's rules first".to_string());
+    }
+    if let Err(e) = pull_team_bundle(&app, &config).await {
+        app.state::<TeamStore>().set_error(e.clone());
+        return Err(e);
+    }
+    Ok(app.state::<TeamStore>().status(true))
+}
+
+// Feature flag commands
+
+#[tauri::command]
+async fn is_feature_enabled(flags: State<'_, FeatureFlags>, name: String) -> Result<bool, String> {
+    Ok(flags.is_enabled(&name))
+}
+
+#[tauri::command]
+async fn list_feature_flags(
+    flags: State<'_, FeatureFlags>,
+) -> Result<BTreeMap<String, FlagState>, String> {
+    Ok(flags.all())
+}
+
+/// Override a flag for this user; `None` goes back to the remote or default value
+#[tauri::command]
+async fn set_feature_flag(
+    flags: State<'_, FeatureFlags>,
+    name: String,
+    enabled: Option<bool>,
+) -> Result<(), String> {
+    flags.set_override(&name, enabled)
+}
+
+// Localization commands
+
+#[tauri::command]
+async fn get_locale(localizer: State<'_, Localizer>) -> Result<LocaleInfo, String> {
+    Ok(localizer.info())
+}
+
+/// Relative ("2 hours ago") and absolute forms of an email's `received_time` in the user's language
+#[tauri::command]
+async fn format_received_time(
+    localizer: State<'_, Localizer>,
+    received_time: String,
+) -> Result<FormattedTime, String> {
+    Ok(dates::format_received_time(&localizer, &received_time))
+}
+
+/// Switch the language of Rust-side text and remember it; an empty locale follows the OS
+#[tauri::command]
+async fn set_locale(
+    app: AppHandle,
+    localizer: State<'_, Localizer>,
+    locale: String,
+) -> Result<LocaleInfo, String> {
+    localizer.set_locale(&locale);
+    let mut raw = app.state::<ConfigStore>().raw()?;
+    raw["ui"]["locale"] = serde_json::Value::String(locale);
+    app.state::<ConfigStore>().save_raw(&raw)?;
+
+    refresh_tray_menu(&app);
+    let info = localizer.info();
+    let _ = app.emit_all("locale-changed", &info);
+    Ok(info)
+}
+
+/// Pull remote flag values, if a flag URL is configured
+async fn refresh_remote_flags(app: &AppHandle, config: &AppConfig) -> Result<(), String> {
+    let url = config.flags.remote_url.trim();
+    if url.is_empty() {
+        return Ok(());
+    }
+    privacy::ensure_allowed(url, config.privacy_mode)?;
+    let client = http_client(app).await?;
+    let response = client.send(client.get(url)).await?;
+    if !response.status().is_success() {
+        return Err(format!("HTTP {}", response.status()));
+    }
+    let body = response.text().await.map_err(|e| e.to_string())?;
+    app.state::<FeatureFlags>()
+        .set_remote(flags::parse_remote(&body)?);
+    Ok(())
+}
+
+// Update commands
+
+/// Ask the configured release channel for a newer version; signatures are checked
+/// against the `pubkey` in tauri.conf.json when the update is installed
+async fn find_update(
+    app: &AppHandle,
+) -> Result<
+    (
+        UpdateInfo,
+        Option<tauri::updater::UpdateResponse<tauri::Wry>>,
+    ),
+    String,
+> {
+    let config = app.state::<ConfigStore>().get()?;
+    if !updater_enabled(app) {
+        return Err(app.state::<Localizer>().text("error-updates-off"));
+    }
+    let channel = config.updates.channel;
+    let endpoint = update::endpoint(channel);
+    privacy::ensure_allowed(&endpoint, config.privacy_mode)?;
+    let result = tauri::updater::builder(app.clone())
+        .endpoints(&[endpoint])
+        .should_install(move |current, release| {
+            release.version() > current && update::accepts(channel, &release.version().to_string())
+        })
+        .skip_events()
+        .check()
+        .await;
+
+    let current_version = app.package_info().version.to_string();
+    match result {
+        Ok(response) => {
+            let info = UpdateInfo {
+                available: response.is_update_available(),
+                current_version,
+                latest_version: response.latest_version().to_string(),
+                notes: response.body().cloned(),
+                channel,
+            };
+            Ok((info, Some(response)))
+        }
+        Err(tauri::updater::Error::UpToDate) => Ok((
+            UpdateInfo {
+                available: false,
+                latest_version: current_version.clone(),
+                current_version,
+                notes: None,
+                channel,
+            },
+            None,
+        )),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+#[tauri::command]
+async fn check_for_updates(app: AppHandle) -> Result<UpdateInfo, String> {
+    find_update(&app).await.map(|(info, _)| info)
+}
+
+/// Download, verify and install the latest release, then restart
+#[tauri::command]
+async fn install_update(app: AppHandle) -> Result<(), String> {
+    let (info, response) = find_update(&app).await?;
+    let response = response
+        .filter(|_| info.available)
+        .ok_or_else(|| app.state::<Localizer>().text("error-no-update"))?;
+    response
+        .download_and_install()
+        .await
+        .map_err(|e| e.to_string())?;
+    app.restart();
+    Ok(())
+}
+
+/// Whether this build can verify updates: the updater is on and has a signing key
+fn updater_enabled(app: &AppHandle) -> bool {
+    let updater = &app.config().tauri.updater;
+    updater.active && !updater.pubkey.trim().is_empty()
+}
+
+/// Background check that announces a new release through the notification module. Skipped
+/// without a word when this build can't update or privacy mode blocks the release server.
+async fn announce_update(app: &AppHandle) -> Result<(), String> {
+    let config = app.state::<ConfigStore>().get()?;
+    let endpoint = update::endpoint(config.updates.channel);
+    if !updater_enabled(app) || privacy::ensure_allowed(&endpoint, config.privacy_mode).is_err() {
+        return Ok(());
+    }
+    let (info, _) = find_update(app).await?;
+    if info.available {
+        let _ = app.emit_all("update-available", &info);
+        let localizer = app.state::<Localizer>();
+        notify(
+            app,
+            &localizer.text("notification-update-title"),
+            &localizer.format(
+                "notification-update-body",
+                &[("version", &info.latest_version)],
+            ),
+        )?;
+    }
+    Ok(())
+}
+
+// Connection test commands
+
+#[tauri::command]
+async fn test_backend_connection(app: AppHandle) -> Result<Diagnostics, String> {
+    let client = http_client(&app).await?;
+    let url = format!("{}/health", backend_url(&app));
+    let result = diagnostics::probe(&client, "backend", &url, None).await.0;
+    if result.ok {
+        app.state::<CircuitBreaker>().record_success();
+    }
+    Ok(result)
+}
+
+/// Whether backend requests are currently failing fast
+#[tauri::command]
+async fn get_backend_health(breaker: State<'_, CircuitBreaker>) -> Result<BackendHealth, String> {
+    Ok(breaker.health())
+}
+
+/// Check that the backend can reach the mail account through Outlook
+#[tauri::command]
+async fn test_email_connection(
+    app: AppHandle,
+    account_id: Option<String>,
+) -> Result<Diagnostics, String> {
+    let client = http_client(&app).await?;
+    let account_id = account_id.unwrap_or_else(|| "default".to_string());
+    let url = format!(
+        "{}/diagnostics/email?account_id={}",
+        backend_url(&app),
+        account_id
+    );
+    let (mut result, body) = diagnostics::probe(&client, &account_id, &url, None).await;
+
+    let status: serde_json::Value = body
+        .and_then(|b| serde_json::from_str(&b).ok())
+        .unwrap_or_default();
+    if result.ok {
+        if status["connected"].as_bool().unwrap_or(false) {
+            result.auth = AuthResult::Ok;
+        } else {
+            result.ok = false;
+            result.auth = AuthResult::Failed;
+            result.reason = Some(
+                status["error"]
+                    .as_str()
+                    .unwrap_or("Mail account is not connected")
+                    .to_string(),
+            );
+        }
+    }
+    Ok(result)
+}
+
+#[tauri::command]
+async fn test_llm_connection(app: AppHandle, provider: String) -> Result<Diagnostics, String> {
+    let config = app.state::<ConfigStore>().get()?;
+    let entry = config
+        .llm
+        .providers()
+        .into_iter()
+        .find(|p| p.provider == provider)
+        .ok_or_else(|| format!("Provider not configured: {}", provider))?;
+    let client = http_client(&app).await?;
+    Ok(diagnostics::test_llm(&client, &entry, config.privacy_mode).await)
+}
+
+/// The key a server presents right now, so a rotated pin can be checked and re-pinned
+#[tauri::command]
+async fn get_certificate_pin(app: AppHandle, url: String) -> Result<String, String> {
+    let config = app.state::<ConfigStore>().get()?;
+    privacy::ensure_allowed(&url, config.privacy_mode)?;
+    http::fetch_pin(&config.network, &url).await
+}
+
+/// Replace the accepted keys for a host; an empty list removes its pin
+#[tauri::command]
+async fn set_certificate_pins(
+    app: AppHandle,
+    host: String,
+    pins: Vec<String>,
+) -> Result<(), String> {
+    let host = host.trim().to_lowercase();
+    let mut raw = app.state::<ConfigStore>().raw()?;
+    let mut entries: Vec<CertificatePin> =
+        serde_json::from_value(raw["network"]["pins"].clone()).unwrap_or_default();
+    entries.retain(|entry| !entry.host.eq_ignore_ascii_case(&host));
+    if !pins.is_empty() {
+        entries.push(CertificatePin { host, sha256: pins });
+    }
+    raw["network"]["pins"] = serde_json::to_value(entries).map_err(|e| e.to_string())?;
+    app.state::<ConfigStore>().save_raw(&raw)
+}
+
+// Window control commands
+
+#[tauri::command]
+async fn minimize_window(window: Window) -> Result<(), String> {
+    window.minimize().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn maximize_window(window: Window) -> Result<(), String> {
+    if window.is_maximized().map_err(|e| e.to_string())? {
+        window.unmaximize().map_err(|e| e.to_string())
+    } else {
+        window.maximize().map_err(|e| e.to_string())
+    }
+}
+
+/// Open a link from the UI in the system browser: only web and mail links, and risky ones only
+/// after the user confirms. `text` is what the link was shown as.
+#[tauri::command]
+async fn open_external(
+    app: AppHandle,
+    window: Window,
+    url: String,
+    text: Option<String>,
+) -> Result<bool, String> {
+    let url = webview::external_url(&url)?;
+    open_link(&app, Some(window), url, text).await
+}
+
+/// Bring the main window forward, for popups that can't reach anything else
+#[tauri::command]
+async fn open_main_window(app: AppHandle) -> Result<(), String> {
+    show_main_window(&app);
+    Ok(())
+}
+
+#[tauri::command]
+async fn close_window(window: Window) -> Result<(), String> {
+    window.close().map_err(|e| e.to_string())
+}
+
+/// Put a translucent effect behind the main window and remember the choice
+#[tauri::command]
+async fn set_window_effect(app: AppHandle, effect: WindowEffect) -> Result<WindowEffect, String> {
+    apply_window_effect(&app, effect)?;
+    let mut raw = app.state::<ConfigStore>().raw()?;
+    raw["ui"]["window_effect"] = serde_json::to_value(effect).map_err(|e| e.to_string())?;
+    app.state::<ConfigStore>().save_raw(&raw)?;
+    let _ = app.emit_all("window-effect-changed", effect);
+    Ok(effect)
+}
+
+fn apply_window_effect(app: &AppHandle, effect: WindowEffect) -> Result<(), String> {
+    if !effect.is_supported() {
+        return Err(format!("{:?} is not available on this platform", effect));
+    }
+    let window = app_window(app, AppWindow::Main)?;
+    // Raw handles aren't Send, so the address crosses to the main thread as a number
+    let native = native_window(&window)?;
+    app.run_on_main_thread(move || {
+        if let Err(e) = platform::set_window_effect(native as *mut std::ffi::c_void, effect) {
+            log_warn!("Failed to set window effect: {}", e);
+        }
+    })
+    .map_err(|e| e.to_string())
+}
+
+/// The NSWindow or HWND behind a Tauri window
+fn native_window(window: &Window) -> Result<usize, String> {
+    #[cfg(target_os = "macos")]
+    return window
+        .ns_window()
+        .map(|ns_window| ns_window as usize)
+        .map_err(|e| e.to_string());
+    #[cfg(target_os = "windows")]
+    return window
+        .hwnd()
+        .map(|hwnd| hwnd.0 as usize)
+        .map_err(|e| e.to_string());
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    {
+        let _ = window;
+        Ok(0)
+    }
+}
+
+/// Shrink the main window to the always-on-top unread ticker, or restore it
+#[tauri::command]
+async fn set_compact_mode(app: AppHandle, compact: bool) -> Result<bool, String> {
+    let window = app_window(&app, AppWindow::Main)?;
+    let mode = app.state::<CompactMode>();
+    if compact {
+        let position = window.outer_position().map_err(|e| e.to_string())?;
+        let size = window.inner_size().map_err(|e| e.to_string())?;
+        let geometry = WindowGeometry {
+            x: position.x,
+            y: position.y,
+            width: size.width,
+            height: size.height,
+        };
+        if mode.enter(geometry) {
+            let (width, height) = COMPACT_SIZE;
+            window
+                .set_min_size(None::<tauri::Size>)
+                .map_err(|e| e.to_string())?;
+            window.set_resizable(false).map_err(|e| e.to_string())?;
+            window
+                .set_size(tauri::Size::Logical(tauri::LogicalSize { width, height }))
+                .map_err(|e| e.to_string())?;
+            window.set_always_on_top(true).map_err(|e| e.to_string())?;
+            window.set_skip_taskbar(true).map_err(|e| e.to_string())?;
+            // Bottom-right, where the reminder popup would appear
+            if let Ok(Some(monitor)) = window.current_monitor() {
+                let scale_factor = monitor.scale_factor();
+                let x = (monitor.size().width as f64 / scale_factor) - width - 20.0;
+                let y = (monitor.size().height as f64 / scale_factor) - height - 60.0;
+                window
+                    .set_position(tauri::Position::Logical(tauri::LogicalPosition { x, y }))
+                    .map_err(|e| e.to_string())?;
+            }
+        }
+    } else if let Some(geometry) = mode.exit() {
+        window.set_always_on_top(false).map_err(|e| e.to_string())?;
+        window.set_skip_taskbar(false).map_err(|e| e.to_string())?;
+        window.set_resizable(true).map_err(|e| e.to_string())?;
+        window
+            .set_min_size(Some(tauri::Size::Logical(tauri::LogicalSize {
+                width: 800.0,
+                height: 600.0,
+            })))
+            .map_err(|e| e.to_string())?;
+        window
+            .set_size(tauri::Size::Physical(tauri::PhysicalSize {
+                width: geometry.width,
+                height: geometry.height,
+            }))
+            .map_err(|e| e.to_string())?;
+        window
+            .set_position(tauri::Position::Physical(tauri::PhysicalPosition {
+                x: geometry.x,
+                y: geometry.y,
+            }))
+            .map_err(|e| e.to_string())?;
+    }
+    let mut raw = app.state::<ConfigStore>().raw()?;
+    raw["ui"]["compact_mode"] = serde_json::Value::Bool(compact);
+    app.state::<ConfigStore>().save_raw(&raw)?;
+    let _ = app.emit_all("compact-mode-changed", compact);
+    Ok(compact)
+}
+
+/// The frontend reports which view the main window shows, e.g. "inbox"
+#[tauri::command]
+async fn set_active_view(presence: State<'_, WindowPresence>, view: String) -> Result<(), String> {
+    presence.set_view(&view);
+    Ok(())
+}
+
+/// Where alerts should go right now, based on main window focus and view
+fn alert_delivery(app: &AppHandle) -> Delivery {
+    let minimized = app_window(app, AppWindow::Main)
+        .map(|window| {
+            window.is_minimized().unwrap_or(false) || !window.is_visible().unwrap_or(true)
+        })
+        .unwrap_or(true);
+    app.state::<WindowPresence>().delivery(minimized)
+}
+
+/// Keep an alert inside the app when the user is already looking at the inbox.
+/// Returns true when it was handled there.
+fn deliver_in_app(app: &AppHandle, title: &str, body: &str) -> bool {
+    if alert_delivery(app) != Delivery::InApp {
+        return false;
+    }
+    let _ = app.emit_all(
+        "in-app-notification",
+        NotificationData {
+            title: title.to_string(),
+            body: body.to_string(),
+            count: 1,
+        },
+    );
+    true
+}
+
+// Theme commands
+
+fn current_theme(app: &AppHandle) -> ResolvedTheme {
+    let config = app
+        .state::<ConfigStore>()
+        .get()
+        .unwrap_or_default()
+        .ui
+        .theme;
+    theme::resolve(&config, app.state::<SystemTheme>().dark())
+}
+
+/// Display settings only, for windows that may not read the rest of the config
+#[tauri::command]
+async fn get_ui_config(app: AppHandle) -> Result<UIConfig, String> {
+    Ok(app.state::<ConfigStore>().get()?.ui)
+}
+
+#[tauri::command]
+async fn get_theme(app: AppHandle) -> Result<ResolvedTheme, String> {
+    Ok(current_theme(&app))
+}
+
+#[tauri::command]
+async fn get_theme_presets() -> Result<Vec<ThemePreset>, String> {
+    Ok(theme::PRESETS.to_vec())
+}
+
+/// Change any of the theme mode, preset and accent; an empty accent goes back to the preset's
+#[tauri::command]
+async fn set_theme(
+    app: AppHandle,
+    mode: Option<ThemeMode>,
+    preset: Option<String>,
+    accent_color: Option<String>,
+) -> Result<ResolvedTheme, String> {
+    let mut raw = app.state::<ConfigStore>().raw()?;
+    theme::migrate_legacy(&mut raw);
+    if let Some(mode) = mode {
+        raw["ui"]["theme"]["mode"] = serde_json::to_value(mode).map_err(|e| e.to_string())?;
+    }
+    if let Some(preset) = preset {
+        let preset =
+            theme::preset(&preset).ok_or_else(|| format!("Unknown theme preset: {}", preset))?;
+        raw["ui"]["theme"]["preset"] = serde_json::Value::String(preset.name.to_string());
+    }
+    if let Some(accent) = accent_color {
+        let accent = if accent.trim().is_empty() {
+            String::new()
+        } else {
+            theme::parse_color(&accent)
+                .ok_or_else(|| format!("Accent color must look like #rrggbb: {}", accent))?
+        };
+        raw["ui"]["theme"]["accent_color"] = serde_json::Value::String(accent);
+    }
+    app.state::<ConfigStore>().save_raw(&raw)?;
+    Ok(apply_theme(&app))
+}
+
+/// Tell every window about the theme in effect and match the tray icon to it
+fn apply_theme(app: &AppHandle) -> ResolvedTheme {
+    let theme = current_theme(app);
+    let _ = app.emit_all("theme-changed", &theme);
+    // macOS recolors the template tray icon itself
+    if !cfg!(target_os = "macos") {
+        let variant = if theme.dark {
+            "icons/tray-dark.png"
+        } else {
+            "icons/tray-light.png"
+        };
+        let icon = app
+            .path_resolver()
+            .resolve_resource(variant)
+            .filter(|path| path.exists());
+        if let Some(icon) = icon {
+            if let Err(e) = app.tray_handle().set_icon(tauri::Icon::File(icon)) {
+                log_warn!("Failed to swap tray icon: {}", e);
+            }
+        }
+    }
+    theme
+}
+
+/// Record the OS appearance reported by the main window, re-theming when it changed
+fn observe_system_theme(app: &AppHandle, system: tauri::Theme) {
+    let dark = matches!(system, tauri::Theme::Dark);
+    if app.state::<SystemTheme>().set_dark(dark) {
+        apply_theme(app);
+    }
+}
+
+// Retention commands
+
+/// Targets listed by name in a confirmation dialog; the rest are only counted
+const MAX_CONFIRM_TARGETS: usize = 10;
+
+/// A one-time token for a destructive command, issued only once the user confirms `action` on
+/// exactly these `targets` (email ids, or purge scope names) in a native dialog. None when the
+/// user declines.
+#[tauri::command]
+async fn request_confirmation(
+    app: AppHandle,
+    window: Window,
+    action: DestructiveAction,
+    targets: Vec<String>,
+) -> Result<Option<Confirmation>, String> {
+    let localizer = app.state::<Localizer>();
+    let title = localizer.text("confirm-title");
+    let count = targets.len().to_string();
+    let mut message = localizer.format(action.message_id(), &[("count", &count)]);
+    for target in targets.iter().take(MAX_CONFIRM_TARGETS) {
+        message.push_str("\n• ");
+        message.push_str(target);
+    }
+    if targets.len() > MAX_CONFIRM_TARGETS {
+        let more = (targets.len() - MAX_CONFIRM_TARGETS).to_string();
+        message.push_str("\n");
+        message.push_str(&localizer.format("confirm-more", &[("count", &more)]));
+    }
+    // The dialog blocks until answered, so it waits off the async runtime
+    let confirmed = tauri::async_runtime::spawn_blocking(move || {
+        tauri::api::dialog::blocking::confirm(Some(&window), title, message)
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+    Ok(confirmed.then(|| {
+        app.state::<Confirmations>()
+            .issue(action, targets, std::time::Instant::now())
+    }))
+}
+
+/// Everything the retention policies and cache purge would touch right now
+async fn retention_preview(
+    app: &AppHandle,
+    cache: &EmailCache,
+    config: &AppConfig,
+) -> Result<RetentionPreview, String> {
+    let retention = &config.retention;
+    let mut preview = RetentionPreview::default();
+
+    if let Some(min_age_days) = retention.min_age_days() {
+        let response = backend(
+            app,
+            format!("/emails/aging?min_age_days={}", min_age_days),
+            "GET".to_string(),
+            None,
+        )
+        .await?;
+        let emails: Vec<AgingEmail> = serde_json::from_str(&response).map_err(|e| e.to_string())?;
+        preview.emails = retention::plan(retention, emails);
+    }
+    if let Some(cutoff) = cache_cutoff(retention, app.state::<SharedClock>().now()) {
+        preview.cache_entries = cache.count_older_than(cutoff)?;
+    }
+    Ok(preview)
+}
+
+fn cache_cutoff(
+    retention: &RetentionConfig,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Option<chrono::DateTime<chrono::Utc>> {
+    (retention.purge_cache_after_months > 0)
+        .then(|| now - chrono::Duration::days(30 * retention.purge_cache_after_months as i64))
+}
+
+fn log_cutoff(
+    retention: &RetentionConfig,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Option<chrono::DateTime<chrono::Utc>> {
+    (retention.purge_logs_after_days > 0)
+        .then(|| now - chrono::Duration::days(retention.purge_logs_after_days as i64))
+}
+
+/// Dry run: show what the maintenance task would archive, delete and purge
+#[tauri::command]
+async fn preview_retention(
+    app: AppHandle,
+    cache: State<'_, EmailCache>,
+) -> Result<RetentionPreview, String> {
+    let config = app.state::<ConfigStore>().get()?;
+    retention_preview(&app, &cache, &config).await
+}
+
+/// Apply retention policies now instead of waiting for the maintenance task. Deleting more
+/// than a few emails needs a confirmation token covering them.
+#[tauri::command]
+async fn run_retention(
+    app: AppHandle,
+    confirmation: Option<String>,
+) -> Result<RetentionPreview, String> {
+    let config = app.state::<ConfigStore>().get()?;
+    let mut preview = retention_preview(&app, &app.state::<EmailCache>(), &config).await?;
+    if !config.retention.enabled {
+        preview.emails.clear();
+    }
+    app.state::<Confirmations>().require(
+        confirmation.as_deref(),
+        DestructiveAction::DeleteEmails,
+        &preview.deletions(),
+        std::time::Instant::now(),
+    )?;
+    apply_retention_plan(&app, &config, preview).await
+}
+
+/// The scheduled retention run. In observe-only mode what it would archive or delete is logged
+/// and nothing, local data included, is removed.
+async fn apply_retention(app: &AppHandle, config: &AppConfig) -> Result<RetentionPreview, String> {
+    let mut preview = retention_preview(app, &app.state::<EmailCache>(), config).await?;
+    if !config.retention.enabled {
+        preview.emails.clear();
+    }
+    if config.observe_only {
+        for item in &preview.emails {
+            record_activity(
+                app,
+                ActivityKind::Observed,
+                Some(&item.email.id),
+                &item.email.subject,
+                serde_json::json!({ "rule": item.policy, "actions": [item.action] }),
+            );
+        }
+        return Ok(preview);
+    }
+    apply_retention_plan(app, config, preview).await
+}
+
+/// Carry out a retention preview: archive and delete its emails, then purge the cache and logs
+async fn apply_retention_plan(
+    app: &AppHandle,
+    config: &AppConfig,
+    mut preview: RetentionPreview,
+) -> Result<RetentionPreview, String> {
+    let cache = app.state::<EmailCache>();
+    let audit = app.state::<AuditLog>();
+    let mut applied = Vec::new();
+    for item in preview.emails {
+        let (endpoint, method, action) = match item.action {
+            RetentionAction::Archive => (
+                format!("/emails/{}/archive", item.email.id),
+                "POST",
+                AuditAction::Archived,
+            ),
+            RetentionAction::Delete => (
+                format!("/emails/{}", item.email.id),
+                "DELETE",
+                AuditAction::Deleted,
+            ),
+        };
+        match backend(app, endpoint, method.to_string(), None).await {
+            Ok(_) => {
+                record_audit(
+                    &audit,
+                    AuditEntry::new(action, Origin::Rule, Some(item.email.id.clone()))
+                        .with_details(serde_json::json!({ "policy": item.policy })),
+                );
+                applied.push(item);
+            }
+            Err(e) => log_warn!("Retention policy \"{}\" failed: {}", item.policy, e),
+        }
+    }
+    preview.emails = applied;
+
+    if let Some(cutoff) = cache_cutoff(&config.retention, app.state::<SharedClock>().now()) {
+        preview.cache_entries = cache.purge_older_than(cutoff)?;
+    }
+    if let Some(cutoff) = log_cutoff(&config.retention, app.state::<SharedClock>().now()) {
+        preview.log_entries =
+            audit.prune_before(cutoff)? + app.state::<ActivityLog>().prune_before(cutoff)?;
+    }
+    Ok(preview)
+}
+
+/// Delete local data on demand: cached bodies, summaries, logs and stored credentials.
+/// No scopes means all of them. Each scope is attempted even if an earlier one fails.
+/// Needs a confirmation token covering every scope purged.
+#[tauri::command]
+async fn purge_local_data(
+    app: AppHandle,
+    scopes: Vec<PurgeScope>,
+    confirmation: Option<String>,
+) -> Result<Vec<PurgeResult>, String> {
+    let scopes = purge::resolve_scopes(&scopes);
+    let names: Vec<String> = scopes
+        .iter()
+        .map(|scope| scope.name().to_string())
+        .collect();
+    app.state::<Confirmations>().require(
+        confirmation.as_deref(),
+        DestructiveAction::PurgeData,
+        &names,
+        std::time::Instant::now(),
+    )?;
+    Ok(scopes
+        .into_iter()
+        .map(|scope| {
+            let removed = match scope {
+                PurgeScope::Bodies => app.state::<EmailCache>().clear(),
+                PurgeScope::Summaries => Ok(app.state::<SummaryCache>().clear()),
+                PurgeScope::Embeddings => Ok(0),
+                PurgeScope::Logs => {
+                    app.state::<Telemetry>().clear();
+                    let audit = app.state::<AuditLog>().clear();
+                    let activity = app.state::<ActivityLog>().clear();
+                    audit.and_then(|audit| Ok(audit + activity?))
+                }
+                PurgeScope::Secrets => {
+                    let config = app.state::<ConfigStore>().purge_secrets();
+                    let keychain = encryption::delete_master_key();
+                    config.and_then(|removed| Ok(removed + keychain? as usize))
+                }
+            };
+            match removed {
+                Ok(removed) => PurgeResult {
+                    scope,
+                    removed,
+                    error: None,
+                },
+                Err(e) => PurgeResult {
+                    scope,
+                    removed: 0,
+                    error: Some(e),
+                },
+            }
+        })
+        .collect())
+}
+
+// Background scheduler
+
+/// Fetch the latest inbox emails of one account from the backend
+async fn fetch_emails(
+    app: &AppHandle,
+    account: String,
+    limit: u32,
+) -> Result<Vec<EmailData>, String> {
+    let endpoint = format!(
+        "/emails?{}",
+        url::form_urlencoded::Serializer::new(String::new())
+            .append_pair("limit", &limit.to_string())
+            .append_pair("account", &account)
+            .finish()
+    );
+    fetch_email_list(app, endpoint).await
+}
+
+/// Tell the UI what changed since the last sync so it can patch its list in place; returns the
+/// emails that just arrived
+fn publish_inbox_changes(
+    app: &AppHandle,
+    synced_accounts: &[String],
+    emails: &[EmailData],
+) -> Vec<EmailData> {
+    let diff = app.state::<InboxSnapshot>().apply(synced_accounts, emails);
+    app.state::<FocusState>().hold_emails(&diff.added);
+    let proposals = app.state::<ProposalStore>();
+    for email in &diff.added {
+        let _ = app.emit_all("email-added", email);
+        record_activity(
+            app,
+            ActivityKind::Received,
+            Some(&email.id),
+            &email.subject,
+            serde_json::json!({ "sender": email.sender, "account": email.account }),
+        );
+        // Answers to offered meeting times get picked up by `respond_to_meeting_reply`
+        if let Some(proposal) = proposals.find_reply(&email.sender_email, &email.subject) {
+            if proposal.email_id != email.id {
+                let _ = app.emit_all(
+                    "meeting-reply",
+                    serde_json::json!({ "email_id": email.id, "proposal": proposal }),
+                );
+            }
+        }
+    }
+    for email in &diff.updated {
+        let _ = app.emit_all("email-updated", email);
+    }
+    for removed in &diff.removed {
+        let _ = app.emit_all("email-removed", removed);
+    }
+    diff.added
+}
+
+/// Sync every due account in parallel, at most `sync_workers` at a time. Failing accounts
+/// back off on their own; the round only fails when no account could be synced.
+/// Returns the accounts that synced along with their emails.
+async fn sync_accounts(
+    app: &AppHandle,
+    config: &AppConfig,
+) -> Result<(Vec<String>, Vec<EmailData>), String> {
+    let now = app.state::<SharedClock>().now();
+    let accounts: Vec<String> =
+        mailboxes::sync_accounts(&config.email.accounts, &config.email.shared_mailboxes)
+            .into_iter()
+            .filter(|account| app.state::<AccountBackoff>().is_due(account, now))
+            .collect();
+    if accounts.is_empty() {
+        return Ok((Vec::new(), Vec::new()));
+    }
+
+    let workers = std::sync::Arc::new(tokio::sync::Semaphore::new(
+        config.email.sync_workers.max(1),
+    ));
+    let mut jobs = tokio::task::JoinSet::new();
+    for account in &accounts {
+        let (app, account, workers) = (app.clone(), account.clone(), workers.clone());
+        let limit = config.email.max_emails_per_check;
+        jobs.spawn(async move {
+            let _permit = workers.acquire_owned().await;
+            let result = fetch_emails(&app, account.clone(), limit).await;
+            (account, result)
+        });
+    }
+
+    let total = accounts.len();
+    let mut emails = Vec::new();
+    let mut last_error = None;
+    let mut completed = 0;
+    let mut synced = Vec::new();
+    while let Some(joined) = jobs.join_next().await {
+        let Ok((account, result)) = joined else {
+            continue;
+        };
+        completed += 1;
+        let backoff = app.state::<AccountBackoff>();
+        let progress = match result {
+            Ok(fetched) => {
+                backoff.record_success(&account);
+                synced.push(account.clone());
+                let count = fetched.len();
+                emails.extend(fetched);
+                SyncProgress {
+                    account,
+                    completed,
+                    total,
+                    fetched: count,
+                    error: None,
+                    retry_at: None,
+                }
+            }
+            Err(e) => {
+                let retry_at = backoff.record_failure(&account, app.state::<SharedClock>().now());
+                last_error = Some(e.clone());
+                SyncProgress {
+                    account,
+                    completed,
+                    total,
+                    fetched: 0,
+                    error: Some(e),
+                    retry_at: Some(retry_at),
+                }
+            }
+        };
+        let _ = app.emit_all("sync-progress", &progress);
+    }
+
+    match last_error {
+        Some(e) if synced.is_empty() => Err(e),
+        _ => Ok((synced, emails)),
+    }
+}
+
+/// Background work allowed under the current battery and network conditions
+fn sync_plan(config: &AppConfig) -> SyncPlan {
+    config
+        .email
+        .power
+        .plan(PowerState::detect(), config.email.check_interval_minutes)
+}
+
+#[tauri::command]
+async fn get_power_state(app: AppHandle) -> Result<SyncPlan, String> {
+    let config = app
+        .state::<ConfigStore>()
+        .get()
+        .map_err(|e| e.to_string())?;
+    Ok(sync_plan(&config))
+}
+
+/// Poll the backend every `check_interval_minutes` (longer when power or data is constrained)
+/// and run scheduled work
+fn start_scheduler(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut constrained = false;
+        loop {
+            let config = app.state::<ConfigStore>().get().unwrap_or_default();
+            let plan = sync_plan(&config);
+            if plan.constrained != constrained {
+                constrained = plan.constrained;
+                let _ = app.emit_all("power-mode-changed", &plan);
+            }
+            {
+                let shutdown = app.state::<Shutdown>();
+                let Some(_task) = shutdown.task() else {
+                    break;
+                };
+                if let Err(e) = scheduler_tick(&app, &config, &plan).await {
+                    log_warn!("Scheduler tick failed: {}", e);
+                }
+            }
+            let minutes = plan.interval_minutes as u64;
+            app.state::<SyncTrigger>()
+                .wait(std::time::Duration::from_secs(minutes * 60))
+                .await;
+        }
+    });
+}
+
+/// How often the clock watcher runs; snoozes fire at most this late
+const CLOCK_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// Release due snoozes and catch suspend/resume. On wake the poll timer is reset with an
+/// immediate sync, and snoozes that expired while asleep fire right away.
+fn start_clock_watch(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let clock = app.state::<SharedClock>().inner().clone();
+        let detector = WakeDetector::new(clock.now());
+        loop {
+            tokio::time::sleep(CLOCK_CHECK_INTERVAL).await;
+            if let Some(slept) =
+                detector.check(clock.now(), CLOCK_CHECK_INTERVAL, CLOCK_CHECK_INTERVAL * 2)
+            {
+                let _ = app.emit_all("system-resumed", slept.as_secs());
+                app.state::<SyncTrigger>().request();
+            }
+            if let Err(e) = release_snoozes(&app).await {
+                log_warn!("Failed to release snoozed emails: {}", e);
+            }
+            remind_unread(&app).await;
+            retry_due_replies(&app).await;
+            if let Err(e) = expire_approvals(&app) {
+                log_warn!("Failed to expire pending approvals: {}", e);
+            }
+            if let Err(e) = expire_codes(&app).await {
+                log_warn!("Failed to expire verification code emails: {}", e);
+            }
+            if let Err(e) = remind_payments(&app).await {
+                log_warn!("Failed to send payment reminders: {}", e);
+            }
+            tick_focus(&app);
+            auto_switch_profile(&app).await;
+            lock_when_idle(&app).await;
+        }
+    });
+}
+
+// App lock commands
+
+/// Lock and tell every window to hide its content
+fn lock_app_now(app: &AppHandle) {
+    if app.state::<AppLock>().lock() {
+        let _ = app.emit_all("app-locked", ());
+    }
+}
+
+async fn lock_when_idle(app: &AppHandle) {
+    let config = app.state::<ConfigStore>().get().unwrap_or_default();
+    if !config.app_lock.enabled || app.state::<AppLock>().is_locked() {
+        return;
+    }
+    let idle = tauri::async_runtime::spawn_blocking(platform::idle_time)
+        .await
+        .ok()
+        .flatten();
+    if config.app_lock.idle_expired(idle) {
+        lock_app_now(app);
+    }
+}
+
+async fn biometric_enabled(app: &AppHandle) -> bool {
+    let config = app.state::<ConfigStore>().get().unwrap_or_default();
+    config.app_lock.biometric
+        && tauri::async_runtime::spawn_blocking(platform::biometric_available)
+            .await
+            .unwrap_or(false)
+}
+
+#[tauri::command]
+async fn get_lock_status(app: AppHandle) -> Result<LockStatus, String> {
+    let biometric = biometric_enabled(&app).await;
+    Ok(app.state::<AppLock>().status(biometric))
+}
+
+#[tauri::command]
+async fn lock_app(app: AppHandle) -> Result<(), String> {
+    if !app.state::<AppLock>().pin_set() {
+        return Err("Set a PIN before locking SERINA".to_string());
+    }
+    lock_app_now(&app);
+    Ok(())
+}
+
+/// Unlock with the PIN, or without one through the OS biometric prompt
+#[tauri::command]
+async fn unlock_app(app: AppHandle, pin: Option<String>) -> Result<(), String> {
+    let lock = app.state::<AppLock>();
+    match pin {
+        Some(pin) => lock.unlock_with_pin(&pin, std::time::Instant::now())?,
+        None => {
+            if !biometric_enabled(&app).await {
+                return Err("Biometric unlock isn't available; enter your PIN".to_string());
+            }
+            let reason = app.state::<Localizer>().text("lock-biometric-reason");
+            let verified =
+                tauri::async_runtime::spawn_blocking(move || platform::verify_biometric(&reason))
+                    .await
+                    .map_err(|e| e.to_string())??;
+            if !verified {
+                return Err("Not recognized; try again or enter your PIN".to_string());
+            }
+            lock.unlock();
+        }
+    }
+    let _ = app.emit_all("app-unlocked", ());
+    Ok(())
+}
+
+/// Set, change or remove (`pin` = None) the unlock PIN
+#[tauri::command]
+async fn set_app_lock_pin(
+    app: AppHandle,
+    pin: Option<String>,
+    current_pin: Option<String>,
+) -> Result<LockStatus, String> {
+    let lock = app.state::<AppLock>();
+    let was_locked = lock.is_locked();
+    lock.set_pin(pin.as_deref(), current_pin.as_deref())?;
+    if was_locked && !lock.is_locked() {
+        let _ = app.emit_all("app-unlocked", ());
+    }
+    let biometric = biometric_enabled(&app).await;
+    Ok(lock.status(biometric))
+}
+
+// Notification profile commands
+
+/// Put a notification profile in effect; an empty name goes back to the base settings
+#[tauri::command]
+async fn set_active_profile(app: AppHandle, name: String) -> Result<String, String> {
+    let notifications = app.state::<ConfigStore>().get()?.notifications;
+    let name = if name.trim().is_empty() {
+        String::new()
+    } else {
+        profiles::find(&notifications, name.trim())
+            .map(|profile| profile.name.clone())
+            .ok_or_else(|| format!("No notification profile named \"{}\"", name.trim()))?
+    };
+    let mut raw = app.state::<ConfigStore>().raw()?;
+    raw["notifications"]["active_profile"] = serde_json::Value::String(name.clone());
+    app.state::<ConfigStore>().save_raw(&raw)?;
+    refresh_tray_menu(&app);
+    let _ = app.emit_all("profile-changed", &name);
+    Ok(name)
+}
+
+/// Switch to the profile whose schedule or Wi-Fi network matches, when that match changes
+async fn auto_switch_profile(app: &AppHandle) {
+    let Ok(config) = app.state::<ConfigStore>().get() else {
+        return;
+    };
+    let notifications = &config.notifications;
+    if notifications.profiles.is_empty() {
+        return;
+    }
+    let ssid = if profiles::uses_ssid(&notifications.profiles) {
+        tauri::async_runtime::spawn_blocking(platform::wifi_ssid)
+            .await
+            .ok()
+            .flatten()
+    } else {
+        None
+    };
+    let selected = profiles::auto_select(
+        &notifications.profiles,
+        app.state::<SharedClock>().local_now(),
+        ssid.as_deref(),
+    )
+    .map(|profile| profile.name.clone());
+    let Some(name) = app.state::<AutoSwitch>().observe(selected.as_deref()) else {
+        return;
+    };
+    if name != notifications.active_profile {
+        if let Err(e) = set_active_profile(app.clone(), name).await {
+            log_warn!("Failed to switch notification profile: {}", e);
+        }
+    }
+}
+
+// Focus session commands
+
+/// Hold back notifications and new mail for `minutes` (default `focus_minutes`), then
+/// deliver them as one digest
+#[tauri::command]
+async fn start_focus_session(app: AppHandle, minutes: Option<u32>) -> Result<FocusSession, String> {
+    track(&app, "focus");
+    let minutes = minutes.unwrap_or_else(|| {
+        app.state::<ConfigStore>()
+            .get()
+            .map(|c| c.notifications.focus_minutes)
+            .unwrap_or(25)
+    });
+    let session = app
+        .state::<FocusState>()
+        .start(minutes, app.state::<SharedClock>().now())?;
+    update_focus_tray(&app);
+    let _ = app.emit_all("focus-started", &session);
+    Ok(session)
+}
+
+/// End the session early, delivering what it held
+#[tauri::command]
+async fn end_focus_session(app: AppHandle) -> Result<Option<FocusDigest>, String> {
+    let digest = app
+        .state::<FocusState>()
+        .end(app.state::<SharedClock>().now());
+    if let Some(digest) = &digest {
+        deliver_focus_digest(&app, digest);
+    }
+    Ok(digest)
+}
+
+#[tauri::command]
+async fn get_focus_session(focus: State<'_, FocusState>) -> Result<Option<FocusSession>, String> {
+    Ok(focus.current())
+}
+
+/// Finish a session whose time is up, or refresh the tray countdown
+fn tick_focus(app: &AppHandle) {
+    match app
+        .state::<FocusState>()
+        .take_finished(app.state::<SharedClock>().now())
+    {
+        Some(digest) => deliver_focus_digest(app, &digest),
+        None => update_focus_tray(app),
+    }
+}
+
+/// Show the catch-up digest: one notification for everything held, details in the app
+fn deliver_focus_digest(app: &AppHandle, digest: &FocusDigest) {
+    update_focus_tray(app);
+    let _ = app.emit_all("focus-ended", digest);
+    let show = app
+        .state::<ConfigStore>()
+        .get()
+        .map(|c| c.notifications.show_desktop_notifications)
+        .unwrap_or(true);
+    if !show || (digest.emails.is_empty() && digest.notifications.is_empty()) {
+        return;
+    }
+    let (title, body) = {
+        let localizer = app.state::<Localizer>();
+        (
+            localizer.text("notification-focus-title"),
+            localizer.format(
+                "notification-focus-body",
+                &[
+                    (
+                        "summary",
+                        &localizer.format_email_count(digest.emails.len() as u32),
+                    ),
+                    ("alerts", &digest.notifications.len().to_string()),
+                ],
+            ),
+        )
+    };
+    if let Err(e) = notify(app, &title, &body) {
+        log_warn!("Failed to show focus digest: {}", e);
+    }
+}
+
+// Upcoming
+
+/// Open (or raise) the list of everything scheduled
+#[tauri::command]
+async fn show_upcoming_window(app: AppHandle) -> Result<(), String> {
+    let window = match app_window(&app, AppWindow::Upcoming) {
+        Ok(window) => window,
+        Err(_) => build_window(
+            &app,
+            AppWindow::Upcoming,
+            AppWindow::Upcoming.url(&[]),
+            None,
+        )?,
+    };
+    window.show().map_err(|e| e.to_string())?;
+    window.set_focus().map_err(|e| e.to_string())
+}
+
+/// Snoozed emails, deadline reminders, scheduled sends and the focus digest, soonest first.
+/// Local items are still listed when the backend can't be reached for the Outbox.
+#[tauri::command]
+async fn get_scheduled_items(app: AppHandle) -> Result<Vec<ScheduledItem>, String> {
+    let outgoing = backend_json::<Vec<OutgoingEmail>>(&app, "/outbox".to_string())
+        .await
+        .unwrap_or_else(|e| {
+            log_warn!("Failed to load scheduled sends: {}", e);
+            Vec::new()
+        });
+    let cache = app.state::<EmailCache>();
+    let subject = |id: &str| cache.get(id).ok().flatten().map(|email| email.subject);
+    Ok(upcoming::collect(
+        &app.state::<SnoozeQueue>().pending(),
+        subject,
+        &outgoing,
+        app.state::<FocusState>().current().as_ref(),
+    ))
+}
+
+/// Call off a scheduled item: a snooze or reminder is dropped, a scheduled send is deleted
+/// from the Outbox, and the focus session ends now with its digest
+#[tauri::command]
+async fn cancel_scheduled_item(
+    app: AppHandle,
+    kind: ScheduledKind,
+    id: String,
+) -> Result<Vec<ScheduledItem>, String> {
+    match kind {
+        ScheduledKind::Snooze | ScheduledKind::DeadlineReminder => {
+            if !app.state::<SnoozeQueue>().cancel(&id)? {
+                return Err("This email is no longer snoozed".to_string());
+            }
+        }
+        ScheduledKind::ScheduledSend => {
+            delete_outbox_item(app.clone(), id).await?;
+        }
+        ScheduledKind::FocusDigest => {
+            end_focus_session(app.clone()).await?;
+        }
+    }
+    get_scheduled_items(app).await
+}
+
+/// Move a scheduled item to `until`
+#[tauri::command]
+async fn reschedule_item(
+    app: AppHandle,
+    kind: ScheduledKind,
+    id: String,
+    until: chrono::DateTime<chrono::Utc>,
+) -> Result<Vec<ScheduledItem>, String> {
+    let now = app.state::<SharedClock>().now();
+    if until <= now {
+        return Err("Pick a time in the future".to_string());
+    }
+    match kind {
+        ScheduledKind::Snooze | ScheduledKind::DeadlineReminder => {
+            let snoozes = app.state::<SnoozeQueue>();
+            let entry = snoozes
+                .pending()
+                .into_iter()
+                .find(|entry| entry.email_id == id)
+                .ok_or_else(|| "This email is no longer snoozed".to_string())?;
+            snoozes.schedule(SnoozedEmail { until, ..entry })?;
+        }
+        ScheduledKind::ScheduledSend => {
+            backend(
+                &app,
+                format!("/outbox/{}/defer", id),
+                "POST".to_string(),
+                Some(serde_json::json!({ "until": until.to_rfc3339() }).to_string()),
+            )
+            .await?;
+        }
+        ScheduledKind::FocusDigest => {
+            let focus = app.state::<FocusState>();
+            if focus.current().is_none() {
+                return Err("The focus session has already ended".to_string());
+            }
+            let minutes = ((until - now).num_seconds() + 59) / 60;
+            let session = focus.start(minutes as u32, now)?;
+            update_focus_tray(&app);
+            let _ = app.emit_all("focus-started", &session);
+        }
+    }
+    get_scheduled_items(app).await
+}
+
+// Shutdown
+
+/// Quit without cutting work off: stop the scheduler, let in-flight syncs and sends finish,
+/// flush the outbox and save state. A step that hangs is given up on after a few seconds.
+/// The Python backend is started on its own, so it keeps running.
+async fn quit(app: &AppHandle) {
+    let shutdown = app.state::<Shutdown>();
+    if !shutdown.begin() {
+        return;
+    }
+    let _ = app.emit_all("shutting-down", {});
+    // Wake the scheduler so it notices instead of sleeping out its interval
+    app.state::<SyncTrigger>().request();
+    if !shutdown.drained(shutdown::STEP_TIMEOUT).await {
+        log_info!("Quitting with {} tasks still running", shutdown.active());
+    }
+    let flush = backend(app, "/outbox/flush".to_string(), "POST".to_string(), None);
+    match tokio::time::timeout(shutdown::STEP_TIMEOUT, flush).await {
+        Ok(Ok(_)) => {}
+        Ok(Err(e)) => log_error!("Failed to flush outbox: {}", e),
+        Err(_) => log_error!("Outbox flush timed out"),
+    }
+    if let Err(e) = remember_mini_inbox_position(app) {
+        log_warn!("Failed to save mini inbox position: {}", e);
+    }
+    if let Err(e) = app.state::<EmailCache>().checkpoint() {
+        log_warn!("Failed to checkpoint cache: {}", e);
+    }
+    app.exit(0);
+}
+
+/// The tray menu in the current language, with a submenu for any notification profiles
+fn tray_menu(localizer: &Localizer, config: &AppConfig, pins: &[PinnedEmail]) -> SystemTrayMenu {
+    let [show, settings, quit] = TRAY_ITEMS
+        .map(|(id, message)| CustomMenuItem::new(id.to_string(), localizer.text(message)));
+    let focus = CustomMenuItem::new(TRAY_FOCUS.to_string(), localizer.text("tray-focus-start"));
+    let upcoming = CustomMenuItem::new(TRAY_UPCOMING.to_string(), localizer.text("tray-upcoming"));
+    let activity = CustomMenuItem::new(TRAY_ACTIVITY.to_string(), localizer.text("tray-activity"));
+    let lock = CustomMenuItem::new(TRAY_LOCK.to_string(), localizer.text("tray-lock"));
+    let mut menu = SystemTrayMenu::new()
+        .add_item(show)
+        .add_item(focus)
+        .add_item(upcoming)
+        .add_item(activity)
+        .add_item(lock);
+
+    let notifications = &config.notifications;
+    if !notifications.profiles.is_empty() {
+        let base = std::iter::once((String::new(), localizer.text("tray-profile-default")));
+        let named = notifications
+            .profiles
+            .iter()
+            .map(|profile| (profile.name.clone(), profile.name.clone()));
+        let mut profiles = SystemTrayMenu::new();
+        for (name, label) in base.chain(named) {
+            let item = CustomMenuItem::new(format!("{}{}", TRAY_PROFILE_PREFIX, name), label);
+            let active = if name.is_empty() {
+                profiles::find(notifications, &notifications.active_profile).is_none()
+            } else {
+                name.eq_ignore_ascii_case(&notifications.active_profile)
+            };
+            profiles = profiles.add_item(if active { item.selected() } else { item });
+        }
+        menu = menu.add_submenu(SystemTraySubmenu::new(
+            localizer.text("tray-profile"),
+            profiles,
+        ));
+    }
+
+    if !pins.is_empty() {
+        let mut pinned = SystemTrayMenu::new();
+        for pin in pins {
+            let actions = SystemTrayMenu::new()
+                .add_item(CustomMenuItem::new(
+                    format!("{}{}", TRAY_PIN_OPEN_PREFIX, pin.email_id),
+                    localizer.text("tray-pin-open"),
+                ))
+                .add_item(CustomMenuItem::new(
+                    format!("{}{}", TRAY_PIN_DONE_PREFIX, pin.email_id),
+                    localizer.text("tray-pin-done"),
+                ));
+            pinned = pinned.add_submenu(SystemTraySubmenu::new(pin.label(), actions));
+        }
+        menu = menu.add_submenu(SystemTraySubmenu::new(
+            localizer.format("tray-pinned", &[("count", &pins.len().to_string())]),
+            pinned,
+        ));
+    }
+
+    menu.add_native_item(SystemTrayMenuItem::Separator)
+        .add_item(settings)
+        .add_native_item(SystemTrayMenuItem::Separator)
+        .add_item(quit)
+}
+
+/// Rebuild the tray menu after the language or profiles change
+fn refresh_tray_menu(app: &AppHandle) {
+    let config = app.state::<ConfigStore>().get().unwrap_or_default();
+    let pins = app.state::<PinStore>().list();
+    let menu = tray_menu(&app.state::<Localizer>(), &config, &pins);
+    if let Err(e) = app.tray_handle().set_menu(menu) {
+        log_warn!("Failed to update tray menu: {}", e);
+    }
+    update_focus_tray(app);
+}
+
+/// Label the tray focus item with the time left, or as a way to start a session
+fn update_focus_tray(app: &AppHandle) {
+    let label = {
+        let localizer = app.state::<Localizer>();
+        match app.state::<FocusState>().current() {
+            Some(session) => localizer.format(
+                "tray-focus-end",
+                &[(
+                    "minutes",
+                    &session
+                        .remaining_minutes(app.state::<SharedClock>().now())
+                        .to_string(),
+                )],
+            ),
+            None => localizer.text("tray-focus-start"),
+        }
+    };
+    let _ = app.tray_handle().get_item(TRAY_FOCUS).set_title(label);
+}
+
+/// Show the unread reminder popup when escalation says one is due
+async fn remind_unread(app: &AppHandle) {
+    let config = app.state::<ConfigStore>().get().unwrap_or_default();
+    let idle = tauri::async_runtime::spawn_blocking(platform::idle_time)
+        .await
+        .ok()
+        .flatten();
+    let unread = app.state::<InboxSnapshot>().unread_count();
+    let due = app.state::<ReminderEscalation>().tick(
+        &config.reminders,
+        unread,
+        idle,
+        CLOCK_CHECK_INTERVAL,
+    );
+    let Some(reminder) = due else {
+        return;
+    };
+    let notifications = &config.notifications;
+    if !notifications.show_desktop_notifications
+        || is_quiet_hours(
+            app.state::<SharedClock>().as_ref(),
+            &notifications.quiet_hours_start,
+            &notifications.quiet_hours_end,
+        )
+    {
+        return;
+    }
+    let _ = app.emit_all("reminder-escalated", reminder);
+    if alert_delivery(app) == Delivery::InApp || is_presenting(app) {
+        return;
+    }
+    if let Err(e) = open_reminder_popup(app, unread, None, None) {
+        log_warn!("Failed to show unread reminder: {}", e);
+    }
+}
+
+#[tauri::command]
+async fn get_snoozed(snoozes: State<'_, SnoozeQueue>) -> Result<Vec<SnoozedEmail>, String> {
+    Ok(snoozes.pending())
+}
+
+/// Bring back snoozed emails whose time has come. During quiet hours they stay queued, and
+/// everything that came due is brought back once quiet hours end.
+async fn release_snoozes(app: &AppHandle) -> Result<(), String> {
+    let notifications = app
+        .state::<ConfigStore>()
+        .get()
+        .unwrap_or_default()
+        .notifications;
+    if notifications.show_desktop_notifications
+        && is_quiet_hours(
+            app.state::<SharedClock>().as_ref(),
+            &notifications.quiet_hours_start,
+            &notifications.quiet_hours_end,
+        )
+    {
+        return Ok(());
+    }
+    let due = app
+        .state::<SnoozeQueue>()
+        .take_due(app.state::<SharedClock>().now())?;
+    for snoozed in due {
+        let _ = app.emit_all("snooze-expired", &snoozed);
+        if !notifications.show_desktop_notifications {
+            continue;
+        }
+        let response = backend(
+            app,
+            format!("/emails/{}", snoozed.email_id),
+            "GET".to_string(),
+            None,
+        )
+        .await;
+        let Some(email) = response
+            .ok()
+            .and_then(|r| serde_json::from_str::<EmailData>(&r).ok())
+        else {
+            continue;
+        };
+        let (title, body) = {
+            let localizer = app.state::<Localizer>();
+            match snoozed.deadline {
+                Some(deadline) => (
+                    localizer.text("notification-deadline-title"),
+                    localizer.format(
+                        "notification-deadline-body",
+                        &[
+                            ("subject", &email.subject),
+                            ("sender", &email.sender),
+                            ("when", &dates::absolute(&localizer, deadline)),
+                        ],
+                    ),
+                ),
+                None => (
+                    localizer.text("notification-snooze-title"),
+                    localizer.format(
+                        "notification-snooze-body",
+                        &[("subject", &email.subject), ("sender", &email.sender)],
+                    ),
+                ),
+            }
+        };
+        let actions = [
+            EmailAction::MarkRead,
+            EmailAction::Snooze,
+            EmailAction::Reply,
+        ];
+        if let Err(e) =
+            notify_email(app, &email.id, &email.sender_email, &title, &body, &actions).await
+        {
+            log_warn!("Failed to notify about snoozed email: {}", e);
+        }
+    }
+    Ok(())
+}
+
+/// Sync right away instead of waiting for the next scheduled check
+#[tauri::command]
+async fn sync_now(trigger: State<'_, SyncTrigger>) -> Result<(), String> {
+    trigger.request();
+    Ok(())
+}
+
+/// Watch OS connectivity and sync as soon as the machine is back online (after sleep, VPN
+/// reconnect) rather than waiting out `check_interval_minutes`
+fn start_network_watch(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let online = tauri::async_runtime::spawn_blocking(platform::is_online)
+                .await
+                .ok()
+                .flatten();
+            if let Some(online) = online {
+                if let Some(online) = app.state::<ConnectivityMonitor>().observe(online) {
+                    let _ = app.emit_all("connectivity-changed", online);
+                    if online {
+                        app.state::<SyncTrigger>().request();
+                    }
+                }
+            }
+            tokio::time::sleep(CONNECTIVITY_POLL).await;
+        }
+    });
+}
+
+/// Apply config changes as they're saved instead of at the next restart. Most settings are
+/// read fresh on use; the poll interval, tray menu, theme and team sync are pushed here.
+fn start_config_watch(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut changes = app.state::<ConfigStore>().subscribe();
+        let current = app.state::<ConfigStore>().get().unwrap_or_default();
+        let mut interval = sync_plan(&current).interval_minutes;
+        let mut team_url = current.team_sync.url;
+        while changes.changed().await.is_ok() {
+            let revision = *changes.borrow_and_update();
+            let config = match app.state::<ConfigStore>().get() {
+                Ok(config) => config,
+                Err(e) => {
+                    log_warn!("Ignoring config change: {}", e);
+                    continue;
+                }
+            };
+            // The scheduler may be asleep on the old interval
+            let plan = sync_plan(&config);
+            if plan.interval_minutes != interval {
+                interval = plan.interval_minutes;
+                app.state::<SyncTrigger>().request();
+            }
+            refresh_tray_menu(&app);
+            apply_theme(&app);
+            start_feeds(&app);
+            apply_team_bundle(&app);
+            // A new team address shouldn't wait out the old pull interval
+            if config.team_sync.url != team_url {
+                team_url = config.team_sync.url.clone();
+                if config.team_sync.is_enabled() {
+                    let app = app.clone();
+                    tauri::async_runtime::spawn(async move {
+                        if let Err(e) = pull_team_bundle(&app, &config).await {
+                            log_warn!("Team sync failed: {}", e);
+                            app.state::<TeamStore>().set_error(e);
+                        }
+                    });
+                }
+            }
+            let _ = app.emit_all("config-changed", revision);
+        }
+    });
+}
+
+/// Probe the backend while the circuit breaker is open and report each open or close
+fn start_backend_health(app: AppHandle) {
+    let events = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut changes = events.state::<CircuitBreaker>().subscribe();
+        while changes.changed().await.is_ok() {
+            let health = changes.borrow_and_update().clone();
+            let _ = events.emit_all("backend-health", &health);
+        }
+    });
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(backend::PROBE_INTERVAL).await;
+            if !app.state::<CircuitBreaker>().is_open() {
+                continue;
+            }
+            match http_client(&app).await {
+                Ok(client) => {
+                    app.state::<CircuitBreaker>()
+                        .probe(&client, &backend_url(&app))
+                        .await
+                }
+                Err(e) => log_debug!("Backend probe skipped: {}", e),
+            }
+        }
+    });
+}
+
+/// Tell the frontend when a request is waiting on a rate limit
+fn start_rate_limit_events(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut events = app.state::<HttpClient>().subscribe_rate_limits();
+        loop {
+            match events.recv().await {
+                Ok(event) => {
+                    let _ = app.emit_all("rate-limited", &event);
+                }
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    });
+}
+
+/// Daily housekeeping: retention policies and cache purge
+fn start_maintenance(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let config = app.state::<ConfigStore>().get().unwrap_or_default();
+            if let Err(e) = apply_retention(&app, &config).await {
+                log_warn!("Maintenance run failed: {}", e);
+            }
+            if let Err(e) = refresh_remote_flags(&app, &config).await {
+                log_warn!("Feature flag refresh failed: {}", e);
+            }
+            if let Err(e) = send_telemetry(&app, &config).await {
+                log_warn!("Telemetry upload failed: {}", e);
+            }
+            if let Err(e) = app.state::<EmailCache>().maintain() {
+                log_warn!("Cache maintenance failed: {}", e);
+            }
+            attachments::purge_older_than(&attachments::attachment_dir(), attachments::MAX_AGE);
+            if config.updates.auto_check {
+                if let Err(e) = announce_update(&app).await {
+                    log_warn!("Update check failed: {}", e);
+                }
+            }
+            tokio::time::sleep(MAINTENANCE_INTERVAL).await;
+        }
+    });
+}
+
+async fn scheduler_tick(
+    app: &AppHandle,
+    config: &AppConfig,
+    plan: &SyncPlan,
+) -> Result<(), String> {
+    let (synced, emails) = sync_accounts(app, config).await?;
+    let added = publish_inbox_changes(app, &synced, &emails);
+    if let Err(e) = app.state::<EmailCache>().store(&emails) {
+        log_warn!("Failed to cache emails: {}", e);
+    }
+    send_auto_replies(app, config, &added).await;
+    run_rules(app, config, &added).await;
+    run_scripts(app, config, &added).await;
+    announce_codes(app, config, &added).await;
+    track_payments(app, config, &added).await;
+    publish_feeds(app, config, &added).await;
+    digest_newsletters(app, config, &added).await;
+    if let Err(e) = update_badge(app).await {
+        log_warn!("Failed to update badge: {}", e);
+    }
+    if let Some(path) = config.sync.bundle_path() {
+        if app.state::<SettingsWatcher>().changed(&path) {
+            import_bundle(app, &path)?;
+        }
+    }
+    if plan.classify
+        && config.spam.monitor
+        && app.state::<FeatureFlags>().is_enabled("spam_monitor")
+    {
+        check_junk_folder(app, config).await?;
+    }
+    Ok(())
+}
+
+/// Flag junk-folder mail from known contacts or VIPs as possibly misfiled
+async fn check_junk_folder(app: &AppHandle, config: &AppConfig) -> Result<(), String> {
+    let response = backend(
+        app,
+        format!("/junk?limit={}", config.spam.scan_limit),
+        "GET".to_string(),
+        None,
+    )
+    .await?;
+    let junk: Vec<JunkEmail> = serde_json::from_str(&response).map_err(|e| e.to_string())?;
+    let suspects = app
+        .state::<MisfiledTracker>()
+        .new_suspects(&junk, &config.notifications.vip_senders);
+
+    for email in suspects {
+        let _ = app.emit_all("possibly-misfiled", &email);
+        let notifications = &config.notifications;
+        if notifications.show_desktop_notifications
+            && !is_quiet_hours(
+                app.state::<SharedClock>().as_ref(),
+                &notifications.quiet_hours_start,
+                &notifications.quiet_hours_end,
+            )
+        {
+            let localizer = app.state::<Localizer>();
+            let _ = notify_email(
+                app,
+                &email.id,
+                &email.sender_email,
+                &localizer.text("notification-misfiled-title"),
+                &localizer.format(
+                    "notification-misfiled-body",
+                    &[
+                        ("subject", &email.subject),
+                        ("sender", &email.sender),
+                        (
+                            "when",
+                            &dates::format_received_time(&localizer, &email.received_time).relative,
+                        ),
+                    ],
+                ),
+                &[EmailAction::NotSpam, EmailAction::Reply],
+            )
+            .await;
+        }
+    }
+    Ok(())
+}
+
+/// Run the new-email rules over mail that just arrived, in the order they're listed. In
+/// observe-only mode each match is only logged.
+async fn run_rules(app: &AppHandle, config: &AppConfig, emails: &[EmailData]) {
+    let active = app.state::<RuleStore>().active(Trigger::NewEmail);
+    for email in emails {
+        for rule in active.iter().filter(|rule| rule.applies_to(email)) {
+            if config.observe_only {
+                record_activity(
+                    app,
+                    ActivityKind::Observed,
+                    Some(&email.id),
+                    &email.subject,
+                    serde_json::json!({ "rule": rule.name, "actions": rule.actions }),
+                );
+                continue;
+            }
+            if let Err(e) = apply_rule(app, rule, email, false).await {
+                log_warn!("Rule \"{}\" failed on {}: {}", rule.name, email.id, e);
+            }
+        }
+    }
+}
+
+/// Carry out a rule's actions on one email and note it in the audit log. Without `confirmed`,
+/// a delete is only offered to the user.
+async fn apply_rule(
+    app: &AppHandle,
+    rule: &Rule,
+    email: &EmailData,
+    confirmed: bool,
+) -> Result<(), String> {
+    apply_actions(app, &rule.actions, &rule.name, email, confirmed).await?;
+    record_audit(
+        &app.state::<AuditLog>(),
+        AuditEntry::new(
+            AuditAction::RuleApplied,
+            Origin::Rule,
+            Some(email.id.clone()),
+        )
+        .with_details(serde_json::json!({
+            "rule": rule.name,
+            "rule_id": rule.id,
+            "actions": rule.actions
+        })),
+    );
+    record_activity(
+        app,
+        ActivityKind::RuleFired,
+        Some(&email.id),
+        &email.subject,
+        serde_json::json!({ "rule": rule.name, "actions": rule.actions }),
+    );
+    track(app, "rule");
+    let _ = app.emit_all(
+        "rule-applied",
+        serde_json::json!({ "rule_id": rule.id, "email_id": email.id }),
+    );
+    Ok(())
+}
+
+/// Carry out actions on one email, stopping at the first that fails. `name` titles
+/// notifications that have no message of their own. Deletes only go ahead when `confirmed`;
+/// otherwise the user is asked with a notification.
+async fn apply_actions(
+    app: &AppHandle,
+    actions: &[RuleAction],
+    name: &str,
+    email: &EmailData,
+    confirmed: bool,
+) -> Result<(), String> {
+    for action in actions {
+        match action {
+            RuleAction::MarkRead => {
+                let endpoint = format!("/emails/{}/mark-read", email.id);
+                backend(app, endpoint, "POST".to_string(), None).await?;
+            }
+            RuleAction::Archive => {
+                let endpoint = format!("/emails/{}/archive", email.id);
+                backend(app, endpoint, "POST".to_string(), None).await?;
+            }
+            RuleAction::Delete if !confirmed => offer_delete(app, name, email).await?,
+            RuleAction::Delete => {
+                let endpoint = format!("/emails/{}", email.id);
+                backend(app, endpoint, "DELETE".to_string(), None).await?;
+            }
+            RuleAction::Snooze { minutes } => {
+                let endpoint = format!("/emails/{}/snooze?minutes={}", email.id, minutes);
+                backend(app, endpoint, "POST".to_string(), None).await?;
+                let now = app.state::<SharedClock>().now();
+                app.state::<SnoozeQueue>().add(&email.id, *minutes, now)?;
+            }
+            RuleAction::CreateTask => {
+                let body = serde_json::json!({
+                    "title": email.subject,
+                    "description": email.snippet
+                });
+                let endpoint = format!("/emails/{}/create-task", email.id);
+                backend(app, endpoint, "POST".to_string(), Some(body.to_string())).await?;
+            }
+            RuleAction::Notify { message } => {
+                let notifications = app
+                    .state::<ConfigStore>()
+                    .get()
+                    .unwrap_or_default()
+                    .notifications;
+                if !notifications.show_desktop_notifications
+                    || is_quiet_hours(
+                        app.state::<SharedClock>().as_ref(),
+                        &notifications.quiet_hours_start,
+                        &notifications.quiet_hours_end,
+                    )
+                {
+                    continue;
+                }
+                let title = match message.trim() {
+                    "" => name,
+                    message => message,
+                };
+                let actions = [EmailAction::Open];
+                notify_email(
+                    app,
+                    &email.id,
+                    &email.sender_email,
+                    title,
+                    &email.subject,
+                    &actions,
+                )
+                .await?;
+            }
+            RuleAction::Label { name } => {
+                let body = serde_json::json!({ "category": name.trim() });
+                let endpoint = format!("/emails/{}/categorize", email.id);
+                backend(app, endpoint, "POST".to_string(), Some(body.to_string())).await?;
+            }
+            RuleAction::Webhook { url, payload } => {
+                let payload = payload.clone().unwrap_or_else(|| {
+                    serde_json::json!({
+                        "email_id": email.id,
+                        "subject": email.subject,
+                        "sender": email.sender,
+                        "sender_email": email.sender_email,
+                        "received_time": email.received_time,
+                    })
+                });
+                post_webhook(app, url.trim(), &payload).await?;
+            }
+            RuleAction::DraftTemplate { template_id } => {
+                let template = app
+                    .state::<TemplateStore>()
+                    .get(template_id)
+                    .ok_or_else(|| format!("Template \"{}\" no longer exists", template_id))?;
+                let my_name = app.state::<ConfigStore>().get()?.email.display_name;
+                let vars = templates::vars_for_email(email, &my_name);
+                let body = serde_json::json!({
+                    "reply_text": templates::render(&template.body, &vars)
+                });
+                let endpoint = format!("/emails/{}/draft-reply", email.id);
+                backend(app, endpoint, "POST".to_string(), Some(body.to_string())).await?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Ask before a delete that no one confirmed, such as from a rule running on new mail; the
+/// notification's Delete button goes ahead with it
+async fn offer_delete(app: &AppHandle, name: &str, email: &EmailData) -> Result<(), String> {
+    let (title, body) = {
+        let localizer = app.state::<Localizer>();
+        (
+            localizer.format("notification-rule-delete-title", &[("rule", name)]),
+            localizer.format(
+                "notification-rule-delete-body",
+                &[("subject", &email.subject), ("sender", &email.sender)],
+            ),
+        )
+    };
+    let actions = [EmailAction::Delete, EmailAction::Open];
+    notify_email(app, &email.id, &email.sender_email, &title, &body, &actions).await
+}
+
+/// Delete an email once the user has agreed to a delete offered by `offer_delete`
+async fn delete_held_email(app: &AppHandle, email_id: String) -> Result<(), String> {
+    backend(
+        app,
+        format!("/emails/{}", email_id),
+        "DELETE".to_string(),
+        None,
+    )
+    .await?;
+    record_audit(
+        &app.state::<AuditLog>(),
+        AuditEntry::new(AuditAction::Deleted, Origin::User, Some(email_id)),
+    );
+    Ok(())
+}
+
+/// POST an action's JSON to its webhook; privacy mode only lets local addresses through
+async fn post_webhook(
+    app: &AppHandle,
+    url: &str,
+    payload: &serde_json::Value,
+) -> Result<(), String> {
+    let config = app.state::<ConfigStore>().get()?;
+    privacy::ensure_allowed(url, config.privacy_mode)?;
+    let client = http_client(app).await?;
+    let response = client.send(client.post(url).json(payload)).await?;
+    if !response.status().is_success() {
+        return Err(format!("HTTP {}", response.status()));
+    }
+    Ok(())
+}
+
+/// Pull the team's rules and templates on their own interval. The last bundle pulled is used
+/// from startup, so shared rules keep working offline; a failed pull keeps it in place.
+fn start_team_sync(app: AppHandle) {
+    apply_team_bundle(&app);
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let config = app.state::<ConfigStore>().get().unwrap_or_default();
+            if config.team_sync.is_enabled() {
+                if let Err(e) = pull_team_bundle(&app, &config).await {
+                    log_warn!("Team sync failed: {}", e);
+                    app.state::<TeamStore>().set_error(e);
+                }
+            }
+            tokio::time::sleep(config.team_sync.interval()).await;
+        }
+    });
+}
+
+/// Fetch the team's bundle over HTTP or from its Git repository and swap it in
+async fn pull_team_bundle(app: &AppHandle, config: &AppConfig) -> Result<(), String> {
+    let team = &config.team_sync;
+    let url = team.url.trim().to_string();
+    privacy::ensure_allowed(&url, config.privacy_mode)?;
+    let (text, revision) = match team.source {
+        TeamSource::Http => {
+            let client = http_client(app).await?;
+            let response = client.send(client.get(&url)).await?;
+            if !response.status().is_success() {
+                return Err(format!("HTTP {}", response.status()));
+            }
+            let etag = response
+                .headers()
+                .get(reqwest::header::ETAG)
+                .and_then(|value| value.to_str().ok())
+                .unwrap_or_default()
+                .to_string();
+            (response.text().await.map_err(|e| e.to_string())?, etag)
+        }
+        TeamSource::Git => {
+            let (branch, path) = (team.branch.clone(), team.path.clone());
+            tauri::async_runtime::spawn_blocking(move || {
+                let dir = config::config_dir().join("team-repo");
+                let revision = teamsync::git_pull(&url, &branch, &dir)?;
+                Ok::<_, String>((teamsync::read_from_repo(&dir, &path)?, revision))
+            })
+            .await
+            .map_err(|e| e.to_string())??
+        }
+    };
+    let (bundle, skipped) = teamsync::parse_bundle(&text)?;
+    app.state::<TeamStore>().replace(TeamSnapshot {
+        bundle,
+        revision,
+        pulled_at: Some(app.state::<SharedClock>().now()),
+        skipped,
+    })?;
+    apply_team_bundle(app);
+    let _ = app.emit_all("team-sync-changed", app.state::<TeamStore>().status(true));
+    Ok(())
+}
+
+/// Hand the last bundle pulled to the rule and template stores, or take the team's items
+/// away when sync is off
+fn apply_team_bundle(app: &AppHandle) {
+    let team = app
+        .state::<ConfigStore>()
+        .get()
+        .unwrap_or_default()
+        .team_sync;
+    let bundle = if team.is_enabled() {
+        app.state::<TeamStore>().snapshot().bundle
+    } else {
+        Default::default()
+    };
+    app.state::<RuleStore>()
+        .set_team(bundle.rules, team.local_wins);
+    app.state::<TemplateStore>()
+        .set_team(bundle.templates, team.local_wins);
+}
+
+/// Run the enabled scripts over mail that just arrived. Each run happens off the async
+/// workers, since a script may take up to `scripts::TIMEOUT`. In observe-only mode the
+/// actions a script returns are logged instead of carried out.
+async fn run_scripts(app: &AppHandle, config: &AppConfig, emails: &[EmailData]) {
+    let enabled = app.state::<ScriptStore>().enabled();
+    if enabled.is_empty() {
+        return;
+    }
+    for email in emails {
+        for script in &enabled {
+            let (source, target) = (script.source.clone(), email.clone());
+            let run =
+                match tauri::async_runtime::spawn_blocking(move || scripts::run(&source, &target))
+                    .await
+                {
+                    Ok(run) => run,
+                    Err(e) => {
+                        log_warn!("Script \"{}\" did not finish: {}", script.name, e);
+                        continue;
+                    }
+                };
+            if let Some(error) = &run.error {
+                log_warn!(
+                    "Script \"{}\" failed on {}: {}",
+                    script.name,
+                    email.id,
+                    error
+                );
+                continue;
+            }
+            if run.actions.is_empty() {
+                continue;
+            }
+            if config.observe_only {
+                record_activity(
+                    app,
+                    ActivityKind::Observed,
+                    Some(&email.id),
+                    &email.subject,
+                    serde_json::json!({ "script": script.name, "actions": run.actions }),
+                );
+                continue;
+            }
+            if let Err(e) = apply_actions(app, &run.actions, &script.name, email, false).await {
+                log_warn!(
+                    "Script \"{}\" actions failed on {}: {}",
+                    script.name,
+                    email.id,
+                    e
+                );
+                continue;
+            }
+            record_audit(
+                &app.state::<AuditLog>(),
+                AuditEntry::new(
+                    AuditAction::RuleApplied,
+                    Origin::Rule,
+                    Some(email.id.clone()),
+                )
+                .with_details(serde_json::json!({
+                    "script": script.name,
+                    "script_id": script.id,
+                    "actions": run.actions
+                })),
+            );
+            record_activity(
+                app,
+                ActivityKind::RuleFired,
+                Some(&email.id),
+                &email.subject,
+                serde_json::json!({ "script": script.name, "actions": run.actions }),
+            );
+            track(app, "script");
+        }
+    }
+}
+
+/// Answer mail that just arrived with the out-of-office reply, at most once per sender per
+/// period. Replies to untrusted recipients wait for approval. In observe-only mode the reply is
+/// logged, once per sender in `emails`, and not sent.
+async fn send_auto_replies(app: &AppHandle, config: &AppConfig, emails: &[EmailData]) {
+    let now = app.state::<SharedClock>().now();
+    if !app.state::<FeatureFlags>().is_enabled("auto_reply")
+        || !config.auto_reply.is_active(now.date_naive())
+    {
+        return;
+    }
+    let responder = app.state::<AutoResponder>();
+    let approvals = app.state::<ApprovalQueue>();
+    let audit = app.state::<AuditLog>();
+    let body = config
+        .auto_reply
+        .template
+        .as_ref()
+        .and_then(|id| app.state::<TemplateStore>().get(id))
+        .map(|t| t.body)
+        .unwrap_or_else(|| config.auto_reply.body.clone());
+
+    let mut observed = HashSet::new();
+    for email in emails {
+        if !responder.should_reply(&config.auto_reply, email, now) {
+            continue;
+        }
+        let vars = templates::vars_for_email(email, &config.email.display_name);
+        let reply = serde_json::json!({
+            "reply_text": templates::render(&body, &vars)
+        });
+        if config.observe_only {
+            if observed.insert(email.sender_email.to_lowercase()) {
+                record_activity(
+                    app,
+                    ActivityKind::Observed,
+                    Some(&email.id),
+                    &email.subject,
+                    serde_json::json!({ "auto_reply": email.sender_email, "reply": reply }),
+                );
+            }
+            continue;
+        }
+        if config.approvals.needs_approval(&email.sender_email) {
+            // Held for the user; the sender counts as answered so it isn't queued again
+            let text = reply["reply_text"].as_str().unwrap_or_default();
+            match approvals.add(email, text, "auto_reply", &config.approvals, now) {
+                Ok(_) => {
+                    responder.record(&email.sender_email, now);
+                    let _ = app.emit_all("pending-approvals", approvals.list());
+                }
+                Err(e) => log_warn!("Failed to queue auto-reply for approval: {}", e),
+            }
+            continue;
+        }
+        match backend(
+            app,
+            format!("/emails/{}/reply", email.id),
+            "POST".to_string(),
+            Some(reply.to_string()),
+        )
+        .await
+        {
+            Ok(_) => {
+                responder.record(&email.sender_email, now);
+                record_audit(
+                    &audit,
+                    AuditEntry::new(AuditAction::ReplySent, Origin::Rule, Some(email.id.clone()))
+                        .with_details(serde_json::json!({ "rule": "auto_reply" })),
+                );
+            }
+            Err(e) => log_warn!("Auto-reply to {} failed: {}", email.sender_email, e),
+        }
+    }
+}
+
+/// Show one-time codes from new mail in a compact notification with a copy button, and
+/// schedule those messages for deletion when configured
+async fn announce_codes(app: &AppHandle, config: &AppConfig, emails: &[EmailData]) {
+    let settings = &config.verification_codes;
+    if !settings.detect {
+        return;
+    }
+    for email in emails {
+        let Some(code) = otp::find_code(email) else {
+            continue;
+        };
+        if settings.delete_after_minutes > 0 {
+            let now = app.state::<SharedClock>().now();
+            if let Err(e) =
+                app.state::<CodeExpiry>()
+                    .track(&email.id, settings.delete_after_minutes, now)
+            {
+                log_warn!("Failed to schedule deletion of {}: {}", email.id, e);
+            }
+        }
+        let _ = app.emit_all(
+            "verification-code",
+            serde_json::json!({ "email_id": email.id, "sender": email.sender, "code": code }),
+        );
+        // Codes are asked for a moment ago, so quiet hours don't hold them back
+        if !config.notifications.show_desktop_notifications {
+            continue;
+        }
+        let (title, body) = {
+            let localizer = app.state::<Localizer>();
+            (
+                localizer.format("notification-code-title", &[("code", &code)]),
+                localizer.format("notification-code-body", &[("sender", &email.sender)]),
+            )
+        };
+        if let Err(e) = notify_email(
+            app,
+            &email.id,
+            &email.sender_email,
+            &title,
+            &body,
+            &[EmailAction::CopyCode],
+        )
+        .await
+        {
+            log_warn!("Failed to notify about verification code: {}", e);
+        }
+    }
+}
+
+/// Track bills among emails that just arrived. The rules go first; when they spot a bill but
+/// miss its amount or due date, the LLM reads it and the rules fill whatever it leaves out.
+async fn track_payments(app: &AppHandle, config: &AppConfig, emails: &[EmailData]) {
+    if !config.payments.track {
+        return;
+    }
+    for email in emails {
+        let Some(found) = payments::detect(email) else {
+            continue;
+        };
+        let (invoice, source) = if found.is_complete() || !config.payments.use_llm {
+            (found, PaymentSource::Rules)
+        } else {
+            match read_invoice(app, email).await {
+                Ok(Some(answer)) => (answer.merge(found), PaymentSource::Llm),
+                // The LLM says it isn't a bill to pay, e.g. a receipt
+                Ok(None) => continue,
+                Err(e) => {
+                    log_warn!("Invoice extraction for {} failed: {}", email.id, e);
+                    (found, PaymentSource::Rules)
+                }
+            }
+        };
+        if invoice.amount.is_empty() && invoice.due_date.is_none() {
+            continue;
+        }
+        let payment = Payment {
+            email_id: email.id.clone(),
+            subject: email.subject.clone(),
+            invoice,
+            source,
+            paid: false,
+            reminded: false,
+            detected_at: app.state::<SharedClock>().now(),
+        };
+        match app.state::<PaymentStore>().insert(&payment) {
+            Ok(true) => {
+                let _ = app.emit_all("payment-detected", &payment);
+            }
+            Ok(false) => {}
+            Err(e) => log_warn!("Failed to track bill {}: {}", email.id, e),
+        }
+    }
+}
+
+async fn read_invoice(app: &AppHandle, email: &EmailData) -> Result<Option<Invoice>, String> {
+    let today = app
+        .state::<SharedClock>()
+        .local_now()
+        .format("%Y-%m-%d (%A)")
+        .to_string();
+    let prompt = llm::invoice_prompt(&parsing::new_content(&email.body), &email.subject, &today);
+    let output = run_llm(app, &prompt).await?;
+    payments::parse_invoice(&output.text)
+}
+
+/// Remind once about each unpaid bill coming due within `remind_days_before`. During quiet
+/// hours reminders wait rather than being dropped.
+async fn remind_payments(app: &AppHandle) -> Result<(), String> {
+    let config = app.state::<ConfigStore>().get().unwrap_or_default();
+    let notifications = &config.notifications;
+    if !config.payments.track
+        || !notifications.show_desktop_notifications
+        || is_quiet_hours(
+            app.state::<SharedClock>().as_ref(),
+            &notifications.quiet_hours_start,
+            &notifications.quiet_hours_end,
+        )
+    {
+        return Ok(());
+    }
+    let today = app
+        .state::<SharedClock>()
+        .now()
+        .with_timezone(&chrono::Local)
+        .date_naive();
+    let store = app.state::<PaymentStore>();
+    let horizon = today + chrono::Duration::days(config.payments.remind_days_before as i64);
+    for payment in store.due_for_reminder(horizon)? {
+        let Some(due_date) = payment.invoice.due_date else {
+            continue;
+        };
+        let days = (due_date - today).num_days();
+        let invoice = &payment.invoice;
+        let amount = format!("{} {}", invoice.currency, invoice.amount);
+        let amount = match amount.trim() {
+            "" => payment.subject.as_str(),
+            amount => amount,
+        };
+        let (title, body) = {
+            let localizer = app.state::<Localizer>();
+            let title = if days < 0 {
+                localizer.text("notification-payment-overdue-title")
+            } else {
+                localizer.format("notification-payment-title", &[("days", &days.to_string())])
+            };
+            let body = localizer.format(
+                "notification-payment-body",
+                &[("payee", &invoice.payee), ("amount", amount)],
+            );
+            (title, body)
+        };
+        let _ = app.emit_all("payment-due", &payment);
+        let actions = [EmailAction::MarkPaid, EmailAction::Open];
+        if let Err(e) = notify_email(app, &payment.email_id, "", &title, &body, &actions).await {
+            log_warn!("Failed to remind about bill {}: {}", payment.email_id, e);
+        }
+        store.mark_reminded(&payment.email_id)?;
+    }
+    Ok(())
+}
+
+/// Move messages with a one-time code to Deleted Items once their time is up
+async fn expire_codes(app: &AppHandle) -> Result<(), String> {
+    let due = app
+        .state::<CodeExpiry>()
+        .take_due(app.state::<SharedClock>().now())?;
+    for message in due {
+        match backend(
+            app,
+            format!("/emails/{}", message.email_id),
+            "DELETE".to_string(),
+            None,
+        )
+        .await
+        {
+            Ok(_) => record_audit(
+                &app.state::<AuditLog>(),
+                AuditEntry::new(AuditAction::Deleted, Origin::Rule, Some(message.email_id))
+                    .with_details(serde_json::json!({ "rule": "verification_code" })),
+            ),
+            Err(e) => log_warn!("Failed to delete {}: {}", message.email_id, e),
+        }
+    }
+    Ok(())
+}
+
+/// Copy new mail that matches a feed's rules into that feed. Newsletters are only known once
+/// the full email is fetched, so a feed that takes them looks at every new email.
+async fn publish_feeds(app: &AppHandle, config: &AppConfig, emails: &[EmailData]) {
+    let feeds = &config.feeds;
+    if !feeds.enabled {
+        return;
+    }
+    let takes_newsletters = feeds.feeds.iter().any(|feed| feed.newsletters);
+    for email in emails {
+        if !takes_newsletters && !feeds.feeds.iter().any(|feed| feed.matches_headers(email)) {
+            continue;
+        }
+        if let Err(e) = publish_to_feeds(app, feeds, email).await {
+            log_warn!("Publishing {} to feeds failed: {}", email.id, e);
+        }
+    }
+}
+
+async fn publish_to_feeds(
+    app: &AppHandle,
+    feeds: &FeedConfig,
+    email: &EmailData,
+) -> Result<(), String> {
+    let response = get_email(app.clone(), email.id.clone()).await?;
+    let value: serde_json::Value = serde_json::from_str(&response).map_err(|e| e.to_string())?;
+    let is_newsletter = value["is_newsletter"].as_bool().unwrap_or(false);
+    let published = dates::parse_received_time(&email.received_time)
+        .unwrap_or_else(|| app.state::<SharedClock>().now());
+    for feed in feeds
+        .feeds
+        .iter()
+        .filter(|feed| feed.matches(email, is_newsletter))
+    {
+        let entry = FeedEntry::new(&feed.name, email, value["html_body"].as_str(), published);
+        app.state::<FeedStore>().publish(entry)?;
+    }
+    Ok(())
+}
+
+/// Serve the Atom feeds once they're turned on. The port is read when the server starts;
+/// turning feeds off answers every request with 404.
+fn start_feeds(app: &AppHandle) {
+    let config = app.state::<ConfigStore>().get().unwrap_or_default().feeds;
+    if !config.enabled || !app.state::<FeedStore>().start_serving() {
+        return;
+    }
+    let listener = match feeds::listen(config.port) {
+        Ok(listener) => listener,
+        Err(e) => {
+            log_warn!("Feeds unavailable on port {}: {}", config.port, e);
+            return;
+        }
+    };
+    let app = app.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        for stream in listener.incoming().flatten() {
+            if let Err(e) = serve_feed(&app, stream) {
+                log_warn!("Failed to serve feed: {}", e);
+            }
+        }
+    });
+}
+
+fn serve_feed(app: &AppHandle, stream: std::net::TcpStream) -> Result<(), String> {
+    let request = feeds::read_request(&stream)?;
+    if !request.is_local() {
+        return feeds::respond(stream, "403 Forbidden", "text/plain", "Forbidden");
+    }
+    if request.method != "GET" {
+        return feeds::respond(stream, "405 Method Not Allowed", "text/plain", "GET only");
+    }
+    let config = app.state::<ConfigStore>().get().unwrap_or_default().feeds;
+    let feed = request
+        .feed_name()
+        .filter(|_| config.enabled)
+        .and_then(|name| config.feed(name));
+    let Some(feed) = feed else {
+        return feeds::respond(stream, "404 Not Found", "text/plain", "No such feed");
+    };
+    let entries = app.state::<FeedStore>().entries(&feed.name);
+    let document = feeds::atom(feed, &entries, app.state::<SharedClock>().now());
+    feeds::respond(
+        stream,
+        "200 OK",
+        "application/atom+xml; charset=utf-8",
+        &document,
+    )
+}
+
+/// Summarize newsletters that just arrived, from their reader view, and archive them. The
+/// summary is kept with the audit entry and announced with `newsletter-digested`.
+async fn digest_newsletters(app: &AppHandle, config: &AppConfig, emails: &[EmailData]) {
+    if !config.newsletters.auto_summarize_archive {
+        return;
+    }
+    for email in emails {
+        if let Err(e) = digest_newsletter(app, config, email).await {
+            log_warn!("Newsletter digest of {} failed: {}", email.id, e);
+        }
+    }
+}
+
+async fn digest_newsletter(
+    app: &AppHandle,
+    config: &AppConfig,
+    email: &EmailData,
+) -> Result<(), String> {
+    // The List-Unsubscribe check and the HTML body both come with the full email
+    let response = get_email(app.clone(), email.id.clone()).await?;
+    let value: serde_json::Value = serde_json::from_str(&response).map_err(|e| e.to_string())?;
+    if !value["is_newsletter"].as_bool().unwrap_or(false)
+        && !config.newsletters.is_listed(&email.sender_email)
+    {
+        return Ok(());
+    }
+    if config.observe_only {
+        record_activity(
+            app,
+            ActivityKind::Observed,
+            Some(&email.id),
+            &email.subject,
+            serde_json::json!({ "rule": "newsletter", "actions": ["summarize", "archive"] }),
+        );
+        return Ok(());
+    }
+    let view = reader::reader_view(email, value["html_body"].as_str());
+    let response =
+        summarize_email(app.clone(), view.markdown, None, Some(email.id.clone())).await?;
+    let summary = summary_text(&response)?;
+    backend(
+        app,
+        format!("/emails/{}/archive", email.id),
+        "POST".to_string(),
+        None,
+    )
+    .await?;
+    record_audit(
+        &app.state::<AuditLog>(),
+        AuditEntry::new(AuditAction::Archived, Origin::Rule, Some(email.id.clone()))
+            .with_details(serde_json::json!({ "rule": "newsletter", "summary": summary })),
+    );
+    track(app, "newsletter_digest");
+    let _ = app.emit_all(
+        "newsletter-digested",
+        serde_json::json!({
+            "email_id": email.id,
+            "subject": email.subject,
+            "sender": email.sender,
+            "summary": summary,
+        }),
+    );
+    Ok(())
+}
+
+/// Open the payment table, falling back to one in memory so the app still starts
+fn open_payments() -> PaymentStore {
+    PaymentStore::open(&config::config_dir().join("payments.db")).unwrap_or_else(|e| {
+        log_warn!("Payment tracking unavailable, using memory only: {}", e);
+        PaymentStore::in_memory().expect("failed to create in-memory payment table")
+    })
+}
+
+/// Open the cache, falling back to a fresh one (refilled by the scheduler) if it is corrupt.
+/// When encryption is on but the keychain can't be used, nothing is written to disk.
+fn open_cache(security: &SecurityConfig) -> EmailCache {
+    let master = if security.encrypt_cache {
+        match encryption::master_key() {
+            Ok(master) => Some(master),
+            Err(e) => {
+                log_warn!("Cache encryption unavailable, using memory only: {}", e);
+                return EmailCache::in_memory().expect("failed to create in-memory cache");
+            }
+        }
+    } else {
+        None
+    };
+    let path = config::config_dir().join("cache.db");
+    match EmailCache::open_or_rebuild(&path)
+        .and_then(|(cache, _)| cache.with_master_key(master.as_ref()))
+    {
+        Ok(cache) => cache,
+        Err(e) => {
+            log_warn!("Email cache unavailable, using memory only: {}", e);
+            EmailCache::in_memory().expect("failed to create in-memory cache")
+        }
+    }
+}
+
+/// Check every invoke against the calling window's capabilities before running the command
+fn guarded(
+    handler: impl Fn(tauri::Invoke) + Send + Sync + 'static,
+) -> impl Fn(tauri::Invoke) + Send + Sync + 'static {
+    move |invoke| {
+        let window = invoke.message.window();
+        let allowed = window.state::<CapabilityStore>().authorize(
+            window.label(),
+            invoke.message.command(),
+            invoke.message.payload(),
+            std::time::Instant::now(),
+        );
+        match allowed {
+            Ok(()) => handler(invoke),
+            Err(e) => {
+                log_warn!(
+                    "Blocked {} from {}: {}",
+                    invoke.message.command(),
+                    window.label(),
+                    e
+                );
+                invoke.resolver.reject(e);
+            }
+        }
+    }
+}
+
+fn main() {
+    let overrides = Overrides::from_process().unwrap_or_else(|e| {
+        log_warn!("Ignoring overrides: {}", e);
+        Overrides::default()
+    });
+    if let Some(level) = overrides.log_level {
+        logging::set_level(level);
+    }
+    if let Some(dir) = overrides.config_dir.clone() {
+        if let Err(e) = config::use_config_dir(dir) {
+            log_warn!("Ignoring config directory override: {}", e);
+        }
+    }
+    let config_store = ConfigStore::new().with_overrides(overrides);
+    let startup_config = config_store.get().unwrap_or_default();
+    let localizer = Localizer::new(&startup_config.ui.locale);
+
+    let pins = PinStore::load(config::config_dir().join("pinned.json"));
+    let app_lock = AppLock::load(config::config_dir().join("app_lock.json"));
+    if startup_config.app_lock.enabled && startup_config.app_lock.lock_on_launch {
+        app_lock.lock();
+    }
+    let system_tray =
+        SystemTray::new().with_menu(tray_menu(&localizer, &startup_config, &pins.list()));
+
+    // The OS launches a new process for each share; when SERINA already runs, it takes over
+    let share = intake::from_process();
+    let handoff = match intake::listen(intake::HANDOFF_PORT) {
+        Ok(listener) => Some(listener),
+        Err(_) => {
+            if let Some(request) = &share {
+                match intake::hand_off(intake::HANDOFF_PORT, request) {
+                    Ok(()) => return,
+                    Err(e) => log_warn!("Failed to reach the running instance: {}", e),
+                }
+            }
+            None
+        }
+    };
+
+    tauri::Builder::default()
+        .manage(config_store)
+        .manage(clock::system())
+        .manage(HttpClient::new())
+        .manage(CircuitBreaker::default())
+        .manage(Budget::load(config::config_dir().join("llm_usage.json")))
+        .manage(SummaryCache::default())
+        .manage(Clipboard::default())
+        .manage(IntakeQueue::default())
+        .manage(OutboxStore::new(std::env::temp_dir().join("serina-outbox")))
+        .manage(MuteList::load(
+            config::config_dir().join("muted_threads.json"),
+        ))
+        .manage(FeedStore::load(config::config_dir().join("feeds.json")))
+        .manage(CodeExpiry::load(
+            config::config_dir().join("expiring_codes.json"),
+        ))
+        .manage(open_payments())
+        .manage(RetryQueue::load(
+            config::config_dir().join("failed_replies.json"),
+            config::config_dir().join("failed_replies"),
+        ))
+        .manage(AuditLog::new(config::config_dir().join("audit.log")))
+        .manage(ActivityLog::new(config::config_dir().join("activity.log")))
+        .manage(CapabilityStore::default())
+        .manage(Confirmations::new())
+        .manage(GeneratedReplies::new())
+        .manage(Telemetry::load(config::config_dir().join("telemetry.json")))
+        .manage(app_lock)
+        .manage(ApprovalQueue::load(
+            config::config_dir().join("pending_approvals.json"),
+        ))
+        .manage(AutoResponder::load(
+            config::config_dir().join("auto_reply.json"),
+        ))
+        .manage(TemplateStore::load(
+            config::config_dir().join("templates.json"),
+        ))
+        .manage(RuleStore::load(config::config_dir().join("rules.json")))
+        .manage(TeamStore::load(config::config_dir().join("team.json")))
+        .manage(ScriptStore::load(config::config_dir().join("scripts.json")))
+        .manage(MisfiledTracker::new())
+        .manage(FeatureFlags::load(
+            config::config_dir().join("features.json"),
+        ))
+        .manage(PermissionState::new())
+        .manage(NotificationList::load(
+            config::config_dir().join("notifications.json"),
+        ))
+        .manage(localizer)
+        .manage(SettingsWatcher::new())
+        .manage(TriageState::new())
+        .manage(SnoozeQueue::load(config::config_dir().join("snoozed.json")))
+        .manage(pins)
+        .manage(ProposalStore::load(
+            config::config_dir().join("meeting_proposals.json"),
+        ))
+        .manage(StreakTracker::load(
+            config::config_dir().join("streaks.json"),
+        ))
+        .manage(FocusState::new())
+        .manage(SystemTheme::new())
+        .manage(CompactMode::new())
+        .manage(PositionMemory::new())
+        .manage(Shutdown::new())
+        .manage(AutoSwitch::new())
+        .manage(SyncTrigger::new())
+        .manage(AccountBackoff::new())
+        .manage(InboxSnapshot::new())
+        .manage(ReminderEscalation::new())
+        .manage(WindowPresence::new())
+        .manage(ConnectivityMonitor::new())
+        .manage(open_cache(&startup_config.security))
+        .setup(move |app| {
+            if app.state::<ConfigStore>().overrides().mock {
+                log_info!("Mock mode: serving fixtures instead of the backend and LLM providers");
+                app.manage(MockBackend::new()?);
+            }
+            // Built here rather than in tauri.conf.json so it gets the navigation handler
+            build_window(
+                &app.handle(),
+                AppWindow::Main,
+                AppWindow::Main.url(&[]),
+                None,
+            )?
+            .show()?;
+            #[cfg(target_os = "macos")]
+            if app
+                .state::<ConfigStore>()
+                .get()
+                .map(|c| c.ui.menu_bar_only)
+                .unwrap_or_default()
+            {
+                app.set_activation_policy(tauri::ActivationPolicy::Accessory);
+            }
+            // Under Wayland without an AppIndicator host the tray icon never appears, so keep
+            // the window up and let the UI offer its own controls instead
+            if !platform::tray_available() {
+                if let Ok(window) = app_window(&app.handle(), AppWindow::Main) {
+                    let _ = window.show();
+                }
+                let _ = app.emit_all("tray-unavailable", {});
+            }
+            if let Some(system) = app_window(&app.handle(), AppWindow::Main)
+                .ok()
+                .and_then(|w| w.theme().ok())
+            {
+                observe_system_theme(&app.handle(), system);
+            } else {
+                apply_theme(&app.handle());
+            }
+            let ui = app.state::<ConfigStore>().get().unwrap_or_default().ui;
+            if ui.window_effect != WindowEffect::None {
+                if let Err(e) = apply_window_effect(&app.handle(), ui.window_effect) {
+                    log_warn!("Window effect unavailable: {}", e);
+                }
+            }
+            if ui.compact_mode {
+                let handle = app.handle();
+                tauri::async_runtime::spawn(async move {
+                    if let Err(e) = set_compact_mode(handle, true).await {
+                        log_warn!("Failed to start in compact mode: {}", e);
+                    }
+                });
+            }
+            start_scheduler(app.handle());
+            start_network_watch(app.handle());
+            start_clock_watch(app.handle());
+            start_config_watch(app.handle());
+            start_rate_limit_events(app.handle());
+            start_backend_health(app.handle());
+            start_maintenance(app.handle());
+            start_team_sync(app.handle());
+            register_triage_shortcuts(&app.handle());
+            if let Some(listener) = handoff {
+                start_intake(app.handle(), listener);
+            }
+            start_feeds(&app.handle());
+            if let Err(e) = platform::register_share_target(forward_share) {
+                log_warn!("Share target unavailable: {}", e);
+            }
+            if let Some(request) = &share {
+                if let Err(e) = open_shared_compose(&app.handle(), request) {
+                    log_warn!("Failed to open shared item: {}", e);
+                }
+            }
+            Ok(())
+        })
+        .on_window_event(|event| {
+            let window = event.window();
+            match (AppWindow::from_label(window.label()), event.event()) {
+                (Some(AppWindow::Main), tauri::WindowEvent::Focused(focused)) => {
+                    window.state::<WindowPresence>().set_focused(*focused);
+                }
+                (
+                    Some(AppWindow::Main),
+                    tauri::WindowEvent::FileDrop(tauri::FileDropEvent::Dropped(paths)),
+                ) => {
+                    stage_dropped_files(window.app_handle(), paths.clone());
+                }
+                (Some(AppWindow::Main), tauri::WindowEvent::ThemeChanged(system)) => {
+                    observe_system_theme(&window.app_handle(), *system);
+                }
+                (Some(AppWindow::MiniInbox), tauri::WindowEvent::Moved(position)) => {
+                    window.state::<PositionMemory>().moved(WindowPosition {
+                        x: position.x,
+                        y: position.y,
+                    });
+                }
+                (Some(AppWindow::Reminder), tauri::WindowEvent::Destroyed) => {
+                    window.state::<CapabilityStore>().revoke(window.label());
+                }
+                (Some(AppWindow::MiniInbox), tauri::WindowEvent::Destroyed) => {
+                    if let Err(e) = remember_mini_inbox_position(&window.app_handle()) {
+                        log_warn!("Failed to save mini inbox position: {}", e);
+                    }
+                }
+                _ => {}
+            }
+        })
+        .system_tray(system_tray)
+        .register_uri_scheme_protocol(mailview::SCHEME, serve_mail)
+        .on_system_tray_event(|app, event| match event {
             SystemTrayEvent::LeftClick {
                 position: _,
                 size: _,
                 ..
             } => {
-                let window = app.get_window("main").unwrap();
-                window.show().unwrap();
-                window.set_focus().unwrap();
+                show_main_window(app);
             }
             SystemTrayEvent::MenuItemClick { id, .. } => match id.as_str() {
                 "quit" => {
-                    std::process::exit(0);
+                    let app = app.clone();
+                    tauri::async_runtime::spawn(async move { quit(&app).await });
                 }
                 "show" => {
-                    let window = app.get_window("main").unwrap();
-                    window.show().unwrap();
-                    window.set_focus().unwrap();
+                    show_main_window(app);
                 }
                 "settings" => {
                     let _ = app.emit_all("show-settings", {});
                 }
+                id if id.starts_with(TRAY_PROFILE_PREFIX) => {
+                    let app = app.clone();
+                    let name = id[TRAY_PROFILE_PREFIX.len()..].to_string();
+                    tauri::async_runtime::spawn(async move {
+                        if let Err(e) = set_active_profile(app, name).await {
+                            log_warn!("Failed to switch notification profile: {}", e);
+                        }
+                    });
+                }
+                id if id.starts_with(TRAY_PIN_OPEN_PREFIX) => {
+                    show_main_window(app);
+                    let _ = app.emit_all("open-email", &id[TRAY_PIN_OPEN_PREFIX.len()..]);
+                }
+                id if id.starts_with(TRAY_PIN_DONE_PREFIX) => {
+                    let email_id = id[TRAY_PIN_DONE_PREFIX.len()..].to_string();
+                    if let Err(e) = unpin_email(app.clone(), email_id) {
+                        log_warn!("Failed to dismiss pin: {}", e);
+                    }
+                }
+                TRAY_UPCOMING => {
+                    let app = app.clone();
+                    tauri::async_runtime::spawn(async move {
+                        if let Err(e) = show_upcoming_window(app).await {
+                            log_warn!("Failed to open upcoming items: {}", e);
+                        }
+                    });
+                }
+                TRAY_LOCK => lock_app_now(app),
+                TRAY_ACTIVITY => {
+                    let app = app.clone();
+                    tauri::async_runtime::spawn(async move {
+                        if let Err(e) = show_activity_window(app).await {
+                            log_warn!("Failed to open the activity timeline: {}", e);
+                        }
+                    });
+                }
+                TRAY_FOCUS => {
+                    let app = app.clone();
+                    tauri::async_runtime::spawn(async move {
+                        let running = app.state::<FocusState>().current().is_some();
+                        let result = if running {
+                            end_focus_session(app.clone()).await.map(|_| ())
+                        } else {
+                            start_focus_session(app.clone(), None).await.map(|_| ())
+                        };
+                        if let Err(e) = result {
+                            log_warn!("Focus session toggle failed: {}", e);
+                        }
+                    });
+                }
                 _ => {}
             },
             _ => {}
         })
-        .invoke_handler(tauri::generate_handler![
+        .invoke_handler(guarded(tauri::generate_handler![
             show_settings_window,
             show_reminder_popup,
+            show_mini_inbox,
+            run_email_action,
             show_system_notification,
+            get_notification_permission,
+            request_notification_permission,
+            get_notification_list,
+            dismiss_notification,
+            is_tray_available,
+            clear_notifications,
             backend_request,
             get_emails,
             get_email,
+            list_attachments,
+            download_attachment,
+            print_email,
+            get_printable_email,
+            print_window,
+            copy_email_as_markdown,
+            copy_summary,
+            get_reader_view,
+            copy_verification_code,
+            get_upcoming_payments,
+            mark_payment_paid,
+            dismiss_payment,
+            get_staged_attachments,
+            remove_staged_attachment,
+            get_compose_draft,
+            pin_email,
+            unpin_email,
+            get_pinned_emails,
+            mute_thread,
+            unmute_thread,
+            get_muted_threads,
+            get_shared_mailboxes,
+            claim_email,
+            release_email,
+            discard_compose_draft,
+            check_recipients,
+            send_email,
+            get_hidden_duplicate_count,
+            compact_database,
+            verify_cache_integrity,
             send_reply,
+            get_failed_replies,
+            retry_failed_reply,
+            edit_failed_reply,
+            discard_failed_reply,
+            list_pending_approvals,
+            approve,
+            reject,
             mark_email_read,
             create_task_from_email,
             snooze_email,
+            get_snoozed,
+            not_spam,
+            triage_start,
+            get_triage_selection,
+            triage_select,
+            triage,
             get_unread_count,
+            get_sent_emails,
+            get_outbox,
+            retry_outbox_item,
+            delete_outbox_item,
+            get_sent_status,
+            get_tracked_sent,
+            preview_retention,
+            run_retention,
+            request_confirmation,
+            purge_local_data,
+            get_telemetry_preview,
+            set_telemetry_enabled,
+            get_audit_log,
+            export_audit_log,
+            list_templates,
+            save_template,
+            delete_template,
+            render_template,
+            draft_template_reply,
+            get_rule_schema,
+            list_rules,
+            save_rule,
+            delete_rule,
+            validate_rule,
+            simulate_rule,
+            run_rule,
+            list_scripts,
+            save_script,
+            delete_script,
+            test_script,
+            get_team_sync_status,
+            sync_team_now,
             summarize_email,
             generate_task_from_email,
+            get_email_segments,
+            extract_email_data,
+            extract_action_items,
+            create_reminder_from_email,
+            propose_meeting_times,
+            get_inbox_analytics,
+            get_streaks,
+            start_focus_session,
+            end_focus_session,
+            get_focus_session,
+            show_upcoming_window,
+            get_activity_feed,
+            get_lock_status,
+            lock_app,
+            unlock_app,
+            set_app_lock_pin,
+            show_activity_window,
+            get_scheduled_items,
+            cancel_scheduled_item,
+            reschedule_item,
+            set_active_profile,
+            get_theme,
+            get_theme_presets,
+            set_theme,
+            set_window_effect,
+            set_compact_mode,
+            respond_to_meeting_reply,
             generate_reply,
+            translate_text,
+            proofread_text,
+            count_tokens,
+            get_llm_usage,
             get_config,
             save_config,
+            export_settings,
+            import_settings,
             health_check,
+            get_backend_health,
+            is_feature_enabled,
+            list_feature_flags,
+            set_feature_flag,
+            get_locale,
+            set_locale,
+            format_received_time,
+            get_power_state,
+            sync_now,
+            check_for_updates,
+            install_update,
+            test_backend_connection,
+            test_email_connection,
+            test_llm_connection,
+            get_certificate_pin,
+            set_certificate_pins,
             minimize_window,
             maximize_window,
-            close_window
-        ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
-}
\ No newline at end of file
+            close_window,
+            open_main_window,
+            get_ui_config,
+            open_external,
+            set_active_view
+        ]))
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            // Cmd+Q and the like go through the same orderly quit as the tray
+            if let tauri::RunEvent::ExitRequested { api, .. } = event {
+                if !app.state::<Shutdown>().is_stopping() {
+                    api.prevent_exit();
+                    let app = app.clone();
+                    tauri::async_runtime::spawn(async move { quit(&app).await });
+                }
+            }
+        });
+}
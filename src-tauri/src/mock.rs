@@ -0,0 +1,228 @@
+// SERINA Mock Backend
+// Canned email and LLM responses for `--mock`, so the frontend runs without Outlook or API keys
+
+use crate::llm::{Completion, Prompt, ProviderRoute};
+use crate::utils::{AppConfig, EmailData};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+const EMAILS: &str = include_str!("../fixtures/mock/emails.json");
+const COMPLETIONS: &str = include_str!("../fixtures/mock/completions.json");
+
+/// Stands in for the Python backend and LLM providers. Actions change the in-memory inbox, so
+/// marking an email read or archiving it behaves as it would against Outlook.
+pub struct MockBackend {
+    emails: Mutex<Vec<EmailData>>,
+    config: Mutex<Value>,
+    completions: HashMap<String, String>,
+}
+
+impl MockBackend {
+    pub fn new() -> Result<Self, String> {
+        let emails =
+            serde_json::from_str(EMAILS).map_err(|e| format!("Bad email fixture: {}", e))?;
+        let completions = serde_json::from_str(COMPLETIONS)
+            .map_err(|e| format!("Bad completion fixture: {}", e))?;
+        let config = serde_json::to_value(AppConfig::default()).map_err(|e| e.to_string())?;
+        Ok(Self {
+            emails: Mutex::new(emails),
+            config: Mutex::new(config),
+            completions,
+        })
+    }
+
+    /// Answer a backend request the way the FastAPI server would
+    pub fn respond(
+        &self,
+        method: &str,
+        endpoint: &str,
+        body: Option<&str>,
+    ) -> Result<String, String> {
+        let url = url::Url::parse("http://mock")
+            .and_then(|base| base.join(endpoint))
+            .map_err(|e| e.to_string())?;
+        let query: HashMap<String, String> = url.query_pairs().into_owned().collect();
+        let segments: Vec<&str> = url.path().trim_matches('/').split('/').collect();
+        let mut emails = self.emails.lock().unwrap();
+
+        let response = match (method.to_uppercase().as_str(), segments.as_slice()) {
+            ("GET", ["health"]) => json!({ "status": "healthy", "service": "SERINA Mock Backend" }),
+            ("GET", ["diagnostics", "email"]) => json!({ "connected": true }),
+            ("GET", ["emails"]) => {
+                let limit = query
+                    .get("limit")
+                    .and_then(|limit| limit.parse().ok())
+                    .unwrap_or(20);
+                let account = query.get("account").map(String::as_str).unwrap_or("");
+                let listed: Vec<&EmailData> = emails
+                    .iter()
+                    .filter(|email| account.is_empty() || email.account == account)
+                    .take(limit)
+                    .collect();
+                json!(listed)
+            }
+            ("GET", ["emails", "unread-count"]) => {
+                json!({ "count": emails.iter().filter(|email| email.is_unread).count() })
+            }
+            (
+                "GET",
+                ["accounts"]
+                | ["emails", "aging"]
+                | ["emails", _, "attachments"]
+                | ["emails", _, "replies"]
+                | ["junk"]
+                | ["sent"]
+                | ["sent", "tracked"]
+                | ["outbox"]
+                | ["calendar", "busy"],
+            ) => json!([]),
+            ("GET", ["contacts", "known"]) => {
+                // Everyone who has written in counts as a contact
+                let known: Vec<String> = url
+                    .query_pairs()
+                    .filter(|(key, _)| key == "email")
+                    .map(|(_, address)| address.to_lowercase())
+                    .filter(|address| {
+                        emails
+                            .iter()
+                            .any(|email| email.sender_email.eq_ignore_ascii_case(address))
+                    })
+                    .collect();
+                json!({ "known": known })
+            }
+            ("GET", ["emails", id]) => json!(find(&emails, id)?),
+            ("POST", ["emails", "send"]) => json!({ "message": "Email sent" }),
+            ("POST", ["emails", id, "mark-read"]) => {
+                find_mut(&mut emails, id)?.is_unread = false;
+                json!({ "message": "Email marked as read" })
+            }
+            ("POST", ["emails", id, "archive"]) | ("DELETE", ["emails", id]) => {
+                find(&emails, id)?;
+                emails.retain(|email| email.id != *id);
+                json!({ "message": "Email removed" })
+            }
+            ("POST", ["emails", id, verb @ ("claim" | "release")]) => {
+                let body: Value = serde_json::from_str(body.unwrap_or("{}"))
+                    .map_err(|e| format!("HTTP 422: {}", e))?;
+                let name = body["name"].as_str().unwrap_or_default();
+                let email = find_mut(&mut emails, id)?;
+                let free = email.claimed_by.is_empty() || email.claimed_by == name;
+                let wanted = if *verb == "claim" { name } else { "" };
+                let changed = (free || body["force"].as_bool().unwrap_or(false))
+                    && email.claimed_by != wanted;
+                if changed {
+                    email.claimed_by = wanted.to_string();
+                }
+                json!({ "claimed_by": email.claimed_by, "changed": changed })
+            }
+            ("POST", ["emails", id, _]) => {
+                find(&emails, id)?;
+                json!({ "message": "Done" })
+            }
+            ("GET", ["config"]) => self.config.lock().unwrap().clone(),
+            ("POST", ["config"]) => {
+                let body: Value = serde_json::from_str(body.unwrap_or("{}"))
+                    .map_err(|e| format!("HTTP 422: {}", e))?;
+                *self.config.lock().unwrap() = body["config"].clone();
+                json!({ "message": "Configuration updated successfully" })
+            }
+            ("POST", ["outbox", ..] | ["calendar", "meetings"]) | ("DELETE", ["outbox", _]) => {
+                json!({ "message": "Done" })
+            }
+            _ => {
+                return Err(format!(
+                    "HTTP 404: no mock response for {} {}",
+                    method, endpoint
+                ))
+            }
+        };
+        Ok(response.to_string())
+    }
+
+    /// The canned answer for a prompt's feature
+    pub fn complete(&self, prompt: &Prompt) -> Completion {
+        let text = self
+            .completions
+            .get(prompt.feature.key())
+            .cloned()
+            .unwrap_or_default();
+        Completion {
+            tokens: crate::tokens::count_tokens(&text, "gpt-3.5-turbo") as u64,
+            text,
+            route: ProviderRoute {
+                provider: "mock".to_string(),
+                model: "mock".to_string(),
+                failures: Vec::new(),
+            },
+        }
+    }
+}
+
+fn find<'a>(emails: &'a [EmailData], id: &str) -> Result<&'a EmailData, String> {
+    emails
+        .iter()
+        .find(|email| email.id == id)
+        .ok_or_else(|| "HTTP 404: Email not found".to_string())
+}
+
+fn find_mut<'a>(emails: &'a mut [EmailData], id: &str) -> Result<&'a mut EmailData, String> {
+    emails
+        .iter_mut()
+        .find(|email| email.id == id)
+        .ok_or_else(|| "HTTP 404: Email not found".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mailboxes::ClaimState;
+
+    #[test]
+    fn test_mock_inbox() {
+        let mock = MockBackend::new().unwrap();
+        let unread = |mock: &MockBackend| {
+            let response = mock.respond("GET", "/emails/unread-count", None).unwrap();
+            serde_json::from_str::<Value>(&response).unwrap()["count"].clone()
+        };
+        assert_eq!(unread(&mock), 3);
+
+        mock.respond("POST", "/emails/mock-001/mark-read", None)
+            .unwrap();
+        assert_eq!(unread(&mock), 2);
+        mock.respond("DELETE", "/emails/mock-002", None).unwrap();
+        let emails: Vec<EmailData> =
+            serde_json::from_str(&mock.respond("GET", "/emails?limit=10", None).unwrap()).unwrap();
+        assert_eq!(emails.len(), 3);
+
+        assert!(mock.respond("GET", "/emails/mock-002", None).is_err());
+        assert!(mock.respond("GET", "/nowhere", None).is_err());
+
+        let claim = |verb: &str, name: &str| {
+            let body = json!({ "name": name }).to_string();
+            let endpoint = format!("/emails/mock-001/{}", verb);
+            serde_json::from_str::<ClaimState>(
+                &mock.respond("POST", &endpoint, Some(&body)).unwrap(),
+            )
+            .unwrap()
+        };
+        assert!(claim("claim", "Ana").changed);
+        assert_eq!(claim("claim", "Ben").claimed_by, "Ana");
+        assert!(!claim("release", "Ben").changed);
+        assert_eq!(claim("release", "Ana").claimed_by, "");
+    }
+
+    #[test]
+    fn test_mock_completion() {
+        let mock = MockBackend::new().unwrap();
+        let completion = mock.complete(&crate::llm::task_prompt("Budget review", "Budget"));
+        assert_eq!(
+            crate::llm::parse_task(&completion.text).title,
+            "Send updated Q3 forecast"
+        );
+        let proofread = mock.complete(&crate::llm::proofread_prompt("Thanks"));
+        assert!(crate::llm::parse_corrections(&proofread.text)
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -0,0 +1,170 @@
+// SERINA Conversation Mute
+// Muted threads stay in the inbox but stop counting toward reminders, digests and priority
+
+use crate::utils::EmailData;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MutedThread {
+    /// Outlook ConversationID
+    pub thread_id: String,
+    /// Subject of the thread when it was muted, for the list of mutes
+    pub subject: String,
+    pub muted_at: DateTime<Utc>,
+    /// When the mute lifts on its own; `None` until unmuted
+    pub until: Option<DateTime<Utc>>,
+}
+
+impl MutedThread {
+    pub fn is_active(&self, now: DateTime<Utc>) -> bool {
+        self.until.is_none_or(|until| until > now)
+    }
+}
+
+/// Muted conversations, saved on every change; expired mutes are dropped as they're noticed
+pub struct MuteList {
+    path: Option<PathBuf>,
+    threads: Mutex<Vec<MutedThread>>,
+}
+
+impl MuteList {
+    pub fn load(path: PathBuf) -> Self {
+        let threads = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+        Self {
+            path: Some(path),
+            threads: Mutex::new(threads),
+        }
+    }
+
+    pub fn in_memory() -> Self {
+        Self {
+            path: None,
+            threads: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Mutes still in effect at `now`
+    pub fn list(&self, now: DateTime<Utc>) -> Vec<MutedThread> {
+        self.threads
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|thread| thread.is_active(now))
+            .cloned()
+            .collect()
+    }
+
+    pub fn is_muted(&self, thread_id: &str, now: DateTime<Utc>) -> bool {
+        !thread_id.is_empty()
+            && self
+                .threads
+                .lock()
+                .unwrap()
+                .iter()
+                .any(|thread| thread.thread_id == thread_id && thread.is_active(now))
+    }
+
+    /// Mute a thread for `minutes`, or until unmuted; muting it again replaces the duration
+    pub fn mute(
+        &self,
+        thread_id: &str,
+        subject: &str,
+        minutes: Option<u32>,
+        now: DateTime<Utc>,
+    ) -> Result<MutedThread, String> {
+        if thread_id.is_empty() {
+            return Err("This email isn't part of a conversation Outlook can track".to_string());
+        }
+        let entry = MutedThread {
+            thread_id: thread_id.to_string(),
+            subject: subject.to_string(),
+            muted_at: now,
+            until: minutes.map(|minutes| now + Duration::minutes(minutes as i64)),
+        };
+        let mut threads = self.threads.lock().unwrap();
+        threads.retain(|thread| thread.thread_id != thread_id && thread.is_active(now));
+        threads.push(entry.clone());
+        self.persist(&threads)?;
+        Ok(entry)
+    }
+
+    /// Returns whether the thread was muted
+    pub fn unmute(&self, thread_id: &str) -> Result<bool, String> {
+        let mut threads = self.threads.lock().unwrap();
+        let before = threads.len();
+        threads.retain(|thread| thread.thread_id != thread_id);
+        if threads.len() == before {
+            return Ok(false);
+        }
+        self.persist(&threads)?;
+        Ok(true)
+    }
+
+    /// Flag the emails that belong to muted threads
+    pub fn apply(&self, emails: &mut [EmailData], now: DateTime<Utc>) {
+        for email in emails {
+            email.muted = self.is_muted(&email.conversation_id, now);
+        }
+    }
+
+    fn persist(&self, threads: &[MutedThread]) -> Result<(), String> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let contents = serde_json::to_string_pretty(threads).map_err(|e| e.to_string())?;
+        std::fs::write(path, contents).map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_mute() {
+        let path = std::env::temp_dir().join(format!("serina-mute-{}.json", std::process::id()));
+        let mutes = MuteList::load(path.clone());
+        let now = Utc.with_ymd_and_hms(2024, 5, 14, 9, 0, 0).unwrap();
+        mutes.mute("storm", "RE: Offsite", Some(60), now).unwrap();
+        mutes.mute("newsletter", "Weekly", None, now).unwrap();
+        assert!(mutes.mute("", "No thread", None, now).is_err());
+
+        let mut emails: Vec<EmailData> = ["storm", "other", ""]
+            .iter()
+            .map(|thread| {
+                serde_json::from_value(serde_json::json!({
+                    "id": format!("id-{}", thread),
+                    "subject": "RE: Offsite",
+                    "sender": "Dana",
+                    "sender_email": "dana@example.com",
+                    "body": "",
+                    "received_time": "",
+                    "is_unread": true,
+                    "conversation_id": thread
+                }))
+                .unwrap()
+            })
+            .collect();
+        mutes.apply(&mut emails, now);
+        let muted: Vec<_> = emails.iter().map(|email| email.muted).collect();
+        assert_eq!(muted, [true, false, false]);
+
+        let later = now + Duration::minutes(61);
+        assert!(!mutes.is_muted("storm", later));
+        assert!(mutes.is_muted("newsletter", later));
+        assert_eq!(mutes.list(later).len(), 1);
+        assert_eq!(MuteList::load(path.clone()).list(now).len(), 2);
+
+        assert!(mutes.unmute("newsletter").unwrap());
+        assert!(!mutes.unmute("newsletter").unwrap());
+        assert!(!mutes.is_muted("newsletter", later));
+        std::fs::remove_file(&path).unwrap();
+    }
+}
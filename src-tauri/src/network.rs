@@ -0,0 +1,85 @@
+// SERINA Network Awareness
+// Tracks connectivity so the scheduler can sync as soon as the machine is back online
+
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::Notify;
+
+/// How often the OS connectivity state is sampled
+pub const CONNECTIVITY_POLL: Duration = Duration::from_secs(10);
+
+/// Wakes the scheduler before its interval is up
+#[derive(Default)]
+pub struct SyncTrigger {
+    notify: Notify,
+}
+
+impl SyncTrigger {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ask for a sync now; a request made while a sync is running is kept for the next wait
+    pub fn request(&self) {
+        self.notify.notify_one();
+    }
+
+    /// Sleep for `interval` or until a sync is requested. Returns true when woken early.
+    pub async fn wait(&self, interval: Duration) -> bool {
+        tokio::select! {
+            _ = tokio::time::sleep(interval) => false,
+            _ = self.notify.notified() => true,
+        }
+    }
+}
+
+/// Last known online state, used to spot transitions
+#[derive(Default)]
+pub struct ConnectivityMonitor {
+    online: Mutex<Option<bool>>,
+}
+
+impl ConnectivityMonitor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_online(&self) -> Option<bool> {
+        *self.online.lock().unwrap()
+    }
+
+    /// Record a sample; returns the new state when it differs from the previous one.
+    /// The first sample only sets the baseline.
+    pub fn observe(&self, online: bool) -> Option<bool> {
+        let mut last = self.online.lock().unwrap();
+        let previous = last.replace(online);
+        match previous {
+            Some(previous) if previous != online => Some(online),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_observe_transitions() {
+        let monitor = ConnectivityMonitor::new();
+        assert_eq!(monitor.observe(false), None);
+        assert_eq!(monitor.observe(false), None);
+        assert_eq!(monitor.observe(true), Some(true));
+        assert_eq!(monitor.observe(false), Some(false));
+        assert_eq!(monitor.is_online(), Some(false));
+    }
+
+    #[tokio::test]
+    async fn test_trigger_wakes_wait() {
+        let trigger = SyncTrigger::new();
+        assert!(!trigger.wait(Duration::from_millis(10)).await);
+        // Requested before anyone was waiting: the next wait returns at once
+        trigger.request();
+        assert!(trigger.wait(Duration::from_secs(60)).await);
+    }
+}
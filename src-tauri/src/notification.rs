@@ -0,0 +1,221 @@
+// SERINA Notification Permission
+// Detects whether the OS lets us show desktop notifications
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+/// Bundle identifier used for notifications and OS notification settings
+pub const APP_ID: &str = "com.serina.emailassistant";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationPermission {
+    Granted,
+    Denied,
+    /// The platform gives no way to ask; notifications are attempted
+    Unknown,
+}
+
+impl NotificationPermission {
+    pub fn allows_notifications(self) -> bool {
+        self != NotificationPermission::Denied
+    }
+}
+
+/// Read a REG_DWORD value out of `reg query` output
+pub fn parse_reg_dword(output: &str, name: &str) -> Option<u32> {
+    output.lines().find_map(|line| {
+        let mut parts = line.split_whitespace();
+        if parts.next()? != name || parts.next()? != "REG_DWORD" {
+            return None;
+        }
+        u32::from_str_radix(parts.next()?.trim_start_matches("0x"), 16).ok()
+    })
+}
+
+#[cfg(target_os = "windows")]
+fn query_dword(key: &str, name: &str) -> Option<u32> {
+    let output = std::process::Command::new("reg")
+        .args(["query", key, "/v", name])
+        .output()
+        .ok()?;
+    parse_reg_dword(&String::from_utf8_lossy(&output.stdout), name)
+}
+
+/// Ask the OS whether notifications are currently allowed
+#[cfg(target_os = "windows")]
+pub fn detect_permission() -> NotificationPermission {
+    const SETTINGS: &str = r"HKCU\Software\Microsoft\Windows\CurrentVersion\Notifications\Settings";
+    const PUSH: &str = r"HKCU\Software\Microsoft\Windows\CurrentVersion\PushNotifications";
+
+    let global_off = query_dword(PUSH, "ToastEnabled") == Some(0);
+    let app_off = query_dword(&format!(r"{}\{}", SETTINGS, APP_ID), "Enabled") == Some(0);
+    if global_off || app_off {
+        NotificationPermission::Denied
+    } else {
+        NotificationPermission::Granted
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn detect_permission() -> NotificationPermission {
+    NotificationPermission::Unknown
+}
+
+/// Open the OS page where the user can allow notifications, where one exists
+pub fn open_permission_settings() -> Result<(), String> {
+    #[cfg(target_os = "windows")]
+    let result = std::process::Command::new("cmd")
+        .args(["/C", "start", "ms-settings:notifications"])
+        .spawn();
+    #[cfg(target_os = "macos")]
+    let result = std::process::Command::new("open")
+        .arg("x-apple.systempreferences:com.apple.preference.notifications")
+        .spawn();
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let result: std::io::Result<()> = Ok(());
+
+    result.map(|_| ()).map_err(|e| e.to_string())
+}
+
+/// Permission as last observed, combining OS settings with failed delivery attempts
+#[derive(Default)]
+pub struct PermissionState {
+    failed: AtomicBool,
+}
+
+impl PermissionState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn current(&self) -> NotificationPermission {
+        if self.failed.load(Ordering::Relaxed) {
+            NotificationPermission::Denied
+        } else {
+            detect_permission()
+        }
+    }
+
+    /// Remember whether the last notification actually reached the OS
+    pub fn record_delivery(&self, delivered: bool) {
+        self.failed.store(!delivered, Ordering::Relaxed);
+    }
+}
+
+/// Oldest entries are dropped past this many
+const MAX_LIST_ENTRIES: usize = 200;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationEntry {
+    pub id: u64,
+    pub title: String,
+    pub body: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Notifications kept until dismissed, for users who can't catch transient toasts
+pub struct NotificationList {
+    path: Option<PathBuf>,
+    entries: Mutex<Vec<NotificationEntry>>,
+}
+
+impl NotificationList {
+    pub fn load(path: PathBuf) -> Self {
+        let entries = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+        Self {
+            path: Some(path),
+            entries: Mutex::new(entries),
+        }
+    }
+
+    pub fn in_memory() -> Self {
+        Self {
+            path: None,
+            entries: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Newest first
+    pub fn list(&self) -> Vec<NotificationEntry> {
+        self.entries.lock().unwrap().iter().rev().cloned().collect()
+    }
+
+    pub fn push(&self, title: &str, body: &str) -> Result<NotificationEntry, String> {
+        let mut entries = self.entries.lock().unwrap();
+        let entry = NotificationEntry {
+            id: entries.last().map_or(1, |last| last.id + 1),
+            title: title.to_string(),
+            body: body.to_string(),
+            created_at: Utc::now(),
+        };
+        entries.push(entry.clone());
+        let overflow = entries.len().saturating_sub(MAX_LIST_ENTRIES);
+        entries.drain(..overflow);
+        self.persist(&entries)?;
+        Ok(entry)
+    }
+
+    pub fn dismiss(&self, id: u64) -> Result<(), String> {
+        let mut entries = self.entries.lock().unwrap();
+        entries.retain(|entry| entry.id != id);
+        self.persist(&entries)
+    }
+
+    pub fn clear(&self) -> Result<(), String> {
+        let mut entries = self.entries.lock().unwrap();
+        entries.clear();
+        self.persist(&entries)
+    }
+
+    fn persist(&self, entries: &[NotificationEntry]) -> Result<(), String> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let contents = serde_json::to_string_pretty(entries).map_err(|e| e.to_string())?;
+        std::fs::write(path, contents).map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_reg_dword() {
+        let output = "\r\nHKEY_CURRENT_USER\\Software\\Microsoft\\Windows\\CurrentVersion\\PushNotifications\r\n    ToastEnabled    REG_DWORD    0x0\r\n";
+        assert_eq!(parse_reg_dword(output, "ToastEnabled"), Some(0));
+        assert_eq!(
+            parse_reg_dword("    Enabled    REG_DWORD    0x1", "Enabled"),
+            Some(1)
+        );
+        assert_eq!(parse_reg_dword(output, "Enabled"), None);
+
+        let state = PermissionState::new();
+        state.record_delivery(false);
+        assert_eq!(state.current(), NotificationPermission::Denied);
+    }
+
+    #[test]
+    fn test_notification_list() {
+        let list = NotificationList::in_memory();
+        let first = list.push("Reminder", "3 new emails").unwrap();
+        let second = list.push("Update available", "SERINA 1.2.0").unwrap();
+        assert_eq!(list.list()[0].id, second.id);
+
+        list.dismiss(first.id).unwrap();
+        assert_eq!(list.list().len(), 1);
+        for _ in 0..MAX_LIST_ENTRIES + 5 {
+            list.push("Reminder", "").unwrap();
+        }
+        assert_eq!(list.list().len(), MAX_LIST_ENTRIES);
+        list.clear().unwrap();
+        assert!(list.list().is_empty());
+    }
+}
@@ -0,0 +1,283 @@
+// SERINA Verification Codes
+// One-time codes spotted in incoming mail, and the expiry that cleans those messages up
+
+use crate::parsing;
+use crate::utils::EmailData;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// Words that put a number in a line in context as a one-time code
+const CODE_WORDS: &[&str] = &[
+    "code",
+    "verification",
+    "verify",
+    "one-time",
+    "one time",
+    "otp",
+    "passcode",
+    "2fa",
+    "two-factor",
+    "código",
+    "codigo",
+];
+
+/// Lines that talk about codes without being about signing in
+const OTHER_CODES: &[&str] = &[
+    "promo",
+    "coupon",
+    "discount",
+    "zip",
+    "postal",
+    "postcode",
+    "source code",
+    "invoice",
+    "tracking",
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OtpConfig {
+    /// Look for codes in new mail and show them in their own notification
+    pub detect: bool,
+    /// Move messages with a code to Deleted Items this long after they arrive; 0 keeps them
+    pub delete_after_minutes: u32,
+}
+
+impl Default for OtpConfig {
+    fn default() -> Self {
+        Self {
+            detect: true,
+            delete_after_minutes: 0,
+        }
+    }
+}
+
+/// The one-time code in an email, if it has one. Codes are 4 to 8 digits, possibly split
+/// in two ("123 456", "123-456", Google's "G-123456"), on a line that says what they are or
+/// alone on a line in an email that does.
+pub fn find_code(email: &EmailData) -> Option<String> {
+    let body = parsing::plain_text(&email.body);
+    let lines: Vec<&str> = std::iter::once(email.subject.as_str())
+        .chain(body.lines())
+        .collect();
+    let about_codes = lines.iter().any(|line| mentions_code(line));
+    if !about_codes {
+        return None;
+    }
+    let on_keyword_line = lines
+        .iter()
+        .filter(|line| mentions_code(line))
+        .find_map(|line| code_in(line));
+    on_keyword_line.or_else(|| {
+        lines
+            .iter()
+            .find_map(|line| code_in(line).filter(|code| normalize(line) == *code))
+    })
+}
+
+fn mentions_code(line: &str) -> bool {
+    let lower = line.to_lowercase();
+    CODE_WORDS.iter().any(|word| lower.contains(word))
+        && !OTHER_CODES.iter().any(|word| lower.contains(word))
+}
+
+/// The line without separators, to tell a code standing alone on its line
+fn normalize(line: &str) -> String {
+    line.chars()
+        .filter(|c| !c.is_whitespace() && *c != '-')
+        .collect()
+}
+
+/// The first code-shaped number on a line, separators removed
+fn code_in(line: &str) -> Option<String> {
+    let words: Vec<&str> = line
+        .split_whitespace()
+        .map(|word| word.trim_matches(|c: char| c.is_ascii_punctuation() && c != '-'))
+        .collect();
+    for (index, word) in words.iter().enumerate() {
+        let previous = index.checked_sub(1).map(|i| words[i]);
+        let next = words.get(index + 1).copied();
+        // "123 456": two groups of three
+        if is_group(word, 3) && next.is_some_and(|next| is_group(next, 3)) {
+            if previous.is_some_and(|previous| is_group(previous, 3)) {
+                continue;
+            }
+            return Some(format!("{}{}", word, next.unwrap()));
+        }
+        if let Some(code) = code_word(word) {
+            return Some(code);
+        }
+    }
+    None
+}
+
+fn is_group(word: &str, length: usize) -> bool {
+    word.len() == length && word.chars().all(|c| c.is_ascii_digit())
+}
+
+fn code_word(word: &str) -> Option<String> {
+    // Google prefixes its codes with "G-"
+    let word = word.strip_prefix("G-").unwrap_or(word);
+    let digits: String = match word.split_once('-') {
+        Some((first, second))
+            if (3..=4).contains(&first.len())
+                && first.len() == second.len()
+                && is_group(first, first.len())
+                && is_group(second, second.len()) =>
+        {
+            format!("{}{}", first, second)
+        }
+        _ => word.to_string(),
+    };
+    let is_year = digits.len() == 4 && (digits.starts_with("19") || digits.starts_with("20"));
+    ((4..=8).contains(&digits.len()) && digits.chars().all(|c| c.is_ascii_digit()) && !is_year)
+        .then_some(digits)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExpiringMessage {
+    pub email_id: String,
+    pub delete_at: DateTime<Utc>,
+}
+
+/// Messages with a code waiting to be deleted, saved on every change. The codes themselves
+/// are never written down; they're read again from the email when needed.
+pub struct CodeExpiry {
+    path: Option<PathBuf>,
+    messages: Mutex<Vec<ExpiringMessage>>,
+}
+
+impl CodeExpiry {
+    pub fn load(path: PathBuf) -> Self {
+        let messages = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+        Self {
+            path: Some(path),
+            messages: Mutex::new(messages),
+        }
+    }
+
+    pub fn in_memory() -> Self {
+        Self {
+            path: None,
+            messages: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Delete the email `minutes` after `now`; tracking it again keeps the first time
+    pub fn track(&self, email_id: &str, minutes: u32, now: DateTime<Utc>) -> Result<(), String> {
+        let mut messages = self.messages.lock().unwrap();
+        if messages.iter().any(|message| message.email_id == email_id) {
+            return Ok(());
+        }
+        messages.push(ExpiringMessage {
+            email_id: email_id.to_string(),
+            delete_at: now + Duration::minutes(minutes as i64),
+        });
+        self.persist(&messages)
+    }
+
+    /// Remove and return the messages whose time is up, including ones missed while SERINA
+    /// was closed
+    pub fn take_due(&self, now: DateTime<Utc>) -> Result<Vec<ExpiringMessage>, String> {
+        let mut messages = self.messages.lock().unwrap();
+        let (due, waiting): (Vec<_>, Vec<_>) = messages
+            .drain(..)
+            .partition(|message| message.delete_at <= now);
+        *messages = waiting;
+        if !due.is_empty() {
+            self.persist(&messages)?;
+        }
+        Ok(due)
+    }
+
+    fn persist(&self, messages: &[ExpiringMessage]) -> Result<(), String> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let contents = serde_json::to_string_pretty(messages).map_err(|e| e.to_string())?;
+        std::fs::write(path, contents).map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn email(subject: &str, body: &str) -> EmailData {
+        serde_json::from_value(serde_json::json!({
+            "id": "a",
+            "subject": subject,
+            "sender": "Example",
+            "sender_email": "no-reply@example.com",
+            "body": body,
+            "received_time": "",
+            "is_unread": true
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_find_code() {
+        let code = |subject, body| find_code(&email(subject, body));
+        assert_eq!(
+            code(
+                "Sign in",
+                "Your verification code is 482913. It expires in 10 minutes."
+            ),
+            Some("482913".to_string())
+        );
+        assert_eq!(
+            code("G-123456 is your Google verification code", ""),
+            Some("123456".to_string())
+        );
+        assert_eq!(
+            code(
+                "Your sign-in code",
+                "Hi,\n\n735 201\n\nValid until 2024. Call 555-0100 if this wasn't you."
+            ),
+            Some("735201".to_string())
+        );
+        assert_eq!(
+            code(
+                "Security alert",
+                "<p>Enter code <b>4821-7730</b> to continue</p>"
+            ),
+            Some("48217730".to_string())
+        );
+        assert_eq!(code("Invoice 12345", "Total 4500, due 2024-06-01"), None);
+        assert_eq!(code("Sale", "Use promo code 202405 for 20% off"), None);
+        assert_eq!(
+            code("Your code", "We changed our code of conduct in 2024"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_code_expiry() {
+        let path = std::env::temp_dir().join(format!("serina-otp-{}.json", std::process::id()));
+        let expiry = CodeExpiry::load(path.clone());
+        let now = Utc.with_ymd_and_hms(2024, 5, 14, 9, 0, 0).unwrap();
+        expiry.track("a", 15, now).unwrap();
+        expiry.track("b", 60, now).unwrap();
+        expiry.track("a", 60, now).unwrap();
+
+        assert!(expiry.take_due(now).unwrap().is_empty());
+        let due = expiry.take_due(now + Duration::minutes(15)).unwrap();
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].email_id, "a");
+        assert_eq!(
+            CodeExpiry::load(path.clone())
+                .take_due(now + Duration::hours(1))
+                .unwrap()[0]
+                .email_id,
+            "b"
+        );
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,262 @@
+// SERINA Outbox
+// Files dropped onto the compose view, validated and copied aside until the reply goes out
+
+use crate::attachments::safe_file_name;
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Largest single file accepted
+pub const MAX_FILE_SIZE: u64 = 20 * 1024 * 1024;
+/// Exchange's default message size limit, less room for the body and encoding overhead
+pub const MAX_TOTAL_SIZE: u64 = 25 * 1024 * 1024;
+/// Progress is reported once per chunk
+const CHUNK_SIZE: usize = 256 * 1024;
+
+/// Types Outlook blocks outright, so the send would fail anyway
+const BLOCKED_EXTENSIONS: &[&str] = &[
+    "ade", "adp", "app", "bat", "chm", "cmd", "com", "cpl", "exe", "hta", "inf", "ins", "isp",
+    "jar", "js", "jse", "lnk", "msc", "msi", "msp", "pif", "ps1", "reg", "scr", "sct", "shb", "vb",
+    "vbe", "vbs", "ws", "wsc", "wsf", "wsh",
+];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StagedAttachment {
+    pub id: u64,
+    pub name: String,
+    pub size: u64,
+    /// The staged copy, which the backend attaches when the reply is sent
+    pub path: PathBuf,
+}
+
+/// Sent while a dropped file is copied into the outbox
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StagingProgress {
+    pub id: u64,
+    pub name: String,
+    pub copied: u64,
+    pub total: u64,
+}
+
+/// A dropped file that wasn't staged, and why
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RejectedFile {
+    pub name: String,
+    pub reason: String,
+}
+
+fn display_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.to_string_lossy().into_owned())
+}
+
+/// Check a dropped path before copying it; returns its size
+pub fn validate(path: &Path, staged_size: u64) -> Result<u64, String> {
+    let meta = std::fs::metadata(path).map_err(|e| e.to_string())?;
+    if !meta.is_file() {
+        return Err("Only files can be attached, not folders".to_string());
+    }
+    let extension = path
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    if BLOCKED_EXTENSIONS.contains(&extension.as_str()) {
+        return Err(format!("Outlook blocks .{} attachments", extension));
+    }
+    if meta.len() == 0 {
+        return Err("File is empty".to_string());
+    }
+    if meta.len() > MAX_FILE_SIZE {
+        return Err(format!(
+            "File is larger than {} MB",
+            MAX_FILE_SIZE / (1024 * 1024)
+        ));
+    }
+    if staged_size + meta.len() > MAX_TOTAL_SIZE {
+        return Err(format!(
+            "Attachments would exceed {} MB in total",
+            MAX_TOTAL_SIZE / (1024 * 1024)
+        ));
+    }
+    Ok(meta.len())
+}
+
+#[derive(Default)]
+struct Staging {
+    next_id: u64,
+    files: Vec<StagedAttachment>,
+}
+
+/// Attachments staged for the reply being composed
+pub struct OutboxStore {
+    dir: PathBuf,
+    staging: Mutex<Staging>,
+}
+
+impl OutboxStore {
+    /// Anything left in `dir` belonged to a reply that was never sent, so it is cleared
+    pub fn new(dir: PathBuf) -> Self {
+        let _ = std::fs::remove_dir_all(&dir);
+        Self {
+            dir,
+            staging: Mutex::default(),
+        }
+    }
+
+    pub fn staged(&self) -> Vec<StagedAttachment> {
+        self.staging.lock().unwrap().files.clone()
+    }
+
+    /// Validate `source` and copy it into the outbox, calling `progress` with bytes copied so
+    /// far and the total after each chunk. Blocks for the length of the copy.
+    pub fn stage(
+        &self,
+        source: &Path,
+        mut progress: impl FnMut(StagingProgress),
+    ) -> Result<StagedAttachment, RejectedFile> {
+        let name = display_name(source);
+        let reject = |reason: String| RejectedFile {
+            name: name.clone(),
+            reason,
+        };
+        let (id, size) = {
+            let mut staging = self.staging.lock().unwrap();
+            let staged_size = staging.files.iter().map(|file| file.size).sum();
+            let size = validate(source, staged_size).map_err(reject)?;
+            staging.next_id += 1;
+            (staging.next_id, size)
+        };
+
+        let dest = self.dir.join(id.to_string()).join(safe_file_name(&name));
+        let copied = copy_with_progress(source, &dest, |copied| {
+            progress(StagingProgress {
+                id,
+                name: name.clone(),
+                copied,
+                total: size,
+            })
+        });
+        if let Err(e) = copied {
+            let _ = std::fs::remove_dir_all(self.dir.join(id.to_string()));
+            return Err(reject(e));
+        }
+
+        let attachment = StagedAttachment {
+            id,
+            name: name.clone(),
+            size,
+            path: dest,
+        };
+        let mut staging = self.staging.lock().unwrap();
+        // Other drops may have landed while this one copied
+        let staged_size: u64 = staging.files.iter().map(|file| file.size).sum();
+        if staged_size + size > MAX_TOTAL_SIZE {
+            drop(staging);
+            let _ = std::fs::remove_dir_all(self.dir.join(id.to_string()));
+            return Err(reject(format!(
+                "Attachments would exceed {} MB in total",
+                MAX_TOTAL_SIZE / (1024 * 1024)
+            )));
+        }
+        staging.files.push(attachment.clone());
+        Ok(attachment)
+    }
+
+    /// Take one attachment off the reply
+    pub fn remove(&self, id: u64) -> Result<(), String> {
+        let mut staging = self.staging.lock().unwrap();
+        let index = staging
+            .files
+            .iter()
+            .position(|file| file.id == id)
+            .ok_or_else(|| "Attachment is no longer staged".to_string())?;
+        staging.files.remove(index);
+        let _ = std::fs::remove_dir_all(self.dir.join(id.to_string()));
+        Ok(())
+    }
+
+    /// Drop everything, e.g. once the reply has been sent
+    pub fn clear(&self) {
+        let mut staging = self.staging.lock().unwrap();
+        for file in staging.files.drain(..) {
+            let _ = std::fs::remove_dir_all(self.dir.join(file.id.to_string()));
+        }
+    }
+}
+
+fn copy_with_progress(
+    source: &Path,
+    dest: &Path,
+    mut progress: impl FnMut(u64),
+) -> Result<(), String> {
+    if let Some(dir) = dest.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    let mut reader = std::fs::File::open(source).map_err(|e| e.to_string())?;
+    let mut writer = std::fs::File::create(dest).map_err(|e| e.to_string())?;
+    let mut buffer = vec![0; CHUNK_SIZE];
+    let mut copied = 0;
+    loop {
+        let read = reader.read(&mut buffer).map_err(|e| e.to_string())?;
+        if read == 0 {
+            break;
+        }
+        writer
+            .write_all(&buffer[..read])
+            .map_err(|e| e.to_string())?;
+        copied += read as u64;
+        progress(copied);
+    }
+    writer.flush().map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stage() {
+        let root = std::env::temp_dir().join(format!("serina-outbox-test-{}", std::process::id()));
+        let sources = root.join("sources");
+        std::fs::create_dir_all(&sources).unwrap();
+        let report = sources.join("Q3 report.pdf");
+        std::fs::write(&report, vec![7u8; CHUNK_SIZE + 10]).unwrap();
+        let script = sources.join("setup.EXE");
+        std::fs::write(&script, b"MZ").unwrap();
+
+        let outbox = OutboxStore::new(root.join("outbox"));
+        let mut updates = Vec::new();
+        let staged = outbox
+            .stage(&report, |update| updates.push(update.copied))
+            .unwrap();
+        assert_eq!(staged.name, "Q3 report.pdf");
+        assert_eq!(
+            std::fs::read(&staged.path).unwrap().len() as u64,
+            staged.size
+        );
+        assert_eq!(updates, vec![CHUNK_SIZE as u64, staged.size]);
+
+        let rejected = outbox.stage(&script, |_| {}).unwrap_err();
+        assert_eq!(rejected.reason, "Outlook blocks .exe attachments");
+        assert!(outbox.stage(&sources, |_| {}).is_err());
+        assert_eq!(outbox.staged().len(), 1);
+
+        outbox.remove(staged.id).unwrap();
+        assert!(!staged.path.exists());
+        assert!(outbox.remove(staged.id).is_err());
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_validate_sizes() {
+        let path = std::env::temp_dir().join(format!("serina-outbox-size-{}", std::process::id()));
+        std::fs::write(&path, b"hello").unwrap();
+        assert_eq!(validate(&path, 0), Ok(5));
+        assert!(validate(&path, MAX_TOTAL_SIZE - 4)
+            .unwrap_err()
+            .contains("in total"));
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,458 @@
+// SERINA Email Parsing
+// Plain-text rendering of email bodies and short previews for lists and notifications
+
+use crate::utils::EmailData;
+use serde::Serialize;
+
+/// Longest preview, in characters; about two lines of the inbox list
+pub const SNIPPET_LENGTH: usize = 140;
+
+/// Elements whose content is never shown
+const HIDDEN_ELEMENTS: &[&str] = &["head", "style", "script", "title"];
+
+/// Elements that start a new line of text
+const BLOCK_ELEMENTS: &[&str] = &[
+    "p",
+    "div",
+    "br",
+    "tr",
+    "li",
+    "table",
+    "blockquote",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "hr",
+    "ul",
+    "ol",
+];
+
+const HTML_MARKERS: &[&str] = &[
+    "<html", "<body", "<div", "<p>", "<p ", "<br", "<table", "<span",
+];
+
+/// Lines that begin legal boilerplate; nothing after them is worth previewing
+const DISCLAIMER_PREFIXES: &[&str] = &[
+    "confidentiality notice",
+    "disclaimer",
+    "this email and any",
+    "this e-mail and any",
+    "this message and any",
+    "the information contained in this",
+];
+
+/// Mobile and client footers that act as a signature
+const CLIENT_SIGNATURES: &[&str] = &["sent from my ", "get outlook for "];
+
+const GREETINGS: &[&str] = &[
+    "dear",
+    "hi",
+    "hello",
+    "hey",
+    "good morning",
+    "good afternoon",
+    "good evening",
+];
+
+pub fn looks_like_html(body: &str) -> bool {
+    let lower = body.to_ascii_lowercase();
+    HTML_MARKERS.iter().any(|marker| lower.contains(marker))
+}
+
+/// Render an HTML body as plain text. Block elements become line breaks and text inside
+/// `<blockquote>` is prefixed with "> ", the same way plain-text replies quote.
+pub fn html_to_text(html: &str) -> String {
+    let mut text = String::new();
+    let mut hidden: Option<String> = None;
+    let mut quote_depth = 0usize;
+    let mut rest = html;
+
+    while !rest.is_empty() {
+        let Some(start) = rest.find('<') else {
+            if hidden.is_none() {
+                push_text(&mut text, rest, quote_depth);
+            }
+            break;
+        };
+        if hidden.is_none() {
+            push_text(&mut text, &rest[..start], quote_depth);
+        }
+        rest = &rest[start..];
+
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+            continue;
+        }
+        let Some(end) = rest.find('>') else {
+            break;
+        };
+        let tag = &rest[1..end];
+        rest = &rest[end + 1..];
+
+        let closing = tag.starts_with('/');
+        let name: String = tag
+            .trim_start_matches('/')
+            .chars()
+            .take_while(|c| c.is_ascii_alphanumeric())
+            .collect::<String>()
+            .to_ascii_lowercase();
+
+        if let Some(element) = &hidden {
+            if closing && *element == name {
+                hidden = None;
+            }
+            continue;
+        }
+        if !closing && HIDDEN_ELEMENTS.contains(&name.as_str()) && !tag.ends_with('/') {
+            hidden = Some(name);
+            continue;
+        }
+        if BLOCK_ELEMENTS.contains(&name.as_str()) {
+            if name == "blockquote" {
+                quote_depth = if closing {
+                    quote_depth.saturating_sub(1)
+                } else {
+                    quote_depth + 1
+                };
+            }
+            text.push('\n');
+        } else if name == "td" || name == "th" {
+            text.push(' ');
+        }
+    }
+
+    text.lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn push_text(text: &mut String, raw: &str, quote_depth: usize) {
+    let decoded = decode_entities(raw);
+    if decoded.trim().is_empty() {
+        text.push(' ');
+        return;
+    }
+    // Source line breaks inside HTML are just whitespace
+    let decoded = decoded.replace(['\r', '\n'], " ");
+    if quote_depth > 0 && (text.is_empty() || text.ends_with('\n')) {
+        text.push_str(&"> ".repeat(quote_depth));
+    }
+    text.push_str(&decoded);
+}
+
+/// Replace character references (`&amp;`, `&#36;`...) with the characters they stand for;
+/// unknown ones are left as written
+pub fn decode_entities(raw: &str) -> String {
+    let mut decoded = String::with_capacity(raw.len());
+    let mut rest = raw;
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let entity = rest[1..]
+            .find(';')
+            .filter(|end| *end <= 10)
+            .and_then(|end| decode_entity(&rest[1..end + 1]).map(|c| (c, end + 2)));
+        match entity {
+            Some((c, length)) => {
+                decoded.push(c);
+                rest = &rest[length..];
+            }
+            None => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+fn decode_entity(name: &str) -> Option<char> {
+    if let Some(number) = name.strip_prefix('#') {
+        let code = match number.strip_prefix(['x', 'X']) {
+            Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+            None => number.parse().ok()?,
+        };
+        return char::from_u32(code);
+    }
+    Some(match name {
+        "nbsp" => ' ',
+        "amp" => '&',
+        "lt" => '<',
+        "gt" => '>',
+        "quot" => '"',
+        "apos" => '\'',
+        "ndash" => '–',
+        "mdash" => '—',
+        "hellip" => '…',
+        "rsquo" => '’',
+        "lsquo" => '‘',
+        "rdquo" => '”',
+        "ldquo" => '“',
+        _ => return None,
+    })
+}
+
+/// The body as plain text, whichever form the backend sent it in
+pub fn plain_text(body: &str) -> String {
+    if looks_like_html(body) {
+        html_to_text(body)
+    } else {
+        body.replace("\r\n", "\n")
+    }
+}
+
+/// A line introducing quoted history: "On … wrote:", Outlook's "From:/Sent:" header block
+/// or an "Original Message" separator
+fn is_attribution(line: &str, next: Option<&str>) -> bool {
+    let lower = line.to_lowercase();
+    (lower.starts_with("on ") && lower.ends_with("wrote:"))
+        || lower.contains("original message-----")
+        || (line.len() >= 5 && line.chars().all(|c| c == '_'))
+        || (lower.starts_with("from:")
+            && next.is_some_and(|next| {
+                let next = next.to_lowercase();
+                ["sent:", "date:", "to:"]
+                    .iter()
+                    .any(|header| next.starts_with(header))
+            }))
+}
+
+fn is_signature_start(line: &str) -> bool {
+    let lower = line.to_lowercase();
+    line.trim_end() == "--"
+        || CLIENT_SIGNATURES
+            .iter()
+            .any(|footer| lower.starts_with(footer))
+        || DISCLAIMER_PREFIXES
+            .iter()
+            .any(|prefix| lower.starts_with(prefix))
+}
+
+/// "Dear Sir," or "Hi Jane," on a line of its own
+fn is_greeting(line: &str) -> bool {
+    let lower = line.to_lowercase();
+    line.chars().count() <= 40
+        && (lower.ends_with(',') || lower.ends_with(':') || lower.ends_with('!'))
+        && GREETINGS.iter().any(|greeting| {
+            lower
+                .strip_prefix(greeting)
+                .is_some_and(|rest| rest.starts_with([' ', ',', '!', ':']))
+        })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SegmentKind {
+    /// What the sender wrote in this message
+    Content,
+    /// Earlier messages in the thread
+    Quoted,
+    /// Sign-off, client footers and disclaimers
+    Signature,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BodySegment {
+    pub kind: SegmentKind,
+    pub text: String,
+}
+
+/// Split a body into new content, quoted history and signature, in reading order.
+/// Inline replies between "> " lines stay content.
+pub fn split_body(body: &str) -> Vec<BodySegment> {
+    let text = plain_text(body);
+    let lines: Vec<&str> = text.lines().map(str::trim_end).collect();
+    let mut segments: Vec<BodySegment> = Vec::new();
+    // Kind of any line that isn't individually quoted
+    let mut section = SegmentKind::Content;
+
+    for (i, line) in lines.iter().enumerate() {
+        let trimmed = line.trim();
+        let next = lines[i + 1..]
+            .iter()
+            .map(|line| line.trim())
+            .find(|line| !line.is_empty());
+        if section != SegmentKind::Quoted && is_attribution(trimmed, next) {
+            if !next.is_some_and(|next| next.starts_with('>')) {
+                section = SegmentKind::Quoted;
+            }
+            push_line(&mut segments, SegmentKind::Quoted, line);
+            continue;
+        }
+        if section == SegmentKind::Content && is_signature_start(trimmed) {
+            section = SegmentKind::Signature;
+        }
+        let kind = if trimmed.starts_with('>') {
+            SegmentKind::Quoted
+        } else {
+            section
+        };
+        push_line(&mut segments, kind, line);
+    }
+
+    for segment in &mut segments {
+        segment.text = segment.text.trim().to_string();
+    }
+    segments.retain(|segment| !segment.text.is_empty());
+    segments
+}
+
+/// Blank lines stay with the segment before them
+fn push_line(segments: &mut Vec<BodySegment>, kind: SegmentKind, line: &str) {
+    match segments.last_mut() {
+        Some(last) if last.kind == kind || line.trim().is_empty() => {
+            last.text.push('\n');
+            last.text.push_str(line);
+        }
+        _ => segments.push(BodySegment {
+            kind,
+            text: line.to_string(),
+        }),
+    }
+}
+
+/// Only what the sender wrote; the whole body when nothing but quotes is found, as with
+/// a bare forward
+pub fn new_content(body: &str) -> String {
+    let content: Vec<String> = split_body(body)
+        .into_iter()
+        .filter(|segment| segment.kind == SegmentKind::Content)
+        .map(|segment| segment.text)
+        .collect();
+    if content.is_empty() {
+        plain_text(body).trim().to_string()
+    } else {
+        content.join("\n\n")
+    }
+}
+
+/// A one or two line preview of what the sender wrote, leaving out greetings, quoted
+/// replies, signatures and disclaimers
+pub fn snippet(body: &str) -> String {
+    let segments = split_body(body);
+    let mut words: Vec<&str> = Vec::new();
+    let content = segments
+        .iter()
+        .take_while(|segment| segment.kind != SegmentKind::Signature)
+        .filter(|segment| segment.kind == SegmentKind::Content);
+    for line in content.flat_map(|segment| segment.text.lines()) {
+        let line = line.trim();
+        if words.is_empty() && is_greeting(line) {
+            continue;
+        }
+        words.extend(line.split_whitespace());
+    }
+    truncate_words(&words, SNIPPET_LENGTH)
+}
+
+fn truncate_words(words: &[&str], max_chars: usize) -> String {
+    let mut preview = String::new();
+    for word in words {
+        let length = preview.chars().count() + word.chars().count() + 1;
+        if length > max_chars {
+            if preview.chars().count() >= max_chars {
+                // A full preview leaves no room for the ellipsis
+                preview.truncate(preview.rfind(' ').unwrap_or(0));
+            }
+            if preview.is_empty() {
+                preview = word.chars().take(max_chars - 1).collect();
+            }
+            preview.push('…');
+            return preview;
+        }
+        if !preview.is_empty() {
+            preview.push(' ');
+        }
+        preview.push_str(word);
+    }
+    preview
+}
+
+/// Fill in `snippet` for emails freshly read from the backend
+pub fn add_snippets(emails: &mut [EmailData]) {
+    for email in emails {
+        email.snippet = snippet(&email.body);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_html_to_text() {
+        let html = "<html><head><style>p { color: red }</style></head><body>\
+            <p>Dear Sir,</p><p>Your order&nbsp;has   shipped &amp; will arrive\n Monday.</p>\
+            <table><tr><td>Total</td><td>&#36;42</td></tr></table>\
+            <!-- tracking pixel --><blockquote><div>Earlier note</div></blockquote></body></html>";
+        assert_eq!(
+            html_to_text(html),
+            "Dear Sir,\nYour order has shipped & will arrive Monday.\nTotal $42\n> Earlier note"
+        );
+        assert_eq!(decode_entities("a &bogus; b & c"), "a &bogus; b & c");
+    }
+
+    #[test]
+    fn test_split_body() {
+        let inline = "See below.\n\nOn Mon, Jane wrote:\n> Can you make 3pm?\nYes, 3pm works.\n\
+            > And lunch?\nSure.\n\n--\nSam\nSent from my phone";
+        let kinds: Vec<(SegmentKind, &str)> = vec![
+            (SegmentKind::Content, "See below."),
+            (
+                SegmentKind::Quoted,
+                "On Mon, Jane wrote:\n> Can you make 3pm?",
+            ),
+            (SegmentKind::Content, "Yes, 3pm works."),
+            (SegmentKind::Quoted, "> And lunch?"),
+            (SegmentKind::Content, "Sure."),
+            (SegmentKind::Signature, "--\nSam\nSent from my phone"),
+        ];
+        let segments = split_body(inline);
+        let actual: Vec<(SegmentKind, &str)> = segments
+            .iter()
+            .map(|segment| (segment.kind, segment.text.as_str()))
+            .collect();
+        assert_eq!(actual, kinds);
+        assert_eq!(
+            new_content(inline),
+            "See below.\n\nYes, 3pm works.\n\nSure."
+        );
+
+        // Outlook puts the whole history below a header block, unquoted
+        let outlook = "Approved.\n\nFrom: Jane\nSent: Monday\nSubject: Budget\n\nPlease approve.";
+        let segments = split_body(outlook);
+        assert_eq!(segments.len(), 2);
+        assert_eq!(segments[1].kind, SegmentKind::Quoted);
+        assert!(segments[1].text.ends_with("Please approve."));
+        assert_eq!(new_content("> only a quote"), "> only a quote");
+    }
+
+    #[test]
+    fn test_snippet() {
+        let reply = "Hi Jane,\n\nSounds good, see you at 3.\n\n-- \nSam\n\n\
+            On Tue, Jane wrote:\n> Can we meet?";
+        assert_eq!(snippet(reply), "Sounds good, see you at 3.");
+
+        let outlook = "<div>Dear Sir,</div><div>Please find the invoice attached.</div>\
+            <div>________________________________</div><div>From: Jane</div><div>Sent: Monday</div>";
+        assert_eq!(snippet(outlook), "Please find the invoice attached.");
+
+        let legal = "Thanks!\nCONFIDENTIALITY NOTICE: This message is private.";
+        assert_eq!(snippet(legal), "Thanks!");
+
+        let long = "word ".repeat(100);
+        let preview = snippet(&long);
+        assert!(preview.ends_with('…'));
+        assert!(preview.chars().count() <= SNIPPET_LENGTH);
+
+        // Words that exactly fill the preview before one more arrives
+        let full = format!("{} {} more", "a".repeat(69), "b".repeat(70));
+        assert_eq!(snippet(&full), format!("{}…", "a".repeat(69)));
+    }
+}
@@ -0,0 +1,478 @@
+// SERINA Payment Tracking
+// Bills and invoices found in incoming mail, kept in a local table so their due dates get a
+// reminder
+
+use crate::dates::parse_received_time;
+use crate::deadline;
+use crate::parsing;
+use crate::utils::EmailData;
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use regex::Regex;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+
+/// Words that make an email worth reading as a bill
+const BILL_WORDS: &[&str] = &[
+    "invoice",
+    "your bill",
+    "amount due",
+    "balance due",
+    "total due",
+    "payment due",
+    "due date",
+    "pay by",
+    "statement is ready",
+    "fatura",
+    "factura",
+    "boleto",
+];
+
+/// Lines whose amount is the one to pay
+const TOTAL_WORDS: &[&str] = &["due", "total", "balance", "amount", "pay"];
+
+/// Amounts written with a currency: "$1,234.56", "€ 80", "R$ 99,90", "USD 20", "45.00 EUR"
+const AMOUNT_PATTERN: &str = r"(?i)(R\$|US\$|\$|€|£|\b(?:USD|EUR|GBP|BRL|CAD|AUD)\b)\s?(\d+(?:[.,\s]\d{3})*(?:[.,]\d{1,2})?)|(\d+(?:[.,\s]\d{3})*(?:[.,]\d{1,2})?)\s?(€|\b(?:USD|EUR|GBP|BRL|CAD|AUD)\b)";
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS payments (
+        email_id TEXT PRIMARY KEY,
+        subject TEXT NOT NULL DEFAULT '',
+        payee TEXT NOT NULL DEFAULT '',
+        amount TEXT NOT NULL DEFAULT '',
+        currency TEXT NOT NULL DEFAULT '',
+        due_date TEXT,
+        source TEXT NOT NULL,
+        paid INTEGER NOT NULL DEFAULT 0,
+        reminded INTEGER NOT NULL DEFAULT 0,
+        detected_at TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS idx_payments_due ON payments(due_date);
+";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PaymentConfig {
+    /// Look for bills in new mail
+    pub track: bool,
+    /// Ask the LLM when the rules find a bill but not its amount or due date
+    pub use_llm: bool,
+    /// Days ahead of the due date to send the reminder
+    pub remind_days_before: u32,
+}
+
+impl Default for PaymentConfig {
+    fn default() -> Self {
+        Self {
+            track: true,
+            use_llm: true,
+            remind_days_before: 3,
+        }
+    }
+}
+
+/// How a bill's details were found
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PaymentSource {
+    Rules,
+    Llm,
+}
+
+impl PaymentSource {
+    fn as_str(self) -> &'static str {
+        match self {
+            PaymentSource::Rules => "rules",
+            PaymentSource::Llm => "llm",
+        }
+    }
+}
+
+/// What a bill asks for; fields the email doesn't give are left empty
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Invoice {
+    pub payee: String,
+    /// Amount as written, e.g. "1,234.56"
+    pub amount: String,
+    /// ISO code where the symbol gives it away, e.g. "USD" for "$"
+    pub currency: String,
+    pub due_date: Option<NaiveDate>,
+}
+
+impl Invoice {
+    pub fn is_complete(&self) -> bool {
+        !self.amount.is_empty() && self.due_date.is_some()
+    }
+
+    /// Fill the gaps with what another extraction found
+    pub fn merge(mut self, other: Invoice) -> Invoice {
+        if self.payee.is_empty() {
+            self.payee = other.payee;
+        }
+        if self.amount.is_empty() {
+            self.amount = other.amount;
+            self.currency = other.currency;
+        }
+        self.due_date = self.due_date.or(other.due_date);
+        self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Payment {
+    pub email_id: String,
+    pub subject: String,
+    #[serde(flatten)]
+    pub invoice: Invoice,
+    pub source: PaymentSource,
+    pub paid: bool,
+    /// The reminder before the due date has gone out
+    pub reminded: bool,
+    pub detected_at: DateTime<Utc>,
+}
+
+pub fn looks_like_bill(subject: &str, text: &str) -> bool {
+    let haystack = format!("{} {}", subject, text).to_lowercase();
+    BILL_WORDS.iter().any(|word| haystack.contains(word))
+}
+
+fn amount_regex() -> &'static Regex {
+    static AMOUNT: OnceLock<Regex> = OnceLock::new();
+    AMOUNT.get_or_init(|| Regex::new(AMOUNT_PATTERN).expect("amount pattern is valid"))
+}
+
+fn currency_code(symbol: &str) -> String {
+    match symbol {
+        "$" | "US$" => "USD".to_string(),
+        "€" => "EUR".to_string(),
+        "£" => "GBP".to_string(),
+        "R$" => "BRL".to_string(),
+        code => code.to_uppercase(),
+    }
+}
+
+/// The first amount with a currency on a line, as (amount, currency)
+fn amount_in(line: &str) -> Option<(String, String)> {
+    let found = amount_regex().captures(line)?;
+    let (symbol, amount) = match (found.get(1), found.get(2)) {
+        (Some(symbol), Some(amount)) => (symbol, amount),
+        _ => (found.get(4)?, found.get(3)?),
+    };
+    Some((
+        amount.as_str().trim().to_string(),
+        currency_code(symbol.as_str()),
+    ))
+}
+
+/// Read a bill with the rules: the amount on a "total"/"due" line (or the first one
+/// anywhere), the due date from deadline detection, and the sender as payee. `None` when the
+/// email doesn't read like a bill at all.
+pub fn detect(email: &EmailData) -> Option<Invoice> {
+    let text = parsing::new_content(&email.body);
+    if !looks_like_bill(&email.subject, &text) {
+        return None;
+    }
+    let lines: Vec<&str> = std::iter::once(email.subject.as_str())
+        .chain(text.lines())
+        .collect();
+    let (amount, currency) = lines
+        .iter()
+        .filter(|line| {
+            let lower = line.to_lowercase();
+            TOTAL_WORDS.iter().any(|word| lower.contains(word))
+        })
+        .chain(lines.iter())
+        .find_map(|line| amount_in(line))
+        .unwrap_or_default();
+    let received = parse_received_time(&email.received_time)
+        .map_or_else(Local::now, |time| time.with_timezone(&Local));
+    // "Due date: May 20" reads as "due May 20" to the deadline rules
+    let due_date = deadline::detect(
+        &text.to_lowercase().replace("due date", "due"),
+        received.naive_local(),
+    )
+    .map(|due| due.date());
+    Some(Invoice {
+        payee: email.sender.clone(),
+        amount,
+        currency,
+        due_date,
+    })
+}
+
+/// Parse the LLM's answer to `llm::invoice_prompt`; `None` when it says this isn't a bill
+pub fn parse_invoice(text: &str) -> Result<Option<Invoice>, String> {
+    #[derive(Deserialize)]
+    struct Answer {
+        #[serde(default)]
+        is_bill: bool,
+        #[serde(default)]
+        payee: String,
+        #[serde(default)]
+        amount: String,
+        #[serde(default)]
+        currency: String,
+        #[serde(default)]
+        due_date: String,
+    }
+    let answer: Answer = serde_json::from_str(crate::llm::strip_code_fence(text))
+        .map_err(|e| format!("Malformed invoice response: {}", e))?;
+    if !answer.is_bill {
+        return Ok(None);
+    }
+    Ok(Some(Invoice {
+        payee: answer.payee.trim().to_string(),
+        amount: answer.amount.trim().to_string(),
+        currency: answer.currency.trim().to_uppercase(),
+        due_date: NaiveDate::parse_from_str(answer.due_date.trim(), "%Y-%m-%d").ok(),
+    }))
+}
+
+/// Tracked bills in their own SQLite file, apart from the email cache, which may be thrown
+/// away and rebuilt
+pub struct PaymentStore {
+    conn: Mutex<Connection>,
+}
+
+impl PaymentStore {
+    pub fn open(path: &Path) -> Result<Self, String> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        Self::init(Connection::open(path).map_err(|e| e.to_string())?)
+    }
+
+    pub fn in_memory() -> Result<Self, String> {
+        Self::init(Connection::open_in_memory().map_err(|e| e.to_string())?)
+    }
+
+    fn init(conn: Connection) -> Result<Self, String> {
+        conn.execute_batch(SCHEMA).map_err(|e| e.to_string())?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    /// Start tracking a bill; returns false when the email was already tracked
+    pub fn insert(&self, payment: &Payment) -> Result<bool, String> {
+        let conn = self.conn.lock().unwrap();
+        let invoice = &payment.invoice;
+        conn.execute(
+            "INSERT OR IGNORE INTO payments
+                (email_id, subject, payee, amount, currency, due_date, source, paid, reminded, detected_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            params![
+                payment.email_id,
+                payment.subject,
+                invoice.payee,
+                invoice.amount,
+                invoice.currency,
+                invoice.due_date.map(|date| date.to_string()),
+                payment.source.as_str(),
+                payment.paid,
+                payment.reminded,
+                payment.detected_at.to_rfc3339(),
+            ],
+        )
+        .map(|inserted| inserted > 0)
+        .map_err(|e| e.to_string())
+    }
+
+    pub fn get(&self, email_id: &str) -> Result<Option<Payment>, String> {
+        let conn = self.conn.lock().unwrap();
+        conn.query_row(
+            "SELECT * FROM payments WHERE email_id = ?1",
+            [email_id],
+            payment_from_row,
+        )
+        .optional()
+        .map_err(|e| e.to_string())
+    }
+
+    /// Unpaid bills, soonest due first; those without a due date come last
+    pub fn upcoming(&self) -> Result<Vec<Payment>, String> {
+        self.query(
+            "SELECT * FROM payments WHERE paid = 0
+             ORDER BY due_date IS NULL, due_date, detected_at",
+            params![],
+        )
+    }
+
+    /// Unpaid bills due on or before `until` whose reminder hasn't gone out
+    pub fn due_for_reminder(&self, until: NaiveDate) -> Result<Vec<Payment>, String> {
+        self.query(
+            "SELECT * FROM payments
+             WHERE paid = 0 AND reminded = 0 AND due_date IS NOT NULL AND due_date <= ?1
+             ORDER BY due_date",
+            params![until.to_string()],
+        )
+    }
+
+    pub fn mark_reminded(&self, email_id: &str) -> Result<(), String> {
+        self.update(
+            "UPDATE payments SET reminded = 1 WHERE email_id = ?1",
+            email_id,
+        )
+        .map(|_| ())
+    }
+
+    /// Returns whether the bill was tracked
+    pub fn mark_paid(&self, email_id: &str) -> Result<bool, String> {
+        self.update("UPDATE payments SET paid = 1 WHERE email_id = ?1", email_id)
+    }
+
+    /// Stop tracking something that wasn't a bill; returns whether it was tracked
+    pub fn remove(&self, email_id: &str) -> Result<bool, String> {
+        self.update("DELETE FROM payments WHERE email_id = ?1", email_id)
+    }
+
+    fn update(&self, sql: &str, email_id: &str) -> Result<bool, String> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(sql, [email_id])
+            .map(|changed| changed > 0)
+            .map_err(|e| e.to_string())
+    }
+
+    fn query(&self, sql: &str, params: &[&dyn rusqlite::ToSql]) -> Result<Vec<Payment>, String> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare_cached(sql).map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(params, payment_from_row)
+            .map_err(|e| e.to_string())?;
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())
+    }
+}
+
+fn payment_from_row(row: &Row) -> rusqlite::Result<Payment> {
+    let due_date: Option<String> = row.get("due_date")?;
+    let source: String = row.get("source")?;
+    let detected_at: String = row.get("detected_at")?;
+    Ok(Payment {
+        email_id: row.get("email_id")?,
+        subject: row.get("subject")?,
+        invoice: Invoice {
+            payee: row.get("payee")?,
+            amount: row.get("amount")?,
+            currency: row.get("currency")?,
+            due_date: due_date.and_then(|date| NaiveDate::parse_from_str(&date, "%Y-%m-%d").ok()),
+        },
+        source: if source == "llm" {
+            PaymentSource::Llm
+        } else {
+            PaymentSource::Rules
+        },
+        paid: row.get("paid")?,
+        reminded: row.get("reminded")?,
+        detected_at: DateTime::parse_from_rfc3339(&detected_at)
+            .map(|time| time.with_timezone(&Utc))
+            .unwrap_or_default(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn email(subject: &str, body: &str) -> EmailData {
+        serde_json::from_value(serde_json::json!({
+            "id": "inv-1",
+            "subject": subject,
+            "sender": "City Power",
+            "sender_email": "billing@citypower.example.com",
+            "body": body,
+            "received_time": "2024-05-08 10:00:00",
+            "is_unread": true
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_detect() {
+        let invoice = detect(&email(
+            "Your May bill is ready",
+            "Usage this month: 320 kWh ($0.15/kWh)\nAmount due: $1,234.56\nDue date: May 20th",
+        ))
+        .unwrap();
+        assert_eq!(invoice.payee, "City Power");
+        assert_eq!(
+            (invoice.amount.as_str(), invoice.currency.as_str()),
+            ("1,234.56", "USD")
+        );
+        assert_eq!(invoice.due_date, NaiveDate::from_ymd_opt(2024, 5, 20));
+        assert!(invoice.is_complete());
+
+        let partial = detect(&email("Fatura disponível", "Valor: 99,90 BRL")).unwrap();
+        assert_eq!(partial.amount, "99,90");
+        assert_eq!(partial.currency, "BRL");
+        assert!(!partial.is_complete());
+        assert!(detect(&email("Lunch?", "Are you free at noon? I owe you $12")).is_none());
+    }
+
+    #[test]
+    fn test_parse_invoice() {
+        let answer = "```json\n{\"is_bill\": true, \"payee\": \"Acme Hosting\", \
+            \"amount\": \"49.00\", \"currency\": \"usd\", \"due_date\": \"2024-06-01\"}\n```";
+        let invoice = parse_invoice(answer).unwrap().unwrap();
+        assert_eq!(invoice.currency, "USD");
+        assert_eq!(invoice.due_date, NaiveDate::from_ymd_opt(2024, 6, 1));
+        let merged = Invoice {
+            payee: "Acme".to_string(),
+            ..Default::default()
+        }
+        .merge(invoice);
+        assert_eq!(merged.payee, "Acme");
+        assert_eq!(merged.amount, "49.00");
+        assert_eq!(parse_invoice("{\"is_bill\": false}").unwrap(), None);
+        assert!(parse_invoice("not json").is_err());
+    }
+
+    #[test]
+    fn test_payment_store() {
+        let store = PaymentStore::in_memory().unwrap();
+        let detected_at = Utc.with_ymd_and_hms(2024, 5, 8, 10, 0, 0).unwrap();
+        let payment = |email_id: &str, due: Option<u32>| Payment {
+            email_id: email_id.to_string(),
+            subject: "Bill".to_string(),
+            invoice: Invoice {
+                payee: "City Power".to_string(),
+                amount: "10.00".to_string(),
+                currency: "USD".to_string(),
+                due_date: due.and_then(|day| NaiveDate::from_ymd_opt(2024, 5, day)),
+            },
+            source: PaymentSource::Rules,
+            paid: false,
+            reminded: false,
+            detected_at,
+        };
+        assert!(store.insert(&payment("late", Some(30))).unwrap());
+        assert!(store.insert(&payment("soon", Some(12))).unwrap());
+        assert!(store.insert(&payment("undated", None)).unwrap());
+        assert!(!store.insert(&payment("soon", Some(1))).unwrap());
+
+        let ids: Vec<_> = store
+            .upcoming()
+            .unwrap()
+            .into_iter()
+            .map(|payment| payment.email_id)
+            .collect();
+        assert_eq!(ids, ["soon", "late", "undated"]);
+        assert_eq!(
+            store.get("soon").unwrap().unwrap(),
+            payment("soon", Some(12))
+        );
+
+        let may_15 = NaiveDate::from_ymd_opt(2024, 5, 15).unwrap();
+        assert_eq!(store.due_for_reminder(may_15).unwrap().len(), 1);
+        store.mark_reminded("soon").unwrap();
+        assert!(store.due_for_reminder(may_15).unwrap().is_empty());
+
+        assert!(store.mark_paid("late").unwrap());
+        assert!(store.remove("undated").unwrap());
+        assert!(!store.remove("undated").unwrap());
+        assert_eq!(store.upcoming().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,152 @@
+// SERINA Pins
+// Emails pinned as lightweight to-dos, kept in the tray until dismissed
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// Longest tray label before it is cut with an ellipsis
+const LABEL_LEN: usize = 48;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PinnedEmail {
+    pub email_id: String,
+    pub subject: String,
+    pub sender: String,
+    pub pinned_at: DateTime<Utc>,
+}
+
+impl PinnedEmail {
+    /// "Subject — Sender", shortened to fit a menu
+    pub fn label(&self) -> String {
+        let subject = if self.subject.trim().is_empty() {
+            "(no subject)"
+        } else {
+            self.subject.trim()
+        };
+        let label = if self.sender.is_empty() {
+            subject.to_string()
+        } else {
+            format!("{} \u{2014} {}", subject, self.sender)
+        };
+        if label.chars().count() <= LABEL_LEN {
+            return label;
+        }
+        let cut: String = label.chars().take(LABEL_LEN - 1).collect();
+        format!("{}\u{2026}", cut.trim_end())
+    }
+}
+
+/// Pinned emails, oldest first, saved on every change
+pub struct PinStore {
+    path: Option<PathBuf>,
+    pins: Mutex<Vec<PinnedEmail>>,
+}
+
+impl PinStore {
+    pub fn load(path: PathBuf) -> Self {
+        let pins = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+        Self {
+            path: Some(path),
+            pins: Mutex::new(pins),
+        }
+    }
+
+    pub fn in_memory() -> Self {
+        Self {
+            path: None,
+            pins: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn list(&self) -> Vec<PinnedEmail> {
+        self.pins.lock().unwrap().clone()
+    }
+
+    pub fn is_pinned(&self, email_id: &str) -> bool {
+        self.pins
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|pin| pin.email_id == email_id)
+    }
+
+    /// Pin an email; pinning it again refreshes its subject and sender but keeps its place
+    pub fn pin(&self, entry: PinnedEmail) -> Result<(), String> {
+        let mut pins = self.pins.lock().unwrap();
+        match pins.iter_mut().find(|pin| pin.email_id == entry.email_id) {
+            Some(existing) => {
+                existing.subject = entry.subject;
+                existing.sender = entry.sender;
+            }
+            None => pins.push(entry),
+        }
+        self.persist(&pins)
+    }
+
+    /// Dismiss a pin; returns whether the email was pinned
+    pub fn unpin(&self, email_id: &str) -> Result<bool, String> {
+        let mut pins = self.pins.lock().unwrap();
+        let before = pins.len();
+        pins.retain(|pin| pin.email_id != email_id);
+        if pins.len() == before {
+            return Ok(false);
+        }
+        self.persist(&pins)?;
+        Ok(true)
+    }
+
+    fn persist(&self, pins: &[PinnedEmail]) -> Result<(), String> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let contents = serde_json::to_string_pretty(pins).map_err(|e| e.to_string())?;
+        std::fs::write(path, contents).map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pin(id: &str, subject: &str) -> PinnedEmail {
+        PinnedEmail {
+            email_id: id.to_string(),
+            subject: subject.to_string(),
+            sender: "Dana".to_string(),
+            pinned_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_pins() {
+        let path = std::env::temp_dir().join(format!("serina-pins-{}.json", std::process::id()));
+        let store = PinStore::load(path.clone());
+        store.pin(pin("a", "Budget")).unwrap();
+        store.pin(pin("b", "Offsite")).unwrap();
+        store.pin(pin("a", "Budget v2")).unwrap();
+        let ids: Vec<_> = store.list().into_iter().map(|p| p.email_id).collect();
+        assert_eq!(ids, ["a", "b"]);
+        assert_eq!(store.list()[0].subject, "Budget v2");
+
+        assert_eq!(PinStore::load(path.clone()).list().len(), 2);
+        assert!(store.unpin("a").unwrap());
+        assert!(!store.unpin("a").unwrap());
+        assert!(!store.is_pinned("a"));
+        assert_eq!(PinStore::load(path.clone()).list().len(), 1);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_label() {
+        assert_eq!(pin("a", "Budget").label(), "Budget \u{2014} Dana");
+        assert_eq!(pin("a", " ").label(), "(no subject) \u{2014} Dana");
+        let long = pin("a", &"Quarterly planning ".repeat(4)).label();
+        assert_eq!(long.chars().count(), LABEL_LEN);
+        assert!(long.ends_with('\u{2026}'));
+    }
+}
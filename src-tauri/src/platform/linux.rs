@@ -0,0 +1,407 @@
+// SERINA Linux Integration
+// StatusNotifierItem tray detection, freedesktop notifications and power/network state
+
+use super::{ActionResponse, EmailAction, EmailNotification};
+use crate::power::PowerState;
+use crate::window::{is_screen_share_process, PresentationState};
+use notify_rust::{Hint, Notification, Urgency};
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use std::time::Duration;
+
+/// D-Bus name owned by the host that displays StatusNotifierItem/AppIndicator icons
+const SNI_WATCHER: &str = "org.kde.StatusNotifierWatcher";
+
+/// Read the reply to `dbus-send ... NameHasOwner`
+pub fn parse_name_has_owner(output: &str) -> Option<bool> {
+    output.lines().find_map(
+        |line| match line.split_whitespace().collect::<Vec<_>>().as_slice() {
+            ["boolean", "true"] => Some(true),
+            ["boolean", "false"] => Some(false),
+            _ => None,
+        },
+    )
+}
+
+fn sni_watcher_present() -> Option<bool> {
+    let output = Command::new("dbus-send")
+        .args([
+            "--session",
+            "--print-reply",
+            "--dest=org.freedesktop.DBus",
+            "/org/freedesktop/DBus",
+            "org.freedesktop.DBus.NameHasOwner",
+            &format!("string:{}", SNI_WATCHER),
+        ])
+        .output()
+        .ok()?;
+    parse_name_has_owner(&String::from_utf8_lossy(&output.stdout))
+}
+
+pub fn is_wayland() -> bool {
+    std::env::var("XDG_SESSION_TYPE").is_ok_and(|t| t.eq_ignore_ascii_case("wayland"))
+        || std::env::var_os("WAYLAND_DISPLAY").is_some()
+}
+
+/// Whether a tray icon will actually be shown. X11 sessions fall back to the legacy
+/// system tray; Wayland has nothing without a StatusNotifierItem host (e.g. stock GNOME).
+pub fn tray_available() -> bool {
+    sni_watcher_present().unwrap_or(false) || !is_wayland()
+}
+
+/// Whether a notification daemon is running that supports buttons
+pub fn supports_actions() -> bool {
+    notify_rust::get_capabilities().is_ok_and(|caps| caps.iter().any(|c| c == "actions"))
+}
+
+fn action_key(action: EmailAction) -> String {
+    match action {
+        // "default" is what the daemon reports for a click on the notification body
+        EmailAction::Open => "default".to_string(),
+        other => serde_json::to_value(other)
+            .ok()
+            .and_then(|v| v.as_str().map(str::to_string))
+            .unwrap_or_default(),
+    }
+}
+
+/// Blocks until an action is invoked or the notification closes; call off the main thread
+pub fn notify_with_actions(
+    notification: &EmailNotification,
+) -> Result<Option<ActionResponse>, String> {
+    let mut alert = Notification::new();
+    alert
+        .appname("SERINA")
+        .summary(&notification.title)
+        .body(&notification.body)
+        .hint(Hint::Category("email.arrived".to_string()))
+        .urgency(if notification.urgent {
+            Urgency::Critical
+        } else {
+            Urgency::Normal
+        });
+    if let Some(avatar) = &notification.avatar {
+        alert.image_path(&avatar.to_string_lossy());
+    }
+    for (action, label) in &notification.actions {
+        alert.action(&action_key(*action), label);
+    }
+
+    let handle = alert.show().map_err(|e| e.to_string())?;
+    let mut invoked = None;
+    handle.wait_for_action(|key| {
+        invoked = notification
+            .actions
+            .iter()
+            .map(|(action, _)| *action)
+            .chain(std::iter::once(EmailAction::Open))
+            .find(|action| action_key(*action) == key);
+    });
+    Ok(invoked.map(|action| ActionResponse {
+        action,
+        email_id: notification.email_id.clone(),
+        // freedesktop notifications have no inline reply; Reply opens the composer instead
+        reply_text: None,
+    }))
+}
+
+/// Read the value out of a `dbus-send --print-reply` for a uint32 property
+pub fn parse_uint32_reply(output: &str) -> Option<u32> {
+    output
+        .split_whitespace()
+        .skip_while(|word| *word != "uint32")
+        .nth(1)?
+        .parse()
+        .ok()
+}
+
+fn network_manager_property(name: &str) -> Option<u32> {
+    let output = Command::new("dbus-send")
+        .args([
+            "--system",
+            "--print-reply",
+            "--dest=org.freedesktop.NetworkManager",
+            "/org/freedesktop/NetworkManager",
+            "org.freedesktop.DBus.Properties.Get",
+            "string:org.freedesktop.NetworkManager",
+            &format!("string:{}", name),
+        ])
+        .output()
+        .ok()?;
+    parse_uint32_reply(&String::from_utf8_lossy(&output.stdout))
+}
+
+fn network_metered() -> bool {
+    // NMMetered: 0 unknown, 1 yes, 2 no, 3 guess-yes, 4 guess-no
+    matches!(network_manager_property("Metered"), Some(1 | 3))
+}
+
+/// Full internet connectivity per NetworkManager; `None` without NetworkManager
+pub fn is_online() -> Option<bool> {
+    // NMConnectivityState: 0 unknown, 1 none, 2 portal, 3 limited, 4 full
+    match network_manager_property("Connectivity")? {
+        0 => None,
+        state => Some(state == 4),
+    }
+}
+
+/// The active network in `nmcli -t -f active,ssid dev wifi` output ("yes:Office")
+pub fn parse_nmcli_ssid(output: &str) -> Option<String> {
+    output
+        .lines()
+        .find_map(|line| line.strip_prefix("yes:"))
+        // nmcli escapes colons inside terse fields
+        .map(|ssid| ssid.replace("\\:", ":"))
+        .filter(|ssid| !ssid.is_empty())
+}
+
+/// Connected Wi-Fi network per NetworkManager
+pub fn wifi_ssid() -> Option<String> {
+    let output = Command::new("nmcli")
+        .args(["-t", "-f", "active,ssid", "dev", "wifi"])
+        .output()
+        .ok()?;
+    parse_nmcli_ssid(&String::from_utf8_lossy(&output.stdout))
+}
+
+fn read_trimmed(path: &Path) -> String {
+    fs::read_to_string(path)
+        .map(|value| value.trim().to_string())
+        .unwrap_or_default()
+}
+
+/// Unplugged means a battery is discharging and no mains adapter is online
+fn on_battery() -> bool {
+    let Ok(supplies) = fs::read_dir("/sys/class/power_supply") else {
+        return false;
+    };
+    let mut discharging = false;
+    for supply in supplies.flatten() {
+        let path = supply.path();
+        match read_trimmed(&path.join("type")).as_str() {
+            "Mains" | "USB" if read_trimmed(&path.join("online")) == "1" => return false,
+            "Battery" => discharging |= read_trimmed(&path.join("status")) == "Discharging",
+            _ => {}
+        }
+    }
+    discharging
+}
+
+/// power-profiles-daemon's saver profile, or the ACPI platform profile when it isn't installed
+fn battery_saver() -> bool {
+    match Command::new("powerprofilesctl").arg("get").output() {
+        Ok(output) if output.status.success() => {
+            String::from_utf8_lossy(&output.stdout).trim() == "power-saver"
+        }
+        _ => read_trimmed(Path::new("/sys/firmware/acpi/platform_profile")) == "low-power",
+    }
+}
+
+/// Read the value out of a `dbus-send --print-reply` for a uint64 result
+pub fn parse_uint64_reply(output: &str) -> Option<u64> {
+    output
+        .split_whitespace()
+        .skip_while(|word| *word != "uint64")
+        .nth(1)?
+        .parse()
+        .ok()
+}
+
+/// Input idle time from Mutter's idle monitor (GNOME, including Wayland), else `xprintidle` on X11
+pub fn idle_time() -> Option<Duration> {
+    let mutter = Command::new("dbus-send")
+        .args([
+            "--session",
+            "--print-reply",
+            "--dest=org.gnome.Mutter.IdleMonitor",
+            "/org/gnome/Mutter/IdleMonitor/Core",
+            "org.gnome.Mutter.IdleMonitor.GetIdletime",
+        ])
+        .output()
+        .ok()
+        .and_then(|output| parse_uint64_reply(&String::from_utf8_lossy(&output.stdout)));
+    let millis = mutter.or_else(|| {
+        let output = Command::new("xprintidle").output().ok()?;
+        String::from_utf8_lossy(&output.stdout).trim().parse().ok()
+    })?;
+    Some(Duration::from_millis(millis))
+}
+
+/// Whether an `fprintd-list` listing shows an enrolled finger
+pub fn has_enrolled_finger(listing: &str) -> bool {
+    listing
+        .lines()
+        .any(|line| line.trim_start().starts_with("- #"))
+}
+
+/// A fingerprint enrolled with fprintd for the current user
+pub fn biometric_available() -> bool {
+    let user = std::env::var("USER").unwrap_or_default();
+    Command::new("fprintd-list")
+        .arg(user)
+        .output()
+        .is_ok_and(|output| has_enrolled_finger(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Ask for a finger on the reader; fprintd shows no prompt of its own, so the lock screen does
+pub fn verify_biometric() -> Result<bool, String> {
+    let output = Command::new("fprintd-verify")
+        .output()
+        .map_err(|e| format!("Fingerprint reader unavailable: {}", e))?;
+    Ok(String::from_utf8_lossy(&output.stdout).contains("verify-match"))
+}
+
+/// The window id in `xprop -root _NET_ACTIVE_WINDOW` output
+pub fn parse_active_window(output: &str) -> Option<&str> {
+    let id = output.rsplit(' ').next()?.trim();
+    (id.starts_with("0x") && id != "0x0").then_some(id)
+}
+
+fn xprop(args: &[&str]) -> Option<String> {
+    let output = Command::new("xprop").args(args).output().ok()?;
+    Some(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Whether the focused X11 window is fullscreen; Wayland doesn't let clients see this
+fn active_window_fullscreen() -> bool {
+    let Some(active) = xprop(&["-root", "_NET_ACTIVE_WINDOW"]) else {
+        return false;
+    };
+    parse_active_window(&active)
+        .and_then(|id| xprop(&["-id", id, "_NET_WM_STATE"]))
+        .is_some_and(|state| state.contains("_NET_WM_STATE_FULLSCREEN"))
+}
+
+fn screen_share_running() -> bool {
+    let Ok(processes) = fs::read_dir("/proc") else {
+        return false;
+    };
+    processes
+        .flatten()
+        .any(|process| is_screen_share_process(&read_trimmed(&process.path().join("comm"))))
+}
+
+pub fn presentation_state() -> PresentationState {
+    PresentationState {
+        fullscreen: active_window_fullscreen(),
+        presentation_mode: false,
+        screen_sharing: screen_share_running(),
+    }
+}
+
+pub fn power_state() -> PowerState {
+    PowerState {
+        on_battery: on_battery(),
+        battery_saver: battery_saver(),
+        metered: network_metered(),
+    }
+}
+
+/// Shown in file managers' "Open With" menus for the common document and image types
+const SHARE_MIME_TYPES: &[&str] = &[
+    "application/pdf",
+    "application/zip",
+    "application/msword",
+    "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+    "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+    "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+    "image/png",
+    "image/jpeg",
+    "image/gif",
+    "text/plain",
+    "text/csv",
+];
+
+/// A hidden desktop entry that launches SERINA with the chosen files
+pub fn share_desktop_entry(exe: &Path) -> String {
+    // Escaped once as a quoted Exec argument, then again as a desktop entry string value
+    let quoted: String = exe
+        .to_string_lossy()
+        .chars()
+        .flat_map(|c| match c {
+            '"' | '`' | '$' | '\\' => vec!['\\', c],
+            c => vec![c],
+        })
+        .collect();
+    let exe = quoted.replace('\\', "\\\\");
+    format!(
+        "[Desktop Entry]\nType=Application\nName=Email with SERINA\nExec=\"{}\" --share %F\n\
+         Icon=serina\nNoDisplay=true\nMimeType={};\n",
+        exe,
+        SHARE_MIME_TYPES.join(";")
+    )
+}
+
+pub fn register_desktop_entry(exe: &Path) -> Result<(), String> {
+    let dir = dirs::data_dir()
+        .ok_or_else(|| "No data directory".to_string())?
+        .join("applications");
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    fs::write(dir.join("serina-share.desktop"), share_desktop_entry(exe)).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_has_enrolled_finger() {
+        assert!(has_enrolled_finger(
+            "Fingerprints for user ana on Synaptics Sensors (press):\n - #0: right-index-finger\n"
+        ));
+        assert!(!has_enrolled_finger(
+            "User ana has no fingers enrolled for Synaptics Sensors.\n"
+        ));
+    }
+
+    #[test]
+    fn test_share_desktop_entry() {
+        let entry = share_desktop_entry(Path::new("/opt/SERINA \"beta\"/serina"));
+        assert!(entry.contains(r#"Exec="/opt/SERINA \\"beta\\"/serina" --share %F"#));
+        assert!(entry.contains("MimeType=application/pdf;"));
+        assert!(entry.ends_with("text/csv;\n"));
+    }
+
+    #[test]
+    fn test_parse_name_has_owner() {
+        let output = "method return time=1700000000.1 sender=org.freedesktop.DBus -> destination=:1.42 serial=3 reply_serial=2\n   boolean true\n";
+        assert_eq!(parse_name_has_owner(output), Some(true));
+        assert_eq!(parse_name_has_owner("   boolean false"), Some(false));
+        assert_eq!(parse_name_has_owner(""), None);
+        assert_eq!(action_key(EmailAction::Open), "default");
+        assert_eq!(action_key(EmailAction::MarkRead), "mark_read");
+    }
+
+    #[test]
+    fn test_parse_nmcli_ssid() {
+        let output = "no:Neighbours\nyes:Office\\:5G\nno:\n";
+        assert_eq!(parse_nmcli_ssid(output).as_deref(), Some("Office:5G"));
+        assert_eq!(parse_nmcli_ssid("no:Neighbours\n"), None);
+    }
+
+    #[test]
+    fn test_parse_uint32_reply() {
+        let reply = |value| {
+            format!("method return time=1 sender=:1.9 -> destination=:1.80 serial=7 reply_serial=2\n   variant       uint32 {}\n", value)
+        };
+        assert_eq!(parse_uint32_reply(&reply(3)), Some(3));
+        assert_eq!(parse_uint32_reply(&reply(4)), Some(4));
+        assert_eq!(
+            parse_active_window("_NET_ACTIVE_WINDOW(WINDOW): window id # 0x3a00007\n"),
+            Some("0x3a00007")
+        );
+        assert_eq!(
+            parse_active_window("_NET_ACTIVE_WINDOW(WINDOW): window id # 0x0"),
+            None
+        );
+        assert_eq!(
+            parse_uint64_reply("method return time=1\n   uint64 93021\n"),
+            Some(93021)
+        );
+        assert_eq!(
+            parse_uint32_reply("Error org.freedesktop.DBus.Error.ServiceUnknown"),
+            None
+        );
+    }
+}
@@ -0,0 +1,332 @@
+// SERINA macOS Integration
+// Dock badge, actionable Notification Center alerts and the Services menu entry
+
+use super::{ActionResponse, EmailAction, EmailNotification};
+use crate::intake::ShareRequest;
+use crate::notification::APP_ID;
+use crate::power::PowerState;
+use crate::window::{PresentationState, WindowEffect, SCREEN_SHARE_PROCESSES};
+use block::ConcreteBlock;
+use cocoa::appkit::NSPasteboardTypeString;
+use cocoa::base::{id, nil, BOOL, YES};
+use cocoa::foundation::{NSRect, NSString};
+use mac_notification_sys::{MainButton, Notification, NotificationResponse};
+use objc::declare::ClassDecl;
+use objc::runtime::{Object, Sel};
+use objc::{class, msg_send, sel, sel_impl};
+use std::ffi::CStr;
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::{mpsc, Once, OnceLock};
+use std::time::Duration;
+
+/// Set the dock tile badge; an empty label removes it. Must run on the main thread.
+pub fn set_dock_badge(label: &str) {
+    unsafe {
+        let app: id = msg_send![class!(NSApplication), sharedApplication];
+        let tile: id = msg_send![app, dockTile];
+        let label = if label.is_empty() {
+            nil
+        } else {
+            NSString::alloc(nil).init_str(label)
+        };
+        let _: () = msg_send![tile, setBadgeLabel: label];
+    }
+}
+
+/// NSVisualEffectMaterialUnderWindowBackground
+const VIBRANCY_MATERIAL: i64 = 21;
+/// NSViewWidthSizable | NSViewHeightSizable
+const FILL_SUPERVIEW: u64 = 2 | 16;
+
+/// Add or remove an NSVisualEffectView under the window's content. Must run on the main thread.
+pub fn set_window_effect(
+    ns_window: *mut std::ffi::c_void,
+    effect: WindowEffect,
+) -> Result<(), String> {
+    unsafe {
+        let content: id = msg_send![ns_window as id, contentView];
+        if content == nil {
+            return Err("Window has no content view".to_string());
+        }
+        let subviews: id = msg_send![content, subviews];
+        let count: usize = msg_send![subviews, count];
+        for i in (0..count).rev() {
+            let view: id = msg_send![subviews, objectAtIndex: i];
+            let is_effect: BOOL = msg_send![view, isKindOfClass: class!(NSVisualEffectView)];
+            if is_effect == YES {
+                let _: () = msg_send![view, removeFromSuperview];
+            }
+        }
+        if effect != WindowEffect::Vibrancy {
+            return Ok(());
+        }
+        let bounds: NSRect = msg_send![content, bounds];
+        let view: id = msg_send![class!(NSVisualEffectView), alloc];
+        let view: id = msg_send![view, initWithFrame: bounds];
+        let _: () = msg_send![view, setMaterial: VIBRANCY_MATERIAL];
+        // Blend with what is behind the window, even while it is inactive
+        let _: () = msg_send![view, setBlendingMode: 0i64];
+        let _: () = msg_send![view, setState: 1i64];
+        let _: () = msg_send![view, setAutoresizingMask: FILL_SUPERVIEW];
+        // NSWindowBelow, so the web view stays on top
+        let _: () = msg_send![content, addSubview: view positioned: -1i64 relativeTo: nil];
+    }
+    Ok(())
+}
+
+/// Deliver as SERINA rather than the process that happens to be running
+fn register_application() {
+    static REGISTER: Once = Once::new();
+    REGISTER.call_once(|| {
+        if let Err(e) = mac_notification_sys::set_application(APP_ID) {
+            crate::log_warn!("Could not register notification bundle: {}", e);
+        }
+    });
+}
+
+/// Blocks until the notification is answered or dismissed; call off the main thread
+pub fn notify_with_actions(
+    notification: &EmailNotification,
+) -> Result<Option<ActionResponse>, String> {
+    register_application();
+    // Notification Center has no inline reply box alongside buttons, so replies happen in the app
+    let buttons: Vec<&(EmailAction, String)> = notification
+        .actions
+        .iter()
+        .filter(|(action, _)| !matches!(action, EmailAction::Open | EmailAction::Reply))
+        .collect();
+    let labels: Vec<&str> = buttons.iter().map(|(_, label)| label.as_str()).collect();
+    let avatar = notification
+        .avatar
+        .as_ref()
+        .map(|path| path.to_string_lossy().into_owned());
+
+    let mut alert = Notification::new();
+    alert
+        .title(&notification.title)
+        .message(&notification.body)
+        .wait_for_click(true);
+    if let Some(avatar) = &avatar {
+        alert.content_image(avatar);
+    }
+    match labels.as_slice() {
+        [] => {}
+        [single] => {
+            alert.main_button(MainButton::SingleAction(single));
+        }
+        [first, ..] => {
+            alert.main_button(MainButton::DropdownActions(first, &labels));
+        }
+    }
+
+    let action = match alert.send().map_err(|e| e.to_string())? {
+        NotificationResponse::Click => Some(EmailAction::Open),
+        NotificationResponse::ActionButton(label) => buttons
+            .iter()
+            .find(|(_, l)| *l == label)
+            .map(|(action, _)| *action),
+        _ => None,
+    };
+    Ok(action.map(|action| ActionResponse {
+        action,
+        email_id: notification.email_id.clone(),
+        reply_text: None,
+    }))
+}
+
+static SHARE_HANDLER: OnceLock<fn(ShareRequest)> = OnceLock::new();
+
+#[link(name = "AppKit", kind = "framework")]
+extern "C" {
+    fn NSUpdateDynamicServices();
+}
+
+unsafe fn ns_string_to_string(string: id) -> Option<String> {
+    if string == nil {
+        return None;
+    }
+    let utf8: *const std::os::raw::c_char = msg_send![string, UTF8String];
+    (!utf8.is_null()).then(|| CStr::from_ptr(utf8).to_string_lossy().into_owned())
+}
+
+/// `shareWithSerina:userData:error:`, the NSMessage named in Info.plist's NSServices entry
+extern "C" fn share_with_serina(
+    _this: &Object,
+    _cmd: Sel,
+    pasteboard: id,
+    _user_data: id,
+    _error: *mut id,
+) {
+    let Some(handler) = SHARE_HANDLER.get() else {
+        return;
+    };
+    let mut request = ShareRequest::default();
+    unsafe {
+        let classes: id = msg_send![class!(NSArray), arrayWithObject: class!(NSURL)];
+        let urls: id = msg_send![pasteboard, readObjectsForClasses: classes options: nil];
+        let count: usize = if urls == nil {
+            0
+        } else {
+            msg_send![urls, count]
+        };
+        for i in 0..count {
+            let url: id = msg_send![urls, objectAtIndex: i];
+            let is_file: BOOL = msg_send![url, isFileURL];
+            if is_file == YES {
+                let path: id = msg_send![url, path];
+                request
+                    .files
+                    .extend(ns_string_to_string(path).map(PathBuf::from));
+            }
+        }
+        // Finder also puts the file names on the pasteboard as text
+        if request.files.is_empty() {
+            let text: id = msg_send![pasteboard, stringForType: NSPasteboardTypeString];
+            request.text = ns_string_to_string(text).unwrap_or_default();
+        }
+    }
+    if !request.is_empty() {
+        handler(request);
+    }
+}
+
+/// Answer the Services menu entry declared in Info.plist. Must run on the main thread.
+pub fn register_services(handler: fn(ShareRequest)) -> Result<(), String> {
+    SHARE_HANDLER
+        .set(handler)
+        .map_err(|_| "Services are already registered".to_string())?;
+    unsafe {
+        let mut decl = ClassDecl::new("SerinaServiceProvider", class!(NSObject))
+            .ok_or_else(|| "Service provider class already exists".to_string())?;
+        decl.add_method(
+            sel!(shareWithSerina:userData:error:),
+            share_with_serina as extern "C" fn(&Object, Sel, id, id, *mut id),
+        );
+        let provider_class = decl.register();
+        let provider: id = msg_send![provider_class, new];
+        let app: id = msg_send![class!(NSApplication), sharedApplication];
+        let _: () = msg_send![app, setServicesProvider: provider];
+        NSUpdateDynamicServices();
+    }
+    Ok(())
+}
+
+fn pmset(args: &[&str]) -> String {
+    Command::new("pmset")
+        .args(args)
+        .output()
+        .map(|output| String::from_utf8_lossy(&output.stdout).into_owned())
+        .unwrap_or_default()
+}
+
+/// Battery source and Low Power Mode from `pmset`. macOS exposes "expensive" networks only
+/// to Network.framework path monitors, so metered is left off here.
+pub fn power_state() -> PowerState {
+    let low_power = pmset(&["-g"]).lines().any(|line| {
+        let mut words = line.split_whitespace();
+        matches!(words.next(), Some("lowpowermode" | "powermode")) && words.next() == Some("1")
+    });
+    PowerState {
+        on_battery: pmset(&["-g", "batt"]).contains("'Battery Power'"),
+        battery_saver: low_power,
+        metered: false,
+    }
+}
+
+/// Whether the routing table can reach the internet, per the System Configuration framework
+pub fn is_online() -> Option<bool> {
+    let output = Command::new("scutil")
+        .args(["-r", "1.1.1.1"])
+        .output()
+        .ok()?;
+    let reachability = String::from_utf8_lossy(&output.stdout);
+    Some(reachability.starts_with("Reachable"))
+}
+
+/// Wi-Fi network from `networksetup`, which answers "Current Wi-Fi Network: Office"
+pub fn wifi_ssid() -> Option<String> {
+    let output = Command::new("networksetup")
+        .args(["-getairportnetwork", "en0"])
+        .output()
+        .ok()?;
+    String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .strip_prefix("Current Wi-Fi Network: ")
+        .map(str::to_string)
+}
+
+/// Input idle time from the HID system's `HIDIdleTime` (nanoseconds)
+pub fn idle_time() -> Option<Duration> {
+    let output = Command::new("ioreg")
+        .args(["-c", "IOHIDSystem", "-d", "4"])
+        .output()
+        .ok()?;
+    let listing = String::from_utf8_lossy(&output.stdout);
+    let nanos = listing
+        .lines()
+        .find(|line| line.contains("\"HIDIdleTime\""))?
+        .rsplit('=')
+        .next()?
+        .trim()
+        .parse()
+        .ok()?;
+    Some(Duration::from_nanos(nanos))
+}
+
+/// `LAPolicyDeviceOwnerAuthenticationWithBiometrics`
+const LA_POLICY_BIOMETRICS: isize = 1;
+
+#[link(name = "LocalAuthentication", kind = "framework")]
+extern "C" {}
+
+/// Touch ID is present and has a finger enrolled
+pub fn biometric_available() -> bool {
+    unsafe {
+        let context: id = msg_send![class!(LAContext), new];
+        let mut error: id = nil;
+        let available: BOOL =
+            msg_send![context, canEvaluatePolicy: LA_POLICY_BIOMETRICS error: &mut error];
+        let _: () = msg_send![context, release];
+        available == YES
+    }
+}
+
+/// Show the Touch ID prompt and wait for the answer
+pub fn verify_biometric(reason: &str) -> Result<bool, String> {
+    let (tx, rx) = mpsc::channel();
+    let reply = ConcreteBlock::new(move |success: BOOL, _error: id| {
+        let _ = tx.send(success == YES);
+    })
+    .copy();
+    let context: id = unsafe {
+        let context: id = msg_send![class!(LAContext), new];
+        let reason = NSString::alloc(nil).init_str(reason);
+        let _: () = msg_send![context, evaluatePolicy: LA_POLICY_BIOMETRICS
+                                      localizedReason: reason
+                                                reply: &*reply];
+        context
+    };
+    let verified = rx
+        .recv_timeout(Duration::from_secs(120))
+        .map_err(|_| "Touch ID didn't answer".to_string());
+    unsafe {
+        let _: () = msg_send![context, release];
+    }
+    verified
+}
+
+/// Screen sharing via its helper process. Other apps' fullscreen state isn't visible without
+/// accessibility permission, so only sharing is detected here.
+pub fn presentation_state() -> PresentationState {
+    let screen_sharing = SCREEN_SHARE_PROCESSES.iter().any(|name| {
+        Command::new("pgrep")
+            .args(["-ix", name])
+            .status()
+            .is_ok_and(|status| status.success())
+    });
+    PresentationState {
+        screen_sharing,
+        ..Default::default()
+    }
+}
@@ -0,0 +1,285 @@
+// SERINA Platform Integration
+// OS-specific shell integration (badges, actionable notifications) behind one interface
+
+#[cfg(target_os = "linux")]
+pub mod linux;
+#[cfg(target_os = "macos")]
+pub mod macos;
+pub mod toast;
+#[cfg(target_os = "windows")]
+pub mod windows;
+
+use crate::intake::ShareRequest;
+use crate::power::PowerState;
+use crate::window::PresentationState;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Buttons offered on email notifications where the OS supports them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EmailAction {
+    /// Clicking the notification body
+    Open,
+    MarkRead,
+    Snooze,
+    NotSpam,
+    /// Send the text typed into the inline reply box
+    Reply,
+    /// Keep it in the tray as a to-do
+    Pin,
+    /// Send a failed reply again without waiting for the next automatic attempt
+    RetrySend,
+    /// Put a failed reply back in the compose view
+    EditReply,
+    /// Drop a failed reply
+    DiscardReply,
+    /// Put the email's one-time code on the clipboard
+    CopyCode,
+    /// Stop reminding about a bill
+    MarkPaid,
+    /// Go ahead with a delete that a rule or script held back for the user
+    Delete,
+}
+
+impl EmailAction {
+    /// Fluent message id for the button label
+    pub fn message_id(self) -> &'static str {
+        match self {
+            EmailAction::Open => "action-open",
+            EmailAction::MarkRead => "action-mark-read",
+            EmailAction::Snooze => "action-snooze",
+            EmailAction::NotSpam => "action-not-spam",
+            EmailAction::Reply => "action-reply",
+            EmailAction::Pin => "action-pin",
+            EmailAction::RetrySend => "action-retry-send",
+            EmailAction::EditReply => "action-edit-reply",
+            EmailAction::DiscardReply => "action-discard-reply",
+            EmailAction::CopyCode => "action-copy-code",
+            EmailAction::MarkPaid => "action-mark-paid",
+            EmailAction::Delete => "action-delete",
+        }
+    }
+}
+
+/// An actionable notification about one email
+#[derive(Debug, Clone, Default)]
+pub struct EmailNotification {
+    pub email_id: String,
+    pub title: String,
+    pub body: String,
+    /// Sender picture, shown where the platform supports images
+    pub avatar: Option<PathBuf>,
+    /// Buttons with their localized labels
+    pub actions: Vec<(EmailAction, String)>,
+    /// Placeholder for an inline reply box; shown only with a `Reply` action
+    pub reply_placeholder: Option<String>,
+    /// Ask the OS to keep it on screen (critical urgency where supported)
+    pub urgent: bool,
+}
+
+/// What the user did with an actionable notification
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActionResponse {
+    pub action: EmailAction,
+    pub email_id: String,
+    pub reply_text: Option<String>,
+}
+
+/// Badge text for an unread count; empty clears the badge
+pub fn badge_label(count: u32) -> String {
+    match count {
+        0 => String::new(),
+        n if n > 99 => "99+".to_string(),
+        n => n.to_string(),
+    }
+}
+
+/// Show the unread count on the app icon. Must run on the main thread; a no-op without a dock
+pub fn set_badge_count(count: u32) {
+    #[cfg(target_os = "macos")]
+    macos::set_dock_badge(&badge_label(count));
+    #[cfg(not(target_os = "macos"))]
+    let _ = count;
+}
+
+/// Put `effect` behind a window's content, replacing any earlier one; `native` is the
+/// NSWindow on macOS and the HWND on Windows. Must run on the main thread.
+pub fn set_window_effect(
+    native: *mut std::ffi::c_void,
+    effect: crate::window::WindowEffect,
+) -> Result<(), String> {
+    if !effect.is_supported() {
+        return Err(format!("{:?} is not available on this platform", effect));
+    }
+    #[cfg(target_os = "macos")]
+    return macos::set_window_effect(native, effect);
+    #[cfg(target_os = "windows")]
+    return windows::set_window_effect(native, effect);
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    {
+        // Only `None` is supported here, and there is nothing to undo
+        let _ = native;
+        Ok(())
+    }
+}
+
+/// Whether `notify_with_actions` can show buttons on this platform
+pub fn supports_notification_actions() -> bool {
+    #[cfg(target_os = "linux")]
+    return linux::supports_actions();
+    #[cfg(not(target_os = "linux"))]
+    cfg!(any(target_os = "macos", target_os = "windows"))
+}
+
+/// Whether the OS will show our tray icon; when it won't, the app must stay reachable some other way
+pub fn tray_available() -> bool {
+    #[cfg(target_os = "linux")]
+    return linux::tray_available();
+    #[cfg(not(target_os = "linux"))]
+    true
+}
+
+/// Current battery and network cost state; anything that can't be read counts as unconstrained
+pub fn power_state() -> PowerState {
+    #[cfg(target_os = "macos")]
+    return macos::power_state();
+    #[cfg(target_os = "windows")]
+    return windows::power_state();
+    #[cfg(target_os = "linux")]
+    return linux::power_state();
+    #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+    PowerState::default()
+}
+
+/// Whether the OS reports internet connectivity; `None` when it can't tell
+pub fn is_online() -> Option<bool> {
+    #[cfg(target_os = "macos")]
+    return macos::is_online();
+    #[cfg(target_os = "windows")]
+    return windows::is_online();
+    #[cfg(target_os = "linux")]
+    return linux::is_online();
+    #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+    None
+}
+
+/// Name of the Wi-Fi network the machine is on; `None` when not on Wi-Fi or unknown
+pub fn wifi_ssid() -> Option<String> {
+    #[cfg(target_os = "macos")]
+    return macos::wifi_ssid();
+    #[cfg(target_os = "windows")]
+    return windows::wifi_ssid();
+    #[cfg(target_os = "linux")]
+    return linux::wifi_ssid();
+    #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+    None
+}
+
+/// Time since the last keyboard or mouse input; `None` when the OS can't tell
+pub fn idle_time() -> Option<Duration> {
+    #[cfg(target_os = "macos")]
+    return macos::idle_time();
+    #[cfg(target_os = "windows")]
+    return windows::idle_time();
+    #[cfg(target_os = "linux")]
+    return linux::idle_time();
+    #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+    None
+}
+
+/// Whether the OS can confirm the user with Windows Hello, Touch ID or a fingerprint reader
+pub fn biometric_available() -> bool {
+    #[cfg(target_os = "macos")]
+    return macos::biometric_available();
+    #[cfg(target_os = "windows")]
+    return windows::biometric_available();
+    #[cfg(target_os = "linux")]
+    return linux::biometric_available();
+    #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+    false
+}
+
+/// Ask the OS to confirm the user biometrically, blocking until they answer. `reason` is shown in
+/// the system prompt where there is one.
+pub fn verify_biometric(reason: &str) -> Result<bool, String> {
+    #[cfg(target_os = "macos")]
+    return macos::verify_biometric(reason);
+    #[cfg(target_os = "windows")]
+    return windows::verify_biometric(reason);
+    #[cfg(target_os = "linux")]
+    {
+        let _ = reason;
+        linux::verify_biometric()
+    }
+    #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+    {
+        let _ = reason;
+        Err("Biometric unlock is not supported on this platform".to_string())
+    }
+}
+
+/// Whether a presentation or screen share looks likely right now
+pub fn presentation_state() -> PresentationState {
+    #[cfg(target_os = "macos")]
+    return macos::presentation_state();
+    #[cfg(target_os = "windows")]
+    return windows::presentation_state();
+    #[cfg(target_os = "linux")]
+    return linux::presentation_state();
+    #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+    PresentationState::default()
+}
+
+/// Offer SERINA as somewhere to share files and text: the file context menu on Windows, the
+/// Services menu on macOS and "Open With" on Linux. Windows and Linux launch the executable with
+/// `--share` arguments; macOS calls `handler` on the running app.
+pub fn register_share_target(handler: fn(ShareRequest)) -> Result<(), String> {
+    #[cfg(target_os = "macos")]
+    return macos::register_services(handler);
+    #[cfg(not(target_os = "macos"))]
+    {
+        let _ = handler;
+        let exe = std::env::current_exe().map_err(|e| e.to_string())?;
+        #[cfg(target_os = "windows")]
+        return windows::register_share_menu(&exe);
+        #[cfg(target_os = "linux")]
+        return linux::register_desktop_entry(&exe);
+        #[cfg(not(any(target_os = "windows", target_os = "linux")))]
+        {
+            let _ = exe;
+            Err("Sharing to SERINA is not supported on this platform".to_string())
+        }
+    }
+}
+
+/// Show a notification with action buttons and wait for the user's choice.
+/// `None` means it was dismissed or timed out.
+pub fn notify_with_actions(
+    notification: &EmailNotification,
+) -> Result<Option<ActionResponse>, String> {
+    #[cfg(target_os = "macos")]
+    return macos::notify_with_actions(notification);
+    #[cfg(target_os = "windows")]
+    return windows::notify_with_actions(notification);
+    #[cfg(target_os = "linux")]
+    return linux::notify_with_actions(notification);
+    #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+    {
+        let _ = notification;
+        Err("Notification actions are not supported on this platform".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_badge_label() {
+        assert_eq!(badge_label(0), "");
+        assert_eq!(badge_label(7), "7");
+        assert_eq!(badge_label(250), "99+");
+    }
+}
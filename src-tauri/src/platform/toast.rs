@@ -0,0 +1,138 @@
+// SERINA Toast Content
+// Windows toast XML and activation arguments, kept platform-neutral so they can be tested anywhere
+
+use super::{ActionResponse, EmailAction, EmailNotification};
+
+/// Id of the inline reply text box, used to read its value back on activation
+pub const REPLY_INPUT_ID: &str = "reply";
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+fn action_name(action: EmailAction) -> String {
+    serde_json::to_value(action)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+/// Activation arguments identifying the action and email, form-encoded
+pub fn activation_arguments(action: EmailAction, email_id: &str) -> String {
+    url::form_urlencoded::Serializer::new(String::new())
+        .append_pair("action", &action_name(action))
+        .append_pair("email", email_id)
+        .finish()
+}
+
+/// Decode activation arguments; `reply_text` is the reply box value, if any
+pub fn parse_activation(arguments: &str, reply_text: Option<String>) -> Option<ActionResponse> {
+    let mut action = None;
+    let mut email_id = None;
+    for (key, value) in url::form_urlencoded::parse(arguments.as_bytes()) {
+        match key.as_ref() {
+            "action" => action = serde_json::from_value(serde_json::json!(value)).ok(),
+            "email" => email_id = Some(value.into_owned()),
+            _ => {}
+        }
+    }
+    Some(ActionResponse {
+        action: action?,
+        email_id: email_id?,
+        reply_text: reply_text.filter(|text| !text.trim().is_empty()),
+    })
+}
+
+/// ToastGeneric XML with the sender avatar, an optional inline reply box and buttons
+pub fn toast_xml(notification: &EmailNotification) -> String {
+    let id = &notification.email_id;
+    let mut xml = format!(
+        r#"<toast launch="{}" activationType="foreground"><visual><binding template="ToastGeneric"><text>{}</text><text>{}</text>"#,
+        escape(&activation_arguments(EmailAction::Open, id)),
+        escape(&notification.title),
+        escape(&notification.body),
+    );
+    if let Some(avatar) = &notification.avatar {
+        xml.push_str(&format!(
+            r#"<image placement="appLogoOverride" hint-crop="circle" src="{}"/>"#,
+            escape(&avatar.to_string_lossy())
+        ));
+    }
+    xml.push_str("</binding></visual><actions>");
+
+    let has_reply = notification
+        .actions
+        .iter()
+        .any(|(action, _)| *action == EmailAction::Reply);
+    if let Some(placeholder) = notification
+        .reply_placeholder
+        .as_ref()
+        .filter(|_| has_reply)
+    {
+        xml.push_str(&format!(
+            r#"<input id="{}" type="text" placeHolderContent="{}"/>"#,
+            REPLY_INPUT_ID,
+            escape(placeholder)
+        ));
+    }
+    for (action, label) in &notification.actions {
+        if *action == EmailAction::Open {
+            continue;
+        }
+        let input = if *action == EmailAction::Reply && notification.reply_placeholder.is_some() {
+            format!(r#" hint-inputId="{}""#, REPLY_INPUT_ID)
+        } else {
+            String::new()
+        };
+        xml.push_str(&format!(
+            r#"<action content="{}" arguments="{}" activationType="foreground"{}/>"#,
+            escape(label),
+            escape(&activation_arguments(*action, id)),
+            input
+        ));
+    }
+    xml.push_str("</actions></toast>");
+    xml
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_activation_round_trip() {
+        let arguments = activation_arguments(EmailAction::MarkRead, "00A1&B=2");
+        let response = parse_activation(&arguments, Some("  ".to_string())).unwrap();
+        assert_eq!(response.action, EmailAction::MarkRead);
+        assert_eq!(response.email_id, "00A1&B=2");
+        assert_eq!(response.reply_text, None);
+        assert!(parse_activation("action=explode&email=1", None).is_none());
+    }
+
+    #[test]
+    fn test_toast_xml() {
+        let notification = EmailNotification {
+            email_id: "42".to_string(),
+            title: "Ana <ana@example.com>".to_string(),
+            body: "Q3 numbers & notes".to_string(),
+            avatar: Some("C:\\Users\\me\\.serina\\avatars\\ana.jpg".into()),
+            actions: vec![
+                (EmailAction::Reply, "Send".to_string()),
+                (EmailAction::MarkRead, "Mark as read".to_string()),
+            ],
+            reply_placeholder: Some("Type a reply".to_string()),
+            urgent: false,
+        };
+        let xml = toast_xml(&notification);
+        assert!(xml.contains("Ana &lt;ana@example.com&gt;"));
+        assert!(xml.contains("numbers &amp; notes"));
+        assert!(xml.contains(r#"placement="appLogoOverride""#));
+        assert!(xml.contains(r#"<input id="reply""#));
+        assert!(xml.contains(r#"hint-inputId="reply""#));
+        assert!(xml.contains("action=mark_read&amp;email=42"));
+    }
+}
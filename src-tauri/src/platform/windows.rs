@@ -0,0 +1,259 @@
+// SERINA Windows Integration
+// Rich WinRT toasts with sender avatar, inline reply and action buttons
+
+use super::toast::{parse_activation, toast_xml, REPLY_INPUT_ID};
+use super::{ActionResponse, EmailNotification};
+use crate::notification::APP_ID;
+use crate::power::PowerState;
+use crate::window::{is_screen_share_process, PresentationState, WindowEffect};
+use std::path::Path;
+use std::sync::mpsc;
+use std::time::Duration;
+use windows::core::{IInspectable, Interface, HSTRING, PCWSTR};
+use windows::Data::Xml::Dom::XmlDocument;
+use windows::Foundation::{IPropertyValue, TypedEventHandler};
+use windows::Networking::Connectivity::{
+    NetworkConnectivityLevel, NetworkCostType, NetworkInformation,
+};
+use windows::Security::Credentials::UI::{
+    UserConsentVerificationResult, UserConsentVerifier, UserConsentVerifierAvailability,
+};
+use windows::Win32::Foundation::HWND;
+use windows::Win32::Graphics::Dwm::{
+    DwmExtendFrameIntoClientArea, DwmSetWindowAttribute, DWMSBT_MAINWINDOW, DWMSBT_NONE,
+    DWMSBT_TRANSIENTWINDOW, DWMWA_SYSTEMBACKDROP_TYPE, DWM_SYSTEMBACKDROP_TYPE,
+};
+use windows::Win32::System::Power::{GetSystemPowerStatus, SYSTEM_POWER_STATUS};
+use windows::Win32::System::Registry::{RegSetKeyValueW, HKEY_CURRENT_USER, REG_SZ};
+use windows::Win32::System::SystemInformation::GetTickCount;
+use windows::Win32::UI::Controls::MARGINS;
+use windows::Win32::UI::Input::KeyboardAndMouse::{GetLastInputInfo, LASTINPUTINFO};
+use windows::Win32::UI::Shell::{
+    SHQueryUserNotificationState, QUNS_BUSY, QUNS_PRESENTATION_MODE, QUNS_QUIET_TIME,
+    QUNS_RUNNING_D3D_FULL_SCREEN,
+};
+use windows::UI::Notifications::{
+    ToastActivatedEventArgs, ToastDismissedEventArgs, ToastNotification, ToastNotificationManager,
+};
+
+/// Read the inline reply box out of the activation arguments
+fn reply_text(args: &ToastActivatedEventArgs) -> Option<String> {
+    let value = args
+        .UserInput()
+        .ok()?
+        .Lookup(&HSTRING::from(REPLY_INPUT_ID))
+        .ok()?;
+    Some(
+        value
+            .cast::<IPropertyValue>()
+            .ok()?
+            .GetString()
+            .ok()?
+            .to_string(),
+    )
+}
+
+/// Blocks until the toast is activated or dismissed; call off the main thread
+pub fn notify_with_actions(
+    notification: &EmailNotification,
+) -> Result<Option<ActionResponse>, String> {
+    let document = XmlDocument::new().map_err(|e| e.to_string())?;
+    document
+        .LoadXml(&HSTRING::from(toast_xml(notification)))
+        .map_err(|e| e.to_string())?;
+    let toast = ToastNotification::CreateToastNotification(&document).map_err(|e| e.to_string())?;
+
+    let (sender, receiver) = mpsc::channel();
+    let activated = sender.clone();
+    toast
+        .Activated(&TypedEventHandler::<ToastNotification, IInspectable>::new(
+            move |_, args| {
+                let response = args
+                    .as_ref()
+                    .and_then(|args| args.cast::<ToastActivatedEventArgs>().ok())
+                    .and_then(|args| {
+                        let arguments = args.Arguments().ok()?.to_string();
+                        parse_activation(&arguments, reply_text(&args))
+                    });
+                let _ = activated.send(response);
+                Ok(())
+            },
+        ))
+        .map_err(|e| e.to_string())?;
+    toast
+        .Dismissed(&TypedEventHandler::<
+            ToastNotification,
+            ToastDismissedEventArgs,
+        >::new(move |_, _| {
+            let _ = sender.send(None);
+            Ok(())
+        }))
+        .map_err(|e| e.to_string())?;
+
+    ToastNotificationManager::CreateToastNotifierWithId(&HSTRING::from(APP_ID))
+        .and_then(|notifier| notifier.Show(&toast))
+        .map_err(|e| e.to_string())?;
+    Ok(receiver.recv().unwrap_or(None))
+}
+
+/// Whether the internet connection profile has a data cap or is roaming
+fn network_metered() -> Option<bool> {
+    let cost = NetworkInformation::GetInternetConnectionProfile()
+        .ok()?
+        .GetConnectionCost()
+        .ok()?;
+    let capped = cost.NetworkCostType().ok()? != NetworkCostType::Unrestricted;
+    Some(capped || cost.Roaming().unwrap_or(false) || cost.OverDataLimit().unwrap_or(false))
+}
+
+pub fn power_state() -> PowerState {
+    let mut status = SYSTEM_POWER_STATUS::default();
+    let read = unsafe { GetSystemPowerStatus(&mut status) }.is_ok();
+    PowerState {
+        // ACLineStatus: 0 offline, 1 online, 255 unknown
+        on_battery: read && status.ACLineStatus == 0,
+        // SystemStatusFlag is 1 while battery saver is on
+        battery_saver: read && status.SystemStatusFlag == 1,
+        metered: network_metered().unwrap_or(false),
+    }
+}
+
+/// Internet access on the active connection profile; no profile means offline
+pub fn is_online() -> Option<bool> {
+    let Ok(profile) = NetworkInformation::GetInternetConnectionProfile() else {
+        return Some(false);
+    };
+    let level = profile.GetNetworkConnectivityLevel().ok()?;
+    Some(level == NetworkConnectivityLevel::InternetAccess)
+}
+
+/// Wi-Fi network from the "SSID : Office" line of `netsh wlan show interfaces`
+pub fn wifi_ssid() -> Option<String> {
+    let output = std::process::Command::new("netsh")
+        .args(["wlan", "show", "interfaces"])
+        .output()
+        .ok()?;
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            (key.trim() == "SSID").then(|| value.trim().to_string())
+        })
+        .filter(|ssid| !ssid.is_empty())
+}
+
+/// Set the DWM system backdrop; needs Windows 11 22H2 or later
+pub fn set_window_effect(hwnd: *mut std::ffi::c_void, effect: WindowEffect) -> Result<(), String> {
+    let hwnd = HWND(hwnd);
+    let backdrop = match effect {
+        WindowEffect::Acrylic => DWMSBT_TRANSIENTWINDOW,
+        WindowEffect::Mica => DWMSBT_MAINWINDOW,
+        WindowEffect::None | WindowEffect::Vibrancy => DWMSBT_NONE,
+    };
+    // The backdrop only shows where the frame extends into the client area
+    let inset = if backdrop == DWMSBT_NONE { 0 } else { -1 };
+    let margins = MARGINS {
+        cxLeftWidth: inset,
+        cxRightWidth: inset,
+        cyTopHeight: inset,
+        cyBottomHeight: inset,
+    };
+    unsafe {
+        DwmExtendFrameIntoClientArea(hwnd, &margins).map_err(|e| e.to_string())?;
+        DwmSetWindowAttribute(
+            hwnd,
+            DWMWA_SYSTEMBACKDROP_TYPE,
+            &backdrop as *const DWM_SYSTEMBACKDROP_TYPE as *const std::ffi::c_void,
+            std::mem::size_of::<DWM_SYSTEMBACKDROP_TYPE>() as u32,
+        )
+        .map_err(|e| e.to_string())
+    }
+}
+
+/// Time since the last input event in this session
+pub fn idle_time() -> Option<Duration> {
+    let mut info = LASTINPUTINFO {
+        cbSize: std::mem::size_of::<LASTINPUTINFO>() as u32,
+        dwTime: 0,
+    };
+    if !unsafe { GetLastInputInfo(&mut info) }.as_bool() {
+        return None;
+    }
+    // Both are 32-bit tick counts, so wrapping subtraction survives the 49-day rollover
+    let idle_ms = unsafe { GetTickCount() }.wrapping_sub(info.dwTime);
+    Some(Duration::from_millis(idle_ms as u64))
+}
+
+/// Windows Hello is set up (face, fingerprint or its PIN)
+pub fn biometric_available() -> bool {
+    UserConsentVerifier::CheckAvailabilityAsync()
+        .and_then(|operation| operation.get())
+        .is_ok_and(|availability| availability == UserConsentVerifierAvailability::Available)
+}
+
+/// Show the Windows Hello prompt and wait for the answer
+pub fn verify_biometric(reason: &str) -> Result<bool, String> {
+    let result = UserConsentVerifier::RequestVerificationAsync(&HSTRING::from(reason))
+        .and_then(|operation| operation.get())
+        .map_err(|e| e.to_string())?;
+    Ok(result == UserConsentVerificationResult::Verified)
+}
+
+fn screen_share_running() -> bool {
+    let Ok(output) = std::process::Command::new("tasklist")
+        .args(["/FO", "CSV", "/NH"])
+        .output()
+    else {
+        return false;
+    };
+    String::from_utf8_lossy(&output.stdout).lines().any(|line| {
+        line.split(',')
+            .next()
+            .is_some_and(|name| is_screen_share_process(name.trim_matches('"')))
+    })
+}
+
+/// The shell's own notion of "don't disturb": fullscreen apps, presentation mode, quiet time
+pub fn presentation_state() -> PresentationState {
+    let state = unsafe { SHQueryUserNotificationState() }.ok();
+    PresentationState {
+        fullscreen: matches!(state, Some(QUNS_BUSY | QUNS_RUNNING_D3D_FULL_SCREEN)),
+        presentation_mode: matches!(state, Some(QUNS_PRESENTATION_MODE | QUNS_QUIET_TIME)),
+        screen_sharing: screen_share_running(),
+    }
+}
+
+/// Per-user context menu verb for every file type; no admin rights needed
+const SHARE_MENU_KEY: &str = "Software\\Classes\\*\\shell\\SerinaShare";
+
+/// Write a string value under HKEY_CURRENT_USER; `name` of `None` sets the key's default value
+fn set_registry_string(key: &str, name: Option<&str>, value: &str) -> Result<(), String> {
+    let key = HSTRING::from(key);
+    let name = name.map(HSTRING::from);
+    let data: Vec<u16> = value.encode_utf16().chain(std::iter::once(0)).collect();
+    unsafe {
+        RegSetKeyValueW(
+            HKEY_CURRENT_USER,
+            PCWSTR(key.as_ptr()),
+            name.as_ref()
+                .map_or(PCWSTR::null(), |name| PCWSTR(name.as_ptr())),
+            REG_SZ.0,
+            Some(data.as_ptr().cast()),
+            (data.len() * std::mem::size_of::<u16>()) as u32,
+        )
+    }
+    .ok()
+    .map_err(|e| e.to_string())
+}
+
+/// "Email with SERINA" in Explorer's file context menu, launching the app with `--share`
+pub fn register_share_menu(exe: &Path) -> Result<(), String> {
+    let exe = exe.to_string_lossy();
+    set_registry_string(SHARE_MENU_KEY, Some("MUIVerb"), "Email with SERINA")?;
+    set_registry_string(SHARE_MENU_KEY, Some("Icon"), &exe)?;
+    set_registry_string(
+        &format!("{}\\command", SHARE_MENU_KEY),
+        None,
+        &format!("\"{}\" --share \"%1\"", exe),
+    )
+}
@@ -0,0 +1,168 @@
+// SERINA Power Awareness
+// Backs off background work on battery saver and metered networks, and notices suspend/resume
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct PowerState {
+    /// Running from battery rather than mains power
+    pub on_battery: bool,
+    /// The OS battery-saver / low-power mode is on
+    pub battery_saver: bool,
+    /// The active network is metered (mobile hotspot, capped plan)
+    pub metered: bool,
+}
+
+impl PowerState {
+    pub fn detect() -> Self {
+        crate::platform::power_state()
+    }
+}
+
+/// How background sync adapts to power and network conditions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PowerPolicy {
+    /// Follow battery saver and metered networks; off keeps the normal schedule
+    pub enabled: bool,
+    /// Also back off when merely unplugged, not only in battery saver
+    pub on_battery: bool,
+    /// Poll interval while constrained; never shorter than the normal interval
+    pub constrained_interval_minutes: u32,
+    pub defer_attachments: bool,
+    pub pause_classification: bool,
+}
+
+impl Default for PowerPolicy {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            on_battery: false,
+            constrained_interval_minutes: 60,
+            defer_attachments: true,
+            pause_classification: true,
+        }
+    }
+}
+
+/// What background work to do right now
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct SyncPlan {
+    pub state: PowerState,
+    pub constrained: bool,
+    pub interval_minutes: u32,
+    /// Download attachments such as sender pictures
+    pub fetch_attachments: bool,
+    /// Run junk-folder classification
+    pub classify: bool,
+}
+
+impl PowerPolicy {
+    pub fn is_constrained(&self, state: PowerState) -> bool {
+        self.enabled
+            && (state.battery_saver || state.metered || (self.on_battery && state.on_battery))
+    }
+
+    pub fn plan(&self, state: PowerState, check_interval_minutes: u32) -> SyncPlan {
+        let interval = check_interval_minutes.max(1);
+        let constrained = self.is_constrained(state);
+        SyncPlan {
+            state,
+            constrained,
+            interval_minutes: if constrained {
+                interval.max(self.constrained_interval_minutes)
+            } else {
+                interval
+            },
+            fetch_attachments: !(constrained && self.defer_attachments),
+            classify: !(constrained && self.pause_classification),
+        }
+    }
+}
+
+/// Spots suspend/resume by comparing wall-clock time between regular checks. Timers pause
+/// while the machine sleeps but the clock doesn't, so a large gap means it was asleep.
+pub struct WakeDetector {
+    last_check: Mutex<DateTime<Utc>>,
+}
+
+impl WakeDetector {
+    pub fn new(now: DateTime<Utc>) -> Self {
+        Self {
+            last_check: Mutex::new(now),
+        }
+    }
+
+    /// Record a check that was due `interval` after the previous one. Returns how long the
+    /// machine was asleep when the gap exceeds the interval by more than `tolerance`.
+    pub fn check(
+        &self,
+        now: DateTime<Utc>,
+        interval: Duration,
+        tolerance: Duration,
+    ) -> Option<Duration> {
+        let mut last = self.last_check.lock().unwrap();
+        let elapsed = now
+            .signed_duration_since(*last)
+            .to_std()
+            .unwrap_or_default();
+        *last = now;
+        let slept = elapsed.checked_sub(interval)?;
+        (slept > tolerance).then_some(slept)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plan() {
+        let policy = PowerPolicy::default();
+        let normal = policy.plan(PowerState::default(), 15);
+        assert!(!normal.constrained && normal.fetch_attachments && normal.classify);
+        assert_eq!(normal.interval_minutes, 15);
+
+        let unplugged = PowerState {
+            on_battery: true,
+            ..Default::default()
+        };
+        assert!(!policy.plan(unplugged, 15).constrained);
+
+        let metered = PowerState {
+            metered: true,
+            ..Default::default()
+        };
+        let plan = policy.plan(metered, 15);
+        assert_eq!(plan.interval_minutes, 60);
+        assert!(!plan.fetch_attachments && !plan.classify);
+        // A user interval already longer than the constrained one is kept
+        assert_eq!(policy.plan(metered, 120).interval_minutes, 120);
+
+        let disabled = PowerPolicy {
+            enabled: false,
+            ..Default::default()
+        };
+        assert!(!disabled.plan(metered, 15).constrained);
+    }
+
+    #[test]
+    fn test_wake_detector() {
+        use chrono::TimeZone;
+        let start = Utc.with_ymd_and_hms(2024, 5, 1, 18, 0, 0).unwrap();
+        let detector = WakeDetector::new(start);
+        let interval = Duration::from_secs(30);
+        let tolerance = Duration::from_secs(60);
+        let at = |secs| start + chrono::Duration::seconds(secs);
+        assert_eq!(detector.check(at(31), interval, tolerance), None);
+        assert_eq!(detector.check(at(100), interval, tolerance), None);
+        // Lid closed overnight
+        assert_eq!(
+            detector.check(at(100 + 8 * 3600), interval, tolerance),
+            Some(Duration::from_secs(8 * 3600 - 30))
+        );
+    }
+}
@@ -0,0 +1,52 @@
+// SERINA Privacy Mode
+// Keeps all traffic on this machine when privacy mode is enabled
+
+/// Whether a URL points at this machine (loopback only)
+pub fn is_local_url(url: &str) -> bool {
+    let Ok(parsed) = reqwest::Url::parse(url) else {
+        return false;
+    };
+    match parsed.host() {
+        Some(url::Host::Domain(domain)) => domain.eq_ignore_ascii_case("localhost"),
+        Some(url::Host::Ipv4(ip)) => ip.is_loopback(),
+        Some(url::Host::Ipv6(ip)) => ip.is_loopback(),
+        None => false,
+    }
+}
+
+/// Refuse outbound requests to anything but this machine while privacy mode is on
+pub fn ensure_allowed(url: &str, privacy_mode: bool) -> Result<(), String> {
+    if privacy_mode && !is_local_url(url) {
+        return Err(format!(
+            "Privacy mode is on: blocked outbound request to {}",
+            reqwest::Url::parse(url)
+                .ok()
+                .and_then(|u| u.host_str().map(str::to_string))
+                .unwrap_or_else(|| url.to_string())
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_local_url() {
+        assert!(is_local_url("http://localhost:11434/v1"));
+        assert!(is_local_url("http://127.0.0.1:8000/emails"));
+        assert!(is_local_url("http://[::1]:8000/"));
+        assert!(!is_local_url("https://api.openai.com/v1"));
+        assert!(!is_local_url("http://192.168.1.20:11434/v1"));
+        assert!(!is_local_url("not a url"));
+    }
+
+    #[test]
+    fn test_ensure_allowed() {
+        assert!(ensure_allowed("https://api.openai.com/v1", false).is_ok());
+        let err = ensure_allowed("https://api.openai.com/v1", true).unwrap_err();
+        assert!(err.contains("api.openai.com"));
+        assert!(ensure_allowed("http://localhost:11434/v1", true).is_ok());
+    }
+}
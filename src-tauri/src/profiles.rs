@@ -0,0 +1,191 @@
+// SERINA Notification Profiles
+// Named sets of quiet hours and VIPs ("Work", "Weekend", "Travel") that override the base
+// notification settings, picked by hand or automatically by time or Wi-Fi network
+
+use crate::utils::NotificationConfig;
+use chrono::{Datelike, NaiveDateTime, NaiveTime, Weekday};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+
+/// Settings left out of a profile keep their base value
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotificationProfile {
+    pub name: String,
+    pub quiet_hours_start: Option<String>,
+    pub quiet_hours_end: Option<String>,
+    pub show_desktop_notifications: Option<bool>,
+    pub vip_senders: Option<Vec<String>>,
+    /// Switch to this profile automatically during these hours
+    pub schedule: Option<ProfileSchedule>,
+    /// Switch to this profile automatically on these Wi-Fi networks
+    pub ssids: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProfileSchedule {
+    /// "Mon" to "Sun"; empty means every day
+    #[serde(default)]
+    pub days: Vec<Weekday>,
+    /// "HH:MM", local time; an end before the start runs past midnight
+    pub start: String,
+    pub end: String,
+}
+
+impl ProfileSchedule {
+    fn matches(&self, now: NaiveDateTime) -> bool {
+        if !self.days.is_empty() && !self.days.contains(&now.weekday()) {
+            return false;
+        }
+        let (Ok(start), Ok(end)) = (
+            NaiveTime::parse_from_str(&self.start, "%H:%M"),
+            NaiveTime::parse_from_str(&self.end, "%H:%M"),
+        ) else {
+            return false;
+        };
+        let time = now.time();
+        if start <= end {
+            time >= start && time < end
+        } else {
+            time >= start || time < end
+        }
+    }
+}
+
+pub fn find<'a>(config: &'a NotificationConfig, name: &str) -> Option<&'a NotificationProfile> {
+    config
+        .profiles
+        .iter()
+        .find(|profile| profile.name.eq_ignore_ascii_case(name))
+}
+
+/// Overlay the active profile onto the base settings; an unknown or empty name leaves them as is
+pub fn apply_active(config: &mut NotificationConfig) {
+    let Some(profile) = find(config, &config.active_profile).cloned() else {
+        return;
+    };
+    if let Some(start) = profile.quiet_hours_start {
+        config.quiet_hours_start = start;
+    }
+    if let Some(end) = profile.quiet_hours_end {
+        config.quiet_hours_end = end;
+    }
+    if let Some(show) = profile.show_desktop_notifications {
+        config.show_desktop_notifications = show;
+    }
+    if let Some(vips) = profile.vip_senders {
+        config.vip_senders = vips;
+    }
+}
+
+/// Whether any profile switches itself on by Wi-Fi network, so the SSID is worth reading
+pub fn uses_ssid(profiles: &[NotificationProfile]) -> bool {
+    profiles.iter().any(|profile| !profile.ssids.is_empty())
+}
+
+/// The profile conditions call for right now: a Wi-Fi match wins over a schedule
+pub fn auto_select<'a>(
+    profiles: &'a [NotificationProfile],
+    now: NaiveDateTime,
+    ssid: Option<&str>,
+) -> Option<&'a NotificationProfile> {
+    let by_network = ssid.and_then(|ssid| {
+        profiles
+            .iter()
+            .find(|profile| profile.ssids.iter().any(|s| s == ssid))
+    });
+    by_network.or_else(|| {
+        profiles.iter().find(|profile| {
+            profile
+                .schedule
+                .as_ref()
+                .is_some_and(|schedule| schedule.matches(now))
+        })
+    })
+}
+
+/// Remembers the last automatic pick, so a profile chosen by hand stays until the
+/// conditions change
+#[derive(Default)]
+pub struct AutoSwitch {
+    last: Mutex<Option<String>>,
+}
+
+impl AutoSwitch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The profile to switch to, when the automatic pick differs from the last one
+    pub fn observe(&self, selected: Option<&str>) -> Option<String> {
+        let mut last = self.last.lock().unwrap();
+        if last.as_deref() == selected {
+            return None;
+        }
+        *last = selected.map(str::to_string);
+        last.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn profiles() -> Vec<NotificationProfile> {
+        vec![
+            NotificationProfile {
+                name: "Weekend".to_string(),
+                show_desktop_notifications: Some(false),
+                schedule: Some(ProfileSchedule {
+                    days: vec![Weekday::Sat, Weekday::Sun],
+                    start: "00:00".to_string(),
+                    end: "23:59".to_string(),
+                }),
+                ..Default::default()
+            },
+            NotificationProfile {
+                name: "Work".to_string(),
+                quiet_hours_start: Some("18:00".to_string()),
+                vip_senders: Some(vec!["@example.com".to_string()]),
+                ssids: vec!["Office".to_string()],
+                ..Default::default()
+            },
+        ]
+    }
+
+    #[test]
+    fn test_apply_active() {
+        let mut config = NotificationConfig {
+            profiles: profiles(),
+            active_profile: "work".to_string(),
+            ..Default::default()
+        };
+        apply_active(&mut config);
+        assert_eq!(config.quiet_hours_start, "18:00");
+        assert_eq!(config.quiet_hours_end, "08:00");
+        assert_eq!(config.vip_senders, ["@example.com"]);
+        assert!(config.show_desktop_notifications);
+    }
+
+    #[test]
+    fn test_auto_select() {
+        let profiles = profiles();
+        // May 11th 2024 is a Saturday
+        let saturday = NaiveDate::from_ymd_opt(2024, 5, 11)
+            .unwrap()
+            .and_hms_opt(10, 0, 0)
+            .unwrap();
+        let name = |ssid| auto_select(&profiles, saturday, ssid).map(|p| p.name.as_str());
+        assert_eq!(name(None), Some("Weekend"));
+        assert_eq!(name(Some("Office")), Some("Work"));
+        let monday = saturday + chrono::Duration::days(2);
+        assert!(auto_select(&profiles, monday, Some("Home")).is_none());
+
+        let switch = AutoSwitch::new();
+        assert_eq!(switch.observe(Some("Work")).as_deref(), Some("Work"));
+        assert_eq!(switch.observe(Some("Work")), None);
+        assert_eq!(switch.observe(None), None);
+        assert_eq!(switch.observe(Some("Work")).as_deref(), Some("Work"));
+    }
+}
@@ -0,0 +1,175 @@
+// SERINA Data Purge
+// On-demand deletion of locally stored mail data, overwriting files before they are removed
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::OpenOptions;
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+
+/// A kind of local data `purge_local_data` can delete
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PurgeScope {
+    /// Cached email bodies and HTML
+    Bodies,
+    /// Generated summaries
+    Summaries,
+    /// Search embeddings. Nothing keeps them on disk yet, so this scope finds nothing to delete.
+    Embeddings,
+    /// Audit log, activity feed and queued usage stats
+    Logs,
+    /// API keys, passwords and tokens in the config file, and the cache key in the keychain
+    Secrets,
+}
+
+impl PurgeScope {
+    pub const ALL: [PurgeScope; 5] = [
+        PurgeScope::Bodies,
+        PurgeScope::Summaries,
+        PurgeScope::Embeddings,
+        PurgeScope::Logs,
+        PurgeScope::Secrets,
+    ];
+
+    /// The name the UI uses, as serialized
+    pub fn name(self) -> &'static str {
+        match self {
+            PurgeScope::Bodies => "bodies",
+            PurgeScope::Summaries => "summaries",
+            PurgeScope::Embeddings => "embeddings",
+            PurgeScope::Logs => "logs",
+            PurgeScope::Secrets => "secrets",
+        }
+    }
+}
+
+/// What one scope's purge removed
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PurgeResult {
+    pub scope: PurgeScope,
+    /// Emails, summaries, log entries or credentials deleted
+    pub removed: usize,
+    pub error: Option<String>,
+}
+
+/// The scopes to purge, each once and in a fixed order; none means all of them
+pub fn resolve_scopes(scopes: &[PurgeScope]) -> Vec<PurgeScope> {
+    PurgeScope::ALL
+        .into_iter()
+        .filter(|scope| scopes.is_empty() || scopes.contains(scope))
+        .collect()
+}
+
+/// Overwrite a file with zeros, flush it to disk and remove it. Returns whether the file existed.
+pub fn secure_delete(path: &Path) -> Result<bool, String> {
+    let len = match std::fs::metadata(path) {
+        Ok(metadata) => metadata.len(),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(false),
+        Err(e) => return Err(e.to_string()),
+    };
+    let mut file = OpenOptions::new()
+        .write(true)
+        .open(path)
+        .map_err(|e| e.to_string())?;
+    let zeros = [0u8; 8192];
+    let mut left = len;
+    while left > 0 {
+        let chunk = left.min(zeros.len() as u64) as usize;
+        file.write_all(&zeros[..chunk]).map_err(|e| e.to_string())?;
+        left -= chunk as u64;
+    }
+    file.sync_all().map_err(|e| e.to_string())?;
+    drop(file);
+    std::fs::remove_file(path).map_err(|e| e.to_string())?;
+    Ok(true)
+}
+
+/// Lines of a JSON-lines log that carry a timestamp
+#[derive(Deserialize)]
+struct Stamped {
+    timestamp: DateTime<Utc>,
+}
+
+/// Drop entries stamped before `cutoff` from a JSON-lines log, securely deleting the old copy.
+/// Lines without a readable timestamp are kept. Returns how many entries were dropped.
+pub fn prune_log(path: &Path, cutoff: DateTime<Utc>) -> Result<usize, String> {
+    let file = match std::fs::File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(e.to_string()),
+    };
+    let lines: Vec<String> = BufReader::new(file)
+        .lines()
+        .collect::<Result<_, _>>()
+        .map_err(|e| e.to_string())?;
+    let kept: Vec<&String> = lines
+        .iter()
+        .filter(|line| {
+            serde_json::from_str::<Stamped>(line).map_or(true, |entry| entry.timestamp >= cutoff)
+        })
+        .collect();
+    let dropped = lines.len() - kept.len();
+    if dropped == 0 {
+        return Ok(0);
+    }
+
+    let mut pruned = path.as_os_str().to_owned();
+    pruned.push(".pruned");
+    let pruned = std::path::PathBuf::from(pruned);
+    let mut contents = String::new();
+    for line in kept {
+        contents.push_str(line);
+        contents.push('\n');
+    }
+    std::fs::write(&pruned, contents).map_err(|e| e.to_string())?;
+    secure_delete(path)?;
+    std::fs::rename(&pruned, path).map_err(|e| e.to_string())?;
+    Ok(dropped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_scopes() {
+        assert_eq!(resolve_scopes(&[]), PurgeScope::ALL.to_vec());
+        for scope in PurgeScope::ALL {
+            assert_eq!(serde_json::to_value(scope).unwrap(), scope.name());
+        }
+        assert_eq!(
+            resolve_scopes(&[PurgeScope::Secrets, PurgeScope::Bodies, PurgeScope::Secrets]),
+            vec![PurgeScope::Bodies, PurgeScope::Secrets]
+        );
+    }
+
+    #[test]
+    fn test_prune_and_delete() {
+        let dir = std::env::temp_dir().join(format!("serina-purge-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("audit.log");
+        std::fs::write(
+            &path,
+            concat!(
+                "{\"timestamp\":\"2026-01-05T10:00:00Z\",\"action\":\"archived\"}\n",
+                "not json\n",
+                "{\"timestamp\":\"2026-09-01T10:00:00Z\",\"action\":\"deleted\"}\n",
+            ),
+        )
+        .unwrap();
+
+        let cutoff = "2026-06-01T00:00:00Z".parse().unwrap();
+        assert_eq!(prune_log(&path, cutoff).unwrap(), 1);
+        let left = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(left.lines().count(), 2);
+        assert!(left.contains("deleted"));
+        assert_eq!(prune_log(&path, cutoff).unwrap(), 0);
+
+        assert!(secure_delete(&path).unwrap());
+        assert!(!path.exists());
+        assert!(!secure_delete(&path).unwrap());
+        assert_eq!(prune_log(&path, cutoff).unwrap(), 0);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
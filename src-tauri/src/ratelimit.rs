@@ -0,0 +1,216 @@
+// SERINA Rate Limits
+// Reads Retry-After and x-ratelimit-* headers and holds requests to a host until its limit lifts
+
+use chrono::{DateTime, Utc};
+use reqwest::header::HeaderMap;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tokio::time::Instant;
+
+/// Retries of a rate-limited request before giving up
+pub const MAX_RETRIES: u32 = 3;
+/// Longer waits than this fail straight away rather than hold a command open
+pub const MAX_WAIT: Duration = Duration::from_secs(60);
+
+/// Sent whenever a request is held back by a rate limit
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RateLimited {
+    pub host: String,
+    pub wait_secs: u64,
+    /// Which retry this wait precedes
+    pub attempt: u32,
+}
+
+/// Per-host queue: once a host says to wait, every request to it waits
+pub struct RateLimiter {
+    blocked_until: Mutex<HashMap<String, Instant>>,
+    events: broadcast::Sender<RateLimited>,
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        Self {
+            blocked_until: Mutex::default(),
+            events: broadcast::channel(16).0,
+        }
+    }
+}
+
+impl RateLimiter {
+    pub fn subscribe(&self) -> broadcast::Receiver<RateLimited> {
+        self.events.subscribe()
+    }
+
+    /// Sleep until `host` may be called again
+    pub async fn wait_turn(&self, host: &str) {
+        loop {
+            let until = self.blocked_until.lock().unwrap().get(host).copied();
+            match until {
+                Some(until) if until > Instant::now() => tokio::time::sleep_until(until).await,
+                _ => return,
+            }
+        }
+    }
+
+    /// Hold requests to `host` for `wait`, keeping any longer hold already in place
+    pub fn block(&self, host: &str, wait: Duration) {
+        let until = Instant::now() + wait;
+        let mut blocked = self.blocked_until.lock().unwrap();
+        let entry = blocked.entry(host.to_string()).or_insert(until);
+        *entry = (*entry).max(until);
+    }
+
+    pub fn notify(&self, event: RateLimited) {
+        // Nobody listening is fine
+        let _ = self.events.send(event);
+    }
+}
+
+fn header<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers.get(name)?.to_str().ok().map(str::trim)
+}
+
+/// How long the server asked us to wait, from the first header that says:
+/// Retry-After (seconds or an HTTP date), retry-after-ms, then the longest x-ratelimit reset
+pub fn retry_after(headers: &HeaderMap, now: DateTime<Utc>) -> Option<Duration> {
+    if let Some(value) = header(headers, "retry-after") {
+        if let Ok(secs) = value.parse::<f64>() {
+            return Some(Duration::from_secs_f64(secs.max(0.0)));
+        }
+        if let Ok(date) = DateTime::parse_from_rfc2822(value) {
+            return Some(
+                (date.with_timezone(&Utc) - now)
+                    .to_std()
+                    .unwrap_or_default(),
+            );
+        }
+    }
+    if let Some(ms) = header(headers, "retry-after-ms").and_then(|ms| ms.parse::<f64>().ok()) {
+        return Some(Duration::from_secs_f64(ms.max(0.0) / 1000.0));
+    }
+    headers
+        .iter()
+        .filter(|(name, _)| name.as_str().starts_with("x-ratelimit-reset"))
+        .filter_map(|(_, value)| reset_wait(value.to_str().ok()?.trim(), now))
+        .max()
+}
+
+/// Whether the response says the quota is used up, so the next request would be limited
+pub fn exhausted(headers: &HeaderMap) -> bool {
+    headers.iter().any(|(name, value)| {
+        name.as_str().starts_with("x-ratelimit-remaining")
+            && value
+                .to_str()
+                .is_ok_and(|value| value.trim().parse::<f64>() == Ok(0.0))
+    })
+}
+
+/// An x-ratelimit reset value: a duration like "6m0s" or "250ms", seconds until the reset, or
+/// the reset time as a Unix timestamp
+fn reset_wait(value: &str, now: DateTime<Utc>) -> Option<Duration> {
+    if let Ok(number) = value.parse::<f64>() {
+        // Anything this large is an epoch time rather than a wait
+        if number > 1_000_000_000.0 {
+            let secs = number - now.timestamp() as f64;
+            return Some(Duration::from_secs_f64(secs.max(0.0)));
+        }
+        return Some(Duration::from_secs_f64(number.max(0.0)));
+    }
+    parse_duration(value)
+}
+
+/// A Go-style duration as sent by OpenAI, e.g. "1h2m3.5s" or "20ms"
+fn parse_duration(value: &str) -> Option<Duration> {
+    let mut total = 0.0;
+    let mut rest = value;
+    while !rest.is_empty() {
+        let split = rest
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .filter(|split| *split > 0)?;
+        let number: f64 = rest[..split].parse().ok()?;
+        let unit_len = rest[split..]
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(rest.len() - split);
+        let scale = match &rest[split..split + unit_len] {
+            "h" => 3600.0,
+            "m" => 60.0,
+            "s" => 1.0,
+            "ms" => 0.001,
+            _ => return None,
+        };
+        total += number * scale;
+        rest = &rest[split + unit_len..];
+    }
+    (!value.is_empty()).then(|| Duration::from_secs_f64(total))
+}
+
+/// Wait before retry `attempt` when the server gave no hint: 2s, 4s, 8s
+pub fn backoff(attempt: u32) -> Duration {
+    Duration::from_secs(2u64.saturating_pow(attempt.max(1)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::{HeaderName, HeaderValue};
+
+    fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+        pairs
+            .iter()
+            .map(|(name, value)| {
+                (
+                    HeaderName::from_static(name),
+                    HeaderValue::from_str(value).unwrap(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_retry_after() {
+        let now = DateTime::parse_from_rfc3339("2024-05-14T09:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let wait = |pairs| retry_after(&headers(pairs), now);
+        assert_eq!(wait(&[("retry-after", "7")]), Some(Duration::from_secs(7)));
+        assert_eq!(
+            wait(&[("retry-after", "Tue, 14 May 2024 09:00:30 GMT")]),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(
+            wait(&[("retry-after-ms", "250")]),
+            Some(Duration::from_millis(250))
+        );
+        assert_eq!(
+            wait(&[
+                ("x-ratelimit-reset-requests", "1m30s"),
+                ("x-ratelimit-reset-tokens", "20ms"),
+            ]),
+            Some(Duration::from_secs(90))
+        );
+        assert_eq!(
+            wait(&[("x-ratelimit-reset", "1715677212")]),
+            Some(Duration::from_secs(12))
+        );
+        assert_eq!(wait(&[("x-ratelimit-reset-requests", "soon")]), None);
+        assert_eq!(wait(&[]), None);
+    }
+
+    #[test]
+    fn test_exhausted() {
+        assert!(exhausted(&headers(&[(
+            "x-ratelimit-remaining-requests",
+            "0"
+        )])));
+        assert!(!exhausted(&headers(&[(
+            "x-ratelimit-remaining-requests",
+            "12"
+        )])));
+        assert!(!exhausted(&headers(&[])));
+        assert_eq!(backoff(1), Duration::from_secs(2));
+        assert_eq!(backoff(3), Duration::from_secs(8));
+    }
+}
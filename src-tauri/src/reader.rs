@@ -0,0 +1,682 @@
+// SERINA Reader Mode
+// The article inside a newsletter, without the mastheads, share bars, footers and tracking
+// pixels around it, as Markdown
+
+use crate::parsing::{self, decode_entities};
+use crate::utils::EmailData;
+use serde::{Deserialize, Serialize};
+
+/// Words read per minute, for the reading-time estimate
+const READING_SPEED: usize = 230;
+
+/// Elements whose content is never shown
+const HIDDEN_ELEMENTS: &[&str] = &["head", "style", "script", "title", "noscript"];
+
+/// Elements that never have content of their own
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "hr", "img", "input", "link", "meta", "source", "wbr",
+];
+
+/// Page furniture rather than article
+const CHROME_ELEMENTS: &[&str] = &[
+    "nav", "header", "footer", "aside", "form", "button", "select", "svg", "iframe",
+];
+
+/// Class and id fragments newsletter templates use for the same furniture
+const CHROME_NAMES: &[&str] = &[
+    "footer",
+    "unsubscribe",
+    "preheader",
+    "social",
+    "share",
+    "navbar",
+    "menu",
+    "advert",
+    "sponsor",
+];
+
+/// Lines that only exist because the email was sent to a list
+const LIST_BOILERPLATE: &[&str] = &[
+    "unsubscribe",
+    "view in browser",
+    "view in your browser",
+    "view this email in your browser",
+    "view it in your browser",
+    "manage your preferences",
+    "update your preferences",
+    "email preferences",
+    "forward to a friend",
+    "you are receiving this",
+    "you received this email",
+];
+
+/// Elements that start a new paragraph
+const BLOCK_ELEMENTS: &[&str] = &[
+    "p",
+    "div",
+    "table",
+    "tbody",
+    "thead",
+    "tr",
+    "td",
+    "th",
+    "section",
+    "article",
+    "main",
+    "center",
+    "body",
+    "dl",
+    "dt",
+    "dd",
+    "figure",
+    "figcaption",
+];
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NewsletterConfig {
+    /// Summarize newsletters as they arrive and archive them, leaving the summary behind
+    pub auto_summarize_archive: bool,
+    /// Addresses or @domains always treated as newsletters, for lists that don't send a
+    /// List-Unsubscribe header
+    pub senders: Vec<String>,
+}
+
+impl NewsletterConfig {
+    pub fn is_listed(&self, sender_email: &str) -> bool {
+        let sender = sender_email.trim().to_lowercase();
+        !sender.is_empty()
+            && self.senders.iter().any(|entry| {
+                let entry = entry.trim().to_lowercase();
+                if entry.starts_with('@') {
+                    sender.ends_with(&entry)
+                } else {
+                    sender == entry
+                }
+            })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ReaderView {
+    pub email_id: String,
+    pub subject: String,
+    pub sender: String,
+    pub markdown: String,
+    pub word_count: usize,
+    pub reading_minutes: usize,
+}
+
+/// The email as an article: the main content of its HTML body, or the new part of a
+/// plain-text one
+pub fn reader_view(email: &EmailData, html: Option<&str>) -> ReaderView {
+    let markdown = match html.filter(|html| parsing::looks_like_html(html)) {
+        Some(html) => to_markdown(html),
+        None => parsing::new_content(&email.body)
+            .split("\n\n")
+            .map(|paragraph| paragraph.trim())
+            .filter(|paragraph| !paragraph.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n\n"),
+    };
+    let word_count = markdown.split_whitespace().count();
+    ReaderView {
+        email_id: email.id.clone(),
+        subject: email.subject.clone(),
+        sender: email.sender.clone(),
+        markdown,
+        word_count,
+        reading_minutes: word_count.div_ceil(READING_SPEED).max(1),
+    }
+}
+
+/// Find the main content of an HTML document and render it as Markdown
+pub fn to_markdown(html: &str) -> String {
+    let tree = Tree::parse(html);
+    let mut writer = Writer::default();
+    tree.render(tree.main_content(), &mut writer);
+    writer.finish()
+}
+
+#[derive(Debug, Default)]
+struct Element {
+    name: String,
+    attributes: Vec<(String, String)>,
+    children: Vec<usize>,
+}
+
+impl Element {
+    fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Furniture, hidden preview text or a tracking pixel
+    fn is_chrome(&self) -> bool {
+        let style: String = self
+            .attribute("style")
+            .unwrap_or_default()
+            .to_lowercase()
+            .split_whitespace()
+            .collect();
+        let names = format!(
+            "{} {} {}",
+            self.attribute("class").unwrap_or_default(),
+            self.attribute("id").unwrap_or_default(),
+            self.attribute("role").unwrap_or_default()
+        )
+        .to_lowercase();
+        let pixel = ["width", "height"]
+            .iter()
+            .any(|size| matches!(self.attribute(size), Some("0" | "1")));
+        CHROME_ELEMENTS.contains(&self.name.as_str())
+            || style.contains("display:none")
+            || style.contains("max-height:0")
+            || names.contains("navigation")
+            || CHROME_NAMES.iter().any(|name| names.contains(name))
+            || (self.name == "img" && pixel)
+    }
+}
+
+#[derive(Debug)]
+enum Node {
+    Element(Element),
+    Text(String),
+}
+
+/// Text measured under an element
+#[derive(Debug, Clone, Copy, Default)]
+struct Measure {
+    /// Characters of text outside links
+    text: usize,
+    /// Characters of link text
+    link_text: usize,
+    links: usize,
+}
+
+/// A forgiving HTML tree: unknown closing tags are ignored and unclosed elements end with
+/// their parent. Furniture is pruned while parsing, so it takes no part in scoring.
+struct Tree {
+    nodes: Vec<Node>,
+    parents: Vec<usize>,
+    measures: Vec<Measure>,
+}
+
+impl Tree {
+    fn parse(html: &str) -> Self {
+        let mut tree = Tree {
+            nodes: vec![Node::Element(Element {
+                name: "#root".to_string(),
+                ..Default::default()
+            })],
+            parents: vec![0],
+            measures: Vec::new(),
+        };
+        let mut open = vec![0usize];
+        let mut hidden: Option<String> = None;
+        // Depth of the pruned element being skipped, counted by name
+        let mut pruned: Option<(String, usize)> = None;
+        let mut rest = html;
+
+        while !rest.is_empty() {
+            let start = rest.find('<').unwrap_or(rest.len());
+            if hidden.is_none() && pruned.is_none() && start > 0 {
+                let parent = *open.last().unwrap();
+                tree.push(parent, Node::Text(decode_entities(&rest[..start])));
+            }
+            rest = &rest[start..];
+            if rest.is_empty() {
+                break;
+            }
+
+            if let Some(comment) = rest.strip_prefix("<!--") {
+                rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+                continue;
+            }
+            let Some(end) = rest.find('>') else {
+                break;
+            };
+            let tag = &rest[1..end];
+            rest = &rest[end + 1..];
+
+            let closing = tag.starts_with('/');
+            let name: String = tag
+                .trim_start_matches('/')
+                .chars()
+                .take_while(|c| c.is_ascii_alphanumeric())
+                .collect::<String>()
+                .to_ascii_lowercase();
+            if name.is_empty() {
+                continue;
+            }
+            let self_closing = tag.ends_with('/') || VOID_ELEMENTS.contains(&name.as_str());
+
+            if let Some(element) = &hidden {
+                if closing && *element == name {
+                    hidden = None;
+                }
+                continue;
+            }
+            if let Some((element, depth)) = &mut pruned {
+                if *element == name && !self_closing {
+                    if closing {
+                        *depth -= 1;
+                    } else {
+                        *depth += 1;
+                    }
+                    if *depth == 0 {
+                        pruned = None;
+                    }
+                }
+                continue;
+            }
+            if closing {
+                if let Some(index) = open
+                    .iter()
+                    .rposition(|&id| id != 0 && tree.element(id).name == name)
+                {
+                    open.truncate(index);
+                }
+                continue;
+            }
+            if HIDDEN_ELEMENTS.contains(&name.as_str()) {
+                if !self_closing {
+                    hidden = Some(name);
+                }
+                continue;
+            }
+
+            let element = Element {
+                attributes: attributes(&tag[name.len()..]),
+                name,
+                children: Vec::new(),
+            };
+            if element.is_chrome() {
+                if !self_closing {
+                    pruned = Some((element.name, 1));
+                }
+                continue;
+            }
+            let parent = *open.last().unwrap();
+            let id = tree.push(parent, Node::Element(element));
+            if !self_closing {
+                open.push(id);
+            }
+        }
+
+        tree.measure();
+        tree
+    }
+
+    fn push(&mut self, parent: usize, node: Node) -> usize {
+        let id = self.nodes.len();
+        self.nodes.push(node);
+        self.parents.push(parent);
+        if let Node::Element(element) = &mut self.nodes[parent] {
+            element.children.push(id);
+        }
+        id
+    }
+
+    fn element(&self, id: usize) -> &Element {
+        match &self.nodes[id] {
+            Node::Element(element) => element,
+            Node::Text(_) => unreachable!("text nodes have no children"),
+        }
+    }
+
+    /// Children always come after their parent, so one backwards pass adds everything up
+    fn measure(&mut self) {
+        let mut measures = vec![Measure::default(); self.nodes.len()];
+        for id in (1..self.nodes.len()).rev() {
+            let mut measure = measures[id];
+            match &self.nodes[id] {
+                Node::Text(text) => measure.text = text.split_whitespace().map(str::len).sum(),
+                Node::Element(element) if element.name == "a" => {
+                    measure.link_text += measure.text;
+                    measure.text = 0;
+                    measure.links += 1;
+                }
+                Node::Element(_) => {}
+            }
+            measures[id] = measure;
+            let parent = &mut measures[self.parents[id]];
+            parent.text += measure.text;
+            parent.link_text += measure.link_text;
+            parent.links += measure.links;
+        }
+        self.measures = measures;
+    }
+
+    /// The innermost element that still holds most of the text outside links. Navigation is
+    /// mostly links and the furniture is already gone, so what's left is the article.
+    fn main_content(&self) -> usize {
+        let mut current = 0;
+        loop {
+            let total = self.measures[current].text;
+            let Node::Element(element) = &self.nodes[current] else {
+                return current;
+            };
+            let largest = element
+                .children
+                .iter()
+                .filter(|&&id| matches!(self.nodes[id], Node::Element(_)))
+                .max_by_key(|&&id| self.measures[id].text);
+            match largest {
+                Some(&id) if total > 0 && self.measures[id].text * 5 >= total * 4 => current = id,
+                _ => return current,
+            }
+        }
+    }
+
+    /// A row of short links, like "Home | Archive | About"
+    fn is_link_list(&self, id: usize) -> bool {
+        let measure = self.measures[id];
+        let all = measure.text + measure.link_text;
+        measure.links >= 3 && measure.link_text * 2 > all && all < 200
+    }
+
+    fn render_children(&self, id: usize, out: &mut Writer) {
+        for &child in &self.element(id).children {
+            self.render(child, out);
+        }
+    }
+
+    /// The element's content as a single line
+    fn inline(&self, id: usize) -> String {
+        let mut writer = Writer::default();
+        self.render_children(id, &mut writer);
+        writer.flush();
+        writer.blocks.join(" ")
+    }
+
+    fn render(&self, id: usize, out: &mut Writer) {
+        let element = match &self.nodes[id] {
+            Node::Text(text) => {
+                // Source line breaks are just whitespace; only <br> breaks a line
+                out.line.push_str(&text.replace(['\r', '\n'], " "));
+                return;
+            }
+            Node::Element(element) => element,
+        };
+        match element.name.as_str() {
+            "br" => out.line.push('\n'),
+            "hr" => out.block("---".to_string()),
+            "img" => {
+                let alt = element.attribute("alt").unwrap_or_default().trim();
+                let src = element.attribute("src").unwrap_or_default();
+                // Decorative images and inline cid: parts have nothing to show in Markdown
+                if !alt.is_empty() && is_web_url(src) {
+                    out.line.push_str(&format!("![{}]({})", alt, src));
+                }
+            }
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                let level = element.name[1..].parse().unwrap_or(1);
+                let text = self.inline(id);
+                if !text.is_empty() {
+                    out.block(format!("{} {}", "#".repeat(level), text));
+                }
+            }
+            "a" => {
+                let text = self.inline(id);
+                let href = element.attribute("href").unwrap_or_default().trim();
+                if text.is_empty() {
+                    return;
+                }
+                if is_web_url(href) || href.starts_with("mailto:") {
+                    out.line.push_str(&format!("[{}]({})", text, href));
+                } else {
+                    out.line.push_str(&text);
+                }
+            }
+            "strong" | "b" => out.line.push_str(&self.emphasis(id, "**")),
+            "em" | "i" => out.line.push_str(&self.emphasis(id, "_")),
+            "ul" | "ol" => {
+                out.flush();
+                let mut items = Vec::new();
+                for &child in &element.children {
+                    let is_item =
+                        matches!(&self.nodes[child], Node::Element(item) if item.name == "li");
+                    if !is_item {
+                        self.render(child, out);
+                        continue;
+                    }
+                    let mut item = Writer::default();
+                    self.render_children(child, &mut item);
+                    let body = item.finish();
+                    if body.is_empty() {
+                        continue;
+                    }
+                    let marker = if element.name == "ol" {
+                        format!("{}.", items.len() + 1)
+                    } else {
+                        "-".to_string()
+                    };
+                    let indent = " ".repeat(marker.len() + 1);
+                    items.push(format!(
+                        "{} {}",
+                        marker,
+                        body.replace('\n', &format!("\n{}", indent))
+                            .replace(&format!("\n{}\n", indent), "\n\n")
+                    ));
+                }
+                if !items.is_empty() {
+                    out.blocks.push(items.join("\n"));
+                }
+            }
+            "blockquote" => {
+                let mut quote = Writer::default();
+                self.render_children(id, &mut quote);
+                let body = quote.finish();
+                if !body.is_empty() {
+                    out.block(
+                        body.lines()
+                            .map(|line| format!("> {}", line).trim_end().to_string())
+                            .collect::<Vec<_>>()
+                            .join("\n"),
+                    );
+                }
+            }
+            "pre" => {
+                let mut raw = String::new();
+                self.raw_text(id, &mut raw);
+                let code = raw.trim_matches('\n');
+                if !code.trim().is_empty() {
+                    out.block(format!("```\n{}\n```", code));
+                }
+            }
+            name if BLOCK_ELEMENTS.contains(&name) => {
+                if self.is_link_list(id) {
+                    return;
+                }
+                out.flush();
+                self.render_children(id, out);
+                out.flush();
+            }
+            _ => self.render_children(id, out),
+        }
+    }
+
+    /// Wrap the element's text in an emphasis marker, keeping the spaces around it outside
+    fn emphasis(&self, id: usize, marker: &str) -> String {
+        let text = self.inline(id);
+        if text.is_empty() {
+            return " ".to_string();
+        }
+        let mut raw = String::new();
+        self.raw_text(id, &mut raw);
+        let space = |present: bool| if present { " " } else { "" };
+        format!(
+            "{}{}{}{}{}",
+            space(raw.starts_with(char::is_whitespace)),
+            marker,
+            text,
+            marker,
+            space(raw.ends_with(char::is_whitespace))
+        )
+    }
+
+    fn raw_text(&self, id: usize, out: &mut String) {
+        match &self.nodes[id] {
+            Node::Text(text) => out.push_str(text),
+            Node::Element(element) if element.name == "br" => out.push('\n'),
+            Node::Element(element) => {
+                for &child in &element.children {
+                    self.raw_text(child, out);
+                }
+            }
+        }
+    }
+}
+
+/// Markdown blocks in order, plus the paragraph being written
+#[derive(Default)]
+struct Writer {
+    blocks: Vec<String>,
+    line: String,
+}
+
+impl Writer {
+    /// End the current paragraph; list boilerplate like "Unsubscribe" is dropped here
+    fn flush(&mut self) {
+        let paragraph = self
+            .line
+            .split('\n')
+            .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join("  \n");
+        self.line.clear();
+        if !paragraph.is_empty() && !is_list_boilerplate(&paragraph) {
+            self.blocks.push(paragraph);
+        }
+    }
+
+    fn block(&mut self, block: String) {
+        self.flush();
+        self.blocks.push(block);
+    }
+
+    fn finish(mut self) -> String {
+        self.flush();
+        self.blocks.join("\n\n")
+    }
+}
+
+fn is_list_boilerplate(paragraph: &str) -> bool {
+    let lower = paragraph.to_lowercase();
+    lower.chars().count() < 300 && LIST_BOILERPLATE.iter().any(|line| lower.contains(line))
+}
+
+fn is_web_url(url: &str) -> bool {
+    url.starts_with("https://") || url.starts_with("http://")
+}
+
+/// Attributes of a start tag, after its name
+fn attributes(mut rest: &str) -> Vec<(String, String)> {
+    let mut found = Vec::new();
+    loop {
+        rest = rest.trim_start_matches(|c: char| c.is_whitespace() || c == '/');
+        let key_end = rest
+            .find(|c: char| c.is_whitespace() || c == '=' || c == '/')
+            .unwrap_or(rest.len());
+        if key_end == 0 {
+            return found;
+        }
+        let key = rest[..key_end].to_ascii_lowercase();
+        rest = rest[key_end..].trim_start();
+        let mut value = String::new();
+        if let Some(after) = rest.strip_prefix('=') {
+            let after = after.trim_start();
+            let (raw, remaining) = match after.chars().next() {
+                Some(quote @ ('"' | '\'')) => {
+                    let body = &after[1..];
+                    let end = body.find(quote).unwrap_or(body.len());
+                    (&body[..end], body.get(end + 1..).unwrap_or(""))
+                }
+                _ => {
+                    let end = after.find(char::is_whitespace).unwrap_or(after.len());
+                    (&after[..end], &after[end..])
+                }
+            };
+            value = decode_entities(raw);
+            rest = remaining;
+        }
+        found.push((key, value));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NEWSLETTER: &str = r#"<html><head><title>Weekly</title><style>td { color: red }</style></head>
+        <body><span style="display: none; max-height: 0">Preview text for the inbox</span>
+        <table><tr><td class="header"><a href="https://example.com/browser">View in browser</a></td></tr>
+        <tr><td><a href="https://example.com">Home</a> | <a href="https://example.com/archive">Archive</a> |
+            <a href="https://example.com/about">About</a></td></tr>
+        <tr><td class="content">
+            <h1>Rust in 2024</h1>
+            <p>The compiler got <b>faster</b> this year, and the &amp; ecosystem
+            kept growing.<br>Read on for highlights.</p>
+            <img src="https://example.com/chart.png" alt="Build times">
+            <ul><li>Async closures</li><li>New <a href="https://blog.example.com/lints">lints</a></li></ul>
+            <blockquote><p>Fearless concurrency, finally fearless.</p></blockquote>
+            <p>That's all this week, and thanks for reading along with us.</p>
+        </td></tr>
+        <tr><td class="footer">You are receiving this because you subscribed.
+            <a href="https://example.com/unsub">Unsubscribe</a></td></tr>
+        </table><img src="https://track.example.com/open.gif" width="1" height="1" alt="pixel">
+        </body></html>"#;
+
+    #[test]
+    fn test_to_markdown() {
+        assert_eq!(
+            to_markdown(NEWSLETTER),
+            "# Rust in 2024\n\n\
+             The compiler got **faster** this year, and the & ecosystem kept growing.  \n\
+             Read on for highlights.\n\n\
+             ![Build times](https://example.com/chart.png)\n\n\
+             - Async closures\n\
+             - New [lints](https://blog.example.com/lints)\n\n\
+             > Fearless concurrency, finally fearless.\n\n\
+             That's all this week, and thanks for reading along with us."
+        );
+        assert_eq!(
+            to_markdown("<ol><li>One</li><li><p>Two</p><p>Still two</p></li></ol>"),
+            "1. One\n2. Two\n\n   Still two"
+        );
+        assert_eq!(to_markdown("<pre>a  b\n c</pre>"), "```\na  b\n c\n```");
+    }
+
+    #[test]
+    fn test_reader_view() {
+        let email: EmailData = serde_json::from_value(serde_json::json!({
+            "id": "a",
+            "subject": "Weekly",
+            "sender": "Rust Digest",
+            "sender_email": "news@digest.example.com",
+            "body": "Plain  first paragraph.\n\nSecond one.\n\n-- \nThe Digest",
+            "received_time": "",
+            "is_unread": true
+        }))
+        .unwrap();
+        let view = reader_view(&email, Some(NEWSLETTER));
+        assert!(view.markdown.starts_with("# Rust in 2024"));
+        assert_eq!(view.reading_minutes, 1);
+
+        let plain = reader_view(&email, None);
+        assert_eq!(plain.markdown, "Plain  first paragraph.\n\nSecond one.");
+        assert_eq!(plain.word_count, 5);
+
+        let config = NewsletterConfig {
+            senders: vec!["@Digest.example.com".to_string(), "a@b.com".to_string()],
+            ..Default::default()
+        };
+        assert!(config.is_listed("news@digest.example.com"));
+        assert!(config.is_listed("A@B.com"));
+        assert!(!config.is_listed("me@example.com"));
+    }
+}
@@ -0,0 +1,240 @@
+// SERINA Recipient Checks
+// Address syntax, likely domain typos (gamil.com) and first-time recipients, checked before a
+// new message goes out
+
+use serde::Serialize;
+use std::collections::HashSet;
+
+/// Longest address SMTP accepts, and its local part
+const MAX_ADDRESS_LEN: usize = 254;
+const MAX_LOCAL_LEN: usize = 64;
+
+/// Mail providers whose misspellings are worth catching
+const COMMON_DOMAINS: &[&str] = &[
+    "aol.com",
+    "gmail.com",
+    "gmx.com",
+    "googlemail.com",
+    "hotmail.com",
+    "icloud.com",
+    "live.com",
+    "me.com",
+    "msn.com",
+    "outlook.com",
+    "proton.me",
+    "protonmail.com",
+    "yahoo.com",
+    "yahoo.com.br",
+    "yahoo.es",
+    "hotmail.es",
+    "uol.com.br",
+    "bol.com.br",
+];
+
+/// Top-level domains typed wrong often enough to correct on their own
+const TLD_TYPOS: &[(&str, &str)] = &[
+    ("con", "com"),
+    ("cmo", "com"),
+    ("ocm", "com"),
+    ("vom", "com"),
+    ("xom", "com"),
+    ("comm", "com"),
+    ("nte", "net"),
+    ("ogr", "org"),
+    ("rog", "org"),
+];
+
+/// What was found about one recipient
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RecipientCheck {
+    pub address: String,
+    /// Why the address can't be sent to; `None` when it's well formed
+    pub error: Option<String>,
+    /// The address with a likely domain typo fixed
+    pub suggestion: Option<String>,
+    /// Not in the contact store; `false` when the store wasn't asked
+    pub new_recipient: bool,
+}
+
+/// The addresses in a To line, separated by `;` or `,`. "Ana <ana@example.com>" yields the
+/// part in angle brackets.
+pub fn split_recipients(to: &str) -> Vec<String> {
+    to.split([';', ','])
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| match (entry.rfind('<'), entry.rfind('>')) {
+            (Some(start), Some(end)) if start < end => entry[start + 1..end].trim().to_string(),
+            _ => entry.to_string(),
+        })
+        .collect()
+}
+
+/// Check an address's syntax: one `@`, a plain local part, and a dotted domain of letters,
+/// digits and hyphens
+pub fn validate_address(address: &str) -> Result<(), String> {
+    let invalid = |reason: &str| Err(format!("{} isn't a valid address: {}", address, reason));
+    if address.len() > MAX_ADDRESS_LEN {
+        return invalid("it's too long");
+    }
+    let Some((local, domain)) = address.rsplit_once('@') else {
+        return invalid("it has no @");
+    };
+    if local.is_empty() || local.len() > MAX_LOCAL_LEN {
+        return invalid("the part before @ is empty or too long");
+    }
+    if local.starts_with('.') || local.ends_with('.') || local.contains("..") {
+        return invalid("dots can't start, end or repeat before @");
+    }
+    if !local
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || "!#$%&'*+-/=?^_`{|}~.".contains(c))
+    {
+        return invalid("the part before @ has characters that aren't allowed");
+    }
+    let labels: Vec<&str> = domain.split('.').collect();
+    if labels.len() < 2 {
+        return invalid("the domain has no dot");
+    }
+    let well_formed = labels.iter().all(|label| {
+        !label.is_empty()
+            && label.len() <= 63
+            && !label.starts_with('-')
+            && !label.ends_with('-')
+            && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+    });
+    if !well_formed {
+        return invalid("the domain isn't well formed");
+    }
+    let tld = labels[labels.len() - 1];
+    if tld.len() < 2 || !tld.chars().all(|c| c.is_ascii_alphabetic()) {
+        return invalid("the domain doesn't end in a real top-level domain");
+    }
+    Ok(())
+}
+
+/// Edit distance counting a swap of neighbouring letters as one edit
+fn distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in rows[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut best = (rows[i - 1][j] + 1)
+                .min(rows[i][j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                best = best.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = best;
+        }
+    }
+    rows[a.len()][b.len()]
+}
+
+/// The domain that was probably meant, when `domain` looks like a typo of a common one
+pub fn suggest_domain(domain: &str) -> Option<String> {
+    let domain = domain.to_ascii_lowercase();
+    if COMMON_DOMAINS.contains(&domain.as_str()) {
+        return None;
+    }
+    let closest = COMMON_DOMAINS
+        .iter()
+        .map(|common| (distance(&domain, common), *common))
+        .min_by_key(|(edits, _)| *edits);
+    if let Some((edits, common)) = closest {
+        // Short names are too close to each other to guess two edits away
+        let allowed = if common.len() <= 8 { 1 } else { 2 };
+        if edits <= allowed {
+            return Some(common.to_string());
+        }
+    }
+    let (name, tld) = domain.rsplit_once('.')?;
+    TLD_TYPOS
+        .iter()
+        .find(|(typo, _)| *typo == tld)
+        .map(|(_, fixed)| format!("{}.{}", name, fixed))
+}
+
+/// Check every recipient on a To line. `known` holds the lower-cased addresses in the contact
+/// store; without it nobody is flagged as new.
+pub fn check_recipients(to: &str, known: Option<&HashSet<String>>) -> Vec<RecipientCheck> {
+    split_recipients(to)
+        .into_iter()
+        .map(|address| {
+            let error = validate_address(&address).err();
+            let suggestion = match (&error, address.rsplit_once('@')) {
+                (None, Some((local, domain))) => {
+                    suggest_domain(domain).map(|domain| format!("{}@{}", local, domain))
+                }
+                _ => None,
+            };
+            let new_recipient = error.is_none()
+                && known.is_some_and(|known| !known.contains(&address.to_lowercase()));
+            RecipientCheck {
+                address,
+                error,
+                suggestion,
+                new_recipient,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_address() {
+        assert!(validate_address("ana.silva+news@example.com.br").is_ok());
+        assert!(validate_address("ana@example").is_err());
+        assert!(validate_address("ana.example.com").is_err());
+        assert!(validate_address("ana..silva@example.com").is_err());
+        assert!(validate_address("ana@-example.com").is_err());
+        assert!(validate_address("ana silva@example.com").is_err());
+        assert!(validate_address("ana@example.c0m").is_err());
+    }
+
+    #[test]
+    fn test_suggest_domain() {
+        assert_eq!(suggest_domain("gamil.com"), Some("gmail.com".to_string()));
+        assert_eq!(
+            suggest_domain("hotmial.com"),
+            Some("hotmail.com".to_string())
+        );
+        assert_eq!(
+            suggest_domain("outlok.com"),
+            Some("outlook.com".to_string())
+        );
+        assert_eq!(
+            suggest_domain("example.con"),
+            Some("example.com".to_string())
+        );
+        assert_eq!(suggest_domain("gmail.com"), None);
+        assert_eq!(suggest_domain("example.com"), None);
+        assert_eq!(suggest_domain("acme.com"), None);
+    }
+
+    #[test]
+    fn test_check_recipients() {
+        let known: HashSet<String> = ["ana@example.com".to_string()].into();
+        let checks = check_recipients(
+            "Ana <Ana@example.com>; bob@gamil.com, not-an-address",
+            Some(&known),
+        );
+        assert_eq!(checks.len(), 3);
+        assert_eq!(checks[0].address, "Ana@example.com");
+        assert!(checks[0].error.is_none() && !checks[0].new_recipient);
+        assert_eq!(checks[1].suggestion.as_deref(), Some("bob@gmail.com"));
+        assert!(checks[1].new_recipient);
+        assert!(checks[2].error.is_some() && !checks[2].new_recipient);
+        assert!(!check_recipients("bob@example.org", None)[0].new_recipient);
+    }
+}
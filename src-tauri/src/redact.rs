@@ -0,0 +1,174 @@
+// SERINA PII Redaction
+// Masks personal data in email content before it is sent to cloud LLM providers
+
+use crate::utils::RedactionConfig;
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+
+const EMAIL_PATTERN: &str = r"[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}";
+const IBAN_PATTERN: &str = r"\b[A-Z]{2}\d{2}(?:\s?[A-Z0-9]{4}){2,7}(?:\s?[A-Z0-9]{1,4})?\b";
+const ACCOUNT_PATTERN: &str = r"\b\d(?:[ -]?\d){11,18}\b";
+const PHONE_PATTERN: &str =
+    r"(?:\+\d{1,3}[\s.-]?)?(?:\(\d{2,4}\)[\s.-]?)?\b\d{3,4}[\s.-]\d{3,4}(?:[\s.-]\d{2,4})?\b";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RedactionKind {
+    Email,
+    Phone,
+    AccountNumber,
+    Name,
+    Custom,
+}
+
+impl RedactionKind {
+    fn label(self) -> &'static str {
+        match self {
+            RedactionKind::Email => "EMAIL",
+            RedactionKind::Phone => "PHONE",
+            RedactionKind::AccountNumber => "ACCOUNT",
+            RedactionKind::Name => "NAME",
+            RedactionKind::Custom => "REDACTED",
+        }
+    }
+}
+
+/// One redacted value and the placeholder that replaced it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Redaction {
+    pub kind: RedactionKind,
+    pub placeholder: String,
+    pub original: String,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Redacted {
+    pub text: String,
+    pub redactions: Vec<Redaction>,
+}
+
+/// Replace personal data in `text` with stable placeholders like `[EMAIL_1]`
+pub fn redact(text: &str, config: &RedactionConfig) -> Result<Redacted, String> {
+    let mut passes: Vec<(RedactionKind, Regex)> = Vec::new();
+
+    if config.emails {
+        passes.push((RedactionKind::Email, compile(EMAIL_PATTERN, false)?));
+    }
+    for pattern in &config.patterns {
+        passes.push((RedactionKind::Custom, compile(pattern, false)?));
+    }
+    for name in config.names.iter().filter(|n| !n.trim().is_empty()) {
+        let pattern = format!(r"\b{}\b", regex::escape(name.trim()));
+        passes.push((RedactionKind::Name, compile(&pattern, true)?));
+    }
+    if config.account_numbers {
+        passes.push((RedactionKind::AccountNumber, compile(IBAN_PATTERN, false)?));
+        passes.push((
+            RedactionKind::AccountNumber,
+            compile(ACCOUNT_PATTERN, false)?,
+        ));
+    }
+    if config.phones {
+        passes.push((RedactionKind::Phone, compile(PHONE_PATTERN, false)?));
+    }
+
+    let mut result = Redacted {
+        text: text.to_string(),
+        redactions: Vec::new(),
+    };
+    for (kind, regex) in passes {
+        let mut output = String::with_capacity(result.text.len());
+        let mut last = 0;
+        for found in regex.find_iter(&result.text) {
+            output.push_str(&result.text[last..found.start()]);
+            output.push_str(&placeholder_for(
+                &mut result.redactions,
+                kind,
+                found.as_str(),
+            ));
+            last = found.end();
+        }
+        output.push_str(&result.text[last..]);
+        result.text = output;
+    }
+    Ok(result)
+}
+
+/// Put the original values back into LLM output that echoes placeholders
+pub fn restore(text: &str, redactions: &[Redaction]) -> String {
+    redactions.iter().fold(text.to_string(), |acc, r| {
+        acc.replace(&r.placeholder, &r.original)
+    })
+}
+
+fn compile(pattern: &str, case_insensitive: bool) -> Result<Regex, String> {
+    RegexBuilder::new(pattern)
+        .case_insensitive(case_insensitive)
+        .build()
+        .map_err(|e| format!("Invalid redaction pattern {:?}: {}", pattern, e))
+}
+
+fn placeholder_for(redactions: &mut Vec<Redaction>, kind: RedactionKind, original: &str) -> String {
+    if let Some(existing) = redactions
+        .iter()
+        .find(|r| r.kind == kind && r.original == original)
+    {
+        return existing.placeholder.clone();
+    }
+    let index = redactions.iter().filter(|r| r.kind == kind).count() + 1;
+    let placeholder = format!("[{}_{}]", kind.label(), index);
+    redactions.push(Redaction {
+        kind,
+        placeholder: placeholder.clone(),
+        original: original.to_string(),
+    });
+    placeholder
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> RedactionConfig {
+        RedactionConfig {
+            enabled: true,
+            names: vec!["Jane Doe".to_string()],
+            patterns: vec![r"ORD-\d+".to_string()],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_redact_and_restore() {
+        let text = "Hi, this is jane doe (jane@example.com, +1 555-123-4567). \
+                    Card 4111 1111 1111 1111, order ORD-9921. Write to jane@example.com.";
+        let redacted = redact(text, &config()).unwrap();
+
+        assert!(!redacted.text.contains("jane@example.com"));
+        assert!(!redacted.text.contains("4111"));
+        assert!(!redacted.text.contains("555-123"));
+        assert!(!redacted.text.contains("ORD-9921"));
+        assert!(redacted.text.contains("[NAME_1]"));
+        // Repeated values share a placeholder
+        assert_eq!(redacted.text.matches("[EMAIL_1]").count(), 2);
+        assert_eq!(
+            redacted
+                .redactions
+                .iter()
+                .filter(|r| r.kind == RedactionKind::Email)
+                .count(),
+            1
+        );
+
+        assert_eq!(restore(&redacted.text, &redacted.redactions), text);
+    }
+
+    #[test]
+    fn test_invalid_pattern() {
+        let config = RedactionConfig {
+            patterns: vec!["(".to_string()],
+            ..Default::default()
+        };
+        assert!(redact("text", &config).is_err());
+    }
+}
@@ -0,0 +1,118 @@
+// SERINA Reminder Escalation
+// Repeats unread reminders only while the user is at the computer and ignoring them
+
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::Duration;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReminderConfig {
+    pub default_snooze_minutes: u32,
+    pub snooze_options: Vec<u32>,
+    /// Active (not idle) minutes with unread mail before the next reminder
+    pub escalate_after_active_minutes: u32,
+    /// No input for this long counts as away from the desk
+    pub idle_threshold_minutes: u32,
+    /// Reminders per batch of unread mail, including the first
+    pub max_reminders: u32,
+    /// How long before a deadline found in an email its reminder goes off
+    pub deadline_lead_minutes: u32,
+}
+
+impl Default for ReminderConfig {
+    fn default() -> Self {
+        Self {
+            default_snooze_minutes: 60,
+            snooze_options: vec![15, 30, 60, 120, 240],
+            escalate_after_active_minutes: 30,
+            idle_threshold_minutes: 5,
+            max_reminders: 3,
+            deadline_lead_minutes: 60,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Escalation {
+    unread: u32,
+    /// Reminders shown for the current unread mail
+    shown: u32,
+    active_since_last: Duration,
+}
+
+/// Decides when an unread reminder is due, counting only time the user was active
+#[derive(Default)]
+pub struct ReminderEscalation {
+    state: Mutex<Escalation>,
+}
+
+impl ReminderEscalation {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Advance by `elapsed`. `idle` is time since the last keyboard or mouse input, `None` when
+    /// the OS can't tell (treated as active). Returns the reminder number when one is due.
+    pub fn tick(
+        &self,
+        config: &ReminderConfig,
+        unread: u32,
+        idle: Option<Duration>,
+        elapsed: Duration,
+    ) -> Option<u32> {
+        let mut state = self.state.lock().unwrap();
+        if unread == 0 {
+            *state = Escalation::default();
+            return None;
+        }
+        // New mail starts a fresh round of reminders
+        if unread > state.unread {
+            state.shown = 0;
+            state.active_since_last = Duration::ZERO;
+        }
+        state.unread = unread;
+
+        let away = Duration::from_secs(config.idle_threshold_minutes as u64 * 60);
+        if idle.is_some_and(|idle| idle >= away) || state.shown >= config.max_reminders {
+            return None;
+        }
+        state.active_since_last += elapsed;
+        let every = Duration::from_secs(config.escalate_after_active_minutes as u64 * 60);
+        if state.shown == 0 || state.active_since_last >= every {
+            state.shown += 1;
+            state.active_since_last = Duration::ZERO;
+            return Some(state.shown);
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escalates_only_while_active() {
+        let config = ReminderConfig::default();
+        let escalation = ReminderEscalation::new();
+        let minute = Duration::from_secs(60);
+        let active = Some(Duration::from_secs(10));
+        let away = Some(Duration::from_secs(20 * 60));
+
+        assert_eq!(escalation.tick(&config, 2, active, minute), Some(1));
+        // An hour away from the desk doesn't count
+        for _ in 0..60 {
+            assert_eq!(escalation.tick(&config, 2, away, minute), None);
+        }
+        for _ in 0..29 {
+            assert_eq!(escalation.tick(&config, 2, active, minute), None);
+        }
+        assert_eq!(escalation.tick(&config, 2, active, minute), Some(2));
+
+        // New mail starts over; reading everything resets
+        assert_eq!(escalation.tick(&config, 3, active, minute), Some(1));
+        assert_eq!(escalation.tick(&config, 0, active, minute), None);
+        assert_eq!(escalation.tick(&config, 1, None, minute), Some(1));
+    }
+}
@@ -0,0 +1,193 @@
+// SERINA Retention
+// Aging policies that archive or delete old mail and purge stale cache entries
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RetentionAction {
+    Archive,
+    Delete,
+}
+
+/// One aging rule, e.g. "archive read newsletters after 30 days"
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetentionPolicy {
+    pub name: String,
+    pub action: RetentionAction,
+    pub older_than_days: u32,
+    /// Leave unread mail alone
+    pub read_only: bool,
+    /// Only mail carrying a List-Unsubscribe header
+    pub newsletters_only: bool,
+    /// When non-empty, only senders containing one of these
+    pub senders: Vec<String>,
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            action: RetentionAction::Archive,
+            older_than_days: 30,
+            read_only: true,
+            newsletters_only: false,
+            senders: Vec::new(),
+        }
+    }
+}
+
+impl RetentionPolicy {
+    pub fn matches(&self, email: &AgingEmail) -> bool {
+        let sender = email.sender_email.to_lowercase();
+        email.age_days >= self.older_than_days
+            && (!self.read_only || !email.is_unread)
+            && (!self.newsletters_only || email.is_newsletter)
+            && (self.senders.is_empty()
+                || self
+                    .senders
+                    .iter()
+                    .any(|s| sender.contains(&s.to_lowercase())))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetentionConfig {
+    /// Run policies from the daily maintenance task
+    pub enabled: bool,
+    pub policies: Vec<RetentionPolicy>,
+    /// Drop local cache entries older than this many months (0 = keep forever)
+    pub purge_cache_after_months: u32,
+    /// Drop audit log and activity feed entries older than this many days (0 = keep forever)
+    pub purge_logs_after_days: u32,
+}
+
+impl Default for RetentionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            policies: vec![RetentionPolicy {
+                name: "Archive read newsletters".to_string(),
+                newsletters_only: true,
+                ..Default::default()
+            }],
+            purge_cache_after_months: 6,
+            purge_logs_after_days: 0,
+        }
+    }
+}
+
+impl RetentionConfig {
+    /// Smallest age any policy acts on, used to narrow the backend query
+    pub fn min_age_days(&self) -> Option<u32> {
+        self.policies.iter().map(|p| p.older_than_days).min()
+    }
+}
+
+/// An inbox message old enough to be considered by retention policies
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AgingEmail {
+    pub id: String,
+    pub subject: String,
+    pub sender: String,
+    pub sender_email: String,
+    pub age_days: u32,
+    pub is_unread: bool,
+    pub is_newsletter: bool,
+}
+
+/// What a policy would do to one email
+#[derive(Debug, Clone, Serialize)]
+pub struct RetentionItem {
+    pub policy: String,
+    pub action: RetentionAction,
+    pub email: AgingEmail,
+}
+
+/// Everything a maintenance run would touch, for the dry-run preview
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RetentionPreview {
+    pub emails: Vec<RetentionItem>,
+    pub cache_entries: usize,
+    /// Log entries dropped; only known once a run has happened
+    pub log_entries: usize,
+}
+
+impl RetentionPreview {
+    /// Ids of the emails the run would delete
+    pub fn deletions(&self) -> Vec<String> {
+        self.emails
+            .iter()
+            .filter(|item| item.action == RetentionAction::Delete)
+            .map(|item| item.email.id.clone())
+            .collect()
+    }
+}
+
+/// Match each email against the policies in order; the first match wins
+pub fn plan(config: &RetentionConfig, emails: Vec<AgingEmail>) -> Vec<RetentionItem> {
+    emails
+        .into_iter()
+        .filter_map(|email| {
+            let policy = config.policies.iter().find(|p| p.matches(&email))?;
+            Some(RetentionItem {
+                policy: policy.name.clone(),
+                action: policy.action,
+                email,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn aging(id: &str, age_days: u32, is_unread: bool, is_newsletter: bool) -> AgingEmail {
+        AgingEmail {
+            id: id.to_string(),
+            sender_email: "news@shop.example".to_string(),
+            age_days,
+            is_unread,
+            is_newsletter,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_plan() {
+        let mut config = RetentionConfig::default();
+        config.policies.push(RetentionPolicy {
+            name: "Delete old shop mail".to_string(),
+            action: RetentionAction::Delete,
+            older_than_days: 90,
+            senders: vec!["@shop.example".to_string()],
+            ..Default::default()
+        });
+
+        let items = plan(
+            &config,
+            vec![
+                aging("newsletter", 40, false, true),
+                aging("unread", 40, true, true),
+                aging("young", 10, false, true),
+                aging("old", 120, false, false),
+            ],
+        );
+        let planned: Vec<(&str, RetentionAction)> = items
+            .iter()
+            .map(|i| (i.email.id.as_str(), i.action))
+            .collect();
+        assert_eq!(
+            planned,
+            [
+                ("newsletter", RetentionAction::Archive),
+                ("old", RetentionAction::Delete)
+            ]
+        );
+        assert_eq!(config.min_age_days(), Some(30));
+    }
+}
@@ -0,0 +1,271 @@
+// SERINA Reply Retry
+// Replies that failed to send, kept on disk with their attachments and retried with backoff
+
+use crate::attachments::safe_file_name;
+use crate::outbox::StagedAttachment;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Automatic attempts after the first failure; after these the reply waits for the user
+pub const MAX_RETRIES: u32 = 5;
+/// Longest wait between automatic attempts
+const MAX_BACKOFF_MINUTES: i64 = 30;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FailedReply {
+    pub id: u64,
+    /// The email being replied to
+    pub email_id: String,
+    pub reply_text: String,
+    /// Sent to everyone on the original rather than just the sender
+    #[serde(default)]
+    pub reply_all: bool,
+    /// Copies owned by the queue, removed with the reply
+    pub attachments: Vec<PathBuf>,
+    /// Failed sends so far, including the first
+    pub attempts: u32,
+    pub last_error: String,
+    pub failed_at: DateTime<Utc>,
+    /// Next automatic attempt; `None` once retries are used up or the error won't go away
+    pub retry_at: Option<DateTime<Utc>>,
+}
+
+/// Whether trying again later could help. Client errors such as a deleted original (404) or a
+/// rejected request won't fix themselves; everything else (backend down, Outlook or SMTP
+/// errors, rate limits, quitting mid-send) might.
+pub fn is_transient(error: &str) -> bool {
+    let status = error
+        .strip_prefix("HTTP ")
+        .and_then(|rest| rest.get(..3))
+        .and_then(|code| code.parse::<u16>().ok());
+    match status {
+        Some(408 | 429) => true,
+        Some(code) => !(400..500).contains(&code),
+        None => true,
+    }
+}
+
+/// When to try again after `attempts` failures: 1, 2, 4... minutes, capped at half an hour
+fn next_attempt(attempts: u32, error: &str, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    if attempts > MAX_RETRIES || !is_transient(error) {
+        return None;
+    }
+    let minutes = 1i64
+        .checked_shl(attempts.saturating_sub(1))
+        .unwrap_or(MAX_BACKOFF_MINUTES)
+        .min(MAX_BACKOFF_MINUTES);
+    Some(now + Duration::minutes(minutes))
+}
+
+/// Move a file, copying when it crosses filesystems
+fn move_file(source: &Path, dest: &Path) -> Result<(), String> {
+    if let Some(dir) = dest.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    if std::fs::rename(source, dest).is_ok() {
+        return Ok(());
+    }
+    std::fs::copy(source, dest).map_err(|e| e.to_string())?;
+    let _ = std::fs::remove_file(source);
+    Ok(())
+}
+
+/// Failed replies, one per email, saved on every change so they survive a restart
+pub struct RetryQueue {
+    path: Option<PathBuf>,
+    /// Where attachments of failed replies are kept
+    dir: PathBuf,
+    replies: Mutex<Vec<FailedReply>>,
+}
+
+impl RetryQueue {
+    pub fn load(path: PathBuf, dir: PathBuf) -> Self {
+        let replies = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+        Self {
+            path: Some(path),
+            dir,
+            replies: Mutex::new(replies),
+        }
+    }
+
+    pub fn in_memory(dir: PathBuf) -> Self {
+        Self {
+            path: None,
+            dir,
+            replies: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Oldest failure first
+    pub fn list(&self) -> Vec<FailedReply> {
+        self.replies.lock().unwrap().clone()
+    }
+
+    pub fn get(&self, email_id: &str) -> Option<FailedReply> {
+        self.replies
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|reply| reply.email_id == email_id)
+            .cloned()
+    }
+
+    /// Keep a reply whose first send failed, moving its staged attachments out of the outbox.
+    /// A reply already waiting for the same email is replaced.
+    pub fn add(
+        &self,
+        email_id: &str,
+        reply_text: &str,
+        reply_all: bool,
+        staged: &[StagedAttachment],
+        error: &str,
+        now: DateTime<Utc>,
+    ) -> Result<FailedReply, String> {
+        self.remove(email_id)?;
+        let mut replies = self.replies.lock().unwrap();
+        let id = replies.iter().map(|reply| reply.id).max().unwrap_or(0) + 1;
+        let mut attachments = Vec::new();
+        for file in staged {
+            let dest = self
+                .dir
+                .join(id.to_string())
+                .join(safe_file_name(&file.name));
+            move_file(&file.path, &dest)?;
+            attachments.push(dest);
+        }
+        let reply = FailedReply {
+            id,
+            email_id: email_id.to_string(),
+            reply_text: reply_text.to_string(),
+            reply_all,
+            attachments,
+            attempts: 1,
+            last_error: error.to_string(),
+            failed_at: now,
+            retry_at: next_attempt(1, error, now),
+        };
+        replies.push(reply.clone());
+        self.persist(&replies)?;
+        Ok(reply)
+    }
+
+    /// Count another failed attempt and schedule the next one
+    pub fn record_failure(
+        &self,
+        email_id: &str,
+        error: &str,
+        now: DateTime<Utc>,
+    ) -> Result<Option<FailedReply>, String> {
+        let mut replies = self.replies.lock().unwrap();
+        let Some(reply) = replies.iter_mut().find(|reply| reply.email_id == email_id) else {
+            return Ok(None);
+        };
+        reply.attempts += 1;
+        reply.last_error = error.to_string();
+        reply.failed_at = now;
+        reply.retry_at = next_attempt(reply.attempts, error, now);
+        let reply = reply.clone();
+        self.persist(&replies)?;
+        Ok(Some(reply))
+    }
+
+    /// Replies whose next automatic attempt has come, including ones missed while SERINA was
+    /// closed
+    pub fn due(&self, now: DateTime<Utc>) -> Vec<FailedReply> {
+        self.replies
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|reply| reply.retry_at.is_some_and(|at| at <= now))
+            .cloned()
+            .collect()
+    }
+
+    /// Take a reply off the queue, deleting its attachment copies. Callers that still need the
+    /// files copy them first.
+    pub fn remove(&self, email_id: &str) -> Result<Option<FailedReply>, String> {
+        let mut replies = self.replies.lock().unwrap();
+        let Some(index) = replies.iter().position(|reply| reply.email_id == email_id) else {
+            return Ok(None);
+        };
+        let reply = replies.remove(index);
+        let _ = std::fs::remove_dir_all(self.dir.join(reply.id.to_string()));
+        self.persist(&replies)?;
+        Ok(Some(reply))
+    }
+
+    fn persist(&self, replies: &[FailedReply]) -> Result<(), String> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let contents = serde_json::to_string_pretty(replies).map_err(|e| e.to_string())?;
+        std::fs::write(path, contents).map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_is_transient() {
+        assert!(is_transient("error sending request: connection refused"));
+        assert!(is_transient(
+            "HTTP 500 Internal Server Error: 500 Internal Server Error"
+        ));
+        assert!(is_transient(
+            "HTTP 429 Too Many Requests: 429 Too Many Requests"
+        ));
+        assert!(!is_transient("HTTP 404 Not Found: 404 Not Found"));
+    }
+
+    #[test]
+    fn test_retry_queue() {
+        let root = std::env::temp_dir().join(format!("serina-retry-{}", std::process::id()));
+        let staged_path = root.join("outbox").join("1").join("notes.txt");
+        std::fs::create_dir_all(staged_path.parent().unwrap()).unwrap();
+        std::fs::write(&staged_path, b"notes").unwrap();
+        let staged = StagedAttachment {
+            id: 1,
+            name: "notes.txt".to_string(),
+            size: 5,
+            path: staged_path.clone(),
+        };
+        let path = root.join("failed.json");
+        let queue = RetryQueue::load(path.clone(), root.join("failed"));
+        let now = Utc.with_ymd_and_hms(2024, 5, 14, 9, 0, 0).unwrap();
+
+        let reply = queue
+            .add("a", "Thanks!", false, &[staged], "HTTP 500: 500", now)
+            .unwrap();
+        assert!(!staged_path.exists());
+        assert_eq!(std::fs::read(&reply.attachments[0]).unwrap(), b"notes");
+        assert_eq!(reply.retry_at, Some(now + Duration::minutes(1)));
+        assert!(queue.due(now).is_empty());
+        assert_eq!(queue.due(now + Duration::minutes(1)).len(), 1);
+
+        let mut later = now;
+        for _ in 0..MAX_RETRIES {
+            later += Duration::hours(1);
+            queue.record_failure("a", "HTTP 500: 500", later).unwrap();
+        }
+        let reply = queue.get("a").unwrap();
+        assert_eq!(reply.attempts, MAX_RETRIES + 1);
+        assert_eq!(reply.retry_at, None);
+        assert_eq!(
+            RetryQueue::load(path, root.join("failed")).list()[0].reply_text,
+            "Thanks!"
+        );
+
+        queue.remove("a").unwrap();
+        assert!(!reply.attachments[0].exists());
+        assert!(queue.list().is_empty());
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,828 @@
+// SERINA Rules
+// Automations built from a trigger, a tree of conditions and a list of actions
+
+use crate::parsing;
+use crate::teamsync;
+use crate::utils::EmailData;
+use regex::RegexBuilder;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// Most cached emails a manual run looks at
+pub const MAX_SCANNED: usize = 200;
+/// Deepest nesting of all/any/not groups a rule may use
+const MAX_DEPTH: usize = 8;
+
+/// When a rule runs
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Trigger {
+    /// Each new email, as the scheduler finds it
+    #[default]
+    NewEmail,
+    /// Only when run by hand, over the cached inbox
+    Manual,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Field {
+    /// Sender name or address
+    Sender,
+    Subject,
+    Body,
+    /// Address of the account the email arrived in
+    Account,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TextOp {
+    Contains,
+    Equals,
+    StartsWith,
+    EndsWith,
+    /// Regular expression
+    Matches,
+}
+
+/// A test on an email. Groups nest, so any boolean combination can be built.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Condition {
+    /// Every condition holds; an empty group matches every email
+    All {
+        conditions: Vec<Condition>,
+    },
+    /// At least one condition holds
+    Any {
+        conditions: Vec<Condition>,
+    },
+    Not {
+        condition: Box<Condition>,
+    },
+    /// Case-insensitive text test on one field
+    Text {
+        field: Field,
+        op: TextOp,
+        value: String,
+    },
+    Unread,
+    HasDeadline,
+}
+
+impl Default for Condition {
+    fn default() -> Self {
+        Condition::All {
+            conditions: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RuleAction {
+    MarkRead,
+    Archive,
+    Delete,
+    Snooze {
+        minutes: u32,
+    },
+    /// An Outlook task titled after the subject
+    CreateTask,
+    /// A desktop notification; an empty message uses the rule's name
+    Notify {
+        message: String,
+    },
+    /// Add an Outlook category
+    Label {
+        name: String,
+    },
+    /// POST JSON to a URL; without a payload, the email's id, subject, sender and received time
+    Webhook {
+        url: String,
+        #[serde(default)]
+        payload: Option<serde_json::Value>,
+    },
+    /// Save a reply rendered from a template as an Outlook draft, for the user to review and send
+    DraftTemplate {
+        template_id: String,
+    },
+}
+
+impl RuleAction {
+    /// Takes the email out of the inbox
+    fn moves(&self) -> bool {
+        matches!(self, RuleAction::Archive | RuleAction::Delete)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Rule {
+    pub id: String,
+    pub name: String,
+    pub enabled: bool,
+    pub trigger: Trigger,
+    pub condition: Condition,
+    pub actions: Vec<RuleAction>,
+    /// Accounts or shared mailboxes the rule runs on; empty runs it everywhere
+    pub mailboxes: Vec<String>,
+    /// Pulled from the team's shared rules rather than created here
+    pub team: bool,
+}
+
+impl Default for Rule {
+    fn default() -> Self {
+        Self {
+            id: String::new(),
+            name: String::new(),
+            enabled: true,
+            trigger: Trigger::default(),
+            condition: Condition::default(),
+            actions: Vec::new(),
+            mailboxes: Vec::new(),
+            team: false,
+        }
+    }
+}
+
+impl Rule {
+    pub fn in_scope(&self, account: &str) -> bool {
+        self.mailboxes.is_empty()
+            || self
+                .mailboxes
+                .iter()
+                .any(|mailbox| mailbox.trim().eq_ignore_ascii_case(account))
+    }
+
+    /// Whether the rule should act on the email: it's in scope and meets the condition
+    pub fn applies_to(&self, email: &EmailData) -> bool {
+        self.in_scope(&email.account) && evaluate(&self.condition, email)
+    }
+}
+
+/// A problem with one part of a rule, located by a path such as `condition.conditions[1].value`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RuleIssue {
+    pub path: String,
+    pub message: String,
+}
+
+impl RuleIssue {
+    fn new(path: &str, message: &str) -> Self {
+        Self {
+            path: path.to_string(),
+            message: message.to_string(),
+        }
+    }
+}
+
+/// Everything wrong with a rule; empty when it can be saved
+pub fn validate(rule: &Rule) -> Vec<RuleIssue> {
+    let mut issues = Vec::new();
+    if rule.name.trim().is_empty() {
+        issues.push(RuleIssue::new("name", "Give the rule a name"));
+    }
+    validate_condition(&rule.condition, "condition", 1, &mut issues);
+    if rule.actions.is_empty() {
+        issues.push(RuleIssue::new("actions", "Add at least one action"));
+    }
+    let mut moved = false;
+    for (index, action) in rule.actions.iter().enumerate() {
+        let path = format!("actions[{}]", index);
+        if moved {
+            issues.push(RuleIssue::new(
+                &path,
+                "The email has already left the inbox; put archive or delete last",
+            ));
+        }
+        if let Some(issue) = action_issue(action, &path) {
+            issues.push(issue);
+        }
+        moved |= action.moves();
+    }
+    issues
+}
+
+/// What's wrong with one action's settings, if anything
+pub fn action_issue(action: &RuleAction, path: &str) -> Option<RuleIssue> {
+    let (field, message) = match action {
+        RuleAction::Snooze { minutes: 0 } => ("minutes", "Snooze for at least a minute"),
+        RuleAction::Label { name } if name.trim().is_empty() => ("name", "Name the category"),
+        RuleAction::DraftTemplate { template_id } if template_id.trim().is_empty() => {
+            ("template_id", "Pick a template")
+        }
+        RuleAction::Webhook { url, .. }
+            if !url::Url::parse(url.trim())
+                .is_ok_and(|url| matches!(url.scheme(), "http" | "https")) =>
+        {
+            ("url", "Enter an http:// or https:// address")
+        }
+        _ => return None,
+    };
+    Some(RuleIssue::new(&format!("{}.{}", path, field), message))
+}
+
+fn validate_condition(
+    condition: &Condition,
+    path: &str,
+    depth: usize,
+    issues: &mut Vec<RuleIssue>,
+) {
+    if depth > MAX_DEPTH {
+        issues.push(RuleIssue::new(path, "Too many nested groups"));
+        return;
+    }
+    match condition {
+        Condition::All { conditions } | Condition::Any { conditions } => {
+            if conditions.is_empty() && matches!(condition, Condition::Any { .. }) {
+                issues.push(RuleIssue::new(
+                    &format!("{}.conditions", path),
+                    "Add at least one condition",
+                ));
+            }
+            for (index, child) in conditions.iter().enumerate() {
+                let child_path = format!("{}.conditions[{}]", path, index);
+                validate_condition(child, &child_path, depth + 1, issues);
+            }
+        }
+        Condition::Not { condition } => {
+            validate_condition(condition, &format!("{}.condition", path), depth + 1, issues);
+        }
+        Condition::Text { op, value, .. } => {
+            let value_path = format!("{}.value", path);
+            if value.trim().is_empty() {
+                issues.push(RuleIssue::new(&value_path, "Enter the text to look for"));
+            } else if *op == TextOp::Matches {
+                if let Err(e) = RegexBuilder::new(value).build() {
+                    issues.push(RuleIssue::new(
+                        &value_path,
+                        &format!("Invalid pattern: {}", e),
+                    ));
+                }
+            }
+        }
+        Condition::Unread | Condition::HasDeadline => {}
+    }
+}
+
+/// Whether one condition held, for highlighting the editor during a dry run
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TraceStep {
+    pub path: String,
+    pub matched: bool,
+}
+
+/// Whether the email meets the condition
+pub fn evaluate(condition: &Condition, email: &EmailData) -> bool {
+    check(condition, email, "condition", &mut Vec::new())
+}
+
+/// Evaluate every condition in the tree, without short-circuiting, so each gets a result
+fn check(condition: &Condition, email: &EmailData, path: &str, trace: &mut Vec<TraceStep>) -> bool {
+    let mut children = |conditions: &[Condition]| -> Vec<bool> {
+        conditions
+            .iter()
+            .enumerate()
+            .map(|(index, child)| {
+                let child_path = format!("{}.conditions[{}]", path, index);
+                check(child, email, &child_path, trace)
+            })
+            .collect()
+    };
+    let matched = match condition {
+        Condition::All { conditions } => children(conditions).into_iter().all(|matched| matched),
+        Condition::Any { conditions } => children(conditions).into_iter().any(|matched| matched),
+        Condition::Not { condition } => {
+            !check(condition, email, &format!("{}.condition", path), trace)
+        }
+        Condition::Text { field, op, value } => match field {
+            Field::Sender => {
+                text_matches(*op, value, &email.sender)
+                    || text_matches(*op, value, &email.sender_email)
+            }
+            Field::Subject => text_matches(*op, value, &email.subject),
+            Field::Body => text_matches(*op, value, &parsing::plain_text(&email.body)),
+            Field::Account => text_matches(*op, value, &email.account),
+        },
+        Condition::Unread => email.is_unread,
+        Condition::HasDeadline => email.deadline.is_some(),
+    };
+    trace.push(TraceStep {
+        path: path.to_string(),
+        matched,
+    });
+    matched
+}
+
+fn text_matches(op: TextOp, value: &str, text: &str) -> bool {
+    let lower = |text: &str| text.trim().to_lowercase();
+    match op {
+        TextOp::Contains => lower(text).contains(&lower(value)),
+        TextOp::Equals => lower(text) == lower(value),
+        TextOp::StartsWith => lower(text).starts_with(&lower(value)),
+        TextOp::EndsWith => lower(text).ends_with(&lower(value)),
+        // Lowercasing a pattern would turn \D into \d, so it ignores case instead. Invalid
+        // patterns never match; validation reports them.
+        TextOp::Matches => RegexBuilder::new(value.trim())
+            .case_insensitive(true)
+            .build()
+            .is_ok_and(|pattern| pattern.is_match(text)),
+    }
+}
+
+/// What a rule would do to an email, without doing it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Simulation {
+    pub matched: bool,
+    /// Every condition's result, children before their group
+    pub trace: Vec<TraceStep>,
+    /// The actions that would run; none when the rule doesn't match or isn't valid
+    pub actions: Vec<RuleAction>,
+    pub issues: Vec<RuleIssue>,
+}
+
+pub fn simulate(rule: &Rule, email: &EmailData) -> Simulation {
+    let issues = validate(rule);
+    let mut trace = Vec::new();
+    let mut matched = check(&rule.condition, email, "condition", &mut trace);
+    if !rule.mailboxes.is_empty() {
+        let in_scope = rule.in_scope(&email.account);
+        trace.push(TraceStep {
+            path: "mailboxes".to_string(),
+            matched: in_scope,
+        });
+        matched &= in_scope;
+    }
+    Simulation {
+        matched,
+        trace,
+        actions: if matched && issues.is_empty() {
+            rule.actions.clone()
+        } else {
+            Vec::new()
+        },
+        issues,
+    }
+}
+
+/// JSON Schema for rules, for editors to build forms and pickers from
+pub fn schema() -> serde_json::Value {
+    let text_field = serde_json::json!({ "enum": ["sender", "subject", "body", "account"] });
+    let text_op = serde_json::json!({
+        "enum": ["contains", "equals", "starts_with", "ends_with", "matches"]
+    });
+    serde_json::json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "title": "SERINA rule",
+        "type": "object",
+        "required": ["name", "condition", "actions"],
+        "properties": {
+            "id": { "type": "string", "description": "Assigned when the rule is first saved" },
+            "name": { "type": "string", "minLength": 1 },
+            "enabled": { "type": "boolean", "default": true },
+            "trigger": { "$ref": "#/$defs/trigger" },
+            "condition": { "$ref": "#/$defs/condition" },
+            "actions": {
+                "type": "array",
+                "minItems": 1,
+                "items": { "$ref": "#/$defs/action" }
+            },
+            "mailboxes": {
+                "type": "array",
+                "items": { "type": "string" },
+                "description": "Account or shared mailbox addresses to run on; empty means all"
+            },
+            "team": { "type": "boolean", "description": "Set on rules shared by the team" }
+        },
+        "$defs": {
+            "trigger": { "enum": ["new_email", "manual"], "default": "new_email" },
+            "condition": {
+                "oneOf": [
+                    group("all", "Every condition holds"),
+                    group("any", "At least one condition holds"),
+                    {
+                        "title": "Not",
+                        "type": "object",
+                        "required": ["type", "condition"],
+                        "properties": {
+                            "type": { "const": "not" },
+                            "condition": { "$ref": "#/$defs/condition" }
+                        }
+                    },
+                    {
+                        "title": "Text",
+                        "type": "object",
+                        "required": ["type", "field", "op", "value"],
+                        "properties": {
+                            "type": { "const": "text" },
+                            "field": text_field,
+                            "op": text_op,
+                            "value": { "type": "string", "minLength": 1 }
+                        }
+                    },
+                    flag("unread", "Unread"),
+                    flag("has_deadline", "Has a deadline")
+                ]
+            },
+            "action": {
+                "oneOf": [
+                    flag("mark_read", "Mark read"),
+                    flag("archive", "Archive"),
+                    flag("delete", "Delete"),
+                    {
+                        "title": "Snooze",
+                        "type": "object",
+                        "required": ["type", "minutes"],
+                        "properties": {
+                            "type": { "const": "snooze" },
+                            "minutes": { "type": "integer", "minimum": 1 }
+                        }
+                    },
+                    flag("create_task", "Create task"),
+                    {
+                        "title": "Notify",
+                        "type": "object",
+                        "required": ["type", "message"],
+                        "properties": {
+                            "type": { "const": "notify" },
+                            "message": { "type": "string" }
+                        }
+                    },
+                    {
+                        "title": "Label",
+                        "type": "object",
+                        "required": ["type", "name"],
+                        "properties": {
+                            "type": { "const": "label" },
+                            "name": { "type": "string", "minLength": 1 }
+                        }
+                    },
+                    {
+                        "title": "Webhook",
+                        "type": "object",
+                        "required": ["type", "url"],
+                        "properties": {
+                            "type": { "const": "webhook" },
+                            "url": { "type": "string", "format": "uri" },
+                            "payload": {}
+                        }
+                    },
+                    {
+                        "title": "Draft from template",
+                        "type": "object",
+                        "required": ["type", "template_id"],
+                        "properties": {
+                            "type": { "const": "draft_template" },
+                            "template_id": { "type": "string", "minLength": 1 }
+                        }
+                    }
+                ]
+            }
+        },
+        "examples": [{
+            "name": "File receipts",
+            "trigger": "new_email",
+            "condition": {
+                "type": "all",
+                "conditions": [
+                    {
+                        "type": "text",
+                        "field": "subject",
+                        "op": "matches",
+                        "value": "receipt|order #\\d+"
+                    },
+                    { "type": "not", "condition": { "type": "has_deadline" } }
+                ]
+            },
+            "actions": [{ "type": "mark_read" }, { "type": "archive" }]
+        }]
+    })
+}
+
+fn group(kind: &str, title: &str) -> serde_json::Value {
+    serde_json::json!({
+        "title": title,
+        "type": "object",
+        "required": ["type", "conditions"],
+        "properties": {
+            "type": { "const": kind },
+            "conditions": { "type": "array", "items": { "$ref": "#/$defs/condition" } }
+        }
+    })
+}
+
+/// A condition or action with no settings of its own
+fn flag(kind: &str, title: &str) -> serde_json::Value {
+    serde_json::json!({
+        "title": title,
+        "type": "object",
+        "required": ["type"],
+        "properties": { "type": { "const": kind } }
+    })
+}
+
+/// Rules persisted as JSON under the config directory, in the order they run
+pub struct RuleStore {
+    path: Option<PathBuf>,
+    rules: Mutex<Vec<Rule>>,
+    /// The team's shared rules and whether local ones with the same id replace them
+    team: Mutex<(Vec<Rule>, bool)>,
+}
+
+impl RuleStore {
+    pub fn load(path: PathBuf) -> Self {
+        let rules = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+        Self {
+            path: Some(path),
+            rules: Mutex::new(rules),
+            team: Mutex::new((Vec::new(), false)),
+        }
+    }
+
+    pub fn in_memory() -> Self {
+        Self {
+            path: None,
+            rules: Mutex::new(Vec::new()),
+            team: Mutex::new((Vec::new(), false)),
+        }
+    }
+
+    /// Swap in the team's shared rules; they aren't saved with the local ones
+    pub fn set_team(&self, rules: Vec<Rule>, local_wins: bool) {
+        *self.team.lock().unwrap() = (rules, local_wins);
+    }
+
+    /// Team and local rules, with only the winning copy of any id both sides use
+    pub fn list(&self) -> Vec<Rule> {
+        let (team, local_wins) = &*self.team.lock().unwrap();
+        let local = self.rules.lock().unwrap();
+        teamsync::merge(team, &local, |rule| &rule.id, *local_wins)
+    }
+
+    pub fn get(&self, id: &str) -> Option<Rule> {
+        self.list().into_iter().find(|rule| rule.id == id)
+    }
+
+    /// Enabled rules with the given trigger
+    pub fn active(&self, trigger: Trigger) -> Vec<Rule> {
+        self.list()
+            .into_iter()
+            .filter(|rule| rule.enabled && rule.trigger == trigger)
+            .collect()
+    }
+
+    /// Insert or update a valid rule; a new id is assigned when it has none. Team rules can
+    /// only be changed here when local copies win, and saving one makes that local copy.
+    pub fn save(&self, mut rule: Rule) -> Result<Rule, String> {
+        let issues = validate(&rule);
+        if !issues.is_empty() {
+            let messages: Vec<String> = issues
+                .iter()
+                .map(|issue| format!("{}: {}", issue.path, issue.message))
+                .collect();
+            return Err(messages.join("; "));
+        }
+        self.ensure_local(&rule.id)?;
+        rule.team = false;
+        let mut rules = self.rules.lock().unwrap();
+        if rule.id.is_empty() {
+            let base = format!("rule-{}", chrono::Utc::now().timestamp_millis());
+            let mut id = base.clone();
+            let mut n = 1;
+            while rules.iter().any(|r| r.id == id) {
+                n += 1;
+                id = format!("{}-{}", base, n);
+            }
+            rule.id = id;
+        }
+        match rules.iter_mut().find(|r| r.id == rule.id) {
+            Some(existing) => *existing = rule.clone(),
+            None => rules.push(rule.clone()),
+        }
+        self.persist(&rules)?;
+        Ok(rule)
+    }
+
+    pub fn delete(&self, id: &str) -> Result<bool, String> {
+        self.ensure_local(id)?;
+        let mut rules = self.rules.lock().unwrap();
+        let before = rules.len();
+        rules.retain(|rule| rule.id != id);
+        let removed = rules.len() != before;
+        if removed {
+            self.persist(&rules)?;
+        }
+        Ok(removed)
+    }
+
+    /// Refuse changes to an id the team's copy owns
+    fn ensure_local(&self, id: &str) -> Result<(), String> {
+        let (team, local_wins) = &*self.team.lock().unwrap();
+        match team.iter().find(|rule| rule.id == id) {
+            Some(rule) if !local_wins => Err(format!(
+                "\"{}\" is one of your team's shared rules and can't be changed here",
+                rule.name
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    fn persist(&self, rules: &[Rule]) -> Result<(), String> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let contents = serde_json::to_string_pretty(rules).map_err(|e| e.to_string())?;
+        std::fs::write(path, contents).map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn email(subject: &str) -> EmailData {
+        serde_json::from_value(serde_json::json!({
+            "id": "a",
+            "subject": subject,
+            "sender": "Shop",
+            "sender_email": "orders@shop.example",
+            "body": "<p>Thanks for your order</p>",
+            "received_time": "",
+            "is_unread": true
+        }))
+        .unwrap()
+    }
+
+    fn example() -> Rule {
+        serde_json::from_value(schema()["examples"][0].clone()).unwrap()
+    }
+
+    #[test]
+    fn test_schema_example() {
+        let rule = example();
+        assert!(rule.enabled);
+        assert_eq!(rule.trigger, Trigger::NewEmail);
+        assert!(validate(&rule).is_empty());
+        assert!(evaluate(&rule.condition, &email("Your Order #1042")));
+        assert!(!evaluate(&rule.condition, &email("Lunch?")));
+    }
+
+    #[test]
+    fn test_validate() {
+        let rule: Rule = serde_json::from_value(serde_json::json!({
+            "name": " ",
+            "condition": {
+                "type": "any",
+                "conditions": [
+                    { "type": "text", "field": "subject", "op": "matches", "value": "(unclosed" },
+                    { "type": "any", "conditions": [] }
+                ]
+            },
+            "actions": [
+                { "type": "delete" },
+                { "type": "snooze", "minutes": 0 },
+                { "type": "webhook", "url": "ftp://hooks.example" },
+                { "type": "draft_template", "template_id": "" }
+            ]
+        }))
+        .unwrap();
+        let paths: Vec<String> = validate(&rule)
+            .into_iter()
+            .map(|issue| issue.path)
+            .collect();
+        assert_eq!(
+            paths,
+            [
+                "name",
+                "condition.conditions[0].value",
+                "condition.conditions[1].conditions",
+                "actions[1]",
+                "actions[1].minutes",
+                "actions[2]",
+                "actions[2].url",
+                "actions[3]",
+                "actions[3].template_id"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_draft_template_action() {
+        let rule: Rule = serde_json::from_value(serde_json::json!({
+            "name": "Draft invoice reply",
+            "condition": { "type": "text", "field": "subject", "op": "contains", "value": "invoice" },
+            "actions": [{ "type": "draft_template", "template_id": "tpl-invoice" }]
+        }))
+        .unwrap();
+        assert!(validate(&rule).is_empty());
+        assert_eq!(
+            rule.actions,
+            [RuleAction::DraftTemplate {
+                template_id: "tpl-invoice".to_string()
+            }]
+        );
+        let schema_actions = schema()["$defs"]["action"]["oneOf"].to_string();
+        assert!(schema_actions.contains("draft_template"));
+
+        // The draft is the template rendered for the matching email
+        let email = email("Invoice 2291");
+        assert!(evaluate(&rule.condition, &email));
+        let vars = crate::templates::vars_for_email(&email, "Lu");
+        assert_eq!(
+            crate::templates::render(
+                "Hi {{sender_name}}, got \"{{subject}}\". {{my_name}}",
+                &vars
+            ),
+            "Hi Shop, got \"Invoice 2291\". Lu"
+        );
+    }
+
+    #[test]
+    fn test_simulate() {
+        let mut rule = example();
+        rule.condition = Condition::Any {
+            conditions: vec![
+                Condition::Text {
+                    field: Field::Sender,
+                    op: TextOp::EndsWith,
+                    value: "@SHOP.example".to_string(),
+                },
+                Condition::Not {
+                    condition: Box::new(Condition::Unread),
+                },
+            ],
+        };
+        let simulation = simulate(&rule, &email("Receipt"));
+        assert!(simulation.matched);
+        assert_eq!(simulation.actions.len(), 2);
+        let trace: Vec<(&str, bool)> = simulation
+            .trace
+            .iter()
+            .map(|step| (step.path.as_str(), step.matched))
+            .collect();
+        assert_eq!(
+            trace,
+            [
+                ("condition.conditions[0]", true),
+                ("condition.conditions[1].condition", true),
+                ("condition.conditions[1]", false),
+                ("condition", true)
+            ]
+        );
+
+        rule.mailboxes = vec!["Support@example.com".to_string()];
+        let simulation = simulate(&rule, &email("Receipt"));
+        assert!(!simulation.matched);
+        assert_eq!(simulation.trace.last().unwrap().path, "mailboxes");
+        let support = EmailData {
+            account: "support@example.com".to_string(),
+            ..email("Receipt")
+        };
+        assert!(rule.applies_to(&support));
+        rule.mailboxes.clear();
+
+        rule.name.clear();
+        let simulation = simulate(&rule, &email("Receipt"));
+        assert!(simulation.matched);
+        assert!(simulation.actions.is_empty());
+    }
+
+    #[test]
+    fn test_rule_store() {
+        let path = std::env::temp_dir().join(format!("serina-rules-{}.json", std::process::id()));
+        let store = RuleStore::load(path.clone());
+        assert!(store.save(Rule::default()).is_err());
+        let mut manual = store.save(example()).unwrap();
+        assert!(manual.id.starts_with("rule-"));
+        manual.trigger = Trigger::Manual;
+        store.save(manual.clone()).unwrap();
+        store.save(example()).unwrap();
+
+        assert_eq!(store.list().len(), 2);
+        assert_eq!(store.active(Trigger::Manual), vec![manual.clone()]);
+        assert_eq!(RuleStore::load(path.clone()).list(), store.list());
+        assert!(store.delete(&manual.id).unwrap());
+        assert!(store.get(&manual.id).is_none());
+
+        let shared = Rule {
+            id: "team-receipts".to_string(),
+            team: true,
+            ..example()
+        };
+        store.set_team(vec![shared.clone()], false);
+        assert_eq!(store.active(Trigger::NewEmail)[0], shared);
+        assert!(store.save(shared.clone()).is_err());
+        assert!(store.delete(&shared.id).is_err());
+        assert_eq!(RuleStore::load(path.clone()).list().len(), 1);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,400 @@
+// SERINA Scripts
+// Per-email hooks written in Rhai. Scripts run sandboxed and can only ask for actions; SERINA
+// carries them out afterwards the same way as rule actions.
+
+use crate::parsing;
+use crate::rules::{self, RuleAction};
+use crate::utils::EmailData;
+use rhai::module_resolvers::DummyModuleResolver;
+use rhai::{Dynamic, Engine, EvalAltResult, Map, Position, Scope, INT};
+use serde::{Deserialize, Serialize};
+use std::panic::AssertUnwindSafe;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Wall-clock limit for one run
+pub const TIMEOUT: Duration = Duration::from_millis(500);
+/// Operations allowed in one run, so a runaway loop stops even before the timeout
+const MAX_OPERATIONS: u64 = 1_000_000;
+/// Actions one run may ask for
+const MAX_ACTIONS: usize = 20;
+/// Lines of `print`/`log` output kept from one run
+const MAX_LOG_LINES: usize = 50;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Script {
+    pub id: String,
+    pub name: String,
+    pub enabled: bool,
+    pub source: String,
+}
+
+impl Default for Script {
+    fn default() -> Self {
+        Self {
+            id: String::new(),
+            name: String::new(),
+            enabled: true,
+            source: String::new(),
+        }
+    }
+}
+
+/// What one run asked for
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ScriptRun {
+    /// Empty when the script failed; a run applies all of its actions or none
+    pub actions: Vec<RuleAction>,
+    pub log: Vec<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Default)]
+struct Output {
+    actions: Vec<RuleAction>,
+    log: Vec<String>,
+}
+
+impl Output {
+    fn log(&mut self, line: &str) {
+        if self.log.len() < MAX_LOG_LINES {
+            self.log.push(line.to_string());
+        }
+    }
+}
+
+type Shared = Arc<Mutex<Output>>;
+
+fn runtime_error(message: &str) -> Box<EvalAltResult> {
+    EvalAltResult::ErrorRuntime(message.into(), Position::NONE).into()
+}
+
+/// Queue an action, checked like a rule action
+fn ask(output: &Shared, action: RuleAction) -> Result<(), Box<EvalAltResult>> {
+    if let Some(issue) = rules::action_issue(&action, "action") {
+        return Err(runtime_error(&issue.message));
+    }
+    let mut output = output.lock().unwrap();
+    if output.actions.len() >= MAX_ACTIONS {
+        return Err(runtime_error("Too many actions for one email"));
+    }
+    output.actions.push(action);
+    Ok(())
+}
+
+/// An engine with no file or module access, resource limits, and the SERINA API:
+/// `archive()`, `delete()`, `mark_read()`, `snooze(minutes)`, `create_task()`,
+/// `notify(message)`, `label(name)`, `webhook(url)`, `webhook(url, payload)`, `log(text)` and
+/// `matches(text, pattern)`
+fn engine(output: &Shared) -> Engine {
+    let mut engine = Engine::new();
+    engine
+        .set_module_resolver(DummyModuleResolver::new())
+        .disable_symbol("eval")
+        .set_max_operations(MAX_OPERATIONS)
+        .set_max_call_levels(32)
+        .set_max_expr_depths(64, 32)
+        .set_max_string_size(1 << 20)
+        .set_max_array_size(10_000)
+        .set_max_map_size(10_000);
+
+    let out = output.clone();
+    engine.on_print(move |text| out.lock().unwrap().log(text));
+    let out = output.clone();
+    engine.on_debug(move |text, _, _| out.lock().unwrap().log(text));
+    let out = output.clone();
+    engine.register_fn("log", move |text: &str| out.lock().unwrap().log(text));
+
+    let simple = [
+        ("archive", RuleAction::Archive),
+        ("delete", RuleAction::Delete),
+        ("mark_read", RuleAction::MarkRead),
+        ("create_task", RuleAction::CreateTask),
+    ];
+    for (name, action) in simple {
+        let out = output.clone();
+        engine.register_fn(name, move || ask(&out, action.clone()));
+    }
+    let out = output.clone();
+    engine.register_fn("snooze", move |minutes: INT| {
+        let minutes = u32::try_from(minutes).map_err(|_| runtime_error("Invalid minutes"))?;
+        ask(&out, RuleAction::Snooze { minutes })
+    });
+    let out = output.clone();
+    engine.register_fn("notify", move |message: &str| {
+        let message = message.to_string();
+        ask(&out, RuleAction::Notify { message })
+    });
+    let out = output.clone();
+    engine.register_fn("label", move |name: &str| {
+        let name = name.to_string();
+        ask(&out, RuleAction::Label { name })
+    });
+    let out = output.clone();
+    engine.register_fn("webhook", move |url: &str| {
+        let url = url.to_string();
+        ask(&out, RuleAction::Webhook { url, payload: None })
+    });
+    let out = output.clone();
+    engine.register_fn("webhook", move |url: &str, payload: Map| {
+        let payload = serde_json::from_str(&rhai::format_map_as_json(&payload))
+            .map_err(|e| runtime_error(&format!("Invalid payload: {}", e)))?;
+        let url = url.to_string();
+        ask(
+            &out,
+            RuleAction::Webhook {
+                url,
+                payload: Some(payload),
+            },
+        )
+    });
+    engine.register_fn("matches", |text: &str, pattern: &str| {
+        regex::RegexBuilder::new(pattern)
+            .case_insensitive(true)
+            .build()
+            .map(|pattern| pattern.is_match(text))
+            .map_err(|e| runtime_error(&format!("Invalid pattern: {}", e)))
+    });
+    engine
+}
+
+/// The `email` variable scripts see
+fn email_map(email: &EmailData) -> Map {
+    let mut map = Map::new();
+    let mut set = |key: &str, value: Dynamic| {
+        map.insert(key.into(), value);
+    };
+    set("id", email.id.clone().into());
+    set("subject", email.subject.clone().into());
+    set("sender", email.sender.clone().into());
+    set("sender_email", email.sender_email.clone().into());
+    set("body", parsing::plain_text(&email.body).into());
+    set("account", email.account.clone().into());
+    set("received", email.received_time.clone().into());
+    set("unread", email.is_unread.into());
+    set("has_deadline", email.deadline.is_some().into());
+    map
+}
+
+/// Check a script for syntax errors without running it
+pub fn compile(source: &str) -> Result<(), String> {
+    let output = Shared::default();
+    engine(&output)
+        .compile(source)
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+/// Run a script against one email and collect what it asked for
+pub fn run(source: &str, email: &EmailData) -> ScriptRun {
+    let output = Shared::default();
+    let mut engine = engine(&output);
+    let started = Instant::now();
+    engine.on_progress(move |_| (started.elapsed() > TIMEOUT).then_some(Dynamic::UNIT));
+    // A script may change its copy of the email; nothing reads it back
+    let mut scope = Scope::new();
+    scope.push("email", email_map(email));
+    // Contain any panic inside the engine to this run
+    let result = std::panic::catch_unwind(AssertUnwindSafe(|| {
+        engine.run_with_scope(&mut scope, source)
+    }))
+    .unwrap_or_else(|_| Err(runtime_error("The script engine failed")));
+    drop(engine);
+
+    let Output { actions, log } = std::mem::take(&mut *output.lock().unwrap());
+    match result {
+        Ok(()) => ScriptRun {
+            actions,
+            log,
+            error: None,
+        },
+        Err(e) => ScriptRun {
+            actions: Vec::new(),
+            log,
+            error: Some(match *e {
+                EvalAltResult::ErrorTerminated(..) => {
+                    format!("Stopped after {} ms", TIMEOUT.as_millis())
+                }
+                EvalAltResult::ErrorTooManyOperations(..) => {
+                    "Stopped after too many operations".to_string()
+                }
+                e => e.to_string(),
+            }),
+        },
+    }
+}
+
+/// Scripts persisted as JSON under the config directory, in the order they run
+pub struct ScriptStore {
+    path: Option<PathBuf>,
+    scripts: Mutex<Vec<Script>>,
+}
+
+impl ScriptStore {
+    pub fn load(path: PathBuf) -> Self {
+        let scripts = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+        Self {
+            path: Some(path),
+            scripts: Mutex::new(scripts),
+        }
+    }
+
+    pub fn in_memory() -> Self {
+        Self {
+            path: None,
+            scripts: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn list(&self) -> Vec<Script> {
+        self.scripts.lock().unwrap().clone()
+    }
+
+    pub fn enabled(&self) -> Vec<Script> {
+        self.scripts
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|script| script.enabled)
+            .cloned()
+            .collect()
+    }
+
+    /// Insert or update a script that compiles; a new id is assigned when it has none
+    pub fn save(&self, mut script: Script) -> Result<Script, String> {
+        if script.name.trim().is_empty() {
+            return Err("Script name is required".to_string());
+        }
+        compile(&script.source)?;
+        let mut scripts = self.scripts.lock().unwrap();
+        if script.id.is_empty() {
+            let base = format!("script-{}", chrono::Utc::now().timestamp_millis());
+            let mut id = base.clone();
+            let mut n = 1;
+            while scripts.iter().any(|s| s.id == id) {
+                n += 1;
+                id = format!("{}-{}", base, n);
+            }
+            script.id = id;
+        }
+        match scripts.iter_mut().find(|s| s.id == script.id) {
+            Some(existing) => *existing = script.clone(),
+            None => scripts.push(script.clone()),
+        }
+        self.persist(&scripts)?;
+        Ok(script)
+    }
+
+    pub fn delete(&self, id: &str) -> Result<bool, String> {
+        let mut scripts = self.scripts.lock().unwrap();
+        let before = scripts.len();
+        scripts.retain(|script| script.id != id);
+        let removed = scripts.len() != before;
+        if removed {
+            self.persist(&scripts)?;
+        }
+        Ok(removed)
+    }
+
+    fn persist(&self, scripts: &[Script]) -> Result<(), String> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let contents = serde_json::to_string_pretty(scripts).map_err(|e| e.to_string())?;
+        std::fs::write(path, contents).map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn email() -> EmailData {
+        serde_json::from_value(serde_json::json!({
+            "id": "a",
+            "subject": "[Ticket #4821] Printer on fire",
+            "sender": "Helpdesk",
+            "sender_email": "support@corp.example",
+            "body": "<p>Floor 3</p>",
+            "received_time": "2024-05-14 09:00:00",
+            "is_unread": true
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_run() {
+        let source = r#"
+            if matches(email.subject, "ticket #\\d+") {
+                label("Support");
+                webhook("https://hooks.example/tickets", #{ id: email.id, floor: 3 });
+                print(`Filed ${email.subject}`);
+            }
+            if email.body.contains("Floor 9") { archive(); }
+        "#;
+        let run = run(source, &email());
+        assert_eq!(run.error, None);
+        assert_eq!(run.log, ["Filed [Ticket #4821] Printer on fire"]);
+        assert_eq!(
+            run.actions,
+            [
+                RuleAction::Label {
+                    name: "Support".to_string()
+                },
+                RuleAction::Webhook {
+                    url: "https://hooks.example/tickets".to_string(),
+                    payload: Some(serde_json::json!({ "id": "a", "floor": 3 })),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sandbox() {
+        let failed = |source: &str| run(source, &email()).error.unwrap();
+        assert!(failed("loop { }").starts_with("Stopped after"));
+        assert!(failed(r#"import "secrets" as s;"#).contains("secrets"));
+        assert!(!failed(r#"eval("archive()")"#).is_empty());
+        assert!(failed("snooze(0);").contains("at least a minute"));
+        let partial = run("archive(); throw \"no\";", &email());
+        assert!(partial.actions.is_empty());
+        assert!(compile("if {").is_err());
+    }
+
+    #[test]
+    fn test_script_store() {
+        let store = ScriptStore::in_memory();
+        assert!(store
+            .save(Script {
+                name: "Broken".to_string(),
+                source: "let = 1;".to_string(),
+                ..Script::default()
+            })
+            .is_err());
+        let saved = store
+            .save(Script {
+                name: "Tickets".to_string(),
+                source: "label(\"Support\");".to_string(),
+                ..Script::default()
+            })
+            .unwrap();
+        assert!(saved.id.starts_with("script-"));
+        store
+            .save(Script {
+                enabled: false,
+                ..saved.clone()
+            })
+            .unwrap();
+        assert_eq!(store.list().len(), 1);
+        assert!(store.enabled().is_empty());
+        assert!(store.delete(&saved.id).unwrap());
+    }
+}
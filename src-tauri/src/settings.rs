@@ -0,0 +1,228 @@
+// SERINA Settings Bundles
+// Portable, secrets-stripped export of config and templates for use across machines
+
+use crate::templates::Template;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+pub const BUNDLE_VERSION: u32 = 1;
+
+/// File name looked for inside the synced folder
+pub const SYNC_FILE_NAME: &str = "serina-settings.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SettingsBundle {
+    pub version: u32,
+    pub exported_at: DateTime<Utc>,
+    pub config: Value,
+    #[serde(default)]
+    pub templates: Vec<Template>,
+}
+
+impl SettingsBundle {
+    /// Build a bundle, dropping API keys and other credentials from the config
+    pub fn new(mut config: Value, templates: Vec<Template>) -> Self {
+        strip_secrets(&mut config);
+        Self {
+            version: BUNDLE_VERSION,
+            exported_at: Utc::now(),
+            config,
+            templates,
+        }
+    }
+
+    pub fn write(&self, path: &Path) -> Result<(), String> {
+        let contents = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        std::fs::write(path, contents).map_err(|e| e.to_string())
+    }
+
+    pub fn read(path: &Path) -> Result<Self, String> {
+        let contents = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        let bundle: Self =
+            serde_json::from_str(&contents).map_err(|e| format!("Invalid settings file: {}", e))?;
+        if bundle.version > BUNDLE_VERSION {
+            return Err(format!(
+                "Settings file version {} is newer than this app supports",
+                bundle.version
+            ));
+        }
+        Ok(bundle)
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SyncConfig {
+    /// Folder (e.g. inside Dropbox) watched for a settings bundle; empty disables sync
+    pub folder: String,
+}
+
+impl SyncConfig {
+    pub fn bundle_path(&self) -> Option<PathBuf> {
+        let folder = self.folder.trim();
+        (!folder.is_empty()).then(|| PathBuf::from(folder).join(SYNC_FILE_NAME))
+    }
+}
+
+fn is_secret_key(key: &str) -> bool {
+    let key = key.to_lowercase();
+    ["api_key", "password", "secret", "token"]
+        .iter()
+        .any(|secret| key.ends_with(secret))
+}
+
+/// Remove credential fields anywhere in a config document; returns how many were removed
+pub fn strip_secrets(value: &mut Value) -> usize {
+    match value {
+        Value::Object(map) => {
+            let before = map.len();
+            map.retain(|key, _| !is_secret_key(key));
+            before - map.len() + map.values_mut().map(strip_secrets).sum::<usize>()
+        }
+        Value::Array(items) => items.iter_mut().map(strip_secrets).sum(),
+        _ => 0,
+    }
+}
+
+/// Fields naming the service an array entry, such as an LLM fallback, talks to
+const IDENTITY_KEYS: &[&str] = &["provider", "base_url", "url"];
+
+/// What an array entry is matched on between configs; `None` for entries with no identity
+fn identity(value: &Value) -> Option<Vec<Option<String>>> {
+    let Value::Object(map) = value else {
+        return None;
+    };
+    let key: Vec<Option<String>> = IDENTITY_KEYS
+        .iter()
+        .map(|field| {
+            map.get(*field)
+                .and_then(Value::as_str)
+                .map(|value| value.trim().trim_end_matches('/').to_lowercase())
+                .filter(|value| !value.is_empty())
+        })
+        .collect();
+    key.iter().any(Option::is_some).then_some(key)
+}
+
+/// Carry this machine's credentials over into an imported config. Array entries get the
+/// credentials of the entry for the same provider and endpoint, wherever it sits in the list;
+/// entries with no counterpart get none.
+pub fn restore_secrets(imported: &mut Value, current: &Value) {
+    match (imported, current) {
+        (Value::Object(imported), Value::Object(current)) => {
+            for (key, value) in current {
+                if is_secret_key(key) {
+                    imported.entry(key.clone()).or_insert_with(|| value.clone());
+                } else if let Some(target) = imported.get_mut(key) {
+                    restore_secrets(target, value);
+                }
+            }
+        }
+        (Value::Array(imported), Value::Array(current)) => {
+            let mut unclaimed: Vec<&Value> = current.iter().collect();
+            for target in imported.iter_mut() {
+                let Some(key) = identity(target) else {
+                    continue;
+                };
+                if let Some(index) = unclaimed
+                    .iter()
+                    .position(|value| identity(value).as_ref() == Some(&key))
+                {
+                    restore_secrets(target, unclaimed.remove(index));
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Notices when the synced bundle file changes on disk
+#[derive(Default)]
+pub struct SettingsWatcher {
+    last_modified: Mutex<Option<SystemTime>>,
+}
+
+impl SettingsWatcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// True when `path` changed since the last call; the first sighting only sets the baseline
+    pub fn changed(&self, path: &Path) -> bool {
+        let Some(modified) = std::fs::metadata(path).and_then(|m| m.modified()).ok() else {
+            return false;
+        };
+        let mut last = self.last_modified.lock().unwrap();
+        let changed = last.is_some_and(|seen| modified > seen);
+        *last = Some(modified);
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_strip_and_restore_secrets() {
+        let current = json!({
+            "llm": {
+                "provider": "openai",
+                "api_key": "sk-local",
+                "fallbacks": [{"provider": "openrouter", "api_key": "or-local"}]
+            },
+            "ui": {"dark_mode": true}
+        });
+
+        let bundle = SettingsBundle::new(current.clone(), Vec::new());
+        assert!(!bundle.config.to_string().contains("local"));
+        assert_eq!(bundle.config["ui"]["dark_mode"], json!(true));
+        assert_eq!(strip_secrets(&mut current.clone()), 2);
+
+        let mut imported = bundle.config;
+        imported["ui"]["dark_mode"] = json!(false);
+        restore_secrets(&mut imported, &current);
+        assert_eq!(imported["llm"]["api_key"], json!("sk-local"));
+        assert_eq!(
+            imported["llm"]["fallbacks"][0]["api_key"],
+            json!("or-local")
+        );
+        assert_eq!(imported["ui"]["dark_mode"], json!(false));
+    }
+
+    #[test]
+    fn test_restore_secrets_follows_reordered_fallbacks() {
+        let current = json!({
+            "llm": {
+                "fallbacks": [
+                    {"provider": "openai", "api_key": "sk-local"},
+                    {"provider": "openrouter", "api_key": "or-local"},
+                    {"provider": "openai", "base_url": "https://llm.corp.example/v1", "api_key": "corp-local"}
+                ]
+            }
+        });
+        let mut imported = json!({
+            "llm": {
+                "fallbacks": [
+                    {"provider": "openai", "base_url": "https://llm.corp.example/v1/"},
+                    {"provider": "anthropic"},
+                    {"provider": "openrouter", "model": "mistral"},
+                    {"provider": "openai", "base_url": "https://other.example/v1"}
+                ]
+            }
+        });
+        restore_secrets(&mut imported, &current);
+        let keys: Vec<Option<&str>> = imported["llm"]["fallbacks"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|fallback| fallback.get("api_key").and_then(Value::as_str))
+            .collect();
+        assert_eq!(keys, [Some("corp-local"), None, Some("or-local"), None]);
+    }
+}
@@ -0,0 +1,86 @@
+// SERINA Shutdown
+// Orderly quit: refuse new work, let in-flight syncs and sends finish, then exit
+
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::Duration;
+
+/// How long quitting waits on each step before giving up on it
+pub const STEP_TIMEOUT: Duration = Duration::from_secs(5);
+
+const DRAIN_POLL: Duration = Duration::from_millis(50);
+
+/// Whether the app is quitting, and how much work is still in flight
+#[derive(Default)]
+pub struct Shutdown {
+    stopping: AtomicBool,
+    active: AtomicUsize,
+}
+
+impl Shutdown {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start shutting down; false when a shutdown is already under way
+    pub fn begin(&self) -> bool {
+        !self.stopping.swap(true, Ordering::SeqCst)
+    }
+
+    pub fn is_stopping(&self) -> bool {
+        self.stopping.load(Ordering::SeqCst)
+    }
+
+    /// Register work that shouldn't be cut off by quitting; `None` once shutting down
+    pub fn task(&self) -> Option<TaskGuard<'_>> {
+        self.active.fetch_add(1, Ordering::SeqCst);
+        let guard = TaskGuard { shutdown: self };
+        // Checked after registering, so `drained` can't miss a task that slipped in
+        (!self.is_stopping()).then_some(guard)
+    }
+
+    pub fn active(&self) -> usize {
+        self.active.load(Ordering::SeqCst)
+    }
+
+    /// Wait for in-flight work to finish; false when it's still running after `timeout`
+    pub async fn drained(&self, timeout: Duration) -> bool {
+        let deadline = tokio::time::Instant::now() + timeout;
+        while self.active() > 0 {
+            if tokio::time::Instant::now() >= deadline {
+                return false;
+            }
+            tokio::time::sleep(DRAIN_POLL).await;
+        }
+        true
+    }
+}
+
+/// Marks a task in flight until dropped
+pub struct TaskGuard<'a> {
+    shutdown: &'a Shutdown,
+}
+
+impl Drop for TaskGuard<'_> {
+    fn drop(&mut self) {
+        self.shutdown.active.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_shutdown() {
+        let shutdown = Shutdown::new();
+        let task = shutdown.task().unwrap();
+        assert!(shutdown.begin());
+        assert!(!shutdown.begin());
+        assert!(shutdown.task().is_none());
+        assert_eq!(shutdown.active(), 1);
+        assert!(!shutdown.drained(Duration::from_millis(10)).await);
+
+        drop(task);
+        assert!(shutdown.drained(Duration::from_millis(10)).await);
+    }
+}
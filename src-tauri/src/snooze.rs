@@ -0,0 +1,133 @@
+// SERINA Snooze Queue
+// Snoozed emails and when they come back, kept in wall-clock time so suspend can't lose them
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnoozedEmail {
+    pub email_id: String,
+    pub until: DateTime<Utc>,
+    /// Set for reminders ahead of a deadline found in the email
+    #[serde(default)]
+    pub deadline: Option<DateTime<Utc>>,
+}
+
+pub struct SnoozeQueue {
+    path: Option<PathBuf>,
+    entries: Mutex<Vec<SnoozedEmail>>,
+}
+
+impl SnoozeQueue {
+    pub fn load(path: PathBuf) -> Self {
+        let entries = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+        Self {
+            path: Some(path),
+            entries: Mutex::new(entries),
+        }
+    }
+
+    pub fn in_memory() -> Self {
+        Self {
+            path: None,
+            entries: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Soonest first
+    pub fn pending(&self) -> Vec<SnoozedEmail> {
+        let mut entries = self.entries.lock().unwrap().clone();
+        entries.sort_by_key(|entry| entry.until);
+        entries
+    }
+
+    /// Snooze an email; snoozing it again replaces the earlier time
+    pub fn add(
+        &self,
+        email_id: &str,
+        minutes: u32,
+        now: DateTime<Utc>,
+    ) -> Result<SnoozedEmail, String> {
+        self.schedule(SnoozedEmail {
+            email_id: email_id.to_string(),
+            until: now + Duration::minutes(minutes as i64),
+            deadline: None,
+        })
+    }
+
+    /// Bring an email back at `entry.until`, replacing any earlier snooze of it
+    pub fn schedule(&self, entry: SnoozedEmail) -> Result<SnoozedEmail, String> {
+        let mut entries = self.entries.lock().unwrap();
+        entries.retain(|other| other.email_id != entry.email_id);
+        entries.push(entry.clone());
+        self.persist(&entries)?;
+        Ok(entry)
+    }
+
+    /// Drop a snooze or reminder without bringing the email back; returns whether there was one
+    pub fn cancel(&self, email_id: &str) -> Result<bool, String> {
+        let mut entries = self.entries.lock().unwrap();
+        let before = entries.len();
+        entries.retain(|entry| entry.email_id != email_id);
+        if entries.len() == before {
+            return Ok(false);
+        }
+        self.persist(&entries)?;
+        Ok(true)
+    }
+
+    /// Remove and return everything due by `now`, including snoozes that expired while asleep
+    pub fn take_due(&self, now: DateTime<Utc>) -> Result<Vec<SnoozedEmail>, String> {
+        let mut entries = self.entries.lock().unwrap();
+        let (mut due, waiting): (Vec<_>, Vec<_>) =
+            entries.drain(..).partition(|entry| entry.until <= now);
+        *entries = waiting;
+        if !due.is_empty() {
+            self.persist(&entries)?;
+        }
+        due.sort_by_key(|entry| entry.until);
+        Ok(due)
+    }
+
+    fn persist(&self, entries: &[SnoozedEmail]) -> Result<(), String> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let contents = serde_json::to_string_pretty(entries).map_err(|e| e.to_string())?;
+        std::fs::write(path, contents).map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_take_due_after_suspend() {
+        let queue = SnoozeQueue::in_memory();
+        let now = Utc.with_ymd_and_hms(2024, 5, 1, 9, 0, 0).unwrap();
+        queue.add("a", 30, now).unwrap();
+        queue.add("b", 120, now).unwrap();
+        queue.add("a", 10, now).unwrap();
+        assert_eq!(queue.pending().len(), 2);
+        assert_eq!(queue.pending()[0].email_id, "a");
+
+        assert!(queue
+            .take_due(now + Duration::minutes(5))
+            .unwrap()
+            .is_empty());
+        // Resuming hours later releases everything that came due in the meantime
+        assert!(queue.cancel("b").unwrap());
+        assert!(!queue.cancel("b").unwrap());
+        queue.add("b", 120, now).unwrap();
+        let due = queue.take_due(now + Duration::hours(5)).unwrap();
+        assert_eq!(due.len(), 2);
+        assert!(queue.pending().is_empty());
+    }
+}
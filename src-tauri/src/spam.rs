@@ -0,0 +1,109 @@
+// SERINA Spam Monitor
+// Spots junk-folder messages from known contacts or VIPs that were likely misfiled
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::Mutex;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SpamConfig {
+    /// Scan the junk folder on every scheduler tick
+    pub monitor: bool,
+    pub scan_limit: u32,
+}
+
+impl Default for SpamConfig {
+    fn default() -> Self {
+        Self {
+            monitor: true,
+            scan_limit: 50,
+        }
+    }
+}
+
+/// A junk-folder message as reported by the backend
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct JunkEmail {
+    pub id: String,
+    pub subject: String,
+    pub sender: String,
+    pub sender_email: String,
+    pub received_time: String,
+    /// Sender is in the Outlook contacts or has been written to before
+    pub known_contact: bool,
+}
+
+/// Whether `sender_email` matches a VIP entry (full address or `@domain`)
+pub fn is_vip(sender_email: &str, vip_senders: &[String]) -> bool {
+    let sender = sender_email.trim().to_lowercase();
+    vip_senders.iter().any(|vip| {
+        let vip = vip.trim().to_lowercase();
+        if vip.starts_with('@') {
+            sender.ends_with(&vip)
+        } else {
+            !vip.is_empty() && sender == vip
+        }
+    })
+}
+
+/// Remembers which junk messages were already flagged so each is reported once
+#[derive(Default)]
+pub struct MisfiledTracker {
+    flagged: Mutex<HashSet<String>>,
+}
+
+impl MisfiledTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Junk messages from trusted senders that haven't been reported yet
+    pub fn new_suspects(&self, junk: &[JunkEmail], vip_senders: &[String]) -> Vec<JunkEmail> {
+        let mut flagged = self.flagged.lock().unwrap();
+        junk.iter()
+            .filter(|email| email.known_contact || is_vip(&email.sender_email, vip_senders))
+            .filter(|email| flagged.insert(email.id.clone()))
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn junk(id: &str, sender_email: &str, known_contact: bool) -> JunkEmail {
+        JunkEmail {
+            id: id.to_string(),
+            sender_email: sender_email.to_string(),
+            known_contact,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_new_suspects() {
+        let vips = vec!["boss@corp.com".to_string(), "@partner.com".to_string()];
+        assert!(is_vip("Boss@Corp.com", &vips));
+        assert!(is_vip("anyone@partner.com", &vips));
+        assert!(!is_vip("someone@corp.com", &vips));
+
+        let tracker = MisfiledTracker::new();
+        let folder = [
+            junk("1", "friend@example.com", true),
+            junk("2", "deals@spam.example", false),
+            junk("3", "a@partner.com", false),
+        ];
+        let ids: Vec<String> = tracker
+            .new_suspects(&folder, &vips)
+            .into_iter()
+            .map(|e| e.id)
+            .collect();
+        assert_eq!(ids, ["1", "3"]);
+
+        // Already reported messages are not flagged again
+        assert!(tracker.new_suspects(&folder, &vips).is_empty());
+    }
+}
@@ -0,0 +1,184 @@
+// SERINA Inbox Streaks
+// Opt-in inbox-zero streaks and a daily triage goal, with milestones for the UI to celebrate
+
+use chrono::{Duration, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// Streak lengths worth a milestone on top of the daily one
+const STREAK_MILESTONES: &[u32] = &[3, 7, 14, 30, 60, 100, 365];
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StreakState {
+    /// Consecutive days the inbox reached zero unread, ending today or yesterday
+    pub current_streak: u32,
+    pub best_streak: u32,
+    pub last_zero_day: Option<NaiveDate>,
+    pub triaged_today: u32,
+    /// Day `triaged_today` counts for
+    pub triage_day: Option<NaiveDate>,
+}
+
+impl StreakState {
+    /// Roll the counters over to `today`: a new day starts at zero triaged, and a streak
+    /// with a missed day is broken
+    fn roll_over(&mut self, today: NaiveDate) {
+        if self.triage_day != Some(today) {
+            self.triage_day = Some(today);
+            self.triaged_today = 0;
+        }
+        if self
+            .last_zero_day
+            .is_none_or(|day| day < today - Duration::days(1))
+        {
+            self.current_streak = 0;
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Milestone {
+    /// First inbox zero of the day
+    InboxZero {
+        streak: u32,
+        best: bool,
+    },
+    /// The streak reached one of the milestone lengths
+    Streak {
+        days: u32,
+    },
+    DailyGoal {
+        triaged: u32,
+    },
+}
+
+pub struct StreakTracker {
+    path: Option<PathBuf>,
+    state: Mutex<StreakState>,
+}
+
+impl StreakTracker {
+    pub fn load(path: PathBuf) -> Self {
+        let state = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+        Self {
+            path: Some(path),
+            state: Mutex::new(state),
+        }
+    }
+
+    pub fn in_memory() -> Self {
+        Self {
+            path: None,
+            state: Mutex::new(StreakState::default()),
+        }
+    }
+
+    pub fn state(&self, today: NaiveDate) -> StreakState {
+        let mut state = self.state.lock().unwrap().clone();
+        state.roll_over(today);
+        state
+    }
+
+    /// Count an email dealt with; reaching `goal` for the first time today is a milestone
+    pub fn record_triaged(&self, today: NaiveDate, goal: u32) -> Result<Vec<Milestone>, String> {
+        let mut state = self.state.lock().unwrap();
+        state.roll_over(today);
+        state.triaged_today += 1;
+        self.persist(&state)?;
+        Ok(if goal > 0 && state.triaged_today == goal {
+            vec![Milestone::DailyGoal {
+                triaged: state.triaged_today,
+            }]
+        } else {
+            Vec::new()
+        })
+    }
+
+    /// Note the unread count; the first time it hits zero on a day extends the streak
+    pub fn record_unread(&self, unread: u32, today: NaiveDate) -> Result<Vec<Milestone>, String> {
+        let mut state = self.state.lock().unwrap();
+        state.roll_over(today);
+        if unread > 0 || state.last_zero_day == Some(today) {
+            return Ok(Vec::new());
+        }
+        state.current_streak += 1;
+        state.last_zero_day = Some(today);
+        let best = state.current_streak > state.best_streak;
+        state.best_streak = state.best_streak.max(state.current_streak);
+        self.persist(&state)?;
+
+        let mut milestones = vec![Milestone::InboxZero {
+            streak: state.current_streak,
+            best,
+        }];
+        if STREAK_MILESTONES.contains(&state.current_streak) {
+            milestones.push(Milestone::Streak {
+                days: state.current_streak,
+            });
+        }
+        Ok(milestones)
+    }
+
+    fn persist(&self, state: &StreakState) -> Result<(), String> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let contents = serde_json::to_string_pretty(state).map_err(|e| e.to_string())?;
+        std::fs::write(path, contents).map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(n: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 5, n).unwrap()
+    }
+
+    #[test]
+    fn test_streak() {
+        let tracker = StreakTracker::in_memory();
+        assert_eq!(
+            tracker.record_unread(0, day(1)).unwrap(),
+            [Milestone::InboxZero {
+                streak: 1,
+                best: true
+            }]
+        );
+        // Only the first zero of a day counts
+        assert!(tracker.record_unread(0, day(1)).unwrap().is_empty());
+        tracker.record_unread(0, day(2)).unwrap();
+        let third = tracker.record_unread(0, day(3)).unwrap();
+        assert_eq!(third[1], Milestone::Streak { days: 3 });
+
+        // Missing a day breaks the streak but keeps the best
+        assert_eq!(tracker.state(day(5)).current_streak, 0);
+        assert_eq!(
+            tracker.record_unread(0, day(5)).unwrap(),
+            [Milestone::InboxZero {
+                streak: 1,
+                best: false
+            }]
+        );
+        assert_eq!(tracker.state(day(5)).best_streak, 3);
+    }
+
+    #[test]
+    fn test_daily_goal() {
+        let tracker = StreakTracker::in_memory();
+        assert!(tracker.record_triaged(day(1), 2).unwrap().is_empty());
+        assert_eq!(
+            tracker.record_triaged(day(1), 2).unwrap(),
+            [Milestone::DailyGoal { triaged: 2 }]
+        );
+        assert!(tracker.record_triaged(day(1), 2).unwrap().is_empty());
+        assert_eq!(tracker.state(day(2)).triaged_today, 0);
+    }
+}
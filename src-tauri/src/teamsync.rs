@@ -0,0 +1,374 @@
+// SERINA Team Sync
+// Shared rule sets and reply templates pulled from a team's Git repository or HTTP endpoint
+
+use crate::rules::{self, Rule};
+use crate::templates::Template;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Component, Path, PathBuf};
+use std::process::Command;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Shortest wait between two pulls, however the interval is set
+pub const MIN_INTERVAL_MINUTES: u32 = 5;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TeamSource {
+    /// A URL answering GET with the bundle
+    #[default]
+    Http,
+    /// A Git repository with the bundle committed to it
+    Git,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TeamSyncConfig {
+    /// Where the team publishes its bundle; empty turns team sync off
+    pub url: String,
+    pub source: TeamSource,
+    /// Git only: the branch to follow and the bundle's path inside the repository
+    pub branch: String,
+    pub path: String,
+    pub interval_minutes: u32,
+    /// When a local rule or template has the same id as a team one, keep the local one.
+    /// By default the team's copy wins and the local one is hidden.
+    pub local_wins: bool,
+}
+
+impl Default for TeamSyncConfig {
+    fn default() -> Self {
+        Self {
+            url: String::new(),
+            source: TeamSource::Http,
+            branch: "main".to_string(),
+            path: "serina-team.json".to_string(),
+            interval_minutes: 60,
+            local_wins: false,
+        }
+    }
+}
+
+impl TeamSyncConfig {
+    pub fn is_enabled(&self) -> bool {
+        !self.url.trim().is_empty()
+    }
+
+    pub fn interval(&self) -> Duration {
+        Duration::from_secs(self.interval_minutes.max(MIN_INTERVAL_MINUTES) as u64 * 60)
+    }
+}
+
+/// What a team publishes: `{"rules": [...], "templates": [...]}`, in the same shapes the
+/// local stores use
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TeamBundle {
+    pub rules: Vec<Rule>,
+    pub templates: Vec<Template>,
+}
+
+/// Parse a bundle, keeping everything usable and marking it as the team's. Items need an
+/// id, since ids decide which copy wins against local ones, and rules must pass validation.
+/// The second value says what was left out and why.
+pub fn parse_bundle(text: &str) -> Result<(TeamBundle, Vec<String>), String> {
+    let bundle: TeamBundle =
+        serde_json::from_str(text).map_err(|e| format!("Invalid team bundle: {}", e))?;
+    let mut skipped = Vec::new();
+
+    let mut seen = HashSet::new();
+    let rules = bundle
+        .rules
+        .into_iter()
+        .filter_map(|mut rule| {
+            let label = if rule.name.is_empty() {
+                &rule.id
+            } else {
+                &rule.name
+            };
+            let problem = if rule.id.trim().is_empty() {
+                Some("it has no id".to_string())
+            } else if !seen.insert(rule.id.clone()) {
+                Some(format!("the id {} is used twice", rule.id))
+            } else {
+                rules::validate(&rule)
+                    .first()
+                    .map(|issue| format!("{}: {}", issue.path, issue.message))
+            };
+            if let Some(problem) = problem {
+                skipped.push(format!("Rule \"{}\": {}", label, problem));
+                return None;
+            }
+            rule.team = true;
+            Some(rule)
+        })
+        .collect();
+
+    let mut seen = HashSet::new();
+    let templates = bundle
+        .templates
+        .into_iter()
+        .filter_map(|mut template| {
+            let label = if template.name.is_empty() {
+                &template.id
+            } else {
+                &template.name
+            };
+            let problem = if template.id.trim().is_empty() {
+                Some("it has no id".to_string())
+            } else if template.name.trim().is_empty() {
+                Some("it has no name".to_string())
+            } else if !seen.insert(template.id.clone()) {
+                Some(format!("the id {} is used twice", template.id))
+            } else {
+                None
+            };
+            if let Some(problem) = problem {
+                skipped.push(format!("Template \"{}\": {}", label, problem));
+                return None;
+            }
+            template.team = true;
+            Some(template)
+        })
+        .collect();
+
+    Ok((TeamBundle { rules, templates }, skipped))
+}
+
+/// Team items and local ones as a single list, team items first. When both sides have an
+/// item with the same id only one is kept, the local one if `local_wins`.
+pub fn merge<T: Clone>(team: &[T], local: &[T], id: fn(&T) -> &str, local_wins: bool) -> Vec<T> {
+    let in_other = |item: &T, other: &[T]| other.iter().any(|o| id(o) == id(item));
+    team.iter()
+        .filter(|item| !(local_wins && in_other(item, local)))
+        .chain(
+            local
+                .iter()
+                .filter(|item| local_wins || !in_other(item, team)),
+        )
+        .cloned()
+        .collect()
+}
+
+/// The last bundle pulled, kept so the team's rules apply from startup and while offline
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TeamSnapshot {
+    pub bundle: TeamBundle,
+    /// The commit for Git sources, the ETag (if any) for HTTP ones
+    pub revision: String,
+    pub pulled_at: Option<DateTime<Utc>>,
+    pub skipped: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TeamSyncStatus {
+    pub enabled: bool,
+    pub revision: String,
+    pub pulled_at: Option<DateTime<Utc>>,
+    pub rules: usize,
+    pub templates: usize,
+    pub skipped: Vec<String>,
+    /// Why the latest pull failed, cleared by the next one that works
+    pub last_error: Option<String>,
+}
+
+pub struct TeamStore {
+    path: Option<PathBuf>,
+    snapshot: Mutex<TeamSnapshot>,
+    last_error: Mutex<Option<String>>,
+}
+
+impl TeamStore {
+    pub fn load(path: PathBuf) -> Self {
+        let snapshot = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+        Self {
+            path: Some(path),
+            snapshot: Mutex::new(snapshot),
+            last_error: Mutex::new(None),
+        }
+    }
+
+    pub fn in_memory() -> Self {
+        Self {
+            path: None,
+            snapshot: Mutex::new(TeamSnapshot::default()),
+            last_error: Mutex::new(None),
+        }
+    }
+
+    pub fn snapshot(&self) -> TeamSnapshot {
+        self.snapshot.lock().unwrap().clone()
+    }
+
+    pub fn replace(&self, snapshot: TeamSnapshot) -> Result<(), String> {
+        *self.last_error.lock().unwrap() = None;
+        let mut current = self.snapshot.lock().unwrap();
+        *current = snapshot;
+        self.persist(&current)
+    }
+
+    pub fn set_error(&self, error: String) {
+        *self.last_error.lock().unwrap() = Some(error);
+    }
+
+    pub fn status(&self, enabled: bool) -> TeamSyncStatus {
+        let snapshot = self.snapshot.lock().unwrap();
+        TeamSyncStatus {
+            enabled,
+            revision: snapshot.revision.clone(),
+            pulled_at: snapshot.pulled_at,
+            rules: snapshot.bundle.rules.len(),
+            templates: snapshot.bundle.templates.len(),
+            skipped: snapshot.skipped.clone(),
+            last_error: self.last_error.lock().unwrap().clone(),
+        }
+    }
+
+    fn persist(&self, snapshot: &TeamSnapshot) -> Result<(), String> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let contents = serde_json::to_string_pretty(snapshot).map_err(|e| e.to_string())?;
+        std::fs::write(path, contents).map_err(|e| e.to_string())
+    }
+}
+
+/// Addresses git may fetch from. Transports like `ext::` run arbitrary commands, so only
+/// https, ssh and scp-style (`git@host:team/repo.git`) addresses are accepted.
+pub fn is_git_url(url: &str) -> bool {
+    url.starts_with("https://")
+        || url.starts_with("ssh://")
+        || url
+            .strip_prefix("git@")
+            .is_some_and(|rest| rest.contains(':'))
+}
+
+/// Clone the repository into `dir`, or bring an existing clone there up to date with the
+/// branch, and return the commit checked out. Only the latest commit is fetched.
+pub fn git_pull(url: &str, branch: &str, dir: &Path) -> Result<String, String> {
+    if !is_git_url(url) {
+        return Err("Use an https://, ssh:// or git@ address for the team repository".to_string());
+    }
+    if branch.trim().is_empty() || branch.starts_with('-') {
+        return Err(format!("Invalid branch name: {}", branch));
+    }
+    if dir.join(".git").is_dir() {
+        git(dir, &["remote", "set-url", "origin", url])?;
+        git(dir, &["fetch", "--depth", "1", "origin", branch])?;
+        git(dir, &["reset", "--hard", "FETCH_HEAD"])?;
+    } else {
+        let _ = std::fs::remove_dir_all(dir);
+        let parent = dir.parent().unwrap_or(dir);
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        let target = dir.to_string_lossy();
+        git(
+            parent,
+            &[
+                "clone", "--depth", "1", "--branch", branch, "--", url, &target,
+            ],
+        )?;
+    }
+    git(dir, &["rev-parse", "HEAD"]).map(|head| head.trim().to_string())
+}
+
+fn git(dir: &Path, args: &[&str]) -> Result<String, String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["-c", "protocol.ext.allow=never"])
+        .args(args)
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .output()
+        .map_err(|e| format!("Couldn't run git: {}", e))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Read the bundle out of a pulled repository; the path can't leave it
+pub fn read_from_repo(dir: &Path, path: &str) -> Result<String, String> {
+    let relative = Path::new(path);
+    if relative.is_absolute()
+        || relative
+            .components()
+            .any(|c| !matches!(c, Component::Normal(_) | Component::CurDir))
+    {
+        return Err("The bundle path must be inside the repository".to_string());
+    }
+    std::fs::read_to_string(dir.join(relative)).map_err(|e| format!("{}: {}", path, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_bundle() {
+        let text = serde_json::json!({
+            "rules": [
+                {
+                    "id": "team-receipts",
+                    "name": "Receipts",
+                    "condition": {"type": "text", "field": "subject", "op": "contains", "value": "receipt"},
+                    "actions": [{"type": "archive"}]
+                },
+                {"id": "", "name": "No id", "actions": [{"type": "archive"}]},
+                {"id": "team-empty", "name": "Does nothing", "actions": []},
+                {"id": "team-receipts", "name": "Again", "actions": [{"type": "archive"}]}
+            ],
+            "templates": [
+                {"id": "team-thanks", "name": "Thanks", "body": "Thanks, {{sender_first_name}}"},
+                {"id": "team-blank", "body": "Hi"}
+            ]
+        })
+        .to_string();
+        let (bundle, skipped) = parse_bundle(&text).unwrap();
+
+        assert_eq!(bundle.rules.len(), 1);
+        assert!(bundle.rules[0].team);
+        assert_eq!(bundle.templates.len(), 1);
+        assert!(bundle.templates[0].team);
+        assert_eq!(skipped.len(), 4);
+        assert!(skipped[2].contains("used twice"));
+        assert!(parse_bundle("<html>").is_err());
+    }
+
+    #[test]
+    fn test_merge() {
+        let team = vec![("shared", "team"), ("greeting", "team")];
+        let local = vec![("greeting", "local"), ("mine", "local")];
+
+        assert_eq!(
+            merge(&team, &local, |item| item.0, false),
+            vec![("shared", "team"), ("greeting", "team"), ("mine", "local")]
+        );
+        assert_eq!(
+            merge(&team, &local, |item| item.0, true),
+            vec![("shared", "team"), ("greeting", "local"), ("mine", "local")]
+        );
+    }
+
+    #[test]
+    fn test_repo_paths() {
+        assert!(is_git_url("https://git.example.com/team/serina.git"));
+        assert!(is_git_url("git@git.example.com:team/serina.git"));
+        assert!(!is_git_url("ext::sh -c touch% /tmp/pwned"));
+        assert!(!is_git_url("file:///etc"));
+        assert!(git_pull("ext::sh", "main", Path::new("unused")).is_err());
+
+        let dir = std::env::temp_dir();
+        assert!(read_from_repo(&dir, "../passwd").is_err());
+        assert!(read_from_repo(&dir, "/etc/passwd").is_err());
+    }
+}
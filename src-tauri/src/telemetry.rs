@@ -0,0 +1,224 @@
+// SERINA Telemetry
+// Opt-in anonymous usage counts: which features get used and what kinds of errors happen
+
+use crate::http::PinnedClient;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TelemetryConfig {
+    /// Off unless the user opts in
+    pub enabled: bool,
+    /// Where batches are posted during maintenance; empty keeps them queued locally
+    pub endpoint: String,
+}
+
+/// Everything that would be sent: feature and error-category counts, never email content,
+/// addresses or identifiers
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TelemetryBatch {
+    pub app_version: String,
+    pub os: String,
+    /// When the oldest queued count was recorded
+    pub since: Option<DateTime<Utc>>,
+    pub features: BTreeMap<String, u64>,
+    pub errors: BTreeMap<String, u64>,
+}
+
+impl TelemetryBatch {
+    pub fn is_empty(&self) -> bool {
+        self.features.is_empty() && self.errors.is_empty()
+    }
+
+    fn merge(&mut self, other: TelemetryBatch) {
+        self.since = match (self.since, other.since) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        for (feature, count) in other.features {
+            *self.features.entry(feature).or_default() += count;
+        }
+        for (category, count) in other.errors {
+            *self.errors.entry(category).or_default() += count;
+        }
+    }
+}
+
+/// The local queue of counts waiting for the next upload
+pub struct Telemetry {
+    path: Option<PathBuf>,
+    queue: Mutex<TelemetryBatch>,
+}
+
+impl Telemetry {
+    pub fn load(path: PathBuf) -> Self {
+        let queue = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+        Self {
+            path: Some(path),
+            queue: Mutex::new(queue),
+        }
+    }
+
+    pub fn in_memory() -> Self {
+        Self {
+            path: None,
+            queue: Mutex::new(TelemetryBatch::default()),
+        }
+    }
+
+    /// Count one use of a feature. Names are fixed strings so nothing user-typed slips in.
+    pub fn record_feature(&self, feature: &'static str) {
+        self.update(|queue| *queue.features.entry(feature.to_string()).or_default() += 1);
+    }
+
+    /// Count an error by category; the message itself is not kept
+    pub fn record_error(&self, error: &str) {
+        let category = error_category(error);
+        self.update(|queue| *queue.errors.entry(category.to_string()).or_default() += 1);
+    }
+
+    /// Exactly what the next upload would contain
+    pub fn preview(&self) -> TelemetryBatch {
+        TelemetryBatch {
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            os: std::env::consts::OS.to_string(),
+            ..self.queue.lock().unwrap().clone()
+        }
+    }
+
+    /// Take the queued counts for upload; `None` when there's nothing to send
+    pub fn take(&self) -> Option<TelemetryBatch> {
+        let batch = self.preview();
+        if batch.is_empty() {
+            return None;
+        }
+        self.update(|queue| *queue = TelemetryBatch::default());
+        Some(batch)
+    }
+
+    /// Put back a batch whose upload failed
+    pub fn restore(&self, batch: TelemetryBatch) {
+        self.update(|queue| queue.merge(batch));
+    }
+
+    /// Forget everything queued, e.g. after opting out
+    pub fn clear(&self) {
+        self.update(|queue| *queue = TelemetryBatch::default());
+    }
+
+    fn update(&self, edit: impl FnOnce(&mut TelemetryBatch)) {
+        let mut queue = self.queue.lock().unwrap();
+        edit(&mut queue);
+        if queue.since.is_none() && !queue.is_empty() {
+            queue.since = Some(Utc::now());
+        }
+        if queue.is_empty() {
+            queue.since = None;
+        }
+        let Some(path) = &self.path else {
+            return;
+        };
+        match serde_json::to_string(&*queue) {
+            Ok(contents) => {
+                if let Err(e) = std::fs::write(path, contents) {
+                    crate::log_warn!("Failed to save telemetry queue: {}", e);
+                }
+            }
+            Err(e) => crate::log_warn!("Failed to save telemetry queue: {}", e),
+        }
+    }
+}
+
+/// Reduce an error message to a coarse category
+pub fn error_category(error: &str) -> &'static str {
+    let lower = error.to_lowercase();
+    if lower.contains("http 429") || lower.contains("rate limit") {
+        "rate_limited"
+    } else if lower.contains("http 401") || lower.contains("http 403") || lower.contains("api key")
+    {
+        "auth"
+    } else if lower.starts_with("http 5") {
+        "server"
+    } else if lower.starts_with("http 4") {
+        "request"
+    } else if lower.contains("timed out") || lower.contains("timeout") {
+        "timeout"
+    } else if lower.contains("connect") || lower.contains("error sending request") {
+        "network"
+    } else if lower.contains("privacy mode") {
+        "privacy_blocked"
+    } else if lower.contains("budget") {
+        "budget"
+    } else if lower.contains("llm") || lower.contains("provider") {
+        "llm"
+    } else if lower.contains("invalid") || lower.contains("malformed") {
+        "invalid_data"
+    } else {
+        "other"
+    }
+}
+
+/// Post a batch to the telemetry endpoint
+pub async fn upload(
+    client: &PinnedClient,
+    endpoint: &str,
+    batch: &TelemetryBatch,
+) -> Result<(), String> {
+    let response = client.send(client.post(endpoint).json(batch)).await?;
+    if !response.status().is_success() {
+        return Err(format!("HTTP {}", response.status()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_queue() {
+        let telemetry = Telemetry::in_memory();
+        assert!(telemetry.take().is_none());
+
+        telemetry.record_feature("summarize");
+        telemetry.record_feature("summarize");
+        telemetry.record_error("HTTP 500 Internal Server Error: sender@example.com");
+        let preview = telemetry.preview();
+        assert_eq!(preview.features["summarize"], 2);
+        assert_eq!(preview.errors["server"], 1);
+        assert!(!serde_json::to_string(&preview)
+            .unwrap()
+            .contains("example.com"));
+
+        let batch = telemetry.take().unwrap();
+        assert!(telemetry.preview().is_empty());
+        telemetry.record_feature("summarize");
+        telemetry.restore(batch);
+        assert_eq!(telemetry.preview().features["summarize"], 3);
+
+        telemetry.clear();
+        assert!(telemetry.take().is_none());
+    }
+
+    #[test]
+    fn test_error_category() {
+        assert_eq!(error_category("HTTP 429 Too Many Requests"), "rate_limited");
+        assert_eq!(error_category("API key was rejected"), "auth");
+        assert_eq!(
+            error_category("error sending request for url (http://127.0.0.1:8000/emails)"),
+            "network"
+        );
+        assert_eq!(
+            error_category("All LLM providers failed (openai: HTTP 503)"),
+            "llm"
+        );
+        assert_eq!(error_category("Something odd"), "other");
+    }
+}
@@ -0,0 +1,292 @@
+// SERINA Templates
+// Reusable reply templates and snippets with {{variable}} placeholders
+
+use crate::teamsync;
+use crate::utils::EmailData;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Template {
+    pub id: String,
+    pub name: String,
+    pub body: String,
+    /// Pulled from the team's shared templates rather than written here
+    pub team: bool,
+}
+
+/// Values substituted into `{{name}}` placeholders
+pub type TemplateVars = HashMap<String, String>;
+
+/// Variables describing the email being answered and the current user
+pub fn vars_for_email(email: &EmailData, my_name: &str) -> TemplateVars {
+    let sender_name = sender_display_name(email);
+    let first_name = sender_name
+        .split_whitespace()
+        .next()
+        .unwrap_or_default()
+        .to_string();
+
+    let mut vars = TemplateVars::new();
+    vars.insert("sender_name".to_string(), sender_name);
+    vars.insert("sender_first_name".to_string(), first_name);
+    vars.insert("sender_email".to_string(), email.sender_email.clone());
+    vars.insert("subject".to_string(), email.subject.clone());
+    vars.insert("my_name".to_string(), my_name.to_string());
+    vars
+}
+
+/// Replace `{{name}}` placeholders; unknown ones are left in place so pre-send checks catch them
+pub fn render(body: &str, vars: &TemplateVars) -> String {
+    let mut output = String::with_capacity(body.len());
+    let mut rest = body;
+
+    while let Some(start) = rest.find("{{") {
+        output.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        match after.find("}}") {
+            Some(end) => {
+                let key = after[..end].trim();
+                match vars.get(key) {
+                    Some(value) => output.push_str(value),
+                    None => output.push_str(&rest[start..start + 2 + end + 2]),
+                }
+                rest = &after[end + 2..];
+            }
+            None => {
+                output.push_str(&rest[start..]);
+                rest = "";
+            }
+        }
+    }
+    output.push_str(rest);
+    output
+}
+
+/// Sender's display name, falling back to the local part of their address
+fn sender_display_name(email: &EmailData) -> String {
+    let name = email.sender.trim();
+    if !name.is_empty() && !name.contains('@') {
+        return name.to_string();
+    }
+    email
+        .sender_email
+        .split('@')
+        .next()
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// Templates persisted as JSON under the config directory, alongside the team's shared ones
+pub struct TemplateStore {
+    path: Option<PathBuf>,
+    templates: Mutex<Vec<Template>>,
+    /// The team's templates and whether local ones with the same id replace them
+    team: Mutex<(Vec<Template>, bool)>,
+}
+
+impl TemplateStore {
+    pub fn load(path: PathBuf) -> Self {
+        let templates = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+        Self {
+            path: Some(path),
+            templates: Mutex::new(templates),
+            team: Mutex::new((Vec::new(), false)),
+        }
+    }
+
+    pub fn in_memory() -> Self {
+        Self {
+            path: None,
+            templates: Mutex::new(Vec::new()),
+            team: Mutex::new((Vec::new(), false)),
+        }
+    }
+
+    /// Swap in the team's shared templates; they aren't saved with the local ones
+    pub fn set_team(&self, templates: Vec<Template>, local_wins: bool) {
+        *self.team.lock().unwrap() = (templates, local_wins);
+    }
+
+    /// Team and local templates, with only the winning copy of any id both sides use
+    pub fn list(&self) -> Vec<Template> {
+        let (team, local_wins) = &*self.team.lock().unwrap();
+        let local = self.templates.lock().unwrap();
+        teamsync::merge(team, &local, |t| &t.id, *local_wins)
+    }
+
+    /// Only the templates written here, e.g. for exporting settings
+    pub fn local(&self) -> Vec<Template> {
+        self.templates.lock().unwrap().clone()
+    }
+
+    pub fn get(&self, id: &str) -> Option<Template> {
+        self.list().into_iter().find(|t| t.id == id)
+    }
+
+    /// Insert or update a template; a new id is assigned when it has none. Team templates can
+    /// only be changed here when local copies win, and saving one makes that local copy.
+    pub fn save(&self, mut template: Template) -> Result<Template, String> {
+        if template.name.trim().is_empty() {
+            return Err("Template name is required".to_string());
+        }
+        self.ensure_local(&template.id)?;
+        template.team = false;
+        let mut templates = self.templates.lock().unwrap();
+        if template.id.is_empty() {
+            let base = format!("tpl-{}", chrono::Utc::now().timestamp_millis());
+            let mut id = base.clone();
+            let mut n = 1;
+            while templates.iter().any(|t| t.id == id) {
+                n += 1;
+                id = format!("{}-{}", base, n);
+            }
+            template.id = id;
+        }
+        match templates.iter_mut().find(|t| t.id == template.id) {
+            Some(existing) => *existing = template.clone(),
+            None => templates.push(template.clone()),
+        }
+        self.persist(&templates)?;
+        Ok(template)
+    }
+
+    /// Swap in a whole template set, e.g. from an imported settings bundle
+    pub fn replace_all(&self, new_templates: Vec<Template>) -> Result<(), String> {
+        let mut templates = self.templates.lock().unwrap();
+        *templates = new_templates;
+        self.persist(&templates)
+    }
+
+    pub fn delete(&self, id: &str) -> Result<bool, String> {
+        self.ensure_local(id)?;
+        let mut templates = self.templates.lock().unwrap();
+        let before = templates.len();
+        templates.retain(|t| t.id != id);
+        let removed = templates.len() != before;
+        if removed {
+            self.persist(&templates)?;
+        }
+        Ok(removed)
+    }
+
+    /// Refuse changes to an id the team's copy owns
+    fn ensure_local(&self, id: &str) -> Result<(), String> {
+        let (team, local_wins) = &*self.team.lock().unwrap();
+        match team.iter().find(|t| t.id == id) {
+            Some(t) if !local_wins => Err(format!(
+                "\"{}\" is one of your team's shared templates and can't be changed here",
+                t.name
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    fn persist(&self, templates: &[Template]) -> Result<(), String> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let contents = serde_json::to_string_pretty(templates).map_err(|e| e.to_string())?;
+        std::fs::write(path, contents).map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn email() -> EmailData {
+        EmailData {
+            subject: "Invoice #42".to_string(),
+            sender: "Jane Doe".to_string(),
+            sender_email: "jane@example.com".to_string(),
+            ..EmailData::sample("1")
+        }
+    }
+
+    #[test]
+    fn test_render() {
+        let vars = vars_for_email(&email(), "Sam");
+        let body =
+            "Hi {{sender_first_name}},\n\nThanks for {{ subject }}.\n\n{{my_name}} {{unknown}} {{";
+        assert_eq!(
+            render(body, &vars),
+            "Hi Jane,\n\nThanks for Invoice #42.\n\nSam {{unknown}} {{"
+        );
+
+        let bare = EmailData {
+            sender: "jane@example.com".to_string(),
+            ..email()
+        };
+        assert_eq!(
+            render("{{sender_name}}", &vars_for_email(&bare, "")),
+            "jane"
+        );
+    }
+
+    #[test]
+    fn test_store() {
+        let store = TemplateStore::in_memory();
+        assert!(store.save(Template::default()).is_err());
+
+        let saved = store
+            .save(Template {
+                name: "Invoice received".to_string(),
+                body: "Thanks, {{sender_name}}".to_string(),
+                ..Default::default()
+            })
+            .unwrap();
+        assert!(!saved.id.is_empty());
+
+        store
+            .save(Template {
+                body: "Got it".to_string(),
+                ..saved.clone()
+            })
+            .unwrap();
+        assert_eq!(store.list().len(), 1);
+        assert_eq!(store.get(&saved.id).unwrap().body, "Got it");
+        assert!(store.delete(&saved.id).unwrap());
+        assert!(store.list().is_empty());
+    }
+
+    #[test]
+    fn test_team_templates() {
+        let store = TemplateStore::in_memory();
+        let shared = Template {
+            id: "team-thanks".to_string(),
+            name: "Thanks".to_string(),
+            body: "Thanks from all of us".to_string(),
+            team: true,
+        };
+        store.set_team(vec![shared.clone()], false);
+        assert_eq!(store.get("team-thanks"), Some(shared.clone()));
+        assert!(store.local().is_empty());
+        assert!(store
+            .save(Template {
+                body: "Thanks from me".to_string(),
+                ..shared.clone()
+            })
+            .is_err());
+        assert!(store.delete("team-thanks").is_err());
+
+        store.set_team(vec![shared.clone()], true);
+        let mine = store
+            .save(Template {
+                body: "Thanks from me".to_string(),
+                ..shared
+            })
+            .unwrap();
+        assert!(!mine.team);
+        assert_eq!(store.list(), vec![mine]);
+    }
+}
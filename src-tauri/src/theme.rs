@@ -0,0 +1,187 @@
+// SERINA Themes
+// Light/dark mode following the OS or fixed, named color presets and a custom accent
+
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ThemeMode {
+    /// Follow the OS appearance, switching live when it changes
+    #[default]
+    System,
+    Light,
+    Dark,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ThemePreset {
+    pub name: &'static str,
+    /// "#rrggbb"
+    pub accent: &'static str,
+}
+
+pub const DEFAULT_PRESET: &str = "serina";
+
+pub const PRESETS: &[ThemePreset] = &[
+    ThemePreset {
+        name: DEFAULT_PRESET,
+        accent: "#06b6d4",
+    },
+    ThemePreset {
+        name: "ocean",
+        accent: "#3b82f6",
+    },
+    ThemePreset {
+        name: "forest",
+        accent: "#10b981",
+    },
+    ThemePreset {
+        name: "sunset",
+        accent: "#f97316",
+    },
+    ThemePreset {
+        name: "grape",
+        accent: "#8b5cf6",
+    },
+    ThemePreset {
+        name: "mono",
+        accent: "#6b7280",
+    },
+];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ThemeConfig {
+    pub mode: ThemeMode,
+    pub preset: String,
+    /// "#rrggbb" overriding the preset's accent; empty uses the preset's
+    pub accent_color: String,
+}
+
+impl Default for ThemeConfig {
+    fn default() -> Self {
+        Self {
+            mode: ThemeMode::System,
+            preset: DEFAULT_PRESET.to_string(),
+            accent_color: String::new(),
+        }
+    }
+}
+
+/// What the UI should draw with right now
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ResolvedTheme {
+    pub mode: ThemeMode,
+    pub dark: bool,
+    pub preset: String,
+    pub accent: String,
+}
+
+pub fn preset(name: &str) -> Option<&'static ThemePreset> {
+    PRESETS
+        .iter()
+        .find(|preset| preset.name.eq_ignore_ascii_case(name))
+}
+
+/// "#rrggbb", normalized to lower case
+pub fn parse_color(value: &str) -> Option<String> {
+    let hex = value.trim().strip_prefix('#')?;
+    (hex.len() == 6 && hex.chars().all(|c| c.is_ascii_hexdigit()))
+        .then(|| format!("#{}", hex.to_ascii_lowercase()))
+}
+
+/// Resolve the configured theme against the OS appearance; an unknown OS setting counts
+/// as dark, SERINA's original look
+pub fn resolve(config: &ThemeConfig, system_dark: Option<bool>) -> ResolvedTheme {
+    let preset = preset(&config.preset).unwrap_or(&PRESETS[0]);
+    ResolvedTheme {
+        mode: config.mode,
+        dark: match config.mode {
+            ThemeMode::System => system_dark.unwrap_or(true),
+            ThemeMode::Light => false,
+            ThemeMode::Dark => true,
+        },
+        preset: preset.name.to_string(),
+        accent: parse_color(&config.accent_color).unwrap_or_else(|| preset.accent.to_string()),
+    }
+}
+
+/// Turn the old `ui.dark_mode` switch into a fixed theme mode, unless a theme is already set
+pub fn migrate_legacy(config: &mut serde_json::Value) {
+    let Some(ui) = config.get_mut("ui").and_then(|ui| ui.as_object_mut()) else {
+        return;
+    };
+    let Some(dark_mode) = ui.remove("dark_mode") else {
+        return;
+    };
+    if !ui.contains_key("theme") {
+        let mode = if dark_mode.as_bool().unwrap_or(true) {
+            ThemeMode::Dark
+        } else {
+            ThemeMode::Light
+        };
+        ui.insert(
+            "theme".to_string(),
+            serde_json::json!({ "mode": mode, "preset": DEFAULT_PRESET }),
+        );
+    }
+}
+
+/// The OS appearance as last reported by the main window
+#[derive(Default)]
+pub struct SystemTheme {
+    dark: Mutex<Option<bool>>,
+}
+
+impl SystemTheme {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn dark(&self) -> Option<bool> {
+        *self.dark.lock().unwrap()
+    }
+
+    /// Record the OS appearance; true when it changed
+    pub fn set_dark(&self, dark: bool) -> bool {
+        self.dark.lock().unwrap().replace(dark) != Some(dark)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve() {
+        let config = ThemeConfig::default();
+        assert!(resolve(&config, Some(true)).dark);
+        assert!(!resolve(&config, Some(false)).dark);
+        assert_eq!(resolve(&config, None).accent, "#06b6d4");
+
+        let custom = ThemeConfig {
+            mode: ThemeMode::Light,
+            preset: "Forest".to_string(),
+            accent_color: "#FF0088".to_string(),
+        };
+        let theme = resolve(&custom, Some(true));
+        assert!(!theme.dark);
+        assert_eq!(theme.preset, "forest");
+        assert_eq!(theme.accent, "#ff0088");
+        assert_eq!(parse_color("red"), None);
+    }
+
+    #[test]
+    fn test_migrate_legacy() {
+        let mut config = serde_json::json!({ "ui": { "dark_mode": false } });
+        migrate_legacy(&mut config);
+        assert_eq!(config["ui"]["theme"]["mode"], "light");
+        assert!(config["ui"].get("dark_mode").is_none());
+
+        let mut both =
+            serde_json::json!({ "ui": { "dark_mode": true, "theme": { "mode": "system" } } });
+        migrate_legacy(&mut both);
+        assert_eq!(both["ui"]["theme"]["mode"], "system");
+    }
+}
@@ -0,0 +1,170 @@
+// SERINA Token Utilities
+// Token counting and context-window fitting for LLM prompts
+
+use crate::llm::resolve_model;
+use crate::utils::ProviderConfig;
+use tiktoken_rs::tokenizer::{get_tokenizer, Tokenizer};
+use tiktoken_rs::CoreBPE;
+
+/// Tokens kept free for the system prompt, instructions and the model's answer
+const RESERVED_TOKENS: usize = 1024;
+
+/// Never budget less than this, even for tiny context windows
+const MIN_INPUT_TOKENS: usize = 512;
+
+/// Run `f` with the BPE encoder matching `model`
+fn with_encoder<T>(model: &str, f: impl FnOnce(&CoreBPE) -> T) -> T {
+    let bpe = match get_tokenizer(model) {
+        Some(Tokenizer::O200kBase) => tiktoken_rs::o200k_base_singleton(),
+        Some(Tokenizer::P50kBase) => tiktoken_rs::p50k_base_singleton(),
+        Some(Tokenizer::R50kBase) | Some(Tokenizer::Gpt2) => tiktoken_rs::r50k_base_singleton(),
+        // Non-OpenAI models get a cl100k approximation
+        _ => tiktoken_rs::cl100k_base_singleton(),
+    };
+    let guard = bpe.lock();
+    f(&guard)
+}
+
+/// Count the tokens `text` takes up for `model`
+pub fn count_tokens(text: &str, model: &str) -> usize {
+    with_encoder(model, |bpe| bpe.encode_ordinary(text).len())
+}
+
+/// Context window size of a model, including a few non-OpenAI families
+pub fn context_window(model: &str) -> usize {
+    let name = model.rsplit('/').next().unwrap_or(model);
+    if name.starts_with("claude-3") {
+        200_000
+    } else if name.starts_with("llama3") || name.starts_with("llama-3") {
+        8_192
+    } else {
+        tiktoken_rs::model::get_context_size(name)
+    }
+}
+
+/// Input tokens that fit every model in a provider chain, so failover never overflows
+pub fn input_budget(chain: &[ProviderConfig]) -> usize {
+    chain
+        .iter()
+        .map(|p| context_window(&resolve_model(p)).saturating_sub(RESERVED_TOKENS))
+        .min()
+        .unwrap_or(0)
+        .max(MIN_INPUT_TOKENS)
+}
+
+/// Cut `text` down to at most `max_tokens` tokens
+pub fn truncate_to_tokens(text: &str, model: &str, max_tokens: usize) -> String {
+    with_encoder(model, |bpe| {
+        let tokens = bpe.encode_ordinary(text);
+        if tokens.len() <= max_tokens {
+            return text.to_string();
+        }
+        // A cut inside a multi-byte character can't be decoded, and the decoded text may encode
+        // differently than the tokens it came from, so back off until the cut both decodes and
+        // fits
+        (0..=max_tokens)
+            .rev()
+            .filter_map(|end| bpe.decode(tokens[..end].to_vec()).ok())
+            .find(|cut| bpe.encode_ordinary(cut).len() <= max_tokens)
+            .unwrap_or_default()
+    })
+}
+
+/// Split `text` into paragraph-aligned chunks of at most `max_tokens` tokens each
+pub fn chunk_text(text: &str, model: &str, max_tokens: usize) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    let mut current_tokens = 0;
+
+    for paragraph in text.split("\n\n").filter(|p| !p.trim().is_empty()) {
+        let paragraph_tokens = count_tokens(paragraph, model);
+
+        if paragraph_tokens > max_tokens {
+            if !current.is_empty() {
+                chunks.push(std::mem::take(&mut current));
+                current_tokens = 0;
+            }
+            let mut rest = paragraph.to_string();
+            while count_tokens(&rest, model) > max_tokens {
+                let head = truncate_to_tokens(&rest, model, max_tokens);
+                if head.is_empty() {
+                    break;
+                }
+                rest = rest[head.len()..].to_string();
+                chunks.push(head);
+            }
+            if !rest.trim().is_empty() {
+                current_tokens = count_tokens(&rest, model);
+                current = rest;
+            }
+            continue;
+        }
+
+        if current_tokens + paragraph_tokens > max_tokens && !current.is_empty() {
+            chunks.push(std::mem::take(&mut current));
+            current_tokens = 0;
+        }
+        if !current.is_empty() {
+            current.push_str("\n\n");
+        }
+        current.push_str(paragraph);
+        current_tokens += paragraph_tokens;
+    }
+
+    if !current.is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_count_tokens() {
+        assert_eq!(count_tokens("", "gpt-3.5-turbo"), 0);
+        assert!(count_tokens("Hello there, how are you?", "gpt-3.5-turbo") > 3);
+        // Unknown models fall back to an approximation instead of failing
+        assert!(count_tokens("Hello there", "llama3") > 0);
+    }
+
+    #[test]
+    fn test_context_window() {
+        assert_eq!(context_window("anthropic/claude-3-haiku"), 200_000);
+        assert_eq!(context_window("llama3"), 8_192);
+        assert!(context_window("gpt-4o") > 100_000);
+    }
+
+    #[test]
+    fn test_truncate_to_tokens() {
+        // Most CJK characters cost a token or more each, and several split across tokens
+        let text = "電子メールの返信を準備しています。".repeat(50);
+        for max_tokens in [1, 7, 50, 333] {
+            let cut = truncate_to_tokens(&text, "gpt-3.5-turbo", max_tokens);
+            assert!(!cut.is_empty() || max_tokens < 2);
+            assert!(text.starts_with(&cut));
+            assert!(count_tokens(&cut, "gpt-3.5-turbo") <= max_tokens);
+        }
+        assert_eq!(truncate_to_tokens("short", "gpt-3.5-turbo", 10), "short");
+    }
+
+    #[test]
+    fn test_chunk_text() {
+        let paragraph = "word ".repeat(100);
+        let text = [paragraph.trim(); 5].join("\n\n");
+        let chunks = chunk_text(&text, "gpt-3.5-turbo", 250);
+        assert!(chunks.len() >= 3);
+        for chunk in &chunks {
+            assert!(count_tokens(chunk, "gpt-3.5-turbo") <= 250);
+        }
+
+        // A single oversized paragraph is split as well
+        let long = "word ".repeat(1000);
+        let chunks = chunk_text(&long, "gpt-3.5-turbo", 300);
+        assert!(chunks.len() >= 4);
+        assert!(chunks
+            .iter()
+            .all(|c| count_tokens(c, "gpt-3.5-turbo") <= 300));
+    }
+}
@@ -0,0 +1,163 @@
+// SERINA Keyboard Triage
+// Selection state for j/k-style triage, shared by in-app and global shortcuts
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TriageAction {
+    Next,
+    Previous,
+    Archive,
+    Snooze,
+    /// Open the reply composer for the selected email
+    Reply,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TriageConfig {
+    /// Minutes used by the snooze action
+    pub snooze_minutes: u32,
+    /// System-wide accelerators such as "CmdOrCtrl+Alt+J", by action; in-app keys are bound by the UI
+    pub global_shortcuts: BTreeMap<TriageAction, String>,
+}
+
+impl Default for TriageConfig {
+    fn default() -> Self {
+        Self {
+            snooze_minutes: 60,
+            global_shortcuts: BTreeMap::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TriageSelection {
+    pub email_id: Option<String>,
+    pub index: usize,
+    pub total: usize,
+}
+
+#[derive(Default)]
+struct Queue {
+    ids: Vec<String>,
+    index: usize,
+}
+
+impl Queue {
+    fn selection(&self) -> TriageSelection {
+        TriageSelection {
+            email_id: self.ids.get(self.index).cloned(),
+            index: self.index,
+            total: self.ids.len(),
+        }
+    }
+}
+
+/// The emails being triaged and which one is selected
+#[derive(Default)]
+pub struct TriageState {
+    queue: Mutex<Queue>,
+}
+
+impl TriageState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn selection(&self) -> TriageSelection {
+        self.queue.lock().unwrap().selection()
+    }
+
+    /// Replace the email list, keeping the selected email selected when it is still there
+    pub fn set_emails(&self, ids: Vec<String>) -> TriageSelection {
+        let mut queue = self.queue.lock().unwrap();
+        let selected = queue.ids.get(queue.index).cloned();
+        let index = selected
+            .and_then(|id| ids.iter().position(|other| *other == id))
+            .unwrap_or_else(|| queue.index.min(ids.len().saturating_sub(1)));
+        *queue = Queue { ids, index };
+        queue.selection()
+    }
+
+    /// Move the selection by one; stops at either end rather than wrapping
+    pub fn step(&self, forward: bool) -> TriageSelection {
+        let mut queue = self.queue.lock().unwrap();
+        if forward {
+            queue.index = (queue.index + 1).min(queue.ids.len().saturating_sub(1));
+        } else {
+            queue.index = queue.index.saturating_sub(1);
+        }
+        queue.selection()
+    }
+
+    pub fn select(&self, email_id: &str) -> Result<TriageSelection, String> {
+        let mut queue = self.queue.lock().unwrap();
+        queue.index = queue
+            .ids
+            .iter()
+            .position(|id| id == email_id)
+            .ok_or_else(|| format!("Email {} is not in the triage list", email_id))?;
+        Ok(queue.selection())
+    }
+
+    /// Drop an email that was archived or snoozed; the next one down becomes selected
+    pub fn remove(&self, email_id: &str) -> TriageSelection {
+        let mut queue = self.queue.lock().unwrap();
+        if let Some(position) = queue.ids.iter().position(|id| id == email_id) {
+            queue.ids.remove(position);
+            if position < queue.index || queue.index >= queue.ids.len() {
+                queue.index = queue.index.saturating_sub(1);
+            }
+        }
+        queue.selection()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ids(names: &[&str]) -> Vec<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    #[test]
+    fn test_navigation() {
+        let state = TriageState::new();
+        assert_eq!(state.selection().email_id, None);
+
+        state.set_emails(ids(&["a", "b", "c"]));
+        assert_eq!(state.step(false).email_id.as_deref(), Some("a"));
+        state.step(true);
+        state.step(true);
+        assert_eq!(state.step(true).email_id.as_deref(), Some("c"));
+
+        // New mail arriving above keeps the same email selected
+        let selection = state.set_emails(ids(&["new", "a", "b", "c"]));
+        assert_eq!(selection.email_id.as_deref(), Some("c"));
+        assert_eq!(selection.index, 3);
+        assert!(state.select("missing").is_err());
+    }
+
+    #[test]
+    fn test_remove_moves_to_next() {
+        let state = TriageState::new();
+        state.set_emails(ids(&["a", "b", "c"]));
+        state.select("b").unwrap();
+        assert_eq!(state.remove("b").email_id.as_deref(), Some("c"));
+        assert_eq!(state.remove("c").email_id.as_deref(), Some("a"));
+        assert_eq!(state.remove("a").total, 0);
+
+        let config: TriageConfig =
+            serde_json::from_str(r#"{"global_shortcuts": {"next": "CmdOrCtrl+Alt+J"}}"#).unwrap();
+        assert_eq!(
+            config.global_shortcuts[&TriageAction::Next],
+            "CmdOrCtrl+Alt+J"
+        );
+        assert_eq!(config.snooze_minutes, 60);
+    }
+}
@@ -0,0 +1,159 @@
+// SERINA Upcoming
+// Everything waiting on a timer — snoozes, deadline reminders, scheduled sends and the focus
+// digest — gathered into one list, soonest first
+
+use crate::dates;
+use crate::focus::FocusSession;
+use crate::snooze::SnoozedEmail;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Id of the focus digest item; there is at most one session
+pub const FOCUS_ITEM_ID: &str = "focus";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScheduledKind {
+    /// An email coming back to the inbox
+    Snooze,
+    /// A reminder ahead of a deadline found in an email
+    DeadlineReminder,
+    /// A message waiting in the Outbox for its deferred delivery time
+    ScheduledSend,
+    /// The catch-up digest delivered when the focus session ends
+    FocusDigest,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScheduledItem {
+    pub kind: ScheduledKind,
+    /// Email id for snoozes and reminders, Outbox entry id for sends, `FOCUS_ITEM_ID` for the
+    /// digest
+    pub id: String,
+    /// Subject of the email or message; empty for the digest
+    pub title: String,
+    /// Recipients of a scheduled send
+    pub detail: String,
+    pub due: DateTime<Utc>,
+    /// The deadline a reminder leads up to
+    pub deadline: Option<DateTime<Utc>>,
+}
+
+/// An Outbox message as listed by the backend
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct OutgoingEmail {
+    pub id: String,
+    #[serde(default)]
+    pub subject: String,
+    #[serde(default)]
+    pub recipients: String,
+    /// Deferred delivery time; unset for messages that go out on the next send
+    #[serde(default)]
+    pub deferred_until: Option<String>,
+}
+
+/// Merge the sources into one list, soonest first. `subject` looks up the subject of a snoozed
+/// email; Outbox messages without a deferred delivery time aren't scheduled and are left out.
+pub fn collect(
+    snoozes: &[SnoozedEmail],
+    subject: impl Fn(&str) -> Option<String>,
+    outgoing: &[OutgoingEmail],
+    focus: Option<&FocusSession>,
+) -> Vec<ScheduledItem> {
+    let snoozed = snoozes.iter().map(|entry| ScheduledItem {
+        kind: if entry.deadline.is_some() {
+            ScheduledKind::DeadlineReminder
+        } else {
+            ScheduledKind::Snooze
+        },
+        id: entry.email_id.clone(),
+        title: subject(&entry.email_id).unwrap_or_default(),
+        detail: String::new(),
+        due: entry.until,
+        deadline: entry.deadline,
+    });
+    let sends = outgoing.iter().filter_map(|email| {
+        let due = dates::parse_received_time(email.deferred_until.as_deref()?)?;
+        Some(ScheduledItem {
+            kind: ScheduledKind::ScheduledSend,
+            id: email.id.clone(),
+            title: email.subject.clone(),
+            detail: email.recipients.clone(),
+            due,
+            deadline: None,
+        })
+    });
+    let digest = focus.map(|session| ScheduledItem {
+        kind: ScheduledKind::FocusDigest,
+        id: FOCUS_ITEM_ID.to_string(),
+        title: String::new(),
+        detail: String::new(),
+        due: session.ends_at,
+        deadline: None,
+    });
+
+    let mut items: Vec<_> = snoozed.chain(sends).chain(digest).collect();
+    items.sort_by_key(|item| item.due);
+    items
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+
+    #[test]
+    fn test_collect() {
+        let now = Utc.with_ymd_and_hms(2024, 5, 14, 9, 0, 0).unwrap();
+        let snoozes = [
+            SnoozedEmail {
+                email_id: "a".to_string(),
+                until: now + Duration::hours(3),
+                deadline: None,
+            },
+            SnoozedEmail {
+                email_id: "b".to_string(),
+                until: now + Duration::hours(1),
+                deadline: Some(now + Duration::hours(2)),
+            },
+        ];
+        let outgoing = [
+            OutgoingEmail {
+                id: "out-1".to_string(),
+                subject: "Weekly update".to_string(),
+                recipients: "team@example.com".to_string(),
+                deferred_until: Some("2024-05-14T11:00:00Z".to_string()),
+            },
+            OutgoingEmail {
+                id: "out-2".to_string(),
+                subject: "Stuck".to_string(),
+                ..Default::default()
+            },
+        ];
+        let session = FocusSession {
+            started_at: now,
+            ends_at: now + Duration::minutes(30),
+        };
+        let subject = |id: &str| (id == "a").then(|| "Budget".to_string());
+
+        let items = collect(&snoozes, subject, &outgoing, Some(&session));
+        let kinds: Vec<_> = items.iter().map(|item| item.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                ScheduledKind::FocusDigest,
+                ScheduledKind::DeadlineReminder,
+                ScheduledKind::ScheduledSend,
+                ScheduledKind::Snooze,
+            ]
+        );
+        assert_eq!(items[2].detail, "team@example.com");
+        assert_eq!(items[3].title, "Budget");
+        assert_eq!(items[1].title, "");
+        assert_eq!(
+            serde_json::to_value(ScheduledKind::ScheduledSend).unwrap(),
+            "scheduled_send"
+        );
+        assert!(collect(&[], |_| None, &outgoing[1..], None).is_empty());
+    }
+}
@@ -0,0 +1,70 @@
+// SERINA Updates
+// Release channels and update manifest locations for the Tauri updater
+
+use serde::{Deserialize, Serialize};
+
+const RELEASES_URL: &str = "https://github.com/lucianoaf8/serina-email/releases";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReleaseChannel {
+    #[default]
+    Stable,
+    Beta,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UpdateConfig {
+    pub channel: ReleaseChannel,
+    /// Check for updates during daily maintenance and notify when one is found
+    pub auto_check: bool,
+}
+
+impl Default for UpdateConfig {
+    fn default() -> Self {
+        Self {
+            channel: ReleaseChannel::Stable,
+            auto_check: true,
+        }
+    }
+}
+
+/// Update manifest URL for a channel
+pub fn endpoint(channel: ReleaseChannel) -> String {
+    match channel {
+        ReleaseChannel::Stable => format!("{}/latest/download/latest.json", RELEASES_URL),
+        ReleaseChannel::Beta => format!("{}/download/beta/latest.json", RELEASES_URL),
+    }
+}
+
+/// Stable users never get pre-release builds, even if a manifest points at one
+pub fn accepts(channel: ReleaseChannel, version: &str) -> bool {
+    channel == ReleaseChannel::Beta || !version.contains('-')
+}
+
+/// Result of an update check, as shown in the settings window
+#[derive(Debug, Clone, Serialize)]
+pub struct UpdateInfo {
+    pub available: bool,
+    pub current_version: String,
+    pub latest_version: String,
+    pub notes: Option<String>,
+    pub channel: ReleaseChannel,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_channels() {
+        assert!(accepts(ReleaseChannel::Stable, "1.2.0"));
+        assert!(!accepts(ReleaseChannel::Stable, "1.3.0-beta.1"));
+        assert!(accepts(ReleaseChannel::Beta, "1.3.0-beta.1"));
+        assert_ne!(
+            endpoint(ReleaseChannel::Stable),
+            endpoint(ReleaseChannel::Beta)
+        );
+    }
+}
@@ -0,0 +1,155 @@
+// SERINA Webview Security
+// The CSP every window runs under, and where windows may navigate: app pages stay, web and
+// mailto links go to the system browser, anything else is blocked
+
+use url::Url;
+
+/// Injected into every app page (`tauri.security.csp`). Scripts only from the bundle, mail
+/// documents only through the sanitizing `serina-mail` protocol, nothing posted anywhere.
+pub const APP_CSP: &str = "default-src 'self'; script-src 'self'; \
+                           style-src 'self' 'unsafe-inline'; \
+                           img-src 'self' data: asset: https://asset.localhost; font-src 'self' data:; \
+                           connect-src 'self' ipc: https://ipc.localhost; \
+                           frame-src serina-mail: https://serina-mail.localhost; \
+                           object-src 'none'; base-uri 'self'; form-action 'none'";
+
+/// `APP_CSP` plus the dev server's hot-reload socket (`tauri.security.devCsp`)
+pub const DEV_CSP: &str = "default-src 'self'; script-src 'self'; \
+                           style-src 'self' 'unsafe-inline'; \
+                           img-src 'self' data: asset: https://asset.localhost; font-src 'self' data:; \
+                           connect-src 'self' ipc: https://ipc.localhost ws://localhost:1420; \
+                           frame-src serina-mail: https://serina-mail.localhost; \
+                           object-src 'none'; base-uri 'self'; form-action 'none'";
+
+/// Where `npm run dev` serves the frontend (`build.devPath`)
+pub const DEV_ORIGIN: &str = "http://localhost:1420";
+
+/// Longest link handed to the browser
+const MAX_URL_LEN: usize = 2048;
+
+/// What to do when a window is about to load a URL
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Navigation {
+    /// One of SERINA's own pages
+    Allow,
+    /// A web or mail link: open it in the system browser and keep the window where it is
+    OpenExternal(Url),
+    Block,
+}
+
+/// Whether `url` is served by the app itself: the bundled frontend, the mail view and asset
+/// protocols, and blank frames. Linux and macOS use custom schemes, Windows `https://<x>.localhost`.
+pub fn is_app_url(url: &Url) -> bool {
+    match url.scheme() {
+        "tauri" | "asset" | "serina-mail" => true,
+        "about" => matches!(url.path(), "blank" | "srcdoc"),
+        "https" => matches!(
+            url.host_str(),
+            Some("tauri.localhost" | "asset.localhost" | "serina-mail.localhost")
+        ),
+        "http" if cfg!(debug_assertions) => {
+            url.origin() == Url::parse(DEV_ORIGIN).expect("valid dev origin").origin()
+        }
+        _ => false,
+    }
+}
+
+/// Where a window may go
+pub fn navigation(url: &Url) -> Navigation {
+    if is_app_url(url) {
+        return Navigation::Allow;
+    }
+    match external_url(url.as_str()) {
+        Ok(url) => Navigation::OpenExternal(url),
+        Err(_) => Navigation::Block,
+    }
+}
+
+/// Check a link before it goes to the system browser: only http(s) and mailto, no embedded
+/// credentials (`https://bank.example@evil.example`), nothing on this machine
+pub fn external_url(raw: &str) -> Result<Url, String> {
+    let raw = raw.trim();
+    if raw.len() > MAX_URL_LEN {
+        return Err("Link is too long".to_string());
+    }
+    if raw.chars().any(|c| c.is_control() || c.is_whitespace()) {
+        return Err("Link contains spaces or control characters".to_string());
+    }
+    let url = Url::parse(raw).map_err(|e| format!("Not a valid link: {}", e))?;
+    match url.scheme() {
+        "mailto" => return Ok(url),
+        "http" | "https" => {}
+        scheme => return Err(format!("{}: links can't be opened", scheme)),
+    }
+    if !url.username().is_empty() || url.password().is_some() {
+        return Err("Links with a user name or password can't be opened".to_string());
+    }
+    let local = match url.host() {
+        None => true,
+        Some(url::Host::Domain(domain)) => {
+            let domain = domain.trim_end_matches('.').to_ascii_lowercase();
+            domain == "localhost" || domain.ends_with(".localhost")
+        }
+        Some(url::Host::Ipv4(ip)) => ip.is_loopback() || ip.is_unspecified(),
+        Some(url::Host::Ipv6(ip)) => ip.is_loopback() || ip.is_unspecified(),
+    };
+    if local {
+        return Err("Links to this computer can't be opened".to_string());
+    }
+    Ok(url)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn nav(url: &str) -> Navigation {
+        navigation(&Url::parse(url).unwrap())
+    }
+
+    #[test]
+    fn test_navigation() {
+        assert_eq!(nav("tauri://localhost/settings"), Navigation::Allow);
+        assert_eq!(
+            nav("https://tauri.localhost/reminder?count=2"),
+            Navigation::Allow
+        );
+        assert_eq!(nav("serina-mail://localhost/42"), Navigation::Allow);
+        assert_eq!(nav("about:blank"), Navigation::Allow);
+        assert_eq!(
+            nav("https://example.com/unsubscribe"),
+            Navigation::OpenExternal(Url::parse("https://example.com/unsubscribe").unwrap())
+        );
+        assert_eq!(nav("file:///etc/passwd"), Navigation::Block);
+        assert_eq!(nav("javascript:alert(1)"), Navigation::Block);
+        assert_eq!(nav("data:text/html,<script>1</script>"), Navigation::Block);
+        assert_eq!(
+            nav("https://tauri.localhost.evil.example/"),
+            Navigation::OpenExternal(Url::parse("https://tauri.localhost.evil.example/").unwrap())
+        );
+    }
+
+    #[test]
+    fn test_external_url() {
+        assert!(external_url(" mailto:ana@example.com ").is_ok());
+        assert!(external_url("https://bank.example@evil.example/").is_err());
+        assert!(external_url("http://127.0.0.1:8000/emails").is_err());
+        assert!(external_url("http://[::1]/").is_err());
+        assert!(external_url("http://api.localhost/").is_err());
+        assert!(external_url("https://example.com/a b").is_err());
+        assert!(external_url("https://example.com/\u{7}").is_err());
+        assert!(external_url("ftp://example.com/").is_err());
+        assert!(external_url(&format!("https://example.com/{}", "a".repeat(MAX_URL_LEN))).is_err());
+    }
+
+    #[test]
+    fn test_config_uses_these_policies() {
+        let config: serde_json::Value =
+            serde_json::from_str(include_str!("../tauri.conf.json")).unwrap();
+        assert_eq!(config["tauri"]["security"]["csp"], APP_CSP);
+        assert_eq!(config["tauri"]["security"]["devCsp"], DEV_CSP);
+        assert_eq!(config["build"]["devPath"], DEV_ORIGIN);
+        // Config windows skip the navigation handler, so every window is built in code
+        assert_eq!(config["tauri"]["windows"], serde_json::json!([]));
+    }
+}
@@ -0,0 +1,269 @@
+// SERINA Window Presence
+// Tracks whether the user is looking at the inbox, or presenting, to decide where alerts go,
+// the main window's look (translucent effects, the compact unread ticker) and where the
+// mini inbox was last dragged to
+
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+/// View name the frontend reports while the email list is on screen
+pub const INBOX_VIEW: &str = "inbox";
+
+/// Where an alert should go
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Delivery {
+    /// Just update the in-app list; the user is already looking at it
+    InApp,
+    /// Desktop notification or reminder popup
+    Desktop,
+}
+
+/// Processes that only run while a screen is being shared (Zoom's share helper)
+pub const SCREEN_SHARE_PROCESSES: &[&str] = &["cpthost"];
+
+pub fn is_screen_share_process(name: &str) -> bool {
+    let name = name.trim().to_lowercase();
+    let name = name.strip_suffix(".exe").unwrap_or(&name);
+    SCREEN_SHARE_PROCESSES.contains(&name)
+}
+
+/// Signs that the screen may be visible to an audience
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct PresentationState {
+    /// A fullscreen app (slides, video call) is in the foreground
+    pub fullscreen: bool,
+    /// The OS presentation / do-not-disturb mode is on
+    pub presentation_mode: bool,
+    pub screen_sharing: bool,
+}
+
+impl PresentationState {
+    pub fn is_presenting(&self) -> bool {
+        self.fullscreen || self.presentation_mode || self.screen_sharing
+    }
+}
+
+/// Focus and current view of the main window
+#[derive(Default)]
+pub struct WindowPresence {
+    focused: AtomicBool,
+    view: Mutex<String>,
+}
+
+impl WindowPresence {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_focused(&self, focused: bool) {
+        self.focused.store(focused, Ordering::Relaxed);
+    }
+
+    pub fn is_focused(&self) -> bool {
+        self.focused.load(Ordering::Relaxed)
+    }
+
+    pub fn set_view(&self, view: &str) {
+        *self.view.lock().unwrap() = view.to_string();
+    }
+
+    pub fn view(&self) -> String {
+        self.view.lock().unwrap().clone()
+    }
+
+    /// In-app only while the focused, unminimized main window shows the inbox. A window on
+    /// another virtual desktop loses focus, so it gets full notifications too.
+    pub fn delivery(&self, minimized: bool) -> Delivery {
+        if self.is_focused() && !minimized && self.view() == INBOX_VIEW {
+            Delivery::InApp
+        } else {
+            Delivery::Desktop
+        }
+    }
+}
+
+/// Translucent backdrop behind the main window
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WindowEffect {
+    #[default]
+    None,
+    /// macOS vibrancy
+    Vibrancy,
+    /// Windows 11 blurred backdrop
+    Acrylic,
+    /// Windows 11 desktop-tinted backdrop
+    Mica,
+}
+
+impl WindowEffect {
+    pub fn is_supported(self) -> bool {
+        match self {
+            WindowEffect::None => true,
+            WindowEffect::Vibrancy => cfg!(target_os = "macos"),
+            WindowEffect::Acrylic | WindowEffect::Mica => cfg!(target_os = "windows"),
+        }
+    }
+}
+
+/// Logical size of the compact unread ticker
+pub const COMPACT_SIZE: (f64, f64) = (300.0, 64.0);
+
+/// Where the main window was before going compact, in physical pixels
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WindowGeometry {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Whether the main window is the compact ticker, and the geometry to go back to
+#[derive(Default)]
+pub struct CompactMode {
+    restore: Mutex<Option<WindowGeometry>>,
+}
+
+impl CompactMode {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_compact(&self) -> bool {
+        self.restore.lock().unwrap().is_some()
+    }
+
+    /// Remember the full-size geometry; false when already compact
+    pub fn enter(&self, geometry: WindowGeometry) -> bool {
+        let mut restore = self.restore.lock().unwrap();
+        if restore.is_some() {
+            return false;
+        }
+        *restore = Some(geometry);
+        true
+    }
+
+    /// Geometry to restore, or `None` when the window wasn't compact
+    pub fn exit(&self) -> Option<WindowGeometry> {
+        self.restore.lock().unwrap().take()
+    }
+}
+
+/// Top-left corner of a window, in physical pixels
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WindowPosition {
+    pub x: i32,
+    pub y: i32,
+}
+
+/// Pull a remembered position back onto the monitor, e.g. after a display was unplugged
+pub fn clamp_to_monitor(
+    position: WindowPosition,
+    window_size: (u32, u32),
+    monitor: WindowGeometry,
+) -> WindowPosition {
+    let max_x = monitor.x + monitor.width.saturating_sub(window_size.0) as i32;
+    let max_y = monitor.y + monitor.height.saturating_sub(window_size.1) as i32;
+    WindowPosition {
+        x: position.x.clamp(monitor.x, max_x),
+        y: position.y.clamp(monitor.y, max_y),
+    }
+}
+
+/// Latest position of a dragged window, saved once the window closes rather than on
+/// every move event
+#[derive(Default)]
+pub struct PositionMemory {
+    moved: Mutex<Option<WindowPosition>>,
+}
+
+impl PositionMemory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn moved(&self, position: WindowPosition) {
+        *self.moved.lock().unwrap() = Some(position);
+    }
+
+    /// The position to save, if the window moved since the last save
+    pub fn take(&self) -> Option<WindowPosition> {
+        self.moved.lock().unwrap().take()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delivery() {
+        let presence = WindowPresence::new();
+        assert_eq!(presence.delivery(false), Delivery::Desktop);
+
+        presence.set_view(INBOX_VIEW);
+        presence.set_focused(true);
+        assert_eq!(presence.delivery(false), Delivery::InApp);
+        assert_eq!(presence.delivery(true), Delivery::Desktop);
+
+        presence.set_view("settings");
+        assert_eq!(presence.delivery(false), Delivery::Desktop);
+        presence.set_view(INBOX_VIEW);
+        presence.set_focused(false);
+        assert_eq!(presence.delivery(false), Delivery::Desktop);
+    }
+
+    #[test]
+    fn test_compact_mode() {
+        let compact = CompactMode::new();
+        let full = WindowGeometry {
+            x: 10,
+            y: 20,
+            width: 1200,
+            height: 800,
+        };
+        assert!(compact.enter(full));
+        assert!(!compact.enter(WindowGeometry { x: 0, ..full }));
+        assert!(compact.is_compact());
+        assert_eq!(compact.exit(), Some(full));
+        assert_eq!(compact.exit(), None);
+        assert!(WindowEffect::None.is_supported());
+    }
+
+    #[test]
+    fn test_clamp_to_monitor() {
+        let monitor = WindowGeometry {
+            x: 0,
+            y: 0,
+            width: 1920,
+            height: 1080,
+        };
+        let inside = WindowPosition { x: 100, y: 200 };
+        assert_eq!(clamp_to_monitor(inside, (340, 420), monitor), inside);
+        let gone = WindowPosition { x: 3000, y: -50 };
+        assert_eq!(
+            clamp_to_monitor(gone, (340, 420), monitor),
+            WindowPosition { x: 1580, y: 0 }
+        );
+
+        let memory = PositionMemory::new();
+        memory.moved(inside);
+        assert_eq!(memory.take(), Some(inside));
+        assert_eq!(memory.take(), None);
+    }
+
+    #[test]
+    fn test_presentation() {
+        assert!(is_screen_share_process("CptHost.exe"));
+        assert!(is_screen_share_process("CptHost"));
+        assert!(!is_screen_share_process("zoom.us"));
+        assert!(!PresentationState::default().is_presenting());
+        let sharing = PresentationState {
+            screen_sharing: true,
+            ..Default::default()
+        };
+        assert!(sharing.is_presenting());
+    }
+}
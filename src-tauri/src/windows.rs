@@ -0,0 +1,210 @@
+// SERINA Window Registry
+// Every window the app opens, with its label, route and creation options in one place
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AppWindow {
+    Main,
+    Settings,
+    Reminder,
+    MiniInbox,
+    /// Hidden; lays out one email for the OS print dialog
+    Print,
+    /// A new message pre-filled from something shared with SERINA
+    Compose,
+    /// Snoozes, reminders, scheduled sends and the focus digest, soonest first
+    Upcoming,
+    /// Timeline of mail received, summarized, answered and acted on by rules
+    Activity,
+}
+
+/// How a window is created; the main window comes from tauri.conf.json with the same values
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WindowOptions {
+    /// Frontend route
+    pub route: &'static str,
+    /// Fluent message id for the title, when the title doesn't depend on content
+    pub title_id: Option<&'static str>,
+    /// Logical size
+    pub size: (f64, f64),
+    pub resizable: bool,
+    pub decorations: bool,
+    pub always_on_top: bool,
+    pub skip_taskbar: bool,
+    pub center: bool,
+    /// See-through background, for the window effect behind the main window
+    pub transparent: bool,
+    /// Smallest logical size the user can shrink it to
+    pub min_size: Option<(f64, f64)>,
+}
+
+impl AppWindow {
+    pub const ALL: [AppWindow; 8] = [
+        AppWindow::Main,
+        AppWindow::Settings,
+        AppWindow::Reminder,
+        AppWindow::MiniInbox,
+        AppWindow::Print,
+        AppWindow::Compose,
+        AppWindow::Upcoming,
+        AppWindow::Activity,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            AppWindow::Main => "main",
+            AppWindow::Settings => "settings",
+            AppWindow::Reminder => "reminder",
+            AppWindow::MiniInbox => "mini-inbox",
+            AppWindow::Print => "print",
+            AppWindow::Compose => "compose",
+            AppWindow::Upcoming => "upcoming",
+            AppWindow::Activity => "activity",
+        }
+    }
+
+    pub fn from_label(label: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|window| window.label() == label)
+    }
+
+    pub fn options(self) -> WindowOptions {
+        match self {
+            AppWindow::Main => WindowOptions {
+                route: "/",
+                title_id: Some("window-main-title"),
+                size: (1200.0, 800.0),
+                resizable: true,
+                decorations: false,
+                always_on_top: false,
+                skip_taskbar: false,
+                center: true,
+                transparent: true,
+                min_size: Some((800.0, 600.0)),
+            },
+            AppWindow::Settings => WindowOptions {
+                route: "/settings",
+                title_id: Some("window-settings-title"),
+                size: (600.0, 500.0),
+                resizable: false,
+                decorations: true,
+                always_on_top: false,
+                skip_taskbar: false,
+                center: true,
+                transparent: false,
+                min_size: None,
+            },
+            AppWindow::Reminder => WindowOptions {
+                route: "/reminder",
+                title_id: None,
+                size: (320.0, 120.0),
+                resizable: false,
+                decorations: false,
+                always_on_top: true,
+                skip_taskbar: true,
+                center: false,
+                transparent: false,
+                min_size: None,
+            },
+            AppWindow::MiniInbox => WindowOptions {
+                route: "/mini-inbox",
+                title_id: Some("window-mini-inbox-title"),
+                size: (340.0, 420.0),
+                resizable: false,
+                decorations: false,
+                always_on_top: true,
+                skip_taskbar: true,
+                center: false,
+                transparent: false,
+                min_size: None,
+            },
+            AppWindow::Print => WindowOptions {
+                route: "/print",
+                title_id: Some("window-print-title"),
+                size: (800.0, 1000.0),
+                resizable: true,
+                decorations: true,
+                always_on_top: false,
+                skip_taskbar: true,
+                center: true,
+                transparent: false,
+                min_size: None,
+            },
+            AppWindow::Compose => WindowOptions {
+                route: "/compose",
+                title_id: Some("window-compose-title"),
+                size: (640.0, 560.0),
+                resizable: true,
+                decorations: true,
+                always_on_top: false,
+                skip_taskbar: false,
+                center: true,
+                transparent: false,
+                min_size: None,
+            },
+            AppWindow::Upcoming => WindowOptions {
+                route: "/upcoming",
+                title_id: Some("window-upcoming-title"),
+                size: (440.0, 520.0),
+                resizable: true,
+                decorations: true,
+                always_on_top: false,
+                skip_taskbar: false,
+                center: true,
+                transparent: false,
+                min_size: None,
+            },
+            AppWindow::Activity => WindowOptions {
+                route: "/activity",
+                title_id: Some("window-activity-title"),
+                size: (440.0, 600.0),
+                resizable: true,
+                decorations: true,
+                always_on_top: false,
+                skip_taskbar: false,
+                center: true,
+                transparent: false,
+                min_size: None,
+            },
+        }
+    }
+
+    /// The window's route with query parameters, e.g. "/reminder?count=3"
+    pub fn url(self, query: &[(&str, String)]) -> String {
+        let route = self.options().route;
+        if query.is_empty() {
+            return route.to_string();
+        }
+        let query = url::form_urlencoded::Serializer::new(String::new())
+            .extend_pairs(query)
+            .finish();
+        format!("{}?{}", route, query)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_labels() {
+        for window in AppWindow::ALL {
+            assert_eq!(AppWindow::from_label(window.label()), Some(window));
+        }
+        assert_eq!(AppWindow::from_label("preferences"), None);
+        assert_eq!(
+            serde_json::to_value(AppWindow::MiniInbox).unwrap(),
+            AppWindow::MiniInbox.label()
+        );
+    }
+
+    #[test]
+    fn test_url() {
+        assert_eq!(AppWindow::Settings.url(&[]), "/settings");
+        assert_eq!(
+            AppWindow::Reminder.url(&[("count", 3.to_string()), ("persistent", "1".to_string())]),
+            "/reminder?count=3&persistent=1"
+        );
+    }
+}
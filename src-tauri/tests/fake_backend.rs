@@ -0,0 +1,286 @@
+// SERINA Integration Tests
+// Backend and LLM requests end to end against a fake HTTP server
+
+use serde_json::json;
+use serina::backend;
+use serina::diagnostics::{self, AuthResult};
+use serina::http::PinnedClient;
+use serina::llm;
+use serina::ratelimit::RateLimiter;
+use serina::utils::{EmailData, ProviderConfig};
+use std::sync::Arc;
+use wiremock::matchers::{body_json, header, method, path, query_param};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn client() -> PinnedClient {
+    PinnedClient::new(reqwest::Client::new())
+}
+
+fn provider(name: &str, server: &MockServer, api_key: &str) -> ProviderConfig {
+    ProviderConfig {
+        provider: name.to_string(),
+        api_key: api_key.to_string(),
+        model: String::new(),
+        base_url: Some(format!("{}/v1", server.uri())),
+    }
+}
+
+fn completion(text: &str) -> ResponseTemplate {
+    ResponseTemplate::new(200).set_body_json(json!({
+        "choices": [{"message": {"role": "assistant", "content": text}}],
+        "usage": {"total_tokens": 42}
+    }))
+}
+
+#[tokio::test]
+async fn test_get_emails() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/emails"))
+        .and(query_param("limit", "5"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([{
+            "id": "abc",
+            "subject": "Budget",
+            "sender": "Dana",
+            "sender_email": "dana@example.com",
+            "body": "See attached",
+            "received_time": "2024-05-14T09:12:00",
+            "is_unread": true
+        }])))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let response = backend::request(&client(), &server.uri(), "get", "/emails?limit=5", None)
+        .await
+        .unwrap();
+    let emails: Vec<EmailData> = serde_json::from_str(&response).unwrap();
+    assert_eq!(emails[0].id, "abc");
+    assert!(emails[0].is_unread);
+}
+
+#[tokio::test]
+async fn test_post_sends_json() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/emails/abc/reply"))
+        .and(header("content-type", "application/json"))
+        .and(body_json(json!({"reply_text": "Thanks!"})))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({"message": "Reply sent"})))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let body = json!({"reply_text": "Thanks!"}).to_string();
+    let response = backend::request(
+        &client(),
+        &server.uri(),
+        "POST",
+        "/emails/abc/reply",
+        Some(body),
+    )
+    .await
+    .unwrap();
+    assert!(response.contains("Reply sent"));
+}
+
+#[tokio::test]
+async fn test_backend_errors() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/emails/abc/mark-read"))
+        .respond_with(ResponseTemplate::new(500))
+        .mount(&server)
+        .await;
+
+    let error = backend::request(
+        &client(),
+        &server.uri(),
+        "POST",
+        "/emails/abc/mark-read",
+        None,
+    )
+    .await
+    .unwrap_err();
+    assert!(error.starts_with("HTTP 500"), "{}", error);
+    assert!(
+        backend::request(&client(), &server.uri(), "PATCH", "/emails/abc", None)
+            .await
+            .is_err()
+    );
+    // Nothing mounted: the fake server answers 404
+    assert!(
+        backend::request(&client(), &server.uri(), "GET", "/health", None)
+            .await
+            .unwrap_err()
+            .starts_with("HTTP 404")
+    );
+}
+
+#[tokio::test]
+async fn test_llm_sends_api_key() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/chat/completions"))
+        .and(header("authorization", "Bearer sk-test"))
+        .respond_with(completion("A short summary."))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let chain = [provider("openai", &server, "sk-test")];
+    let prompt = llm::summary_prompt("The review moved to Thursday.", None);
+    let result = llm::complete(&client(), &chain, &prompt).await.unwrap();
+    assert_eq!(result.text, "A short summary.");
+    assert_eq!(result.tokens, 42);
+    assert_eq!(result.route.model, "gpt-3.5-turbo");
+    assert!(result.route.failures.is_empty());
+}
+
+#[tokio::test]
+async fn test_llm_falls_back() {
+    let (primary, fallback) = (MockServer::start().await, MockServer::start().await);
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(503))
+        .expect(2)
+        .mount(&primary)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/v1/chat/completions"))
+        .respond_with(completion(
+            "Title: Send forecast\nDescription: Before Wednesday",
+        ))
+        .expect(1)
+        .mount(&fallback)
+        .await;
+
+    let chain = [
+        provider("openai", &primary, "sk-test"),
+        provider("openrouter", &fallback, "or-test"),
+    ];
+    let prompt = llm::task_prompt("Send the forecast by Wednesday", "Forecast");
+    let result = llm::complete(&client(), &chain, &prompt).await.unwrap();
+    assert_eq!(llm::parse_task(&result.text).title, "Send forecast");
+    assert_eq!(result.route.provider, "openrouter");
+    assert_eq!(result.route.failures.len(), 1);
+    assert_eq!(result.route.failures[0].provider, "openai");
+
+    // Every provider down: the error names each one
+    let error = llm::complete(&client(), &chain[..1], &prompt)
+        .await
+        .unwrap_err();
+    assert!(error.contains("openai: HTTP 503"), "{}", error);
+}
+
+#[tokio::test]
+async fn test_large_responses_stream() {
+    let server = MockServer::start().await;
+    let emails: Vec<serde_json::Value> = (0..3000)
+        .map(|i| {
+            json!({
+                "id": format!("msg-{}", i),
+                "subject": format!("Report {}", i),
+                "sender": "Dana",
+                "sender_email": "dana@example.com",
+                "body": "Quarterly numbers attached. ".repeat(20),
+                "received_time": "2024-05-14T09:12:00",
+                "is_unread": i % 2 == 0
+            })
+        })
+        .collect();
+    let list = serde_json::to_vec(&emails).unwrap();
+    assert!(list.len() as u64 > backend::SPOOL_THRESHOLD);
+    Mock::given(method("GET"))
+        .and(path("/emails"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(list, "application/json"))
+        .mount(&server)
+        .await;
+    let attachment: Vec<u8> = (0..=255u8).cycle().take(300_000).collect();
+    Mock::given(method("GET"))
+        .and(path("/emails/msg-1/attachments/0"))
+        .respond_with(ResponseTemplate::new(200).set_body_bytes(attachment.clone()))
+        .mount(&server)
+        .await;
+
+    let parsed: Vec<EmailData> =
+        backend::request_json(&client(), &server.uri(), "GET", "/emails", None)
+            .await
+            .unwrap();
+    assert_eq!(parsed.len(), 3000);
+    assert_eq!(parsed[2999].id, "msg-2999");
+
+    let dest = std::env::temp_dir().join(format!("serina-test-{}.bin", std::process::id()));
+    let size = backend::download(
+        &client(),
+        &server.uri(),
+        "/emails/msg-1/attachments/0",
+        &dest,
+    )
+    .await
+    .unwrap();
+    assert_eq!(size, attachment.len() as u64);
+    assert_eq!(std::fs::read(&dest).unwrap(), attachment);
+    std::fs::remove_file(dest).unwrap();
+}
+
+#[tokio::test]
+async fn test_rate_limit_retry() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/chat/completions"))
+        .respond_with(ResponseTemplate::new(429).insert_header("retry-after-ms", "50"))
+        .up_to_n_times(1)
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/v1/chat/completions"))
+        .respond_with(completion("Title: Reply to Dana"))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let limiter = Arc::new(RateLimiter::default());
+    let mut events = limiter.subscribe();
+    let client = client().with_limiter(limiter);
+    let chain = [provider("openai", &server, "sk-test")];
+    let prompt = llm::task_prompt("Reply to Dana", "Budget");
+    let result = llm::complete(&client, &chain, &prompt).await.unwrap();
+    assert_eq!(llm::parse_task(&result.text).title, "Reply to Dana");
+    assert!(result.route.failures.is_empty());
+
+    let event = events.try_recv().unwrap();
+    assert_eq!(event.attempt, 1);
+    assert_eq!(event.wait_secs, 1);
+}
+
+#[tokio::test]
+async fn test_rate_limit_too_long() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/emails"))
+        .respond_with(ResponseTemplate::new(429).insert_header("retry-after", "3600"))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let error = backend::request(&client(), &server.uri(), "GET", "/emails", None)
+        .await
+        .unwrap_err();
+    assert!(error.contains("try again in 3600s"), "{}", error);
+}
+
+#[tokio::test]
+async fn test_llm_connection_rejected_key() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v1/models"))
+        .respond_with(ResponseTemplate::new(401))
+        .mount(&server)
+        .await;
+
+    let result =
+        diagnostics::test_llm(&client(), &provider("openai", &server, "sk-bad"), false).await;
+    assert!(!result.ok);
+    assert_eq!(result.auth, AuthResult::Failed);
+}
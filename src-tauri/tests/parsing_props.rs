@@ -0,0 +1,95 @@
+// SERINA Parser Properties
+// Malformed bodies from the wild must never panic the parser or leak hidden content
+
+use proptest::prelude::*;
+use serina::parsing::{self, SNIPPET_LENGTH};
+use serina::{deadline, extract, redact};
+
+/// Fragments real-world HTML mail is made of, glued together in any order
+fn html_soup() -> impl Strategy<Value = String> {
+    let fragment = prop_oneof![
+        Just("<p>".to_string()),
+        Just("</p>".to_string()),
+        Just("<br/>".to_string()),
+        Just("<blockquote>".to_string()),
+        Just("</blockquote>".to_string()),
+        Just("<td>".to_string()),
+        Just("<style>".to_string()),
+        Just("</style>".to_string()),
+        Just("<!--".to_string()),
+        Just("-->".to_string()),
+        Just("<".to_string()),
+        Just(">".to_string()),
+        Just("&amp;".to_string()),
+        Just("&#x1F600;".to_string()),
+        Just("&#99999999;".to_string()),
+        Just("&".to_string()),
+        "\\PC{0,12}",
+    ];
+    prop::collection::vec(fragment, 0..40).prop_map(|parts| parts.concat())
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+proptest! {
+    #[test]
+    fn html_to_text_never_panics(html in prop_oneof!["\\PC*", html_soup()]) {
+        let text = parsing::html_to_text(&html);
+        prop_assert!(text.lines().all(|line| !line.is_empty() && line.trim() == line));
+    }
+
+    #[test]
+    fn escaped_text_round_trips(text in "\\PC{0,200}") {
+        let html = format!("<div>{}</div>", escape(&text));
+        let expected = text.split_whitespace().collect::<Vec<_>>().join(" ");
+        prop_assert_eq!(parsing::html_to_text(&html), expected);
+    }
+
+    #[test]
+    fn hidden_elements_stay_hidden(
+        visible in "[a-z]{1,20}",
+        hidden in "[A-Z]{1,20}",
+        element in prop::sample::select(vec!["script", "style", "title", "SCRIPT"]),
+    ) {
+        let html = format!("<p>{}</p><{e} type=\"x\">{}</{e}><p>{}</p>", visible, hidden, visible, e = element);
+        let text = parsing::html_to_text(&html);
+        prop_assert!(!text.contains(&hidden));
+        prop_assert_eq!(text, format!("{}\n{}", visible, visible));
+    }
+
+    #[test]
+    fn snippets_fit_the_list(body in prop_oneof!["\\PC*", html_soup(), "(\\w{1,30} ){0,80}"]) {
+        let snippet = parsing::snippet(&body);
+        prop_assert!(snippet.chars().count() <= SNIPPET_LENGTH);
+        prop_assert!(!snippet.contains('\n'));
+    }
+
+    #[test]
+    fn body_splitting_never_panics(body in prop_oneof!["\\PC*", html_soup(), "((>+ )?[^\\n]{0,40}\\n){0,30}"]) {
+        let segments = parsing::split_body(&body);
+        prop_assert!(segments.iter().all(|segment| !segment.text.is_empty()));
+        let _ = parsing::new_content(&body);
+    }
+
+    #[test]
+    fn other_body_scanners_never_panic(body in prop_oneof!["\\PC*", html_soup()]) {
+        let reference = chrono::NaiveDate::from_ymd_opt(2024, 5, 14)
+            .unwrap()
+            .and_hms_opt(9, 0, 0)
+            .unwrap();
+        let _ = deadline::detect(&body, reference);
+        let _ = extract::from_schema_org(&body);
+        let _ = extract::parse_extraction(&body);
+        let config = serina::utils::RedactionConfig {
+            enabled: true,
+            ..Default::default()
+        };
+        if let Ok(redacted) = redact::redact(&body, &config) {
+            prop_assert_eq!(redact::restore(&redacted.text, &redacted.redactions), body);
+        }
+    }
+}
@@ -1,27 +1,106 @@
 import React, { useEffect, useState } from "react";
 import { Routes, Route, useLocation } from "react-router-dom";
 import { invoke } from "@tauri-apps/api/tauri";
+import { listen } from "@tauri-apps/api/event";
+import { appWindow } from "@tauri-apps/api/window";
 import EmailView from "./pages/EmailView";
 import Settings from "./pages/Settings";
 import ReminderPopup from "./pages/ReminderPopup";
+import MiniInbox from "./pages/MiniInbox";
+import PrintView from "./pages/PrintView";
+import Compose from "./pages/Compose";
+import Upcoming from "./pages/Upcoming";
+import Activity from "./pages/Activity";
 import ErrorBoundary from "./components/ErrorBoundary";
+import CompactTicker from "./components/CompactTicker";
+import LockScreen from "./components/LockScreen";
 import { NotificationProvider } from "./components/NotificationSystem";
 
+interface LockStatus {
+  locked: boolean;
+  pin_set: boolean;
+  biometric_available: boolean;
+}
+
+interface ResolvedTheme {
+  mode: 'system' | 'light' | 'dark';
+  dark: boolean;
+  preset: string;
+  accent: string;
+}
+
 function App() {
   const location = useLocation();
   const [darkMode, setDarkMode] = useState(true);
+  const [accent, setAccent] = useState('#06b6d4');
+  const [highContrast, setHighContrast] = useState(false);
+  const [translucent, setTranslucent] = useState(false);
+  // Only the main window turns into the ticker; settings and popups keep their layout
+  const [compact, setCompact] = useState(false);
+  const isMainWindow = appWindow.label === 'main';
   const [isLoading, setIsLoading] = useState(true);
+  const [lock, setLock] = useState<LockStatus | null>(null);
 
   useEffect(() => {
     // Load user preferences on startup
     loadConfig();
+    // The OS appearance and theme settings can change while the app is open
+    const unlisten = listen<ResolvedTheme>('theme-changed', ({ payload }) => applyTheme(payload));
+    const unlistenEffect = listen<string>('window-effect-changed', ({ payload }) =>
+      setTranslucent(payload !== 'none')
+    );
+    const unlistenCompact = listen<boolean>('compact-mode-changed', ({ payload }) =>
+      setCompact(payload && isMainWindow)
+    );
+    // Saved from another window or by the backend
+    const unlistenConfig = listen<number>('config-changed', () => loadConfig());
+    // Web and mail links open in the system browser, never inside the app
+    const openLinksExternally = (event: MouseEvent) => {
+      const link = (event.target as Element | null)?.closest('a[href]') as HTMLAnchorElement | null;
+      if (!link || link.hasAttribute('download') || link.origin === window.location.origin) return;
+      event.preventDefault();
+      invoke('open_external', { url: link.href, text: link.textContent }).catch((error) =>
+        console.error('Failed to open link:', error)
+      );
+    };
+    document.addEventListener('click', openLinksExternally);
+    const unlistenLocked = listen('app-locked', () => loadLockStatus());
+    const unlistenUnlocked = listen('app-unlocked', () =>
+      setLock(current => (current ? { ...current, locked: false } : current))
+    );
+    return () => {
+      document.removeEventListener('click', openLinksExternally);
+      unlistenLocked.then((fn) => fn());
+      unlistenUnlocked.then((fn) => fn());
+      unlisten.then((fn) => fn());
+      unlistenEffect.then((fn) => fn());
+      unlistenCompact.then((fn) => fn());
+      unlistenConfig.then((fn) => fn());
+    };
   }, []);
 
+  const applyTheme = (theme: ResolvedTheme) => {
+    setDarkMode(theme.dark);
+    setAccent(theme.accent);
+  };
+
+  const loadLockStatus = async () => {
+    try {
+      setLock(await invoke<LockStatus>('get_lock_status'));
+    } catch (error) {
+      console.error('Failed to load lock status:', error);
+    }
+  };
+
   const loadConfig = async () => {
     try {
-      const response = await invoke('get_config');
-      const config = JSON.parse(response as string);
-      setDarkMode(config.ui?.dark_mode ?? true);
+      await loadLockStatus();
+      applyTheme(await invoke<ResolvedTheme>('get_theme'));
+      // Only the display settings: popups aren't allowed the rest of the config
+      const ui = await invoke<{ high_contrast: boolean; window_effect: string; compact_mode: boolean }>('get_ui_config');
+      setHighContrast(ui.high_contrast);
+      setTranslucent(ui.window_effect !== 'none');
+      setCompact(ui.compact_mode && isMainWindow);
     } catch (error) {
       console.error('Failed to load config:', error);
     } finally {
@@ -31,12 +110,7 @@ function App() {
 
   const toggleDarkMode = async () => {
     try {
-      const response = await invoke('get_config');
-      const config = JSON.parse(response as string);
-      config.ui = { ...config.ui, dark_mode: !darkMode };
-      
-      await invoke('save_config', { config });
-      setDarkMode(!darkMode);
+      applyTheme(await invoke<ResolvedTheme>('set_theme', { mode: darkMode ? 'light' : 'dark' }));
     } catch (error) {
       console.error('Failed to toggle dark mode:', error);
     }
@@ -53,11 +127,34 @@ function App() {
     );
   }
 
+  if (lock?.locked) {
+    return (
+      <ErrorBoundary>
+        <LockScreen biometricAvailable={lock.biometric_available} promptOnOpen={isMainWindow} />
+      </ErrorBoundary>
+    );
+  }
+
+  if (compact) {
+    return (
+      <ErrorBoundary>
+        <CompactTicker />
+      </ErrorBoundary>
+    );
+  }
+
   return (
     <ErrorBoundary>
       <NotificationProvider>
-        <div className={darkMode ? 'dark' : ''}>
-          <div className="min-h-screen bg-white dark:bg-gray-900 text-gray-900 dark:text-white">
+        <div
+          className={`${darkMode ? 'dark' : ''} ${highContrast ? 'high-contrast' : ''}`}
+          style={{ '--serina-accent': accent } as React.CSSProperties}
+        >
+          <div
+            className={`min-h-screen text-gray-900 dark:text-white ${
+              translucent && isMainWindow ? 'bg-white/70 dark:bg-gray-900/70' : 'bg-white dark:bg-gray-900'
+            }`}
+          >
             <ErrorBoundary>
               <Routes>
                 <Route 
@@ -90,6 +187,46 @@ function App() {
                     </ErrorBoundary>
                   } 
                 />
+                <Route
+                  path="/mini-inbox"
+                  element={
+                    <ErrorBoundary>
+                      <MiniInbox />
+                    </ErrorBoundary>
+                  }
+                />
+                <Route
+                  path="/print"
+                  element={
+                    <ErrorBoundary>
+                      <PrintView />
+                    </ErrorBoundary>
+                  }
+                />
+                <Route
+                  path="/compose"
+                  element={
+                    <ErrorBoundary>
+                      <Compose />
+                    </ErrorBoundary>
+                  }
+                />
+                <Route
+                  path="/upcoming"
+                  element={
+                    <ErrorBoundary>
+                      <Upcoming />
+                    </ErrorBoundary>
+                  }
+                />
+                <Route
+                  path="/activity"
+                  element={
+                    <ErrorBoundary>
+                      <Activity />
+                    </ErrorBoundary>
+                  }
+                />
               </Routes>
             </ErrorBoundary>
           </div>
@@ -0,0 +1,72 @@
+import React, { useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/tauri';
+import { listen } from '@tauri-apps/api/event';
+import { Mail, Maximize2 } from 'lucide-react';
+
+interface AddedEmail {
+  subject: string;
+  sender: string;
+  muted?: boolean;
+}
+
+// The main window in compact mode: unread count and the latest arrival, always on top
+const CompactTicker: React.FC = () => {
+  const [unread, setUnread] = useState<number | null>(null);
+  const [latest, setLatest] = useState<AddedEmail | null>(null);
+
+  useEffect(() => {
+    invoke<string>('get_unread_count')
+      .then((response) => setUnread(JSON.parse(response).count ?? 0))
+      .catch((error) => console.error('Failed to load unread count:', error));
+
+    const unlistenCount = listen<number>('unread-count-changed', ({ payload }) => setUnread(payload));
+    const unlistenAdded = listen<AddedEmail>('email-added', ({ payload }) => {
+      // Replies in muted conversations don't take over the ticker
+      if (!payload.muted) setLatest(payload);
+    });
+    return () => {
+      unlistenCount.then((fn) => fn());
+      unlistenAdded.then((fn) => fn());
+    };
+  }, []);
+
+  const expand = () => {
+    invoke('set_compact_mode', { compact: false }).catch((error) =>
+      console.error('Failed to leave compact mode:', error)
+    );
+  };
+
+  return (
+    <div
+      data-tauri-drag-region
+      onDoubleClick={expand}
+      role="status"
+      aria-live="polite"
+      className="h-screen flex items-center px-3 space-x-3 select-none rounded-lg border border-gray-700 bg-gray-900/90 text-white"
+    >
+      <div className="flex items-center space-x-1 text-cyan-400" data-tauri-drag-region>
+        <Mail className="w-4 h-4" />
+        <span className="text-lg font-bold">{unread ?? '–'}</span>
+      </div>
+      <div className="flex-1 min-w-0 text-xs" data-tauri-drag-region>
+        {latest ? (
+          <>
+            <p className="truncate font-semibold">{latest.subject || '(no subject)'}</p>
+            <p className="truncate text-gray-400">{latest.sender}</p>
+          </>
+        ) : (
+          <p className="text-gray-400">{unread === 0 ? 'Inbox clear' : 'Unread emails'}</p>
+        )}
+      </div>
+      <button
+        onClick={expand}
+        aria-label="Open full window"
+        className="p-1 rounded hover:bg-gray-700 text-gray-400 hover:text-white"
+      >
+        <Maximize2 className="w-4 h-4" />
+      </button>
+    </div>
+  );
+};
+
+export default CompactTicker;
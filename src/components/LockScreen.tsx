@@ -0,0 +1,80 @@
+import React, { useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/tauri';
+import { Fingerprint, Lock } from 'lucide-react';
+
+interface LockScreenProps {
+  biometricAvailable: boolean;
+  // Only one window should raise the system prompt on its own
+  promptOnOpen: boolean;
+}
+
+// Shown in place of every window's content while SERINA is locked
+const LockScreen: React.FC<LockScreenProps> = ({ biometricAvailable, promptOnOpen }) => {
+  const [pin, setPin] = useState('');
+  const [error, setError] = useState('');
+  const [busy, setBusy] = useState(false);
+
+  const unlock = async (withPin: boolean) => {
+    setBusy(true);
+    try {
+      await invoke('unlock_app', { pin: withPin ? pin : null });
+      setError('');
+    } catch (e) {
+      setError(String(e));
+    } finally {
+      setPin('');
+      setBusy(false);
+    }
+  };
+
+  // Offer the fingerprint / face prompt straight away; the PIN stays as the fallback
+  useEffect(() => {
+    if (biometricAvailable && promptOnOpen) {
+      unlock(false);
+    }
+  }, [biometricAvailable, promptOnOpen]);
+
+  return (
+    <div className="h-screen flex flex-col items-center justify-center space-y-4 bg-gray-900 text-gray-200">
+      <Lock className="w-10 h-10 text-cyan-400" />
+      <p className="text-sm font-bold tracking-wider">SERINA IS LOCKED</p>
+      <form
+        onSubmit={e => {
+          e.preventDefault();
+          if (pin) unlock(true);
+        }}
+        className="flex space-x-2"
+      >
+        <input
+          type="password"
+          inputMode="numeric"
+          autoFocus
+          value={pin}
+          onChange={e => setPin(e.target.value.replace(/\D/g, '').slice(0, 12))}
+          placeholder="PIN"
+          className="w-32 bg-gray-800 border border-gray-600 rounded px-3 py-2 text-center tracking-widest focus:outline-none focus:ring-2 focus:ring-cyan-500"
+        />
+        <button
+          type="submit"
+          disabled={busy || !pin}
+          className="px-3 py-2 rounded bg-cyan-600 hover:bg-cyan-500 disabled:opacity-50 text-sm"
+        >
+          Unlock
+        </button>
+      </form>
+      {biometricAvailable && (
+        <button
+          onClick={() => unlock(false)}
+          disabled={busy}
+          className="flex items-center space-x-1 text-xs text-cyan-400 hover:text-cyan-300 disabled:opacity-50"
+        >
+          <Fingerprint className="w-4 h-4" />
+          <span>Use biometrics</span>
+        </button>
+      )}
+      {error && <p className="text-xs text-red-400">{error}</p>}
+    </div>
+  );
+};
+
+export default LockScreen;
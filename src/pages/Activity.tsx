@@ -0,0 +1,127 @@
+import React, { useCallback, useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/tauri';
+import { listen } from '@tauri-apps/api/event';
+import { Eye, FileText, Inbox, Reply, Zap } from 'lucide-react';
+import { sanitizeTextInput } from '../utils/sanitization';
+
+type ActivityKind = 'received' | 'summarized' | 'replied' | 'rule_fired' | 'observed';
+
+interface ActivityEvent {
+  seq: number;
+  timestamp: string;
+  kind: ActivityKind;
+  email_id: string | null;
+  title: string;
+  details: Record<string, unknown> | null;
+}
+
+interface ActivityPage {
+  events: ActivityEvent[];
+  next_cursor: number | null;
+}
+
+const KINDS: Record<ActivityKind, { label: string; icon: React.ReactNode }> = {
+  received: { label: 'Received', icon: <Inbox className="w-4 h-4 text-blue-400" /> },
+  summarized: { label: 'Summarized', icon: <FileText className="w-4 h-4 text-cyan-400" /> },
+  replied: { label: 'Replied', icon: <Reply className="w-4 h-4 text-green-400" /> },
+  rule_fired: { label: 'Automation', icon: <Zap className="w-4 h-4 text-yellow-400" /> },
+  observed: { label: 'Would have run (observe only)', icon: <Eye className="w-4 h-4 text-purple-400" /> }
+};
+
+const formatTime = (iso: string) =>
+  new Date(iso).toLocaleString(undefined, { weekday: 'short', hour: '2-digit', minute: '2-digit', day: 'numeric', month: 'short' });
+
+// Rule actions serialize as { type: "mark_read" }; built-in automations log plain names
+const describeAction = (action: unknown) => {
+  const name = typeof action === 'string' ? action : (action as { type?: unknown } | null)?.type;
+  return typeof name === 'string' ? name.replace(/_/g, ' ') : '';
+};
+
+// The second line under the title: who sent it, or which rule acted and what it did
+const describe = (event: ActivityEvent) => {
+  const details = event.details ?? {};
+  if (event.kind === 'received' && typeof details.sender === 'string') {
+    return `From ${details.sender}`;
+  }
+  if (event.kind === 'observed' && typeof details.auto_reply === 'string') {
+    return `Auto-reply to ${details.auto_reply}`;
+  }
+  if (event.kind === 'rule_fired' || event.kind === 'observed') {
+    const name = details.rule ?? details.script;
+    const actions = Array.isArray(details.actions) ? details.actions.map(describeAction).join(', ') : '';
+    if (typeof name !== 'string') return '';
+    return event.kind === 'observed' && actions ? `${name}: ${actions}` : name;
+  }
+  return '';
+};
+
+// Everything that happened to mail, newest first, growing live and paging back on demand
+const Activity: React.FC = () => {
+  const [events, setEvents] = useState<ActivityEvent[]>([]);
+  const [cursor, setCursor] = useState<number | null>(null);
+  const [loading, setLoading] = useState(true);
+  const [error, setError] = useState('');
+
+  const loadPage = useCallback(async (from: number | null) => {
+    setLoading(true);
+    try {
+      const page = await invoke<ActivityPage>('get_activity_feed', { cursor: from });
+      setEvents(current => (from === null ? page.events : [...current, ...page.events]));
+      setCursor(page.next_cursor);
+      setError('');
+    } catch (e) {
+      setError(String(e));
+    } finally {
+      setLoading(false);
+    }
+  }, []);
+
+  useEffect(() => {
+    loadPage(null);
+    const unlisten = listen<ActivityEvent>('activity', event => {
+      setEvents(current =>
+        current.some(existing => existing.seq === event.payload.seq) ? current : [event.payload, ...current]
+      );
+    });
+    return () => {
+      unlisten.then(stop => stop());
+    };
+  }, [loadPage]);
+
+  return (
+    <div className="h-screen flex flex-col bg-gray-900 text-gray-200 text-sm">
+      <ul className="flex-1 overflow-y-auto divide-y divide-gray-800">
+        {!loading && events.length === 0 && <li className="p-6 text-center text-gray-400">No activity yet</li>}
+        {events.map(event => {
+          const kind = KINDS[event.kind];
+          const detail = describe(event);
+          return (
+            <li key={event.seq} className="px-4 py-3 space-y-1">
+              <div className="flex items-center space-x-2">
+                {kind.icon}
+                <span className="text-xs text-gray-400">{kind.label}</span>
+                <span className="flex-1 text-right text-xs text-gray-300">{formatTime(event.timestamp)}</span>
+              </div>
+              <p className="truncate">{event.title ? sanitizeTextInput(event.title, 200) : '(no subject)'}</p>
+              {detail && <p className="text-xs text-gray-500 truncate">{sanitizeTextInput(detail, 200)}</p>}
+            </li>
+          );
+        })}
+        {cursor !== null && (
+          <li className="p-3 text-center">
+            <button
+              onClick={() => loadPage(cursor)}
+              disabled={loading}
+              className="px-3 py-1 rounded bg-gray-700 hover:bg-gray-600 disabled:opacity-50 text-xs"
+            >
+              Load older
+            </button>
+          </li>
+        )}
+      </ul>
+      {error && <p className="px-4 py-2 text-xs text-red-400 border-t border-gray-700">{error}</p>}
+    </div>
+  );
+};
+
+export default Activity;
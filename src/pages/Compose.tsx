@@ -0,0 +1,186 @@
+import React, { useEffect, useState } from 'react';
+import { useSearchParams } from 'react-router-dom';
+import { invoke } from '@tauri-apps/api/tauri';
+import { appWindow } from '@tauri-apps/api/window';
+import { Paperclip, Send, X } from 'lucide-react';
+
+interface ComposeDraft {
+  id: number;
+  subject: string;
+  body: string;
+  attachments: string[];
+  rejected: { name: string; reason: string }[];
+}
+
+interface RecipientCheck {
+  address: string;
+  error: string | null;
+  suggestion: string | null;
+  new_recipient: boolean;
+}
+
+const fileName = (path: string) => path.split(/[\\/]/).pop() || path;
+
+// A new message pre-filled from files or text shared with SERINA from another app
+const Compose: React.FC = () => {
+  const [params] = useSearchParams();
+  const [draft, setDraft] = useState<ComposeDraft | null>(null);
+  const [to, setTo] = useState('');
+  const [subject, setSubject] = useState('');
+  const [body, setBody] = useState('');
+  const [sending, setSending] = useState(false);
+  const [error, setError] = useState('');
+  const [checks, setChecks] = useState<RecipientCheck[]>([]);
+
+  const loadDraft = async (draftId: number) => {
+    try {
+      const loaded = await invoke<ComposeDraft>('get_compose_draft', { draftId });
+      setDraft(loaded);
+      setSubject(loaded.subject);
+      setBody(loaded.body);
+      setError('');
+    } catch (e) {
+      setError(String(e));
+    }
+  };
+
+  useEffect(() => {
+    const draftId = Number(params.get('draft'));
+    if (draftId) loadDraft(draftId);
+  }, [params]);
+
+  // Another share while this window is open replaces the draft
+  useEffect(() => {
+    const unlisten = appWindow.listen<number>('compose-draft', ({ payload }) => {
+      loadDraft(payload);
+    });
+    return () => {
+      unlisten.then(stop => stop());
+    };
+  }, []);
+
+  // Checked once typing pauses, so typos and unknown recipients show before Send
+  useEffect(() => {
+    if (!to.trim()) {
+      setChecks([]);
+      return;
+    }
+    const timer = setTimeout(() => {
+      invoke<RecipientCheck[]>('check_recipients', { to })
+        .then(setChecks)
+        .catch(() => setChecks([]));
+    }, 500);
+    return () => clearTimeout(timer);
+  }, [to]);
+
+  const applySuggestion = (check: RecipientCheck) => {
+    if (check.suggestion) setTo(current => current.replace(check.address, check.suggestion!));
+  };
+
+  const handleSend = async (allowRecipientWarnings = false) => {
+    if (!draft) return;
+    try {
+      setSending(true);
+      await invoke('send_email', { draftId: draft.id, to, subject, body, allowRecipientWarnings });
+      await appWindow.close();
+    } catch (e) {
+      // Held back by a recipient check; the user can fix the address or send anyway
+      let blocked: { error?: string; warnings?: { message: string }[] } = {};
+      try {
+        blocked = JSON.parse(String(e));
+      } catch {
+        // A plain error message
+      }
+      if (blocked.error === 'send_blocked' && blocked.warnings) {
+        const messages = blocked.warnings.map(warning => warning.message).join('\n');
+        if (window.confirm(`${messages}\n\nSend anyway?`)) {
+          await handleSend(true);
+        }
+        return;
+      }
+      setError(String(e));
+    } finally {
+      setSending(false);
+    }
+  };
+
+  const handleDiscard = async () => {
+    if (draft) await invoke('discard_compose_draft', { draftId: draft.id });
+    await appWindow.close();
+  };
+
+  return (
+    <div className="h-screen flex flex-col bg-gray-900 text-gray-200 text-sm">
+      <div className="p-4 space-y-2 border-b border-gray-700">
+        <input
+          value={to}
+          onChange={e => setTo(e.target.value)}
+          placeholder="To (separate addresses with ;)"
+          className="w-full bg-gray-800 border border-gray-600 rounded px-3 py-2 focus:outline-none focus:ring-2 focus:ring-blue-500"
+        />
+        {checks.filter(check => check.error || check.suggestion || check.new_recipient).map(check => (
+          <p key={check.address} className="text-xs text-yellow-400">
+            {check.error ??
+              (check.suggestion ? (
+                <>
+                  Did you mean{' '}
+                  <button onClick={() => applySuggestion(check)} className="underline text-cyan-300">
+                    {check.suggestion}
+                  </button>
+                  ?
+                </>
+              ) : check.new_recipient ? (
+                `New recipient: ${check.address} isn't in your contacts`
+              ) : null)}
+          </p>
+        ))}
+        <input
+          value={subject}
+          onChange={e => setSubject(e.target.value)}
+          placeholder="Subject"
+          className="w-full bg-gray-800 border border-gray-600 rounded px-3 py-2 focus:outline-none focus:ring-2 focus:ring-blue-500"
+        />
+        {draft && draft.attachments.length > 0 && (
+          <div className="flex flex-wrap gap-2">
+            {draft.attachments.map(path => (
+              <span key={path} className="flex items-center space-x-1 px-2 py-1 rounded border border-gray-600 text-xs">
+                <Paperclip className="w-3 h-3" />
+                <span>{fileName(path)}</span>
+              </span>
+            ))}
+          </div>
+        )}
+        {draft?.rejected.map(file => (
+          <p key={file.name} className="text-xs text-yellow-400">
+            {file.name} wasn't attached: {file.reason}
+          </p>
+        ))}
+      </div>
+      <textarea
+        value={body}
+        onChange={e => setBody(e.target.value)}
+        className="flex-1 m-4 bg-gray-800 border border-gray-600 rounded p-3 resize-none focus:outline-none focus:ring-2 focus:ring-blue-500"
+      />
+      {error && <p className="px-4 text-xs text-red-400">{error}</p>}
+      <div className="p-4 flex justify-end space-x-2 border-t border-gray-700">
+        <button
+          onClick={handleDiscard}
+          className="px-3 py-1 rounded bg-gray-700 hover:bg-gray-600 flex items-center space-x-1"
+        >
+          <X className="w-4 h-4" />
+          <span>Discard</span>
+        </button>
+        <button
+          onClick={() => handleSend()}
+          disabled={!draft || !to.trim() || sending}
+          className="px-3 py-1 rounded bg-blue-600 hover:bg-blue-500 disabled:opacity-50 flex items-center space-x-1"
+        >
+          <Send className="w-4 h-4" />
+          <span>{sending ? 'Sending...' : 'Send'}</span>
+        </button>
+      </div>
+    </div>
+  );
+};
+
+export default Compose;
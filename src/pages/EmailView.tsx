@@ -2,8 +2,9 @@ import React, { useState, useRef, useCallback, useEffect, useLayoutEffect } from
 import { useNavigate } from 'react-router-dom';
 import { Rnd } from 'react-rnd';
 import type { DraggableEvent, DraggableData } from 'react-draggable';
-import { invoke } from "@tauri-apps/api/tauri";
-import { Settings, Moon, Sun, Minimize2, Maximize2, X, Mail, Bot, CheckSquare, Send, Archive, Flag, Clock, MoreHorizontal, Layout, Check, Maximize, Minimize, Minus } from "lucide-react";
+import { invoke, convertFileSrc } from "@tauri-apps/api/tauri";
+import { listen } from "@tauri-apps/api/event";
+import { Settings, Moon, Sun, Minimize2, Maximize2, X, Mail, Bot, CheckSquare, Send, Archive, Flag, Clock, MoreHorizontal, Layout, Check, Maximize, Minimize, Minus, PictureInPicture2 } from "lucide-react";
 import { useNotifications } from '../components/NotificationSystem';
 import { sanitizeEmailContent, sanitizeSearchQuery, sanitizeTextInput, safeJsonParse } from '../utils/sanitization';
 
@@ -16,6 +17,72 @@ interface Email {
   received_time: string;
   is_unread: boolean;
   importance?: number;
+  account?: string;
+  snippet?: string;
+  deadline?: string | null;
+  conversation_id?: string;
+  muted?: boolean;
+  claimed_by?: string;
+}
+
+interface SharedMailbox {
+  address: string;
+  name: string;
+}
+
+interface BodySegment {
+  kind: 'content' | 'quoted' | 'signature';
+  text: string;
+}
+
+interface AttachmentInfo {
+  index: number;
+  name: string;
+  size: number;
+}
+
+interface StagedAttachment {
+  id: number;
+  name: string;
+  size: number;
+  path: string;
+}
+
+interface PendingApproval {
+  id: number;
+  email_id: string;
+  recipient: string;
+  subject: string;
+  reply_text: string;
+  source: string;
+  expires_at: string;
+}
+
+interface FailedReply {
+  id: number;
+  email_id: string;
+  reply_text: string;
+  reply_all: boolean;
+  attachments: string[];
+  attempts: number;
+  last_error: string;
+  retry_at: string | null;
+}
+
+interface ReaderView {
+  email_id: string;
+  subject: string;
+  sender: string;
+  markdown: string;
+  word_count: number;
+  reading_minutes: number;
+}
+
+interface StagingProgress {
+  id: number;
+  name: string;
+  copied: number;
+  total: number;
 }
 
 interface EmailViewProps {
@@ -25,11 +92,17 @@ interface EmailViewProps {
 
 const SerinaEmailReviewer: React.FC<EmailViewProps> = ({ darkMode, onToggleDarkMode }) => {
   const navigate = useNavigate();
-  const { showSuccess, showError } = useNotifications();
+  const { showSuccess, showError, showInfo } = useNotifications();
   const [emails, setEmails] = useState<Email[]>([]);
   const [selectedEmailIndex, setSelectedEmailIndex] = useState(0);
   const [selectedEmail, setSelectedEmail] = useState<Email | null>(null);
   const [replyText, setReplyText] = useState('');
+  const [replyAll, setReplyAll] = useState(false);
+  const [stagedAttachments, setStagedAttachments] = useState<StagedAttachment[]>([]);
+  const [stagingProgress, setStagingProgress] = useState<Record<number, StagingProgress>>({});
+  const [fileHover, setFileHover] = useState(false);
+  const [failedReplies, setFailedReplies] = useState<FailedReply[]>([]);
+  const [pendingApprovals, setPendingApprovals] = useState<PendingApproval[]>([]);
   const [selectedLLM, setSelectedLLM] = useState('openai');
   const [selectedModel, setSelectedModel] = useState('gpt-4');
 
@@ -55,6 +128,14 @@ const SerinaEmailReviewer: React.FC<EmailViewProps> = ({ darkMode, onToggleDarkM
   const [searchQuery, setSearchQuery] = useState('');
   const [loading, setLoading] = useState(true);
   const [summary, setSummary] = useState<string>("");
+  const [bodySegments, setBodySegments] = useState<BodySegment[]>([]);
+  const [attachments, setAttachments] = useState<AttachmentInfo[]>([]);
+  const [openAttachment, setOpenAttachment] = useState<{ name: string; src: string } | null>(null);
+  const [formatted, setFormatted] = useState(false);
+  const [readerView, setReaderView] = useState<ReaderView | null>(null);
+  // One-time codes spotted in new mail this session, by email id
+  const [codes, setCodes] = useState<Record<string, string>>({});
+  const [sharedMailboxes, setSharedMailboxes] = useState<SharedMailbox[]>([]);
   const [summaryLoading, setSummaryLoading] = useState(false);
   const [replyLoading, setReplyLoading] = useState(false);
   const gridRef = useRef<HTMLDivElement>(null);
@@ -231,13 +312,203 @@ const SerinaEmailReviewer: React.FC<EmailViewProps> = ({ darkMode, onToggleDarkM
 
   useEffect(() => {
     loadEmails();
+    invoke<SharedMailbox[]>('get_shared_mailboxes')
+      .then(setSharedMailboxes)
+      .catch(() => setSharedMailboxes([]));
+    // Lets the app keep alerts in-app while the inbox is on screen
+    invoke('set_active_view', { view: 'inbox' }).catch(() => {});
+    return () => {
+      invoke('set_active_view', { view: '' }).catch(() => {});
+    };
+  }, []);
+
+  // Split the open email so quoted history and signatures can be collapsed
+  useEffect(() => {
+    if (!selectedEmail) {
+      setBodySegments([]);
+      return;
+    }
+    invoke<BodySegment[]>('get_email_segments', { body: selectedEmail.body })
+      .then(setBodySegments)
+      .catch(() => setBodySegments([]));
+  }, [selectedEmail?.id, selectedEmail?.body]);
+
+  useEffect(() => {
+    setOpenAttachment(null);
+    setFormatted(false);
+    setReaderView(null);
+    if (!selectedEmail) {
+      setAttachments([]);
+      return;
+    }
+    invoke<AttachmentInfo[]>('list_attachments', { emailId: selectedEmail.id })
+      .then(setAttachments)
+      .catch(() => setAttachments([]));
+  }, [selectedEmail?.id]);
+
+  // Formatted view is served from the local cache by the serina-mail protocol; fetching the
+  // email first stores its HTML there, and offline the cached copy is shown as is
+  const handleToggleFormatted = async () => {
+    if (!selectedEmail) return;
+    if (!formatted) {
+      await invoke('get_email', { emailId: selectedEmail.id }).catch(() => {});
+    }
+    setReaderView(null);
+    setFormatted(!formatted);
+  };
+
+  // Reader mode: just the article of a newsletter, as Markdown
+  const handleToggleReader = async () => {
+    if (!selectedEmail) return;
+    if (readerView) {
+      setReaderView(null);
+      return;
+    }
+    try {
+      setReaderView(await invoke<ReaderView>('get_reader_view', { emailId: selectedEmail.id }));
+      setFormatted(false);
+    } catch (error) {
+      showError('Reader mode unavailable', String(error));
+    }
+  };
+
+  // The file is streamed to disk by the app and loaded by path, not passed over IPC
+  const handleOpenAttachment = async (attachment: AttachmentInfo) => {
+    if (!selectedEmail) return;
+    try {
+      const path = await invoke<string>('download_attachment', { emailId: selectedEmail.id, attachment });
+      setOpenAttachment({ name: attachment.name, src: convertFileSrc(path) });
+    } catch (error) {
+      showError('Failed to open attachment', String(error));
+    }
+  };
+
+  // Patch the list from sync events instead of refetching it
+  useEffect(() => {
+    const sanitize = (email: Email): Email => ({
+      ...email,
+      subject: sanitizeTextInput(email.subject, 200),
+      sender: sanitizeTextInput(email.sender, 100),
+      sender_email: sanitizeTextInput(email.sender_email, 254),
+      body: sanitizeEmailContent(email.body),
+      snippet: sanitizeTextInput(email.snippet ?? '', 200)
+    });
+    const unlisten = Promise.all([
+      listen<Email>('email-added', ({ payload }) => {
+        setEmails(current => current.some(email => email.id === payload.id)
+          ? current
+          : [sanitize(payload), ...current]);
+      }),
+      listen<Email>('email-updated', ({ payload }) => {
+        setEmails(current => current.map(email =>
+          email.id === payload.id ? sanitize(payload) : email));
+      }),
+      listen<{ id: string }>('email-removed', ({ payload }) => {
+        setEmails(current => current.filter(email => email.id !== payload.id));
+      }),
+      // Alerts the app kept in-app because the inbox is already on screen
+      listen<{ title: string; body: string }>('in-app-notification', ({ payload }) => {
+        showInfo(sanitizeTextInput(payload.title, 200), sanitizeTextInput(payload.body, 500));
+      }),
+      // Files dropped on the window are staged by the app for the reply being composed
+      listen('tauri://file-drop-hover', () => setFileHover(true)),
+      listen('tauri://file-drop-cancelled', () => setFileHover(false)),
+      listen('tauri://file-drop', () => setFileHover(false)),
+      listen<StagingProgress>('attachment-progress', ({ payload }) => {
+        setStagingProgress(current => ({ ...current, [payload.id]: payload }));
+      }),
+      listen<{ name: string; reason: string }>('attachment-rejected', ({ payload }) => {
+        showError(`Couldn't attach ${sanitizeTextInput(payload.name, 200)}`, payload.reason);
+      }),
+      listen<StagedAttachment[]>('staged-attachments', ({ payload }) => {
+        setStagedAttachments(payload);
+        setStagingProgress({});
+      }),
+      // Replies that failed to send are kept and retried by the app
+      listen<FailedReply[]>('failed-replies', ({ payload }) => setFailedReplies(payload)),
+      // Automated replies wait here until approved or rejected
+      listen<PendingApproval[]>('pending-approvals', ({ payload }) => setPendingApprovals(payload)),
+      listen<{ email_id: string; sender: string; code: string }>('verification-code', ({ payload }) => {
+        setCodes(current => ({ ...current, [payload.email_id]: payload.code }));
+      }),
+      listen<{ email_id: string; subject: string; sender: string; summary: string }>('newsletter-digested', ({ payload }) => {
+        showInfo(
+          `Newsletter archived: ${sanitizeTextInput(payload.subject, 200)}`,
+          sanitizeTextInput(payload.summary, 500)
+        );
+      }),
+      listen('reply-resent', () => {
+        showSuccess('Reply sent', 'A reply that failed earlier has now been delivered.');
+      }),
+      listen<FailedReply>('edit-reply', ({ payload }) => {
+        setReplyText(payload.reply_text);
+        setReplyAll(payload.reply_all);
+        showInfo('Reply restored', 'The unsent reply is back in the reply box.');
+      }),
+      listen<{ host: string; wait_secs: number; attempt: number }>('rate-limited', ({ payload }) => {
+        showInfo('Rate limited', `${payload.host} asked to slow down; retrying in ${payload.wait_secs}s`);
+      }),
+      listen<{ state: 'closed' | 'open'; consecutive_failures: number }>('backend-health', ({ payload }) => {
+        if (payload.state === 'open') {
+          showError('Backend unavailable', 'Actions will fail fast until it answers again');
+        } else {
+          showSuccess('Backend reconnected');
+        }
+      })
+    ]);
+    return () => {
+      unlisten.then(handlers => handlers.forEach(stop => stop()));
+    };
   }, []);
 
+  useEffect(() => {
+    invoke<StagedAttachment[]>('get_staged_attachments')
+      .then(setStagedAttachments)
+      .catch(error => console.error('Failed to load staged attachments:', error));
+    invoke<FailedReply[]>('get_failed_replies')
+      .then(setFailedReplies)
+      .catch(error => console.error('Failed to load failed replies:', error));
+    invoke<PendingApproval[]>('list_pending_approvals')
+      .then(setPendingApprovals)
+      .catch(error => console.error('Failed to load pending approvals:', error));
+  }, []);
+
+  const handleApproval = async (pending: PendingApproval, approved: boolean) => {
+    try {
+      if (approved) {
+        await invoke('approve', { id: pending.id });
+        showSuccess('Reply sent', `Sent to ${sanitizeTextInput(pending.recipient, 200)}`);
+      } else {
+        await invoke('reject', { id: pending.id });
+      }
+    } catch (error) {
+      showError(approved ? 'Reply not sent' : 'Failed to reject reply', String(error));
+    }
+  };
+
+  const handleFailedReply = async (reply: FailedReply, action: 'retry' | 'edit' | 'discard') => {
+    const command = { retry: 'retry_failed_reply', edit: 'edit_failed_reply', discard: 'discard_failed_reply' }[action];
+    try {
+      await invoke(command, { emailId: reply.email_id });
+    } catch (error) {
+      showError(action === 'retry' ? 'Reply still not sent' : 'Failed to update reply', String(error));
+    }
+  };
+
+  const handleRemoveStaged = async (id: number) => {
+    try {
+      setStagedAttachments(await invoke<StagedAttachment[]>('remove_staged_attachment', { id }));
+    } catch (error) {
+      showError('Failed to remove attachment', String(error));
+    }
+  };
+
   useEffect(() => {
     if (emails.length > 0) {
       const email = emails[selectedEmailIndex];
       setSelectedEmail(email);
       if (email && email.id !== selectedEmail?.id) {
+        setReplyAll(false);
         generateSummary(email);
       }
     }
@@ -292,7 +563,8 @@ const SerinaEmailReviewer: React.FC<EmailViewProps> = ({ darkMode, onToggleDarkM
         subject: sanitizeTextInput(email.subject, 200),
         sender: sanitizeTextInput(email.sender, 100),
         sender_email: sanitizeTextInput(email.sender_email, 254),
-        body: sanitizeEmailContent(email.body)
+        body: sanitizeEmailContent(email.body),
+        snippet: sanitizeTextInput(email.snippet ?? '', 200)
       }));
       
       setEmails(sanitizedEmails);
@@ -311,7 +583,8 @@ const SerinaEmailReviewer: React.FC<EmailViewProps> = ({ darkMode, onToggleDarkM
     try {
       setSummaryLoading(true);
       const response = await invoke('summarize_email', { 
-        emailContent: email.body 
+        emailContent: email.body,
+        emailId: email.id
       });
       const summaryData = safeJsonParse(response as string, { summary: "Unable to generate summary" });
       setSummary(sanitizeTextInput(summaryData.summary, 2000));
@@ -330,10 +603,16 @@ const SerinaEmailReviewer: React.FC<EmailViewProps> = ({ darkMode, onToggleDarkM
       setReplyLoading(true);
       const response = await invoke('generate_reply', {
         emailContent: selectedEmail.body,
-        instruction: "Write a professional, helpful reply"
+        instruction: "Write a professional, helpful reply",
+        emailId: selectedEmail.id
       });
-      const replyData = safeJsonParse(response as string, { reply: "" });
+      const replyData: { reply: string; warnings?: { kind: string; message: string }[] } =
+        safeJsonParse(response as string, { reply: "" });
       setReplyText(sanitizeTextInput(replyData.reply, 10000));
+      // Sending asks again unless the draft is fixed first
+      if (replyData.warnings?.length) {
+        showInfo('Check this draft before sending', replyData.warnings.map(warning => warning.message).join('\n'));
+      }
     } catch (error) {
       console.error('Failed to generate reply:', error);
       showError('Failed to generate reply', 'There was an error generating the AI reply. Please try again.');
@@ -342,7 +621,15 @@ const SerinaEmailReviewer: React.FC<EmailViewProps> = ({ darkMode, onToggleDarkM
     }
   };
 
-  const handleSendReply = async () => {
+  const handleSendReply = async (
+    overrides: {
+      allow_missing_attachment?: boolean;
+      allow_collision?: boolean;
+      acknowledge_guardrails?: boolean;
+      allow_reply_all?: boolean;
+      allow_external?: boolean;
+    } = {}
+  ) => {
     if (!selectedEmail || !replyText.trim()) return;
 
     const sanitizedReplyText = sanitizeTextInput(replyText, 10000);
@@ -354,13 +641,38 @@ const SerinaEmailReviewer: React.FC<EmailViewProps> = ({ darkMode, onToggleDarkM
     try {
       await invoke('send_reply', {
         emailId: selectedEmail.id,
-        replyText: sanitizedReplyText
+        replyText: sanitizedReplyText,
+        options: { reply_all: replyAll, ...overrides }
       });
       setReplyText("");
+      setReplyAll(false);
       showSuccess('Reply sent successfully!', 'Your email reply has been delivered.');
     } catch (error) {
+      // Held back by a check; the user can look again or send anyway
+      const blocked: { error?: string; warnings?: { kind: string; message: string }[] } =
+        safeJsonParse(String(error), {}) ?? {};
+      if (blocked.error === 'send_blocked' && blocked.warnings) {
+        const messages = blocked.warnings.map(warning => warning.message).join('\n');
+        if (window.confirm(`${messages}\n\nSend anyway?`)) {
+          await handleSendReply({
+            allow_missing_attachment: true,
+            allow_collision: true,
+            acknowledge_guardrails: true,
+            allow_reply_all: true,
+            allow_external: true
+          });
+        }
+        return;
+      }
       console.error('Failed to send reply:', error);
-      showError('Failed to send reply', 'There was an error sending your email. Please try again.');
+      const failed = await invoke<FailedReply[]>('get_failed_replies').catch(() => []);
+      if (failed.some(reply => reply.email_id === selectedEmail.id)) {
+        // Saved for retry, so it no longer belongs in the reply box
+        setReplyText("");
+        showError('Reply not sent yet', String(error));
+      } else {
+        showError('Failed to send reply', 'There was an error sending your email. Please try again.');
+      }
     }
   };
 
@@ -409,6 +721,44 @@ const SerinaEmailReviewer: React.FC<EmailViewProps> = ({ darkMode, onToggleDarkM
     }
   };
 
+  // Muted conversations always rank lowest
+  const priorityOf = (email: Email) => (email.muted ? 0 : email.importance);
+
+  const handleMute = async (email: Email, minutes: string) => {
+    try {
+      if (email.muted) {
+        await invoke('unmute_thread', { threadId: email.conversation_id });
+        showSuccess('Conversation unmuted');
+      } else {
+        await invoke('mute_thread', {
+          threadId: email.conversation_id,
+          minutes: minutes === 'forever' ? null : Number(minutes)
+        });
+        showSuccess('Conversation muted', 'New replies won\'t notify you or count toward reminders');
+      }
+    } catch (error) {
+      showError('Failed to change mute', String(error));
+    }
+  };
+
+  const sharedMailboxOf = (email: Email) =>
+    sharedMailboxes.find(mailbox => mailbox.address.trim().toLowerCase() === (email.account ?? '').toLowerCase());
+
+  const handleClaim = async (email: Email, release: boolean, force = false) => {
+    try {
+      await invoke(release ? 'release_email' : 'claim_email', { emailId: email.id, force });
+      showSuccess(release ? 'Claim released' : 'Claimed', release ? 'Anyone on the team can pick it up' : 'Teammates will see you have it');
+    } catch (error) {
+      const message = String(error);
+      // Someone else holds it; offer to take it over
+      if (!force && message.includes('claimed by') && window.confirm(`${message}. Take it over?`)) {
+        await handleClaim(email, release, true);
+        return;
+      }
+      showError(release ? 'Failed to release' : 'Failed to claim', message);
+    }
+  };
+
   const getPriorityLabel = (importance: number = 1) => {
     switch (importance) {
       case 2: return 'high';
@@ -459,7 +809,7 @@ const SerinaEmailReviewer: React.FC<EmailViewProps> = ({ darkMode, onToggleDarkM
                 selectedEmailIndex === index 
                   ? 'bg-gradient-to-r from-blue-600/20 to-purple-600/20 border-l-4 border-l-blue-400' 
                   : 'hover:bg-gray-700/30'
-              }`}
+              }${email.muted ? ' opacity-60' : ''}`}
               style={{ borderColor: '#374151' }}
             >
               <div className="flex items-start justify-between mb-1">
@@ -471,8 +821,8 @@ const SerinaEmailReviewer: React.FC<EmailViewProps> = ({ darkMode, onToggleDarkM
                   <div 
                     className="w-2 h-2 rounded-full shadow-sm"
                     style={{ 
-                      backgroundColor: getPriorityColor(email.importance),
-                      boxShadow: `0 0 4px ${getPriorityColor(email.importance)}40`
+                      backgroundColor: getPriorityColor(priorityOf(email)),
+                      boxShadow: `0 0 4px ${getPriorityColor(priorityOf(email))}40`
                     }}
                   ></div>
                   <span className="text-xs text-gray-400">
@@ -482,7 +832,7 @@ const SerinaEmailReviewer: React.FC<EmailViewProps> = ({ darkMode, onToggleDarkM
               </div>
               <h4 className="font-medium text-gray-300 text-sm mb-1 line-clamp-1">{email.subject}</h4>
               <p className="text-xs text-gray-500 line-clamp-2">
-                {email.body.substring(0, 100)}...
+                {email.snippet || `${email.body.substring(0, 100)}...`}
               </p>
             </div>
           ))
@@ -511,16 +861,22 @@ const SerinaEmailReviewer: React.FC<EmailViewProps> = ({ darkMode, onToggleDarkM
                 <div className="flex items-center space-x-3 text-sm text-gray-400">
                   <span className="font-medium text-gray-300">{selectedEmail.sender}</span>
                   <span>{new Date(selectedEmail.received_time).toLocaleString()}</span>
+                  {sharedMailboxOf(selectedEmail) && (
+                    <span className="text-cyan-300">
+                      {sharedMailboxOf(selectedEmail)?.name || selectedEmail.account}
+                      {selectedEmail.claimed_by ? ` · claimed by ${selectedEmail.claimed_by}` : ' · unclaimed'}
+                    </span>
+                  )}
                 </div>
               </div>
               <span 
                 className="px-3 py-1 rounded-full text-xs font-medium text-white capitalize shadow-lg"
                 style={{ 
-                  backgroundColor: getPriorityColor(selectedEmail.importance),
-                  boxShadow: `0 0 12px ${getPriorityColor(selectedEmail.importance)}40`
+                  backgroundColor: getPriorityColor(priorityOf(selectedEmail)),
+                  boxShadow: `0 0 12px ${getPriorityColor(priorityOf(selectedEmail))}40`
                 }}
               >
-                {getPriorityLabel(selectedEmail.importance)}
+                {selectedEmail.muted ? 'muted' : getPriorityLabel(selectedEmail.importance)}
               </span>
             </div>
             <div className="flex space-x-2 mt-3">
@@ -537,16 +893,149 @@ const SerinaEmailReviewer: React.FC<EmailViewProps> = ({ darkMode, onToggleDarkM
                 <CheckSquare className="w-4 h-4" />
                 <span>Create Task</span>
               </button>
+              <button
+                onClick={handleToggleFormatted}
+                className="px-3 py-1 bg-gray-600 text-white rounded text-sm hover:bg-gray-500"
+              >
+                {formatted ? 'Plain Text' : 'Formatted'}
+              </button>
+              <button
+                onClick={handleToggleReader}
+                className="px-3 py-1 bg-gray-600 text-white rounded text-sm hover:bg-gray-500"
+              >
+                {readerView ? 'Full Email' : 'Reader'}
+              </button>
+              <button
+                onClick={() => invoke('print_email', { emailId: selectedEmail.id })
+                  .catch(error => showError('Failed to print', String(error)))}
+                className="px-3 py-1 bg-gray-600 text-white rounded text-sm hover:bg-gray-500"
+              >
+                Print
+              </button>
+              <button
+                onClick={() => invoke('pin_email', { emailId: selectedEmail.id })
+                  .then(() => showSuccess('Pinned', 'It stays in the tray until you mark it done'))
+                  .catch(error => showError('Failed to pin email', String(error)))}
+                className="px-3 py-1 bg-amber-600 text-white rounded text-sm hover:bg-amber-500"
+              >
+                Pin
+              </button>
+              <button
+                onClick={() => invoke('copy_email_as_markdown', { emailId: selectedEmail.id })
+                  .then(() => showSuccess('Email copied', 'Paste it into a doc or chat'))
+                  .catch(error => showError('Failed to copy email', String(error)))}
+                className="px-3 py-1 bg-gray-600 text-white rounded text-sm hover:bg-gray-500"
+              >
+                Copy
+              </button>
+              {codes[selectedEmail.id] && (
+                <button
+                  onClick={() => invoke('copy_verification_code', { emailId: selectedEmail.id })
+                    .then(() => showSuccess('Code copied', codes[selectedEmail.id]))
+                    .catch(error => showError('Failed to copy code', String(error)))}
+                  className="px-3 py-1 bg-cyan-600 text-white rounded text-sm hover:bg-cyan-500 font-mono"
+                >
+                  Copy code {codes[selectedEmail.id]}
+                </button>
+              )}
+              {sharedMailboxOf(selectedEmail) && (
+                <button
+                  onClick={() => handleClaim(selectedEmail, !!selectedEmail.claimed_by)}
+                  className="px-3 py-1 bg-cyan-700 text-white rounded text-sm hover:bg-cyan-600"
+                >
+                  {selectedEmail.claimed_by ? 'Release' : 'Claim'}
+                </button>
+              )}
+              {selectedEmail.conversation_id && (selectedEmail.muted ? (
+                <button
+                  onClick={() => handleMute(selectedEmail, '')}
+                  className="px-3 py-1 bg-gray-600 text-white rounded text-sm hover:bg-gray-500"
+                >
+                  Unmute
+                </button>
+              ) : (
+                <select
+                  value=""
+                  onChange={e => handleMute(selectedEmail, e.target.value)}
+                  aria-label="Mute conversation"
+                  className="px-2 py-1 bg-gray-600 text-white rounded text-sm hover:bg-gray-500"
+                >
+                  <option value="" disabled>Mute</option>
+                  <option value="60">For 1 hour</option>
+                  <option value="480">For 8 hours</option>
+                  <option value="1440">For 1 day</option>
+                  <option value="forever">Until unmuted</option>
+                </select>
+              ))}
             </div>
           </div>
           <div className="flex-1 p-4 overflow-y-auto" style={{ backgroundColor: '#1f2937' }}>
-            <div className="bg-gray-800 rounded-lg p-4 border border-gray-700 shadow-lg">
-              <div className="prose max-w-none text-sm text-gray-300">
-                <div className="whitespace-pre-wrap break-words">
-                  {selectedEmail.body}
+            {attachments.length > 0 && (
+              <div className="mb-3 flex flex-wrap gap-2">
+                {attachments.map(attachment => (
+                  <button
+                    key={attachment.index}
+                    onClick={() => handleOpenAttachment(attachment)}
+                    className="px-2 py-1 rounded border border-gray-600 text-xs text-gray-300 hover:bg-gray-700"
+                  >
+                    {sanitizeTextInput(attachment.name, 200)} ({Math.ceil(attachment.size / 1024)} KB)
+                  </button>
+                ))}
+              </div>
+            )}
+            {openAttachment && (
+              <div className="mb-3 p-3 rounded-lg border border-gray-700 bg-gray-800 text-sm text-gray-300">
+                {/\.(png|jpe?g|gif|webp|bmp)$/i.test(openAttachment.name) ? (
+                  <img src={openAttachment.src} alt={openAttachment.name} className="max-w-full max-h-96" />
+                ) : (
+                  <a href={openAttachment.src} download={openAttachment.name} className="text-cyan-400 underline">
+                    Save {sanitizeTextInput(openAttachment.name, 200)}
+                  </a>
+                )}
+              </div>
+            )}
+            {readerView ? (
+              <div className="bg-gray-800 rounded-lg p-4 border border-gray-700 shadow-lg">
+                <p className="mb-3 text-xs text-gray-500">
+                  {readerView.reading_minutes} min read · {readerView.word_count} words
+                </p>
+                <div className="whitespace-pre-wrap break-words text-sm text-gray-300 leading-relaxed">
+                  {sanitizeEmailContent(readerView.markdown)}
                 </div>
               </div>
-            </div>
+            ) : formatted ? (
+              <iframe
+                title={selectedEmail.subject}
+                src={convertFileSrc(selectedEmail.id, 'serina-mail')}
+                sandbox=""
+                className="w-full h-full min-h-[400px] rounded-lg border border-gray-700 bg-white"
+              />
+            ) : (
+              <div className="bg-gray-800 rounded-lg p-4 border border-gray-700 shadow-lg">
+                <div className="prose max-w-none text-sm text-gray-300">
+                  {bodySegments.length === 0 ? (
+                    <div className="whitespace-pre-wrap break-words">
+                      {selectedEmail.body}
+                    </div>
+                  ) : (
+                    bodySegments.map((segment, index) => segment.kind === 'content' ? (
+                      <div key={index} className="whitespace-pre-wrap break-words mb-3">
+                        {sanitizeEmailContent(segment.text)}
+                      </div>
+                    ) : (
+                      <details key={index} className="mb-3 text-gray-500">
+                        <summary className="cursor-pointer text-xs">
+                          {segment.kind === 'quoted' ? 'Show quoted text' : 'Show signature'}
+                        </summary>
+                        <div className="whitespace-pre-wrap break-words mt-2">
+                          {sanitizeEmailContent(segment.text)}
+                        </div>
+                      </details>
+                    ))
+                  )}
+                </div>
+              </div>
+            )}
           </div>
         </>
       )}
@@ -595,7 +1084,19 @@ const SerinaEmailReviewer: React.FC<EmailViewProps> = ({ darkMode, onToggleDarkM
       </div>
       <div className="flex-1 p-3 overflow-y-auto">
         <div className="mb-4">
-          <h4 className="font-medium text-gray-200 text-sm mb-2">Summary</h4>
+          <div className="flex items-center justify-between mb-2">
+            <h4 className="font-medium text-gray-200 text-sm">Summary</h4>
+            {selectedEmail && summary && !summaryLoading && (
+              <button
+                onClick={() => invoke('copy_summary', { emailId: selectedEmail.id })
+                  .then(() => showSuccess('Summary copied', 'Paste it into a doc or chat'))
+                  .catch(error => showError('Failed to copy summary', String(error)))}
+                className="text-xs text-gray-400 hover:text-gray-200"
+              >
+                Copy
+              </button>
+            )}
+          </div>
           <div className="bg-gray-800 p-3 rounded border border-gray-700 text-sm shadow-lg">
             {summaryLoading ? (
               <div className="flex items-center space-x-2">
@@ -650,12 +1151,78 @@ const SerinaEmailReviewer: React.FC<EmailViewProps> = ({ darkMode, onToggleDarkM
           </button>
         </div>
       </div>
-      <div className="flex-1 p-3">
+      <div className="flex-1 p-3 flex flex-col gap-2">
+        {pendingApprovals.map(pending => (
+          <div key={pending.id} className="px-3 py-2 rounded border border-yellow-700 bg-yellow-900/20 text-xs text-yellow-100 space-y-1">
+            <p>
+              Automatic reply to {sanitizeTextInput(pending.recipient, 200)} about "{sanitizeTextInput(pending.subject, 200)}"
+              is waiting for approval until {new Date(pending.expires_at).toLocaleString()}.
+            </p>
+            <p className="text-gray-300 whitespace-pre-wrap line-clamp-3">{sanitizeTextInput(pending.reply_text, 1000)}</p>
+            <div className="flex space-x-3">
+              <button onClick={() => handleApproval(pending, true)} className="text-cyan-300 hover:text-cyan-200">
+                Approve and send
+              </button>
+              <button onClick={() => handleApproval(pending, false)} className="text-red-300 hover:text-red-200">
+                Reject
+              </button>
+            </div>
+          </div>
+        ))}
+        {failedReplies
+          .filter(reply => reply.email_id === selectedEmail?.id)
+          .map(reply => (
+            <div key={reply.id} className="px-3 py-2 rounded border border-red-800 bg-red-900/30 text-xs text-red-200 space-y-1">
+              <p>
+                Reply not sent: {sanitizeTextInput(reply.last_error, 300)}.{' '}
+                {reply.retry_at
+                  ? `Trying again at ${new Date(reply.retry_at).toLocaleTimeString()}.`
+                  : 'Automatic retries have stopped.'}
+              </p>
+              <div className="flex space-x-3">
+                <button onClick={() => handleFailedReply(reply, 'retry')} className="text-cyan-300 hover:text-cyan-200">
+                  Retry now
+                </button>
+                <button onClick={() => handleFailedReply(reply, 'edit')} className="text-gray-300 hover:text-white">
+                  Edit
+                </button>
+                <button onClick={() => handleFailedReply(reply, 'discard')} className="text-red-300 hover:text-red-200">
+                  Discard
+                </button>
+              </div>
+            </div>
+          ))}
+        {(stagedAttachments.length > 0 || Object.keys(stagingProgress).length > 0) && (
+          <div className="flex flex-wrap gap-2">
+            {stagedAttachments.map(attachment => (
+              <span
+                key={attachment.id}
+                className="flex items-center space-x-1 px-2 py-1 rounded border border-gray-600 text-xs text-gray-300"
+              >
+                <span>{sanitizeTextInput(attachment.name, 200)} ({Math.ceil(attachment.size / 1024)} KB)</span>
+                <button
+                  onClick={() => handleRemoveStaged(attachment.id)}
+                  className="text-gray-500 hover:text-gray-200"
+                  aria-label={`Remove ${attachment.name}`}
+                >
+                  ×
+                </button>
+              </span>
+            ))}
+            {Object.values(stagingProgress)
+              .filter(progress => progress.copied < progress.total)
+              .map(progress => (
+                <span key={progress.id} className="px-2 py-1 rounded border border-blue-700 text-xs text-blue-300">
+                  {sanitizeTextInput(progress.name, 200)} {Math.floor((progress.copied / progress.total) * 100)}%
+                </span>
+              ))}
+          </div>
+        )}
         <textarea 
           value={replyText}
           onChange={(e) => setReplyText(sanitizeTextInput(e.target.value, 10000))}
-          placeholder="Type your reply here or use AI to generate one..."
-          className="w-full h-full border border-gray-600 rounded p-3 text-sm resize-none focus:outline-none focus:ring-2 focus:ring-blue-500 focus:border-transparent transition-all duration-200 bg-gray-800 text-gray-300 placeholder-gray-500"
+          placeholder={fileHover ? "Drop files to attach them to this reply" : "Type your reply here or use AI to generate one..."}
+          className={`w-full flex-1 border${fileHover ? ' ring-2 ring-blue-500' : ''} border-gray-600 rounded p-3 text-sm resize-none focus:outline-none focus:ring-2 focus:ring-blue-500 focus:border-transparent transition-all duration-200 bg-gray-800 text-gray-300 placeholder-gray-500`}
         />
       </div>
     </div>
@@ -728,6 +1295,13 @@ const SerinaEmailReviewer: React.FC<EmailViewProps> = ({ darkMode, onToggleDarkM
                 </button>
               </div>
             )}
+            <button
+              onClick={() => invoke('show_mini_inbox').catch(error => console.error('Failed to open mini inbox:', error))}
+              className="p-2 text-gray-400 hover:text-gray-200 transition-colors duration-200 hover:scale-110"
+              title="Mini Inbox"
+            >
+              <PictureInPicture2 className="w-5 h-5" />
+            </button>
             <button
               onClick={() => navigate('/settings')}
               className="p-2 text-gray-400 hover:text-gray-200 transition-colors duration-200 hover:scale-110"
@@ -845,6 +1419,14 @@ const SerinaEmailReviewer: React.FC<EmailViewProps> = ({ darkMode, onToggleDarkM
           </div>
           
           <div className="flex items-center space-x-3">
+            <label className="flex items-center space-x-2 text-sm text-gray-300 cursor-pointer">
+              <input
+                type="checkbox"
+                checked={replyAll}
+                onChange={(e) => setReplyAll(e.target.checked)}
+              />
+              <span>Reply all</span>
+            </label>
             <button 
               onClick={handleCreateTask}
               className="px-4 py-2 rounded-lg font-medium transition-all duration-200 hover:scale-105 shadow-lg flex items-center space-x-2" 
@@ -858,7 +1440,7 @@ const SerinaEmailReviewer: React.FC<EmailViewProps> = ({ darkMode, onToggleDarkM
               <span>Create Task</span>
             </button>
             <button 
-              onClick={handleSendReply}
+              onClick={() => handleSendReply()}
               className="px-6 py-2 rounded-lg font-medium transition-all duration-200 hover:scale-105 shadow-lg flex items-center space-x-2" 
               style={{
                 background: 'linear-gradient(135deg, #3b82f6 0%, #1d4ed8 100%)',
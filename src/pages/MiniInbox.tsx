@@ -0,0 +1,194 @@
+import React, { useCallback, useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/tauri';
+import { listen } from '@tauri-apps/api/event';
+import { Mail, Check, Clock, ExternalLink, Pin, X } from 'lucide-react';
+import { safeJsonParse, sanitizeTextInput } from '../utils/sanitization';
+
+interface Email {
+  id: string;
+  subject: string;
+  sender: string;
+  received_time: string;
+  is_unread: boolean;
+  snippet?: string;
+  muted?: boolean;
+}
+
+interface PinnedEmail {
+  email_id: string;
+  subject: string;
+  sender: string;
+  pinned_at: string;
+}
+
+type InlineAction = 'open' | 'mark_read' | 'snooze' | 'pin';
+
+const LIMIT = 8;
+
+// Always-on-top picture-in-picture list of the latest unread emails; drag it by the header
+const MiniInbox: React.FC = () => {
+  const [emails, setEmails] = useState<Email[]>([]);
+  const [loading, setLoading] = useState(true);
+  const [pins, setPins] = useState<PinnedEmail[]>([]);
+
+  const loadEmails = useCallback(async () => {
+    try {
+      const response = await invoke('get_emails', { limit: 50 });
+      const unread = safeJsonParse(response as string, [])
+        .filter((email: Email) => email.is_unread && !email.muted)
+        .slice(0, LIMIT)
+        .map((email: Email) => ({
+          ...email,
+          subject: sanitizeTextInput(email.subject, 200),
+          sender: sanitizeTextInput(email.sender, 100),
+          snippet: sanitizeTextInput(email.snippet ?? '', 200)
+        }));
+      setEmails(unread);
+    } catch (error) {
+      console.error('Failed to load unread emails:', error);
+    } finally {
+      setLoading(false);
+    }
+  }, []);
+
+  useEffect(() => {
+    loadEmails();
+    invoke<PinnedEmail[]>('get_pinned_emails')
+      .then(setPins)
+      .catch(error => console.error('Failed to load pinned emails:', error));
+    const unlisten = Promise.all([
+      listen('email-added', () => loadEmails()),
+      listen<{ id: string }>('email-removed', ({ payload }) =>
+        setEmails(prev => prev.filter(email => email.id !== payload.id))
+      ),
+      listen('unread-count-changed', () => loadEmails()),
+      listen<PinnedEmail[]>('pins-changed', ({ payload }) => setPins(payload))
+    ]);
+    return () => {
+      unlisten.then(handlers => handlers.forEach(stop => stop()));
+    };
+  }, [loadEmails]);
+
+  const runAction = async (email: Email, action: InlineAction) => {
+    try {
+      await invoke('run_email_action', { emailId: email.id, action });
+      if (action !== 'open' && action !== 'pin') {
+        setEmails(prev => prev.filter(e => e.id !== email.id));
+      }
+    } catch (error) {
+      console.error(`Failed to ${action} email:`, error);
+    }
+  };
+
+  const dismissPin = async (pin: PinnedEmail) => {
+    try {
+      setPins(await invoke<PinnedEmail[]>('unpin_email', { emailId: pin.email_id }));
+    } catch (error) {
+      console.error('Failed to dismiss pin:', error);
+    }
+  };
+
+  const close = () => {
+    invoke('close_window').catch(error => console.error('Failed to close mini inbox:', error));
+  };
+
+  return (
+    <div className="h-screen flex flex-col select-none rounded-lg border border-gray-700 bg-gray-900/95 text-white overflow-hidden">
+      <div data-tauri-drag-region className="flex items-center justify-between px-3 py-2 border-b border-gray-700">
+        <div data-tauri-drag-region className="flex items-center space-x-2 text-cyan-400">
+          <Mail className="w-4 h-4" />
+          <h1 className="text-xs font-bold tracking-wider">UNREAD</h1>
+          <span className="text-xs text-gray-400">{loading ? '…' : emails.length}</span>
+        </div>
+        <button
+          onClick={close}
+          aria-label="Close mini inbox"
+          className="p-1 rounded text-gray-400 hover:text-white hover:bg-gray-700"
+        >
+          <X className="w-4 h-4" />
+        </button>
+      </div>
+
+      {pins.length > 0 && (
+        <ul className="border-b border-gray-700 divide-y divide-gray-800" aria-label="Pinned emails">
+          {pins.map(pin => (
+            <li key={pin.email_id} className="flex items-center px-3 py-2 space-x-2 hover:bg-gray-800">
+              <Pin className="w-3 h-3 text-amber-400 flex-shrink-0" />
+              <button
+                onClick={() => invoke('run_email_action', { emailId: pin.email_id, action: 'open' })}
+                className="flex-1 min-w-0 text-left"
+                title="Open in SERINA"
+              >
+                <p className="text-xs text-gray-300 truncate">{sanitizeTextInput(pin.subject, 200) || '(no subject)'}</p>
+                <p className="text-xs text-gray-500 truncate">{sanitizeTextInput(pin.sender, 100)}</p>
+              </button>
+              <button
+                onClick={() => dismissPin(pin)}
+                aria-label="Dismiss pin"
+                className="flex items-center space-x-1 text-xs text-green-400 hover:text-green-300"
+              >
+                <Check className="w-3 h-3" />
+                <span>Done</span>
+              </button>
+            </li>
+          ))}
+        </ul>
+      )}
+
+      <ul className="flex-1 overflow-y-auto divide-y divide-gray-800" aria-live="polite">
+        {!loading && emails.length === 0 && (
+          <li className="p-4 text-center text-xs text-gray-400">All caught up</li>
+        )}
+        {emails.map(email => (
+          <li key={email.id} className="group px-3 py-2 hover:bg-gray-800">
+            <button
+              onClick={() => runAction(email, 'open')}
+              className="w-full text-left"
+              title="Open in SERINA"
+            >
+              <p className="text-xs font-semibold truncate">{email.sender}</p>
+              <p className="text-xs text-gray-300 truncate">{email.subject || '(no subject)'}</p>
+              {email.snippet && <p className="text-xs text-gray-500 truncate">{email.snippet}</p>}
+            </button>
+            <div className="mt-1 flex space-x-2 opacity-0 group-hover:opacity-100 focus-within:opacity-100">
+              <button
+                onClick={() => runAction(email, 'mark_read')}
+                aria-label="Mark as read"
+                className="flex items-center space-x-1 text-xs text-green-400 hover:text-green-300"
+              >
+                <Check className="w-3 h-3" />
+                <span>Read</span>
+              </button>
+              <button
+                onClick={() => runAction(email, 'snooze')}
+                aria-label="Snooze"
+                className="flex items-center space-x-1 text-xs text-yellow-400 hover:text-yellow-300"
+              >
+                <Clock className="w-3 h-3" />
+                <span>Snooze</span>
+              </button>
+              <button
+                onClick={() => runAction(email, 'pin')}
+                aria-label="Pin"
+                className="flex items-center space-x-1 text-xs text-amber-400 hover:text-amber-300"
+              >
+                <Pin className="w-3 h-3" />
+                <span>Pin</span>
+              </button>
+              <button
+                onClick={() => runAction(email, 'open')}
+                aria-label="Open in SERINA"
+                className="flex items-center space-x-1 text-xs text-cyan-400 hover:text-cyan-300"
+              >
+                <ExternalLink className="w-3 h-3" />
+                <span>Open</span>
+              </button>
+            </div>
+          </li>
+        ))}
+      </ul>
+    </div>
+  );
+};
+
+export default MiniInbox;
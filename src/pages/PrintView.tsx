@@ -0,0 +1,72 @@
+import React, { useEffect, useState } from 'react';
+import { useSearchParams } from 'react-router-dom';
+import { invoke } from '@tauri-apps/api/tauri';
+
+interface PrintableEmail {
+  subject: string;
+  from: string;
+  received_time: string;
+  attachments: { index: number; name: string; size: number }[];
+  // Sanitized by the app before it gets here
+  body_html: string;
+}
+
+// Rendered in the hidden print window; asks for the OS print dialog once the layout is ready
+const PrintView: React.FC = () => {
+  const [params] = useSearchParams();
+  const [email, setEmail] = useState<PrintableEmail | null>(null);
+  const [error, setError] = useState('');
+
+  useEffect(() => {
+    const emailId = params.get('email');
+    if (!emailId) return;
+    invoke<PrintableEmail>('get_printable_email', { emailId })
+      .then(setEmail)
+      .catch(e => setError(String(e)));
+  }, [params]);
+
+  useEffect(() => {
+    if (email) {
+      invoke('print_window').catch(e => setError(String(e)));
+    }
+  }, [email]);
+
+  if (error) {
+    return <p className="p-6 text-sm text-red-600">Couldn't print this email: {error}</p>;
+  }
+  if (!email) {
+    return null;
+  }
+
+  return (
+    <div className="p-8 bg-white text-black text-sm print:p-0">
+      <h1 className="text-xl font-bold mb-3">{email.subject}</h1>
+      <table className="mb-4 text-left">
+        <tbody>
+          <tr>
+            <th className="pr-4 font-semibold align-top">From</th>
+            <td>{email.from}</td>
+          </tr>
+          <tr>
+            <th className="pr-4 font-semibold align-top">Date</th>
+            <td>{new Date(email.received_time).toLocaleString()}</td>
+          </tr>
+          {email.attachments.length > 0 && (
+            <tr>
+              <th className="pr-4 font-semibold align-top">Attachments</th>
+              <td>
+                {email.attachments
+                  .map(attachment => `${attachment.name} (${Math.ceil(attachment.size / 1024)} KB)`)
+                  .join(', ')}
+              </td>
+            </tr>
+          )}
+        </tbody>
+      </table>
+      <hr className="mb-4 border-gray-400" />
+      <div dangerouslySetInnerHTML={{ __html: email.body_html }} />
+    </div>
+  );
+};
+
+export default PrintView;
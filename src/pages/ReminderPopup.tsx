@@ -1,7 +1,7 @@
 import React, { useState, useEffect } from 'react';
 import { useSearchParams } from "react-router-dom";
 import { invoke } from "@tauri-apps/api/tauri";
-import { Mail, AlertTriangle, Clock, Check, Star, X } from "lucide-react";
+import { Mail, AlertTriangle, Clock, Check, X } from "lucide-react";
 
 const SerinaNotificationWindow: React.FC = () => {
   const [searchParams] = useSearchParams();
@@ -12,10 +12,19 @@ const SerinaNotificationWindow: React.FC = () => {
   const [emailCount, setEmailCount] = useState(0);
   const [pendingCount, setPendingCount] = useState(0);
   const [loading, setLoading] = useState(true);
+  // A notice that isn't about new mail, e.g. a rule message or an error
+  const noticeTitle = searchParams.get('title');
+  const noticeBody = searchParams.get('body') ?? '';
+  // The one email this popup may act on, and the short-lived token that lets it
+  const emailId = searchParams.get('email');
+  const capabilityToken = searchParams.get('token');
 
   useEffect(() => {
     const count = searchParams.get('count');
     setEmailCount(parseInt(count || '0'));
+    setPendingCount(parseInt(searchParams.get('pending') || '0'));
+    // Persistent mode: the popup stays until the user dismisses it
+    if (searchParams.get('persistent') === '1') setKeepOpen(true);
     loadEmailCounts();
   }, [searchParams]);
 
@@ -25,13 +34,7 @@ const SerinaNotificationWindow: React.FC = () => {
       // Get unread count from backend
       const unreadResponse = await invoke('get_unread_count');
       const unreadData = JSON.parse(unreadResponse as string);
-      
-      // Get all emails to calculate pending
-      const emailsResponse = await invoke('get_emails', { limit: 50 });
-      const emailsData = JSON.parse(emailsResponse as string);
-      
       setEmailCount(unreadData.count || 0);
-      setPendingCount(emailsData.length || 0);
     } catch (error) {
       console.error('Failed to load email counts:', error);
     } finally {
@@ -75,11 +78,7 @@ const SerinaNotificationWindow: React.FC = () => {
   const handleInitiateTriage = async () => {
     try {
       // Focus main window and close this popup
-      await invoke('backend_request', {
-        endpoint: '/focus-main-window',
-        method: 'POST',
-        body: null
-      });
+      await invoke('open_main_window');
       await invoke('close_window');
     } catch (error) {
       console.error('Failed to initiate triage:', error);
@@ -89,8 +88,9 @@ const SerinaNotificationWindow: React.FC = () => {
 
   const handleSnooze = async (minutes: number) => {
     try {
-      // Implement snooze logic - close popup for now
-      console.log(`Snoozed for ${minutes} minutes`);
+      if (emailId && capabilityToken) {
+        await invoke('snooze_email', { emailId, minutes, capabilityToken });
+      }
       await invoke('close_window');
     } catch (error) {
       console.error('Failed to snooze:', error);
@@ -112,12 +112,14 @@ const SerinaNotificationWindow: React.FC = () => {
     setKeepOpen(true);
   };
 
-  const showNotification = async (title: string, body: string) => {
+  const handleMarkRead = async () => {
     try {
-      await invoke('show_system_notification', { title, body });
+      await invoke('mark_email_read', { emailId, capabilityToken });
+      await invoke('close_window');
     } catch (error) {
-      console.error('Failed to show notification:', error);
+      console.error('Failed to mark email read:', error);
     }
+    setIsVisible(false);
   };
 
   if (!isVisible) return null;
@@ -125,6 +127,9 @@ const SerinaNotificationWindow: React.FC = () => {
   return (
     <div className="fixed bottom-4 right-4 z-50">
       <div 
+        role="alertdialog"
+        aria-labelledby="reminder-title"
+        aria-describedby="reminder-summary"
         className="w-80 rounded-xl border shadow-2xl overflow-hidden backdrop-blur-sm"
         style={{
           background: 'linear-gradient(135deg, #1e293b 0%, #0f172a 90%, #1e293b 100%)',
@@ -146,13 +151,14 @@ const SerinaNotificationWindow: React.FC = () => {
                 <Mail className="w-4 h-4 text-white" />
               </div>
               <div>
-                <h2 className="text-sm font-bold text-cyan-300 tracking-wider">SERINA SENTINEL</h2>
+                <h2 id="reminder-title" className="text-sm font-bold text-cyan-300 tracking-wider">SERINA SENTINEL</h2>
                 <p className="text-xs text-gray-400 font-medium tracking-wide">EMAIL NOTIFICATION</p>
               </div>
             </div>
             
             <button 
               onClick={handleClose}
+              aria-label="Dismiss reminder"
               className="text-gray-400 hover:text-gray-200 transition-colors duration-200 p-1"
             >
               <X className="w-4 h-4" />
@@ -185,7 +191,7 @@ const SerinaNotificationWindow: React.FC = () => {
 
         {/* Metrics */}
         <div className="p-4">
-          <div className="grid grid-cols-2 gap-4 mb-4">
+          <div id="reminder-summary" aria-live="polite" className="grid grid-cols-2 gap-4 mb-4">
             <div 
               className="p-4 rounded-lg border text-center"
               style={{
@@ -219,8 +225,26 @@ const SerinaNotificationWindow: React.FC = () => {
             </div>
           </div>
 
+          {/* Notice */}
+          {noticeTitle && (
+            <div
+              className="p-3 rounded-lg border mb-4 flex items-start space-x-2"
+              style={{
+                background: 'linear-gradient(135deg, #164e63 0%, #0c4a6e 100%)',
+                borderColor: '#0ea5e9',
+                boxShadow: '0 0 12px rgba(14, 165, 233, 0.3)'
+              }}
+            >
+              <AlertTriangle className="w-4 h-4 text-cyan-400 flex-shrink-0 mt-0.5" />
+              <div className="min-w-0">
+                <div className="text-sm font-bold text-cyan-200 tracking-wide">{noticeTitle}</div>
+                {noticeBody && <div className="text-xs text-gray-300 mt-1 break-words">{noticeBody}</div>}
+              </div>
+            </div>
+          )}
+
           {/* Priority Alert */}
-          {emailCount > 0 && (
+          {!noticeTitle && emailCount > 0 && (
             <div 
               className="p-3 rounded-lg border mb-4 flex items-center space-x-2"
               style={{
@@ -237,7 +261,7 @@ const SerinaNotificationWindow: React.FC = () => {
           )}
 
           {/* No New Emails State */}
-          {emailCount === 0 && !loading && (
+          {!noticeTitle && emailCount === 0 && !loading && (
             <div 
               className="p-3 rounded-lg border mb-4 flex items-center space-x-2"
               style={{
@@ -294,22 +318,15 @@ const SerinaNotificationWindow: React.FC = () => {
             </button>
           </div>
 
-          {/* Quick Actions */}
-          {emailCount > 0 && (
-            <div className="grid grid-cols-2 gap-2 mb-4">
+          {/* Quick Actions: only for the email this popup was opened for */}
+          {emailId && capabilityToken && (
+            <div className="grid grid-cols-1 gap-2 mb-4">
               <button 
-                onClick={() => showNotification('SERINA', 'Marked all emails as read')}
+                onClick={handleMarkRead}
                 className="p-2 rounded border border-gray-600 text-xs font-semibold text-green-400 hover:bg-gray-700 hover:text-green-300 transition-all duration-200 flex items-center justify-center space-x-1"
               >
                 <Check className="w-3 h-3" />
-                <span>Mark All Read</span>
-              </button>
-              <button 
-                onClick={() => showNotification('SERINA', 'Flagged emails for priority review')}
-                className="p-2 rounded border border-gray-600 text-xs font-semibold text-yellow-400 hover:bg-gray-700 hover:text-yellow-300 transition-all duration-200 flex items-center justify-center space-x-1"
-              >
-                <Star className="w-3 h-3" />
-                <span>Flag Priority</span>
+                <span>Mark Read</span>
               </button>
             </div>
           )}
@@ -26,22 +26,106 @@ interface Config {
     notification_position: string;
     default_snooze_minutes: number;
     auto_dismiss_seconds: number;
+    persistent_list: boolean;
   };
   todo: {
     default_list_name: string;
     include_ai_summary: boolean;
   };
   ui: {
-    dark_mode: boolean;
+    theme: {
+      mode: 'system' | 'light' | 'dark';
+      preset: string;
+      accent_color: string;
+    };
     window_width: number;
     window_height: number;
     minimize_to_tray: boolean;
+    high_contrast: boolean;
+    gamification: boolean;
+    daily_triage_goal: number;
+    window_effect: 'none' | 'vibrancy' | 'acrylic' | 'mica';
+    compact_mode: boolean;
   };
   security: {
     encrypt_api_keys: boolean;
+    encrypt_cache?: boolean;
   };
+  telemetry?: {
+    enabled: boolean;
+    endpoint: string;
+  };
+  newsletters?: {
+    auto_summarize_archive: boolean;
+    senders: string[];
+  };
+  verification_codes?: {
+    detect: boolean;
+    delete_after_minutes: number;
+  };
+  payments?: {
+    track: boolean;
+    use_llm: boolean;
+    remind_days_before: number;
+  };
+  feeds?: {
+    enabled: boolean;
+    port: number;
+    feeds: { name: string; title: string; newsletters: boolean; senders: string[]; subject_keywords: string[] }[];
+  };
+  team_sync?: TeamSyncConfig;
+  app_lock?: AppLockConfig;
+  observe_only?: boolean;
+}
+
+interface AppLockConfig {
+  enabled: boolean;
+  lock_on_launch: boolean;
+  idle_minutes: number;
+  biometric: boolean;
+}
+
+interface LockStatus {
+  locked: boolean;
+  pin_set: boolean;
+  biometric_available: boolean;
+}
+
+const DEFAULT_APP_LOCK: AppLockConfig = {
+  enabled: false,
+  lock_on_launch: true,
+  idle_minutes: 10,
+  biometric: true
+};
+
+interface TeamSyncConfig {
+  url: string;
+  source: 'http' | 'git';
+  branch: string;
+  path: string;
+  interval_minutes: number;
+  local_wins: boolean;
+}
+
+interface TeamSyncStatus {
+  enabled: boolean;
+  revision: string;
+  pulled_at: string | null;
+  rules: number;
+  templates: number;
+  skipped: string[];
+  last_error: string | null;
 }
 
+const DEFAULT_TEAM_SYNC: TeamSyncConfig = {
+  url: '',
+  source: 'http',
+  branch: 'main',
+  path: 'serina-team.json',
+  interval_minutes: 60,
+  local_wins: false
+};
+
 const SerinaSettings: React.FC<SettingsProps> = ({ darkMode, onToggleDarkMode }) => {
   const navigate = useNavigate();
   const [config, setConfig] = useState<Config | null>(null);
@@ -52,11 +136,57 @@ const SerinaSettings: React.FC<SettingsProps> = ({ darkMode, onToggleDarkMode })
   const [message, setMessage] = useState<string>("");
   const [showApiKey, setShowApiKey] = useState(false);
   const [activeTab, setActiveTab] = useState('llm');
+  const [telemetryPreview, setTelemetryPreview] = useState<string>("");
+  const [themePresets, setThemePresets] = useState<{ name: string; accent: string }[]>([]);
+  const [teamStatus, setTeamStatus] = useState<TeamSyncStatus | null>(null);
+  const [lockStatus, setLockStatus] = useState<LockStatus | null>(null);
+  const [pinDraft, setPinDraft] = useState({ pin: '', current: '' });
 
   useEffect(() => {
     loadConfig();
+    invoke<{ name: string; accent: string }[]>('get_theme_presets')
+      .then(setThemePresets)
+      .catch((error) => console.error('Failed to load theme presets:', error));
+    invoke<TeamSyncStatus>('get_team_sync_status')
+      .then(setTeamStatus)
+      .catch((error) => console.error('Failed to load team sync status:', error));
+    invoke<LockStatus>('get_lock_status')
+      .then(setLockStatus)
+      .catch((error) => console.error('Failed to load lock status:', error));
   }, []);
 
+  const updateAppLock = (changes: Partial<AppLockConfig>) => {
+    if (!config) return;
+    setConfig({ ...config, app_lock: { ...DEFAULT_APP_LOCK, ...config.app_lock, ...changes } });
+  };
+
+  // `pin` null removes it
+  const savePin = async (pin: string | null) => {
+    try {
+      setLockStatus(
+        await invoke<LockStatus>('set_app_lock_pin', { pin, currentPin: pinDraft.current || null })
+      );
+      setPinDraft({ pin: '', current: '' });
+      setMessage(pin ? 'PIN saved successfully' : 'PIN removed successfully');
+    } catch (error) {
+      setMessage(`Failed to update PIN: ${error}`);
+    }
+  };
+
+  const updateTeamSync = (changes: Partial<TeamSyncConfig>) => {
+    if (!config) return;
+    setConfig({ ...config, team_sync: { ...DEFAULT_TEAM_SYNC, ...config.team_sync, ...changes } });
+  };
+
+  const syncTeamNow = async () => {
+    try {
+      setTeamStatus(await invoke<TeamSyncStatus>('sync_team_now'));
+    } catch (error) {
+      setMessage(`Team sync failed: ${error}`);
+      invoke<TeamSyncStatus>('get_team_sync_status').then(setTeamStatus).catch(() => {});
+    }
+  };
+
   const loadConfig = async () => {
     try {
       const response = await invoke('get_config');
@@ -81,20 +211,27 @@ const SerinaSettings: React.FC<SettingsProps> = ({ darkMode, onToggleDarkMode })
           show_desktop_notifications: true,
           notification_position: 'bottom-right',
           default_snooze_minutes: 15,
-          auto_dismiss_seconds: 10
+          auto_dismiss_seconds: 10,
+          persistent_list: false
         },
         todo: {
           default_list_name: 'Tasks',
           include_ai_summary: true
         },
         ui: {
-          dark_mode: true,
+          theme: { mode: 'system', preset: 'serina', accent_color: '' },
           window_width: 1200,
           window_height: 800,
-          minimize_to_tray: false
+          minimize_to_tray: false,
+          high_contrast: false,
+          gamification: false,
+          daily_triage_goal: 20,
+          window_effect: 'none',
+          compact_mode: false
         },
         security: {
-          encrypt_api_keys: true
+          encrypt_api_keys: true,
+          encrypt_cache: true
         }
       };
       setConfig(mockConfig);
@@ -169,6 +306,21 @@ const SerinaSettings: React.FC<SettingsProps> = ({ darkMode, onToggleDarkMode })
     setConfig(newConfig);
   };
 
+  // Themes apply right away; the local copy is kept in step so Save doesn't undo them
+  const updateTheme = async (change: Partial<Config['ui']['theme']>) => {
+    if (!config) return;
+    try {
+      await invoke('set_theme', {
+        mode: change.mode,
+        preset: change.preset,
+        accentColor: change.accent_color,
+      });
+      updateConfig('ui', 'theme', { ...config.ui.theme, ...change });
+    } catch (error) {
+      setMessage(`Theme not changed: ${error}`);
+    }
+  };
+
   const closeWindow = async () => {
     try {
       await invoke('close_window');
@@ -179,11 +331,14 @@ const SerinaSettings: React.FC<SettingsProps> = ({ darkMode, onToggleDarkMode })
     }
   };
 
-  const testReminderPopup = () => {
-    // Open the reminder popup in a new tab/window to simulate how it would appear
-    const testUrl = `/reminder?count=3`;
-    window.open(testUrl, '_blank', 'width=400,height=600,left=100,top=100');
-    setMessage('Reminder popup test opened! Check the new window.');
+  const testReminderPopup = async () => {
+    // Windows only open through the backend, which gives them the navigation guard
+    try {
+      await invoke('show_reminder_popup', { emailCount: 3 });
+      setMessage('Reminder popup test opened! Check the new window.');
+    } catch (error) {
+      setMessage(`Failed to open the reminder popup: ${error}`);
+    }
   };
 
   const tabs = [
@@ -454,6 +609,65 @@ const SerinaSettings: React.FC<SettingsProps> = ({ darkMode, onToggleDarkMode })
                 </div>
               </div>
 
+              <div className="mt-6 p-4 rounded-lg border border-gray-600 flex items-center justify-between">
+                <div>
+                  <span className="text-sm font-bold text-cyan-400 tracking-wide">OBSERVE ONLY</span>
+                  <p className="text-xs text-gray-400">Rules, scripts, auto-replies and newsletter digests only log what they would do to the activity feed</p>
+                </div>
+                <button
+                  onClick={() => setConfig({ ...config, observe_only: !config.observe_only })}
+                  className={`w-12 h-6 rounded-full transition-all duration-200 ${
+                    config.observe_only ? 'bg-cyan-500' : 'bg-gray-600'
+                  }`}
+                >
+                  <div className={`w-5 h-5 bg-white rounded-full transition-transform duration-200 ${
+                    config.observe_only ? 'translate-x-6' : 'translate-x-0.5'
+                  }`} />
+                </button>
+              </div>
+
+              <div className="mt-6 p-4 rounded-lg border border-gray-600 space-y-3">
+                <div className="flex items-center justify-between">
+                  <div>
+                    <span className="text-sm font-bold text-cyan-400 tracking-wide">DIGEST NEWSLETTERS</span>
+                    <p className="text-xs text-gray-400">Summarize newsletters as they arrive, then archive them</p>
+                  </div>
+                  <button
+                    onClick={() => setConfig({
+                      ...config,
+                      newsletters: {
+                        senders: [],
+                        ...config.newsletters,
+                        auto_summarize_archive: !config.newsletters?.auto_summarize_archive
+                      }
+                    })}
+                    className={`w-12 h-6 rounded-full transition-all duration-200 ${
+                      config.newsletters?.auto_summarize_archive ? 'bg-cyan-500' : 'bg-gray-600'
+                    }`}
+                  >
+                    <div className={`w-5 h-5 bg-white rounded-full transition-transform duration-200 ${
+                      config.newsletters?.auto_summarize_archive ? 'translate-x-6' : 'translate-x-0.5'
+                    }`} />
+                  </button>
+                </div>
+                <div>
+                  <label className="block text-xs text-gray-400 mb-1">Also treat these senders as newsletters (addresses or @domains, comma separated)</label>
+                  <input
+                    type="text"
+                    defaultValue={(config.newsletters?.senders ?? []).join(', ')}
+                    onBlur={(e) => setConfig({
+                      ...config,
+                      newsletters: {
+                        auto_summarize_archive: false,
+                        ...config.newsletters,
+                        senders: e.target.value.split(',').map(sender => sender.trim()).filter(Boolean)
+                      }
+                    })}
+                    className="w-full p-2 border border-gray-600 rounded-lg text-sm focus:outline-none focus:ring-2 focus:ring-cyan-500 bg-gray-800 text-gray-300"
+                  />
+                </div>
+              </div>
+
               {testResults.outlook !== undefined && (
                 <div className={`mt-4 flex items-center space-x-2 text-sm ${testResults.outlook ? 'text-green-400' : 'text-red-400'}`}>
                   {testResults.outlook ? <CheckCircle className="w-4 h-4" /> : <AlertCircle className="w-4 h-4" />}
@@ -566,6 +780,206 @@ const SerinaSettings: React.FC<SettingsProps> = ({ darkMode, onToggleDarkMode })
                   </button>
                 </div>
 
+                <div className="p-4 rounded-lg border border-gray-600 space-y-3">
+                  <div className="flex items-center justify-between">
+                    <div>
+                      <span className="text-sm font-bold text-cyan-400 tracking-wide">VERIFICATION CODES</span>
+                      <p className="text-xs text-gray-400">Show one-time codes from new mail with a Copy code button</p>
+                    </div>
+                    <button
+                      onClick={() => setConfig({
+                        ...config,
+                        verification_codes: {
+                          delete_after_minutes: 0,
+                          ...config.verification_codes,
+                          detect: !(config.verification_codes?.detect ?? true)
+                        }
+                      })}
+                      className={`w-12 h-6 rounded-full transition-all duration-200 ${
+                        config.verification_codes?.detect ?? true ? 'bg-cyan-500' : 'bg-gray-600'
+                      }`}
+                    >
+                      <div className={`w-5 h-5 bg-white rounded-full transition-transform duration-200 ${
+                        config.verification_codes?.detect ?? true ? 'translate-x-6' : 'translate-x-0.5'
+                      }`} />
+                    </button>
+                  </div>
+                  <div>
+                    <label className="block text-xs text-gray-400 mb-1">Delete code emails</label>
+                    <select
+                      value={config.verification_codes?.delete_after_minutes ?? 0}
+                      onChange={(e) => setConfig({
+                        ...config,
+                        verification_codes: {
+                          detect: true,
+                          ...config.verification_codes,
+                          delete_after_minutes: parseInt(e.target.value)
+                        }
+                      })}
+                      className="w-full p-2 border border-gray-600 rounded-lg text-sm focus:outline-none focus:ring-2 focus:ring-cyan-500 bg-gray-800 text-gray-300"
+                    >
+                      <option value={0}>Never</option>
+                      <option value={15}>After 15 minutes</option>
+                      <option value={60}>After 1 hour</option>
+                      <option value={1440}>After 1 day</option>
+                    </select>
+                  </div>
+                </div>
+
+                <div className="p-4 rounded-lg border border-gray-600 space-y-3">
+                  <div className="flex items-center justify-between">
+                    <div>
+                      <span className="text-sm font-bold text-cyan-400 tracking-wide">BILLS</span>
+                      <p className="text-xs text-gray-400">Track invoices in new mail and remind before they're due</p>
+                    </div>
+                    <button
+                      onClick={() => setConfig({
+                        ...config,
+                        payments: {
+                          use_llm: true,
+                          remind_days_before: 3,
+                          ...config.payments,
+                          track: !(config.payments?.track ?? true)
+                        }
+                      })}
+                      className={`w-12 h-6 rounded-full transition-all duration-200 ${
+                        config.payments?.track ?? true ? 'bg-cyan-500' : 'bg-gray-600'
+                      }`}
+                    >
+                      <div className={`w-5 h-5 bg-white rounded-full transition-transform duration-200 ${
+                        config.payments?.track ?? true ? 'translate-x-6' : 'translate-x-0.5'
+                      }`} />
+                    </button>
+                  </div>
+                  <div>
+                    <label className="block text-xs text-gray-400 mb-1">Remind me</label>
+                    <select
+                      value={config.payments?.remind_days_before ?? 3}
+                      onChange={(e) => setConfig({
+                        ...config,
+                        payments: {
+                          track: true,
+                          use_llm: true,
+                          ...config.payments,
+                          remind_days_before: parseInt(e.target.value)
+                        }
+                      })}
+                      className="w-full p-2 border border-gray-600 rounded-lg text-sm focus:outline-none focus:ring-2 focus:ring-cyan-500 bg-gray-800 text-gray-300"
+                    >
+                      <option value={0}>On the due date</option>
+                      <option value={1}>1 day before</option>
+                      <option value={3}>3 days before</option>
+                      <option value={7}>A week before</option>
+                    </select>
+                  </div>
+                </div>
+
+                <div className="p-4 rounded-lg border border-gray-600 space-y-3">
+                  <div className="flex items-center justify-between">
+                    <div>
+                      <span className="text-sm font-bold text-cyan-400 tracking-wide">RSS FEEDS</span>
+                      <p className="text-xs text-gray-400">Publish newsletters and other selected mail as Atom feeds on this computer</p>
+                    </div>
+                    <button
+                      onClick={() => setConfig({
+                        ...config,
+                        feeds: {
+                          port: 47614,
+                          feeds: [{ name: 'newsletters', title: 'Newsletters', newsletters: true, senders: [], subject_keywords: [] }],
+                          ...config.feeds,
+                          enabled: !config.feeds?.enabled
+                        }
+                      })}
+                      className={`w-12 h-6 rounded-full transition-all duration-200 ${
+                        config.feeds?.enabled ? 'bg-cyan-500' : 'bg-gray-600'
+                      }`}
+                    >
+                      <div className={`w-5 h-5 bg-white rounded-full transition-transform duration-200 ${
+                        config.feeds?.enabled ? 'translate-x-6' : 'translate-x-0.5'
+                      }`} />
+                    </button>
+                  </div>
+                  {config.feeds?.enabled && (
+                    <ul className="space-y-1">
+                      {config.feeds.feeds.map(feed => (
+                        <li key={feed.name} className="text-xs text-gray-400">
+                          {feed.title}:{' '}
+                          <span className="font-mono text-gray-300 select-all">
+                            http://127.0.0.1:{config.feeds?.port}/feeds/{feed.name}.atom
+                          </span>
+                        </li>
+                      ))}
+                    </ul>
+                  )}
+                </div>
+
+                <div className="p-4 rounded-lg border border-gray-600 space-y-3">
+                  <div>
+                    <span className="text-sm font-bold text-cyan-400 tracking-wide">TEAM RULES</span>
+                    <p className="text-xs text-gray-400">Pull shared rules and reply templates from your team's repository or server</p>
+                  </div>
+                  <div className="flex space-x-2">
+                    <select
+                      value={config.team_sync?.source ?? 'http'}
+                      onChange={(e) => updateTeamSync({ source: e.target.value as TeamSyncConfig['source'] })}
+                      className="p-2 border border-gray-600 rounded-lg text-sm focus:outline-none focus:ring-2 focus:ring-cyan-500 bg-gray-800 text-gray-300"
+                    >
+                      <option value="http">HTTP</option>
+                      <option value="git">Git</option>
+                    </select>
+                    <input
+                      type="text"
+                      value={config.team_sync?.url ?? ''}
+                      onChange={(e) => updateTeamSync({ url: sanitizeTextInput(e.target.value, 500) })}
+                      placeholder={config.team_sync?.source === 'git' ? 'git@git.example.com:team/serina.git' : 'https://example.com/serina-team.json'}
+                      className="flex-1 p-2 border border-gray-600 rounded-lg text-sm focus:outline-none focus:ring-2 focus:ring-cyan-500 bg-gray-800 text-gray-300"
+                    />
+                  </div>
+                  {config.team_sync?.source === 'git' && (
+                    <div className="flex space-x-2">
+                      <input
+                        type="text"
+                        value={config.team_sync.branch}
+                        onChange={(e) => updateTeamSync({ branch: sanitizeTextInput(e.target.value, 100) })}
+                        placeholder="main"
+                        className="w-1/3 p-2 border border-gray-600 rounded-lg text-sm focus:outline-none focus:ring-2 focus:ring-cyan-500 bg-gray-800 text-gray-300"
+                      />
+                      <input
+                        type="text"
+                        value={config.team_sync.path}
+                        onChange={(e) => updateTeamSync({ path: sanitizeTextInput(e.target.value, 200) })}
+                        placeholder="serina-team.json"
+                        className="flex-1 p-2 border border-gray-600 rounded-lg text-sm focus:outline-none focus:ring-2 focus:ring-cyan-500 bg-gray-800 text-gray-300"
+                      />
+                    </div>
+                  )}
+                  <label className="flex items-center space-x-2 text-xs text-gray-400">
+                    <input
+                      type="checkbox"
+                      checked={config.team_sync?.local_wins ?? false}
+                      onChange={(e) => updateTeamSync({ local_wins: e.target.checked })}
+                    />
+                    <span>My own rules and templates override the team's when they share an id</span>
+                  </label>
+                  {teamStatus?.enabled && (
+                    <div className="flex items-center justify-between text-xs text-gray-400">
+                      <span>
+                        {teamStatus.last_error
+                          ? `Last pull failed: ${teamStatus.last_error}`
+                          : teamStatus.pulled_at
+                            ? `${teamStatus.rules} rules and ${teamStatus.templates} templates, pulled ${new Date(teamStatus.pulled_at).toLocaleString()}`
+                            : 'Not pulled yet'}
+                      </span>
+                      <button onClick={syncTeamNow} className="text-cyan-400 hover:text-cyan-300">
+                        Sync now
+                      </button>
+                    </div>
+                  )}
+                  {teamStatus?.skipped.map(reason => (
+                    <p key={reason} className="text-xs text-yellow-400">Skipped {reason}</p>
+                  ))}
+                </div>
+
                 <div 
                   className="p-4 rounded-lg border flex items-center space-x-3"
                   style={{
@@ -704,7 +1118,7 @@ const SerinaSettings: React.FC<SettingsProps> = ({ darkMode, onToggleDarkMode })
                   <button
                     onClick={() => {
                       onToggleDarkMode();
-                      updateConfig('ui', 'dark_mode', !darkMode);
+                      updateConfig('ui', 'theme', { ...config.ui.theme, mode: darkMode ? 'light' : 'dark' });
                     }}
                     className={`w-12 h-6 rounded-full transition-all duration-200 ${
                       darkMode ? 'bg-cyan-500' : 'bg-gray-600'
@@ -716,6 +1130,109 @@ const SerinaSettings: React.FC<SettingsProps> = ({ darkMode, onToggleDarkMode })
                   </button>
                 </div>
 
+                <div className="grid grid-cols-1 md:grid-cols-3 gap-6">
+                  <div>
+                    <label className="block text-sm font-bold text-cyan-400 mb-2 tracking-wide">APPEARANCE</label>
+                    <select
+                      value={config.ui.theme.mode}
+                      onChange={(e) => updateTheme({ mode: e.target.value as Config['ui']['theme']['mode'] })}
+                      className="w-full p-3 border border-gray-600 rounded-lg text-sm focus:outline-none focus:ring-2 focus:ring-cyan-500 focus:border-transparent bg-gray-800 text-gray-300"
+                    >
+                      <option value="system">Match system</option>
+                      <option value="light">Light</option>
+                      <option value="dark">Dark</option>
+                    </select>
+                  </div>
+
+                  <div>
+                    <label className="block text-sm font-bold text-cyan-400 mb-2 tracking-wide">THEME PRESET</label>
+                    <select
+                      value={config.ui.theme.preset}
+                      onChange={(e) => updateTheme({ preset: e.target.value })}
+                      className="w-full p-3 border border-gray-600 rounded-lg text-sm focus:outline-none focus:ring-2 focus:ring-cyan-500 focus:border-transparent bg-gray-800 text-gray-300"
+                    >
+                      {themePresets.map((preset) => (
+                        <option key={preset.name} value={preset.name}>
+                          {preset.name.charAt(0).toUpperCase() + preset.name.slice(1)}
+                        </option>
+                      ))}
+                    </select>
+                  </div>
+
+                  <div>
+                    <label className="block text-sm font-bold text-cyan-400 mb-2 tracking-wide">ACCENT COLOR</label>
+                    <div className="flex items-center space-x-2">
+                      <input
+                        type="color"
+                        aria-label="Accent color"
+                        value={config.ui.theme.accent_color || themePresets.find((p) => p.name === config.ui.theme.preset)?.accent || '#06b6d4'}
+                        onChange={(e) => updateTheme({ accent_color: e.target.value })}
+                        className="h-10 w-16 border border-gray-600 rounded-lg bg-gray-800"
+                      />
+                      {config.ui.theme.accent_color && (
+                        <button
+                          onClick={() => updateTheme({ accent_color: '' })}
+                          className="text-xs text-gray-400 hover:text-cyan-400"
+                        >
+                          Use preset
+                        </button>
+                      )}
+                    </div>
+                  </div>
+                </div>
+
+                <div className="grid grid-cols-1 md:grid-cols-2 gap-6">
+                  <div>
+                    <label className="block text-sm font-bold text-cyan-400 mb-2 tracking-wide">WINDOW EFFECT</label>
+                    <select
+                      value={config.ui.window_effect}
+                      onChange={async (e) => {
+                        const effect = e.target.value;
+                        try {
+                          await invoke('set_window_effect', { effect });
+                          updateConfig('ui', 'window_effect', effect);
+                        } catch (error) {
+                          setMessage(`Window effect not applied: ${error}`);
+                        }
+                      }}
+                      className="w-full p-3 border border-gray-600 rounded-lg text-sm focus:outline-none focus:ring-2 focus:ring-cyan-500 focus:border-transparent bg-gray-800 text-gray-300"
+                    >
+                      <option value="none">None</option>
+                      <option value="vibrancy">Vibrancy (macOS)</option>
+                      <option value="acrylic">Acrylic (Windows 11)</option>
+                      <option value="mica">Mica (Windows 11)</option>
+                    </select>
+                  </div>
+
+                  <div className="flex items-center justify-between p-4 rounded-lg border border-gray-600">
+                    <div>
+                      <span className="text-sm font-bold text-cyan-400 tracking-wide">COMPACT WIDGET</span>
+                      <p className="text-xs text-gray-400">Shrink the main window to an always-on-top unread ticker</p>
+                    </div>
+                    <button
+                      role="switch"
+                      aria-checked={config.ui.compact_mode}
+                      aria-label="Compact widget"
+                      onClick={async () => {
+                        const compact = !config.ui.compact_mode;
+                        try {
+                          await invoke('set_compact_mode', { compact });
+                          updateConfig('ui', 'compact_mode', compact);
+                        } catch (error) {
+                          setMessage(`Compact mode not changed: ${error}`);
+                        }
+                      }}
+                      className={`w-12 h-6 rounded-full transition-all duration-200 ${
+                        config.ui.compact_mode ? 'bg-cyan-500' : 'bg-gray-600'
+                      }`}
+                    >
+                      <div className={`w-5 h-5 bg-white rounded-full transition-transform duration-200 ${
+                        config.ui.compact_mode ? 'translate-x-6' : 'translate-x-0.5'
+                      }`} />
+                    </button>
+                  </div>
+                </div>
+
                 <div className="flex items-center justify-between p-4 rounded-lg border border-gray-600">
                   <div>
                     <span className="text-sm font-bold text-cyan-400 tracking-wide">MINIMIZE TO TRAY</span>
@@ -733,6 +1250,83 @@ const SerinaSettings: React.FC<SettingsProps> = ({ darkMode, onToggleDarkMode })
                   </button>
                 </div>
 
+                <div className="flex items-center justify-between p-4 rounded-lg border border-gray-600">
+                  <div>
+                    <span className="text-sm font-bold text-cyan-400 tracking-wide">HIGH CONTRAST</span>
+                    <p className="text-xs text-gray-400">Stronger colors and focus outlines for low vision</p>
+                  </div>
+                  <button
+                    role="switch"
+                    aria-checked={config.ui.high_contrast}
+                    aria-label="High contrast"
+                    onClick={() => updateConfig('ui', 'high_contrast', !config.ui.high_contrast)}
+                    className={`w-12 h-6 rounded-full transition-all duration-200 ${
+                      config.ui.high_contrast ? 'bg-cyan-500' : 'bg-gray-600'
+                    }`}
+                  >
+                    <div className={`w-5 h-5 bg-white rounded-full transition-transform duration-200 ${
+                      config.ui.high_contrast ? 'translate-x-6' : 'translate-x-0.5'
+                    }`} />
+                  </button>
+                </div>
+
+                <div className="flex items-center justify-between p-4 rounded-lg border border-gray-600">
+                  <div>
+                    <span className="text-sm font-bold text-cyan-400 tracking-wide">INBOX STREAKS</span>
+                    <p className="text-xs text-gray-400">Track days at inbox zero and a daily triage goal</p>
+                  </div>
+                  <button
+                    role="switch"
+                    aria-checked={config.ui.gamification}
+                    aria-label="Inbox streaks"
+                    onClick={() => updateConfig('ui', 'gamification', !config.ui.gamification)}
+                    className={`w-12 h-6 rounded-full transition-all duration-200 ${
+                      config.ui.gamification ? 'bg-cyan-500' : 'bg-gray-600'
+                    }`}
+                  >
+                    <div className={`w-5 h-5 bg-white rounded-full transition-transform duration-200 ${
+                      config.ui.gamification ? 'translate-x-6' : 'translate-x-0.5'
+                    }`} />
+                  </button>
+                </div>
+
+                {config.ui.gamification && (
+                  <div className="flex items-center justify-between p-4 rounded-lg border border-gray-600">
+                    <div>
+                      <span className="text-sm font-bold text-cyan-400 tracking-wide">DAILY TRIAGE GOAL</span>
+                      <p className="text-xs text-gray-400">Emails to read, archive or snooze each day</p>
+                    </div>
+                    <input
+                      type="number"
+                      value={config.ui.daily_triage_goal}
+                      onChange={(e) => updateConfig('ui', 'daily_triage_goal', parseInt(e.target.value))}
+                      min="1"
+                      aria-label="Daily triage goal"
+                      className="w-20 p-2 border border-gray-600 rounded-lg text-sm focus:outline-none focus:ring-2 focus:ring-cyan-500 focus:border-transparent bg-gray-800 text-gray-300"
+                    />
+                  </div>
+                )}
+
+                <div className="flex items-center justify-between p-4 rounded-lg border border-gray-600">
+                  <div>
+                    <span className="text-sm font-bold text-cyan-400 tracking-wide">KEEP NOTIFICATIONS</span>
+                    <p className="text-xs text-gray-400">Keep notifications in a list until dismissed instead of auto-hiding</p>
+                  </div>
+                  <button
+                    role="switch"
+                    aria-checked={config.notifications.persistent_list}
+                    aria-label="Keep notifications until dismissed"
+                    onClick={() => updateConfig('notifications', 'persistent_list', !config.notifications.persistent_list)}
+                    className={`w-12 h-6 rounded-full transition-all duration-200 ${
+                      config.notifications.persistent_list ? 'bg-cyan-500' : 'bg-gray-600'
+                    }`}
+                  >
+                    <div className={`w-5 h-5 bg-white rounded-full transition-transform duration-200 ${
+                      config.notifications.persistent_list ? 'translate-x-6' : 'translate-x-0.5'
+                    }`} />
+                  </button>
+                </div>
+
                 <div className="grid grid-cols-1 md:grid-cols-2 gap-6">
                   <div>
                     <label className="block text-sm font-bold text-cyan-400 mb-2 tracking-wide">WINDOW WIDTH</label>
@@ -801,6 +1395,191 @@ const SerinaSettings: React.FC<SettingsProps> = ({ darkMode, onToggleDarkMode })
                   </button>
                 </div>
 
+                <div className="flex items-center justify-between p-4 rounded-lg border border-gray-600">
+                  <div>
+                    <span className="text-sm font-bold text-cyan-400 tracking-wide">ENCRYPT LOCAL CACHE</span>
+                    <p className="text-xs text-gray-400">Keep cached emails encrypted with a key held in the system keychain; applies after restart</p>
+                  </div>
+                  <button
+                    onClick={() => updateConfig('security', 'encrypt_cache', !(config.security.encrypt_cache ?? true))}
+                    className={`w-12 h-6 rounded-full transition-all duration-200 ${
+                      config.security.encrypt_cache ?? true ? 'bg-cyan-500' : 'bg-gray-600'
+                    }`}
+                  >
+                    <div className={`w-5 h-5 bg-white rounded-full transition-transform duration-200 ${
+                      config.security.encrypt_cache ?? true ? 'translate-x-6' : 'translate-x-0.5'
+                    }`} />
+                  </button>
+                </div>
+
+                <div className="p-4 rounded-lg border border-gray-600 space-y-3">
+                  <div className="flex items-center justify-between">
+                    <div>
+                      <span className="text-sm font-bold text-cyan-400 tracking-wide">APP LOCK</span>
+                      <p className="text-xs text-gray-400">Ask for a PIN or biometrics before showing mail; notifications only say how many emails arrived while locked</p>
+                    </div>
+                    <button
+                      onClick={() => updateAppLock({ enabled: !config.app_lock?.enabled })}
+                      className={`w-12 h-6 rounded-full transition-all duration-200 ${
+                        config.app_lock?.enabled ? 'bg-cyan-500' : 'bg-gray-600'
+                      }`}
+                    >
+                      <div className={`w-5 h-5 bg-white rounded-full transition-transform duration-200 ${
+                        config.app_lock?.enabled ? 'translate-x-6' : 'translate-x-0.5'
+                      }`} />
+                    </button>
+                  </div>
+                  {config.app_lock?.enabled && (
+                    <div className="space-y-3">
+                      <label className="flex items-center space-x-2 text-xs text-gray-300">
+                        <input
+                          type="checkbox"
+                          checked={config.app_lock.lock_on_launch}
+                          onChange={e => updateAppLock({ lock_on_launch: e.target.checked })}
+                        />
+                        <span>Lock when SERINA starts</span>
+                      </label>
+                      <label className="flex items-center space-x-2 text-xs text-gray-300">
+                        <span>Lock after</span>
+                        <input
+                          type="number"
+                          min={0}
+                          value={config.app_lock.idle_minutes}
+                          onChange={e => updateAppLock({ idle_minutes: Math.max(0, parseInt(e.target.value) || 0) })}
+                          className="w-16 bg-gray-800 border border-gray-600 rounded px-2 py-1"
+                        />
+                        <span>idle minutes (0 = never)</span>
+                      </label>
+                      <label className="flex items-center space-x-2 text-xs text-gray-300">
+                        <input
+                          type="checkbox"
+                          checked={config.app_lock.biometric}
+                          onChange={e => updateAppLock({ biometric: e.target.checked })}
+                        />
+                        <span>
+                          Unlock with Windows Hello, Touch ID or fingerprint
+                          {lockStatus && !lockStatus.biometric_available && ' (not available on this computer)'}
+                        </span>
+                      </label>
+                      <div className="flex items-center space-x-2">
+                        {lockStatus?.pin_set && (
+                          <input
+                            type="password"
+                            inputMode="numeric"
+                            placeholder="Current PIN"
+                            value={pinDraft.current}
+                            onChange={e => setPinDraft({ ...pinDraft, current: e.target.value.replace(/\D/g, '') })}
+                            className="w-28 bg-gray-800 border border-gray-600 rounded px-2 py-1 text-xs"
+                          />
+                        )}
+                        <input
+                          type="password"
+                          inputMode="numeric"
+                          placeholder={lockStatus?.pin_set ? 'New PIN' : 'PIN (4-12 digits)'}
+                          value={pinDraft.pin}
+                          onChange={e => setPinDraft({ ...pinDraft, pin: e.target.value.replace(/\D/g, '') })}
+                          className="w-32 bg-gray-800 border border-gray-600 rounded px-2 py-1 text-xs"
+                        />
+                        <button
+                          onClick={() => savePin(pinDraft.pin)}
+                          disabled={!pinDraft.pin}
+                          className="px-2 py-1 rounded bg-cyan-600 hover:bg-cyan-500 disabled:opacity-50 text-xs"
+                        >
+                          {lockStatus?.pin_set ? 'Change PIN' : 'Set PIN'}
+                        </button>
+                        {lockStatus?.pin_set && (
+                          <button
+                            onClick={() => savePin(null)}
+                            disabled={!pinDraft.current}
+                            className="px-2 py-1 rounded border border-red-500 text-red-400 disabled:opacity-50 text-xs"
+                          >
+                            Remove PIN
+                          </button>
+                        )}
+                      </div>
+                      {lockStatus && !lockStatus.pin_set && (
+                        <p className="text-xs text-yellow-400">Set a PIN to turn the lock on</p>
+                      )}
+                    </div>
+                  )}
+                </div>
+
+                <div className="p-4 rounded-lg border border-gray-600 space-y-3">
+                  <div className="flex items-center justify-between">
+                    <div>
+                      <span className="text-sm font-bold text-cyan-400 tracking-wide">ANONYMOUS USAGE STATS</span>
+                      <p className="text-xs text-gray-400">Feature counts and error categories only - never email content</p>
+                    </div>
+                    <button
+                      onClick={async () => {
+                        const enabled = !config.telemetry?.enabled;
+                        try {
+                          await invoke('set_telemetry_enabled', { enabled });
+                          setConfig({ ...config, telemetry: { endpoint: '', ...config.telemetry, enabled } });
+                        } catch (error) {
+                          setMessage(`Failed to update usage stats: ${error}`);
+                        }
+                      }}
+                      className={`w-12 h-6 rounded-full transition-all duration-200 ${
+                        config.telemetry?.enabled ? 'bg-cyan-500' : 'bg-gray-600'
+                      }`}
+                    >
+                      <div className={`w-5 h-5 bg-white rounded-full transition-transform duration-200 ${
+                        config.telemetry?.enabled ? 'translate-x-6' : 'translate-x-0.5'
+                      }`} />
+                    </button>
+                  </div>
+                  <button
+                    onClick={async () => {
+                      const preview = await invoke('get_telemetry_preview');
+                      setTelemetryPreview(JSON.stringify(preview, null, 2));
+                    }}
+                    className="text-xs text-cyan-400 hover:text-cyan-300 underline"
+                  >
+                    Show what would be sent
+                  </button>
+                  {telemetryPreview && (
+                    <pre className="text-xs text-gray-300 bg-gray-900 rounded p-3 overflow-auto max-h-48">{telemetryPreview}</pre>
+                  )}
+                </div>
+
+                <div className="flex items-center justify-between p-4 rounded-lg border border-gray-600">
+                  <div>
+                    <span className="text-sm font-bold text-cyan-400 tracking-wide">DELETE MY DATA</span>
+                    <p className="text-xs text-gray-400">Securely erase cached emails, summaries, logs and saved API keys from this computer</p>
+                  </div>
+                  <button
+                    onClick={async () => {
+                      try {
+                        // The app asks natively and only purges with a token for exactly what was confirmed
+                        const scopes = ['bodies', 'summaries', 'embeddings', 'logs', 'secrets'];
+                        const confirmation = await invoke<{ token: string } | null>('request_confirmation', {
+                          action: 'purge_data',
+                          targets: scopes,
+                        });
+                        if (!confirmation) {
+                          return;
+                        }
+                        const results = await invoke<{ scope: string; removed: number; error: string | null }[]>(
+                          'purge_local_data',
+                          { scopes, confirmation: confirmation.token }
+                        );
+                        const failed = results.filter(result => result.error);
+                        setMessage(
+                          failed.length === 0
+                            ? 'Local data deleted successfully'
+                            : `Failed to delete ${failed.map(result => `${result.scope}: ${result.error}`).join('; ')}`
+                        );
+                      } catch (error) {
+                        setMessage(`Failed to delete local data: ${error}`);
+                      }
+                    }}
+                    className="px-3 py-1 rounded border border-red-500 text-red-400 hover:bg-red-900 text-xs font-bold"
+                  >
+                    DELETE
+                  </button>
+                </div>
+
                 <div 
                   className="p-4 rounded-lg border flex items-center space-x-3"
                   style={{
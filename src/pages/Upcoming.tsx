@@ -0,0 +1,212 @@
+import React, { useCallback, useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/tauri';
+import { listen } from '@tauri-apps/api/event';
+import { AlarmClock, Check, Clock, Pencil, Receipt, Send, Target, X } from 'lucide-react';
+import { sanitizeTextInput } from '../utils/sanitization';
+
+type ScheduledKind = 'snooze' | 'deadline_reminder' | 'scheduled_send' | 'focus_digest';
+
+interface ScheduledItem {
+  kind: ScheduledKind;
+  id: string;
+  title: string;
+  detail: string;
+  due: string;
+  deadline: string | null;
+}
+
+interface Payment {
+  email_id: string;
+  subject: string;
+  payee: string;
+  amount: string;
+  currency: string;
+  due_date: string | null;
+}
+
+const KINDS: Record<ScheduledKind, { label: string; icon: React.ReactNode }> = {
+  snooze: { label: 'Snoozed', icon: <Clock className="w-4 h-4 text-yellow-400" /> },
+  deadline_reminder: { label: 'Deadline reminder', icon: <AlarmClock className="w-4 h-4 text-red-400" /> },
+  scheduled_send: { label: 'Scheduled send', icon: <Send className="w-4 h-4 text-blue-400" /> },
+  focus_digest: { label: 'Focus digest', icon: <Target className="w-4 h-4 text-cyan-400" /> }
+};
+
+// "2024-05-14T09:30" in local time, as a datetime-local input expects
+const toInputValue = (iso: string) => {
+  const date = new Date(iso);
+  date.setMinutes(date.getMinutes() - date.getTimezoneOffset());
+  return date.toISOString().slice(0, 16);
+};
+
+const formatDue = (iso: string) =>
+  new Date(iso).toLocaleString(undefined, { weekday: 'short', hour: '2-digit', minute: '2-digit', day: 'numeric', month: 'short' });
+
+// "2024-06-01" is a calendar day, not midnight UTC
+const formatDay = (day: string) =>
+  new Date(`${day}T00:00`).toLocaleDateString(undefined, { weekday: 'short', day: 'numeric', month: 'short' });
+
+// Snoozes, reminders, scheduled sends and the focus digest in one list, soonest first
+const Upcoming: React.FC = () => {
+  const [items, setItems] = useState<ScheduledItem[]>([]);
+  const [payments, setPayments] = useState<Payment[]>([]);
+  const [loading, setLoading] = useState(true);
+  const [editing, setEditing] = useState<{ key: string; value: string } | null>(null);
+  const [error, setError] = useState('');
+
+  const loadItems = useCallback(async () => {
+    try {
+      setItems(await invoke<ScheduledItem[]>('get_scheduled_items'));
+      setPayments(await invoke<Payment[]>('get_upcoming_payments'));
+    } catch (e) {
+      setError(String(e));
+    } finally {
+      setLoading(false);
+    }
+  }, []);
+
+  useEffect(() => {
+    loadItems();
+    const unlisten = Promise.all([
+      listen('snooze-expired', () => loadItems()),
+      listen('focus-started', () => loadItems()),
+      listen('focus-ended', () => loadItems()),
+      listen('payment-detected', () => loadItems()),
+      listen<Payment[]>('payments-changed', event => setPayments(event.payload))
+    ]);
+    return () => {
+      unlisten.then(handlers => handlers.forEach(stop => stop()));
+    };
+  }, [loadItems]);
+
+  const keyOf = (item: ScheduledItem) => `${item.kind}:${item.id}`;
+
+  const cancel = async (item: ScheduledItem) => {
+    try {
+      setItems(await invoke<ScheduledItem[]>('cancel_scheduled_item', { kind: item.kind, id: item.id }));
+      setError('');
+    } catch (e) {
+      setError(String(e));
+    }
+  };
+
+  const settlePayment = async (payment: Payment, command: 'mark_payment_paid' | 'dismiss_payment') => {
+    try {
+      setPayments(await invoke<Payment[]>(command, { emailId: payment.email_id }));
+      setError('');
+    } catch (e) {
+      setError(String(e));
+    }
+  };
+
+  const reschedule = async (item: ScheduledItem, value: string) => {
+    try {
+      const until = new Date(value).toISOString();
+      setItems(await invoke<ScheduledItem[]>('reschedule_item', { kind: item.kind, id: item.id, until }));
+      setEditing(null);
+      setError('');
+    } catch (e) {
+      setError(String(e));
+    }
+  };
+
+  return (
+    <div className="h-screen flex flex-col bg-gray-900 text-gray-200 text-sm">
+      <ul className="flex-1 overflow-y-auto divide-y divide-gray-800">
+        {payments.map(payment => (
+          <li key={`payment:${payment.email_id}`} className="px-4 py-3 space-y-1">
+            <div className="flex items-center space-x-2">
+              <Receipt className="w-4 h-4 text-green-400" />
+              <span className="text-xs text-gray-400">Bill</span>
+              <span className="flex-1 text-right text-xs text-gray-300">
+                {payment.due_date ? `Due ${formatDay(payment.due_date)}` : 'No due date'}
+              </span>
+            </div>
+            <p className="truncate">
+              {sanitizeTextInput(payment.payee, 100)}
+              {payment.amount && ` · ${sanitizeTextInput(`${payment.currency} ${payment.amount}`.trim(), 40)}`}
+            </p>
+            <p className="text-xs text-gray-500 truncate">{sanitizeTextInput(payment.subject, 200)}</p>
+            <div className="flex space-x-3">
+              <button
+                onClick={() => settlePayment(payment, 'mark_payment_paid')}
+                className="flex items-center space-x-1 text-xs text-green-400 hover:text-green-300"
+              >
+                <Check className="w-3 h-3" />
+                <span>Mark paid</span>
+              </button>
+              <button
+                onClick={() => settlePayment(payment, 'dismiss_payment')}
+                className="flex items-center space-x-1 text-xs text-red-400 hover:text-red-300"
+              >
+                <X className="w-3 h-3" />
+                <span>Not a bill</span>
+              </button>
+            </div>
+          </li>
+        ))}
+        {!loading && items.length === 0 && payments.length === 0 && (
+          <li className="p-6 text-center text-gray-400">Nothing scheduled</li>
+        )}
+        {items.map(item => {
+          const kind = KINDS[item.kind];
+          const key = keyOf(item);
+          return (
+            <li key={key} className="px-4 py-3 space-y-1">
+              <div className="flex items-center space-x-2">
+                {kind.icon}
+                <span className="text-xs text-gray-400">{kind.label}</span>
+                <span className="flex-1 text-right text-xs text-gray-300">{formatDue(item.due)}</span>
+              </div>
+              {item.title && <p className="truncate">{sanitizeTextInput(item.title, 200)}</p>}
+              {item.detail && <p className="text-xs text-gray-500 truncate">To {sanitizeTextInput(item.detail, 200)}</p>}
+              {item.deadline && <p className="text-xs text-red-300">Due {formatDue(item.deadline)}</p>}
+              {editing?.key === key ? (
+                <div className="flex items-center space-x-2">
+                  <input
+                    type="datetime-local"
+                    value={editing.value}
+                    onChange={e => setEditing({ key, value: e.target.value })}
+                    className="flex-1 bg-gray-800 border border-gray-600 rounded px-2 py-1 text-xs focus:outline-none focus:ring-2 focus:ring-blue-500"
+                  />
+                  <button
+                    onClick={() => reschedule(item, editing.value)}
+                    disabled={!editing.value}
+                    className="px-2 py-1 rounded bg-blue-600 hover:bg-blue-500 disabled:opacity-50 text-xs"
+                  >
+                    Save
+                  </button>
+                  <button
+                    onClick={() => setEditing(null)}
+                    className="px-2 py-1 rounded bg-gray-700 hover:bg-gray-600 text-xs"
+                  >
+                    Cancel
+                  </button>
+                </div>
+              ) : (
+                <div className="flex space-x-3">
+                  <button
+                    onClick={() => setEditing({ key, value: toInputValue(item.due) })}
+                    className="flex items-center space-x-1 text-xs text-cyan-400 hover:text-cyan-300"
+                  >
+                    <Pencil className="w-3 h-3" />
+                    <span>Change time</span>
+                  </button>
+                  <button
+                    onClick={() => cancel(item)}
+                    className="flex items-center space-x-1 text-xs text-red-400 hover:text-red-300"
+                  >
+                    <X className="w-3 h-3" />
+                    <span>{item.kind === 'focus_digest' ? 'End now' : 'Cancel'}</span>
+                  </button>
+                </div>
+              )}
+            </li>
+          );
+        })}
+      </ul>
+      {error && <p className="px-4 py-2 text-xs text-red-400 border-t border-gray-700">{error}</p>}
+    </div>
+  );
+};
+
+export default Upcoming;